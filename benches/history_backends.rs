@@ -0,0 +1,114 @@
+// benches/history_backends.rs
+//
+// Times snapshot collection with each history backend over a generated
+// linear history. Run with:
+//
+//     cargo bench --features gix --bench history_backends [-- COMMITS]
+
+use std::env;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tdd_ratchet::gix_history::GixHistory;
+use tdd_ratchet::history::{GitHistory, HistoryProvider};
+
+const DEFAULT_COMMITS: usize = 5_000;
+const ROUNDS: u32 = 5;
+const WINDOW: usize = 20;
+
+fn main() {
+    let commits = env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_COMMITS);
+    let dir = tempfile::tempdir().unwrap();
+    generate_history(dir.path(), commits);
+
+    println!("history_backends: {commits} commits, best of {ROUNDS}");
+    report(
+        "revwalk",
+        time(|| git2_revwalk(dir.path())),
+        time(|| gix_revwalk(dir.path())),
+    );
+    report(
+        "snapshots",
+        time(|| GitHistory::new(dir.path()).snapshots().unwrap().len()),
+        time(|| GixHistory::new(dir.path()).snapshots().unwrap().len()),
+    );
+}
+
+fn report(name: &str, git2: Duration, gix: Duration) {
+    println!(
+        "  {name:<10} git2 {git2:>10.2?}  gix {gix:>10.2?}  speedup {:.2}x",
+        git2.as_secs_f64() / gix.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+/// Best wall time of `ROUNDS` calls; `f` returns the number of commits seen.
+fn time(f: impl Fn() -> usize) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            assert!(f() > 0);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn git2_revwalk(path: &Path) -> usize {
+    let repo = git2::Repository::open(path).unwrap();
+    let mut walk = repo.revwalk().unwrap();
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+        .unwrap();
+    walk.push_head().unwrap();
+    walk.count()
+}
+
+fn gix_revwalk(path: &Path) -> usize {
+    let repo = gix::open(path).unwrap();
+    let head = repo.head_id().unwrap();
+    repo.rev_walk([head]).all().unwrap().count()
+}
+
+/// One commit per iteration, each adding a pending test and promoting the
+/// previous one, so every commit carries a different status file. Only the
+/// latest `WINDOW` tests are kept so the walk, not JSON parsing, dominates.
+fn generate_history(path: &Path, commits: usize) {
+    let repo = git2::Repository::init(path).unwrap();
+    let signature = git2::Signature::now("Bench", "bench@example.com").unwrap();
+    let mut tests = serde_json::Map::new();
+    let mut parent: Option<git2::Oid> = None;
+
+    for i in 0..commits {
+        if i > 0 {
+            tests.insert(format!("test_{}", i - 1), "passing".into());
+        }
+        if i >= WINDOW {
+            tests.remove(&format!("test_{}", i - WINDOW));
+        }
+        tests.insert(format!("test_{i}"), "pending".into());
+        let json = serde_json::json!({ "tests": tests }).to_string();
+
+        let blob = repo.blob(json.as_bytes()).unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert(".test-status.json", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let parents: Vec<git2::Commit<'_>> = parent
+            .map(|oid| repo.find_commit(oid).unwrap())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
+        let message = format!("Add test_{i}");
+        parent = Some(
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &parent_refs,
+            )
+            .unwrap(),
+        );
+    }
+}
@@ -0,0 +1,111 @@
+// benches/history_walk.rs
+//
+// Times snapshot collection over a long history in which the status file
+// was introduced late, against an unbounded walk that reads every commit.
+// Run with:
+//
+//     cargo bench --bench history_walk [-- COMMITS]
+//
+// COMMITS (default 50,000) is the number of commits before the status file
+// was added; `TRACKED` more commits carry it.
+
+use std::env;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tdd_ratchet::history::{GitHistory, HistoryProvider};
+
+const DEFAULT_COMMITS: usize = 50_000;
+const TRACKED: usize = 200;
+const ROUNDS: u32 = 5;
+
+fn main() {
+    let commits = env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_COMMITS);
+    let dir = tempfile::tempdir().unwrap();
+    generate_history(dir.path(), commits);
+
+    let bounded = time(|| GitHistory::new(dir.path()).snapshots().unwrap().len());
+    let unbounded = time(|| unbounded_snapshots(dir.path()));
+    println!(
+        "history_walk: {commits} commits before the status file, {TRACKED} after, best of {ROUNDS}"
+    );
+    println!(
+        "  unbounded {unbounded:>10.2?}  bounded {bounded:>10.2?}  speedup {:.2}x",
+        unbounded.as_secs_f64() / bounded.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+/// Best wall time of `ROUNDS` calls; `f` returns the number of snapshots,
+/// which must be the same for both walks.
+fn time(f: impl Fn() -> usize) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            assert_eq!(f(), TRACKED);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// The walk without a bound: every reachable commit's tree is searched for
+/// the status file.
+fn unbounded_snapshots(path: &Path) -> usize {
+    let repo = git2::Repository::open(path).unwrap();
+    let mut walk = repo.revwalk().unwrap();
+    walk.push_head().unwrap();
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+        .unwrap();
+    walk.filter(|oid| {
+        let commit = repo.find_commit(*oid.as_ref().unwrap()).unwrap();
+        commit
+            .tree()
+            .unwrap()
+            .get_path(Path::new(".test-status.json"))
+            .is_ok()
+    })
+    .count()
+}
+
+/// `commits` commits that each change a source file, then `TRACKED` commits
+/// that also carry a status file with one more pending test each.
+fn generate_history(path: &Path, commits: usize) {
+    let repo = git2::Repository::init(path).unwrap();
+    let signature = git2::Signature::now("Bench", "bench@example.com").unwrap();
+    let mut tests = serde_json::Map::new();
+    let mut parent: Option<git2::Oid> = None;
+
+    for i in 0..commits + TRACKED {
+        let mut builder = repo.treebuilder(None).unwrap();
+        let source = repo.blob(format!("// revision {i}\n").as_bytes()).unwrap();
+        builder.insert("lib.rs", source, 0o100644).unwrap();
+        if i >= commits {
+            tests.insert(format!("test_{i}"), "pending".into());
+            let json = serde_json::json!({ "tests": tests }).to_string();
+            let status = repo.blob(json.as_bytes()).unwrap();
+            builder
+                .insert(".test-status.json", status, 0o100644)
+                .unwrap();
+        }
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let parents: Vec<git2::Commit<'_>> = parent
+            .map(|oid| repo.find_commit(oid).unwrap())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
+        let message = format!("Commit {i}");
+        parent = Some(
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &parent_refs,
+            )
+            .unwrap(),
+        );
+    }
+}
@@ -0,0 +1,68 @@
+// Accepted failures: known breakages shipped on purpose, tolerated until
+// their expiry and reported as violations after it.
+
+use crate::history::HistorySnapshot;
+use crate::status::AcceptedFailure;
+use crate::timeline::format_date;
+
+/// Whether `test`'s accepted failure has expired at the newest snapshot.
+///
+/// Pure function — no IO. A date is compared with the newest commit's date,
+/// so a commit always gets the same verdict. Commits are counted from the
+/// first commit in the unbroken run of snapshots that carry this same
+/// acceptance; an acceptance not committed yet has not expired. One without
+/// any expiry has.
+pub fn acceptance_expired(
+    test: &str,
+    acceptance: &AcceptedFailure,
+    snapshots: &[HistorySnapshot],
+) -> bool {
+    let Some(head) = snapshots.last() else {
+        return false;
+    };
+    if let Some(until) = &acceptance.until {
+        return format_date(head.info.time).as_str() > until.as_str();
+    }
+    let Some(commits) = acceptance.commits else {
+        return true;
+    };
+    commits_since_acceptance(test, acceptance, snapshots) > commits as usize
+}
+
+/// How many commits after the one recording `test`'s acceptance carry it, up
+/// to the newest snapshot. Pure function — no IO.
+pub fn commits_since_acceptance(
+    test: &str,
+    acceptance: &AcceptedFailure,
+    snapshots: &[HistorySnapshot],
+) -> usize {
+    let carried = snapshots
+        .iter()
+        .rev()
+        .take_while(|snapshot| {
+            snapshot
+                .status
+                .tests
+                .get(test)
+                .and_then(|entry| entry.accepted_failure.as_ref())
+                == Some(acceptance)
+        })
+        .count();
+    // The commit recording the acceptance does not count toward it.
+    carried.saturating_sub(1)
+}
+
+/// Whether `date` is a `YYYY-MM-DD` date.
+pub fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    let numeric =
+        |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+    if !(numeric(year, 4) && numeric(month, 2) && numeric(day, 2)) {
+        return false;
+    }
+    let (month, day): (u32, u32) = (month.parse().unwrap_or(0), day.parse().unwrap_or(0));
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
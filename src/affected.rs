@@ -0,0 +1,174 @@
+// Affected-tests mode: run only the packages a change can affect.
+//
+// Files changed since a base commit (committed or not) are mapped to the
+// workspace package whose directory holds them, and every package that
+// depends on one of those, directly or not, is affected too. A change
+// outside every package, such as the workspace `Cargo.toml` or
+// `Cargo.lock`, can affect anything, so it runs the whole suite.
+
+use crate::error::{ParseError, RatchetError};
+use crate::status::is_status_file_name;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Where `--affected` compares against when no `--merge-base` or `--since`
+/// is given: the merge base with the first of these that exists.
+pub const DEFAULT_AFFECTED_TARGETS: [&str; 4] = ["origin/HEAD", "origin/main", "main", "master"];
+
+/// A package of the Cargo workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspacePackage {
+    pub name: String,
+    /// The directory holding its `Cargo.toml`, relative to the repository
+    /// root, with `/` separators; empty for the root.
+    pub dir: String,
+    /// Names of the packages it depends on, of any kind.
+    pub dependencies: Vec<String>,
+}
+
+/// Which tests a change can affect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Affected {
+    /// A change outside every package; the whole suite runs.
+    Everything,
+    /// Only these packages' tests; none when nothing changed.
+    Packages(BTreeSet<String>),
+}
+
+/// The packages `changed` files (relative to the repository root) affect,
+/// with every workspace package depending on them. A file belongs to the
+/// package with the deepest directory holding it. Changes to the ratchet's
+/// own files (status files and `.ratchet-*` sidecars) affect nothing.
+/// Pure function — no IO.
+pub fn affected_packages(packages: &[WorkspacePackage], changed: &[String]) -> Affected {
+    let mut affected = BTreeSet::new();
+    for path in changed {
+        let name = Path::new(path).file_name().and_then(|name| name.to_str());
+        if name.is_some_and(|name| is_status_file_name(name) || name.starts_with(".ratchet-")) {
+            continue;
+        }
+        let owner = packages
+            .iter()
+            .filter(|package| {
+                package.dir.is_empty()
+                    || path
+                        .strip_prefix(&package.dir)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|package| package.dir.len());
+        match owner {
+            Some(package) => {
+                affected.insert(package.name.clone());
+            }
+            None => return Affected::Everything,
+        }
+    }
+
+    loop {
+        let dependents: Vec<String> = packages
+            .iter()
+            .filter(|package| !affected.contains(&package.name))
+            .filter(|package| package.dependencies.iter().any(|d| affected.contains(d)))
+            .map(|package| package.name.clone())
+            .collect();
+        if dependents.is_empty() {
+            return Affected::Packages(affected);
+        }
+        affected.extend(dependents);
+    }
+}
+
+/// Files changed between `base` and the working tree of the repository at
+/// `repo_path`, including untracked files, relative to its root.
+pub fn changed_paths(repo_path: &Path, base: &str) -> Result<Vec<String>, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let tree = repo.revparse_single(base)?.peel_to_tree()?;
+    let mut options = git2::DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+    let mut paths = BTreeSet::new();
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()] {
+            if let Some(path) = file.path().and_then(Path::to_str) {
+                paths.insert(path.to_string());
+            }
+        }
+    }
+    Ok(paths.into_iter().collect())
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    target_directory: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    name: String,
+    manifest_path: PathBuf,
+    dependencies: Vec<MetadataDependency>,
+}
+
+#[derive(Deserialize)]
+struct MetadataDependency {
+    name: String,
+}
+
+/// The packages of the workspace at `project_dir`, from `cargo metadata`,
+/// with directories relative to `repo_root`. Packages outside the
+/// repository are left out.
+pub fn workspace_packages(
+    project_dir: &Path,
+    repo_root: &Path,
+) -> Result<Vec<WorkspacePackage>, RatchetError> {
+    let metadata = cargo_metadata(project_dir)?;
+    let repo_root = repo_root
+        .canonicalize()
+        .map_err(|e| RatchetError::io(repo_root, e))?;
+    Ok(metadata
+        .packages
+        .into_iter()
+        .filter_map(|package| {
+            let dir = package.manifest_path.parent()?.canonicalize().ok()?;
+            let dir = dir.strip_prefix(&repo_root).ok()?;
+            let dir: Vec<&str> = dir.iter().filter_map(|part| part.to_str()).collect();
+            Some(WorkspacePackage {
+                name: package.name,
+                dir: dir.join("/"),
+                dependencies: package.dependencies.into_iter().map(|d| d.name).collect(),
+            })
+        })
+        .collect())
+}
+
+/// The target directory the workspace at `project_dir` builds into, from
+/// `cargo metadata`.
+pub fn target_directory(project_dir: &Path) -> Result<PathBuf, RatchetError> {
+    Ok(cargo_metadata(project_dir)?.target_directory)
+}
+
+fn cargo_metadata(project_dir: &Path) -> Result<Metadata, RatchetError> {
+    const COMMAND: &str = "cargo metadata";
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(project_dir)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| RatchetError::command(COMMAND, e))?;
+    if !output.status.success() {
+        return Err(RatchetError::command_exit(
+            COMMAND,
+            output.status,
+            &output.stderr,
+        ));
+    }
+    Ok(
+        serde_json::from_slice(&output.stdout).map_err(|source| ParseError::CommandOutput {
+            command: COMMAND.to_string(),
+            source,
+        })?,
+    )
+}
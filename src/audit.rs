@@ -0,0 +1,338 @@
+// Append-only audit trail in `.ratchet-audit.jsonl` (configurable under
+// `[audit]`): one JSON record per line.
+
+use crate::error::{ParseError, RatchetError};
+use crate::errors::violation_summary;
+use crate::ratchet::{EvalResult, Violation, Warning};
+use crate::status::{TestState, TrackedStatus};
+use crate::timeline::{format_date, short_commit};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+
+pub const AUDIT_FILE_NAME: &str = ".ratchet-audit.jsonl";
+
+/// One line of the audit file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum AuditRecord {
+    /// A `Ratchet-Override` trailer suppressed a violation.
+    Override {
+        commit: String,
+        test: String,
+        rule: String,
+        reason: String,
+        /// Unix time the override was first recorded.
+        recorded_at: i64,
+    },
+    /// One ratchet run, recorded when `[audit] enabled = true`.
+    Run {
+        recorded_at: i64,
+        /// Commit checked out during the run, if any.
+        head: Option<String>,
+        violations: Vec<AuditViolation>,
+        /// Status changes the run wrote to the working tree.
+        transitions: Vec<AuditTransition>,
+        overrides: Vec<AuditOverride>,
+    },
+    /// `cargo ratchet rebaseline` moved the global baseline.
+    Rebaseline {
+        recorded_at: i64,
+        commit: String,
+        /// The baseline it replaced, if any.
+        previous: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditViolation {
+    /// Violation code, e.g. `RATCHET002`; empty in records written before
+    /// codes existed.
+    #[serde(default)]
+    pub code: String,
+    pub rule: String,
+    pub subject: String,
+    pub detail: String,
+}
+
+impl AuditViolation {
+    /// The audit form of `violation`.
+    pub fn of(violation: &Violation) -> Self {
+        let (rule, subject, detail) = violation_summary(violation);
+        AuditViolation {
+            code: violation.code().to_string(),
+            rule: rule.to_string(),
+            subject,
+            detail,
+        }
+    }
+}
+
+/// A test's state before and after a run; `None` means untracked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditTransition {
+    pub test: String,
+    pub from: Option<TestState>,
+    pub to: Option<TestState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditOverride {
+    pub commit: String,
+    pub test: String,
+    pub rule: String,
+    pub reason: String,
+}
+
+impl AuditRecord {
+    pub fn recorded_at(&self) -> i64 {
+        match self {
+            AuditRecord::Override { recorded_at, .. }
+            | AuditRecord::Run { recorded_at, .. }
+            | AuditRecord::Rebaseline { recorded_at, .. } => *recorded_at,
+        }
+    }
+
+    /// Whether the record mentions `test` as an override, violation subject,
+    /// or transition.
+    pub fn mentions(&self, test: &str) -> bool {
+        match self {
+            AuditRecord::Override { test: t, .. } => t == test,
+            AuditRecord::Run {
+                violations,
+                transitions,
+                overrides,
+                ..
+            } => {
+                violations.iter().any(|v| v.subject == test)
+                    || transitions.iter().any(|t| t.test == test)
+                    || overrides.iter().any(|o| o.test == test)
+            }
+            AuditRecord::Rebaseline { .. } => false,
+        }
+    }
+}
+
+/// The audit record for one run. `before` is the committed status the run
+/// started from. Pure function — no IO.
+pub fn run_record(
+    head: Option<&str>,
+    before: &TrackedStatus,
+    result: &EvalResult,
+    now: i64,
+) -> AuditRecord {
+    let violations = result.violations.iter().map(AuditViolation::of).collect();
+
+    let after = &result.updated.tests;
+    let names: BTreeSet<&String> = before.tests.keys().chain(after.keys()).collect();
+    let transitions = names
+        .into_iter()
+        .filter_map(|test| {
+            let from = before.tests.get(test).map(|e| e.state());
+            let to = after.get(test).map(|e| e.state());
+            (from != to).then(|| AuditTransition {
+                test: test.clone(),
+                from,
+                to,
+            })
+        })
+        .collect();
+
+    let overrides = result
+        .warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            Warning::OverrideApplied {
+                commit,
+                test,
+                rule,
+                reason,
+            } => Some(AuditOverride {
+                commit: commit.clone(),
+                test: test.clone(),
+                rule: rule.clone(),
+                reason: reason.clone(),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    AuditRecord::Run {
+        recorded_at: now,
+        head: head.map(str::to_string),
+        violations,
+        transitions,
+        overrides,
+    }
+}
+
+/// Read every record in the audit file; a missing file has none.
+pub fn read_audit(path: &Path) -> Result<Vec<AuditRecord>, RatchetError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        std::fs::read_to_string(path).map_err(|source| RatchetError::io(path, source))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|source| {
+                RatchetError::from(ParseError::Audit {
+                    path: path.to_path_buf(),
+                    line: index + 1,
+                    source,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Append a record for each override used in a run that is not already in
+/// the audit file. Returns how many were appended.
+pub fn record_overrides(
+    path: &Path,
+    warnings: &[Warning],
+    now: i64,
+) -> Result<usize, RatchetError> {
+    let existing = read_audit(path)?;
+    let new_records: Vec<AuditRecord> = warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            Warning::OverrideApplied {
+                commit,
+                test,
+                rule,
+                reason,
+            } => Some(AuditRecord::Override {
+                commit: commit.clone(),
+                test: test.clone(),
+                rule: rule.clone(),
+                reason: reason.clone(),
+                recorded_at: now,
+            }),
+            _ => None,
+        })
+        .filter(|record| !existing.iter().any(|e| same_override(e, record)))
+        .collect();
+    if new_records.is_empty() {
+        return Ok(0);
+    }
+    append_records(path, &new_records)?;
+    Ok(new_records.len())
+}
+
+/// Append one record to the audit file, creating it if needed.
+pub fn append_record(path: &Path, record: &AuditRecord) -> Result<(), RatchetError> {
+    append_records(path, std::slice::from_ref(record))
+}
+
+/// Render records for `audit show`, one or more lines each.
+pub fn format_audit(records: &[AuditRecord]) -> String {
+    let mut out = String::new();
+    for record in records {
+        let date = format_date(record.recorded_at());
+        match record {
+            AuditRecord::Override {
+                commit,
+                test,
+                rule,
+                reason,
+                ..
+            } => out.push_str(&format!(
+                "{date} override {} {rule} {test}: {reason}\n",
+                short_commit(commit)
+            )),
+            AuditRecord::Rebaseline {
+                commit, previous, ..
+            } => out.push_str(&format!(
+                "{date} rebaseline {} (was {})\n",
+                short_commit(commit),
+                previous.as_deref().map_or("first snapshot", short_commit)
+            )),
+            AuditRecord::Run {
+                head,
+                violations,
+                transitions,
+                overrides,
+                ..
+            } => {
+                out.push_str(&format!(
+                    "{date} run {} {}, {} transition{}, {} override{}\n",
+                    head.as_deref().map_or("(no commit)", short_commit),
+                    if violations.is_empty() {
+                        "ok".to_string()
+                    } else {
+                        format!(
+                            "{} violation{}",
+                            violations.len(),
+                            if violations.len() == 1 { "" } else { "s" }
+                        )
+                    },
+                    transitions.len(),
+                    if transitions.len() == 1 { "" } else { "s" },
+                    overrides.len(),
+                    if overrides.len() == 1 { "" } else { "s" },
+                ));
+                for v in violations {
+                    out.push_str(&format!("    {} {}: {}\n", v.rule, v.subject, v.detail));
+                }
+                for t in transitions {
+                    out.push_str(&format!(
+                        "    {}: {} -> {}\n",
+                        t.test,
+                        state_label(t.from),
+                        state_label(t.to)
+                    ));
+                }
+                for o in overrides {
+                    out.push_str(&format!(
+                        "    override {} {}: {}\n",
+                        o.rule, o.test, o.reason
+                    ));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn state_label(state: Option<TestState>) -> String {
+    state.map_or("untracked".to_string(), |s| s.to_string())
+}
+
+fn same_override(a: &AuditRecord, b: &AuditRecord) -> bool {
+    match (a, b) {
+        (
+            AuditRecord::Override {
+                commit: c1,
+                test: t1,
+                rule: r1,
+                ..
+            },
+            AuditRecord::Override {
+                commit: c2,
+                test: t2,
+                rule: r2,
+                ..
+            },
+        ) => c1 == c2 && t1 == t2 && r1 == r2,
+        _ => false,
+    }
+}
+
+fn append_records(path: &Path, records: &[AuditRecord]) -> Result<(), RatchetError> {
+    let io_error = |source| RatchetError::io(path, source);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_error)?;
+    for record in records {
+        let line = serde_json::to_string(record).expect("audit records serialize");
+        writeln!(file, "{line}").map_err(io_error)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+// README badges for `cargo ratchet badge`: a flat SVG in the style of
+// shields.io, or the JSON a shields.io endpoint badge reads, published as a
+// CI artifact.
+//
+// The badge shows passing and pending counts from the status file. When the
+// last run (`.ratchet-last-run.json`) had violations they are shown too and
+// the badge turns red; otherwise it is yellow while tests are pending and
+// green when none are.
+
+use crate::last_run::RunSummary;
+use crate::status::{TestState, TrackedStatus};
+use serde_json::json;
+
+/// Output format for `cargo ratchet badge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BadgeFormat {
+    /// A standalone SVG image (the default).
+    #[default]
+    Svg,
+    /// A shields.io endpoint badge: `https://img.shields.io/endpoint?url=...`.
+    ShieldsJson,
+}
+
+impl BadgeFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "svg" => Some(BadgeFormat::Svg),
+            "shields-json" => Some(BadgeFormat::ShieldsJson),
+            _ => None,
+        }
+    }
+}
+
+/// The badge's health, which picks its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeColor {
+    /// No violations and nothing pending.
+    Green,
+    /// No violations, some tests pending.
+    Yellow,
+    /// The last run had violations.
+    Red,
+}
+
+impl BadgeColor {
+    /// The shields.io color name.
+    pub fn name(self) -> &'static str {
+        match self {
+            BadgeColor::Green => "brightgreen",
+            BadgeColor::Yellow => "yellow",
+            BadgeColor::Red => "red",
+        }
+    }
+
+    fn hex(self) -> &'static str {
+        match self {
+            BadgeColor::Green => "#4c1",
+            BadgeColor::Yellow => "#dfb317",
+            BadgeColor::Red => "#e05d44",
+        }
+    }
+}
+
+/// What a badge says.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Badge {
+    pub label: String,
+    pub message: String,
+    pub color: BadgeColor,
+}
+
+/// The badge for `status`, with violations from `last_run` if there was one.
+/// Pure function — no IO.
+pub fn build_badge(status: &TrackedStatus, last_run: Option<&RunSummary>) -> Badge {
+    let count = |state| {
+        status
+            .tests
+            .values()
+            .filter(|entry| entry.state() == state)
+            .count()
+    };
+    let pending = count(TestState::Pending);
+    let mut message = format!("{} passing, {pending} pending", count(TestState::Passing));
+    let violations = last_run.map_or(0, |run| run.violations);
+    let color = if violations > 0 {
+        message.push_str(&format!(
+            ", {violations} violation{}",
+            if violations == 1 { "" } else { "s" }
+        ));
+        BadgeColor::Red
+    } else if pending > 0 {
+        BadgeColor::Yellow
+    } else {
+        BadgeColor::Green
+    };
+    Badge {
+        label: "ratchet".into(),
+        message,
+        color,
+    }
+}
+
+/// The badge as a shields.io endpoint JSON document.
+pub fn format_shields_json(badge: &Badge) -> String {
+    let document = json!({
+        "schemaVersion": 1,
+        "label": badge.label,
+        "message": badge.message,
+        "color": badge.color.name(),
+    });
+    serde_json::to_string_pretty(&document).expect("badge JSON serializes") + "\n"
+}
+
+/// The badge as a flat SVG image. Text widths are estimated, as the font is
+/// only known to the viewer.
+pub fn format_svg(badge: &Badge) -> String {
+    let label_width = text_width(&badge.label);
+    let message_width = text_width(&badge.message);
+    let width = label_width + message_width;
+    let label = escape_xml(&badge.label);
+    let message = escape_xml(&badge.message);
+    let label_x = label_width / 2;
+    let message_x = label_width + message_width / 2;
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+  <title>{label}: {message}</title>
+  <linearGradient id="s" x2="0" y2="100%">
+    <stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
+    <stop offset="1" stop-opacity=".1"/>
+  </linearGradient>
+  <clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="20" fill="#555"/>
+    <rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/>
+    <rect width="{width}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text>
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{message_x}" y="15" fill="#010101" fill-opacity=".3">{message}</text>
+    <text x="{message_x}" y="14">{message}</text>
+  </g>
+</svg>
+"##,
+        color = badge.color.hex(),
+    )
+}
+
+/// Approximate width of `text` in 11px Verdana, plus padding.
+fn text_width(text: &str) -> usize {
+    text.chars().count() * 7 + 10
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
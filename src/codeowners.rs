@@ -0,0 +1,181 @@
+// CODEOWNERS: which team owns the file a test is defined in, so a report
+// covering many teams can say whose violation each one is.
+//
+// The file is read from the same places GitHub looks, first match wins:
+// `.github/CODEOWNERS`, `CODEOWNERS`, then `docs/CODEOWNERS`. Patterns follow
+// GitHub's rules, and the last matching line decides a file's owners.
+
+use crate::errors::violation_summary;
+use crate::ratchet::{EvalResult, Violation, Warning};
+use std::path::{Component, Path};
+
+/// Where CODEOWNERS is looked for, relative to the repository root.
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// A parsed CODEOWNERS file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Codeowners {
+    rules: Vec<OwnerRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OwnerRule {
+    pattern: String,
+    owners: Vec<String>,
+}
+
+impl Codeowners {
+    /// Parse CODEOWNERS text. Blank lines and comments are skipped. A line
+    /// with a pattern and no owners leaves matching files unowned. Pure
+    /// function — no IO.
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .filter_map(|line| {
+                let line = match line.find(" #") {
+                    Some(comment) => &line[..comment],
+                    None => line,
+                };
+                let mut fields = line.split_whitespace();
+                let pattern = fields.next().filter(|pattern| !pattern.starts_with('#'))?;
+                Some(OwnerRule {
+                    pattern: pattern.to_string(),
+                    owners: fields.map(String::from).collect(),
+                })
+            })
+            .collect();
+        Codeowners { rules }
+    }
+
+    /// The CODEOWNERS file of the repository at `repo_dir`, if it has one.
+    pub fn load(repo_dir: &Path) -> Option<Self> {
+        CODEOWNERS_PATHS
+            .iter()
+            .find_map(|path| std::fs::read_to_string(repo_dir.join(path)).ok())
+            .map(|contents| Self::parse(&contents))
+    }
+
+    /// Owners of the file at `path`, relative to the repository root.
+    pub fn owners(&self, path: &Path) -> &[String] {
+        let segments: Vec<String> = path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(segment) => Some(segment.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| pattern_matches(&rule.pattern, &segments))
+            .map_or(&[], |rule| &rule.owners)
+    }
+}
+
+/// Whether a CODEOWNERS `pattern` matches the file at `path`, given as path
+/// segments.
+///
+/// A pattern with a slash anywhere but at the end is anchored at the root;
+/// others match at any depth. A pattern that matches a directory matches
+/// every file under it, unless its last segment is a wildcard such as
+/// `docs/*`, which covers only the directory's own files. A pattern ending
+/// in `/` matches only directories.
+fn pattern_matches(pattern: &str, path: &[&str]) -> bool {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+    if trimmed.is_empty() {
+        return false;
+    }
+    let mut segments: Vec<&str> = trimmed.split('/').collect();
+    if !anchored {
+        segments.insert(0, "**");
+    }
+    let last = segments.last().copied().unwrap_or_default();
+    let covers_contents = last == "**" || !last.contains(['*', '?']);
+    match_segments(&segments, path, dir_only, covers_contents || dir_only)
+}
+
+/// Match pattern segments against path segments. When the pattern runs out
+/// first, the path is a file under the matched directory: allowed only with
+/// `prefix`. `dir_only` requires that.
+fn match_segments(pattern: &[&str], path: &[&str], dir_only: bool, prefix: bool) -> bool {
+    match pattern.split_first() {
+        None => {
+            if path.is_empty() {
+                !dir_only
+            } else {
+                prefix
+            }
+        }
+        Some((&"**", rest)) => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..], dir_only, prefix))
+        }
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                glob_segment(segment, name) && match_segments(rest, path_rest, dir_only, prefix)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match one path segment, where `*` matches any run of characters and `?`
+/// any one character.
+fn glob_segment(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Fill in the owners of every located test in `result`. Pure function —
+/// no IO.
+pub fn assign_owners(result: &mut EvalResult, codeowners: &Codeowners) {
+    for location in result.locations.values_mut() {
+        location.owners = codeowners.owners(&location.path).to_vec();
+    }
+}
+
+/// Keep only what `owner` is responsible for: violations and rule warnings
+/// about a test located in a file `owner` owns, plus those that cannot be
+/// traced to a file. Returns how many were dropped. Pure function — no IO.
+pub fn retain_owned(result: &mut EvalResult, owner: &str) -> usize {
+    let locations = &result.locations;
+    let owned = |violation: &Violation| {
+        let (_, subject, _) = violation_summary(violation);
+        locations
+            .get(&subject)
+            .is_none_or(|location| location.owners.iter().any(|o| o == owner))
+    };
+    let before = result.violations.len() + result.warnings.len();
+    result.violations.retain(|violation| owned(violation));
+    result.warnings.retain(|warning| match warning {
+        Warning::RuleWarning { violation } => owned(violation),
+        _ => true,
+    });
+    before - result.violations.len() - result.warnings.len()
+}
@@ -0,0 +1,1344 @@
+// Project configuration: optional `ratchet.toml` next to `.test-status.json`.
+
+use crate::audit::AUDIT_FILE_NAME;
+use crate::coverage::{COVERAGE_FILE_NAME, DEFAULT_COVERAGE_COMMAND};
+use crate::error::{ParseError, RatchetError};
+use crate::gatekeeper::Handshake;
+use crate::git_cli::GitCliHistory;
+use crate::grandfather::GrandfatherPatterns;
+use crate::history::{GitHistory, HistoryProvider};
+use crate::hooks::HookEvent;
+use crate::jj_history::JjHistory;
+use crate::parameterized::ParameterizedGroups;
+use crate::partition::Partition;
+use crate::result_cache::{RemoteCache, ResultCache};
+use crate::run_filter::RunFilter;
+use crate::runner::{
+    CommandRunner, CompositeRunner, NextestRunner, OutputParser, RetryingRunner, RunnerError,
+    TestRunner,
+};
+use crate::state_ref::StateRefHistory;
+use crate::status::{STATUS_FILE_NAME, Toolchain, target_status_file_name};
+use crate::toolchain::detect_toolchain;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const CONFIG_FILE_NAME: &str = "ratchet.toml";
+
+/// Settings read from `ratchet.toml`. Every field is optional; a missing file
+/// means all defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RatchetConfig {
+    /// Test runners whose results are combined into one ratchet run. Empty
+    /// means the default: a single `cargo nextest` runner.
+    #[serde(default)]
+    pub runners: Vec<RunnerConfig>,
+    /// Optional ratchet rules, all off by default.
+    #[serde(default)]
+    pub rules: RulesConfig,
+    /// Audit trail settings.
+    #[serde(default)]
+    pub audit: AuditConfig,
+    /// How git history is read.
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// Independent ratchets for subdirectories. Empty means one ratchet for
+    /// the whole project.
+    #[serde(default)]
+    pub roots: Vec<RootConfig>,
+    /// Limits on how long the test run may take.
+    #[serde(default)]
+    pub timeouts: TimeoutsConfig,
+    /// How often a run that failed outside the tests is retried.
+    #[serde(default)]
+    pub retries: RetriesConfig,
+    /// Where the ratchet state is stored.
+    #[serde(default)]
+    pub state: StateConfig,
+    /// Patterns of pre-existing tests that may pass without being pending.
+    #[serde(default)]
+    pub grandfather: GrandfatherPatterns,
+    /// Patterns of parameterized test cases tracked as one test each.
+    #[serde(default)]
+    pub parameterized: ParameterizedGroups,
+    /// Preset for every `[rules]` setting the table leaves out.
+    #[serde(default)]
+    pub strictness: Option<Strictness>,
+    /// How the gatekeeper test proves the ratchet ran it.
+    #[serde(default)]
+    pub gatekeeper: GatekeeperConfig,
+    /// Commands run after evaluation for what the run did.
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// Per-test coverage of promoted tests.
+    #[serde(default)]
+    pub coverage: CoverageConfig,
+    /// Extra rules checked by `ratchet-rule-*` plugins.
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    /// Per-test execution times kept in the status file.
+    #[serde(default)]
+    pub timing: TimingConfig,
+    /// Reuse of test results while their binaries' inputs are unchanged.
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub nextest: NextestConfig,
+    /// Only check history after this commit. Set by `--since`, never read
+    /// from `ratchet.toml`.
+    #[serde(skip)]
+    pub since: Option<String>,
+    /// Read the committed status as merged into this commit. Set by
+    /// `--merge-base`, never read from `ratchet.toml`.
+    #[serde(skip)]
+    pub merge_target: Option<String>,
+    /// Only report violations in files this CODEOWNERS owner owns. Set by
+    /// `--owner`, never read from `ratchet.toml`.
+    #[serde(skip)]
+    pub owner: Option<String>,
+    /// Only run and evaluate these test binaries. Set by `--test`, never
+    /// read from `ratchet.toml`.
+    #[serde(skip)]
+    pub binaries: Vec<String>,
+    /// Only run and evaluate the tests these `-p` and `-E` filters select.
+    /// Set on the command line, never read from `ratchet.toml`.
+    #[serde(skip)]
+    pub filter: RunFilter,
+    /// Build the tests into this target directory. Set by `check --staged`
+    /// so its checkout reuses the project's build, never read from
+    /// `ratchet.toml`.
+    #[serde(skip)]
+    pub target_dir: Option<PathBuf>,
+}
+
+/// The `[state]` table.
+///
+/// ```toml
+/// [state]
+/// storage = "ref"
+/// transition-history = 5
+/// per-target = true
+/// toolchain = true
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct StateConfig {
+    #[serde(default)]
+    pub storage: StateStorage,
+    /// How many of its latest state changes each status entry keeps; none
+    /// by default.
+    #[serde(default)]
+    pub transition_history: usize,
+    /// Track the tests of a `--target` run in a status file of their own,
+    /// next to the default one; see [`target_status_file_name`].
+    #[serde(default)]
+    pub per_target: bool,
+    /// Record the toolchain of the run that changes a test's state in its
+    /// entry and transitions.
+    #[serde(default)]
+    pub toolchain: bool,
+}
+
+/// Where the ratchet keeps its status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StateStorage {
+    /// `.test-status.json` in the working tree, committed with the code.
+    #[default]
+    File,
+    /// Commits on `refs/ratchet/state`; the working tree is not touched.
+    /// History is checked along the ref, with libgit2.
+    Ref,
+}
+
+/// The `[gatekeeper]` table.
+///
+/// ```toml
+/// [gatekeeper]
+/// handshake = true
+/// ```
+///
+/// With `handshake`, every run writes a fresh nonce that the gatekeeper must
+/// echo back, instead of the gatekeeper trusting `TDD_RATCHET=1`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct GatekeeperConfig {
+    #[serde(default)]
+    pub handshake: bool,
+}
+
+impl GatekeeperConfig {
+    /// A fresh handshake for one run, when enabled.
+    pub fn begin_handshake(&self) -> Result<Option<Arc<Handshake>>, RunnerError> {
+        if !self.handshake {
+            return Ok(None);
+        }
+        Handshake::write_nonce()
+            .map(|handshake| Some(Arc::new(handshake)))
+            .map_err(|source| RunnerError::Handshake { source })
+    }
+}
+
+/// The `[hooks]` table: a command, program first, per event. Each runs in
+/// the project directory after evaluation with the event as JSON on stdin.
+///
+/// ```toml
+/// [hooks]
+/// on-promotion = ["scripts/close-tickets.sh"]
+/// on-violation = ["notify-send", "tdd-ratchet"]
+/// on-new-pending = ["scripts/open-tickets.sh"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub on_promotion: Option<Vec<String>>,
+    #[serde(default)]
+    pub on_violation: Option<Vec<String>>,
+    #[serde(default)]
+    pub on_new_pending: Option<Vec<String>>,
+}
+
+impl HooksConfig {
+    /// The command configured for `event`, if any.
+    pub fn command(&self, event: &HookEvent) -> Option<&[String]> {
+        match event {
+            HookEvent::Promotion { .. } => self.on_promotion.as_deref(),
+            HookEvent::Violation { .. } => self.on_violation.as_deref(),
+            HookEvent::NewPending { .. } => self.on_new_pending.as_deref(),
+        }
+    }
+}
+
+/// Seconds a plugin may run, when unset.
+pub const DEFAULT_PLUGIN_TIMEOUT_SECS: u64 = 60;
+
+/// The `[plugins]` table. Each name in `rules` runs the program
+/// `ratchet-rule-<name>` from `PATH`; see [`crate::plugins`].
+///
+/// ```toml
+/// [plugins]
+/// rules = ["licenses", "no-todo"]
+/// timeout = 60
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PluginsConfig {
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// Seconds each plugin may run before it is stopped and skipped.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+impl PluginsConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_PLUGIN_TIMEOUT_SECS))
+    }
+}
+
+/// Retries of a run after an infrastructure failure, when unset.
+pub const DEFAULT_INFRASTRUCTURE_RETRIES: u32 = 2;
+
+/// The `[retries]` table.
+///
+/// ```toml
+/// [retries]
+/// infrastructure = 2
+/// ```
+///
+/// A run killed by a signal, or whose build died in the linker or the
+/// compiler cache, is retried as a whole before anything is reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RetriesConfig {
+    /// Retries after an infrastructure failure; 0 turns retrying off.
+    #[serde(default)]
+    pub infrastructure: Option<u32>,
+}
+
+impl RetriesConfig {
+    pub fn infrastructure(&self) -> u32 {
+        self.infrastructure
+            .unwrap_or(DEFAULT_INFRASTRUCTURE_RETRIES)
+    }
+}
+
+/// The `[timeouts]` table, in seconds.
+///
+/// ```toml
+/// [timeouts]
+/// run = 1800
+/// test = 120
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TimeoutsConfig {
+    /// Wall-clock limit for all runners together. A run that hits it is
+    /// stopped and reported as timed out; the status file is not updated.
+    #[serde(default)]
+    pub run: Option<u64>,
+    /// Per-test limit, passed to nextest; a test that exceeds it fails.
+    /// Other runners take their own per-test settings in `args`.
+    #[serde(default)]
+    pub test: Option<u64>,
+}
+
+impl TimeoutsConfig {
+    /// When a run starting now must finish.
+    fn deadline(&self) -> Option<Instant> {
+        self.run
+            .map(|secs| Instant::now() + Duration::from_secs(secs))
+    }
+
+    fn nextest_runner(
+        &self,
+        project_dir: &Path,
+        pending: &BTreeSet<String>,
+        deadline: Option<Instant>,
+        handshake: Option<&Arc<Handshake>>,
+    ) -> NextestRunner {
+        let mut runner = NextestRunner::new(project_dir).with_pending(pending.clone());
+        if let Some(deadline) = deadline {
+            runner = runner.with_deadline(deadline);
+        }
+        if let Some(handshake) = handshake {
+            runner = runner.with_handshake(Arc::clone(handshake));
+        }
+        if let Some(secs) = self.test {
+            runner = runner.with_test_timeout(Duration::from_secs(secs));
+        }
+        runner
+    }
+}
+
+/// The `[history]` table.
+///
+/// ```toml
+/// [history]
+/// backend = "gix"
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct HistoryConfig {
+    #[serde(default)]
+    pub backend: HistoryBackend,
+}
+
+/// Library used to walk git history.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryBackend {
+    /// libgit2, via the `git2` crate, falling back to the `git` CLI when
+    /// libgit2 cannot open the repository.
+    #[default]
+    Git2,
+    /// The `git` CLI only.
+    GitCli,
+    /// gitoxide; requires building with the `gix` feature.
+    #[cfg(feature = "gix")]
+    Gix,
+    /// A Jujutsu repository colocated with git: the tip is resolved with
+    /// `jj`, skipping the auto-committed working copy.
+    Jj,
+}
+
+impl HistoryConfig {
+    /// History provider for the status file at `status_path`, relative to
+    /// the repository at `repo_path`.
+    pub fn provider(&self, repo_path: &Path, status_path: &Path) -> Box<dyn HistoryProvider> {
+        match self.backend {
+            HistoryBackend::Git2 if git2::Repository::open(repo_path).is_ok() => {
+                Box::new(GitHistory::new(repo_path).with_status_path(status_path))
+            }
+            HistoryBackend::Git2 | HistoryBackend::GitCli => {
+                Box::new(GitCliHistory::new(repo_path).with_status_path(status_path))
+            }
+            #[cfg(feature = "gix")]
+            HistoryBackend::Gix => Box::new(
+                crate::gix_history::GixHistory::new(repo_path).with_status_path(status_path),
+            ),
+            HistoryBackend::Jj => Box::new(JjHistory::new(repo_path).with_status_path(status_path)),
+        }
+    }
+}
+
+/// One `[[roots]]` entry: a subdirectory with its own status file, test
+/// runners, and history. The directory's own `ratchet.toml`, if any,
+/// configures its runners and rules.
+///
+/// ```toml
+/// [[roots]]
+/// path = "services/api"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RootConfig {
+    /// Root directory, relative to the project directory.
+    pub path: PathBuf,
+    /// Status file, relative to the project directory. Defaults to
+    /// `.test-status.json` inside `path`.
+    #[serde(default)]
+    pub status: Option<PathBuf>,
+}
+
+impl RootConfig {
+    /// The status file path relative to the project directory, which is also
+    /// its path in git history.
+    pub fn status_path(&self) -> PathBuf {
+        self.status
+            .clone()
+            .unwrap_or_else(|| self.path.join(STATUS_FILE_NAME))
+    }
+}
+
+/// The `[rules]` table.
+///
+/// ```toml
+/// [rules]
+/// promotion-must-not-edit-test = true
+/// promoted-tests-must-assert = true
+/// passing-to-ignored = "deny"
+/// dirty-worktree = "deny"
+/// flake-threshold = 5
+/// quarantine-after = 3
+/// max-pending = 5
+/// max-promotions-per-commit = 1
+/// pending-test-commit-types = ["test"]
+/// regression = "error"
+/// test-disappeared = "warn"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RulesConfig {
+    /// Flag commits that promote a test to passing while also changing that
+    /// test's `#[test]` function body.
+    #[serde(default)]
+    pub promotion_must_not_edit_test: bool,
+    /// Warn when a test promoted to passing has no assertion in its body.
+    #[serde(default)]
+    pub promoted_tests_must_assert: bool,
+    /// How to treat a passing test that the run reports as ignored.
+    #[serde(default)]
+    pub passing_to_ignored: RuleLevel,
+    /// How to treat uncommitted changes to the status file or test files,
+    /// which history checks cannot see yet; defaults to `warn`.
+    #[serde(default)]
+    pub dirty_worktree: Option<RuleLevel>,
+    /// Report a test that flakes once its flake count exceeds this;
+    /// defaults to [`DEFAULT_FLAKE_THRESHOLD`].
+    #[serde(default)]
+    pub flake_threshold: Option<u32>,
+    /// Quarantine a passing test as flaky once its outcome has flipped this
+    /// many times in a row on the same commit; off when unset.
+    #[serde(default)]
+    pub quarantine_after: Option<u32>,
+    /// Most tests that may be pending at once. A run that would add pending
+    /// tests beyond it is a violation, and the new tests are not recorded.
+    #[serde(default)]
+    pub max_pending: Option<usize>,
+    /// Flag commits that promote more than this many tests to passing.
+    #[serde(default)]
+    pub max_promotions_per_commit: Option<usize>,
+    /// Conventional-commit types, such as `test`, that a commit adding
+    /// pending tests must have. Empty turns the check off.
+    #[serde(default)]
+    pub pending_test_commit_types: Vec<String>,
+    /// Severity of the core checks; each defaults to `deny`.
+    #[serde(default)]
+    pub new_test_passed: Option<RuleLevel>,
+    #[serde(default)]
+    pub regression: Option<RuleLevel>,
+    #[serde(default)]
+    pub test_disappeared: Option<RuleLevel>,
+    #[serde(default)]
+    pub skipped_pending: Option<RuleLevel>,
+    #[serde(default)]
+    pub missing_gatekeeper: Option<RuleLevel>,
+}
+
+impl RulesConfig {
+    /// How strictly `rule` is enforced.
+    pub fn level(&self, rule: Rule) -> RuleLevel {
+        let configured = match rule {
+            Rule::NewTestPassed => self.new_test_passed,
+            Rule::Regression => self.regression,
+            Rule::TestDisappeared => self.test_disappeared,
+            Rule::SkippedPending => self.skipped_pending,
+            Rule::MissingGatekeeper => self.missing_gatekeeper,
+            Rule::PassingToIgnored => return self.passing_to_ignored,
+            Rule::DirtyWorktree => return self.dirty_worktree.unwrap_or(RuleLevel::Warn),
+        };
+        configured.unwrap_or(RuleLevel::Deny)
+    }
+
+    /// Take each setting whose `[rules]` key is not in `explicit` from
+    /// `preset`.
+    pub fn fill_unset(&mut self, preset: &RulesConfig, explicit: &BTreeSet<&str>) {
+        let unset = |key: &str| !explicit.contains(key);
+        if unset("promotion-must-not-edit-test") {
+            self.promotion_must_not_edit_test = preset.promotion_must_not_edit_test;
+        }
+        if unset("promoted-tests-must-assert") {
+            self.promoted_tests_must_assert = preset.promoted_tests_must_assert;
+        }
+        if unset("flake-threshold") {
+            self.flake_threshold = preset.flake_threshold;
+        }
+        if unset("quarantine-after") {
+            self.quarantine_after = preset.quarantine_after;
+        }
+        if unset("max-pending") {
+            self.max_pending = preset.max_pending;
+        }
+        if unset("max-promotions-per-commit") {
+            self.max_promotions_per_commit = preset.max_promotions_per_commit;
+        }
+        if unset("pending-test-commit-types") {
+            self.pending_test_commit_types = preset.pending_test_commit_types.clone();
+        }
+        for rule in Rule::ALL {
+            if unset(rule.name()) {
+                self.set_level(rule, preset.level(rule));
+            }
+        }
+    }
+
+    /// Enforce `rule` at `level`, e.g. from `--allow`/`--deny`.
+    pub fn set_level(&mut self, rule: Rule, level: RuleLevel) {
+        match rule {
+            Rule::NewTestPassed => self.new_test_passed = Some(level),
+            Rule::Regression => self.regression = Some(level),
+            Rule::TestDisappeared => self.test_disappeared = Some(level),
+            Rule::SkippedPending => self.skipped_pending = Some(level),
+            Rule::MissingGatekeeper => self.missing_gatekeeper = Some(level),
+            Rule::PassingToIgnored => self.passing_to_ignored = level,
+            Rule::DirtyWorktree => self.dirty_worktree = Some(level),
+        }
+    }
+}
+
+/// A bundle of `[rules]` settings, chosen with `strictness` at the top of
+/// `ratchet.toml`, so a team can adopt the ratchet a step at a time. Keys set
+/// under `[rules]` override the preset.
+///
+/// ```toml
+/// strictness = "strict"
+///
+/// [rules]
+/// max-promotions-per-commit = 2
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strictness {
+    /// Only regressions and a missing gatekeeper fail the run; the
+    /// test-first checks warn.
+    Relaxed,
+    /// The defaults.
+    Standard,
+    /// Every check fails the run, promotions must not edit their test and
+    /// must assert, and each commit promotes at most one test.
+    Strict,
+}
+
+impl Strictness {
+    /// The `[rules]` this preset stands for.
+    pub fn rules(self) -> RulesConfig {
+        match self {
+            Strictness::Relaxed => RulesConfig {
+                new_test_passed: Some(RuleLevel::Warn),
+                test_disappeared: Some(RuleLevel::Warn),
+                skipped_pending: Some(RuleLevel::Warn),
+                passing_to_ignored: RuleLevel::Warn,
+                dirty_worktree: Some(RuleLevel::Allow),
+                ..RulesConfig::default()
+            },
+            Strictness::Standard => RulesConfig::default(),
+            Strictness::Strict => RulesConfig {
+                promotion_must_not_edit_test: true,
+                promoted_tests_must_assert: true,
+                passing_to_ignored: RuleLevel::Deny,
+                dirty_worktree: Some(RuleLevel::Deny),
+                max_promotions_per_commit: Some(1),
+                ..RulesConfig::default()
+            },
+        }
+    }
+}
+
+/// Flake count above which a flaky test is reported.
+pub const DEFAULT_FLAKE_THRESHOLD: u32 = 2;
+
+/// How strictly an optional rule is enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleLevel {
+    /// Not checked.
+    #[default]
+    #[serde(alias = "off")]
+    Allow,
+    /// Reported as a warning.
+    Warn,
+    /// Reported as a violation.
+    #[serde(alias = "error")]
+    Deny,
+}
+
+/// A check whose severity can be configured under `[rules]` or on the
+/// command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rule {
+    NewTestPassed,
+    Regression,
+    TestDisappeared,
+    SkippedPending,
+    MissingGatekeeper,
+    PassingToIgnored,
+    DirtyWorktree,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 7] = [
+        Rule::NewTestPassed,
+        Rule::Regression,
+        Rule::TestDisappeared,
+        Rule::SkippedPending,
+        Rule::MissingGatekeeper,
+        Rule::PassingToIgnored,
+        Rule::DirtyWorktree,
+    ];
+
+    /// The rule's key under `[rules]`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::NewTestPassed => "new-test-passed",
+            Rule::Regression => "regression",
+            Rule::TestDisappeared => "test-disappeared",
+            Rule::SkippedPending => "skipped-pending",
+            Rule::MissingGatekeeper => "missing-gatekeeper",
+            Rule::PassingToIgnored => "passing-to-ignored",
+            Rule::DirtyWorktree => "dirty-worktree",
+        }
+    }
+
+    /// Parse a rule by its key, or by the violation name shown in reports
+    /// (`NewTestPassed`).
+    pub fn parse(name: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| {
+            rule.name() == name || rule.name().replace('-', "") == name.to_ascii_lowercase()
+        })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The `[audit]` table.
+///
+/// ```toml
+/// [audit]
+/// enabled = true
+/// file = "audit/ratchet.jsonl"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AuditConfig {
+    /// Append a record of every ratchet run. Overrides are recorded either
+    /// way.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Audit file, relative to the project directory. Defaults to
+    /// `.ratchet-audit.jsonl`.
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+}
+
+impl AuditConfig {
+    /// Path of the audit file for a project.
+    pub fn path(&self, project_dir: &Path) -> PathBuf {
+        project_dir.join(self.file.as_deref().unwrap_or(Path::new(AUDIT_FILE_NAME)))
+    }
+}
+
+/// The `[coverage]` table.
+///
+/// ```toml
+/// [coverage]
+/// enabled = true
+/// file = "coverage/ratchet.json"
+/// command = ["cargo", "llvm-cov", "nextest", "--json", "-E"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CoverageConfig {
+    /// Measure each promoted test and warn when it covers no new lines.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Coverage record, relative to the project directory. Defaults to
+    /// `.ratchet-coverage.json`.
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    /// Program and arguments that print a `cargo llvm-cov --json` export for
+    /// the nextest filterset appended to them.
+    #[serde(default)]
+    pub command: Option<Vec<String>>,
+}
+
+impl CoverageConfig {
+    /// Path of the coverage record for a project.
+    pub fn path(&self, project_dir: &Path) -> PathBuf {
+        project_dir.join(
+            self.file
+                .as_deref()
+                .unwrap_or(Path::new(COVERAGE_FILE_NAME)),
+        )
+    }
+
+    pub fn command(&self) -> Vec<String> {
+        match &self.command {
+            Some(command) => command.clone(),
+            None => DEFAULT_COVERAGE_COMMAND.map(String::from).to_vec(),
+        }
+    }
+}
+
+/// Slowest tests listed in the report, when unset.
+pub const DEFAULT_SLOWEST_TESTS: usize = 10;
+
+/// Percentage the suite may slow down between runs before a warning, when
+/// unset.
+pub const DEFAULT_MAX_SUITE_GROWTH: u32 = 20;
+
+/// The `[timing]` table.
+///
+/// ```toml
+/// [timing]
+/// enabled = true
+/// slowest = 5
+/// max-growth = 10
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TimingConfig {
+    /// Record each test's execution time in its status entry.
+    #[serde(default)]
+    pub enabled: bool,
+    /// How many of the slowest tests the report lists; 0 lists none.
+    #[serde(default)]
+    pub slowest: Option<usize>,
+    /// Warn when the total suite time grows by more than this percentage
+    /// since the last run.
+    #[serde(default)]
+    pub max_growth: Option<u32>,
+}
+
+/// The `[cache]` table.
+///
+/// ```toml
+/// [cache]
+/// enabled = true
+/// signing-key-env = "RATCHET_CACHE_KEY"
+///
+/// [cache.remote]
+/// kind = "s3"
+/// url = "s3://ci-cache/ratchet"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Reuse the results of test binaries whose inputs have not changed
+    /// since they last ran; see [`ResultCache`]. Off by default, and
+    /// bypassed by `--no-cache`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Share cached results with other machines through a remote store.
+    /// Its results are trusted: anyone who can write to it can make tests
+    /// pass, unless `signing-key-env` is set.
+    #[serde(default)]
+    pub remote: Option<RemoteCacheConfig>,
+    /// Environment variable holding a key that signs uploaded results and
+    /// verifies downloaded ones, so only holders of the key can add
+    /// results to the remote store.
+    #[serde(default)]
+    pub signing_key_env: Option<String>,
+}
+
+impl CacheConfig {
+    /// The remote store, with the signing key from `signing-key-env`.
+    pub fn remote_cache(&self) -> Option<RemoteCache> {
+        let mut remote = self.remote.as_ref()?.remote();
+        remote.signing_key = self
+            .signing_key_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok());
+        Some(remote)
+    }
+}
+
+/// The `[cache.remote]` table: where cached results are shared. Each kind
+/// reads an object with a GET command that prints it and writes one with a
+/// PUT command that reads it from stdin.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum RemoteCacheConfig {
+    /// Plain HTTP GET and PUT of `<url>/<key>` with `curl`, sending the
+    /// bearer token from the `token-env` environment variable, if set, on
+    /// curl's stdin rather than its command line.
+    Http {
+        url: String,
+        #[serde(default, rename = "token-env")]
+        token_env: Option<String>,
+    },
+    /// `<url>/<key>` in S3 with `aws s3 cp`; `url` is `s3://bucket/prefix`.
+    S3 { url: String },
+    /// `<url>/<key>` in Google Cloud Storage with `gcloud storage`; `url` is
+    /// `gs://bucket/prefix`.
+    Gcs { url: String },
+    /// Any other store: `get` and `put` are argvs in which `{key}` is
+    /// replaced by the object's name.
+    Command { get: Vec<String>, put: Vec<String> },
+}
+
+impl RemoteCacheConfig {
+    /// The GET and PUT commands for this store.
+    pub fn remote(&self) -> RemoteCache {
+        let argv = |args: &[&str], url: &str| -> Vec<String> {
+            args.iter()
+                .map(|arg| arg.replace("{url}", url.trim_end_matches('/')))
+                .collect()
+        };
+        match self {
+            RemoteCacheConfig::Http { url, token_env } => {
+                let token = token_env.as_ref().and_then(|name| std::env::var(name).ok());
+                // A token is read as a header from stdin, so it never shows
+                // in the process list; the object to store then comes from a
+                // file instead.
+                let (auth, body): (&[&str], &str) = match token {
+                    Some(_) => (&["-H", "@-"], "@{file}"),
+                    None => (&[], "@-"),
+                };
+                let get = [&["curl", "-fsS"], auth, &["{url}/{key}"]].concat();
+                let put = [
+                    &["curl", "-fsS", "-X", "PUT"],
+                    auth,
+                    &["--data-binary", body, "{url}/{key}"],
+                ]
+                .concat();
+                RemoteCache {
+                    get: argv(&get, url),
+                    put: argv(&put, url),
+                    secret: token.map(|token| format!("Authorization: Bearer {token}\n")),
+                    signing_key: None,
+                }
+            }
+            RemoteCacheConfig::S3 { url } => RemoteCache {
+                get: argv(&["aws", "s3", "cp", "--quiet", "{url}/{key}", "-"], url),
+                put: argv(&["aws", "s3", "cp", "--quiet", "-", "{url}/{key}"], url),
+                secret: None,
+                signing_key: None,
+            },
+            RemoteCacheConfig::Gcs { url } => RemoteCache {
+                get: argv(&["gcloud", "storage", "cat", "{url}/{key}"], url),
+                put: argv(&["gcloud", "storage", "cp", "-", "{url}/{key}"], url),
+                secret: None,
+                signing_key: None,
+            },
+            RemoteCacheConfig::Command { get, put } => RemoteCache {
+                get: get.clone(),
+                put: put.clone(),
+                secret: None,
+                signing_key: None,
+            },
+        }
+    }
+}
+
+impl TimingConfig {
+    pub fn slowest(&self) -> usize {
+        self.slowest.unwrap_or(DEFAULT_SLOWEST_TESTS)
+    }
+
+    pub fn max_growth(&self) -> u32 {
+        self.max_growth.unwrap_or(DEFAULT_MAX_SUITE_GROWTH)
+    }
+}
+
+/// The `[nextest]` table: options passed on to `cargo nextest run`, so a
+/// project's nextest tuning applies under the ratchet too.
+///
+/// ```toml
+/// [nextest]
+/// profile = "ci"
+/// retries = 2
+/// test-threads = 4
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct NextestConfig {
+    /// The nextest profile from `.config/nextest.toml`; `--profile`
+    /// overrides it.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Retry failing tests; a test passing on a retry counts as flaky.
+    #[serde(default)]
+    pub retries: Option<u32>,
+    /// Tests run at once; negative counts are relative to the CPUs, as in
+    /// nextest.
+    #[serde(default)]
+    pub test_threads: Option<i64>,
+    /// Show the tests' output as they run, one test at a time.
+    #[serde(default)]
+    pub no_capture: bool,
+    /// Build and run the tests in release mode, from `--release`.
+    #[serde(skip)]
+    pub release: bool,
+    /// The target triple to build and run the tests for, from `--target`.
+    #[serde(skip)]
+    pub target: Option<String>,
+}
+
+impl NextestConfig {
+    /// The `cargo nextest run` arguments for these options. Pure function —
+    /// no IO.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(profile) = &self.profile {
+            args.extend(["--profile".to_string(), profile.clone()]);
+        }
+        if let Some(retries) = self.retries {
+            args.extend(["--retries".to_string(), retries.to_string()]);
+        }
+        if let Some(threads) = self.test_threads {
+            args.extend(["--test-threads".to_string(), threads.to_string()]);
+        }
+        if self.no_capture {
+            args.push("--no-capture".to_string());
+        }
+        if self.release {
+            args.push("--release".to_string());
+        }
+        if let Some(target) = &self.target {
+            args.extend(["--target".to_string(), target.clone()]);
+        }
+        args
+    }
+}
+
+/// One `[[runners]]` entry.
+///
+/// ```toml
+/// [[runners]]
+/// kind = "command"
+/// command = ["make", "test"]
+/// parser = "libtest"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum RunnerConfig {
+    /// `cargo nextest run` in the project directory.
+    Nextest,
+    /// `pytest -v` plus any extra `args`; test IDs are recorded as
+    /// `pytest::file$test`.
+    Pytest {
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+    },
+    /// `npx jest --json` plus any extra `args`; tests are recorded as
+    /// `js::file$describe › title`.
+    Jest {
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+    },
+    /// `npx vitest run --reporter=json` plus any extra `args`; same naming as
+    /// Jest so switching between them keeps test identities.
+    Vitest {
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+    },
+    /// `go test -json` with `args` (default `./...`); tests are recorded as
+    /// `go::package$TestName`.
+    Go {
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+    },
+    /// `cargo test` with `args` (default `--test cucumber`) for a cucumber-rs
+    /// target that writes its `writer::Json` report to stdout; scenarios are
+    /// recorded as `cucumber::feature$scenario`.
+    Cucumber {
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+    },
+    /// `wasm-pack test` with `args` (default `--headless --chrome`) for
+    /// wasm-bindgen browser tests; tests are recorded as
+    /// `wasm::target$test`.
+    WasmPack {
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+    },
+    /// `cargo test` plus any extra `args` for an embedded crate whose cargo
+    /// runner is `probe-rs run`, parsing defmt-test logs; tests are recorded
+    /// as `embedded::target$module::test`.
+    DefmtTest {
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+    },
+    /// An arbitrary command whose stdout is parsed with `parser`.
+    Command {
+        command: Vec<String>,
+        #[serde(default)]
+        parser: OutputParser,
+        /// Directory to run in, relative to the project directory.
+        #[serde(default)]
+        workdir: Option<PathBuf>,
+    },
+}
+
+impl RatchetConfig {
+    /// Load `ratchet.toml` from the project directory, or defaults if absent.
+    pub fn load(project_dir: &Path) -> Result<Self, RatchetError> {
+        let path = project_dir.join(CONFIG_FILE_NAME);
+        if !path.exists() {
+            return Ok(RatchetConfig::default());
+        }
+        let contents =
+            std::fs::read_to_string(&path).map_err(|source| RatchetError::io(&path, source))?;
+        Self::parse_from_str(&contents, &path)
+    }
+
+    pub fn parse_from_str(contents: &str, path: &Path) -> Result<Self, RatchetError> {
+        let parse_error = |source| {
+            RatchetError::from(ParseError::Config {
+                path: path.to_path_buf(),
+                source,
+            })
+        };
+        let mut config: RatchetConfig = toml::from_str(contents).map_err(parse_error)?;
+        if let Some(strictness) = config.strictness {
+            let table: toml::Table = toml::from_str(contents).map_err(parse_error)?;
+            let explicit: BTreeSet<&str> = table
+                .get("rules")
+                .and_then(toml::Value::as_table)
+                .into_iter()
+                .flat_map(|rules| rules.keys().map(String::as_str))
+                .collect();
+            config.rules.fill_unset(&strictness.rules(), &explicit);
+        }
+        Ok(config)
+    }
+
+    /// History provider for the status file at `status_path`, relative to
+    /// the repository at `repo_path`: the state ref's log in ref storage,
+    /// otherwise the configured `[history]` backend.
+    pub fn history_provider(
+        &self,
+        repo_path: &Path,
+        status_path: &Path,
+    ) -> Box<dyn HistoryProvider> {
+        match self.state.storage {
+            StateStorage::File => self.history.provider(repo_path, status_path),
+            StateStorage::Ref => {
+                Box::new(StateRefHistory::new(repo_path).with_status_path(status_path))
+            }
+        }
+    }
+
+    /// [`Self::history_provider`] bounded to the commits after `since`, for
+    /// `--since` and `--merge-base`; with a `merge_target`, the committed
+    /// status is read as merged into it. Only libgit2 can bound its walk,
+    /// so there is none for other backends or ref storage.
+    pub fn bounded_history_provider(
+        &self,
+        repo_path: &Path,
+        status_path: &Path,
+        since: &str,
+    ) -> Option<Box<dyn HistoryProvider>> {
+        match (self.state.storage, self.history.backend) {
+            (StateStorage::File, HistoryBackend::Git2)
+                if git2::Repository::open(repo_path).is_ok() =>
+            {
+                let history = GitHistory::new(repo_path)
+                    .with_status_path(status_path)
+                    .with_since(since);
+                Some(Box::new(match &self.merge_target {
+                    Some(target) => history.with_merge_target(target),
+                    None => history,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Build a runner for one partition of a sharded run. Only nextest can
+    /// split a run, so there is none when other runners are configured.
+    pub fn partition_runner(
+        &self,
+        project_dir: &Path,
+        pending: &BTreeSet<String>,
+        partition: Partition,
+        handshake: Option<&Arc<Handshake>>,
+    ) -> Option<NextestRunner> {
+        if self
+            .runners
+            .iter()
+            .any(|runner| !matches!(runner, RunnerConfig::Nextest))
+        {
+            return None;
+        }
+        Some(
+            self.timeouts
+                .nextest_runner(project_dir, pending, self.timeouts.deadline(), handshake)
+                .with_partition(partition)
+                .with_args(self.nextest.args()),
+        )
+    }
+
+    /// Where this run keeps its status, for a root whose status file is at
+    /// `status`: with `[state] per-target` and `--target`, the target's
+    /// own status file next to it.
+    pub fn run_status_path(&self, status: &Path) -> PathBuf {
+        match &self.nextest.target {
+            Some(target) if self.state.per_target => {
+                status.with_file_name(target_status_file_name(target))
+            }
+            _ => status.to_path_buf(),
+        }
+    }
+
+    /// Build the configured test runner. `pending` names feed the nextest
+    /// progress line. The `[timeouts] run` clock starts here, so build the
+    /// runner just before running it.
+    /// The toolchain the tests run with, when `[state] toolchain` is set.
+    pub fn toolchain(&self, project_dir: &Path) -> Option<Toolchain> {
+        if !self.state.toolchain {
+            return None;
+        }
+        let nextest = self.runners.is_empty()
+            || self
+                .runners
+                .iter()
+                .any(|runner| matches!(runner, RunnerConfig::Nextest));
+        detect_toolchain(project_dir, self.nextest.target.as_deref(), nextest)
+    }
+
+    pub fn test_runner(
+        &self,
+        project_dir: &Path,
+        pending: &BTreeSet<String>,
+    ) -> Result<Box<dyn TestRunner>, RunnerError> {
+        let deadline = self.timeouts.deadline();
+        let handshake = self.gatekeeper.begin_handshake()?;
+        let handshake = handshake.as_ref();
+        let mut nextest = self
+            .timeouts
+            .nextest_runner(project_dir, pending, deadline, handshake)
+            .with_binaries(self.binaries.clone())
+            .with_filter(self.filter.clone())
+            .with_args(self.nextest.args());
+        if let Some(dir) = &self.target_dir {
+            nextest = nextest.with_target_dir(dir);
+        }
+        // The cache's fingerprints do not cover the build mode or target.
+        if self.cache.enabled && !self.nextest.release && self.nextest.target.is_none() {
+            let mut cache = ResultCache::new(project_dir);
+            if let Some(remote) = self.cache.remote_cache() {
+                cache = cache.with_remote(remote);
+            }
+            nextest = nextest.with_cache(cache);
+        }
+        let runner: Box<dyn TestRunner> = if self.runners.is_empty() {
+            Box::new(nextest)
+        } else {
+            let mut runners = self
+                .runners
+                .iter()
+                .map(|runner| {
+                    runner.build(
+                        project_dir,
+                        &nextest,
+                        deadline,
+                        handshake,
+                        self.target_dir.as_deref(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if runners.len() == 1 {
+                runners.remove(0)
+            } else {
+                Box::new(CompositeRunner::new(runners))
+            }
+        };
+        Ok(Box::new(RetryingRunner::new(
+            runner,
+            self.retries.infrastructure(),
+        )))
+    }
+}
+
+impl RunnerConfig {
+    /// Build this runner; `nextest` is the configured nextest runner, used
+    /// as is for `kind = "nextest"`.
+    fn build(
+        &self,
+        project_dir: &Path,
+        nextest: &NextestRunner,
+        deadline: Option<Instant>,
+        handshake: Option<&Arc<Handshake>>,
+        target_dir: Option<&Path>,
+    ) -> Result<Box<dyn TestRunner>, RunnerError> {
+        match self {
+            RunnerConfig::Nextest => Ok(Box::new(nextest.clone())),
+            RunnerConfig::Pytest { args, workdir } => adapter_runner(
+                &["pytest", "-v", "--color=no", "-p", "no:cacheprovider"],
+                args,
+                runner_dir(project_dir, workdir),
+                OutputParser::Pytest,
+                deadline,
+                handshake,
+                target_dir,
+            ),
+            RunnerConfig::Jest { args, workdir } => adapter_runner(
+                &["npx", "jest", "--json"],
+                args,
+                runner_dir(project_dir, workdir),
+                OutputParser::JestJson,
+                deadline,
+                handshake,
+                target_dir,
+            ),
+            RunnerConfig::Vitest { args, workdir } => adapter_runner(
+                &["npx", "vitest", "run", "--reporter=json"],
+                args,
+                runner_dir(project_dir, workdir),
+                OutputParser::JestJson,
+                deadline,
+                handshake,
+                target_dir,
+            ),
+            RunnerConfig::Go { args, workdir } => {
+                let default_args = ["./...".to_string()];
+                adapter_runner(
+                    &["go", "test", "-json"],
+                    if args.is_empty() { &default_args } else { args },
+                    runner_dir(project_dir, workdir),
+                    OutputParser::GoTestJson,
+                    deadline,
+                    handshake,
+                    target_dir,
+                )
+            }
+            RunnerConfig::Cucumber { args, workdir } => {
+                let default_args = ["--test".to_string(), "cucumber".to_string()];
+                adapter_runner(
+                    &["cargo", "test"],
+                    if args.is_empty() { &default_args } else { args },
+                    runner_dir(project_dir, workdir),
+                    OutputParser::CucumberJson,
+                    deadline,
+                    handshake,
+                    target_dir,
+                )
+            }
+            RunnerConfig::WasmPack { args, workdir } => {
+                let default_args = ["--headless".to_string(), "--chrome".to_string()];
+                adapter_runner(
+                    &["wasm-pack", "test"],
+                    if args.is_empty() { &default_args } else { args },
+                    runner_dir(project_dir, workdir),
+                    OutputParser::WasmBindgen,
+                    deadline,
+                    handshake,
+                    target_dir,
+                )
+            }
+            RunnerConfig::DefmtTest { args, workdir } => adapter_runner(
+                &["cargo", "test"],
+                args,
+                runner_dir(project_dir, workdir),
+                OutputParser::DefmtTest,
+                deadline,
+                handshake,
+                target_dir,
+            ),
+            RunnerConfig::Command {
+                command,
+                parser,
+                workdir,
+            } => command_runner(
+                command,
+                runner_dir(project_dir, workdir),
+                *parser,
+                deadline,
+                handshake,
+                target_dir,
+            ),
+        }
+    }
+}
+
+/// A [`CommandRunner`] for a built-in adapter: a fixed base command plus
+/// user-supplied extra arguments.
+fn adapter_runner(
+    base: &[&str],
+    extra_args: &[String],
+    dir: PathBuf,
+    parser: OutputParser,
+    deadline: Option<Instant>,
+    handshake: Option<&Arc<Handshake>>,
+    target_dir: Option<&Path>,
+) -> Result<Box<dyn TestRunner>, RunnerError> {
+    let command: Vec<String> = base
+        .iter()
+        .map(|s| s.to_string())
+        .chain(extra_args.iter().cloned())
+        .collect();
+    command_runner(&command, dir, parser, deadline, handshake, target_dir)
+}
+
+fn command_runner(
+    command: &[String],
+    dir: PathBuf,
+    parser: OutputParser,
+    deadline: Option<Instant>,
+    handshake: Option<&Arc<Handshake>>,
+    target_dir: Option<&Path>,
+) -> Result<Box<dyn TestRunner>, RunnerError> {
+    let mut runner = CommandRunner::new(command, dir, parser)?;
+    if let Some(deadline) = deadline {
+        runner = runner.with_deadline(deadline);
+    }
+    if let Some(handshake) = handshake {
+        runner = runner.with_handshake(Arc::clone(handshake));
+    }
+    if let Some(dir) = target_dir {
+        runner = runner.with_target_dir(dir);
+    }
+    Ok(Box::new(runner))
+}
+
+fn runner_dir(project_dir: &Path, workdir: &Option<PathBuf>) -> PathBuf {
+    workdir
+        .as_ref()
+        .map_or_else(|| project_dir.to_path_buf(), |w| project_dir.join(w))
+}
@@ -0,0 +1,305 @@
+// Coverage attribution: which source lines each promoted test covers, from
+// `cargo llvm-cov` run on that test alone.
+//
+// A test promoted to passing that covers no line other recorded tests do not
+// already cover is a strong smell: it may pass whatever the code does. Lines
+// are recorded per test in `.ratchet-coverage.json`, which is meant to be
+// committed so every checkout compares against the same record. Lines move
+// as code changes, so the comparison is a heuristic and only ever warns.
+
+use crate::config::CoverageConfig;
+use crate::error::{EvaluationError, ParseError, RatchetError};
+use crate::ratchet::{EvalResult, Warning};
+use crate::status::{TestState, TrackedStatus};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub const COVERAGE_FILE_NAME: &str = ".ratchet-coverage.json";
+
+/// Runs one test under coverage when `[coverage] command` is unset; the
+/// test's nextest filterset is appended.
+pub const DEFAULT_COVERAGE_COMMAND: [&str; 5] = ["cargo", "llvm-cov", "nextest", "--json", "-E"];
+
+/// Covered line numbers, by source file relative to the project directory.
+pub type CoveredLines = BTreeMap<String, BTreeSet<u32>>;
+
+/// The lines covered in `json`, a `cargo llvm-cov --json` export. Files
+/// outside `project_dir` are left out. Pure function — no IO.
+///
+/// A line counts as covered when a region with a non-zero count starts on
+/// it or runs through it.
+pub fn parse_llvm_cov_json(json: &str, project_dir: &Path) -> Result<CoveredLines, RatchetError> {
+    let export: LlvmCovExport =
+        serde_json::from_str(json).map_err(|source| ParseError::CommandOutput {
+            command: "llvm-cov export".to_string(),
+            source,
+        })?;
+    let mut covered = CoveredLines::new();
+    for file in export.data.iter().flat_map(|data| &data.files) {
+        let path = Path::new(&file.filename);
+        let relative = if path.is_absolute() {
+            match path.strip_prefix(project_dir) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            }
+        } else {
+            path
+        };
+        let mut lines = BTreeSet::new();
+        for (i, segment) in file.segments.iter().enumerate() {
+            if !segment.counts() {
+                continue;
+            }
+            let end = file
+                .segments
+                .get(i + 1)
+                .map_or(segment.line(), |next| next.line().max(segment.line()));
+            lines.extend(segment.line()..=end);
+        }
+        if !lines.is_empty() {
+            covered.insert(relative.to_string_lossy().into_owned(), lines);
+        }
+    }
+    Ok(covered)
+}
+
+#[derive(Deserialize)]
+struct LlvmCovExport {
+    data: Vec<LlvmCovData>,
+}
+
+#[derive(Deserialize)]
+struct LlvmCovData {
+    files: Vec<LlvmCovFile>,
+}
+
+#[derive(Deserialize)]
+struct LlvmCovFile {
+    filename: String,
+    #[serde(default)]
+    segments: Vec<Segment>,
+}
+
+/// `[line, column, count, has_count, is_region_entry, is_gap_region]`; older
+/// exports leave out the last field.
+#[derive(Deserialize)]
+struct Segment(
+    u32,
+    IgnoredAny,
+    u64,
+    bool,
+    IgnoredAny,
+    #[serde(default)] bool,
+);
+
+impl Segment {
+    fn line(&self) -> u32 {
+        self.0
+    }
+
+    fn counts(&self) -> bool {
+        let (count, has_count, is_gap) = (self.2, self.3, self.5);
+        has_count && !is_gap && count > 0
+    }
+}
+
+/// The lines each promoted test covered, as stored in
+/// `.ratchet-coverage.json`. Lines are kept as ranges such as `"3-9,12"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageRecord {
+    pub tests: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl CoverageRecord {
+    /// Read the record; a missing file is an empty record.
+    pub fn load(path: &Path) -> Result<Self, RatchetError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|source| {
+                ParseError::Sidecar {
+                    path: path.to_path_buf(),
+                    source,
+                }
+                .into()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(RatchetError::io(path, e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), RatchetError> {
+        let json = serde_json::to_string_pretty(self).map_err(|source| ParseError::Sidecar {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        std::fs::write(path, json + "\n").map_err(|e| RatchetError::io(path, e))
+    }
+
+    /// The lines recorded for `test`.
+    pub fn lines(&self, test: &str) -> CoveredLines {
+        self.tests
+            .get(test)
+            .into_iter()
+            .flatten()
+            .map(|(file, ranges)| (file.clone(), parse_ranges(ranges)))
+            .collect()
+    }
+
+    pub fn insert(&mut self, test: &str, covered: &CoveredLines) {
+        let ranges = covered
+            .iter()
+            .map(|(file, lines)| (file.clone(), format_ranges(lines)))
+            .collect();
+        self.tests.insert(test.to_string(), ranges);
+    }
+
+    /// How many lines in `covered` no recorded test other than `test`
+    /// covers. Pure function — no IO.
+    pub fn new_lines(&self, test: &str, covered: &CoveredLines) -> usize {
+        let mut known = CoveredLines::new();
+        for other in self.tests.keys().filter(|other| *other != test) {
+            for (file, lines) in self.lines(other) {
+                known.entry(file).or_default().extend(lines);
+            }
+        }
+        covered
+            .iter()
+            .map(|(file, lines)| match known.get(file) {
+                Some(known) => lines.difference(known).count(),
+                None => lines.len(),
+            })
+            .sum()
+    }
+}
+
+/// `lines` as comma-separated ranges, e.g. `"3-9,12"`. Pure function — no
+/// IO.
+pub fn format_ranges(lines: &BTreeSet<u32>) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &line in lines {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => ranges.push((line, line)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The lines in ranges written by [`format_ranges`]; malformed parts are
+/// skipped. Pure function — no IO.
+pub fn parse_ranges(ranges: &str) -> BTreeSet<u32> {
+    let mut lines = BTreeSet::new();
+    for part in ranges.split(',').map(str::trim) {
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        if let (Ok(start), Ok(end)) = (start.parse::<u32>(), end.parse::<u32>()) {
+            lines.extend(start..=end);
+        }
+    }
+    lines
+}
+
+/// The nextest filterset that selects only `test`.
+pub fn coverage_filter(test: &str) -> String {
+    match test.split_once('$') {
+        Some((binary, path)) => format!("binary_id(={binary}) & test(={path})"),
+        None => format!("test(={test})"),
+    }
+}
+
+/// Run `test` alone under coverage with `command`, which prints an llvm-cov
+/// JSON export, and read the lines it covered.
+pub fn measure_test(
+    command: &[String],
+    project_dir: &Path,
+    test: &str,
+) -> Result<CoveredLines, RatchetError> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| EvaluationError::EmptyCommand("coverage".to_string()))?;
+    let output = Command::new(program)
+        .args(args)
+        .arg(coverage_filter(test))
+        .current_dir(project_dir)
+        .env("TDD_RATCHET", "1")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| RatchetError::command(program, e))?;
+    if !output.status.success() {
+        return Err(RatchetError::command_exit(
+            program,
+            output.status,
+            &output.stderr,
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_llvm_cov_json(&stdout, project_dir)
+}
+
+/// Measure each test this run promoted from pending to passing, add a
+/// [`Warning::NoNewCoverage`] for those that cover no new lines, and
+/// record their lines in the coverage file. `previous` is the committed
+/// status the run started from. Returns the tests that could not be
+/// measured, with why.
+pub fn check_promoted_coverage(
+    result: &mut EvalResult,
+    previous: &TrackedStatus,
+    project_dir: &Path,
+    config: &CoverageConfig,
+) -> Result<Vec<(String, RatchetError)>, RatchetError> {
+    let path = config.path(project_dir);
+    let mut record = CoverageRecord::load(&path)?;
+    let before = record.clone();
+    record
+        .tests
+        .retain(|test, _| result.updated.tests.contains_key(test));
+
+    let promoted: Vec<String> = result
+        .updated
+        .tests
+        .iter()
+        .filter(|(test, entry)| {
+            entry.state() == TestState::Passing
+                && previous
+                    .tests
+                    .get(*test)
+                    .is_some_and(|before| before.state() == TestState::Pending)
+        })
+        .map(|(test, _)| test.clone())
+        .collect();
+    let command = config.command();
+    let mut failures = Vec::new();
+    for test in promoted {
+        let covered = match measure_test(&command, project_dir, &test) {
+            Ok(covered) => covered,
+            Err(e) => {
+                failures.push((test, e));
+                continue;
+            }
+        };
+        if record.new_lines(&test, &covered) == 0 {
+            result.warnings.push(Warning::NoNewCoverage {
+                test: test.clone(),
+                covered: covered.values().map(BTreeSet::len).sum(),
+            });
+        }
+        record.insert(&test, &covered);
+    }
+
+    if record != before {
+        record.save(&path)?;
+    }
+    Ok(failures)
+}
@@ -0,0 +1,348 @@
+// Long-running daemon for editor integrations: JSON-RPC 2.0 over stdio or a
+// unix socket, one JSON message per line.
+//
+// The daemon keeps the repository open and caches history snapshots until
+// `HEAD` or the state ref moves, so repeated `evaluate` requests only pay for
+// the test run. Methods:
+//
+// - `evaluate`: run the ratchet, like a plain `cargo ratchet` run
+// - `status`: the state of every tracked test
+// - `logTest` (`{"test": NAME}`): every committed state change of one test
+//
+// Evaluation is a callback, so the binary runs it through the same steps as
+// the CLI (quarantine, plugins, coverage, the audit log and hooks) and the
+// daemon's verdicts match `cargo ratchet`. In watch mode the daemon also
+// polls the project's files and re-evaluates when they change.
+//
+// Whenever an evaluation changes the state of any test, a `stateChanged`
+// notification follows (after the response, for an `evaluate` request).
+// Each evaluation holds the project's run lock, waiting for a run already
+// holding it, so it never overlaps a manual `cargo ratchet`.
+
+use crate::error::RatchetError;
+use crate::history::{HistoryProvider, HistorySnapshot};
+use crate::json_report::evaluation_json;
+use crate::ratchet::EvalResult;
+use crate::run_lock::RunLock;
+use crate::state_ref::STATE_REF;
+use crate::status::{StatusFile, TestEntry, TestState, TrackedStatus};
+use crate::test_edits::TestEdit;
+use crate::timeline::test_timeline;
+use crate::watch::Watcher;
+use serde_json::{Value, json};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+/// JSON-RPC error codes.
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// The request was valid but the ratchet failed, e.g. the tests did not
+/// build.
+pub const RATCHET_ERROR: i64 = -32000;
+
+/// A history provider that reuses its snapshots until the repository's
+/// `HEAD` or state ref moves.
+pub struct CachedHistory<H> {
+    inner: H,
+    repo: Option<git2::Repository>,
+    cache: RefCell<Option<(String, Vec<HistorySnapshot>)>>,
+}
+
+impl<H: HistoryProvider> CachedHistory<H> {
+    /// Cache `inner`, watching the repository at `repo_path` for new
+    /// commits. Without a repository nothing is cached.
+    pub fn new(inner: H, repo_path: &Path) -> Self {
+        CachedHistory {
+            inner,
+            repo: git2::Repository::discover(repo_path).ok(),
+            cache: RefCell::new(None),
+        }
+    }
+
+    /// Identifies the history currently visible: the `HEAD` and state ref
+    /// targets.
+    fn cache_key(&self) -> Option<String> {
+        let repo = self.repo.as_ref()?;
+        let head = repo.head().ok()?.target()?;
+        let state = repo.refname_to_id(STATE_REF).ok();
+        Some(format!(
+            "{head}:{}",
+            state.map(|id| id.to_string()).unwrap_or_default()
+        ))
+    }
+}
+
+impl<H: HistoryProvider> HistoryProvider for CachedHistory<H> {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        self.inner.head_status()
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        let Some(key) = self.cache_key() else {
+            return self.inner.snapshots();
+        };
+        if let Some((cached_key, snapshots)) = &*self.cache.borrow()
+            && *cached_key == key
+        {
+            return Ok(snapshots.clone());
+        }
+        let snapshots = self.inner.snapshots()?;
+        *self.cache.borrow_mut() = Some((key, snapshots.clone()));
+        Ok(snapshots)
+    }
+
+    fn edited_tests(&self, commit: &str, tests: &[String]) -> Result<Vec<TestEdit>, RatchetError> {
+        self.inner.edited_tests(commit, tests)
+    }
+
+    fn dirty_paths(&self) -> Result<Vec<String>, RatchetError> {
+        self.inner.dirty_paths()
+    }
+}
+
+/// Runs the ratchet once with the daemon's cached history: gathers,
+/// evaluates, and saves. Returns the committed status the run started from
+/// and the evaluation.
+pub type Evaluator<'a> =
+    Box<dyn FnMut(&dyn HistoryProvider) -> Result<(TrackedStatus, EvalResult), RatchetError> + 'a>;
+
+/// The daemon's state between requests.
+pub struct Daemon<'a> {
+    project_dir: PathBuf,
+    history: CachedHistory<Box<dyn HistoryProvider + 'a>>,
+    evaluator: Evaluator<'a>,
+    /// How often to poll the project's files, and what they were last time.
+    watch: Option<(Duration, Watcher)>,
+    /// Test states after the last evaluation, for change notifications.
+    states: Option<BTreeMap<String, TestState>>,
+}
+
+impl<'a> Daemon<'a> {
+    pub fn new(
+        project_dir: impl Into<PathBuf>,
+        history: impl HistoryProvider + 'a,
+        evaluator: Evaluator<'a>,
+    ) -> Self {
+        let project_dir = project_dir.into();
+        Daemon {
+            history: CachedHistory::new(Box::new(history), &project_dir),
+            project_dir,
+            evaluator,
+            watch: None,
+            states: None,
+        }
+    }
+
+    /// While serving, check the project's files every `interval` and
+    /// re-evaluate when any changed.
+    pub fn with_watch(mut self, interval: Duration) -> Self {
+        self.watch = Some((interval, Watcher::new(&self.project_dir)));
+        self
+    }
+
+    /// Answer every request read from `reader` until it is closed, writing
+    /// one message per line to `writer`. In watch mode, notifications from
+    /// file changes are written between responses.
+    pub fn serve(
+        &mut self,
+        reader: impl BufRead + Send,
+        mut writer: impl Write,
+    ) -> Result<(), RatchetError> {
+        thread::scope(|scope| {
+            let (sender, lines) = mpsc::channel();
+            scope.spawn(move || {
+                for line in reader.lines() {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+            loop {
+                let received = match &self.watch {
+                    Some((interval, _)) => lines.recv_timeout(*interval),
+                    None => lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                let messages = match received {
+                    Ok(line) => {
+                        let line = line.map_err(RatchetError::Connection)?;
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        self.handle(&line)
+                    }
+                    Err(RecvTimeoutError::Timeout) => self.poll(),
+                    Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                };
+                write_messages(&mut writer, &messages).map_err(RatchetError::Connection)?;
+            }
+        })
+    }
+
+    /// In watch mode, re-evaluate if the project's files changed since the
+    /// last poll or evaluation, and return the notifications that caused.
+    pub fn poll(&mut self) -> Vec<Value> {
+        let Some((_, watcher)) = &mut self.watch else {
+            return Vec::new();
+        };
+        if !watcher.changed() {
+            return Vec::new();
+        }
+        let mut notifications = Vec::new();
+        if let Err((_, message)) = self.evaluate(&mut notifications) {
+            warn!(%message, "evaluation after a file change failed");
+        }
+        notifications
+    }
+
+    /// Handle one JSON-RPC message. Returns the response, if the message
+    /// was a request rather than a notification, followed by any
+    /// notifications it caused.
+    pub fn handle(&mut self, message: &str) -> Vec<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => return vec![error_response(Value::Null, PARSE_ERROR, &e.to_string())],
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return vec![error_response(
+                id.unwrap_or(Value::Null),
+                INVALID_REQUEST,
+                "missing method",
+            )];
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let mut notifications = Vec::new();
+        let outcome = match method {
+            "evaluate" => self.evaluate(&mut notifications),
+            "status" => self.status(),
+            "logTest" => self.log_test(&params),
+            other => Err((METHOD_NOT_FOUND, format!("unknown method `{other}`"))),
+        };
+
+        let mut messages = Vec::new();
+        if let Some(id) = id {
+            messages.push(match outcome {
+                Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                Err((code, message)) => error_response(id, code, &message),
+            });
+        }
+        messages.extend(notifications);
+        messages
+    }
+
+    fn evaluate(&mut self, notifications: &mut Vec<Value>) -> Result<Value, (i64, String)> {
+        let lock = RunLock::acquire(&self.project_dir).map_err(ratchet_error)?;
+        let evaluated = (self.evaluator)(&self.history);
+        drop(lock);
+        // The run's own writes, such as the status file, are not changes.
+        if let Some((_, watcher)) = &mut self.watch {
+            watcher.reset();
+        }
+        let (previous, result) = evaluated.map_err(ratchet_error)?;
+
+        let states = test_states(&result.updated.tests);
+        let previous = self
+            .states
+            .take()
+            .unwrap_or_else(|| test_states(&previous.tests));
+        let changes = state_changes(&previous, &states);
+        if !changes.is_empty() {
+            notifications.push(json!({
+                "jsonrpc": "2.0",
+                "method": "stateChanged",
+                "params": {"changes": changes},
+            }));
+        }
+        self.states = Some(states);
+        Ok(evaluation_json(&result, false))
+    }
+
+    fn status(&self) -> Result<Value, (i64, String)> {
+        let states = match &self.states {
+            Some(states) => states.clone(),
+            None => self
+                .history
+                .head_status()
+                .map_err(|e| (RATCHET_ERROR, e.to_string()))?
+                .map(|status| test_states(&status.tests))
+                .unwrap_or_default(),
+        };
+        Ok(json!({ "tests": states }))
+    }
+
+    fn log_test(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let Some(test) = params.get("test").and_then(Value::as_str) else {
+            return Err((INVALID_PARAMS, "`logTest` needs a `test` name".into()));
+        };
+        let snapshots = self
+            .history
+            .snapshots()
+            .map_err(|e| (RATCHET_ERROR, e.to_string()))?;
+        let changes: Vec<Value> = test_timeline(&snapshots, test)
+            .into_iter()
+            .map(|change| {
+                json!({
+                    "commit": change.commit,
+                    "author": change.info.author,
+                    "time": change.info.time,
+                    "summary": change.info.summary,
+                    "from": change.from,
+                    "to": change.to,
+                    "renamedFrom": change.renamed_from,
+                })
+            })
+            .collect();
+        Ok(json!({ "test": test, "changes": changes }))
+    }
+}
+
+fn test_states<'s>(
+    tests: impl IntoIterator<Item = (&'s String, &'s TestEntry)>,
+) -> BTreeMap<String, TestState> {
+    tests
+        .into_iter()
+        .map(|(name, entry)| (name.clone(), entry.state()))
+        .collect()
+}
+
+/// One `{test, from, to}` object per test whose state differs; `null`
+/// means untracked.
+fn state_changes(
+    before: &BTreeMap<String, TestState>,
+    after: &BTreeMap<String, TestState>,
+) -> Vec<Value> {
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    names
+        .into_iter()
+        .filter(|name| before.get(*name) != after.get(*name))
+        .map(|name| json!({"test": name, "from": before.get(name), "to": after.get(name)}))
+        .collect()
+}
+
+fn write_messages(writer: &mut impl Write, messages: &[Value]) -> io::Result<()> {
+    for message in messages {
+        writeln!(writer, "{message}")?;
+    }
+    writer.flush()
+}
+
+fn ratchet_error(error: RatchetError) -> (i64, String) {
+    (RATCHET_ERROR, error.to_string())
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message},
+    })
+}
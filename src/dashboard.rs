@@ -0,0 +1,131 @@
+// Dashboard: a grouped overview of tracked tests and recent history for the
+// interactive `tui` command.
+
+use crate::history::HistorySnapshot;
+use crate::status::{TestState, TrackedStatus};
+use crate::timeline::{StateChange, short_commit, state_changes};
+
+/// Everything the dashboard shows, computed from the status and history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dashboard {
+    pub passing: Vec<String>,
+    pub pending: Vec<PendingTest>,
+    pub recent_promotions: Vec<Promotion>,
+}
+
+/// A pending test and how long it has been waiting for an implementation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingTest {
+    pub name: String,
+    /// The commit where the current pending streak started, if committed.
+    pub since_commit: Option<String>,
+    /// Number of committed snapshots the test has been pending in.
+    pub commits_pending: usize,
+}
+
+/// A pending → passing transition observed in git history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Promotion {
+    pub test: String,
+    pub commit: String,
+}
+
+/// Build the dashboard. Pure function — no IO.
+///
+/// `status` is the status to display (usually the latest ratchet output) and
+/// `snapshots` are the committed history snapshots, oldest first.
+pub fn build_dashboard(
+    status: &TrackedStatus,
+    snapshots: &[HistorySnapshot],
+    promotion_limit: usize,
+) -> Dashboard {
+    let passing = status
+        .tests
+        .iter()
+        .filter(|(_, entry)| entry.state() == TestState::Passing)
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let pending = status
+        .tests
+        .iter()
+        .filter(|(_, entry)| entry.state() == TestState::Pending)
+        .map(|(name, _)| pending_age(name, snapshots))
+        .collect();
+
+    let recent_promotions = state_changes(snapshots)
+        .into_iter()
+        .rev()
+        .filter(StateChange::is_promotion)
+        .take(promotion_limit)
+        .map(|change| Promotion {
+            test: change.test,
+            commit: change.commit,
+        })
+        .collect();
+
+    Dashboard {
+        passing,
+        pending,
+        recent_promotions,
+    }
+}
+
+fn pending_age(name: &str, snapshots: &[HistorySnapshot]) -> PendingTest {
+    let streak: Vec<&HistorySnapshot> = snapshots
+        .iter()
+        .rev()
+        .take_while(|s| {
+            s.status
+                .tests
+                .get(name)
+                .is_some_and(|e| e.state() == TestState::Pending)
+        })
+        .collect();
+
+    PendingTest {
+        name: name.to_string(),
+        since_commit: streak.last().map(|s| s.commit.clone()),
+        commits_pending: streak.len(),
+    }
+}
+
+/// Render the dashboard as plain text for the terminal.
+pub fn render_dashboard(dashboard: &Dashboard) -> String {
+    let mut out = String::new();
+
+    out.push_str(&format!("Pending ({})\n", dashboard.pending.len()));
+    for test in &dashboard.pending {
+        match &test.since_commit {
+            Some(commit) => out.push_str(&format!(
+                "  ○ {}  (pending for {} commit{}, since {})\n",
+                test.name,
+                test.commits_pending,
+                if test.commits_pending == 1 { "" } else { "s" },
+                short_commit(commit)
+            )),
+            None => out.push_str(&format!("  ○ {}  (not yet committed)\n", test.name)),
+        }
+    }
+
+    out.push('\n');
+    out.push_str(&format!("Passing ({})\n", dashboard.passing.len()));
+    for name in &dashboard.passing {
+        out.push_str(&format!("  ✓ {name}\n"));
+    }
+
+    out.push('\n');
+    out.push_str("Recent promotions\n");
+    if dashboard.recent_promotions.is_empty() {
+        out.push_str("  (none in history)\n");
+    }
+    for promotion in &dashboard.recent_promotions {
+        out.push_str(&format!(
+            "  ↑ {}  ({})\n",
+            promotion.test,
+            short_commit(&promotion.commit)
+        ));
+    }
+
+    out
+}
@@ -0,0 +1,88 @@
+// Project discovery: like git, the ratchet works from any subdirectory of
+// a project. The project directory is the nearest directory, from the
+// current one up to the repository root, holding a status file or
+// `ratchet.toml`; without one it is the repository root.
+
+use crate::config::CONFIG_FILE_NAME;
+use crate::status::STATUS_FILE_NAME;
+use std::path::{Path, PathBuf};
+
+/// The project directory for a run started in `start`, which must be
+/// absolute. Outside a git repository with no project files above it, that
+/// is `start` itself.
+pub fn discover_project_dir(start: &Path) -> PathBuf {
+    let git_root = git2::Repository::discover(start)
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf));
+    find_project_dir(start, git_root.as_deref(), |dir| {
+        dir.join(STATUS_FILE_NAME).is_file() || dir.join(CONFIG_FILE_NAME).is_file()
+    })
+}
+
+/// The nearest of `start` and its ancestors for which `is_project` holds,
+/// not looking above `git_root`; else `git_root`, or `start` when it is not
+/// in a repository. Pure function — no IO beyond `is_project`.
+pub fn find_project_dir(
+    start: &Path,
+    git_root: Option<&Path>,
+    is_project: impl Fn(&Path) -> bool,
+) -> PathBuf {
+    let git_root = git_root.filter(|root| start.starts_with(root));
+    for dir in start.ancestors() {
+        if is_project(dir) {
+            return dir.to_path_buf();
+        }
+        if Some(dir) == git_root {
+            return dir.to_path_buf();
+        }
+    }
+    start.to_path_buf()
+}
+
+/// Where a project sits in its git repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoLocation {
+    /// The repository's working directory; git history is read from here.
+    pub repo_dir: PathBuf,
+    /// The project directory relative to `repo_dir`; empty at the root.
+    pub prefix: PathBuf,
+}
+
+impl RepoLocation {
+    /// The project's status file, relative to `repo_dir`.
+    pub fn status_path(&self) -> PathBuf {
+        self.prefix.join(STATUS_FILE_NAME)
+    }
+}
+
+/// Locate the project at `project_dir` in the repository containing it.
+/// Outside a repository, the project directory stands in for the
+/// repository root.
+pub fn locate_in_repo(project_dir: &Path) -> RepoLocation {
+    let located = git2::Repository::discover(project_dir)
+        .ok()
+        .and_then(|repo| {
+            let root = repo.workdir()?.canonicalize().ok()?;
+            let prefix = project_dir
+                .canonicalize()
+                .ok()?
+                .strip_prefix(&root)
+                .ok()?
+                .to_path_buf();
+            Some((root, prefix))
+        });
+    let Some((root, prefix)) = located else {
+        return RepoLocation {
+            repo_dir: project_dir.to_path_buf(),
+            prefix: PathBuf::new(),
+        };
+    };
+    // Prefer the ancestor of `project_dir` to git's canonical path, so that
+    // both keep the same form when reached through a symlink.
+    let repo_dir = project_dir
+        .ancestors()
+        .nth(prefix.components().count())
+        .filter(|dir| dir.canonicalize().ok().as_deref() == Some(root.as_path()))
+        .map_or(root.clone(), Path::to_path_buf);
+    RepoLocation { repo_dir, prefix }
+}
@@ -0,0 +1,218 @@
+// Errors returned by the library.
+//
+// Public functions that can fail return [`RatchetError`], whose variants say
+// where a failure came from: the filesystem, git, a malformed file, a test
+// runner, or inputs the ratchet cannot work with. Each keeps the underlying
+// error as its `source`. Test runners and plugins report their own
+// [`RunnerError`] and [`PluginError`](crate::plugins::PluginError), which a
+// run wraps in [`RatchetError::Runner`] or reports as a warning.
+//
+// Every error enum is `#[non_exhaustive]`, so new kinds of failure are not
+// breaking changes for embedders.
+
+use crate::logging::LoggingError;
+use crate::partition::Partition;
+use crate::runner::RunnerError;
+use crate::self_update::SelfUpdateError;
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RatchetError {
+    /// Reading or writing a file failed.
+    #[error("Failed to read/write {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// Reading the repository or its history failed.
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    /// Reading or writing the state ref (`[state] storage = "ref"`) failed.
+    #[error("Failed to read/write ratchet state in {reference}: {source}")]
+    StateRef {
+        reference: String,
+        #[source]
+        source: git2::Error,
+    },
+    /// A file the ratchet reads or writes is not in the expected format.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    /// The tests could not be run.
+    #[error(transparent)]
+    Runner(#[from] RunnerError),
+    /// The project or the inputs given cannot be ratcheted as they are.
+    #[error(transparent)]
+    Evaluation(#[from] EvaluationError),
+    /// `self update` could not fetch, verify, or install a release.
+    #[error(transparent)]
+    SelfUpdate(#[from] SelfUpdateError),
+    /// A command the ratchet runs besides the tests, such as `rustc -vV` or
+    /// a remote cache store, could not be run or failed.
+    #[error("{command} failed: {source}")]
+    Command {
+        command: String,
+        #[source]
+        source: io::Error,
+    },
+    /// A command the ratchet runs besides the tests, such as a hook or the
+    /// coverage command, exited unsuccessfully. `stderr` is the last line
+    /// it printed there, if it was captured.
+    #[error("{command} exited with {status}{}", detail(.stderr))]
+    CommandExit {
+        command: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+    /// Talking to a client of the daemon or the status server failed.
+    #[error("connection failed: {0}")]
+    Connection(#[source] io::Error),
+    /// Logging could not be set up.
+    #[error(transparent)]
+    Logging(#[from] LoggingError),
+}
+
+impl RatchetError {
+    /// A failed read or write of `path`.
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        RatchetError::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+
+    /// `command` exited with `status`, having printed `stderr`; only its
+    /// last line is kept.
+    pub fn command_exit(command: impl Into<String>, status: ExitStatus, stderr: &[u8]) -> Self {
+        let stderr = String::from_utf8_lossy(stderr);
+        RatchetError::CommandExit {
+            command: command.into(),
+            status,
+            stderr: stderr.lines().last().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// A failed run of `command`.
+    pub fn command(command: impl Into<String>, source: io::Error) -> Self {
+        RatchetError::Command {
+            command: command.into(),
+            source,
+        }
+    }
+}
+
+/// A file that could not be read or written in its format.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ParseError {
+    #[error("Failed to parse JSON in status file {}: {source}", path.display())]
+    Status {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Failed to serialize status file {}: {source}", path.display())]
+    SerializeStatus {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Failed to parse config file {}: {source}", path.display())]
+    Config {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("Failed to parse audit file {} line {line}: {source}", path.display())]
+    Audit {
+        path: PathBuf,
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A JSON sidecar the ratchet keeps next to the status file, such as
+    /// `.ratchet-last-run.json`.
+    #[error("Failed to parse or serialize {}: {source}", path.display())]
+    Sidecar {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// JSON printed by a command the ratchet runs, such as `cargo metadata`
+    /// or the coverage command.
+    #[error("Failed to parse the output of {command}: {source}")]
+    CommandOutput {
+        command: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// Partial results written by `run --emit-results`.
+    #[error("Failed to parse {}: {source}", path.display())]
+    PartialResults {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Inputs the ratchet cannot work with.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum EvaluationError {
+    /// Shards disagree on how many partitions the run was split into.
+    #[error(
+        "partial results come from runs split {} ways; merge shards of one run only",
+        join(.counts.iter(), " and ")
+    )]
+    MixedPartitionCounts { counts: BTreeSet<u32> },
+    /// The same partition was given twice.
+    #[error("partition {0} was given more than once")]
+    DuplicatePartition(Partition),
+    /// Some partitions are missing; their tests would look disappeared.
+    #[error(
+        "missing partition{} {}; their tests would be reported as disappeared",
+        if .0.len() == 1 { "" } else { "s" },
+        join(.0.iter(), ", ")
+    )]
+    MissingPartitions(Vec<Partition>),
+    /// No `[package]` name in `Cargo.toml`, e.g. a virtual workspace root.
+    #[error("Cargo.toml has no [package] name to track the test under")]
+    NoPackage,
+    /// `tests/gatekeeper.rs` exists but does not hold a gatekeeper test.
+    #[error(
+        "{} already exists without a gatekeeper test",
+        crate::gatekeeper::GATEKEEPER_PATH
+    )]
+    GatekeeperPathTaken,
+    /// A command configured in `ratchet.toml`, such as a hook, has no
+    /// program to run.
+    #[error("the {0} command is empty")]
+    EmptyCommand(String),
+    /// `HEAD` cannot be merged into the `--merge-base` target without
+    /// conflicts, so there is no merge result to test.
+    #[error(
+        "HEAD does not merge cleanly into {target}: conflicts in {}",
+        .paths.join(", ")
+    )]
+    MergeConflicts { target: String, paths: Vec<String> },
+}
+
+fn detail(stderr: &str) -> String {
+    if stderr.is_empty() {
+        String::new()
+    } else {
+        format!(": {stderr}")
+    }
+}
+
+fn join(items: impl Iterator<Item = impl ToString>, separator: &str) -> String {
+    items
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
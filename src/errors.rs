@@ -1,10 +1,55 @@
 // Report formatting: produces the complete tdd-ratchet output after a run.
 
-use crate::ratchet::{EvalResult, GATEKEEPER_TEST_NAME, Violation, Warning};
-use crate::status::TestState;
+use crate::gatekeeper::GATEKEEPER_PATH;
+use crate::ratchet::{EvalResult, GATEKEEPER_TEST_NAME, LastGreen, Violation, Warning};
+use crate::status::{AcceptedFailure, StatusFile, TestEntry, TestState, TrackedStatus};
+use crate::test_locations::TestLocation;
+use crate::timeline::{format_date, short_commit};
+use std::collections::{BTreeMap, BTreeSet};
 
 const SEPARATOR: &str = "───────────────────────────────────────────────────────────────";
 
+/// Output format for the end-of-run report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// Self-documenting plain text for terminals (the default).
+    #[default]
+    Text,
+    /// Collapsible Markdown for PR comments and `$GITHUB_STEP_SUMMARY`.
+    Markdown,
+    /// SARIF 2.1.0 for code-scanning platforms.
+    Sarif,
+    /// A JSON object for build systems and scripts.
+    Json,
+}
+
+impl ReportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(ReportFormat::Text),
+            "markdown" | "md" => Some(ReportFormat::Markdown),
+            "sarif" => Some(ReportFormat::Sarif),
+            "json" => Some(ReportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Options for the text report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReportOptions {
+    /// Print the whole captured output of each regression instead of the
+    /// first [`FAILURE_EXCERPT_LINES`] lines.
+    pub full_output: bool,
+    /// Warnings fail the run (`--deny warnings`).
+    pub deny_warnings: bool,
+    /// How many of the slowest timed tests to list; 0 lists none.
+    pub slowest: usize,
+}
+
+/// Lines of a regression's captured output shown by default.
+pub const FAILURE_EXCERPT_LINES: usize = 20;
+
 struct ReportSection {
     title: String,
     why: String,
@@ -14,26 +59,56 @@ struct ReportSection {
     extra: Option<String>,
 }
 
+/// Format the complete report for a ratchet evaluation with default
+/// options.
+pub fn format_report(result: &EvalResult) -> String {
+    format_report_with(result, ReportOptions::default())
+}
+
 /// Format the complete report for a ratchet evaluation.
 ///
 /// Takes the full eval result and produces all output. This is the single
 /// function that owns all output formatting.
-pub fn format_report(result: &EvalResult) -> String {
+pub fn format_report_with(result: &EvalResult, options: ReportOptions) -> String {
     let mut tdd_violations: Vec<&Violation> = Vec::new();
     let mut regressions: Vec<&Violation> = Vec::new();
     let mut disappeared: Vec<&Violation> = Vec::new();
     let mut rename_violations: Vec<&Violation> = Vec::new();
     let mut removal_violations: Vec<&Violation> = Vec::new();
+    let mut tampered: Vec<&Violation> = Vec::new();
+    let mut promotion_edits: Vec<&Violation> = Vec::new();
+    let mut newly_ignored: Vec<&Violation> = Vec::new();
+    let mut pending_limit: Vec<&Violation> = Vec::new();
+    let mut promotion_batches: Vec<&Violation> = Vec::new();
+    let mut mistyped_additions: Vec<&Violation> = Vec::new();
+    let mut expired_acceptances: Vec<&Violation> = Vec::new();
+    let mut open_gatekeepers: Vec<&Violation> = Vec::new();
+    let mut dirty_worktree: Vec<&Violation> = Vec::new();
+    let mut plugin_violations: Vec<&Violation> = Vec::new();
+    let mut duplicate_names: Vec<&Violation> = Vec::new();
     let mut missing_gatekeeper = false;
+    let mut missing_handshake = false;
 
     for v in &result.violations {
         match v {
             Violation::NewTestPassed { .. } | Violation::SkippedPending { .. } => {
                 tdd_violations.push(v);
             }
+            Violation::PromotionEditedTest { .. } => {
+                promotion_edits.push(v);
+            }
+            Violation::TooManyPromotions { .. } => {
+                promotion_batches.push(v);
+            }
+            Violation::PendingTestCommitType { .. } => {
+                mistyped_additions.push(v);
+            }
             Violation::Regression { .. } => {
                 regressions.push(v);
             }
+            Violation::AcceptedFailureExpired { .. } => {
+                expired_acceptances.push(v);
+            }
             Violation::TestDisappeared { .. } => {
                 disappeared.push(v);
             }
@@ -49,9 +124,33 @@ pub fn format_report(result: &EvalResult) -> String {
             | Violation::RemovalConflictsWithRename { .. } => {
                 removal_violations.push(v);
             }
+            Violation::StatusTampered { .. } => {
+                tampered.push(v);
+            }
+            Violation::PassingTestIgnored { .. } => {
+                newly_ignored.push(v);
+            }
+            Violation::PendingLimitExceeded { .. } => {
+                pending_limit.push(v);
+            }
             Violation::MissingGatekeeper => {
                 missing_gatekeeper = true;
             }
+            Violation::GatekeeperDoesNotBlock { .. } => {
+                open_gatekeepers.push(v);
+            }
+            Violation::GatekeeperHandshakeMissing => {
+                missing_handshake = true;
+            }
+            Violation::DirtyWorktree { .. } => {
+                dirty_worktree.push(v);
+            }
+            Violation::PluginViolation { .. } => {
+                plugin_violations.push(v);
+            }
+            Violation::DuplicateTestName { .. } => {
+                duplicate_names.push(v);
+            }
         }
     }
 
@@ -69,50 +168,341 @@ pub fn format_report(result: &EvalResult) -> String {
         .filter(|(_, s)| s.state() == TestState::Pending)
         .map(|(name, _)| name)
         .collect();
+    let draft_count = result
+        .updated
+        .tests
+        .values()
+        .filter(|s| s.state() == TestState::Draft)
+        .count();
 
-    let has_any_violation = !result.violations.is_empty();
+    let has_any_violation = result.is_failure(options.deny_warnings);
 
     let mut out = String::new();
 
+    if let Some(since) = &result.history_since {
+        out.push_str(&format!(
+            "tdd-ratchet: bounded check: history checked only after {} (--since); \
+             run without --since for the full check\n",
+            short_commit(since)
+        ));
+    }
+
+    if let Some(partial) = &result.partial {
+        out.push_str(&format!(
+            "tdd-ratchet: partial run: only tests selected by {partial} were checked; \
+             run without filters for the full check\n"
+        ));
+    }
+
+    if result.infrastructure_retries > 0 {
+        out.push_str(&format!(
+            "tdd-ratchet: test run retried {} after infrastructure failures\n",
+            retry_count(result.infrastructure_retries)
+        ));
+    }
+
+    if !dirty_worktree.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_dirty_worktree(&dirty_worktree),
+            &dirty_worktree,
+        )));
+    }
+
+    if !duplicate_names.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_duplicate_names(&duplicate_names),
+            &duplicate_names,
+        )));
+    }
+
     if !tdd_violations.is_empty() {
-        out.push_str(&render_section(format_tdd_violations(&tdd_violations)));
+        out.push_str(&render_section(with_codes(
+            format_tdd_violations(&tdd_violations, &result.locations),
+            &tdd_violations,
+        )));
+    }
+
+    if !promotion_edits.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_promotion_edits(&promotion_edits),
+            &promotion_edits,
+        )));
+    }
+
+    if !promotion_batches.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_promotion_batches(&promotion_batches),
+            &promotion_batches,
+        )));
+    }
+
+    if !mistyped_additions.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_mistyped_additions(&mistyped_additions),
+            &mistyped_additions,
+        )));
     }
 
     if !disappeared.is_empty() {
-        out.push_str(&render_section(format_disappeared_tests(&disappeared)));
+        out.push_str(&render_section(with_codes(
+            format_disappeared_tests(&disappeared),
+            &disappeared,
+        )));
+    }
+
+    if !newly_ignored.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_ignored_passing_tests(&newly_ignored, &result.locations),
+            &newly_ignored,
+        )));
+    }
+
+    if !pending_limit.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_pending_limit(&pending_limit),
+            &pending_limit,
+        )));
     }
 
     if !rename_violations.is_empty() {
-        out.push_str(&render_section(format_rename_violations(
+        out.push_str(&render_section(with_codes(
+            format_rename_violations(&rename_violations),
             &rename_violations,
         )));
     }
 
     if !removal_violations.is_empty() {
-        out.push_str(&render_section(format_removal_violations(
+        out.push_str(&render_section(with_codes(
+            format_removal_violations(&removal_violations),
             &removal_violations,
         )));
     }
 
+    if !tampered.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_tampered_status(&tampered),
+            &tampered,
+        )));
+    }
+
     if missing_gatekeeper {
-        out.push_str(&render_section(format_missing_gatekeeper()));
+        out.push_str(&render_section(with_codes(
+            format_missing_gatekeeper(),
+            &[&Violation::MissingGatekeeper],
+        )));
+    }
+
+    if missing_handshake {
+        out.push_str(&render_section(with_codes(
+            format_missing_handshake(),
+            &[&Violation::GatekeeperHandshakeMissing],
+        )));
+    }
+
+    if !open_gatekeepers.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_open_gatekeepers(&open_gatekeepers, &result.locations),
+            &open_gatekeepers,
+        )));
     }
 
     if !regressions.is_empty() {
-        out.push_str(&render_section(format_regressions(&regressions)));
+        out.push_str(&render_section(with_codes(
+            format_regressions(
+                &regressions,
+                &result.updated.tests,
+                &result.last_green,
+                &result.failure_output,
+                &result.locations,
+                options,
+            ),
+            &regressions,
+        )));
     }
 
-    if !result.warnings.is_empty() {
-        out.push_str(&format_warnings(&result.warnings));
+    if !expired_acceptances.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_expired_acceptances(&expired_acceptances, &result.locations),
+            &expired_acceptances,
+        )));
+    }
+
+    if !plugin_violations.is_empty() {
+        out.push_str(&render_section(with_codes(
+            format_plugin_violations(&plugin_violations),
+            &plugin_violations,
+        )));
+    }
+
+    let reseals: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::IntegrityOverride { .. }))
+        .collect();
+    let trailer_overrides: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::OverrideApplied { .. }))
+        .collect();
+    let rename_warnings: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| {
+            matches!(
+                w,
+                Warning::RenameApplied { .. } | Warning::StaleRename { .. }
+            )
+        })
+        .collect();
+    let ignored_warnings: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::PassingTestIgnored { .. }))
+        .collect();
+    if !ignored_warnings.is_empty() {
+        out.push_str(&render_section(format_ignored_passing_warnings(
+            &ignored_warnings,
+        )));
+    }
+    let flaky: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::FlakyTest { .. }))
+        .collect();
+    if !flaky.is_empty() {
+        out.push_str(&render_section(format_flaky_tests(&flaky)));
+    }
+    let quarantined: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| {
+            matches!(
+                w,
+                Warning::Quarantined { .. } | Warning::QuarantinedTestFailed { .. }
+            )
+        })
+        .collect();
+    if !quarantined.is_empty() {
+        out.push_str(&render_section(format_quarantined_tests(&quarantined)));
+    }
+    let passing_drafts: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::DraftPassing { .. }))
+        .collect();
+    if !passing_drafts.is_empty() {
+        out.push_str(&render_section(format_passing_drafts(&passing_drafts)));
+    }
+    let trivial: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::TrivialTest { .. }))
+        .collect();
+    if !trivial.is_empty() {
+        out.push_str(&render_section(format_trivial_tests(
+            &trivial,
+            &result.locations,
+        )));
+    }
+    let uncovered: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::NoNewCoverage { .. }))
+        .collect();
+    if !uncovered.is_empty() {
+        out.push_str(&render_section(format_uncovered_promotions(&uncovered)));
+    }
+    let drifted: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::ToolchainChanged { .. }))
+        .collect();
+    if !drifted.is_empty() {
+        out.push_str(&render_section(format_toolchain_changes(&drifted)));
+    }
+    let slowdowns: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::SuiteSlower { .. }))
+        .collect();
+    if !slowdowns.is_empty() {
+        out.push_str(&render_section(format_suite_slowdowns(&slowdowns)));
+    }
+    let rule_warnings: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| matches!(w, Warning::RuleWarning { .. }))
+        .collect();
+    if !rule_warnings.is_empty() {
+        out.push_str(&render_section(format_rule_warnings(&rule_warnings)));
+    }
+    let plugin_warnings: Vec<&Warning> = result
+        .warnings
+        .iter()
+        .filter(|w| {
+            matches!(
+                w,
+                Warning::PluginWarning { .. } | Warning::PluginFailed { .. }
+            )
+        })
+        .collect();
+    if !plugin_warnings.is_empty() {
+        out.push_str(&render_section(format_plugin_warnings(&plugin_warnings)));
+    }
+    if !reseals.is_empty() {
+        out.push_str(&render_section(format_integrity_overrides(&reseals)));
+    }
+    if !trailer_overrides.is_empty() {
+        out.push_str(&render_section(format_trailer_overrides(
+            &trailer_overrides,
+        )));
+    }
+    if !rename_warnings.is_empty() {
+        out.push_str(&format_warnings(&rename_warnings));
+    }
+
+    let accepted = active_acceptances(result);
+    if !accepted.is_empty() {
+        let noun = if accepted.len() == 1 {
+            "accepted failure"
+        } else {
+            "accepted failures"
+        };
+        out.push_str(&format!("tdd-ratchet: {} {noun}\n", accepted.len()));
+        for (name, acceptance) in &accepted {
+            out.push_str(&format!("  ~ {name}: {acceptance}\n"));
+        }
+    }
+
+    let slowest = slowest_tests(&result.updated, options.slowest);
+    if !slowest.is_empty() {
+        let noun = if slowest.len() == 1 { "test" } else { "tests" };
+        out.push_str(&format!("tdd-ratchet: {} slowest {noun}\n", slowest.len()));
+        for (name, ms) in &slowest {
+            out.push_str(&format!("  {:>9}  {name}\n", format_ms(*ms)));
+        }
+    }
+
+    if !result.violations.is_empty() {
+        out.push_str(
+            "tdd-ratchet: run `cargo ratchet explain <CODE>` for more on a violation code\n",
+        );
     }
 
     // Success line — only when no violations at all
     if !has_any_violation {
+        let drafts = match draft_count {
+            0 => String::new(),
+            1 => ", 1 draft".to_string(),
+            n => format!(", {n} drafts"),
+        };
         if pending.is_empty() {
-            out.push_str(&format!("tdd-ratchet: ok ({passing_count} passing)\n"));
+            out.push_str(&format!(
+                "tdd-ratchet: ok ({passing_count} passing{drafts})\n"
+            ));
         } else {
             out.push_str(&format!(
-                "tdd-ratchet: ok ({passing_count} passing, {} pending)\n",
+                "tdd-ratchet: ok ({passing_count} passing, {} pending{drafts})\n",
                 pending.len()
             ));
             for name in &pending {
@@ -121,6 +511,24 @@ pub fn format_report(result: &EvalResult) -> String {
         }
     }
 
+    if !result.warnings.is_empty() {
+        let count = result.warnings.len();
+        let noun = if count == 1 { "warning" } else { "warnings" };
+        if options.deny_warnings {
+            out.push_str(&format!(
+                "tdd-ratchet: {count} {noun}, failing the run (--deny warnings)\n"
+            ));
+        } else {
+            out.push_str(&format!(
+                "tdd-ratchet: {count} {noun}, not failing the run (pass --deny warnings to fail on them)\n"
+            ));
+        }
+    }
+
+    if let Some(delta) = &result.since_last_run {
+        out.push_str(&format!("tdd-ratchet: {delta}\n"));
+    }
+
     out
 }
 
@@ -128,6 +536,21 @@ fn detail_line(message: impl Into<String>) -> String {
     format!("    ✗ {}\n", message.into())
 }
 
+/// `test`, prefixed with its `path:line` and followed by its owners when
+/// known.
+fn located(test: &str, locations: &BTreeMap<String, TestLocation>) -> String {
+    match locations.get(test) {
+        Some(location) if !location.owners.is_empty() => {
+            format!(
+                "{location} {test} (owned by {})",
+                location.owners.join(", ")
+            )
+        }
+        Some(location) => format!("{location} {test}"),
+        None => test.to_string(),
+    }
+}
+
 fn warning_line(message: impl Into<String>) -> String {
     format!("    ! {}\n", message.into())
 }
@@ -162,24 +585,38 @@ fn render_section(section: ReportSection) -> String {
     out
 }
 
+/// Append the codes of `violations` to the section title, so each can be
+/// looked up with `cargo ratchet explain`.
+fn with_codes(mut section: ReportSection, violations: &[&Violation]) -> ReportSection {
+    let codes: BTreeSet<&str> = violations.iter().map(|v| v.code()).collect();
+    let codes: Vec<&str> = codes.into_iter().collect();
+    section.title = format!("{} [{}]", section.title, codes.join(", "));
+    section
+}
+
 fn story_14_why(specific_context: &str) -> String {
     format!("This project uses tdd-ratchet to enforce test-first discipline. {specific_context}")
 }
 
-fn format_tdd_violations(violations: &[&Violation]) -> ReportSection {
+fn format_tdd_violations(
+    violations: &[&Violation],
+    locations: &BTreeMap<String, TestLocation>,
+) -> ReportSection {
     let mut details = Vec::new();
 
     for violation in violations {
         match violation {
             Violation::NewTestPassed { test } => {
                 details.push(detail_line(format!(
-                    "New test passed without failing first: {test}"
+                    "New test passed without failing first: {}",
+                    located(test, locations)
                 )));
             }
             Violation::SkippedPending { test, commit } => {
                 let short = &commit[..8.min(commit.len())];
                 details.push(detail_line(format!(
-                    "Test skipped the pending state in git history: {test} (commit {short})"
+                    "Test skipped the pending state in git history: {} (commit {short})",
+                    located(test, locations)
                 )));
             }
             _ => unreachable!(),
@@ -198,6 +635,30 @@ fn format_tdd_violations(violations: &[&Violation]) -> ReportSection {
     }
 }
 
+fn format_promotion_edits(violations: &[&Violation]) -> ReportSection {
+    let details = violations
+        .iter()
+        .map(|violation| match violation {
+            Violation::PromotionEditedTest { test, commit, path } => detail_line(format!(
+                "{test} changed in {path} in the commit that promoted it (commit {})",
+                &commit[..8.min(commit.len())]
+            )),
+            _ => unreachable!(),
+        })
+        .collect();
+
+    ReportSection {
+        title: "test edited while being promoted".into(),
+        why: story_14_why(
+            "A pending test describes the behavior you committed to before implementing it, so changing that test in the commit that makes it pass can quietly weaken the promise.",
+        ),
+        problem: "A commit promoted a test from pending to passing and also changed the body of that same test.".into(),
+        fix: "Keep test changes and the implementation that makes them pass in separate commits: commit the revised test (still `pending`) first, then commit the implementation with `.test-status.json` showing it as `passing`. If history is already wrong, rebase to split the commit.".into(),
+        details,
+        extra: None,
+    }
+}
+
 fn format_disappeared_tests(violations: &[&Violation]) -> ReportSection {
     let count = violations.len();
     let test_word = if count == 1 { "test is" } else { "tests are" };
@@ -287,6 +748,175 @@ fn format_removal_violations(removal_violations: &[&Violation]) -> ReportSection
     }
 }
 
+fn format_promotion_batches(violations: &[&Violation]) -> ReportSection {
+    let mut limit = 0;
+    let details = violations
+        .iter()
+        .map(|violation| {
+            let Violation::TooManyPromotions {
+                commit,
+                tests,
+                limit: max,
+            } = violation
+            else {
+                unreachable!()
+            };
+            limit = *max;
+            detail_line(format!(
+                "commit {} promoted {} tests: {}",
+                short_commit(commit),
+                tests.len(),
+                tests.join(", ")
+            ))
+        })
+        .collect();
+
+    ReportSection {
+        title: if violations.len() == 1 {
+            "commit promotes too many tests".into()
+        } else {
+            "commits promote too many tests".into()
+        },
+        why: story_14_why(
+            "This project implements one behavior per commit, so each commit may promote only a few pending tests to passing (`max-promotions-per-commit` under `[rules]` in `ratchet.toml`).",
+        ),
+        problem: format!(
+            "A commit moved more than {limit} test{} from pending to passing.",
+            if limit == 1 { "" } else { "s" }
+        ),
+        fix: "Split the commit so each one makes at most that many tests pass. For commits already shared, `cargo ratchet rebaseline` stops checking history before a commit.".into(),
+        details,
+        extra: None,
+    }
+}
+
+fn format_mistyped_additions(violations: &[&Violation]) -> ReportSection {
+    let mut types = String::new();
+    let details = violations
+        .iter()
+        .map(|violation| {
+            let Violation::PendingTestCommitType {
+                commit,
+                summary,
+                tests,
+                allowed,
+            } = violation
+            else {
+                unreachable!()
+            };
+            types = allowed
+                .iter()
+                .map(|kind| format!("`{kind}:`"))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            detail_line(format!(
+                "commit {} \"{summary}\" added pending: {}",
+                short_commit(commit),
+                tests.join(", ")
+            ))
+        })
+        .collect();
+
+    ReportSection {
+        title: if violations.len() == 1 {
+            "commit adding pending tests has the wrong type".into()
+        } else {
+            "commits adding pending tests have the wrong type".into()
+        },
+        why: story_14_why(
+            "Commits that add failing tests are marked with a conventional-commit type (`pending-test-commit-types` under `[rules]` in `ratchet.toml`), so the red step of each cycle stands out in history.",
+        ),
+        problem: format!("A commit added pending tests, but its summary does not start with {types}."),
+        fix: "Reword the commit, e.g. with `git rebase -i`, before it is shared. For commits already shared, `cargo ratchet rebaseline` stops checking history before a commit.".into(),
+        details,
+        extra: None,
+    }
+}
+
+fn format_pending_limit(violations: &[&Violation]) -> ReportSection {
+    let mut details = Vec::new();
+    let mut summary = String::new();
+    for violation in violations {
+        let Violation::PendingLimitExceeded {
+            tests,
+            pending,
+            limit,
+        } = violation
+        else {
+            unreachable!()
+        };
+        let are = if *pending == 1 {
+            "test is"
+        } else {
+            "tests are"
+        };
+        summary = format!("{pending} {are} already pending and the limit is {limit}.");
+        details.extend(
+            tests
+                .iter()
+                .map(|test| detail_line(format!("New pending test not recorded: {test}"))),
+        );
+    }
+
+    ReportSection {
+        title: "too many pending tests".into(),
+        why: story_14_why(
+            "This project caps how many tests may be pending at once (`max-pending` under `[rules]` in `ratchet.toml`) to keep TDD batches small.",
+        ),
+        problem: format!(
+            "This run added failing tests beyond the cap. {summary} The new tests were not recorded as pending."
+        ),
+        fix: "Finish existing work first: make some pending tests pass, or remove new failing tests until the count fits. Then run the ratchet again.".into(),
+        details,
+        extra: None,
+    }
+}
+
+fn format_dirty_worktree(violations: &[&Violation]) -> ReportSection {
+    let details = violations
+        .iter()
+        .flat_map(|violation| match violation {
+            Violation::DirtyWorktree { paths } => paths,
+            _ => unreachable!(),
+        })
+        .map(|path| detail_line(format!("Uncommitted changes: {path}")))
+        .collect();
+
+    ReportSection {
+        title: "uncommitted status or test files".into(),
+        why: story_14_why(
+            "History checks only see committed snapshots, so changes to `.test-status.json` or to tests that are not committed yet can hide violations until a later run.",
+        ),
+        problem: "The status file or test files have uncommitted changes, so this run was not checked against the history they will be committed into.".into(),
+        fix: "Commit the changes (or stash them) and run the ratchet again. To make this check fail the run, pass `--strict` or set `dirty-worktree = \"deny\"` under `[rules]` in `ratchet.toml`.".into(),
+        details,
+        extra: None,
+    }
+}
+
+fn format_duplicate_names(violations: &[&Violation]) -> ReportSection {
+    let details = violations
+        .iter()
+        .map(|violation| match violation {
+            Violation::DuplicateTestName { test, count } => {
+                detail_line(format!("Reported {count} times: {test}"))
+            }
+            _ => unreachable!(),
+        })
+        .collect();
+
+    ReportSection {
+        title: "duplicate test names".into(),
+        why: story_14_why(
+            "Each test has one entry in `.test-status.json`, keyed by its name, so every test in a run needs a name of its own.",
+        ),
+        problem: "Several tests in this run were reported under the same name. Names without a binary prefix, such as those from `parser = \"libtest\"`, collide when two test binaries have a test at the same path; the tests then share one status entry, and one test's result masks the other's.".into(),
+        fix: "Switch to full test IDs (`binary$path`): run the tests with the `nextest` runner, or give a `command` runner `parser = \"libtest-json\"` and nextest's libtest-json output. Otherwise rename one of the tests.".into(),
+        details,
+        extra: None,
+    }
+}
+
 fn format_missing_gatekeeper() -> ReportSection {
     ReportSection {
         title: "missing gatekeeper test".into(),
@@ -307,32 +937,480 @@ fn format_missing_gatekeeper() -> ReportSection {
     }
 }
 
-fn format_regressions(violations: &[&Violation]) -> ReportSection {
-    let count = violations.len();
-    let test_word = if count == 1 { "test is" } else { "tests are" };
+fn format_missing_handshake() -> ReportSection {
+    ReportSection {
+        title: "gatekeeper did not answer the handshake".into(),
+        why: story_14_why(
+            "With `[gatekeeper] handshake` on, each run hands the tests a fresh nonce and the gatekeeper must echo it back, so setting `TDD_RATCHET=1` by hand is not enough to bypass the ratchet.",
+        ),
+        problem: format!(
+            "the `{GATEKEEPER_TEST_NAME}` test passed but never echoed this run's nonce, so it does not take part in the handshake."
+        ),
+        fix: format!(
+            "Replace the gatekeeper with the handshake version printed by `cargo ratchet gatekeeper print`, e.g. in `{GATEKEEPER_PATH}`, and commit it."
+        ),
+        details: Vec::new(),
+        extra: None,
+    }
+}
+
+fn format_open_gatekeepers(
+    violations: &[&Violation],
+    locations: &BTreeMap<String, TestLocation>,
+) -> ReportSection {
     let details = violations
         .iter()
         .map(|violation| match violation {
-            Violation::Regression { test } => {
-                detail_line(format!("Previously passing test now fails: {test}"))
-            }
+            Violation::GatekeeperDoesNotBlock { test } => detail_line(format!(
+                "Gatekeeper passed without TDD_RATCHET set: {}",
+                located(test, locations)
+            )),
             _ => unreachable!(),
         })
         .collect();
 
     ReportSection {
-        title: "regression detected".into(),
+        title: "gatekeeper does not block cargo test".into(),
         why: story_14_why(
-            "Once a test is accepted as passing, later failures mean the protected behavior regressed and the suite is no longer keeping that promise.",
+            "It only works when tests are run through the ratchet, and the gatekeeper test is what makes a direct `cargo test` run fail.",
+        ),
+        problem: "tdd-ratchet ran the gatekeeper test on its own without `TDD_RATCHET` set, as a direct `cargo test` would, and it passed, so it no longer prevents bypassing the ratchet.".into(),
+        fix: format!("Restore the gatekeeper so it panics unless `TDD_RATCHET` is set. `cargo ratchet --init` writes the canonical version to `{GATEKEEPER_PATH}`; compare yours with it."),
+        details,
+        extra: None,
+    }
+}
+
+fn format_regressions(
+    violations: &[&Violation],
+    tests: &BTreeMap<String, TestEntry>,
+    last_green: &BTreeMap<String, LastGreen>,
+    failure_output: &BTreeMap<String, String>,
+    locations: &BTreeMap<String, TestLocation>,
+    options: ReportOptions,
+) -> ReportSection {
+    let count = violations.len();
+    let test_word = if count == 1 { "test is" } else { "tests are" };
+    let details = violations
+        .iter()
+        .map(|violation| match violation {
+            Violation::Regression { test } => {
+                let since = tests
+                    .get(test)
+                    .and_then(|entry| entry.promoted.as_ref())
+                    .map(|p| format!(" (green since {} by {})", short_commit(&p.commit), p.author))
+                    .unwrap_or_default();
+                let green = last_green
+                    .get(test)
+                    .map(|green| {
+                        format!(
+                            ", last green at {} ({})",
+                            short_commit(&green.commit),
+                            format_date(green.time)
+                        )
+                    })
+                    .unwrap_or_default();
+                detail_line(format!(
+                    "Previously passing test now fails: {}{since}{green}",
+                    located(test, locations)
+                ))
+            }
+            _ => unreachable!(),
+        })
+        .collect();
+
+    ReportSection {
+        title: "regression detected".into(),
+        why: story_14_why(
+            "Once a test is accepted as passing, later failures mean the protected behavior regressed and the suite is no longer keeping that promise.",
         ),
         problem: format!("{count} tracked passing {test_word} was previously tracked as passing but is now failing in the current run."),
         fix: "Fix the failing test, or if the change is intentional, run `cargo ratchet` and commit the code change together with the updated `.test-status.json`. Always commit `.test-status.json` whenever tdd-ratchet changes it.".into(),
         details,
+        extra: failure_excerpts(violations, failure_output, options),
+    }
+}
+
+fn format_expired_acceptances(
+    violations: &[&Violation],
+    locations: &BTreeMap<String, TestLocation>,
+) -> ReportSection {
+    let details = violations
+        .iter()
+        .map(|violation| match violation {
+            Violation::AcceptedFailureExpired { test, acceptance } => detail_line(format!(
+                "Accepted failure expired: {} — {acceptance}",
+                located(test, locations)
+            )),
+            _ => unreachable!(),
+        })
+        .collect();
+
+    ReportSection {
+        title: "accepted failure expired".into(),
+        why: story_14_why(
+            "A passing test may be allowed to fail for a limited time with `cargo ratchet accept-failure`, so a known breakage can ship without losing track of it.",
+        ),
+        problem: "A test is still failing after its accepted failure expired, so it counts as a regression again.".into(),
+        fix: "Fix the failing test. If more time is really needed, run `cargo ratchet accept-failure <TEST>` again with a new reason and expiry and commit the updated `.test-status.json`.".into(),
+        details,
         extra: None,
     }
 }
 
-fn format_warnings(warnings: &[Warning]) -> String {
+/// Tests whose failures are currently tolerated, i.e. accepted failures
+/// that have not expired.
+fn active_acceptances(result: &EvalResult) -> Vec<(&String, &AcceptedFailure)> {
+    let expired: BTreeSet<&String> = result
+        .violations
+        .iter()
+        .filter_map(|violation| match violation {
+            Violation::AcceptedFailureExpired { test, .. } => Some(test),
+            _ => None,
+        })
+        .collect();
+    result
+        .updated
+        .tests
+        .iter()
+        .filter(|(name, _)| !expired.contains(name))
+        .filter_map(|(name, entry)| Some((name, entry.accepted_failure.as_ref()?)))
+        .collect()
+}
+
+const IGNORE_WHY: &str = "An ignored test no longer runs, so marking a passing test `#[ignore]` silently drops the behavior it protects from the suite.";
+const IGNORE_FIX: &str = "Remove the `#[ignore]` attribute, or if ignoring the test is intentional, run `cargo ratchet allow-ignore <TEST>` and commit the updated `.test-status.json` together with the change.";
+
+fn format_ignored_passing_tests(
+    violations: &[&Violation],
+    locations: &BTreeMap<String, TestLocation>,
+) -> ReportSection {
+    let details = violations
+        .iter()
+        .map(|violation| match violation {
+            Violation::PassingTestIgnored { test } => detail_line(format!(
+                "Previously passing test now ignored: {}",
+                located(test, locations)
+            )),
+            _ => unreachable!(),
+        })
+        .collect();
+
+    ReportSection {
+        title: "passing test ignored".into(),
+        why: story_14_why(IGNORE_WHY),
+        problem: "A test tracked as passing was reported as ignored in the current run.".into(),
+        fix: IGNORE_FIX.into(),
+        details,
+        extra: None,
+    }
+}
+
+fn format_ignored_passing_warnings(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: "passing test ignored (warning)".into(),
+        why: story_14_why(IGNORE_WHY),
+        problem: "A test tracked as passing was reported as ignored in the current run.".into(),
+        fix: IGNORE_FIX.into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_flaky_tests(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "flaky test".into()
+        } else {
+            "flaky tests".into()
+        },
+        why: story_14_why(
+            "A test that only passes on retry cannot be trusted to catch a regression, so repeated flakes are surfaced instead of being absorbed by retries.",
+        ),
+        problem: "A test passed only after a retry, and has done so more often than `flake-threshold` allows.".into(),
+        fix: "Make the test deterministic. The flake count is kept in `.test-status.json`; raise `flake-threshold` under `[rules]` in `ratchet.toml` to tolerate more.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_quarantined_tests(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "quarantined test".into()
+        } else {
+            "quarantined tests".into()
+        },
+        why: story_14_why(
+            "A test whose outcome flips without any source change says nothing about the code, so after `quarantine-after` flips in a row it is quarantined as flaky and its failures no longer fail the run.",
+        ),
+        problem: "A test kept flipping between passing and failing on the same commit, or a quarantined test failed.".into(),
+        fix: "Make the test deterministic, then run `cargo ratchet unquarantine <TEST>` and commit `.test-status.json` to enforce it strictly again.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_passing_drafts(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "draft test passes".into()
+        } else {
+            "draft tests pass".into()
+        },
+        why: story_14_why(
+            "A draft is a sketched test whose failure proves nothing yet. `promote-drafts` only moves a draft the last run saw failing to pending, and it must be committed as pending before it may pass.",
+        ),
+        problem: "A test in the draft state passed, so it was left as a draft.".into(),
+        fix: "Make sure the test asserts what it is named after and fails without the implementation, then run `cargo ratchet promote-drafts <TEST>` and commit `.test-status.json`.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_trivial_tests(
+    warnings: &[&Warning],
+    locations: &BTreeMap<String, TestLocation>,
+) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "promoted test asserts nothing".into()
+        } else {
+            "promoted tests assert nothing".into()
+        },
+        why: story_14_why(
+            "A test only protects behavior if it can fail, so a promoted test with nothing to check is flagged even though it failed once.",
+        ),
+        problem: "A test was promoted to passing, but its body has no assertion, panic, `unwrap`, or `?`.".into(),
+        fix: "Assert on the behavior the test is named after. Turn this check off with `promoted-tests-must-assert = false` under `[rules]` in `ratchet.toml`.".into(),
+        details: warnings
+            .iter()
+            .map(|w| match w {
+                Warning::TrivialTest { test } => warning_line(format!(
+                    "Promoted test asserts nothing: {}",
+                    located(test, locations)
+                )),
+                other => format_warning(other),
+            })
+            .collect(),
+        extra: None,
+    }
+}
+
+fn format_uncovered_promotions(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "promoted test covers no new lines".into()
+        } else {
+            "promoted tests cover no new lines".into()
+        },
+        why: story_14_why(
+            "A test that exercises no code other tests do not already exercise may pass whatever the code does, even though it failed once.",
+        ),
+        problem: "A test was promoted to passing, but measured on its own it covers no line that the tests in the coverage record do not already cover.".into(),
+        fix: "Check that the test exercises the behavior it is named after. Line numbers in the coverage record drift as code changes, so this is a heuristic; turn it off by removing `enabled = true` from `[coverage]` in `ratchet.toml`.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_toolchain_changes(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "regression under a different toolchain".into()
+        } else {
+            "regressions under a different toolchain".into()
+        },
+        why: story_14_why(
+            "A test that was promoted under one toolchain and fails under another may have been broken by the new compiler or test runner rather than by the code.",
+        ),
+        problem: "A regressed test was promoted under a different rustc, nextest, or target than this run's.".into(),
+        fix: "Check whether the failure reproduces with the toolchain the test was promoted under before looking for the change that broke it.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_suite_slowdowns(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: "test suite got slower".into(),
+        why: story_14_why(
+            "Execution times are ratcheted too, so a suite that creeps slower run by run is noticed before it is too slow to run on every change.",
+        ),
+        problem: "The recorded execution times add up to noticeably more than they did on the last run.".into(),
+        fix: "Look for the newly slow tests in the slowest-tests list. Raise `max-growth` under `[timing]` in `ratchet.toml` to tolerate more.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+/// The `count` tests with the longest recorded execution times, slowest
+/// first. Pure function — no IO.
+pub fn slowest_tests(status: &StatusFile, count: usize) -> Vec<(&str, u64)> {
+    let mut timed: Vec<(&str, u64)> = status
+        .tests
+        .iter()
+        .filter_map(|(name, entry)| Some((name.as_str(), entry.exec_time_ms?)))
+        .collect();
+    timed.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    timed.truncate(count);
+    timed
+}
+
+/// Milliseconds as seconds, e.g. `1.250s`.
+fn format_ms(ms: u64) -> String {
+    format!("{}.{:03}s", ms / 1000, ms % 1000)
+}
+
+fn format_rule_warnings(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "rule violation (warning)".into()
+        } else {
+            "rule violations (warnings)".into()
+        },
+        why: story_14_why(
+            "Each check is a named rule, and this project has lowered some of them to `warn` so they are reported without failing the run.",
+        ),
+        problem: "A check set to `warn` found a problem.".into(),
+        fix: "Fix it as you would the error. The severity is set under `[rules]` in `ratchet.toml`, or for one run with `--deny <rule>`/`--allow <rule>`. The test's status is not updated while the problem remains.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_plugin_violations(violations: &[&Violation]) -> ReportSection {
+    let details = violations
+        .iter()
+        .map(|violation| match violation {
+            Violation::PluginViolation {
+                plugin,
+                subject,
+                message,
+            } => detail_line(format!("[{plugin}] {subject}: {message}")),
+            _ => unreachable!(),
+        })
+        .collect();
+
+    ReportSection {
+        title: "plugin rule violation".into(),
+        why: story_14_why(
+            "This project adds its own checks with `ratchet-rule-*` plugins listed under `[plugins]` in `ratchet.toml`.",
+        ),
+        problem: "A plugin rule found a problem with this run.".into(),
+        fix: "Fix what the plugin reports; each line names the plugin that found it. See the plugin's own documentation for what its rule requires.".into(),
+        details,
+        extra: None,
+    }
+}
+
+fn format_plugin_warnings(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "plugin warning".into()
+        } else {
+            "plugin warnings".into()
+        },
+        why: story_14_why(
+            "This project adds its own checks with `ratchet-rule-*` plugins listed under `[plugins]` in `ratchet.toml`.",
+        ),
+        problem: "A plugin reported a warning, or could not be run and its checks were skipped.".into(),
+        fix: "Address the plugin's warning. If a plugin failed, check that `ratchet-rule-<name>` is on `PATH` and answers with JSON on stdout; a failed plugin never fails the run unless warnings are denied.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+/// The captured output of each regression that has some, truncated unless
+/// `options.full_output` is set.
+fn failure_excerpts(
+    violations: &[&Violation],
+    failure_output: &BTreeMap<String, String>,
+    options: ReportOptions,
+) -> Option<String> {
+    let mut out = String::new();
+    for violation in violations {
+        let Violation::Regression { test } = violation else {
+            continue;
+        };
+        let Some(output) = failure_output.get(test) else {
+            continue;
+        };
+        let lines: Vec<&str> = output.trim_end().lines().collect();
+        let shown = if options.full_output {
+            lines.len()
+        } else {
+            lines.len().min(FAILURE_EXCERPT_LINES)
+        };
+        out.push_str(&format!("  Output of {test}:\n"));
+        for line in &lines[..shown] {
+            out.push_str(&format!("    │ {line}\n"));
+        }
+        if shown < lines.len() {
+            out.push_str(&format!(
+                "    │ … {} more lines (run with --full-output to see everything)\n",
+                lines.len() - shown
+            ));
+        }
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+fn format_tampered_status(violations: &[&Violation]) -> ReportSection {
+    let details = violations
+        .iter()
+        .map(|violation| match violation {
+            Violation::StatusTampered { commit, problem } => detail_line(format!(
+                "{problem} (commit {})",
+                &commit[..8.min(commit.len())]
+            )),
+            _ => unreachable!(),
+        })
+        .collect();
+
+    ReportSection {
+        title: "status file integrity check failed".into(),
+        why: story_14_why(
+            "Every `.test-status.json` the ratchet writes carries a fingerprint chained to the previous committed status, so hand edits (for example promoting a test to `passing` without running it) are visible in history.",
+        ),
+        problem: "A committed `.test-status.json` does not carry a valid fingerprint, so it was edited or replaced without running tdd-ratchet.".into(),
+        fix: "If the edit was a mistake, rewrite history so that commit carries the ratchet-written status file. If the manual edit was legitimate, declare it: run `cargo ratchet reseal --reason \"<why>\"` and commit the resealed `.test-status.json` without running `cargo ratchet` in between. A reseal acknowledges all earlier history and is shown as a warning.".into(),
+        details,
+        extra: None,
+    }
+}
+
+fn format_integrity_overrides(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: "status file resealed after manual edit".into(),
+        why: story_14_why(
+            "Manual edits to `.test-status.json` bypass the ratchet, so they must be declared with a reason and stay visible to reviewers.",
+        ),
+        problem: "The newest committed `.test-status.json` was resealed with `cargo ratchet reseal`; integrity checks before it are waived.".into(),
+        fix: "Review the reason below. Nothing else to do — the next ratchet run seals normally.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_trailer_overrides(warnings: &[&Warning]) -> ReportSection {
+    ReportSection {
+        title: if warnings.len() == 1 {
+            "violation overridden by commit trailer".into()
+        } else {
+            "violations overridden by commit trailers".into()
+        },
+        why: story_14_why(
+            "Emergency overrides are allowed, but every one is recorded in `.ratchet-audit.jsonl` so skipped rules stay visible.",
+        ),
+        problem: "A `Ratchet-Override` trailer suppressed a violation for the named test.".into(),
+        fix: "Nothing to do now. Fix the underlying problem soon; the trailer only covers the commit that carries it.".into(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
+        extra: None,
+    }
+}
+
+fn format_warnings(warnings: &[&Warning]) -> String {
     render_section(ReportSection {
         title: if warnings.len() == 1 {
             "rename warning".into()
@@ -348,7 +1426,7 @@ fn format_warnings(warnings: &[Warning]) -> String {
             "Temporary rename mappings no longer need to stay in `.test-status.json`.".into()
         },
         fix: "Remove the `renames` entry in your next commit once the rename bridge is no longer needed.".into(),
-        details: warnings.iter().map(format_warning).collect(),
+        details: warnings.iter().map(|w| format_warning(w)).collect(),
         extra: None,
     })
 }
@@ -361,5 +1439,448 @@ fn format_warning(warning: &Warning) -> String {
         Warning::StaleRename { new_name, old_name } => warning_line(format!(
             "{new_name} -> {old_name} is stale; the temporary `renames` entry can be removed"
         )),
+        Warning::OverrideApplied {
+            commit,
+            test,
+            rule,
+            reason,
+        } => warning_line(format!(
+            "{rule} for {test} overridden in commit {}: {reason}",
+            &commit[..8.min(commit.len())]
+        )),
+        Warning::IntegrityOverride { commit, reason } => warning_line(format!(
+            "commit {} resealed: {reason}",
+            &commit[..8.min(commit.len())]
+        )),
+        Warning::PassingTestIgnored { test } => {
+            warning_line(format!("Previously passing test now ignored: {test}"))
+        }
+        Warning::FlakyTest { test, flakes } => {
+            warning_line(format!("{test} passed on retry ({flakes} flaky runs)"))
+        }
+        Warning::Quarantined { test, flips } => warning_line(format!(
+            "{test} quarantined as flaky after {flips} flips on the same commit"
+        )),
+        Warning::QuarantinedTestFailed { test } => {
+            warning_line(format!("Quarantined test failed (tolerated): {test}"))
+        }
+        Warning::DraftPassing { test } => {
+            warning_line(format!("Draft test passes, still a draft: {test}"))
+        }
+        Warning::TrivialTest { test } => {
+            warning_line(format!("Promoted test asserts nothing: {test}"))
+        }
+        Warning::NoNewCoverage { test, covered: 0 } => {
+            warning_line(format!("Promoted test covers no lines at all: {test}"))
+        }
+        Warning::NoNewCoverage { test, covered } => warning_line(format!(
+            "Promoted test covers no new lines: {test} ({covered} lines, all covered by other tests)"
+        )),
+        Warning::ToolchainChanged {
+            test,
+            promoted,
+            current,
+        } => warning_line(format!(
+            "{test} regressed under {current}; it was promoted under {promoted}"
+        )),
+        Warning::SuiteSlower {
+            previous_ms,
+            current_ms,
+            max_growth,
+        } => warning_line(format!(
+            "suite took {} against {} on the last run (more than {max_growth}% slower)",
+            format_ms(*current_ms),
+            format_ms(*previous_ms)
+        )),
+        Warning::RuleWarning { violation } => {
+            let (kind, test, message) = violation_summary(violation);
+            warning_line(format!("[{}] {kind}: {test}: {message}", violation.code()))
+        }
+        Warning::PluginWarning {
+            plugin,
+            subject,
+            message,
+        } => warning_line(format!("[{plugin}] {subject}: {message}")),
+        Warning::PluginFailed { plugin, reason } => warning_line(format!(
+            "plugin {plugin} failed, its checks were skipped: {reason}"
+        )),
+    }
+}
+
+/// Format a Markdown summary of a ratchet evaluation.
+///
+/// `previous` is the committed status the run started from; it is used to
+/// show how the passing/pending counts changed. The violations table and the
+/// pending list are wrapped in `<details>` blocks so long lists stay collapsed
+/// in PR comments.
+pub fn format_markdown_report(result: &EvalResult, previous: &TrackedStatus) -> String {
+    markdown_report(result, previous, "## tdd-ratchet")
+}
+
+/// One root's result in a multi-root run.
+#[derive(Debug, Clone)]
+pub struct RootResult {
+    /// The root's directory, as configured.
+    pub root: String,
+    /// Committed status the run started from.
+    pub previous: TrackedStatus,
+    pub result: EvalResult,
+}
+
+/// Text report for a multi-root run: each root's report under its own
+/// heading, then one summary line.
+pub fn format_roots_report(roots: &[RootResult]) -> String {
+    format_roots_report_with(roots, ReportOptions::default())
+}
+
+/// [`format_roots_report`] with explicit report options.
+pub fn format_roots_report_with(roots: &[RootResult], options: ReportOptions) -> String {
+    let mut out = String::new();
+    for root in roots {
+        out.push_str(&format!("═══ {} ═══\n", root.root));
+        out.push_str(&format_report_with(&root.result, options));
+        out.push('\n');
+    }
+    out.push_str(&roots_summary(roots, options));
+    out.push('\n');
+    out
+}
+
+/// Markdown report for a multi-root run: a summary heading, then each
+/// root's report one heading level down.
+pub fn format_markdown_roots_report(roots: &[RootResult]) -> String {
+    let failing = roots
+        .iter()
+        .filter(|r| !r.result.violations.is_empty())
+        .count();
+    let mut out = if failing == 0 {
+        format!("## tdd-ratchet: ✅ ok ({})\n\n", root_count(roots.len()))
+    } else {
+        format!(
+            "## tdd-ratchet: ❌ {failing} of {} with violations\n\n",
+            root_count(roots.len())
+        )
+    };
+    for root in roots {
+        out.push_str(&markdown_report(
+            &root.result,
+            &root.previous,
+            &format!("### {}", markdown_code(&root.root)),
+        ));
+        out.push('\n');
+    }
+    out
+}
+
+fn roots_summary(roots: &[RootResult], options: ReportOptions) -> String {
+    let failing: Vec<&str> = roots
+        .iter()
+        .filter(|r| r.result.is_failure(options.deny_warnings))
+        .map(|r| r.root.as_str())
+        .collect();
+    if failing.is_empty() {
+        format!("tdd-ratchet: ok ({})", root_count(roots.len()))
+    } else {
+        format!(
+            "tdd-ratchet: {} of {} with violations ({})",
+            failing.len(),
+            root_count(roots.len()),
+            failing.join(", ")
+        )
+    }
+}
+
+fn root_count(count: usize) -> String {
+    format!("{count} root{}", if count == 1 { "" } else { "s" })
+}
+
+fn markdown_report(result: &EvalResult, previous: &TrackedStatus, heading: &str) -> String {
+    let mut out = String::new();
+
+    if result.violations.is_empty() {
+        out.push_str(&format!("{heading}: ✅ ok\n\n"));
+    } else {
+        out.push_str(&format!(
+            "{heading}: ❌ {} violation{}\n\n",
+            result.violations.len(),
+            if result.violations.len() == 1 {
+                ""
+            } else {
+                "s"
+            }
+        ));
+    }
+    if let Some(since) = &result.history_since {
+        out.push_str(&format!(
+            "> Bounded check: history checked only after `{}` (`--since`).\n\n",
+            short_commit(since)
+        ));
+    }
+    if let Some(partial) = &result.partial {
+        out.push_str(&format!(
+            "> Partial run: only tests selected by `{partial}` were checked.\n\n"
+        ));
+    }
+    if result.infrastructure_retries > 0 {
+        out.push_str(&format!(
+            "> Test run retried {} after infrastructure failures.\n\n",
+            retry_count(result.infrastructure_retries)
+        ));
+    }
+
+    let (passing_before, pending_before) = state_counts(&previous.tests);
+    let (passing_after, pending_after) = state_counts(&result.updated.tests);
+    out.push_str("| | Passing | Pending |\n|---|---:|---:|\n");
+    out.push_str(&format!(
+        "| Before | {passing_before} | {pending_before} |\n"
+    ));
+    out.push_str(&format!("| After | {passing_after} | {pending_after} |\n"));
+    out.push_str(&format!(
+        "| Δ | {} | {} |\n\n",
+        signed_delta(passing_before, passing_after),
+        signed_delta(pending_before, pending_after)
+    ));
+
+    if !result.violations.is_empty() {
+        out.push_str(&format!(
+            "<details open><summary>Violations ({})</summary>\n\n",
+            result.violations.len()
+        ));
+        out.push_str("| Code | Rule | Test | Detail |\n|---|---|---|---|\n");
+        for violation in &result.violations {
+            let (rule, subject, detail) = violation_summary(violation);
+            out.push_str(&format!(
+                "| {} | {rule} | {} | {} |\n",
+                violation.code(),
+                markdown_code(&subject),
+                escape_table_cell(&detail)
+            ));
+        }
+        out.push_str("\n</details>\n\n");
+    }
+
+    let accepted = active_acceptances(result);
+    if !accepted.is_empty() {
+        out.push_str(&format!(
+            "<details open><summary>Accepted failures ({})</summary>\n\n",
+            accepted.len()
+        ));
+        for (name, acceptance) in accepted {
+            out.push_str(&format!("- {}: {acceptance}\n", markdown_code(name)));
+        }
+        out.push_str("\n</details>\n\n");
+    }
+
+    let pending: Vec<&String> = result
+        .updated
+        .tests
+        .iter()
+        .filter(|(_, entry)| entry.state() == TestState::Pending)
+        .map(|(name, _)| name)
+        .collect();
+    if !pending.is_empty() {
+        out.push_str(&format!(
+            "<details><summary>Pending tests ({})</summary>\n\n",
+            pending.len()
+        ));
+        for name in pending {
+            out.push_str(&format!("- {}\n", markdown_code(name)));
+        }
+        out.push_str("\n</details>\n");
+    }
+
+    out
+}
+
+fn retry_count(retries: u32) -> String {
+    format!("{retries} time{}", if retries == 1 { "" } else { "s" })
+}
+
+fn state_counts(tests: &BTreeMap<String, TestEntry>) -> (usize, usize) {
+    let passing = tests
+        .values()
+        .filter(|e| e.state() == TestState::Passing)
+        .count();
+    (passing, tests.len() - passing)
+}
+
+fn signed_delta(before: usize, after: usize) -> String {
+    match after.cmp(&before) {
+        std::cmp::Ordering::Greater => format!("+{}", after - before),
+        std::cmp::Ordering::Less => format!("-{}", before - after),
+        std::cmp::Ordering::Equal => "0".into(),
+    }
+}
+
+fn markdown_code(text: &str) -> String {
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("`{}`", escape_table_cell(text))
+    }
+}
+
+fn escape_table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// One-line summary of a violation: rule name, subject, and detail.
+pub(crate) fn violation_summary(violation: &Violation) -> (&'static str, String, String) {
+    match violation {
+        Violation::NewTestPassed { test } => (
+            "NewTestPassed",
+            test.clone(),
+            "new test passed without failing first".into(),
+        ),
+        Violation::Regression { test } => (
+            "Regression",
+            test.clone(),
+            "previously passing test now fails".into(),
+        ),
+        Violation::TestDisappeared { test } => (
+            "TestDisappeared",
+            test.clone(),
+            "tracked test missing from the run".into(),
+        ),
+        Violation::SkippedPending { test, commit } => (
+            "SkippedPending",
+            test.clone(),
+            format!(
+                "skipped the pending state in commit {}",
+                &commit[..8.min(commit.len())]
+            ),
+        ),
+        Violation::MissingGatekeeper => (
+            "MissingGatekeeper",
+            GATEKEEPER_TEST_NAME.into(),
+            "no gatekeeper test found in the run".into(),
+        ),
+        Violation::RenameOldNameMissing { new_name, old_name } => (
+            "InvalidRename",
+            new_name.clone(),
+            format!("old name {old_name} is not present in committed status"),
+        ),
+        Violation::RenameNewNameMissing { new_name, old_name } => (
+            "InvalidRename",
+            new_name.clone(),
+            format!("renamed from {old_name} but not found in the current run"),
+        ),
+        Violation::RenameOldNameStillPresent { new_name, old_name } => (
+            "InvalidRename",
+            new_name.clone(),
+            format!("old name {old_name} still appears in the current run"),
+        ),
+        Violation::RenameNewNameAlreadyTracked { new_name, old_name } => (
+            "InvalidRename",
+            new_name.clone(),
+            format!("already tracked independently (rename from {old_name})"),
+        ),
+        Violation::RenameOldNameMappedMultipleTimes { old_name } => (
+            "InvalidRename",
+            old_name.clone(),
+            "multiple rename entries point at this old name".into(),
+        ),
+        Violation::RemovalMissingTrackedTest { test } => (
+            "InvalidRemoval",
+            test.clone(),
+            "removal target is not present in committed status".into(),
+        ),
+        Violation::RemovalTestStillPresent { test } => (
+            "InvalidRemoval",
+            test.clone(),
+            "removal target still appears in the current run".into(),
+        ),
+        Violation::RemovalConflictsWithRename { test } => (
+            "InvalidRemoval",
+            test.clone(),
+            "removal target also participates in a rename".into(),
+        ),
+        Violation::PromotionEditedTest { test, commit, path } => (
+            "PromotionEditedTest",
+            test.clone(),
+            format!(
+                "test body in {path} changed in promoting commit {}",
+                &commit[..8.min(commit.len())]
+            ),
+        ),
+        Violation::StatusTampered { commit, problem } => (
+            "StatusTampered",
+            ".test-status.json".into(),
+            format!("{problem} in commit {}", &commit[..8.min(commit.len())]),
+        ),
+        Violation::PassingTestIgnored { test } => (
+            "PassingTestIgnored",
+            test.clone(),
+            "passing test is now ignored".into(),
+        ),
+        Violation::TooManyPromotions {
+            commit,
+            tests,
+            limit,
+        } => (
+            "TooManyPromotions",
+            tests.join(", "),
+            format!(
+                "{} promoted in commit {}, limit is {limit}",
+                tests.len(),
+                short_commit(commit)
+            ),
+        ),
+        Violation::PendingTestCommitType {
+            commit,
+            summary,
+            tests,
+            ..
+        } => (
+            "PendingTestCommitType",
+            tests.join(", "),
+            format!(
+                "added as pending in commit {} \"{summary}\" without an allowed commit type",
+                short_commit(commit)
+            ),
+        ),
+        Violation::PendingLimitExceeded {
+            tests,
+            pending,
+            limit,
+        } => (
+            "PendingLimitExceeded",
+            tests.join(", "),
+            format!("{pending} already pending, limit is {limit}"),
+        ),
+        Violation::GatekeeperHandshakeMissing => (
+            "GatekeeperHandshakeMissing",
+            GATEKEEPER_TEST_NAME.into(),
+            "gatekeeper did not echo the run's nonce".into(),
+        ),
+        Violation::GatekeeperDoesNotBlock { test } => (
+            "GatekeeperDoesNotBlock",
+            test.clone(),
+            "gatekeeper passed without TDD_RATCHET set".into(),
+        ),
+        Violation::AcceptedFailureExpired { test, acceptance } => (
+            "AcceptedFailureExpired",
+            test.clone(),
+            format!("accepted failure expired: {acceptance}"),
+        ),
+        Violation::DirtyWorktree { paths } => (
+            "DirtyWorktree",
+            paths.join(", "),
+            "uncommitted changes".into(),
+        ),
+        Violation::PluginViolation {
+            plugin,
+            subject,
+            message,
+        } => (
+            "PluginViolation",
+            subject.clone(),
+            format!("[{plugin}] {message}"),
+        ),
+        Violation::DuplicateTestName { test, count } => (
+            "DuplicateTestName",
+            test.clone(),
+            format!("reported {count} times in one run"),
+        ),
     }
 }
@@ -0,0 +1,288 @@
+// Long-form descriptions of violation codes, printed by
+// `cargo ratchet explain <code>` in the manner of `rustc --explain`.
+//
+// Codes are stable: a code is never reused for a different violation, so
+// they can be searched for, linked to, and matched on in CI scripts.
+
+/// The description of one violation code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Explanation {
+    /// The code, e.g. `RATCHET001`.
+    pub code: &'static str,
+    /// The violation's name, as shown in reports and the audit trail.
+    pub name: &'static str,
+    /// What the violation means.
+    pub summary: &'static str,
+    /// Why the ratchet treats it as a problem.
+    pub rationale: &'static str,
+    /// Steps that resolve it, in order.
+    pub remediation: &'static [&'static str],
+}
+
+/// Every violation code, in code order.
+pub const EXPLANATIONS: [Explanation; 25] = [
+    Explanation {
+        code: "RATCHET001",
+        name: "NewTestPassed",
+        summary: "A test that is not tracked in `.test-status.json` passed on its first run.",
+        rationale: "A test that has never failed has not been shown to detect anything: it may pass because the behavior works, or because it checks nothing. Requiring each new test to be seen failing first is the core of the ratchet.",
+        remediation: &[
+            "Revert or stash the implementation so the test fails, run `cargo ratchet` to record it as pending, and commit.",
+            "Restore the implementation, run `cargo ratchet` again to promote the test, and commit.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET002",
+        name: "Regression",
+        summary: "A test recorded as passing failed in this run.",
+        rationale: "Once a test passes it stays passing; the ratchet never moves a test back to pending, so behavior that worked cannot silently stop working.",
+        remediation: &[
+            "Fix the code, or the test if it is wrong, until the test passes again.",
+            "If the behavior was removed on purpose, delete the test and declare it under `removals` in `.test-status.json`.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET003",
+        name: "TestDisappeared",
+        summary: "A tracked test was not reported by the test run at all.",
+        rationale: "Deleting or renaming a test would otherwise be an easy way to get rid of a failing one, so every tracked test must keep running until its removal or rename is declared.",
+        remediation: &[
+            "If the test was renamed, add `\"new_name\": \"old_name\"` under `renames` in `.test-status.json`.",
+            "If the test was deleted on purpose, add its name under `removals` in `.test-status.json`.",
+            "Otherwise make sure the runner still builds and runs it, e.g. that its file or feature is still included.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET004",
+        name: "SkippedPending",
+        summary: "Git history shows a test committed as passing without ever being committed as pending.",
+        rationale: "The pending state has to be committed so that history proves the test failed before the implementation landed; a test that appears as passing skipped that proof.",
+        remediation: &[
+            "If the commit is not shared yet, split it: commit the test as pending first, then the implementation.",
+            "For history that cannot be rewritten, `cargo ratchet rebaseline` stops checking commits before the new baseline.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET005",
+        name: "MissingGatekeeper",
+        summary: "The `tdd_ratchet_gatekeeper` test was not in the run.",
+        rationale: "The gatekeeper test fails under a plain `cargo test`, so tests are always run through the ratchet. Without it the ratchet cannot tell that it is looking at the project's own test suite.",
+        remediation: &[
+            "Add a `#[test] fn tdd_ratchet_gatekeeper()` that panics unless the `TDD_RATCHET` environment variable is set; the missing-gatekeeper report prints one to copy.",
+            "Check that the configured runner includes the file that defines it.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET006",
+        name: "RenameOldNameMissing",
+        summary: "A declared rename refers to an old name that is not tracked.",
+        rationale: "A rename carries a test's committed state over to its new name, so its old name must be a tracked test.",
+        remediation: &[
+            "Correct the old name in `renames` in `.test-status.json` to the name that is tracked.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET007",
+        name: "RenameNewNameMissing",
+        summary: "A declared rename refers to a new name that is not in the run.",
+        rationale: "The renamed test must run under its new name, or the rename would hide a disappeared test.",
+        remediation: &[
+            "Correct the new name in `renames` in `.test-status.json` to the test's actual name.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET008",
+        name: "RenameOldNameStillPresent",
+        summary: "A test was declared renamed, but a test with its old name still runs.",
+        rationale: "A rename moves one test's state to a new name; if the old test still exists, the rename would duplicate its state instead.",
+        remediation: &[
+            "Finish the rename in the code, or remove the entry from `renames` if the test was copied rather than renamed.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET009",
+        name: "RenameNewNameAlreadyTracked",
+        summary: "A rename targets a name that is already tracked as another test.",
+        rationale: "Applying the rename would overwrite the state of an existing test.",
+        remediation: &[
+            "Pick a new name that is not tracked yet, or remove the conflicting entry from `renames`.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET010",
+        name: "RenameOldNameMappedMultipleTimes",
+        summary: "Several renames in `.test-status.json` share the same old name.",
+        rationale: "One test's state can only move to one new name.",
+        remediation: &[
+            "Keep one entry for the old name; new tests split from it start as new tests.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET011",
+        name: "RemovalMissingTrackedTest",
+        summary: "A declared removal names a test that is not tracked.",
+        rationale: "Removals only exist to retire tracked tests; an unknown name is most likely a typo that leaves the real test unaccounted for.",
+        remediation: &["Correct the name under `removals`, or delete the entry."],
+    },
+    Explanation {
+        code: "RATCHET012",
+        name: "RemovalTestStillPresent",
+        summary: "A test was declared removed, but it still runs.",
+        rationale: "Removing a test from tracking while it still runs would let it fail without being reported.",
+        remediation: &[
+            "Delete the test from the code, or drop it from `removals` if it should stay.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET013",
+        name: "RemovalConflictsWithRename",
+        summary: "A test is both declared removed and part of a rename.",
+        rationale: "A test cannot be retired and carried over to a new name at the same time.",
+        remediation: &["Keep either the removal or the rename, not both."],
+    },
+    Explanation {
+        code: "RATCHET014",
+        name: "PromotionEditedTest",
+        summary: "A commit promoted a test to passing and also changed that test's body.",
+        rationale: "With `promotion-must-not-edit-test` enabled, a test has to pass as it was written while pending. Editing it in the promoting commit could weaken it until it passes.",
+        remediation: &[
+            "Split the commit: change the test in one commit (it stays pending), then make it pass in the next.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET015",
+        name: "StatusTampered",
+        summary: "A committed `.test-status.json` does not match its integrity seal.",
+        rationale: "The status file records which tests failed first. Editing it by hand could mark a test as passing that was never seen failing, so every status the ratchet writes is sealed.",
+        remediation: &[
+            "Revert the manual edit, or let `cargo ratchet` rewrite the status file.",
+            "If the edit was intended, run `cargo ratchet reseal --reason <TEXT>` and commit the result.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET016",
+        name: "PassingTestIgnored",
+        summary: "A test recorded as passing is now marked `#[ignore]`.",
+        rationale: "Ignoring a passing test stops it from protecting anything while it stays green in the status file.",
+        remediation: &[
+            "Remove the `#[ignore]`, or run `cargo ratchet allow-ignore <TEST>` and commit `.test-status.json` if ignoring it is intended.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET017",
+        name: "TooManyPromotions",
+        summary: "A commit promoted more tests to passing than `max-promotions-per-commit` allows.",
+        rationale: "Promoting tests a few at a time keeps each commit to one behavior, which keeps history reviewable and bisectable.",
+        remediation: &[
+            "Split the commit so each one promotes at most the allowed number of tests.",
+            "For commits already shared, `cargo ratchet rebaseline` stops checking history before a commit.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET018",
+        name: "PendingLimitExceeded",
+        summary: "New pending tests would take the number of pending tests over `max-pending`.",
+        rationale: "A long list of pending tests is unfinished work; the limit keeps the team finishing behaviors before starting new ones.",
+        remediation: &[
+            "Make some pending tests pass first, or delete the new ones until there is room.",
+            "Raise `max-pending` under `[rules]` in `ratchet.toml` if the limit is too low.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET019",
+        name: "AcceptedFailureExpired",
+        summary: "A test is still failing after the accepted failure recorded for it expired.",
+        rationale: "Accepting a failure lets a known breakage ship, but only for a limited time; once the acceptance expires the failure is a regression again.",
+        remediation: &[
+            "Fix the failing test.",
+            "If more time is needed, run `cargo ratchet accept-failure <TEST> --reason <TEXT> --until <DATE>` again and commit `.test-status.json`.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET020",
+        name: "GatekeeperDoesNotBlock",
+        summary: "The gatekeeper test passed when run on its own without `TDD_RATCHET` set.",
+        rationale: "The gatekeeper is what makes a direct `cargo test` fail; one that always passes silently lets anyone bypass the ratchet.",
+        remediation: &[
+            "Restore the gatekeeper so it panics unless `TDD_RATCHET` is set; `cargo ratchet --init` writes the canonical version to `tests/gatekeeper.rs`.",
+            "Commit the fixed gatekeeper and run `cargo ratchet` again.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET021",
+        name: "GatekeeperHandshakeMissing",
+        summary: "With `[gatekeeper] handshake` on, the gatekeeper passed without echoing the run's nonce.",
+        rationale: "The handshake makes a bypass take deliberate effort: exporting `TDD_RATCHET=1` is not enough when the gatekeeper must read a nonce that exists only during a ratchet run.",
+        remediation: &[
+            "Replace the gatekeeper with the output of `cargo ratchet gatekeeper print` and commit it.",
+            "Or turn the handshake off by removing `handshake = true` from `[gatekeeper]` in `ratchet.toml`.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET022",
+        name: "DirtyWorktree",
+        summary: "`.test-status.json` or test files have uncommitted changes.",
+        rationale: "History checks read committed snapshots only, so an uncommitted status file or test edit is invisible to them and can mask a violation until a later run.",
+        remediation: &[
+            "Commit or stash the listed paths and run `cargo ratchet` again.",
+            "The check warns by default; `--strict` or `dirty-worktree = \"deny\"` under `[rules]` makes it fail the run, and `\"allow\"` turns it off.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET023",
+        name: "PluginViolation",
+        summary: "A `ratchet-rule-*` plugin listed under `[plugins]` in `ratchet.toml` reported a violation.",
+        rationale: "Plugins carry checks specific to this project. They see the same status, results, and history as the built-in rules, and their violations fail the run the same way.",
+        remediation: &[
+            "Fix what the plugin reports; the report names the plugin and the subject it flagged.",
+            "See the plugin's own documentation for its rule, or remove it from `rules` under `[plugins]`.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET024",
+        name: "PendingTestCommitType",
+        summary: "A commit added pending tests without one of the conventional-commit types in `pending-test-commit-types`.",
+        rationale: "Marking the commits that add failing tests, such as with `test:`, keeps the red step of each red-green cycle easy to find in history and changelogs.",
+        remediation: &[
+            "Reword the commit so its summary starts with an allowed type, e.g. `test: cover empty input`.",
+            "For commits already shared, `cargo ratchet rebaseline` stops checking history before a commit.",
+        ],
+    },
+    Explanation {
+        code: "RATCHET025",
+        name: "DuplicateTestName",
+        summary: "Several results in one run were reported under the same test name.",
+        rationale: "Each test has one status entry keyed by its name. Tests that share a name, such as same-path tests in two binaries reported without a binary prefix, share that entry, so one test's result masks the other's.",
+        remediation: &[
+            "Switch to full test IDs (`binary$path`) with the `nextest` runner, or a `command` runner with `parser = \"libtest-json\"`.",
+            "Otherwise rename one of the tests so each name is unique.",
+        ],
+    },
+];
+
+/// The explanation for `code`, matched case-insensitively. The `RATCHET`
+/// prefix may be left out: `2`, `002`, and `ratchet002` all find
+/// `RATCHET002`.
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+    let code = code.trim();
+    let digits = match code.get(..7) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("RATCHET") => &code[7..],
+        _ => code,
+    };
+    let number: u32 = digits.parse().ok()?;
+    EXPLANATIONS
+        .iter()
+        .find(|explanation| explanation.code[7..].parse() == Ok(number))
+}
+
+/// The text `cargo ratchet explain` prints for one code.
+pub fn format_explanation(explanation: &Explanation) -> String {
+    let mut out = format!(
+        "{}: {}\n\n{}\n\nWhy: {}\n\nWhat to do:\n",
+        explanation.code, explanation.name, explanation.summary, explanation.rationale
+    );
+    for (i, step) in explanation.remediation.iter().enumerate() {
+        out.push_str(&format!("  {}. {step}\n", i + 1));
+    }
+    out
+}
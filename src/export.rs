@@ -0,0 +1,55 @@
+// History export: one row per (commit, test, state) from the snapshot walk,
+// so adherence trends can be analyzed in spreadsheets or notebooks.
+
+use crate::history::HistorySnapshot;
+
+/// Output format for `cargo ratchet export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// RFC 4180 CSV with a header row (the default).
+    #[default]
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Column names, in order.
+pub const CSV_HEADER: [&str; 5] = ["commit", "time", "author", "test", "state"];
+
+/// Every tracked test at every snapshot (oldest first) as CSV. `time` is the
+/// commit time in Unix seconds. Pure function — no IO.
+pub fn export_csv(snapshots: &[HistorySnapshot]) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push('\n');
+    for snapshot in snapshots {
+        for (test, entry) in &snapshot.status.tests {
+            let row = [
+                snapshot.commit.clone(),
+                snapshot.info.time.to_string(),
+                snapshot.info.author.clone(),
+                test.clone(),
+                entry.state().to_string(),
+            ];
+            let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Quote a field when it contains a delimiter, quote, or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,207 @@
+// The gatekeeper test: the source `cargo ratchet --init` scaffolds, and the
+// per-run nonce handshake that the stronger variant takes part in.
+
+use crate::error::{EvaluationError, RatchetError};
+use crate::ratchet::GATEKEEPER_TEST_NAME;
+use crate::runner::{RunnerError, TestResult};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::BuildHasher;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable naming the file that holds the run's nonce.
+pub const NONCE_FILE_ENV: &str = "TDD_RATCHET_NONCE_FILE";
+
+/// Environment variable naming the file the gatekeeper echoes the nonce to.
+pub const ACK_FILE_ENV: &str = "TDD_RATCHET_ACK_FILE";
+
+/// Where the scaffolded gatekeeper goes, relative to the crate root.
+pub const GATEKEEPER_PATH: &str = "tests/gatekeeper.rs";
+
+/// The canonical gatekeeper test: it fails unless the ratchet runs it.
+pub const GATEKEEPER_SOURCE: &str = "\
+// tests/gatekeeper.rs
+//
+// Bypass prevention: fails when `cargo test` is run directly. tdd-ratchet
+// sets TDD_RATCHET=1 when it runs the tests.
+
+#[test]
+fn tdd_ratchet_gatekeeper() {
+    if std::env::var(\"TDD_RATCHET\").is_err() {
+        panic!(
+            \"\\n\\n\\
+             This project uses strict TDD via tdd-ratchet.\\n\\
+             Do not run `cargo test` directly.\\n\\
+             Run `cargo ratchet` instead.\\n\\
+             \\n\"
+        );
+    }
+}
+";
+
+/// The gatekeeper for `[gatekeeper] handshake = true`: instead of trusting
+/// `TDD_RATCHET`, it echoes the run's nonce back to the ratchet.
+pub const HANDSHAKE_GATEKEEPER_SOURCE: &str = "\
+// tests/gatekeeper.rs
+//
+// Bypass prevention: fails unless tdd-ratchet runs the tests. The ratchet
+// writes a fresh nonce for every run and checks that this test echoed it.
+
+#[test]
+fn tdd_ratchet_gatekeeper() {
+    let nonce = std::env::var(\"TDD_RATCHET_NONCE_FILE\")
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok());
+    let (Some(nonce), Ok(ack)) = (nonce, std::env::var(\"TDD_RATCHET_ACK_FILE\")) else {
+        panic!(
+            \"\\n\\n\\
+             This project uses strict TDD via tdd-ratchet.\\n\\
+             Do not run `cargo test` directly.\\n\\
+             Run `cargo ratchet` instead.\\n\\
+             \\n\"
+        );
+    };
+    std::fs::write(ack, nonce).expect(\"gatekeeper could not answer the tdd-ratchet handshake\");
+}
+";
+
+/// The gatekeeper source for the configured mode.
+pub fn gatekeeper_source(handshake: bool) -> &'static str {
+    if handshake {
+        HANDSHAKE_GATEKEEPER_SOURCE
+    } else {
+        GATEKEEPER_SOURCE
+    }
+}
+
+/// One run's nonce handshake. The nonce file exists only while the
+/// handshake is alive, so a test run outside the ratchet finds nothing to
+/// echo unless someone recreates the files and variables by hand.
+#[derive(Debug)]
+pub struct Handshake {
+    dir: PathBuf,
+    nonce: String,
+}
+
+impl Handshake {
+    /// Write a fresh nonce to a private temporary directory.
+    pub fn begin() -> Result<Self, RatchetError> {
+        Self::write_nonce().map_err(|source| RunnerError::Handshake { source }.into())
+    }
+
+    pub(crate) fn write_nonce() -> io::Result<Self> {
+        let nonce = fresh_nonce();
+        let dir = std::env::temp_dir().join(format!("tdd-ratchet-{nonce}"));
+        fs::create_dir_all(&dir)?;
+        let handshake = Handshake { dir, nonce };
+        fs::write(handshake.nonce_file(), &handshake.nonce)?;
+        Ok(handshake)
+    }
+
+    fn nonce_file(&self) -> PathBuf {
+        self.dir.join("nonce")
+    }
+
+    fn ack_file(&self) -> PathBuf {
+        self.dir.join("ack")
+    }
+
+    /// Variables to set on the test command.
+    pub fn env(&self) -> [(&'static str, PathBuf); 2] {
+        [
+            (NONCE_FILE_ENV, self.nonce_file()),
+            (ACK_FILE_ENV, self.ack_file()),
+        ]
+    }
+
+    /// Forget any earlier acknowledgement, before a new run.
+    pub fn reset(&self) {
+        let _ = fs::remove_file(self.ack_file());
+    }
+
+    /// Whether a gatekeeper echoed the nonce since the last [`reset`].
+    ///
+    /// [`reset`]: Handshake::reset
+    pub fn acknowledged(&self) -> bool {
+        fs::read_to_string(self.ack_file()).is_ok_and(|ack| ack.trim() == self.nonce)
+    }
+}
+
+impl Drop for Handshake {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 32 hex digits that differ between runs and processes.
+fn fresh_nonce() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let state = RandomState::new();
+    let high = state.hash_one((now, process::id()));
+    let low = state.hash_one((high, now));
+    format!("{high:016x}{low:016x}")
+}
+
+/// What [`scaffold_gatekeeper`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scaffold {
+    /// The gatekeeper was written; it is tracked under this name.
+    Written { test: String },
+    /// The run already had a gatekeeper test.
+    AlreadyPresent,
+}
+
+/// Write [`GATEKEEPER_SOURCE`] to `tests/gatekeeper.rs` in `crate_dir`,
+/// unless `results` already contain a gatekeeper test. An existing file is
+/// never overwritten.
+pub fn scaffold_gatekeeper(
+    crate_dir: &Path,
+    results: &[TestResult],
+) -> Result<Scaffold, RatchetError> {
+    scaffold_gatekeeper_with(crate_dir, results, GATEKEEPER_SOURCE)
+}
+
+/// [`scaffold_gatekeeper`] writing `source` instead of the default.
+pub fn scaffold_gatekeeper_with(
+    crate_dir: &Path,
+    results: &[TestResult],
+    source: &str,
+) -> Result<Scaffold, RatchetError> {
+    if results
+        .iter()
+        .any(|r| r.name.ends_with(GATEKEEPER_TEST_NAME))
+    {
+        return Ok(Scaffold::AlreadyPresent);
+    }
+    let package = package_name(crate_dir)?;
+    let path = crate_dir.join(GATEKEEPER_PATH);
+    if path.exists() {
+        return Err(EvaluationError::GatekeeperPathTaken.into());
+    }
+    let tests_dir = crate_dir.join("tests");
+    fs::create_dir_all(&tests_dir).map_err(|e| RatchetError::io(&tests_dir, e))?;
+    fs::write(&path, source).map_err(|e| RatchetError::io(&path, e))?;
+    Ok(Scaffold::Written {
+        test: format!("{package}::gatekeeper${GATEKEEPER_TEST_NAME}"),
+    })
+}
+
+fn package_name(crate_dir: &Path) -> Result<String, RatchetError> {
+    let manifest_path = crate_dir.join("Cargo.toml");
+    let manifest =
+        fs::read_to_string(&manifest_path).map_err(|e| RatchetError::io(&manifest_path, e))?;
+    let manifest: toml::Table =
+        toml::from_str(&manifest).map_err(|_| EvaluationError::NoPackage)?;
+    manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|name| name.as_str())
+        .map(str::to_string)
+        .ok_or(EvaluationError::NoPackage.into())
+}
@@ -0,0 +1,176 @@
+// `cargo ratchet gc`: prune status-file entries that no longer do anything.
+//
+// Over years a status file collects per-test baselines made redundant by a
+// later global baseline, and renames long since carried through history.
+// Committed snapshots keep every rename they were written with, so history
+// checks still follow a test's identity after its rename is pruned here.
+
+use crate::error::RatchetError;
+use crate::history::HistorySnapshot;
+use crate::status::StatusFile;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+/// Days a rename stays in the status file after it was first committed,
+/// unless `--retention-days` says otherwise.
+pub const DEFAULT_RETENTION_DAYS: u64 = 90;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// What the repository says about the global baseline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcEvidence {
+    /// Per-test baselines that are the global baseline or one of its
+    /// ancestors.
+    pub before_baseline: BTreeSet<String>,
+}
+
+/// One entry `gc` removes from the status file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GcChange {
+    /// A per-test baseline at or before the global baseline, for a test the
+    /// global baseline already grandfathers.
+    RedundantBaseline { test: String, baseline: String },
+    /// A rename whose old and new names are both untracked.
+    OrphanedRename { new_name: String, old_name: String },
+    /// A carried-out rename committed longer ago than the retention window.
+    ExpiredRename {
+        new_name: String,
+        old_name: String,
+        age_days: i64,
+    },
+}
+
+impl fmt::Display for GcChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GcChange::RedundantBaseline { test, baseline } => write!(
+                f,
+                "baseline of {test}: {} is covered by the global baseline, removed",
+                &baseline[..8.min(baseline.len())]
+            ),
+            GcChange::OrphanedRename { new_name, old_name } => write!(
+                f,
+                "rename {old_name} -> {new_name}: neither test is tracked, removed"
+            ),
+            GcChange::ExpiredRename {
+                new_name,
+                old_name,
+                age_days,
+            } => write!(
+                f,
+                "rename {old_name} -> {new_name}: committed {age_days} days ago, removed"
+            ),
+        }
+    }
+}
+
+/// Work out which entries of `status` no longer do anything. Pure function —
+/// no IO.
+///
+/// A per-test baseline is redundant when it is at or before the global
+/// baseline and the test is tracked in the status committed there: the test
+/// first appears at the global baseline, which grandfathers it on its own.
+/// A rename is kept while its old name is still tracked. Once carried out it
+/// is dropped `retention_days` after it was first committed, or at once when
+/// its new name is no longer tracked either. `now` is Unix seconds.
+pub fn plan_gc(
+    status: &StatusFile,
+    snapshots: &[HistorySnapshot],
+    evidence: &GcEvidence,
+    now: i64,
+    retention_days: u64,
+) -> Vec<GcChange> {
+    let mut changes = Vec::new();
+
+    let at_baseline = status
+        .baseline
+        .as_deref()
+        .and_then(|baseline| snapshots.iter().find(|s| s.commit == baseline));
+    if let Some(at_baseline) = at_baseline {
+        for (test, entry) in &status.tests {
+            let Some(baseline) = entry.baseline() else {
+                continue;
+            };
+            if evidence.before_baseline.contains(baseline)
+                && at_baseline.status.tests.contains_key(test)
+            {
+                changes.push(GcChange::RedundantBaseline {
+                    test: test.clone(),
+                    baseline: baseline.to_string(),
+                });
+            }
+        }
+    }
+
+    let retention = i64::try_from(retention_days)
+        .unwrap_or(i64::MAX)
+        .saturating_mul(SECONDS_PER_DAY);
+    for (new_name, old_name) in &status.renames {
+        if status.tests.contains_key(old_name) {
+            continue;
+        }
+        if !status.tests.contains_key(new_name) {
+            changes.push(GcChange::OrphanedRename {
+                new_name: new_name.clone(),
+                old_name: old_name.clone(),
+            });
+            continue;
+        }
+        let committed = snapshots
+            .iter()
+            .find(|s| s.status.renames.get(new_name) == Some(old_name));
+        if let Some(committed) = committed {
+            let age = now - committed.info.time;
+            if age >= retention {
+                changes.push(GcChange::ExpiredRename {
+                    new_name: new_name.clone(),
+                    old_name: old_name.clone(),
+                    age_days: age / SECONDS_PER_DAY,
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+/// Apply planned changes to a status file.
+pub fn apply_gc(status: &mut StatusFile, changes: &[GcChange]) {
+    for change in changes {
+        match change {
+            GcChange::RedundantBaseline { test, .. } => {
+                if let Some(entry) = status.tests.get_mut(test) {
+                    entry.baseline = None;
+                }
+            }
+            GcChange::OrphanedRename { new_name, .. }
+            | GcChange::ExpiredRename { new_name, .. } => {
+                status.renames.remove(new_name);
+            }
+        }
+    }
+}
+
+/// Collect [`GcEvidence`] for `status` from the repository.
+pub fn gather_gc_evidence(
+    repo_path: &Path,
+    status: &StatusFile,
+) -> Result<GcEvidence, RatchetError> {
+    let mut evidence = GcEvidence::default();
+    let Some(global) = status.baseline.as_deref() else {
+        return Ok(evidence);
+    };
+    let repo = git2::Repository::open(repo_path)?;
+    let global = git2::Oid::from_str(global)?;
+    for baseline in status.tests.values().filter_map(|entry| entry.baseline()) {
+        let Ok(oid) = git2::Oid::from_str(baseline) else {
+            continue;
+        };
+        if oid == global || repo.graph_descendant_of(global, oid).unwrap_or(false) {
+            evidence.before_baseline.insert(baseline.to_string());
+        }
+    }
+    Ok(evidence)
+}
@@ -0,0 +1,216 @@
+// History inspection by shelling out to the `git` CLI. Used when libgit2
+// cannot open the repository (unusual worktree layouts, partial clones,
+// repository extensions libgit2 does not support).
+
+use crate::error::RatchetError;
+use crate::history::{CommitInfo, HistoryProvider, HistorySnapshot, dirty_paths_in};
+use crate::overrides::parse_overrides;
+use crate::status::{STATUS_FILE_NAME, StatusFile};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Field and record separators for `git log --format`.
+const FIELD: char = '\x1f';
+const RECORD: char = '\x1e';
+
+/// Reads history by running `git` in the repository directory.
+#[derive(Debug, Clone)]
+pub struct GitCliHistory {
+    repo_path: PathBuf,
+    status_path: PathBuf,
+    start: String,
+}
+
+impl GitCliHistory {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        GitCliHistory {
+            repo_path: repo_path.into(),
+            status_path: PathBuf::from(STATUS_FILE_NAME),
+            start: "HEAD".to_string(),
+        }
+    }
+
+    /// Read the status file at `status_path`, relative to the repository
+    /// root, instead of `.test-status.json`.
+    pub fn with_status_path(mut self, status_path: impl Into<PathBuf>) -> Self {
+        self.status_path = status_path.into();
+        self
+    }
+
+    /// Walk history from `rev` (a ref name or commit hash) instead of `HEAD`.
+    pub fn with_start(mut self, rev: impl Into<String>) -> Self {
+        self.start = rev.into();
+        self
+    }
+
+    fn git(&self, args: &[&str], stdin: Option<&str>) -> Result<Vec<u8>, git2::Error> {
+        let mut child = Command::new("git")
+            .args(args)
+            .current_dir(&self.repo_path)
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| git2::Error::from_str(&format!("failed to run git: {e}")))?;
+        // Feed stdin from another thread so a full stdout pipe cannot
+        // deadlock a large `cat-file --batch`.
+        let writer = match (stdin, child.stdin.take()) {
+            (Some(input), Some(mut pipe)) => {
+                let input = input.to_string();
+                Some(thread::spawn(move || pipe.write_all(input.as_bytes())))
+            }
+            _ => None,
+        };
+        let output = child
+            .wait_with_output()
+            .map_err(|e| git2::Error::from_str(&format!("failed to run git: {e}")))?;
+        if let Some(writer) = writer {
+            writer
+                .join()
+                .unwrap_or(Ok(()))
+                .map_err(|e| git2::Error::from_str(&format!("failed to write to git: {e}")))?;
+        }
+        if !output.status.success() {
+            return Err(git2::Error::from_str(&format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+
+    /// The commit the walk starts from; fails before the first commit.
+    fn start_commit(&self) -> Result<String, git2::Error> {
+        let rev = format!("{}^{{commit}}", self.start);
+        let output = self
+            .git(&["rev-parse", "--verify", "--quiet", &rev], None)
+            .map_err(|_| {
+                git2::Error::from_str(&format!("cannot resolve history start `{}`", self.start))
+            })?;
+        Ok(String::from_utf8_lossy(&output).trim().to_string())
+    }
+
+    /// The status file at each of `commits`, read in one `git cat-file
+    /// --batch` call.
+    fn status_files(&self, commits: &[&str]) -> Result<Vec<Option<StatusFile>>, git2::Error> {
+        let path = self.status_path.to_string_lossy().replace('\\', "/");
+        let requests: String = commits.iter().map(|c| format!("{c}:{path}\n")).collect();
+        let output = self.git(&["cat-file", "--batch"], Some(&requests))?;
+
+        let mut rest = output.as_slice();
+        let mut files = Vec::with_capacity(commits.len());
+        for commit in commits {
+            let (header, after) = split_line(rest);
+            let header = String::from_utf8_lossy(header);
+            if header.ends_with(" missing") || header.ends_with(" ambiguous") {
+                files.push(None);
+                rest = after;
+                continue;
+            }
+            let size: usize = header
+                .rsplit(' ')
+                .next()
+                .and_then(|s| s.parse().ok())
+                .filter(|_| header.contains(" blob "))
+                .ok_or_else(|| {
+                    git2::Error::from_str(&format!("unexpected git cat-file output: {header}"))
+                })?;
+            let content = after
+                .get(..size)
+                .ok_or_else(|| git2::Error::from_str("git cat-file output ended early"))?;
+            let content = std::str::from_utf8(content).map_err(|e| {
+                git2::Error::from_str(&format!(
+                    "Invalid UTF-8 in {}: {e}",
+                    self.status_path.display()
+                ))
+            })?;
+            let status = StatusFile::parse_historical_from_str(content, &self.status_path)
+                .map_err(|e| {
+                    git2::Error::from_str(&format!(
+                        "Failed to parse {} at {}: {}",
+                        self.status_path.display(),
+                        commit,
+                        e
+                    ))
+                })?;
+            files.push(Some(status));
+            // The blob is followed by a newline.
+            rest = after.get(size + 1..).unwrap_or_default();
+        }
+        Ok(files)
+    }
+}
+
+impl HistoryProvider for GitCliHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        let start = self.start_commit()?;
+        Ok(self.status_files(&[start.as_str()])?.pop().flatten())
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        let start = self.start_commit()?;
+        let format = format!("--format=%H{FIELD}%an{FIELD}%at{FIELD}%P{FIELD}%s{FIELD}%B{RECORD}");
+        let log = self.git(&["log", "--topo-order", "--reverse", &format, &start], None)?;
+        let log = String::from_utf8_lossy(&log);
+
+        let commits: Vec<(&str, CommitInfo)> = log
+            .split(RECORD)
+            .filter_map(|record| {
+                let mut fields = record.trim_start_matches('\n').splitn(6, FIELD);
+                let commit = fields.next().filter(|c| !c.is_empty())?;
+                let author = fields.next()?.to_string();
+                let time = fields.next()?.parse().unwrap_or_default();
+                let parents = fields
+                    .next()?
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect();
+                let summary = fields.next()?.to_string();
+                let message = fields.next().unwrap_or_default();
+                Some((
+                    commit,
+                    CommitInfo {
+                        author,
+                        time,
+                        summary,
+                        overrides: parse_overrides(message),
+                        parents,
+                    },
+                ))
+            })
+            .collect();
+
+        let ids: Vec<&str> = commits.iter().map(|(commit, _)| *commit).collect();
+        let files = self.status_files(&ids)?;
+        Ok(commits
+            .into_iter()
+            .zip(files)
+            .filter_map(|((commit, info), status)| {
+                Some(HistorySnapshot {
+                    commit: commit.to_string(),
+                    info,
+                    status: status?,
+                })
+            })
+            .collect())
+    }
+
+    fn dirty_paths(&self) -> Result<Vec<String>, RatchetError> {
+        dirty_paths_in(&self.repo_path, &self.status_path)
+    }
+}
+
+/// Split off the first line of `bytes`, without its newline.
+fn split_line(bytes: &[u8]) -> (&[u8], &[u8]) {
+    match bytes.iter().position(|&b| b == b'\n') {
+        Some(end) => (&bytes[..end], &bytes[end + 1..]),
+        None => (bytes, &[]),
+    }
+}
@@ -0,0 +1,150 @@
+// History inspection through gitoxide (`gix`), a pure-Rust alternative to
+// libgit2 that walks large histories faster. Enabled with the `gix` feature
+// and selected with `[history] backend = "gix"`.
+
+use crate::error::RatchetError;
+use crate::history::{
+    CommitInfo, HistoryProvider, HistorySnapshot, dirty_paths_in, status_file_history,
+};
+use crate::overrides::parse_overrides;
+use crate::status::{STATUS_FILE_NAME, StatusFile};
+use crate::test_edits::{TestEdit, edited_tests_in_commit};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Reads history from a git repository via gitoxide.
+#[derive(Debug, Clone)]
+pub struct GixHistory {
+    repo_path: PathBuf,
+    status_path: PathBuf,
+    start: String,
+}
+
+impl GixHistory {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        GixHistory {
+            repo_path: repo_path.into(),
+            status_path: PathBuf::from(STATUS_FILE_NAME),
+            start: "HEAD".to_string(),
+        }
+    }
+
+    /// Read the status file at `status_path`, relative to the repository
+    /// root, instead of `.test-status.json`.
+    pub fn with_status_path(mut self, status_path: impl Into<PathBuf>) -> Self {
+        self.status_path = status_path.into();
+        self
+    }
+
+    /// Walk history from `rev` (a ref name or commit hash) instead of `HEAD`.
+    pub fn with_start(mut self, rev: impl Into<String>) -> Self {
+        self.start = rev.into();
+        self
+    }
+
+    fn open(&self) -> Result<gix::Repository, git2::Error> {
+        gix::open(&self.repo_path).map_err(gix_error)
+    }
+
+    fn start_commit<'repo>(
+        &self,
+        repo: &'repo gix::Repository,
+    ) -> Result<gix::Commit<'repo>, git2::Error> {
+        repo.rev_parse_single(self.start.as_str())
+            .map_err(gix_error)?
+            .object()
+            .map_err(gix_error)?
+            .peel_to_commit()
+            .map_err(gix_error)
+    }
+}
+
+impl HistoryProvider for GixHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        let repo = self.open()?;
+        let start = self.start_commit(&repo)?;
+        Ok(status_file_at_commit(&start, &self.status_path)?)
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        let mut repo = self.open()?;
+        // Each commit's tree is looked up once; cache the shared subtrees.
+        repo.object_cache_size_if_unset(4 * 1024 * 1024);
+        let head = self.start_commit(&repo)?.id;
+
+        let mut found = BTreeMap::new();
+        let order = status_file_history(head, |id| {
+            let commit = repo.find_commit(id).map_err(gix_error)?;
+            let Some(status) = status_file_at_commit(&commit, &self.status_path)? else {
+                return Ok(None);
+            };
+            found.insert(id, (commit_info(&commit)?, status));
+            Ok(Some(
+                commit.parent_ids().map(|parent| parent.detach()).collect(),
+            ))
+        })?;
+
+        Ok(order
+            .into_iter()
+            .filter_map(|id| {
+                let (info, status) = found.remove(&id)?;
+                Some(HistorySnapshot {
+                    commit: id.to_string(),
+                    info,
+                    status,
+                })
+            })
+            .collect())
+    }
+
+    fn edited_tests(&self, commit: &str, tests: &[String]) -> Result<Vec<TestEdit>, RatchetError> {
+        edited_tests_in_commit(&self.repo_path, commit, tests)
+    }
+
+    fn dirty_paths(&self) -> Result<Vec<String>, RatchetError> {
+        dirty_paths_in(&self.repo_path, &self.status_path)
+    }
+}
+
+fn status_file_at_commit(
+    commit: &gix::Commit<'_>,
+    status_path: &Path,
+) -> Result<Option<StatusFile>, git2::Error> {
+    let tree = commit.tree().map_err(gix_error)?;
+    let Some(entry) = tree.lookup_entry_by_path(status_path).map_err(gix_error)? else {
+        return Ok(None);
+    };
+    let object = entry.object().map_err(gix_error)?;
+    let content = std::str::from_utf8(&object.data).map_err(|e| {
+        git2::Error::from_str(&format!("Invalid UTF-8 in {}: {e}", status_path.display()))
+    })?;
+
+    StatusFile::parse_historical_from_str(content, status_path)
+        .map(Some)
+        .map_err(|e| {
+            git2::Error::from_str(&format!(
+                "Failed to parse {} at {}: {}",
+                status_path.display(),
+                commit.id,
+                e
+            ))
+        })
+}
+
+fn commit_info(commit: &gix::Commit<'_>) -> Result<CommitInfo, git2::Error> {
+    let author = commit.author().map_err(gix_error)?;
+    let message = commit.message_raw().map_err(gix_error)?.to_string();
+    let summary = commit.message().map_err(gix_error)?.summary().to_string();
+    Ok(CommitInfo {
+        author: author.name.to_string(),
+        time: commit.time().map_err(gix_error)?.seconds,
+        summary,
+        overrides: parse_overrides(&message),
+        parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+    })
+}
+
+/// Errors surface through [`HistoryProvider`] as `git2::Error`s.
+fn gix_error(error: impl std::fmt::Display) -> git2::Error {
+    git2::Error::from_str(&error.to_string())
+}
@@ -0,0 +1,67 @@
+// Glob rules for families of pre-existing tests exempt from test-first
+// checks.
+
+use serde::Deserialize;
+
+/// Test-name patterns from `grandfather` in `ratchet.toml`:
+///
+/// ```toml
+/// grandfather = ["legacy::*", "vendor_*"]
+/// ```
+///
+/// A matching test may pass without ever being pending, so it is exempt from
+/// NewTestPassed and SkippedPending. A pattern matches the full tracked name
+/// (`crate::binary$path::to::test`) or the test path after the `$`. `*`
+/// matches any run of characters, including `::`; every other character
+/// matches itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct GrandfatherPatterns(Vec<String>);
+
+impl GrandfatherPatterns {
+    pub fn new(patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        GrandfatherPatterns(patterns.into_iter().map(Into::into).collect())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether any pattern grandfathers `test`.
+    pub fn matches(&self, test: &str) -> bool {
+        self.0.iter().any(|pattern| test_matches(pattern, test))
+    }
+}
+
+/// Whether `pattern` matches the tracked name `test` or its test path after
+/// the `$`.
+pub(crate) fn test_matches(pattern: &str, test: &str) -> bool {
+    let path = test.split_once('$').map(|(_, path)| path);
+    glob_matches(pattern, test) || path.is_some_and(|path| glob_matches(pattern, path))
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters.
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text it has consumed up to, to
+    // backtrack to when a literal stops matching.
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            star = Some((p, t));
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((after_star, consumed)) = star {
+            p = after_star;
+            t = consumed + 1;
+            star = Some((after_star, consumed + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -1,9 +1,16 @@
 // Git history inspection: verify no test skipped the pending state.
 
+use crate::error::RatchetError;
+use crate::grandfather::GrandfatherPatterns;
+use crate::merge_queue::merge_tests;
+use crate::overrides::{Override, parse_overrides};
 use crate::ratchet::GATEKEEPER_TEST_NAME;
-use crate::status::{StatusFile, TestState};
+use crate::status::{STATUS_FILE_NAME, StatusFile, TestState, is_wildcard, wildcard_matches};
+use crate::test_edits::{TestEdit, edited_tests_in_commit};
+use rayon::prelude::*;
 use std::collections::{BTreeMap, BTreeSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info_span, trace};
 
 #[derive(Debug, Clone)]
 pub enum HistoryViolation {
@@ -15,40 +22,506 @@ pub enum HistoryViolation {
 #[derive(Debug, Clone)]
 pub struct HistorySnapshot {
     pub commit: String,
+    pub info: CommitInfo,
     pub status: StatusFile,
 }
 
+/// Commit metadata carried alongside a snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitInfo {
+    pub author: String,
+    /// Commit time in seconds since the Unix epoch.
+    pub time: i64,
+    pub summary: String,
+    /// `Ratchet-Override` trailers in the commit message.
+    pub overrides: Vec<Override>,
+    /// The commit's parents, which relate snapshots across merges.
+    pub parents: Vec<String>,
+}
+
+impl CommitInfo {
+    fn from_commit(commit: &git2::Commit<'_>) -> Self {
+        CommitInfo {
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time: commit.time().seconds(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            overrides: parse_overrides(commit.message().unwrap_or_default()),
+            parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+        }
+    }
+}
+
+/// Source of committed status-file history for a ratchet run.
+pub trait HistoryProvider {
+    /// The status file committed at `HEAD`, if any.
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError>;
+    /// Status-file snapshots from oldest to newest.
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError>;
+    /// Tests among `tests` whose own source changed in `commit`. Providers
+    /// without source access report none.
+    fn edited_tests(
+        &self,
+        _commit: &str,
+        _tests: &[String],
+    ) -> Result<Vec<TestEdit>, RatchetError> {
+        Ok(Vec::new())
+    }
+    /// The status file and test files with changes not yet committed
+    /// (staged, unstaged, or untracked), relative to the repository root.
+    /// Providers without a working tree report none.
+    fn dirty_paths(&self) -> Result<Vec<String>, RatchetError> {
+        Ok(Vec::new())
+    }
+}
+
+impl<T: HistoryProvider + ?Sized> HistoryProvider for &T {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        (**self).head_status()
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        (**self).snapshots()
+    }
+
+    fn edited_tests(&self, commit: &str, tests: &[String]) -> Result<Vec<TestEdit>, RatchetError> {
+        (**self).edited_tests(commit, tests)
+    }
+
+    fn dirty_paths(&self) -> Result<Vec<String>, RatchetError> {
+        (**self).dirty_paths()
+    }
+}
+
+impl<T: HistoryProvider + ?Sized> HistoryProvider for Box<T> {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        (**self).head_status()
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        (**self).snapshots()
+    }
+
+    fn edited_tests(&self, commit: &str, tests: &[String]) -> Result<Vec<TestEdit>, RatchetError> {
+        (**self).edited_tests(commit, tests)
+    }
+
+    fn dirty_paths(&self) -> Result<Vec<String>, RatchetError> {
+        (**self).dirty_paths()
+    }
+}
+
+/// The status file and test files with uncommitted changes in the
+/// repository at `repo_path`. A test file is anything under a `tests`
+/// directory or a `*_test.rs`/`*_tests.rs` file.
+pub fn dirty_paths_in(repo_path: &Path, status_path: &Path) -> Result<Vec<String>, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    Ok(repo
+        .statuses(Some(&mut options))?
+        .iter()
+        .filter(|entry| entry.status() != git2::Status::CURRENT)
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .filter(|path| Path::new(path) == status_path || is_test_path(Path::new(path)))
+        .collect())
+}
+
+fn is_test_path(path: &Path) -> bool {
+    let in_tests = path
+        .parent()
+        .is_some_and(|dir| dir.components().any(|c| c.as_os_str() == "tests"));
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    in_tests
+        || (path.extension().is_some_and(|ext| ext == "rs")
+            && (stem.ends_with("_test") || stem.ends_with("_tests")))
+}
+
+/// Reads history from a git repository via libgit2. In a linked worktree,
+/// `HEAD` is the worktree's own `HEAD`.
+#[derive(Debug, Clone)]
+pub struct GitHistory {
+    repo_path: PathBuf,
+    status_path: PathBuf,
+    start: String,
+    since: Option<String>,
+    merge_target: Option<String>,
+}
+
+impl GitHistory {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        GitHistory {
+            repo_path: repo_path.into(),
+            status_path: PathBuf::from(STATUS_FILE_NAME),
+            start: "HEAD".to_string(),
+            since: None,
+            merge_target: None,
+        }
+    }
+
+    /// Read the status file at `status_path`, relative to the repository
+    /// root, instead of `.test-status.json`.
+    pub fn with_status_path(mut self, status_path: impl Into<PathBuf>) -> Self {
+        self.status_path = status_path.into();
+        self
+    }
+
+    /// Walk history from `rev` (a ref name or commit hash) instead of `HEAD`.
+    /// The status at `rev` then stands in for the status at `HEAD`.
+    pub fn with_start(mut self, rev: impl Into<String>) -> Self {
+        self.start = rev.into();
+        self
+    }
+
+    /// Only walk the commits after `rev`, as in `rev..HEAD`. The status at
+    /// `rev` becomes the first snapshot, so every test it tracks is
+    /// grandfathered.
+    pub fn with_since(mut self, rev: impl Into<String>) -> Self {
+        self.since = Some(rev.into());
+        self
+    }
+
+    /// Read the status at `HEAD` as it would be after merging into `rev`,
+    /// the way a merge queue builds the branch; see [`merge_tests`].
+    pub fn with_merge_target(mut self, rev: impl Into<String>) -> Self {
+        self.merge_target = Some(rev.into());
+        self
+    }
+}
+
+impl HistoryProvider for GitHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        let repo = git2::Repository::open(&self.repo_path)?;
+        let start = resolve_start(&repo, &self.start)?;
+        let ours = status_file_at_commit(&repo, start, &self.status_path)?;
+        let Some(target) = &self.merge_target else {
+            return Ok(ours);
+        };
+        let target = resolve_start(&repo, target)?;
+        let theirs = status_file_at_commit(&repo, target, &self.status_path)?;
+        let Some(mut ours) = ours else {
+            return Ok(theirs);
+        };
+        let base =
+            status_file_at_commit(&repo, repo.merge_base(start, target)?, &self.status_path)?;
+        let empty = BTreeMap::new();
+        ours.tests = merge_tests(
+            base.as_ref().map_or(&empty, |status| &status.tests),
+            &ours.tests,
+            theirs.as_ref().map_or(&empty, |status| &status.tests),
+        );
+        Ok(Some(ours))
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        let _span = info_span!("history", start = %self.start, since = ?self.since).entered();
+        let snapshots = collect_snapshots(
+            &self.repo_path,
+            &self.status_path,
+            &self.start,
+            self.since.as_deref(),
+        )?;
+        for snapshot in &snapshots {
+            trace!(
+                commit = %snapshot.commit,
+                tests = snapshot.status.tests.len(),
+                "status snapshot"
+            );
+        }
+        debug!(snapshots = snapshots.len(), "walked status file history");
+        Ok(snapshots)
+    }
+
+    fn edited_tests(&self, commit: &str, tests: &[String]) -> Result<Vec<TestEdit>, RatchetError> {
+        edited_tests_in_commit(&self.repo_path, commit, tests)
+    }
+
+    fn dirty_paths(&self) -> Result<Vec<String>, RatchetError> {
+        dirty_paths_in(&self.repo_path, &self.status_path)
+    }
+}
+
 /// Collect status file snapshots from git history.
 ///
-/// Returns snapshots from oldest to newest for every commit that contains a
+/// Returns snapshots from oldest to newest for every commit reachable from
+/// `start` (a ref name such as `HEAD`, or a commit hash) that contains a
 /// committed .test-status.json. The first snapshot is the implicit baseline.
-pub fn collect_history_snapshots(repo_path: &Path) -> Result<Vec<HistorySnapshot>, git2::Error> {
-    let repo = git2::Repository::open(repo_path)?;
+///
+/// The walk stops at commits without the status file, so history from
+/// before the file was introduced is never read.
+pub fn collect_history_snapshots(
+    repo_path: &Path,
+    start: &str,
+) -> Result<Vec<HistorySnapshot>, RatchetError> {
+    collect_history_snapshots_at(repo_path, Path::new(STATUS_FILE_NAME), start)
+}
 
-    let mut snapshots = Vec::new();
+/// [`collect_history_snapshots`] for the status file at `status_path`,
+/// relative to the repository root.
+pub fn collect_history_snapshots_at(
+    repo_path: &Path,
+    status_path: &Path,
+    start: &str,
+) -> Result<Vec<HistorySnapshot>, RatchetError> {
+    Ok(collect_snapshots(repo_path, status_path, start, None)?)
+}
 
-    let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
-    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+/// [`collect_history_snapshots_at`] bounded to the commits after `since`, as
+/// in `since..start`. The snapshot at `since` comes first, so it stands in
+/// for the baseline.
+pub fn collect_history_snapshots_since(
+    repo_path: &Path,
+    status_path: &Path,
+    start: &str,
+    since: &str,
+) -> Result<Vec<HistorySnapshot>, RatchetError> {
+    Ok(collect_snapshots(
+        repo_path,
+        status_path,
+        start,
+        Some(since),
+    )?)
+}
 
-    for oid_result in revwalk {
-        let oid = oid_result?;
+fn collect_snapshots(
+    repo_path: &Path,
+    status_path: &Path,
+    start: &str,
+    since: Option<&str>,
+) -> Result<Vec<HistorySnapshot>, git2::Error> {
+    let repo = git2::Repository::open(repo_path)?;
+    let start = resolve_start(&repo, start)?;
+    let bound = match since {
+        Some(rev) => Some(since_bound(&repo, start, rev)?),
+        None => None,
+    };
 
-        if let Some(sf) = status_file_at_commit(&repo, oid)? {
-            snapshots.push(HistorySnapshot {
+    // Walk first, noting each commit's status blob; parse the blobs after.
+    let mut found = BTreeMap::new();
+    let order = status_file_history(start, |oid| {
+        let at_bound = bound.as_ref().is_some_and(|(since, _)| *since == oid);
+        if !at_bound
+            && bound
+                .as_ref()
+                .is_some_and(|(_, range)| !range.contains(&oid))
+        {
+            return Ok(None);
+        }
+        let commit = repo.find_commit(oid)?;
+        let Some(blob) = status_blob_at_commit(&commit, status_path)? else {
+            return Ok(None);
+        };
+        found.insert(oid, (blob, CommitInfo::from_commit(&commit)));
+        if at_bound {
+            return Ok(Some(Vec::new()));
+        }
+        Ok(Some(commit.parent_ids().collect()))
+    })?;
+    let statuses = parse_status_blobs(
+        repo_path,
+        status_path,
+        order.iter().map(|oid| (found[oid].0, *oid)),
+    )?;
+
+    Ok(order
+        .into_iter()
+        .filter_map(|oid| {
+            let (blob, info) = found.remove(&oid)?;
+            Some(HistorySnapshot {
                 commit: oid.to_string(),
-                status: sf,
-            });
+                info,
+                status: statuses[&blob].clone(),
+            })
+        })
+        .collect())
+}
+
+/// The commits reachable from `start` that carry the status file at
+/// `status_path`, oldest first, with their metadata. The same walk as
+/// [`collect_history_snapshots_at`], without reading the status blobs, for
+/// callers that cache what they derive from each commit.
+pub fn status_file_commits(
+    repo_path: &Path,
+    status_path: &Path,
+    start: &str,
+) -> Result<Vec<(String, CommitInfo)>, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let start = resolve_start(&repo, start)?;
+    let mut found = BTreeMap::new();
+    let order = status_file_history(start, |oid| {
+        let commit = repo.find_commit(oid)?;
+        if status_blob_at_commit(&commit, status_path)?.is_none() {
+            return Ok(None);
+        }
+        found.insert(oid, CommitInfo::from_commit(&commit));
+        Ok(Some(commit.parent_ids().collect()))
+    })?;
+    Ok(order
+        .into_iter()
+        .filter_map(|oid| Some((oid.to_string(), found.remove(&oid)?)))
+        .collect())
+}
+
+/// The commit `rev` names and the commits in `rev..start`. `rev` must be
+/// `start` or one of its ancestors.
+fn since_bound(
+    repo: &git2::Repository,
+    start: git2::Oid,
+    rev: &str,
+) -> Result<(git2::Oid, BTreeSet<git2::Oid>), git2::Error> {
+    let since = repo.revparse_single(rev)?.peel_to_commit()?.id();
+    if since != start && !repo.graph_descendant_of(start, since)? {
+        return Err(git2::Error::from_str(&format!(
+            "{rev} is not an ancestor of the history start"
+        )));
+    }
+    let mut walk = repo.revwalk()?;
+    walk.push(start)?;
+    walk.hide(since)?;
+    Ok((since, walk.collect::<Result<_, _>>()?))
+}
+
+/// Parse each distinct status blob among `blobs`, given as `(blob, commit)`
+/// pairs, once. Blobs are parsed in parallel, each thread with its own
+/// repository handle, as libgit2 handles cannot be shared across threads.
+/// A blob that fails to parse is reported at the first commit listing it.
+fn parse_status_blobs(
+    repo_path: &Path,
+    status_path: &Path,
+    blobs: impl IntoIterator<Item = (git2::Oid, git2::Oid)>,
+) -> Result<BTreeMap<git2::Oid, StatusFile>, git2::Error> {
+    let mut first_commit = BTreeMap::new();
+    for (blob, commit) in blobs {
+        first_commit.entry(blob).or_insert(commit);
+    }
+    let blobs: Vec<(git2::Oid, git2::Oid)> = first_commit.into_iter().collect();
+    blobs
+        .par_iter()
+        .map_init(
+            || git2::Repository::open(repo_path),
+            |repo, &(blob, commit)| {
+                let repo = repo
+                    .as_ref()
+                    .map_err(|e| git2::Error::new(e.code(), e.class(), e.message()))?;
+                Ok((blob, parse_status_blob(repo, blob, commit, status_path)?))
+            },
+        )
+        .collect()
+}
+
+/// The commits reachable from `start` that carry the status file, oldest
+/// first. `visit` reads one commit and returns its parents, or `None` when
+/// the commit has no status file; its parents are then never visited, which
+/// bounds the walk to the file's history.
+pub(crate) fn status_file_history<Id: Ord + Copy>(
+    start: Id,
+    mut visit: impl FnMut(Id) -> Result<Option<Vec<Id>>, git2::Error>,
+) -> Result<Vec<Id>, git2::Error> {
+    let mut parents = BTreeMap::new();
+    let mut seen = BTreeSet::from([start]);
+    let mut queue = vec![start];
+    while let Some(id) = queue.pop() {
+        if let Some(ids) = visit(id)? {
+            queue.extend(ids.iter().filter(|parent| seen.insert(**parent)));
+            parents.insert(id, ids);
+        }
+    }
+    Ok(topological_order(start, &parents)
+        .into_iter()
+        .filter(|id| parents.contains_key(id))
+        .collect())
+}
+
+/// Oldest-first order in which every commit follows all of its parents,
+/// matching libgit2's `TOPOLOGICAL | REVERSE` walk.
+fn topological_order<Id: Ord + Copy>(head: Id, parents: &BTreeMap<Id, Vec<Id>>) -> Vec<Id> {
+    let mut order = Vec::with_capacity(parents.len());
+    let mut visited = BTreeSet::new();
+    // Iterative post-order DFS: (commit, whether its parents are queued).
+    let mut stack = vec![(head, false)];
+    while let Some((id, expanded)) = stack.pop() {
+        if expanded {
+            order.push(id);
+            continue;
+        }
+        if !visited.insert(id) {
+            continue;
         }
+        stack.push((id, true));
+        for parent in parents.get(&id).into_iter().flatten().rev() {
+            if !visited.contains(parent) {
+                stack.push((*parent, false));
+            }
+        }
+    }
+    order
+}
+
+/// Resolve `rev` to the commit it names. `HEAD` may be detached.
+fn resolve_start(repo: &git2::Repository, rev: &str) -> Result<git2::Oid, git2::Error> {
+    repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id())
+        .map_err(|e| {
+            git2::Error::from_str(&format!(
+                "cannot resolve history start `{rev}`: {}",
+                e.message()
+            ))
+        })
+}
+
+/// The commit `HEAD` points at, or `None` outside a repository or before the
+/// first commit.
+pub fn head_commit(repo_path: &Path) -> Option<String> {
+    let repo = git2::Repository::open(repo_path).ok()?;
+    let head = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(head.id().to_string())
+}
+
+/// Resolve `rev` to a full commit hash that `HEAD` contains, for use as a
+/// baseline.
+pub fn resolve_baseline(repo_path: &Path, rev: &str) -> Result<String, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let commit = repo.revparse_single(rev)?.peel_to_commit()?.id();
+    let head = repo.head()?.peel_to_commit()?.id();
+    if commit != head && !repo.graph_descendant_of(head, commit)? {
+        return Err(git2::Error::from_str(&format!("{rev} is not an ancestor of HEAD")).into());
     }
+    Ok(commit.to_string())
+}
 
-    Ok(snapshots)
+/// The commits in `from..to`: those reachable from `to` but not from
+/// `from`, as full hashes.
+pub fn commits_in_range(
+    repo_path: &Path,
+    from: &str,
+    to: &str,
+) -> Result<BTreeSet<String>, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let mut walk = repo.revwalk()?;
+    walk.push(resolve_start(&repo, to)?)?;
+    walk.hide(resolve_start(&repo, from)?)?;
+    walk.map(|oid| Ok(oid?.to_string())).collect()
 }
 
-pub fn read_head_status(repo_path: &Path) -> Result<Option<StatusFile>, git2::Error> {
+pub fn read_head_status(repo_path: &Path) -> Result<Option<StatusFile>, RatchetError> {
+    read_head_status_at(repo_path, Path::new(STATUS_FILE_NAME))
+}
+
+/// [`read_head_status`] for the status file at `status_path`, relative to the
+/// repository root.
+pub fn read_head_status_at(
+    repo_path: &Path,
+    status_path: &Path,
+) -> Result<Option<StatusFile>, RatchetError> {
     let repo = git2::Repository::open(repo_path)?;
     let head = repo.head()?.peel_to_commit()?;
-    status_file_at_commit(&repo, head.id())
+    Ok(status_file_at_commit(&repo, head.id(), status_path)?)
 }
 
 /// Check history snapshots for TDD violations. Pure function — no IO.
@@ -61,13 +534,89 @@ pub fn read_head_status(repo_path: &Path) -> Result<Option<StatusFile>, git2::Er
 /// When a test has a per-test baseline pointing to commit X, history checking
 /// for that test starts at X. The test's first appearance at or after X is
 /// grandfathered, just like tests in the first committed status snapshot.
+///
+/// The global baseline is read from the latest committed status snapshot;
+/// see [`check_history_snapshots_from`].
 pub fn check_history_snapshots(snapshots: &[HistorySnapshot]) -> Vec<HistoryViolation> {
-    let mut first_seen = BTreeMap::new();
-    let mut identity_aliases = BTreeMap::new();
-    let mut violations = Vec::new();
-    let active_identities = active_history_identities(snapshots);
+    let baseline = snapshots.last().and_then(|s| s.status.baseline.as_deref());
+    check_history_snapshots_from(snapshots, baseline)
+}
 
-    let first_snapshot_commit = snapshots.first().map(|s| s.commit.clone());
+/// [`check_history_snapshots`] with an explicit global baseline. Snapshots
+/// the baseline commit descends from are skipped and the snapshot at it
+/// takes the place of the first committed snapshot. A baseline that is not among the
+/// snapshots has no effect.
+pub fn check_history_snapshots_from(
+    snapshots: &[HistorySnapshot],
+    baseline: Option<&str>,
+) -> Vec<HistoryViolation> {
+    check_history_snapshots_with(snapshots, baseline, &GrandfatherPatterns::default())
+}
+
+/// [`check_history_snapshots_from`] with `grandfather` patterns from
+/// `ratchet.toml`: matching tests may first appear as passing anywhere in
+/// history.
+///
+/// Snapshots are related through their commits' parents, so a test counts
+/// as tracked before a snapshot only when an ancestor of it tracked the
+/// test. A pending snapshot on a side branch therefore covers the passing
+/// one it leads to however octopus or criss-cross merges order the walk,
+/// and a test added as passing on one branch is not excused by a pending
+/// entry on another. Snapshots without parent information are taken as one
+/// line of history in order.
+pub fn check_history_snapshots_with(
+    snapshots: &[HistorySnapshot],
+    baseline: Option<&str>,
+    grandfather: &GrandfatherPatterns,
+) -> Vec<HistoryViolation> {
+    let dag = SnapshotDag::new(snapshots);
+    let baseline = baseline.and_then(|b| dag.index.get(b).copied());
+    // History the baseline descends from is not checked, but still shows
+    // which tests were tracked before.
+    let before_baseline = baseline.map(|b| dag.ancestors(b)).unwrap_or_default();
+    let first_snapshot = baseline.unwrap_or(0);
+
+    let mut identity_aliases = BTreeMap::new();
+    for snapshot in snapshots {
+        record_history_renames(&mut identity_aliases, &snapshot.status);
+    }
+    let active_identities = active_history_identities(snapshots, &identity_aliases);
+    // The identities each snapshot tracks, and its wildcard entries; a test
+    // one of them tracked already went through the ratchet under it. A draft
+    // has not: its failure proves nothing, so a draft that turns passing
+    // without a pending commit in between skipped pending.
+    let tracked: Vec<BTreeSet<&str>> = snapshots
+        .iter()
+        .map(|snapshot| {
+            snapshot
+                .status
+                .tests
+                .iter()
+                .filter(|(_, entry)| entry.state() != TestState::Draft)
+                .map(|(name, _)| resolve_history_identity(&identity_aliases, name))
+                .collect()
+        })
+        .collect();
+    let wildcards: Vec<Vec<&str>> = snapshots
+        .iter()
+        .map(|snapshot| {
+            snapshot
+                .status
+                .tests
+                .keys()
+                .map(String::as_str)
+                .filter(|name| is_wildcard(name))
+                .collect()
+        })
+        .collect();
+    let tracked_before = |index: usize, identity: &str| {
+        dag.ancestors(index).into_iter().any(|ancestor| {
+            tracked[ancestor].contains(identity)
+                || wildcards[ancestor]
+                    .iter()
+                    .any(|wildcard| wildcard_matches(wildcard, identity))
+        })
+    };
 
     // Collect per-test baselines from the latest committed status snapshot.
     let per_test_baselines: BTreeMap<String, String> = snapshots
@@ -81,66 +630,111 @@ pub fn check_history_snapshots(snapshots: &[HistorySnapshot]) -> Vec<HistoryViol
         })
         .unwrap_or_default();
 
-    // Build a commit-to-index map for efficient ordering lookups.
-    let commit_index: BTreeMap<&str, usize> = snapshots
-        .iter()
-        .enumerate()
-        .map(|(i, s)| (s.commit.as_str(), i))
-        .collect();
-
-    for snapshot in snapshots {
-        record_history_renames(&mut identity_aliases, &snapshot.status);
-
+    let mut violations = Vec::new();
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        if before_baseline.contains(&index) {
+            continue;
+        }
         for (test_name, entry) in &snapshot.status.tests {
             let identity_name = resolve_history_identity(&identity_aliases, test_name);
 
-            if !active_identities.contains(identity_name) {
+            if !active_identities.contains(identity_name) || entry.state() != TestState::Passing {
                 continue;
             }
 
-            if !mark_first_appearance(&mut first_seen, identity_name) {
+            // Tracked by a parent, so not a first appearance on this line.
+            if dag.parents[index]
+                .iter()
+                .any(|&parent| tracked[parent].contains(identity_name))
+            {
                 continue;
             }
 
-            let state = entry.state();
-
-            if state != TestState::Passing {
+            if is_grandfathered(identity_name, index, first_snapshot, grandfather)
+                || tracked_before(index, identity_name)
+                || is_grandfathered_by_per_test_baseline(
+                    identity_name,
+                    index,
+                    &per_test_baselines,
+                    &dag,
+                )
+            {
                 continue;
             }
 
-            if !is_grandfathered(
-                identity_name,
-                &snapshot.commit,
-                first_snapshot_commit.as_deref(),
-                &per_test_baselines,
-                &commit_index,
-            ) {
-                violations.push(HistoryViolation::SkippedPending {
-                    test: test_name.clone(),
-                    commit: snapshot.commit.clone(),
-                });
-            }
+            violations.push(HistoryViolation::SkippedPending {
+                test: test_name.clone(),
+                commit: snapshot.commit.clone(),
+            });
         }
     }
 
     violations
 }
 
-fn active_history_identities(snapshots: &[HistorySnapshot]) -> BTreeSet<String> {
+/// Ancestry between snapshots: the parents of each snapshot among them, by
+/// index.
+struct SnapshotDag<'a> {
+    parents: Vec<Vec<usize>>,
+    index: BTreeMap<&'a str, usize>,
+}
+
+impl<'a> SnapshotDag<'a> {
+    /// Parents come from each snapshot's [`CommitInfo::parents`]; parent
+    /// commits without a snapshot are left out. When no snapshot records
+    /// parents, each snapshot's parent is the one before it.
+    fn new(snapshots: &'a [HistorySnapshot]) -> Self {
+        let index: BTreeMap<&str, usize> = snapshots
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.commit.as_str(), i))
+            .collect();
+        let linear = snapshots.iter().all(|s| s.info.parents.is_empty());
+        let parents = snapshots
+            .iter()
+            .enumerate()
+            .map(|(i, snapshot)| {
+                if linear {
+                    i.checked_sub(1).into_iter().collect()
+                } else {
+                    snapshot
+                        .info
+                        .parents
+                        .iter()
+                        .filter_map(|parent| index.get(parent.as_str()).copied())
+                        .collect()
+                }
+            })
+            .collect();
+        SnapshotDag { parents, index }
+    }
+
+    /// The snapshots `index` descends from, not including itself.
+    fn ancestors(&self, index: usize) -> BTreeSet<usize> {
+        let mut seen = BTreeSet::new();
+        let mut stack = self.parents[index].clone();
+        while let Some(ancestor) = stack.pop() {
+            if seen.insert(ancestor) {
+                stack.extend(&self.parents[ancestor]);
+            }
+        }
+        seen
+    }
+}
+
+fn active_history_identities(
+    snapshots: &[HistorySnapshot],
+    identity_aliases: &BTreeMap<String, String>,
+) -> BTreeSet<String> {
     let Some(latest_snapshot) = snapshots.last() else {
         return BTreeSet::new();
     };
 
-    let mut final_aliases = BTreeMap::new();
-    for snapshot in snapshots {
-        record_history_renames(&mut final_aliases, &snapshot.status);
-    }
-
     latest_snapshot
         .status
         .tests
         .keys()
-        .map(|test_name| resolve_history_identity(&final_aliases, test_name).to_string())
+        .map(|test_name| resolve_history_identity(identity_aliases, test_name).to_string())
         .collect()
 }
 
@@ -162,79 +756,91 @@ fn resolve_history_identity<'a>(
     current
 }
 
-fn mark_first_appearance(first_seen: &mut BTreeMap<String, ()>, test_name: &str) -> bool {
-    first_seen.insert(test_name.to_string(), ()).is_none()
-}
-
 fn is_grandfathered(
     test_name: &str,
-    snapshot_commit: &str,
-    first_snapshot_commit: Option<&str>,
-    per_test_baselines: &BTreeMap<String, String>,
-    commit_index: &BTreeMap<&str, usize>,
+    snapshot: usize,
+    first_snapshot: usize,
+    grandfather: &GrandfatherPatterns,
 ) -> bool {
-    is_gatekeeper(test_name)
-        || first_snapshot_commit.is_some_and(|first| snapshot_commit == first)
-        || is_grandfathered_by_per_test_baseline(
-            test_name,
-            snapshot_commit,
-            per_test_baselines,
-            commit_index,
-        )
+    is_gatekeeper(test_name) || grandfather.matches(test_name) || snapshot == first_snapshot
 }
 
 fn is_gatekeeper(test_name: &str) -> bool {
     test_name.ends_with(GATEKEEPER_TEST_NAME)
 }
 
+/// Whether `test_name` has a per-test baseline that `snapshot` is at or
+/// descends from. A baseline commit without a snapshot covers every
+/// snapshot.
 fn is_grandfathered_by_per_test_baseline(
     test_name: &str,
-    snapshot_commit: &str,
+    snapshot: usize,
     per_test_baselines: &BTreeMap<String, String>,
-    commit_index: &BTreeMap<&str, usize>,
+    dag: &SnapshotDag<'_>,
 ) -> bool {
     per_test_baselines
         .get(test_name)
-        .is_some_and(|baseline_commit| {
-            let snapshot_idx = commit_index.get(snapshot_commit);
-            let baseline_idx = commit_index.get(baseline_commit.as_str());
-            match (snapshot_idx, baseline_idx) {
-                (Some(&snapshot_idx), Some(&baseline_idx)) => snapshot_idx >= baseline_idx,
-                (Some(_), None) => true,
-                _ => false,
-            }
-        })
+        .is_some_and(
+            |baseline_commit| match dag.index.get(baseline_commit.as_str()) {
+                Some(&baseline) => {
+                    baseline == snapshot || dag.ancestors(snapshot).contains(&baseline)
+                }
+                None => true,
+            },
+        )
 }
 
 /// Convenience: collect snapshots and check them in one call.
 /// Used by existing callers that don't need the split.
-pub fn check_history(repo_path: &Path) -> Result<Vec<HistoryViolation>, git2::Error> {
-    let snapshots = collect_history_snapshots(repo_path)?;
+pub fn check_history(repo_path: &Path) -> Result<Vec<HistoryViolation>, RatchetError> {
+    let snapshots = collect_history_snapshots(repo_path, "HEAD")?;
     Ok(check_history_snapshots(&snapshots))
 }
 
 /// Read .test-status.json from a specific commit's tree.
-fn status_file_at_commit(
+pub(crate) fn status_file_at_commit(
     repo: &git2::Repository,
     oid: git2::Oid,
+    status_path: &Path,
 ) -> Result<Option<StatusFile>, git2::Error> {
     let commit = repo.find_commit(oid)?;
-    let tree = commit.tree()?;
-
-    let entry = match tree.get_name(".test-status.json") {
-        Some(e) => e,
-        None => return Ok(None),
-    };
+    match status_blob_at_commit(&commit, status_path)? {
+        Some(blob) => parse_status_blob(repo, blob, oid, status_path).map(Some),
+        None => Ok(None),
+    }
+}
 
-    let blob = repo.find_blob(entry.id())?;
-    let content = std::str::from_utf8(blob.content())
-        .map_err(|e| git2::Error::from_str(&format!("Invalid UTF-8 in .test-status.json: {e}")))?;
+/// The blob holding the status file at `status_path` in `commit`'s tree.
+fn status_blob_at_commit(
+    commit: &git2::Commit<'_>,
+    status_path: &Path,
+) -> Result<Option<git2::Oid>, git2::Error> {
+    match commit.tree()?.get_path(status_path) {
+        Ok(entry) => Ok(Some(entry.id())),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
 
-    match StatusFile::parse_historical_from_str(content, Path::new(".test-status.json")) {
-        Ok(sf) => Ok(Some(sf)),
+/// Parse the status file in `blob`, read at commit `oid`.
+fn parse_status_blob(
+    repo: &git2::Repository,
+    blob: git2::Oid,
+    oid: git2::Oid,
+    status_path: &Path,
+) -> Result<StatusFile, git2::Error> {
+    let blob = repo.find_blob(blob)?;
+    let content = std::str::from_utf8(blob.content()).map_err(|e| {
+        git2::Error::from_str(&format!("Invalid UTF-8 in {}: {e}", status_path.display()))
+    })?;
+
+    match StatusFile::parse_historical_from_str(content, status_path) {
+        Ok(sf) => Ok(sf),
         Err(e) => Err(git2::Error::from_str(&format!(
-            "Failed to parse .test-status.json at {}: {}",
-            oid, e
+            "Failed to parse {} at {}: {}",
+            status_path.display(),
+            oid,
+            e
         ))),
     }
 }
@@ -0,0 +1,115 @@
+// Event hooks: commands from `[hooks]` in `ratchet.toml`, run after
+// evaluation with the event as JSON on stdin, so teams can wire up ticket
+// updates or notifications without changing the ratchet.
+
+use crate::audit::AuditViolation;
+use crate::error::{EvaluationError, RatchetError};
+use crate::ratchet::EvalResult;
+use crate::status::{TestState, TrackedStatus};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Something a run did that a hook can react to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum HookEvent {
+    /// Tests that went from pending to passing.
+    Promotion { tests: Vec<String> },
+    /// The run's violations.
+    Violation { violations: Vec<AuditViolation> },
+    /// Tests tracked for the first time, as pending.
+    NewPending { tests: Vec<String> },
+}
+
+impl HookEvent {
+    /// The `[hooks]` key that names this event's command.
+    pub fn key(&self) -> &'static str {
+        match self {
+            HookEvent::Promotion { .. } => "on-promotion",
+            HookEvent::Violation { .. } => "on-violation",
+            HookEvent::NewPending { .. } => "on-new-pending",
+        }
+    }
+}
+
+/// The events of a run that started from the committed status `before`.
+/// Events with nothing in them are left out. Pure function — no IO.
+pub fn hook_events(before: &TrackedStatus, result: &EvalResult) -> Vec<HookEvent> {
+    let state_before = |test: &str| before.tests.get(test).map(|entry| entry.state());
+    let after = &result.updated.tests;
+    let promoted: Vec<String> = after
+        .iter()
+        .filter(|(test, entry)| {
+            entry.state() == TestState::Passing && state_before(test) == Some(TestState::Pending)
+        })
+        .map(|(test, _)| test.clone())
+        .collect();
+    let new_pending: Vec<String> = after
+        .iter()
+        .filter(|(test, entry)| entry.state() == TestState::Pending && state_before(test).is_none())
+        .map(|(test, _)| test.clone())
+        .collect();
+    let violations: Vec<AuditViolation> =
+        result.violations.iter().map(AuditViolation::of).collect();
+
+    let mut events = Vec::new();
+    if !promoted.is_empty() {
+        events.push(HookEvent::Promotion { tests: promoted });
+    }
+    if !violations.is_empty() {
+        events.push(HookEvent::Violation { violations });
+    }
+    if !new_pending.is_empty() {
+        events.push(HookEvent::NewPending { tests: new_pending });
+    }
+    events
+}
+
+/// What a hook reads on stdin.
+#[derive(Serialize)]
+struct HookPayload<'a> {
+    #[serde(flatten)]
+    event: &'a HookEvent,
+    /// Commit checked out during the run, if any.
+    head: Option<&'a str>,
+}
+
+/// Run `command` in `dir` with `event` as JSON on stdin. Its output goes to
+/// stderr, so it never mixes with a report on stdout.
+pub fn run_hook(
+    command: &[String],
+    dir: &Path,
+    event: &HookEvent,
+    head: Option<&str>,
+) -> Result<(), RatchetError> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| EvaluationError::EmptyCommand(format!("{} hook", event.key())))?;
+    let payload = serde_json::to_string(&HookPayload { event, head })
+        .expect("hook payloads have only string keys");
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::from(io::stderr()))
+        .spawn()
+        .map_err(|e| RatchetError::command(program, e))?;
+    let mut stdin = child.stdin.take().expect("child stdin should be piped");
+    // A hook that ignores its input may exit before reading it.
+    match stdin.write_all(payload.as_bytes()) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            return Err(RatchetError::command(program, e));
+        }
+        _ => {}
+    }
+    drop(stdin);
+    let status = child
+        .wait()
+        .map_err(|e| RatchetError::command(program, e))?;
+    if !status.success() {
+        return Err(RatchetError::command_exit(program, status, &[]));
+    }
+    Ok(())
+}
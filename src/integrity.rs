@@ -0,0 +1,242 @@
+// Status file integrity: a chained fingerprint over the tracked entries and
+// the global baseline and renames, so hand edits to committed status show up
+// when history is checked.
+//
+// The fingerprint is tamper-evident, not tamper-proof: anyone can recompute
+// it. It catches edits made without the ratchet, and deliberate edits must be
+// declared with an override reason that the report surfaces.
+
+use crate::history::HistorySnapshot;
+use crate::status::{Integrity, StatusFile};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Fingerprint format written by [`seal`]. Version 1 covered test names,
+/// states, and baselines; version 2 adds quarantine marks; version 3 adds
+/// `allow_ignore`, accepted failures, and the global baseline and renames.
+pub const SEAL_VERSION: u32 = 3;
+
+/// Fingerprint of `status` chained onto the digest of the status it was
+/// derived from, in the current [`SEAL_VERSION`]. Everything that changes
+/// how a test is evaluated is covered; timestamps and metadata may be edited
+/// freely.
+pub fn fingerprint(previous: Option<&str>, status: &StatusFile) -> String {
+    fingerprint_version(SEAL_VERSION, previous, status)
+}
+
+/// [`fingerprint`] in the format of seal `version`, to check seals written
+/// before the current one.
+pub fn fingerprint_version(version: u32, previous: Option<&str>, status: &StatusFile) -> String {
+    let mut canonical = format!("tdd-ratchet integrity v{version}\n");
+    canonical.push_str(&format!("previous {}\n", previous.unwrap_or("-")));
+    if version >= 3 {
+        canonical.push_str(&format!(
+            "baseline {}\nrenames {}\n",
+            status.baseline.as_deref().unwrap_or("-"),
+            json(&status.renames)
+        ));
+    }
+    for (name, entry) in &status.tests {
+        canonical.push_str(&format!(
+            "{} {} {}",
+            json(name),
+            entry.state,
+            entry.baseline().unwrap_or("-")
+        ));
+        if version >= 2 {
+            canonical.push_str(&format!(" {}", json(&entry.quarantine)));
+        }
+        if version >= 3 {
+            canonical.push_str(&format!(
+                " {} {}",
+                entry.allow_ignore,
+                json(&entry.accepted_failure)
+            ));
+        }
+        canonical.push('\n');
+    }
+    git2::Oid::hash_object(git2::ObjectType::Blob, canonical.as_bytes())
+        .expect("hashing a buffer does not touch the repository")
+        .to_string()
+}
+
+fn json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("status fields serialize")
+}
+
+/// A seal for `status` derived from the status with digest `previous`.
+///
+/// A run only seals fields it took from the committed status or changed
+/// itself; the working tree can withdraw a grant but never add one. Commands
+/// that grant one, like `rebaseline`, seal with an override reason instead.
+pub fn seal(previous: Option<&str>, status: &StatusFile) -> Integrity {
+    Integrity {
+        previous: previous.map(str::to_string),
+        digest: fingerprint(previous, status),
+        version: SEAL_VERSION,
+        override_reason: None,
+    }
+}
+
+/// The digest new status should chain onto: the newest committed seal.
+pub fn latest_digest(snapshots: &[HistorySnapshot]) -> Option<&str> {
+    snapshots
+        .last()
+        .and_then(|s| s.status.integrity.as_ref())
+        .map(|i| i.digest.as_str())
+}
+
+/// Why a committed status failed the integrity check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TamperProblem {
+    /// The entries do not match the recorded digest.
+    DigestMismatch,
+    /// The seal chains from a digest that appears nowhere earlier in history.
+    BrokenChain,
+    /// An earlier commit was sealed but this one is not.
+    SealRemoved,
+    /// The seal uses an older fingerprint format than an earlier commit's,
+    /// one that covers fewer fields.
+    Downgraded,
+}
+
+impl fmt::Display for TamperProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TamperProblem::DigestMismatch => {
+                write!(f, "tracked entries do not match their recorded fingerprint")
+            }
+            TamperProblem::BrokenChain => {
+                write!(
+                    f,
+                    "fingerprint does not chain from any earlier committed status"
+                )
+            }
+            TamperProblem::SealRemoved => write!(f, "integrity fingerprint was removed"),
+            TamperProblem::Downgraded => {
+                write!(f, "fingerprint uses an older format than earlier history")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityFinding {
+    Tampered {
+        commit: String,
+        problem: TamperProblem,
+    },
+    /// A snapshot declares a deliberate manual edit.
+    Overridden { commit: String, reason: String },
+}
+
+/// Check that every sealed snapshot's fingerprint matches its entries and
+/// chains from an earlier snapshot. Pure function — no IO.
+///
+/// Snapshots from before the first seal are not checked. An override seal
+/// acknowledges everything up to and including its commit; checking resumes
+/// after it. Every override seal in `snapshots` is reported, not only one at
+/// the newest snapshot. Seals in an older format are checked in that format,
+/// until a newer one appears: the next ratchet run reseals in the current
+/// format.
+pub fn check_integrity(snapshots: &[HistorySnapshot]) -> Vec<IntegrityFinding> {
+    let mut findings = Vec::new();
+    let mut known: BTreeSet<&str> = BTreeSet::new();
+    let mut newest_version = 0;
+    let mut previous: Option<&HistorySnapshot> = None;
+
+    // Commits that did not touch the status file repeat its seal, so each
+    // override is reported once, at the commit that made it.
+    let mut last_seal = None;
+    for snapshot in snapshots {
+        let seal = snapshot.status.integrity.as_ref();
+        if let Some(integrity) = seal
+            && let Some(reason) = &integrity.override_reason
+            && last_seal != Some(integrity)
+        {
+            findings.push(IntegrityFinding::Overridden {
+                commit: snapshot.commit.clone(),
+                reason: reason.clone(),
+            });
+        }
+        last_seal = seal;
+    }
+
+    let override_index = snapshots.iter().rposition(|s| {
+        s.status
+            .integrity
+            .as_ref()
+            .is_some_and(|i| i.override_reason.is_some())
+    });
+    let checked = match override_index {
+        Some(index) => {
+            let snapshot = &snapshots[index];
+            let integrity = snapshot
+                .status
+                .integrity
+                .as_ref()
+                .expect("override is sealed");
+            known.insert(integrity.digest.as_str());
+            newest_version = integrity.version;
+            previous = Some(snapshot);
+            &snapshots[index + 1..]
+        }
+        None => snapshots,
+    };
+
+    for snapshot in checked {
+        let status = &snapshot.status;
+        // Commits that did not touch the status file repeat the same seal;
+        // edits to uncovered fields (timestamps, metadata) keep it too.
+        let same_seal = previous.is_some_and(|p| p.status.integrity == status.integrity);
+        let unchanged = same_seal
+            && previous.is_some_and(|p| {
+                p.status.tests == status.tests
+                    && p.status.baseline == status.baseline
+                    && p.status.renames == status.renames
+            });
+        previous = Some(snapshot);
+        if unchanged {
+            continue;
+        }
+
+        let problem = match &status.integrity {
+            None if known.is_empty() => None,
+            None => Some(TamperProblem::SealRemoved),
+            Some(integrity) => {
+                let problem = if integrity.version < newest_version {
+                    Some(TamperProblem::Downgraded)
+                } else if fingerprint_version(
+                    integrity.version,
+                    integrity.previous.as_deref(),
+                    status,
+                ) != integrity.digest
+                {
+                    Some(TamperProblem::DigestMismatch)
+                } else if !same_seal
+                    && !known.is_empty()
+                    && !integrity
+                        .previous
+                        .as_deref()
+                        .is_some_and(|p| known.contains(p))
+                {
+                    Some(TamperProblem::BrokenChain)
+                } else {
+                    None
+                };
+                known.insert(integrity.digest.as_str());
+                newest_version = newest_version.max(integrity.version);
+                problem
+            }
+        };
+        if let Some(problem) = problem {
+            findings.push(IntegrityFinding::Tampered {
+                commit: snapshot.commit.clone(),
+                problem,
+            });
+        }
+    }
+
+    findings
+}
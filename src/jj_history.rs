@@ -0,0 +1,116 @@
+// History inspection for Jujutsu (`jj`) repositories colocated with git.
+//
+// jj auto-commits the working copy as `@`, so the working tree's edits to the
+// status file already live in a commit. Walking from that commit would treat
+// uncommitted status changes as history. Instead the tip is resolved through
+// `jj` (by default `@-`, the parent of the working-copy commit) and the
+// colocated git repository is walked from there.
+
+use crate::error::RatchetError;
+use crate::history::{GitHistory, HistoryProvider, HistorySnapshot};
+use crate::status::{STATUS_FILE_NAME, StatusFile};
+use crate::test_edits::TestEdit;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Revset of the newest commit that is not jj's working copy.
+pub const DEFAULT_TIP_REVSET: &str = "@-";
+
+/// Reads history from a jj repository colocated with git.
+#[derive(Debug, Clone)]
+pub struct JjHistory {
+    repo_path: PathBuf,
+    status_path: PathBuf,
+    tip: String,
+    program: PathBuf,
+}
+
+impl JjHistory {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        JjHistory {
+            repo_path: repo_path.into(),
+            status_path: PathBuf::from(STATUS_FILE_NAME),
+            tip: DEFAULT_TIP_REVSET.to_string(),
+            program: PathBuf::from("jj"),
+        }
+    }
+
+    /// Read the status file at `status_path`, relative to the repository
+    /// root, instead of `.test-status.json`.
+    pub fn with_status_path(mut self, status_path: impl Into<PathBuf>) -> Self {
+        self.status_path = status_path.into();
+        self
+    }
+
+    /// Walk history from the commit `revset` resolves to instead of `@-`.
+    pub fn with_start(mut self, revset: impl Into<String>) -> Self {
+        self.tip = revset.into();
+        self
+    }
+
+    /// Run `program` instead of the `jj` on `PATH`.
+    pub fn with_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// The git commit id of the tip. `--ignore-working-copy` keeps `jj` from
+    /// snapshotting the working tree, which would rewrite `@` as a side
+    /// effect of reading history.
+    pub fn tip_commit(&self) -> Result<String, RatchetError> {
+        let output = Command::new(&self.program)
+            .args([
+                "log",
+                "--no-graph",
+                "--ignore-working-copy",
+                "--limit",
+                "1",
+                "-r",
+                &self.tip,
+                "-T",
+                "commit_id",
+            ])
+            .current_dir(&self.repo_path)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| git2::Error::from_str(&format!("failed to run jj: {e}")))?;
+        if !output.status.success() {
+            return Err(git2::Error::from_str(&format!(
+                "cannot resolve jj revset `{}`: {}",
+                self.tip,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+            .into());
+        }
+        let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if commit.is_empty() {
+            return Err(git2::Error::from_str(&format!(
+                "jj revset `{}` matched no commit",
+                self.tip
+            ))
+            .into());
+        }
+        Ok(commit)
+    }
+
+    /// The colocated git history, starting at the tip.
+    fn git_history(&self) -> Result<GitHistory, RatchetError> {
+        Ok(GitHistory::new(&self.repo_path)
+            .with_status_path(&self.status_path)
+            .with_start(self.tip_commit()?))
+    }
+}
+
+impl HistoryProvider for JjHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        self.git_history()?.head_status()
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        self.git_history()?.snapshots()
+    }
+
+    fn edited_tests(&self, commit: &str, tests: &[String]) -> Result<Vec<TestEdit>, RatchetError> {
+        GitHistory::new(&self.repo_path).edited_tests(commit, tests)
+    }
+}
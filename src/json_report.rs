@@ -0,0 +1,80 @@
+// JSON output (`--format json`) for build systems and scripts, and the
+// evaluation object the daemon returns for `evaluate`.
+//
+// Each evaluation has `ok`, the passing/pending/draft counts, the
+// violations with their codes, the number of warnings, every tracked test's
+// state after the run, and the text report for humans.
+
+use crate::errors::{RootResult, format_report, violation_summary};
+use crate::ratchet::EvalResult;
+use crate::status::TestState;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// The evaluation of one run as a JSON object. `deny_warnings` counts
+/// warnings as failures for `ok`, as `--deny warnings` does.
+pub fn evaluation_json(result: &EvalResult, deny_warnings: bool) -> Value {
+    let violations: Vec<Value> = result
+        .violations
+        .iter()
+        .map(|violation| {
+            let (rule, subject, detail) = violation_summary(violation);
+            json!({
+                "code": violation.code(),
+                "rule": rule,
+                "subject": subject,
+                "detail": detail,
+            })
+        })
+        .collect();
+    let count = |state| {
+        result
+            .updated
+            .tests
+            .values()
+            .filter(|entry| entry.state() == state)
+            .count()
+    };
+    let tests: BTreeMap<&String, TestState> = result
+        .updated
+        .tests
+        .iter()
+        .map(|(name, entry)| (name, entry.state()))
+        .collect();
+    json!({
+        "ok": !result.is_failure(deny_warnings),
+        "passing": count(TestState::Passing),
+        "pending": count(TestState::Pending),
+        "drafts": count(TestState::Draft),
+        "violations": violations,
+        "warnings": result.warnings.len(),
+        "tests": tests,
+        "report": format_report(result),
+    })
+}
+
+/// `--format json` for a single-root run.
+pub fn format_json(result: &EvalResult, deny_warnings: bool) -> String {
+    pretty(&evaluation_json(result, deny_warnings))
+}
+
+/// `--format json` for a multi-root run: `ok` for the whole run, then each
+/// root's evaluation with its `root` directory.
+pub fn format_json_roots(roots: &[RootResult], deny_warnings: bool) -> String {
+    let evaluations: Vec<Value> = roots
+        .iter()
+        .map(|root| {
+            let mut evaluation = evaluation_json(&root.result, deny_warnings);
+            evaluation["root"] = json!(root.root);
+            evaluation
+        })
+        .collect();
+    pretty(&json!({
+        "ok": roots.iter().all(|root| !root.result.is_failure(deny_warnings)),
+        "roots": evaluations,
+    }))
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).expect("JSON values serialize") + "\n"
+}
@@ -0,0 +1,181 @@
+// Summary of the previous ratchet run, kept in an untracked sidecar
+// (`.ratchet-last-run.json`) so each run can report what changed since the
+// last one without touching the committed status file.
+
+use crate::error::{ParseError, RatchetError};
+use crate::ratchet::{EvalResult, Violation, Warning};
+use crate::runner::{TestOutcome, TestResult};
+use crate::status::TestState;
+use crate::timeline::short_commit;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+pub const LAST_RUN_FILE_NAME: &str = ".ratchet-last-run.json";
+
+/// Compact record of one run's outcome.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub recorded_at: i64,
+    /// Commit checked out during the run, if any.
+    pub head: Option<String>,
+    pub passing: usize,
+    /// Pending tests by name, so the next run can tell which were promoted.
+    pub pending: BTreeSet<String>,
+    pub regressions: usize,
+    /// Violations of every kind, regressions included.
+    #[serde(default)]
+    pub violations: usize,
+    /// Sum of the tests' recorded execution times in milliseconds; `None`
+    /// when no test was timed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suite_time_ms: Option<u64>,
+    /// Drafts whose latest run failed; only these may be promoted. See
+    /// [`failing_drafts`].
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub failing_drafts: BTreeSet<String>,
+}
+
+/// What changed between the previous run and this one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunDelta {
+    /// Change in the number of pending tests.
+    pub pending: i64,
+    /// Tests pending last run and passing now.
+    pub promoted: usize,
+    /// Regressions in this run.
+    pub regressions: usize,
+    /// The previous run's commit, when it differs from this run's.
+    pub previous_head: Option<String>,
+}
+
+impl fmt::Display for RunDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "since last run: {:+} pending, {} promoted, {} regression{}",
+            self.pending,
+            self.promoted,
+            self.regressions,
+            if self.regressions == 1 { "" } else { "s" }
+        )?;
+        if let Some(head) = &self.previous_head {
+            write!(f, " (last run at {})", short_commit(head))?;
+        }
+        Ok(())
+    }
+}
+
+/// Summarize an evaluation. Pure function — no IO.
+pub fn summarize(head: Option<&str>, result: &EvalResult, now: i64) -> RunSummary {
+    let tests = &result.updated.tests;
+    RunSummary {
+        recorded_at: now,
+        head: head.map(str::to_string),
+        passing: tests
+            .values()
+            .filter(|entry| entry.state() == TestState::Passing)
+            .count(),
+        pending: tests
+            .iter()
+            .filter(|(_, entry)| entry.state() == TestState::Pending)
+            .map(|(name, _)| name.clone())
+            .collect(),
+        regressions: result
+            .violations
+            .iter()
+            .filter(|v| matches!(v, Violation::Regression { .. }))
+            .count(),
+        violations: result.violations.len(),
+        suite_time_ms: tests
+            .values()
+            .filter_map(|entry| entry.exec_time_ms)
+            .reduce(|total, ms| total + ms),
+        failing_drafts: BTreeSet::new(),
+    }
+}
+
+/// The drafts whose most recent observed outcome is a failure: those that
+/// failed in `results`, and those the `previous` run saw failing that this
+/// run did not run. Pure function — no IO.
+pub fn failing_drafts(
+    previous: Option<&RunSummary>,
+    result: &EvalResult,
+    results: &[TestResult],
+) -> BTreeSet<String> {
+    result
+        .updated
+        .tests
+        .iter()
+        .filter(|(_, entry)| entry.state() == TestState::Draft)
+        .map(|(name, _)| name)
+        .filter(|name| {
+            let mut outcomes = results.iter().filter(|r| &r.name == *name);
+            match outcomes.next() {
+                Some(first) => {
+                    first.outcome == TestOutcome::Failed
+                        && outcomes.all(|r| r.outcome == TestOutcome::Failed)
+                }
+                None => previous.is_some_and(|p| p.failing_drafts.contains(*name)),
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// The change from the `previous` run to this one, at `head`.
+pub fn run_delta(previous: &RunSummary, head: Option<&str>, result: &EvalResult) -> RunDelta {
+    let current = summarize(head, result, previous.recorded_at);
+    RunDelta {
+        pending: current.pending.len() as i64 - previous.pending.len() as i64,
+        promoted: previous
+            .pending
+            .iter()
+            .filter(|test| {
+                result
+                    .updated
+                    .tests
+                    .get(*test)
+                    .is_some_and(|entry| entry.state() == TestState::Passing)
+            })
+            .count(),
+        regressions: current.regressions,
+        previous_head: previous
+            .head
+            .clone()
+            .filter(|head| current.head.as_ref() != Some(head)),
+    }
+}
+
+/// A [`Warning::SuiteSlower`] when the suite took more than `max_growth`
+/// percent longer in `current` than in `previous`. Pure function — no IO.
+pub fn suite_slowdown(
+    previous: &RunSummary,
+    current: &RunSummary,
+    max_growth: u32,
+) -> Option<Warning> {
+    let (previous_ms, current_ms) = (previous.suite_time_ms?, current.suite_time_ms?);
+    let limit = previous_ms + previous_ms * u64::from(max_growth) / 100;
+    (previous_ms > 0 && current_ms > limit).then_some(Warning::SuiteSlower {
+        previous_ms,
+        current_ms,
+        max_growth,
+    })
+}
+
+/// Read the last run's summary. A missing or unreadable file has none: the
+/// sidecar is a convenience, never a reason to fail a run.
+pub fn load_last_run(path: &Path) -> Option<RunSummary> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Write `summary` as the last run.
+pub fn save_last_run(path: &Path, summary: &RunSummary) -> Result<(), RatchetError> {
+    let json = serde_json::to_string_pretty(summary).map_err(|source| ParseError::Sidecar {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    std::fs::write(path, json + "\n").map_err(|e| RatchetError::io(path, e))
+}
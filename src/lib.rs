@@ -1,5 +1,62 @@
+pub mod accepted_failures;
+pub mod affected;
+pub mod audit;
+pub mod badge;
+pub mod codeowners;
+pub mod config;
+pub mod coverage;
+pub mod daemon;
+pub mod dashboard;
+pub mod discovery;
+pub mod error;
 pub mod errors;
+pub mod explain;
+pub mod export;
+pub mod gatekeeper;
+pub mod gc;
+pub mod git_cli;
+#[cfg(feature = "gix")]
+pub mod gix_history;
+pub mod grandfather;
 pub mod history;
+pub mod hooks;
+pub mod integrity;
+pub mod jj_history;
+pub mod json_report;
+pub mod last_run;
+pub mod logging;
+pub mod merge_queue;
+pub mod metrics;
+pub mod overrides;
+pub mod parameterized;
+pub mod partition;
+pub mod pipeline;
+pub mod plugins;
+pub mod quarantine;
 pub mod ratchet;
+pub mod repair;
+pub mod replay;
+pub mod result_cache;
+pub mod run_filter;
+pub mod run_lock;
 pub mod runner;
+pub mod sarif;
+pub mod schema;
+pub mod self_update;
+pub mod staged;
+pub mod stale;
+pub mod state_ref;
+pub mod stats;
 pub mod status;
+pub mod status_diff;
+pub mod status_server;
+pub mod test_edits;
+pub mod test_locations;
+pub mod timeline;
+pub mod toolchain;
+pub mod trend;
+pub mod trivial_tests;
+pub mod watch;
+
+pub use error::RatchetError;
+pub use pipeline::Ratchet;
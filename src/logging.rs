@@ -0,0 +1,80 @@
+// Diagnostic logging: the runner invocations, history walk and evaluation
+// decisions are `tracing` spans and events. Nothing is logged unless asked
+// for with `--log-level` or `RUST_LOG`; `--log-file` also writes the run's
+// events as JSON lines, for debugging behavior seen only in CI.
+
+use crate::error::RatchetError;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use thiserror::Error;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::ParseError;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, fmt};
+
+/// Why logging could not be set up.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum LoggingError {
+    #[error("invalid log level `{directives}`: {source}")]
+    InvalidLevel {
+        directives: String,
+        #[source]
+        source: ParseError,
+    },
+    /// Another subscriber was installed first.
+    #[error(transparent)]
+    AlreadySet(#[from] tracing_subscriber::util::TryInitError),
+}
+
+/// What the log file records when no level is given.
+pub const DEFAULT_LOG_FILE_FILTER: &str = "tdd_ratchet=debug,cargo_ratchet=debug";
+
+/// The filters for stderr and the log file, as `RUST_LOG` directives:
+/// `--log-level` wins over `RUST_LOG`. Without either, stderr logs nothing
+/// and the log file records [`DEFAULT_LOG_FILE_FILTER`]. Pure function — no
+/// IO.
+pub fn log_filters(flag: Option<&str>, env: Option<&str>) -> (String, String) {
+    match flag.or(env).filter(|level| !level.trim().is_empty()) {
+        Some(level) => (level.to_string(), level.to_string()),
+        None => ("off".to_string(), DEFAULT_LOG_FILE_FILTER.to_string()),
+    }
+}
+
+/// Install the global subscriber: human-readable events on stderr and, with
+/// `log_file`, JSON lines in that file, which is truncated first.
+pub fn init(flag: Option<&str>, log_file: Option<&Path>) -> Result<(), RatchetError> {
+    let env = std::env::var(EnvFilter::DEFAULT_ENV).ok();
+    let (stderr_filter, file_filter) = log_filters(flag, env.as_deref());
+    let parse = |directives: &str| {
+        EnvFilter::try_new(directives).map_err(|source| LoggingError::InvalidLevel {
+            directives: directives.to_string(),
+            source,
+        })
+    };
+    let stderr = fmt::layer()
+        .with_writer(io::stderr)
+        .with_filter(parse(&stderr_filter)?);
+    let file = match log_file {
+        Some(path) => Some(
+            fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .with_writer(Mutex::new(
+                    File::create(path).map_err(|e| RatchetError::io(path, e))?,
+                ))
+                .with_filter(parse(&file_filter)?),
+        ),
+        None => None,
+    };
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .try_init()
+        .map_err(LoggingError::from)?;
+    Ok(())
+}
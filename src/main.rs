@@ -1,27 +1,268 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::path::Path;
-use std::process::{self, Command, Stdio};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
 
-use tdd_ratchet::errors::format_report;
-use tdd_ratchet::history::{collect_history_snapshots, read_head_status};
-use tdd_ratchet::ratchet::evaluate;
-use tdd_ratchet::runner::{TestOutcome, TestResult, parse_nextest_output};
+use tdd_ratchet::Ratchet;
+use tdd_ratchet::RatchetError;
+use tdd_ratchet::accepted_failures::is_valid_date;
+use tdd_ratchet::affected::{
+    Affected, DEFAULT_AFFECTED_TARGETS, affected_packages, changed_paths, target_directory,
+    workspace_packages,
+};
+use tdd_ratchet::audit::{
+    AuditRecord, append_record, format_audit, read_audit, record_overrides, run_record,
+};
+use tdd_ratchet::badge::{BadgeFormat, build_badge, format_shields_json, format_svg};
+use tdd_ratchet::codeowners::{Codeowners, assign_owners, retain_owned};
+use tdd_ratchet::config::{RatchetConfig, RootConfig, Rule, RuleLevel, StateStorage, TimingConfig};
+use tdd_ratchet::coverage::check_promoted_coverage;
+use tdd_ratchet::daemon::Daemon;
+use tdd_ratchet::dashboard::{build_dashboard, render_dashboard};
+use tdd_ratchet::discovery::{discover_project_dir, locate_in_repo};
+use tdd_ratchet::errors::{
+    ReportFormat, ReportOptions, RootResult, format_markdown_report, format_markdown_roots_report,
+    format_report_with, format_roots_report_with,
+};
+use tdd_ratchet::explain::{EXPLANATIONS, explain, format_explanation};
+use tdd_ratchet::export::{ExportFormat, export_csv};
+use tdd_ratchet::gatekeeper::{
+    GATEKEEPER_PATH, Scaffold, gatekeeper_source, scaffold_gatekeeper_with,
+};
+use tdd_ratchet::gc::{DEFAULT_RETENTION_DAYS, GcChange, apply_gc, gather_gc_evidence, plan_gc};
+use tdd_ratchet::history::{
+    GitHistory, HistoryProvider, HistorySnapshot, commits_in_range, head_commit, resolve_baseline,
+};
+use tdd_ratchet::hooks::{hook_events, run_hook};
+use tdd_ratchet::integrity::{latest_digest, seal};
+use tdd_ratchet::json_report::{format_json, format_json_roots};
+use tdd_ratchet::last_run::{
+    LAST_RUN_FILE_NAME, failing_drafts, load_last_run, run_delta, save_last_run, suite_slowdown,
+    summarize,
+};
+use tdd_ratchet::logging;
+use tdd_ratchet::merge_queue::{MergeBase, MergedTree, merge_base};
+use tdd_ratchet::metrics::{SINGLE_ROOT, format_metrics};
+use tdd_ratchet::partition::{
+    PartialResults, Partition, RecordedRunner, load_partial_results, load_results_file,
+    merge_partial_results, save_partial_results,
+};
+use tdd_ratchet::plugins::{PluginContext, apply_findings, run_plugins};
+use tdd_ratchet::quarantine::{
+    FLIPS_FILE_NAME, load_flips, observe_flips, quarantine_flaky_tests, save_flips,
+    unchanged_commit,
+};
+use tdd_ratchet::ratchet::{EvalResult, GATEKEEPER_TEST_NAME};
+use tdd_ratchet::repair::{apply_repair, gather_repair_evidence_at, plan_repair};
+use tdd_ratchet::replay::{format_replay, replay};
+use tdd_ratchet::run_filter::RunFilter;
+use tdd_ratchet::run_lock::RunLock;
+use tdd_ratchet::runner::{
+    NextestRunner, RunnerError, TestOutcome, TestResult, TestRunner, merge_retries,
+    parse_nextest_events_stream,
+};
+use tdd_ratchet::sarif::format_sarif;
+use tdd_ratchet::schema::{current_schema, validate_status_json};
+use tdd_ratchet::self_update::{is_newer, latest_release, update};
+use tdd_ratchet::staged::StagedTree;
+use tdd_ratchet::stale::{
+    StaleFormat, StaleOptions, format_stale_markdown, format_stale_text, stale_report,
+};
+use tdd_ratchet::state_ref::{RefStatusStore, STATE_REF, write_state};
+use tdd_ratchet::stats::{compute_stats_with_audit, format_stats};
 use tdd_ratchet::status::{
-    StatusFile, TestEntry, TestState, TrackedStatus, WorkingTreeInstructions,
+    AcceptedFailure, CURRENT_VERSION, STATUS_FILE_NAME, StatusFile, StatusStore, TestEntry,
+    TestState, TrackedStatus, WorkingTreeStatusFile, unix_now,
+};
+use tdd_ratchet::status_diff::{diff_status, format_status_diff, status_at_rev};
+use tdd_ratchet::status_server::StatusEndpoints;
+use tdd_ratchet::test_locations::annotate_violations;
+use tdd_ratchet::timeline::{
+    format_blame, format_timeline, test_timeline, with_recorded_transitions,
+};
+use tdd_ratchet::trend::{
+    TREND_CACHE_FILE_NAME, TrendCache, TrendFormat, collect_trend, trend_csv,
 };
+use tdd_ratchet::trivial_tests::warn_trivial_promotions;
 
-const HELP_TEXT: &str = "Usage: cargo-ratchet [--init] [--help] [--version]\n\nOptions:\n  --init          Initialize .test-status.json from the current test run\n  --help, -h      Print help\n  --version, -V   Print version\n";
+const HELP_TEXT: &str = "\
+Usage: cargo-ratchet [--init] [--help] [--version] [COMMAND]
 
-struct GatheredRun {
-    status: TrackedStatus,
-    instructions: WorkingTreeInstructions,
-    results: Vec<tdd_ratchet::runner::TestResult>,
-    history_snapshots: Vec<tdd_ratchet::history::HistorySnapshot>,
-}
+Commands:
+  tui             Interactive dashboard of tracked tests; re-runs on keypress
+  allow-ignore <TEST>
+                  Let a passing test be #[ignore]d without tripping the
+                  passing-to-ignored rule
+  accept-failure <TEST> --reason <TEXT> (--until <DATE> | --commits <N>)
+                  Tolerate a passing test's failures until DATE
+                  (YYYY-MM-DD) or for N more commits
+  unquarantine <TEST>
+                  Enforce a test quarantined as flaky strictly again
+  draft <TEST>... Mark sketched tests, e.g. with todo!() bodies, as drafts:
+                  not pending, and never promoted while drafts
+  promote-drafts [TEST]...
+                  Move the named drafts, or all of them, to pending
+  gatekeeper print [--handshake]
+                  Print the gatekeeper test for this project; the
+                  handshake version when [gatekeeper] handshake is on
+  stats [--json]  Print TDD hygiene metrics computed from git history
+  report --stale [--older-than <N>] [--expiring-within <N>]
+         [--format text|markdown|json]
+                  Print pending tests older than N days (default 14),
+                  quarantined tests, and accepted failures expiring within
+                  N days (default 7); prints nothing when all is well
+  export [--format csv]
+                  Print one row per commit, test, and state in history
+  trend [--last <N>] [--format json|csv]
+                  Print passing and pending counts at each of the last N
+                  commits with a .test-status.json (default all)
+  log <TEST>      Print every committed state change of one test
+  blame <TEST>    Print a test's state and the commits that added and
+                  promoted it
+  replay <A>..<B>  Replay the history rules at each commit from A to B
+                  (default HEAD) and print where each violation first
+                  appeared
+  diff <REV> [--head]
+                  Print the tests added, promoted, regressed, renamed, and
+                  retired in .test-status.json since REV, up to the
+                  working tree or, with --head, to HEAD
+  explain [CODE]  Describe a violation code (e.g. RATCHET001), or list them
+  audit show [--test <TEST>] [--limit <N>] [--json]
+                  Print the audit trail of runs and overrides
+  migrate         Rewrite .test-status.json in the current format version
+  rebaseline [REV] [--yes]
+                  Move the baseline to REV (default HEAD), grandfathering
+                  everything before it
+  repair [--dry-run]
+                  Fix baselines and pending history after a rebase
+  gc [--retention-days <N>] [--dry-run]
+                  Prune per-test baselines the global baseline covers and
+                  renames older than N days (default 90)
+  reseal --reason <TEXT>
+                  Re-fingerprint a hand-edited .test-status.json, recording why
+  run [--partition <M/N>] --emit-results <FILE>
+                  Run the tests, or one nextest partition of them, and
+                  write the results to FILE without evaluating them
+  check [--staged]
+                  Run the ratchet; with --staged, on a temporary checkout
+                  of the index, e.g. in a pre-commit hook
+  merge-results <FILE>...
+                  Evaluate the combined results of a sharded run
+  eval --stdin    Evaluate libtest-json events piped in from another test
+                  runner instead of running the tests
+  state show      Print the status stored in refs/ratchet/state
+  state import [PATH]
+                  Commit a status file (default .test-status.json) onto
+                  refs/ratchet/state
+  daemon [--socket <PATH>] [--watch]
+                  Serve JSON-RPC requests (evaluate, status, logTest) on
+                  stdio, or on a unix socket, for editor integrations; with
+                  --watch, re-evaluate when files change
+  serve [--host <ADDR>] [--port <PORT>]
+                  Serve read-only JSON status endpoints over HTTP (default
+                  127.0.0.1:8080)
+  badge [--format svg|shields-json] [--out <FILE>]
+                  Render a README badge of passing and pending counts and
+                  the last run's violations
+  self update [--check]
+                  Install the latest release over this binary after
+                  verifying its checksum; with --check, only report whether
+                  one is available
+  schema          Print the JSON Schema for the status file
+  validate [PATH] Validate a status file (default .test-status.json)
+
+Options:
+  --init          Initialize .test-status.json from the current test run
+  --baseline <REV>
+                  With --init, record REV as the baseline commit
+  --with-gatekeeper, --no-gatekeeper
+                  With --init, write tests/gatekeeper.rs unless the run
+                  already has a gatekeeper test (default: --with-gatekeeper)
+  --results-file <FILE>
+                  Evaluate results captured earlier, as nextest libtest-json
+                  or by run --emit-results, instead of running the tests
+  --format <FMT>  Report format: text (default), markdown, json, or sarif
+  --full-output   Show the whole captured output of each regression
+  --allow <RULE>, --deny <RULE>
+                  Turn a rule off or make it an error for this run; rules
+                  are new-test-passed, regression, test-disappeared,
+                  skipped-pending, missing-gatekeeper, passing-to-ignored,
+                  dirty-worktree
+  --deny warnings Fail the run on warnings too
+  --strict        Fail the run when .test-status.json or test files have
+                  uncommitted changes (same as --deny dirty-worktree)
+  --metrics-out <FILE>
+                  Write Prometheus metrics for node_exporter's textfile
+                  collector to FILE
+  --merge-base <REV>
+                  Check the branch as a merge queue builds it: only
+                  commits since the merge base with REV, against the
+                  status merged with REV's
+  --since <REV>   Only check history after REV, e.g. in a pre-push hook;
+                  the baseline is left alone
+  --owner <OWNER> Only report violations in test files OWNER owns in
+                  CODEOWNERS, e.g. @org/backend; the status file is
+                  still updated for every test
+  --test <BINARY> Only run and check the tests of one test binary, e.g.
+                  integration or my-crate::integration; repeatable. Other
+                  binaries' tests are kept as they are
+  -p, --package <PACKAGE>
+                  Only run and check the tests of one package; repeatable
+  --affected      Only run and check the packages changed since the
+                  merge base (with --merge-base, or origin/HEAD, main
+                  or master), uncommitted changes included, and the
+                  packages depending on them
+  --no-cache      Rerun every test binary instead of reusing cached
+                  results ([cache] enabled in ratchet.toml)
+  --profile <NAME>
+                  Run nextest with this profile from
+                  .config/nextest.toml ([nextest] profile in
+                  ratchet.toml)
+  --release       Build and run the tests in release mode
+  --target <TRIPLE>
+                  Build and run the tests for TRIPLE; with [state]
+                  per-target, its tests are tracked in
+                  .test-status.<TRIPLE>.json
+  -E, --filterset <EXPR>
+                  Only run and check the tests a nextest filterset
+                  selects; repeatable. Tests the filters provably leave
+                  out are kept as they are, and the run is reported as
+                  partial
+  --wait          Wait for another ratchet run in this project to finish
+                  instead of failing
+  --log-level <LEVEL>
+                  Log runner invocations, the history walk and
+                  evaluation decisions to stderr, e.g. debug or
+                  tdd_ratchet=trace (overrides RUST_LOG)
+  --log-file <FILE>
+                  Also write the run's log to FILE as JSON lines, at
+                  debug level unless --log-level or RUST_LOG say
+                  otherwise
+  --project-dir <DIR>
+                  Check the project in DIR. By default the project is
+                  the nearest directory up to the repository root with
+                  a .test-status.json or ratchet.toml, else the
+                  repository root, so any subdirectory works. Other
+                  paths on the command line stay relative to the
+                  current directory
+  --help, -h      Print help
+  --version, -V   Print version
+
+Exit status: 0 ok, 1 violations or errors, 2 usage error, 3 build failed
+";
+
+/// Exit code when the tests could not be built, so nothing was checked.
+const EXIT_BUILD_FAILED: i32 = 3;
+
+/// How many promotions the dashboard lists.
+const DASHBOARD_PROMOTIONS: usize = 10;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let args = cli_args();
 
     if args.iter().any(|a| a == "--help" || a == "-h") {
         print!("{HELP_TEXT}");
@@ -33,22 +274,405 @@ fn main() {
         return;
     }
 
-    let project_dir = env::current_dir().unwrap_or_else(|e| {
+    if let Err(e) = logging::init(
+        flag_value(&args, "--log-level"),
+        flag_value(&args, "--log-file").map(Path::new),
+    ) {
+        eprintln!("tdd-ratchet: failed to set up logging: {e}");
+        process::exit(2);
+    }
+
+    let current_dir = env::current_dir().unwrap_or_else(|e| {
         eprintln!("tdd-ratchet: cannot determine current directory: {e}");
         process::exit(1);
     });
+    let project_dir = match flag_value(&args, "--project-dir") {
+        Some(dir) => {
+            let dir = current_dir.join(dir);
+            if !dir.is_dir() {
+                eprintln!(
+                    "tdd-ratchet: --project-dir {} is not a directory",
+                    dir.display()
+                );
+                process::exit(2);
+            }
+            dir
+        }
+        None => discover_project_dir(&current_dir),
+    };
 
     let status_path = project_dir.join(".test-status.json");
 
     if args.iter().any(|a| a == "--init") {
-        init(&status_path, &project_dir);
+        init(
+            &status_path,
+            &project_dir,
+            flag_value(&args, "--baseline"),
+            !args.iter().any(|a| a == "--no-gatekeeper"),
+        );
         return;
     }
 
-    run_ratchet(&project_dir, &status_path);
+    let options = ReportOptions {
+        full_output: args.iter().any(|a| a == "--full-output"),
+        deny_warnings: flag_values(&args, "--deny").contains(&"warnings"),
+        slowest: 0,
+    };
+
+    match args.first().map(String::as_str) {
+        Some("tui") => tui(&project_dir, &status_path),
+        Some("log") => log(&project_dir, &status_path, args.get(1)),
+        Some("blame") => blame(&project_dir, &status_path, args.get(1)),
+        Some("replay") => replay_range(&project_dir, args.get(1)),
+        Some("diff") => diff(
+            &project_dir,
+            &status_path,
+            args[1..].iter().find(|a| !a.starts_with("--")),
+            args.iter().any(|a| a == "--head"),
+        ),
+        Some("migrate") => migrate(&status_path),
+        Some("rebaseline") => rebaseline(
+            &project_dir,
+            &status_path,
+            args.iter()
+                .skip(1)
+                .find(|a| !a.starts_with('-'))
+                .map_or("HEAD", String::as_str),
+            args.iter().any(|a| a == "--yes" || a == "-y"),
+        ),
+        Some("repair") => repair(
+            &project_dir,
+            &status_path,
+            args.iter().any(|a| a == "--dry-run"),
+        ),
+        Some("gc") => gc(
+            &project_dir,
+            &status_path,
+            flag_value(&args, "--retention-days"),
+            args.iter().any(|a| a == "--dry-run"),
+        ),
+        Some("allow-ignore") => allow_ignore(&project_dir, &status_path, args.get(1)),
+        Some("accept-failure") => accept_failure(
+            &project_dir,
+            &status_path,
+            args.get(1).filter(|a| !a.starts_with('-')),
+            flag_value(&args, "--reason"),
+            flag_value(&args, "--until"),
+            flag_value(&args, "--commits"),
+        ),
+        Some("unquarantine") => unquarantine(&project_dir, &status_path, args.get(1)),
+        Some("draft") => draft(&project_dir, &status_path, &leading_names(&args[1..])),
+        Some("promote-drafts") => {
+            promote_drafts(&project_dir, &status_path, &leading_names(&args[1..]))
+        }
+        Some("explain") => explain_code(args.get(1)),
+        Some("gatekeeper") => match args.get(1).map(String::as_str) {
+            Some("print") => {
+                let handshake = load_config(&project_dir).gatekeeper.handshake
+                    || args.iter().any(|a| a == "--handshake");
+                print!("{}", gatekeeper_source(handshake));
+            }
+            _ => {
+                eprintln!("tdd-ratchet: usage: cargo ratchet gatekeeper print [--handshake]");
+                process::exit(2);
+            }
+        },
+        Some("daemon") => daemon(
+            &project_dir,
+            &status_path,
+            flag_value(&args, "--socket"),
+            args.iter().any(|a| a == "--watch"),
+        ),
+        Some("serve") => serve(
+            &project_dir,
+            &status_path,
+            flag_value(&args, "--host").unwrap_or("127.0.0.1"),
+            flag_value(&args, "--port").unwrap_or("8080"),
+        ),
+        Some("badge") => badge(
+            &project_dir,
+            &status_path,
+            flag_value(&args, "--format"),
+            flag_value(&args, "--out"),
+        ),
+        Some("reseal") => reseal(&project_dir, &status_path, flag_value(&args, "--reason")),
+        Some("self") => match args.get(1).map(String::as_str) {
+            Some("update") => self_update(args.iter().any(|a| a == "--check")),
+            _ => {
+                eprintln!("tdd-ratchet: usage: cargo ratchet self update [--check]");
+                process::exit(2);
+            }
+        },
+        Some("schema") => print!("{}", current_schema()),
+        Some("validate") => {
+            let path = args.get(1).map_or(status_path.clone(), PathBuf::from);
+            if !validate(&path) {
+                process::exit(1);
+            }
+        }
+        Some("audit") => match args.get(1).map(String::as_str) {
+            Some("show") => audit_show(
+                &project_dir,
+                flag_value(&args, "--test"),
+                flag_value(&args, "--limit"),
+                args.iter().any(|a| a == "--json"),
+            ),
+            _ => {
+                eprintln!(
+                    "tdd-ratchet: usage: cargo ratchet audit show [--test <TEST>] [--limit <N>] [--json]"
+                );
+                process::exit(2);
+            }
+        },
+        Some("stats") => stats(&project_dir, args.iter().any(|a| a == "--json")),
+        Some("report") if args.iter().any(|a| a == "--stale") => report_stale(
+            &project_dir,
+            &status_path,
+            flag_value(&args, "--older-than"),
+            flag_value(&args, "--expiring-within"),
+            flag_value(&args, "--format"),
+        ),
+        Some("report") => {
+            eprintln!(
+                "tdd-ratchet: usage: cargo ratchet report --stale [--older-than <DAYS>] \
+                 [--expiring-within <DAYS>] [--format text|markdown|json]"
+            );
+            process::exit(2);
+        }
+        Some("export") => export(&project_dir, flag_value(&args, "--format")),
+        Some("trend") => trend(
+            &project_dir,
+            flag_value(&args, "--last"),
+            flag_value(&args, "--format"),
+        ),
+        Some("run")
+            if args
+                .iter()
+                .any(|a| a.starts_with("--emit-results") || a.starts_with("--partition")) =>
+        {
+            emit_results(
+                &project_dir,
+                flag_value(&args, "--partition"),
+                flag_value(&args, "--emit-results"),
+                &args,
+            )
+        }
+        Some("check") if args.iter().any(|a| a == "--staged") => {
+            if check_staged(
+                &project_dir,
+                report_format(&args),
+                options,
+                &rule_levels(&args),
+                flag_value(&args, "--metrics-out"),
+            ) {
+                process::exit(1);
+            }
+        }
+        Some("merge-results") => {
+            let paths: Vec<&str> = args
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter(|(i, arg)| {
+                    !arg.starts_with('-')
+                        && ![
+                            "--format",
+                            "--allow",
+                            "--deny",
+                            "--metrics-out",
+                            "--log-level",
+                            "--log-file",
+                            "--project-dir",
+                            "--profile",
+                            "--target",
+                        ]
+                        .contains(&args[i - 1].as_str())
+                })
+                .map(|(_, arg)| arg.as_str())
+                .collect();
+            let _lock = lock_run(&project_dir, args.iter().any(|a| a == "--wait"));
+            if merge_results(
+                &project_dir,
+                &status_path,
+                &paths,
+                report_format(&args),
+                options,
+                &rule_levels(&args),
+                flag_value(&args, "--metrics-out"),
+            ) {
+                process::exit(1);
+            }
+        }
+        Some("eval") if args.iter().any(|a| a == "--stdin") => {
+            let _lock = lock_run(&project_dir, args.iter().any(|a| a == "--wait"));
+            if eval_stdin(
+                &project_dir,
+                &status_path,
+                report_format(&args),
+                options,
+                &rule_levels(&args),
+                flag_value(&args, "--metrics-out"),
+            ) {
+                process::exit(1);
+            }
+        }
+        Some("eval") => {
+            eprintln!(
+                "tdd-ratchet: usage: some-runner | cargo ratchet eval --stdin [--format FMT]"
+            );
+            process::exit(2);
+        }
+        Some("state") => match args.get(1).map(String::as_str) {
+            Some("show") => state_show(&project_dir),
+            Some("import") => state_import(
+                &project_dir,
+                &args.get(2).map_or(status_path.clone(), PathBuf::from),
+            ),
+            _ => {
+                eprintln!("tdd-ratchet: usage: cargo ratchet state show | state import [PATH]");
+                process::exit(2);
+            }
+        },
+        Some(other) if !other.starts_with('-') && other != "run" && other != "check" => {
+            eprintln!("tdd-ratchet: unknown command `{other}`. Run with --help for usage.");
+            process::exit(2);
+        }
+        _ => {
+            let _lock = lock_run(&project_dir, args.iter().any(|a| a == "--wait"));
+            if let Some(path) = flag_value(&args, "--results-file") {
+                if eval_results_file(
+                    &project_dir,
+                    &status_path,
+                    path,
+                    report_format(&args),
+                    options,
+                    &rule_levels(&args),
+                    flag_value(&args, "--metrics-out"),
+                ) {
+                    process::exit(1);
+                }
+            } else if run_ratchet(
+                &project_dir,
+                &status_path,
+                report_format(&args),
+                options,
+                &rule_levels(&args),
+                flag_value(&args, "--metrics-out"),
+                run_scope(&project_dir, &args),
+            ) {
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Take the project's run lock, held until the returned guard is dropped.
+/// Exits if another run holds it, unless `wait`, which waits for that run
+/// to finish.
+fn lock_run(project_dir: &Path, wait: bool) -> RunLock {
+    let lock_failed = |e: RatchetError| -> ! {
+        eprintln!("tdd-ratchet: failed to lock the project: {e}");
+        process::exit(1);
+    };
+    match RunLock::try_acquire(project_dir).unwrap_or_else(|e| lock_failed(e)) {
+        Ok(lock) => lock,
+        Err(held) if wait => {
+            eprintln!("tdd-ratchet: {held}; waiting for it to finish");
+            RunLock::acquire(project_dir).unwrap_or_else(|e| lock_failed(e))
+        }
+        Err(held) => {
+            eprintln!("tdd-ratchet: {held}; rerun with --wait to wait for it to finish");
+            process::exit(1);
+        }
+    }
+}
+
+/// The `--format` of the ratchet report; exits on an unknown format.
+fn report_format(args: &[String]) -> ReportFormat {
+    match flag_value(args, "--format") {
+        Some(name) => ReportFormat::parse(name).unwrap_or_else(|| {
+            eprintln!(
+                "tdd-ratchet: unknown report format `{name}` (expected text, markdown, json, or sarif)"
+            );
+            process::exit(2);
+        }),
+        None => ReportFormat::Text,
+    }
 }
 
-fn init(status_path: &Path, project_dir: &Path) {
+/// Rule severities from `--allow <RULE>`, `--deny <RULE>`, and `--strict`,
+/// in order; exits on an unknown rule. `--deny warnings` is not a rule and
+/// is skipped.
+fn rule_levels(args: &[String]) -> Vec<(Rule, RuleLevel)> {
+    let mut levels = Vec::new();
+    for (i, arg) in args.iter().enumerate() {
+        if arg == "--strict" {
+            levels.push((Rule::DirtyWorktree, RuleLevel::Deny));
+            continue;
+        }
+        let (level, name) = match arg.as_str() {
+            "--allow" => (RuleLevel::Allow, args.get(i + 1).map(String::as_str)),
+            "--deny" => (RuleLevel::Deny, args.get(i + 1).map(String::as_str)),
+            _ => match (arg.strip_prefix("--allow="), arg.strip_prefix("--deny=")) {
+                (Some(name), _) => (RuleLevel::Allow, Some(name)),
+                (_, Some(name)) => (RuleLevel::Deny, Some(name)),
+                _ => continue,
+            },
+        };
+        if level == RuleLevel::Deny && name == Some("warnings") {
+            continue;
+        }
+        let rule = name.and_then(Rule::parse).unwrap_or_else(|| {
+            let names: Vec<&str> = Rule::ALL.iter().map(|rule| rule.name()).collect();
+            eprintln!(
+                "tdd-ratchet: {arg} needs a rule, one of: {}",
+                names.join(", ")
+            );
+            process::exit(2);
+        });
+        levels.push((rule, level));
+    }
+    levels
+}
+
+/// Every value of a repeatable `--flag value` or `--flag=value` option.
+fn flag_values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    args.iter()
+        .enumerate()
+        .filter_map(|(i, arg)| {
+            if arg == flag {
+                args.get(i + 1).map(String::as_str)
+            } else {
+                arg.strip_prefix(flag)
+                    .and_then(|rest| rest.strip_prefix('='))
+            }
+        })
+        .collect()
+}
+
+/// Value of a `--flag value` or `--flag=value` option.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == flag {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix(flag)
+                .and_then(|rest| rest.strip_prefix('='))
+        }
+    })
+}
+
+/// Command-line arguments without the program name. When invoked as
+/// `cargo ratchet`, cargo passes the subcommand name as the first argument.
+fn cli_args() -> Vec<String> {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a == "ratchet") {
+        args.remove(0);
+    }
+    args
+}
+
+fn init(status_path: &Path, project_dir: &Path, baseline: Option<&str>, with_gatekeeper: bool) {
     if status_path.exists() {
         eprintln!(
             "tdd-ratchet: .test-status.json already exists. Remove it first to re-initialize."
@@ -57,9 +681,39 @@ fn init(status_path: &Path, project_dir: &Path) {
     }
 
     let mut status = StatusFile::empty();
+    status.baseline = baseline.map(|rev| {
+        resolve_baseline(&locate_in_repo(project_dir).repo_dir, rev).unwrap_or_else(|e| {
+            eprintln!("tdd-ratchet: invalid --baseline `{rev}`: {e}");
+            process::exit(2);
+        })
+    });
 
     // Run tests and snapshot existing results into the status file
-    status.tests = status_entries_from_results(&run_nextest(project_dir, false));
+    let config = load_config(project_dir);
+    let results = if config.runners.is_empty() {
+        NextestRunner::new(project_dir).inherit_stderr(false).run()
+    } else {
+        config
+            .test_runner(project_dir, &BTreeSet::new())
+            .and_then(|runner| runner.run())
+    }
+    .unwrap_or_else(|e| runner_failed(&e));
+    status.tests = status_entries_from_results(&results);
+    if with_gatekeeper {
+        let source = gatekeeper_source(config.gatekeeper.handshake);
+        match scaffold_gatekeeper_with(project_dir, &results, source) {
+            Ok(Scaffold::Written { test }) => {
+                println!("tdd-ratchet: wrote {GATEKEEPER_PATH}; commit it with .test-status.json");
+                status
+                    .tests
+                    .insert(test, TestEntry::new(TestState::Passing));
+            }
+            Ok(Scaffold::AlreadyPresent) => {}
+            Err(e) => eprintln!("tdd-ratchet: not scaffolding the gatekeeper test: {e}"),
+        }
+    }
+    status.stamp(unix_now());
+    status.integrity = Some(seal(None, &status));
 
     status.write_to_path(status_path).unwrap_or_else(|e| {
         eprintln!("tdd-ratchet: failed to create status file: {e}");
@@ -79,117 +733,1960 @@ fn init(status_path: &Path, project_dir: &Path) {
     println!("tdd-ratchet: initialized .test-status.json ({passing} passing, {pending} pending)");
 }
 
-fn run_ratchet(project_dir: &Path, status_path: &Path) {
-    let gathered = gather_run(project_dir);
+/// What a run checks and reports: the part of history from `--since` or
+/// `--merge-base`, the owner from `--owner`, the test binaries from
+/// `--test`, the tests selected by `-p` and `-E`, whether `--no-cache`
+/// bypasses cached results, and the nextest profile, build mode and target
+/// from `--profile`, `--release` and `--target`.
+#[derive(Debug, Clone, Default)]
+struct RunScope {
+    /// Only history after this commit is checked.
+    since: Option<String>,
+    /// The tests run on `HEAD` merged into this commit, and the committed
+    /// status is read as merged into it.
+    merge_target: Option<String>,
+    /// Only violations in files this CODEOWNERS owner owns are reported.
+    owner: Option<String>,
+    /// Only these test binaries are run and evaluated.
+    binaries: Vec<String>,
+    /// Only the tests these filters select are run and evaluated.
+    filter: RunFilter,
+    /// Rerun every test binary, even with `[cache] enabled`.
+    no_cache: bool,
+    /// The nextest profile, instead of `[nextest] profile`.
+    profile: Option<String>,
+    /// Build and run the tests in release mode.
+    release: bool,
+    /// Build and run the tests for this target triple.
+    target: Option<String>,
+}
 
-    // ── Phase 2: Evaluate (pure) ────────────────────────────────────
-    let result = evaluate(
-        &gathered.status,
-        &gathered.instructions,
-        &gathered.results,
-        &gathered.history_snapshots,
+impl RunScope {
+    fn apply(&self, config: &mut RatchetConfig) {
+        config.since = self.since.clone();
+        config.merge_target = self.merge_target.clone();
+        config.owner = self.owner.clone();
+        config.binaries = self.binaries.clone();
+        config.filter = self.filter.clone();
+        if self.no_cache {
+            config.cache.enabled = false;
+        }
+        if let Some(profile) = &self.profile {
+            config.nextest.profile = Some(profile.clone());
+        }
+        config.nextest.release = self.release;
+        config.nextest.target = self.target.clone();
+    }
+}
+
+/// The scope from `--since <REV>` or `--merge-base <REV>`,
+/// `--owner <OWNER>`, `--test <BINARY>`, `-p <PACKAGE>`, `-E <FILTERSET>`,
+/// `--affected`, `--no-cache`, `--profile <NAME>`, `--release` and
+/// `--target <TRIPLE>`; exits on an invalid revision.
+fn run_scope(project_dir: &Path, args: &[String]) -> RunScope {
+    let owner = flag_value(args, "--owner").map(String::from);
+    let binaries = flag_values(args, "--test")
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut filter = RunFilter {
+        packages: ["-p", "--package"]
+            .into_iter()
+            .flat_map(|flag| flag_values(args, flag))
+            .map(String::from)
+            .collect(),
+        filtersets: ["-E", "--filterset"]
+            .into_iter()
+            .flat_map(|flag| flag_values(args, flag))
+            .map(String::from)
+            .collect(),
+    };
+    let repo_dir = locate_in_repo(project_dir).repo_dir;
+    let range = match (
+        flag_value(args, "--since"),
+        flag_value(args, "--merge-base"),
+    ) {
+        (None, None) => RunScope::default(),
+        (Some(_), Some(_)) => {
+            eprintln!("tdd-ratchet: --since and --merge-base cannot be combined");
+            process::exit(2);
+        }
+        (Some(rev), None) => RunScope {
+            since: Some(resolve_baseline(&repo_dir, rev).unwrap_or_else(|e| {
+                eprintln!("tdd-ratchet: invalid --since `{rev}`: {e}");
+                process::exit(2);
+            })),
+            merge_target: None,
+            ..RunScope::default()
+        },
+        (None, Some(rev)) => {
+            let MergeBase { base, target } = merge_base(&repo_dir, rev).unwrap_or_else(|e| {
+                eprintln!("tdd-ratchet: invalid --merge-base `{rev}`: {e}");
+                process::exit(2);
+            });
+            RunScope {
+                since: Some(base),
+                merge_target: Some(target),
+                ..RunScope::default()
+            }
+        }
+    };
+    if args.iter().any(|a| a == "--affected") {
+        affected_filter(project_dir, range.since.as_deref(), &mut filter);
+    }
+    RunScope {
+        owner,
+        binaries,
+        filter,
+        no_cache: args.iter().any(|a| a == "--no-cache"),
+        profile: flag_value(args, "--profile").map(String::from),
+        release: args.iter().any(|a| a == "--release"),
+        target: flag_value(args, "--target").map(String::from),
+        ..range
+    }
+}
+
+/// Limit `filter` to the packages affected by changes since `since`, or
+/// since the merge base with the first of [`DEFAULT_AFFECTED_TARGETS`];
+/// exits when there is nothing to compare against.
+fn affected_filter(project_dir: &Path, since: Option<&str>, filter: &mut RunFilter) {
+    let repo_dir = locate_in_repo(project_dir).repo_dir;
+    let base = since.map(String::from).or_else(|| {
+        DEFAULT_AFFECTED_TARGETS
+            .iter()
+            .find_map(|target| merge_base(&repo_dir, target).ok())
+            .map(|merge_base| merge_base.base)
+    });
+    let Some(base) = base else {
+        eprintln!(
+            "tdd-ratchet: --affected found no branch to compare against; \
+             pass --merge-base <REV> or --since <REV>"
+        );
+        process::exit(2);
+    };
+    let changed = changed_paths(&repo_dir, &base).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: cannot list changed files: {e}");
+        process::exit(1);
+    });
+    let packages = workspace_packages(project_dir, &repo_dir).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: cannot read the workspace: {e}");
+        process::exit(1);
+    });
+    match affected_packages(&packages, &changed) {
+        Affected::Everything => eprintln!(
+            "tdd-ratchet: changes since {} are outside any package; running every test",
+            &base[..8]
+        ),
+        Affected::Packages(affected) if affected.is_empty() => {
+            eprintln!(
+                "tdd-ratchet: no package changed since {}; running only the gatekeeper",
+                &base[..8]
+            );
+            filter.filtersets.push("none()".to_string());
+        }
+        Affected::Packages(affected) => {
+            eprintln!(
+                "tdd-ratchet: affected since {}: {}",
+                &base[..8],
+                affected.iter().cloned().collect::<Vec<_>>().join(", ")
+            );
+            filter.packages.extend(affected);
+        }
+    }
+}
+
+/// Run the full ratchet and print the report. Returns whether there were
+/// violations. `scope` bounds the history check and the report.
+fn run_ratchet(
+    project_dir: &Path,
+    status_path: &Path,
+    format: ReportFormat,
+    options: ReportOptions,
+    levels: &[(Rule, RuleLevel)],
+    metrics_out: Option<&str>,
+    scope: RunScope,
+) -> bool {
+    let mut config = load_run_config(project_dir, levels);
+    let options = timed_options(options, &config.timing);
+    if !config.roots.is_empty() {
+        return run_roots(
+            project_dir,
+            &config.roots,
+            format,
+            options,
+            levels,
+            metrics_out,
+            &scope,
+        );
+    }
+    scope.apply(&mut config);
+    report_root(
+        project_dir,
+        status_path,
+        &config,
+        None,
+        format,
+        options,
+        metrics_out,
+    )
+}
+
+/// Evaluate the merged results of a sharded run and print the report.
+/// Returns whether there were violations.
+fn merge_results(
+    project_dir: &Path,
+    status_path: &Path,
+    paths: &[&str],
+    format: ReportFormat,
+    options: ReportOptions,
+    levels: &[(Rule, RuleLevel)],
+    metrics_out: Option<&str>,
+) -> bool {
+    if paths.is_empty() {
+        eprintln!("tdd-ratchet: usage: cargo ratchet merge-results <FILE>...");
+        process::exit(2);
+    }
+    let config = load_run_config(project_dir, levels);
+    if !config.roots.is_empty() {
+        eprintln!("tdd-ratchet: merge-results does not support [[roots]] projects");
+        process::exit(2);
+    }
+    let options = timed_options(options, &config.timing);
+    let parts: Vec<PartialResults> = paths
+        .iter()
+        .map(|path| {
+            load_partial_results(Path::new(path)).unwrap_or_else(|e| {
+                eprintln!("tdd-ratchet: {e}");
+                process::exit(1);
+            })
+        })
+        .collect();
+    let results = merge_partial_results(&parts).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    report_root(
+        project_dir,
+        status_path,
+        &config,
+        Some(results),
+        format,
+        options,
+        metrics_out,
+    )
+}
+
+/// Evaluate libtest-json events read from stdin, as a test runner outside
+/// tdd-ratchet printed them, and print the report. Returns whether there
+/// were violations.
+fn eval_stdin(
+    project_dir: &Path,
+    status_path: &Path,
+    format: ReportFormat,
+    options: ReportOptions,
+    levels: &[(Rule, RuleLevel)],
+    metrics_out: Option<&str>,
+) -> bool {
+    let config = single_root_config(project_dir, levels, "eval --stdin");
+    let options = timed_options(options, &config.timing);
+    let results = parse_nextest_events_stream(io::stdin().lock())
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap_or_else(|e| {
+            eprintln!("tdd-ratchet: failed to read test results from stdin: {e}");
+            process::exit(1);
+        });
+    if results.is_empty() {
+        // Evaluating nothing would report every tracked test as disappeared.
+        eprintln!(
+            "tdd-ratchet: no libtest-json test events on stdin; pipe in e.g. `cargo nextest run --message-format libtest-json`"
+        );
+        process::exit(1);
+    }
+    report_root(
+        project_dir,
+        status_path,
+        &config,
+        Some(merge_retries(results)),
+        format,
+        options,
+        metrics_out,
+    )
+}
+
+/// Evaluate the results captured in `path` by an earlier run instead of
+/// running the tests, and print the report. Returns whether there were
+/// violations.
+fn eval_results_file(
+    project_dir: &Path,
+    status_path: &Path,
+    path: &str,
+    format: ReportFormat,
+    options: ReportOptions,
+    levels: &[(Rule, RuleLevel)],
+    metrics_out: Option<&str>,
+) -> bool {
+    let config = single_root_config(project_dir, levels, "--results-file");
+    let options = timed_options(options, &config.timing);
+    let results = load_results_file(Path::new(path)).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    if results.is_empty() {
+        eprintln!(
+            "tdd-ratchet: no test results in {path}; expected nextest libtest-json output or a file from `run --emit-results`"
+        );
+        process::exit(1);
+    }
+    report_root(
+        project_dir,
+        status_path,
+        &config,
+        Some(results),
+        format,
+        options,
+        metrics_out,
+    )
+}
+
+/// The run config for a command that evaluates recorded results; exits for
+/// a `[[roots]]` project, whose roots each need results of their own.
+fn single_root_config(
+    project_dir: &Path,
+    levels: &[(Rule, RuleLevel)],
+    command: &str,
+) -> RatchetConfig {
+    let config = load_run_config(project_dir, levels);
+    if !config.roots.is_empty() {
+        eprintln!("tdd-ratchet: {command} does not support [[roots]] projects");
+        process::exit(2);
+    }
+    config
+}
+
+/// Run the tests, or one partition of them, and write the results to
+/// `emit` for `merge-results` instead of evaluating them. `--profile`,
+/// `--release` and `--target` in `args` are passed on to nextest.
+fn emit_results(project_dir: &Path, partition: Option<&str>, emit: Option<&str>, args: &[String]) {
+    let partition = partition.map(|value| {
+        Partition::parse(value).unwrap_or_else(|| {
+            eprintln!("tdd-ratchet: invalid --partition `{value}` (expected M/N, e.g. 1/4)");
+            process::exit(2);
+        })
+    });
+    let Some(emit) = emit else {
+        eprintln!(
+            "tdd-ratchet: --partition needs --emit-results <FILE>; a single shard cannot be evaluated"
+        );
+        process::exit(2);
+    };
+    let mut config = load_config(project_dir);
+    if let Some(profile) = flag_value(args, "--profile") {
+        config.nextest.profile = Some(profile.to_string());
+    }
+    config.nextest.release = args.iter().any(|a| a == "--release");
+    config.nextest.target = flag_value(args, "--target").map(String::from);
+    let pending = tracked_pending(&*history_provider(&config, project_dir));
+    let runner: Box<dyn TestRunner> = match partition {
+        Some(partition) => {
+            let handshake = config
+                .gatekeeper
+                .begin_handshake()
+                .unwrap_or_else(|e| runner_failed(&e));
+            Box::new(
+                config
+                    .partition_runner(project_dir, &pending, partition, handshake.as_ref())
+                    .unwrap_or_else(|| {
+                        eprintln!(
+                            "tdd-ratchet: --partition needs the nextest runner; other runners cannot be split"
+                        );
+                        process::exit(2);
+                    }),
+            )
+        }
+        None => config
+            .test_runner(project_dir, &pending)
+            .unwrap_or_else(|e| runner_failed(&e)),
+    };
+    let results = runner.run().unwrap_or_else(|e| runner_failed(&e));
+    // A shard without the gatekeeper has nothing to acknowledge.
+    let ran_gatekeeper = results
+        .iter()
+        .any(|r| r.name.ends_with(GATEKEEPER_TEST_NAME) && r.outcome == TestOutcome::Passed);
+    if ran_gatekeeper && runner.gatekeeper_acknowledged() == Some(false) {
+        eprintln!(
+            "tdd-ratchet: the gatekeeper did not echo this run's nonce (RATCHET021); {emit} was not written"
+        );
+        process::exit(1);
+    }
+
+    let count = results.len();
+    let partial = PartialResults { partition, results };
+    save_partial_results(Path::new(emit), &partial).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: failed to write {emit}: {e}");
+        process::exit(1);
+    });
+    eprintln!("tdd-ratchet: wrote {count} results to {emit}");
+}
+
+/// Ratchet the single root at `project_dir` and print the report. Returns
+/// whether there were violations.
+fn report_root(
+    project_dir: &Path,
+    status_path: &Path,
+    config: &RatchetConfig,
+    recorded: Option<Vec<TestResult>>,
+    format: ReportFormat,
+    options: ReportOptions,
+    metrics_out: Option<&str>,
+) -> bool {
+    let started = Instant::now();
+    let location = locate_in_repo(project_dir);
+    let relative_status = config.run_status_path(&location.status_path());
+    let merged = config.merge_target.as_deref().map(|target| {
+        merged_checkout(
+            &location.repo_dir,
+            target,
+            std::slice::from_ref(&relative_status),
+        )
+    });
+    let (root_dir, status_path) = match &merged {
+        Some(merged) => (
+            merged.path().join(&location.prefix),
+            merged.path().join(&relative_status),
+        ),
+        None => (
+            project_dir.to_path_buf(),
+            config.run_status_path(status_path),
+        ),
+    };
+    let (previous, result) = ratchet_root(
+        &location.repo_dir,
+        &root_dir,
+        &relative_status,
+        &status_path,
+        config,
+        recorded,
+    );
+    print_report(&previous, &result, format, options, metrics_out, started)
+}
+
+/// Evaluate the staged tree, checked out in a temporary directory, against
+/// the committed history. Returns whether there were violations.
+fn check_staged(
+    project_dir: &Path,
+    format: ReportFormat,
+    options: ReportOptions,
+    levels: &[(Rule, RuleLevel)],
+    metrics_out: Option<&str>,
+) -> bool {
+    let started = Instant::now();
+    let location = locate_in_repo(project_dir);
+    let staged = StagedTree::checkout(&location.repo_dir).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: failed to check out the staged tree: {e}");
+        process::exit(1);
+    });
+    let staged_dir = staged.path().join(&location.prefix);
+    let mut config = load_run_config(&staged_dir, levels);
+    if !config.roots.is_empty() {
+        eprintln!("tdd-ratchet: check --staged does not support [[roots]] projects");
+        process::exit(2);
+    }
+    let options = timed_options(options, &config.timing);
+    // Unstaged changes are not part of the commit being checked.
+    config
+        .rules
+        .set_level(Rule::DirtyWorktree, RuleLevel::Allow);
+    // Build in the project's own target directory, so each pre-commit run
+    // is an incremental build rather than a cold one in the checkout.
+    if env::var_os("CARGO_TARGET_DIR").is_none() {
+        match target_directory(project_dir) {
+            Ok(dir) => config.target_dir = Some(dir),
+            Err(e) => eprintln!("tdd-ratchet: building the staged tree from scratch: {e}"),
+        }
+    }
+    let (previous, result) = ratchet_root(
+        &location.repo_dir,
+        &staged_dir,
+        &location.status_path(),
+        &staged_dir.join(STATUS_FILE_NAME),
+        &config,
+        None,
+    );
+    print_report(&previous, &result, format, options, metrics_out, started)
+}
+
+/// `options` listing the slowest tests when `[timing]` is enabled.
+fn timed_options(options: ReportOptions, timing: &TimingConfig) -> ReportOptions {
+    ReportOptions {
+        slowest: if timing.enabled { timing.slowest() } else { 0 },
+        ..options
+    }
+}
+
+/// `HEAD` merged into the `--merge-base` target and checked out, so the
+/// tests run on the code the merge produces. The working-tree status files
+/// at `status_paths`, relative to the repository, are copied in so their
+/// renames and removals still apply; the run saves into the checkout and
+/// leaves the working tree alone. Exits when the merge has conflicts.
+fn merged_checkout(repo_dir: &Path, target: &str, status_paths: &[PathBuf]) -> MergedTree {
+    let merged = MergedTree::checkout(repo_dir, target, status_paths).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: --merge-base: {e}");
+        process::exit(1);
+    });
+    for path in status_paths {
+        let (from, to) = (repo_dir.join(path), merged.path().join(path));
+        let copied = if from.exists() {
+            fs::copy(&from, &to).map(drop)
+        } else if to.exists() {
+            fs::remove_file(&to)
+        } else {
+            Ok(())
+        };
+        copied.unwrap_or_else(|e| {
+            eprintln!(
+                "tdd-ratchet: cannot copy {} into the merge: {e}",
+                from.display()
+            );
+            process::exit(1);
+        });
+    }
+    merged
+}
+
+/// Print the report for one evaluated root and write its metrics. Returns
+/// whether the run failed.
+fn print_report(
+    previous: &TrackedStatus,
+    result: &EvalResult,
+    format: ReportFormat,
+    options: ReportOptions,
+    metrics_out: Option<&str>,
+    started: Instant,
+) -> bool {
+    let has_violations = result.is_failure(options.deny_warnings);
+    match format {
+        ReportFormat::Text => eprint!("\n{}", format_report_with(result, options)),
+        ReportFormat::Markdown => print!("{}", format_markdown_report(result, previous)),
+        ReportFormat::Sarif => print!("{}", format_sarif([(result, Path::new(STATUS_FILE_NAME))])),
+        ReportFormat::Json => print!("{}", format_json(result, options.deny_warnings)),
+    }
+    if let Some(path) = metrics_out {
+        write_metrics(
+            path,
+            &format_metrics([(SINGLE_ROOT, result)], started.elapsed()),
+        );
+    }
+
+    has_violations
+}
+
+/// Where the configured state storage saves the status file at
+/// `relative_status` (working-tree path `status_path`).
+fn status_store(
+    repo_dir: &Path,
+    relative_status: &Path,
+    status_path: &Path,
+    config: &RatchetConfig,
+) -> Box<dyn StatusStore> {
+    match config.state.storage {
+        StateStorage::File => Box::new(WorkingTreeStatusFile::new(status_path)),
+        StateStorage::Ref => {
+            Box::new(RefStatusStore::new(repo_dir).with_status_path(relative_status))
+        }
+    }
+}
+
+/// How often `daemon --watch` checks the project's files for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Serve JSON-RPC requests on stdio, or on a unix socket at `socket`, until
+/// the input is closed (or, for a socket, forever). With `watch`, also
+/// re-evaluate whenever the project's files change.
+fn daemon(project_dir: &Path, status_path: &Path, socket: Option<&str>, watch: bool) {
+    let config = load_config(project_dir);
+    if !config.roots.is_empty() {
+        eprintln!("tdd-ratchet: daemon does not support [[roots]] projects");
+        process::exit(2);
+    }
+    let location = locate_in_repo(project_dir);
+    let relative_status = location.status_path();
+    let mut daemon = Daemon::new(
+        project_dir,
+        config.history_provider(&location.repo_dir, &relative_status),
+        Box::new(|history| {
+            evaluate_root(
+                &location.repo_dir,
+                project_dir,
+                &relative_status,
+                status_path,
+                &config,
+                None,
+                history,
+            )
+        }),
     );
+    if watch {
+        daemon = daemon.with_watch(WATCH_INTERVAL);
+    }
+
+    let served = match socket {
+        None => daemon.serve(io::BufReader::new(io::stdin()), io::stdout().lock()),
+        Some(path) => serve_socket(&mut daemon, Path::new(path)),
+    };
+    if let Err(e) = served {
+        eprintln!("tdd-ratchet: daemon stopped: {e}");
+        process::exit(1);
+    }
+}
+
+/// Serve one client at a time on a unix socket at `path`.
+#[cfg(unix)]
+fn serve_socket(daemon: &mut Daemon, path: &Path) -> Result<(), RatchetError> {
+    use std::os::unix::net::UnixListener;
+
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| RatchetError::io(path, e))?;
+    }
+    let listener = UnixListener::bind(path).map_err(|e| RatchetError::io(path, e))?;
+    eprintln!("tdd-ratchet: daemon listening on {}", path.display());
+    for stream in listener.incoming() {
+        let stream = stream.map_err(RatchetError::Connection)?;
+        let reader = stream.try_clone().map_err(RatchetError::Connection)?;
+        daemon.serve(io::BufReader::new(reader), stream)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve_socket(_daemon: &mut Daemon, _path: &Path) -> Result<(), RatchetError> {
+    Err(RatchetError::Connection(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "unix sockets are not available on this platform; use stdio",
+    )))
+}
+
+/// Serve the read-only HTTP status endpoints on `host:port`.
+fn serve(project_dir: &Path, status_path: &Path, host: &str, port: &str) {
+    let Ok(port) = port.parse::<u16>() else {
+        eprintln!("tdd-ratchet: --port must be a port number, got `{port}`");
+        process::exit(2);
+    };
+    let config = load_config(project_dir);
+    let endpoints = StatusEndpoints::new(
+        history_provider(&config, project_dir),
+        status_path,
+        project_dir.join(LAST_RUN_FILE_NAME),
+    );
+    let listener = TcpListener::bind((host, port)).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: cannot listen on {host}:{port}: {e}");
+        process::exit(1);
+    });
+    let address = listener
+        .local_addr()
+        .map_or_else(|_| format!("{host}:{port}"), |a| a.to_string());
+    eprintln!("tdd-ratchet: serving status on http://{address}/");
+    endpoints.serve(listener, |e| eprintln!("tdd-ratchet: serve: {e}"));
+}
+
+/// Write a badge for the current status and last run to `out`, or stdout.
+fn badge(project_dir: &Path, status_path: &Path, format: Option<&str>, out: Option<&str>) {
+    let format = match format {
+        Some(name) => BadgeFormat::parse(name).unwrap_or_else(|| {
+            eprintln!("tdd-ratchet: unknown badge format `{name}` (expected svg or shields-json)");
+            process::exit(2);
+        }),
+        None => BadgeFormat::default(),
+    };
+    let status = if status_path.exists() {
+        StatusFile::load(status_path)
+            .map(StatusFile::into_tracked_status)
+            .unwrap_or_else(|e| {
+                eprintln!("tdd-ratchet: {e}");
+                process::exit(1);
+            })
+    } else {
+        load_committed_status_input(project_dir)
+    };
+    let last_run = load_last_run(&project_dir.join(LAST_RUN_FILE_NAME));
+    let badge = build_badge(&status, last_run.as_ref());
+    let rendered = match format {
+        BadgeFormat::Svg => format_svg(&badge),
+        BadgeFormat::ShieldsJson => format_shields_json(&badge),
+    };
+    match out {
+        Some(path) => fs::write(path, rendered).unwrap_or_else(|e| {
+            eprintln!("tdd-ratchet: cannot write {path}: {e}");
+            process::exit(1);
+        }),
+        None => print!("{rendered}"),
+    }
+}
+
+/// Report a test run that produced no usable results and exit. A build
+/// failure gets its own exit code and skips evaluation entirely: with no
+/// tests run, every tracked test would otherwise look disappeared.
+fn runner_failed(error: &RunnerError) -> ! {
+    if let RunnerError::CompileFailed { output, .. } = error {
+        eprint!("{output}");
+        eprintln!("tdd-ratchet: {error}");
+        process::exit(EXIT_BUILD_FAILED);
+    }
+    eprintln!("tdd-ratchet: {error}");
+    // A missing nextest is a setup problem, like a usage error.
+    if matches!(error, RunnerError::NextestMissing) {
+        process::exit(2);
+    }
+    process::exit(1);
+}
+
+/// Run every configured root and print one aggregated report. Returns
+/// whether any root had violations.
+fn run_roots(
+    project_dir: &Path,
+    roots: &[RootConfig],
+    format: ReportFormat,
+    options: ReportOptions,
+    levels: &[(Rule, RuleLevel)],
+    metrics_out: Option<&str>,
+    scope: &RunScope,
+) -> bool {
+    let started = Instant::now();
+    let location = locate_in_repo(project_dir);
+    let merged = scope.merge_target.as_deref().map(|target| {
+        let status_paths: Vec<PathBuf> = roots
+            .iter()
+            .map(|root| {
+                let config = load_run_config(&project_dir.join(&root.path), levels);
+                location
+                    .prefix
+                    .join(config.run_status_path(&root.status_path()))
+            })
+            .collect();
+        merged_checkout(&location.repo_dir, target, &status_paths)
+    });
+    let run_dir = match &merged {
+        Some(merged) => merged.path().join(&location.prefix),
+        None => project_dir.to_path_buf(),
+    };
+    let results: Vec<RootResult> = roots
+        .iter()
+        .map(|root| {
+            let mut config = load_run_config(&project_dir.join(&root.path), levels);
+            scope.apply(&mut config);
+            let root_status = config.run_status_path(&root.status_path());
+            let (previous, result) = ratchet_root(
+                &location.repo_dir,
+                &run_dir.join(&root.path),
+                &location.prefix.join(&root_status),
+                &run_dir.join(&root_status),
+                &config,
+                None,
+            );
+            RootResult {
+                root: root.path.display().to_string(),
+                previous,
+                result,
+            }
+        })
+        .collect();
+
+    match format {
+        ReportFormat::Text => eprint!("\n{}", format_roots_report_with(&results, options)),
+        ReportFormat::Markdown => print!("{}", format_markdown_roots_report(&results)),
+        ReportFormat::Json => print!("{}", format_json_roots(&results, options.deny_warnings)),
+        ReportFormat::Sarif => {
+            let status_paths: Vec<PathBuf> = roots.iter().map(RootConfig::status_path).collect();
+            print!(
+                "{}",
+                format_sarif(
+                    results
+                        .iter()
+                        .map(|root| &root.result)
+                        .zip(status_paths.iter().map(PathBuf::as_path))
+                )
+            );
+        }
+    }
+    if let Some(path) = metrics_out {
+        let roots = results.iter().map(|r| (r.root.as_str(), &r.result));
+        write_metrics(path, &format_metrics(roots, started.elapsed()));
+    }
+
+    results
+        .iter()
+        .any(|r| r.result.is_failure(options.deny_warnings))
+}
+
+/// Record this run's outcomes in the flip record, when the source is
+/// committed, and quarantine the tests that flipped `after` times in a row.
+fn quarantine_flips(
+    result: &mut EvalResult,
+    results: &[TestResult],
+    repo_dir: &Path,
+    root_dir: &Path,
+    after: u32,
+) {
+    let path = root_dir.join(FLIPS_FILE_NAME);
+    let mut record = load_flips(&path);
+    if let Some(commit) = unchanged_commit(repo_dir) {
+        record = observe_flips(&record, &commit, results);
+        if let Err(e) = save_flips(&path, &record) {
+            eprintln!("tdd-ratchet: failed to record flips: {e}");
+        }
+    }
+    quarantine_flaky_tests(result, &record, after);
+}
+
+/// Write `metrics` to `path` through a temporary file, so a textfile
+/// collector never reads a half-written file. Failing to write them does not
+/// fail the run.
+fn write_metrics(path: &str, metrics: &str) {
+    let temporary = format!("{path}.tmp");
+    if let Err(e) = fs::write(&temporary, metrics).and_then(|()| fs::rename(&temporary, path)) {
+        eprintln!("tdd-ratchet: failed to write metrics to {path}: {e}");
+    }
+}
+
+/// Names of the tests pending or draft in the committed status.
+fn tracked_pending(history: &dyn HistoryProvider) -> BTreeSet<String> {
+    history
+        .head_status()
+        .ok()
+        .flatten()
+        .map(|status| status.tests)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, entry)| matches!(entry.state(), TestState::Pending | TestState::Draft))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Gather, evaluate, and save one ratchet: tests run in `root_dir`, status
+/// lives at `relative_status` in the repository at `repo_dir`. Returns the
+/// committed status the run started from and the evaluation. `recorded`
+/// results, e.g. merged shards, stand in for running the tests. History is
+/// bounded to the commits after `config.since`, when set, and the returned
+/// violations to those in `config.owner`'s files.
+fn ratchet_root(
+    repo_dir: &Path,
+    root_dir: &Path,
+    relative_status: &Path,
+    status_path: &Path,
+    config: &RatchetConfig,
+    recorded: Option<Vec<TestResult>>,
+) -> (TrackedStatus, EvalResult) {
+    let history = match config.since.as_deref() {
+        Some(since) => config
+            .bounded_history_provider(repo_dir, relative_status, since)
+            .unwrap_or_else(|| {
+                eprintln!(
+                    "tdd-ratchet: --since and --merge-base need the git2 history backend and file state storage"
+                );
+                process::exit(2);
+            }),
+        None => config.history_provider(repo_dir, relative_status),
+    };
+    evaluate_root(
+        repo_dir,
+        root_dir,
+        relative_status,
+        status_path,
+        config,
+        recorded,
+        &*history,
+    )
+    .unwrap_or_else(|e| match e {
+        RatchetError::Runner(e) => runner_failed(&e),
+        RatchetError::Git(e) => {
+            eprintln!("tdd-ratchet: failed to inspect git history: {e}");
+            process::exit(1);
+        }
+        e => {
+            eprintln!("tdd-ratchet: {e}");
+            process::exit(1);
+        }
+    })
+}
+
+/// The steps of [`ratchet_root`] after choosing the history, shared with the
+/// daemon so its verdicts match a CLI run. Failures that stop the run are
+/// returned; the optional steps only warn.
+fn evaluate_root(
+    repo_dir: &Path,
+    root_dir: &Path,
+    relative_status: &Path,
+    status_path: &Path,
+    config: &RatchetConfig,
+    recorded: Option<Vec<TestResult>>,
+    history: &dyn HistoryProvider,
+) -> Result<(TrackedStatus, EvalResult), RatchetError> {
+    // Recorded results may come from another machine's toolchain.
+    let toolchain = match recorded {
+        Some(_) => None,
+        None => config.toolchain(root_dir),
+    };
+    let runner: Box<dyn TestRunner> = match recorded {
+        Some(results) => Box::new(RecordedRunner::new(results)),
+        None => config.test_runner(root_dir, &tracked_pending(history))?,
+    };
+    let store = status_store(repo_dir, relative_status, status_path, config);
+    let ratchet = Ratchet::builder(root_dir)
+        .runner(runner)
+        .history(history)
+        .rules(config.rules.clone())
+        .grandfather(config.grandfather.clone())
+        .transition_history(config.state.transition_history)
+        .exec_times(config.timing.enabled)
+        .toolchain(toolchain)
+        .parameterized(config.parameterized.clone())
+        .binaries(config.binaries.clone())
+        .filter(config.filter.clone())
+        .status_store(&*store)
+        .build();
+
+    // ── Phase 1: Gather ─────────────────────────────────────────────
+    let gathered = ratchet.gather()?;
+
+    // ── Phase 2: Evaluate (pure) ────────────────────────────────────
+    let mut result = gathered.evaluate();
+    result.history_since = config.since.clone();
+    if let Some(after) = config.rules.quarantine_after {
+        quarantine_flips(&mut result, &gathered.results, repo_dir, root_dir, after);
+    }
 
     // ── Phase 3: Output ─────────────────────────────────────────────
+    let prefix = root_dir.strip_prefix(repo_dir).unwrap_or(Path::new(""));
+    annotate_violations(&mut result, root_dir, prefix);
+    if let Some(codeowners) = Codeowners::load(repo_dir) {
+        assign_owners(&mut result, &codeowners);
+    }
+    if config.rules.promoted_tests_must_assert {
+        warn_trivial_promotions(&mut result, &gathered.status, root_dir, prefix);
+    }
+    if config.coverage.enabled {
+        match check_promoted_coverage(&mut result, &gathered.status, root_dir, &config.coverage) {
+            Ok(failures) => {
+                for (test, e) in failures {
+                    eprintln!("tdd-ratchet: could not measure coverage of {test}: {e}");
+                }
+            }
+            Err(e) => eprintln!("tdd-ratchet: failed to update coverage record: {e}"),
+        }
+    }
+    let head = head_commit(repo_dir);
+    if !config.plugins.rules.is_empty() {
+        let context = PluginContext::new(
+            head.as_deref(),
+            &gathered.status.tests,
+            &gathered.results,
+            &gathered.history_snapshots,
+            &result,
+        );
+        for (plugin, outcome) in run_plugins(&config.plugins, root_dir, &context) {
+            apply_findings(&mut result, &plugin, outcome);
+        }
+    }
+
     // Always save the updated status file — valid transitions (new
     // pending tests, promotions) should persist even when there are
     // violations. This prevents losing state on partial runs.
-    result
-        .updated
-        .write_to_path(status_path)
+    ratchet.save(&result)?;
+    let audit_path = config.audit.path(root_dir);
+    if let Err(e) = record_overrides(&audit_path, &result.warnings, unix_now()) {
+        eprintln!("tdd-ratchet: failed to record overrides: {e}");
+    }
+    let last_run_path = root_dir.join(LAST_RUN_FILE_NAME);
+    let previous = load_last_run(&last_run_path);
+    let mut summary = summarize(head.as_deref(), &result, unix_now());
+    summary.failing_drafts = failing_drafts(previous.as_ref(), &result, &gathered.results);
+    if let Some(previous) = previous {
+        result.since_last_run = Some(run_delta(&previous, head.as_deref(), &result));
+        if config.timing.enabled
+            && let Some(warning) = suite_slowdown(&previous, &summary, config.timing.max_growth())
+        {
+            result.warnings.push(warning);
+        }
+    }
+    if let Err(e) = save_last_run(&last_run_path, &summary) {
+        eprintln!("tdd-ratchet: failed to record run summary: {e}");
+    }
+    if config.audit.enabled {
+        let record = run_record(head.as_deref(), &gathered.status, &result, unix_now());
+        if let Err(e) = append_record(&audit_path, &record) {
+            eprintln!("tdd-ratchet: failed to record run in audit file: {e}");
+        }
+    }
+    for event in hook_events(&gathered.status, &result) {
+        let Some(command) = config.hooks.command(&event) else {
+            continue;
+        };
+        if let Err(e) = run_hook(command, root_dir, &event, head.as_deref()) {
+            eprintln!("tdd-ratchet: {} hook failed: {e}", event.key());
+        }
+    }
+    if let Some(owner) = &config.owner {
+        let hidden = retain_owned(&mut result, owner);
+        if hidden > 0 {
+            eprintln!("tdd-ratchet: {hidden} violation(s) in files not owned by {owner} not shown");
+        }
+    }
+
+    Ok((gathered.status, result))
+}
+
+fn tui(project_dir: &Path, status_path: &Path) {
+    let stdin = io::stdin();
+    let mut last_run: Option<bool> = None;
+
+    loop {
+        // The working-tree file is the latest ratchet output; fall back to
+        // the committed status before the first run.
+        let status = if status_path.exists() {
+            StatusFile::load(status_path)
+                .map(StatusFile::into_tracked_status)
+                .unwrap_or_else(|e| {
+                    eprintln!("tdd-ratchet: failed to read status file: {e}");
+                    process::exit(1);
+                })
+        } else {
+            load_committed_status_input(project_dir)
+        };
+        let snapshots = load_history_snapshots(project_dir);
+        let dashboard = build_dashboard(&status, &snapshots, DASHBOARD_PROMOTIONS);
+
+        // Keep a failing run's violations on screen; the dashboard is drawn
+        // below them instead of over them.
+        if last_run == Some(true) {
+            println!();
+        } else {
+            print!("\x1b[2J\x1b[H");
+        }
+        println!("tdd-ratchet dashboard");
+        match last_run {
+            Some(true) => println!("last run: violations (see output above the dashboard)"),
+            Some(false) => println!("last run: ok"),
+            None => println!("last run: not run in this session"),
+        }
+        println!();
+        print!("{}", render_dashboard(&dashboard));
+        println!();
+        print!("[Enter/r] re-run ratchet   [q] quit > ");
+        io::stdout().flush().ok();
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        match line.trim() {
+            "q" | "quit" => return,
+            "" | "r" => {
+                let lock = lock_run(project_dir, true);
+                last_run = Some(run_ratchet(
+                    project_dir,
+                    status_path,
+                    ReportFormat::Text,
+                    ReportOptions::default(),
+                    &[],
+                    None,
+                    RunScope::default(),
+                ));
+                drop(lock);
+                print!("\nPress Enter to return to the dashboard...");
+                io::stdout().flush().ok();
+                line.clear();
+                stdin.lock().read_line(&mut line).ok();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn stats(project_dir: &Path, json: bool) {
+    let audit = read_project_audit(project_dir);
+    let stats = compute_stats_with_audit(&load_history_snapshots(project_dir), &audit);
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).expect("stats should serialize")
+        );
+    } else {
+        print!("{}", format_stats(&stats));
+    }
+}
+
+/// `report --stale`: print pending tests, quarantines and accepted failures
+/// that need attention, for a cron job to post.
+fn report_stale(
+    project_dir: &Path,
+    status_path: &Path,
+    older_than: Option<&str>,
+    expiring_within: Option<&str>,
+    format: Option<&str>,
+) {
+    let days = |flag: &str, value: Option<&str>, default: u64| match value {
+        None => default,
+        Some(days) => days.parse().unwrap_or_else(|_| {
+            eprintln!("tdd-ratchet: {flag} expects a number of days, got `{days}`");
+            process::exit(2);
+        }),
+    };
+    let defaults = StaleOptions::default();
+    let options = StaleOptions {
+        older_than_days: days("--older-than", older_than, defaults.older_than_days),
+        expiring_within_days: days(
+            "--expiring-within",
+            expiring_within,
+            defaults.expiring_within_days,
+        ),
+    };
+    let format = match format {
+        Some(name) => StaleFormat::parse(name).unwrap_or_else(|| {
+            eprintln!(
+                "tdd-ratchet: unknown report format `{name}` (expected text, markdown or json)"
+            );
+            process::exit(2);
+        }),
+        None => StaleFormat::default(),
+    };
+    let status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let report = stale_report(
+        &status,
+        &load_history_snapshots(project_dir),
+        unix_now(),
+        options,
+    );
+    match format {
+        StaleFormat::Text => print!("{}", format_stale_text(&report)),
+        StaleFormat::Markdown => print!("{}", format_stale_markdown(&report)),
+        StaleFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("stale report should serialize")
+        ),
+    }
+}
+
+fn read_project_audit(project_dir: &Path) -> Vec<AuditRecord> {
+    read_audit(&load_config(project_dir).audit.path(project_dir)).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    })
+}
+
+/// `audit show`: print audit records, newest last, optionally filtered to one
+/// test and limited to the most recent `limit`.
+fn audit_show(project_dir: &Path, test: Option<&str>, limit: Option<&str>, json: bool) {
+    let limit = limit.map(|n| {
+        n.parse::<usize>().unwrap_or_else(|_| {
+            eprintln!("tdd-ratchet: --limit expects a number, got `{n}`");
+            process::exit(2);
+        })
+    });
+    let mut records: Vec<AuditRecord> = read_project_audit(project_dir)
+        .into_iter()
+        .filter(|record| test.is_none_or(|t| record.mentions(t)))
+        .collect();
+    if let Some(limit) = limit {
+        records.drain(..records.len().saturating_sub(limit));
+    }
+    if json {
+        for record in &records {
+            println!(
+                "{}",
+                serde_json::to_string(record).expect("audit records serialize")
+            );
+        }
+    } else if records.is_empty() {
+        println!("tdd-ratchet: no audit records");
+    } else {
+        print!("{}", format_audit(&records));
+    }
+}
+
+fn log(project_dir: &Path, status_path: &Path, test: Option<&String>) {
+    let Some(test) = test else {
+        eprintln!(
+            "tdd-ratchet: `log` needs a test name, e.g. `cargo ratchet log my-crate::tests$my_test`"
+        );
+        process::exit(2);
+    };
+    let status = StatusFile::load(status_path).ok();
+    let timeline = with_recorded_transitions(
+        test,
+        status.as_ref().and_then(|status| status.tests.get(test)),
+        test_timeline(&load_history_snapshots(project_dir), test),
+    );
+    print!("{}", format_timeline(test, &timeline));
+}
+
+fn blame(project_dir: &Path, status_path: &Path, test: Option<&String>) {
+    let Some(test) = test else {
+        eprintln!("tdd-ratchet: `blame` needs a test name");
+        process::exit(2);
+    };
+    let status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let entry = status.tests.get(test);
+    let timeline = with_recorded_transitions(
+        test,
+        entry,
+        test_timeline(&load_history_snapshots(project_dir), test),
+    );
+    if entry.is_none() && timeline.is_empty() {
+        eprintln!("tdd-ratchet: `{test}` is not tracked and has no committed history");
+        process::exit(2);
+    }
+    print!("{}", format_blame(test, entry, &timeline));
+}
+
+/// Replay the history rules over the commits in `range` (`A..B`, or `A`
+/// for `A..HEAD`) and print where each violation first appeared.
+fn replay_range(project_dir: &Path, range: Option<&String>) {
+    let Some(range) = range else {
+        eprintln!(
+            "tdd-ratchet: `replay` needs a commit range, e.g. `cargo ratchet replay v1.0..main`"
+        );
+        process::exit(2);
+    };
+    let (from, to) = match range.split_once("..") {
+        Some((from, "")) => (from, "HEAD"),
+        Some((from, to)) => (from, to),
+        None => (range.as_str(), "HEAD"),
+    };
+    let location = locate_in_repo(project_dir);
+    let in_range = commits_in_range(&location.repo_dir, from, to).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: cannot replay {range}: {e}");
+        process::exit(2);
+    });
+    let config = load_config(project_dir);
+    let snapshots = GitHistory::new(&location.repo_dir)
+        .with_status_path(location.status_path())
+        .with_start(to)
+        .snapshots()
         .unwrap_or_else(|e| {
-            eprintln!("tdd-ratchet: failed to save status file: {e}");
+            eprintln!("tdd-ratchet: failed to inspect git history: {e}");
             process::exit(1);
         });
+    let replay = replay(&snapshots, &in_range, &config.grandfather, &config.rules);
+    print!("{}", format_replay(range, &replay));
+}
 
-    let has_violations = !result.violations.is_empty();
-    let report = format_report(&result);
-    eprint!("\n{report}");
-
-    if has_violations {
+/// Update this binary to the latest release, or with `check` only say
+/// whether there is one.
+fn self_update(check: bool) {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = latest_release().unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: cannot check for updates: {e}");
         process::exit(1);
+    });
+    let latest = release.version();
+    if !is_newer(latest, current) {
+        println!("tdd-ratchet: {current} is the latest version");
+        return;
     }
+    if check {
+        println!(
+            "tdd-ratchet: {latest} is available (running {current}); run `cargo ratchet self update` to install it"
+        );
+        return;
+    }
+    let exe = env::current_exe().unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: cannot locate the running binary: {e}");
+        process::exit(1);
+    });
+    if let Err(e) = update(&release, &exe) {
+        eprintln!("tdd-ratchet: update to {latest} failed: {e}");
+        process::exit(1);
+    }
+    println!(
+        "tdd-ratchet: updated {} from {current} to {latest}",
+        exe.display()
+    );
+}
+
+/// Print how the status file changed since `rev`.
+fn diff(project_dir: &Path, status_path: &Path, rev: Option<&String>, head: bool) {
+    let Some(rev) = rev else {
+        eprintln!("tdd-ratchet: `diff` needs a commit or branch, e.g. `cargo ratchet diff main`");
+        process::exit(2);
+    };
+    let location = locate_in_repo(project_dir);
+    let committed = |rev: &str| {
+        status_at_rev(&location.repo_dir, rev, &location.status_path())
+            .unwrap_or_else(|e| {
+                eprintln!("tdd-ratchet: cannot read .test-status.json at `{rev}`: {e}");
+                process::exit(2);
+            })
+            .unwrap_or_else(StatusFile::empty)
+    };
+    let old = committed(rev);
+    let (label, new) = if head {
+        ("HEAD", committed("HEAD"))
+    } else {
+        let status = if status_path.exists() {
+            StatusFile::load(status_path).unwrap_or_else(|e| {
+                eprintln!("tdd-ratchet: {e}");
+                process::exit(1);
+            })
+        } else {
+            StatusFile::empty()
+        };
+        ("working tree", status)
+    };
+    print!(
+        "{}",
+        format_status_diff(rev, label, &diff_status(&old, &new))
+    );
 }
 
-fn gather_run(project_dir: &Path) -> GatheredRun {
-    let status = load_committed_status_input(project_dir);
-    let instructions = load_working_tree_instructions(project_dir);
-    let results = run_nextest(project_dir, true);
-    let history_snapshots = collect_history_snapshots(project_dir).unwrap_or_else(|e| {
+fn export(project_dir: &Path, format: Option<&str>) {
+    let format = match format {
+        Some(name) => ExportFormat::parse(name).unwrap_or_else(|| {
+            eprintln!("tdd-ratchet: unknown export format `{name}` (expected csv)");
+            process::exit(2);
+        }),
+        None => ExportFormat::default(),
+    };
+    let snapshots = load_history_snapshots(project_dir);
+    match format {
+        ExportFormat::Csv => print!("{}", export_csv(&snapshots)),
+    }
+}
+
+/// Print per-commit test counts, reusing the counts cached by earlier runs.
+fn trend(project_dir: &Path, last: Option<&str>, format: Option<&str>) {
+    let last = last.map(|n| {
+        n.parse::<usize>().unwrap_or_else(|_| {
+            eprintln!("tdd-ratchet: --last expects a number, got `{n}`");
+            process::exit(2);
+        })
+    });
+    let format = match format {
+        Some(name) => TrendFormat::parse(name).unwrap_or_else(|| {
+            eprintln!("tdd-ratchet: unknown trend format `{name}` (expected json or csv)");
+            process::exit(2);
+        }),
+        None => TrendFormat::default(),
+    };
+    let cache_path = project_dir.join(TREND_CACHE_FILE_NAME);
+    let mut cache = TrendCache::load(&cache_path);
+    let location = locate_in_repo(project_dir);
+    let points = collect_trend(
+        &location.repo_dir,
+        &location.status_path(),
+        last,
+        &mut cache,
+    )
+    .unwrap_or_else(|e| {
         eprintln!("tdd-ratchet: failed to inspect git history: {e}");
         process::exit(1);
     });
+    if let Err(e) = cache.save(&cache_path) {
+        eprintln!("tdd-ratchet: failed to write {TREND_CACHE_FILE_NAME}: {e}");
+    }
+    match format {
+        TrendFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&points).expect("trend points should serialize")
+        ),
+        TrendFormat::Csv => print!("{}", trend_csv(&points)),
+    }
+}
 
-    GatheredRun {
-        status,
-        instructions,
-        results,
-        history_snapshots,
+fn migrate(status_path: &Path) {
+    let status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    if !status.needs_migration() {
+        println!("tdd-ratchet: .test-status.json is already version {CURRENT_VERSION}");
+        return;
     }
+    let from = status.version();
+    status.save(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    println!("tdd-ratchet: migrated .test-status.json from version {from} to {CURRENT_VERSION}");
 }
 
-fn load_committed_status_input(project_dir: &Path) -> TrackedStatus {
-    read_head_status(project_dir)
+/// Seal the working-tree status file as a declared manual edit.
+fn reseal(project_dir: &Path, status_path: &Path, reason: Option<&str>) {
+    let Some(reason) = reason.filter(|r| !r.trim().is_empty()) else {
+        eprintln!(
+            "tdd-ratchet: `reseal` needs a reason, e.g. `cargo ratchet reseal --reason \"fix bad merge\"`"
+        );
+        process::exit(2);
+    };
+    let mut status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let snapshots = load_history_snapshots(project_dir);
+    let mut integrity = seal(latest_digest(&snapshots), &status);
+    integrity.override_reason = Some(reason.to_string());
+    status.integrity = Some(integrity);
+    status.save(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    println!(
+        "tdd-ratchet: resealed .test-status.json; commit it without running `cargo ratchet` first"
+    );
+}
+
+/// Describe one violation code, or list every code without one.
+fn explain_code(code: Option<&String>) {
+    let Some(code) = code else {
+        for explanation in &EXPLANATIONS {
+            println!(
+                "{} {}: {}",
+                explanation.code, explanation.name, explanation.summary
+            );
+        }
+        return;
+    };
+    match explain(code) {
+        Some(explanation) => print!("{}", format_explanation(explanation)),
+        None => {
+            eprintln!(
+                "tdd-ratchet: unknown violation code `{code}`; run `cargo ratchet explain` to list them"
+            );
+            process::exit(2);
+        }
+    }
+}
+
+/// Mark a tracked test as allowed to be ignored, for the
+/// `passing-to-ignored` rule.
+fn allow_ignore(project_dir: &Path, status_path: &Path, test: Option<&String>) {
+    let Some(test) = test else {
+        eprintln!("tdd-ratchet: `allow-ignore` needs a test name");
+        process::exit(2);
+    };
+    let mut status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let Some(entry) = status.tests.get_mut(test) else {
+        eprintln!("tdd-ratchet: `{test}` is not tracked in .test-status.json");
+        process::exit(2);
+    };
+    entry.allow_ignore = true;
+    save_declared(
+        project_dir,
+        status_path,
+        &mut status,
+        format!("allow-ignore {test}"),
+    );
+    println!(
+        "tdd-ratchet: {test} may be ignored once .test-status.json is committed; commit it without running `cargo ratchet` first"
+    );
+}
+
+/// Record an accepted failure for a passing test: its failures are
+/// tolerated until the acceptance expires.
+fn accept_failure(
+    project_dir: &Path,
+    status_path: &Path,
+    test: Option<&String>,
+    reason: Option<&str>,
+    until: Option<&str>,
+    commits: Option<&str>,
+) {
+    let Some(test) = test else {
+        eprintln!("tdd-ratchet: `accept-failure` needs a test name");
+        process::exit(2);
+    };
+    let Some(reason) = reason.filter(|r| !r.trim().is_empty()) else {
+        eprintln!("tdd-ratchet: `accept-failure` needs --reason <TEXT>");
+        process::exit(2);
+    };
+    let (until, commits) = match (until, commits) {
+        (Some(until), None) if is_valid_date(until) => (Some(until.to_string()), None),
+        (Some(until), None) => {
+            eprintln!("tdd-ratchet: --until needs a YYYY-MM-DD date, got `{until}`");
+            process::exit(2);
+        }
+        (None, Some(commits)) => match commits.parse::<u32>() {
+            Ok(commits) if commits > 0 => (None, Some(commits)),
+            _ => {
+                eprintln!("tdd-ratchet: --commits needs a positive number, got `{commits}`");
+                process::exit(2);
+            }
+        },
+        _ => {
+            eprintln!("tdd-ratchet: `accept-failure` needs exactly one of --until and --commits");
+            process::exit(2);
+        }
+    };
+    let mut status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let Some(entry) = status.tests.get_mut(test) else {
+        eprintln!("tdd-ratchet: `{test}` is not tracked in .test-status.json");
+        process::exit(2);
+    };
+    if entry.state() != TestState::Passing {
+        eprintln!(
+            "tdd-ratchet: `{test}` is pending; only passing tests can have failures accepted"
+        );
+        process::exit(2);
+    }
+    let acceptance = AcceptedFailure {
+        reason: reason.to_string(),
+        until,
+        commits,
+    };
+    let accepted = acceptance.to_string();
+    entry.accepted_failure = Some(acceptance);
+    save_declared(
+        project_dir,
+        status_path,
+        &mut status,
+        format!("accept-failure {test}: {accepted}"),
+    );
+    println!(
+        "tdd-ratchet: failures of {test} accepted once .test-status.json is committed: {accepted}; commit it without running `cargo ratchet` first"
+    );
+}
+
+/// Clear a test's quarantine, so its failures are regressions again, and
+/// start counting its flips over.
+fn unquarantine(project_dir: &Path, status_path: &Path, test: Option<&String>) {
+    let Some(test) = test else {
+        eprintln!("tdd-ratchet: `unquarantine` needs a test name");
+        process::exit(2);
+    };
+    let mut status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let Some(entry) = status.tests.get_mut(test) else {
+        eprintln!("tdd-ratchet: `{test}` is not tracked in .test-status.json");
+        process::exit(2);
+    };
+    if entry.quarantine.take().is_none() {
+        eprintln!("tdd-ratchet: `{test}` is not quarantined");
+        process::exit(2);
+    }
+    save_resealed(project_dir, status_path, &mut status);
+    let flips_path = project_dir.join(FLIPS_FILE_NAME);
+    let mut flips = load_flips(&flips_path);
+    if flips.tests.remove(test).is_some()
+        && let Err(e) = save_flips(&flips_path, &flips)
+    {
+        eprintln!("tdd-ratchet: failed to reset flip count: {e}");
+    }
+    println!(
+        "tdd-ratchet: {test} is no longer quarantined; commit .test-status.json with the change"
+    );
+}
+
+/// The arguments before the first flag.
+fn leading_names(args: &[String]) -> Vec<String> {
+    args.iter()
+        .take_while(|arg| !arg.starts_with('-'))
+        .cloned()
+        .collect()
+}
+
+/// Put pending or not yet tracked tests in the draft state. The file is
+/// resealed the way a run would, since states are fingerprinted.
+fn draft(project_dir: &Path, status_path: &Path, tests: &[String]) {
+    if tests.is_empty() {
+        eprintln!("tdd-ratchet: `draft` needs at least one test name");
+        process::exit(2);
+    }
+    // Skeletons may be drafted before the first run creates the file.
+    let mut status = if status_path.exists() {
+        StatusFile::load(status_path).unwrap_or_else(|e| {
+            eprintln!("tdd-ratchet: {e}");
+            process::exit(1);
+        })
+    } else {
+        StatusFile::empty()
+    };
+    for test in tests {
+        if status
+            .tests
+            .get(test)
+            .is_some_and(|entry| entry.state() == TestState::Passing)
+        {
+            eprintln!("tdd-ratchet: `{test}` is passing; only pending tests can be drafts");
+            process::exit(2);
+        }
+    }
+    for test in tests {
+        status.set_test_state(test.clone(), TestState::Draft);
+    }
+    save_resealed(project_dir, status_path, &mut status);
+    println!(
+        "tdd-ratchet: {} now {} a draft; commit .test-status.json with the change",
+        tests.join(", "),
+        if tests.len() == 1 { "is" } else { "are" }
+    );
+}
+
+/// Move drafts to pending once they have real assertions: the named ones,
+/// or every draft when none is named. Only a draft the last run saw failing
+/// is promoted, so a pending test has always failed before it passes.
+fn promote_drafts(project_dir: &Path, status_path: &Path, tests: &[String]) {
+    let mut status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let drafts: Vec<String> = status
+        .tests
+        .iter()
+        .filter(|(_, entry)| entry.state() == TestState::Draft)
+        .map(|(name, _)| name.clone())
+        .collect();
+    for test in tests {
+        if !drafts.contains(test) {
+            eprintln!("tdd-ratchet: `{test}` is not a draft");
+            process::exit(2);
+        }
+    }
+    if drafts.is_empty() {
+        println!("tdd-ratchet: no draft tests to promote");
+        return;
+    }
+    let failing = load_last_run(&project_dir.join(LAST_RUN_FILE_NAME))
+        .map(|summary| summary.failing_drafts)
+        .unwrap_or_default();
+    for test in tests {
+        if !failing.contains(test) {
+            eprintln!(
+                "tdd-ratchet: `{test}` has not been seen failing; make it fail without the implementation and run `cargo ratchet` before promoting it"
+            );
+            process::exit(2);
+        }
+    }
+    let promoted = if tests.is_empty() {
+        let (promoted, unseen): (Vec<String>, Vec<String>) =
+            drafts.into_iter().partition(|test| failing.contains(test));
+        if !unseen.is_empty() {
+            println!(
+                "tdd-ratchet: not promoting {}: not seen failing in the last run",
+                unseen.join(", ")
+            );
+        }
+        promoted
+    } else {
+        tests.to_vec()
+    };
+    if promoted.is_empty() {
+        return;
+    }
+    for test in &promoted {
+        status.set_test_state(test.clone(), TestState::Pending);
+    }
+    save_resealed(project_dir, status_path, &mut status);
+    println!(
+        "tdd-ratchet: promoted {} to pending; commit .test-status.json with the change",
+        promoted.join(", ")
+    );
+}
+
+fn save_resealed(project_dir: &Path, status_path: &Path, status: &mut StatusFile) {
+    let snapshots = load_history_snapshots(project_dir);
+    status.integrity = Some(seal(latest_digest(&snapshots), status));
+    status.save(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+}
+
+/// Seal `status` as a declared edit made by a command, so the integrity
+/// report shows `reason` once it is committed, and save it.
+fn save_declared(project_dir: &Path, status_path: &Path, status: &mut StatusFile, reason: String) {
+    let snapshots = load_history_snapshots(project_dir);
+    let mut integrity = seal(latest_digest(&snapshots), status);
+    integrity.override_reason = Some(reason);
+    status.integrity = Some(integrity);
+    status.save(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+}
+
+/// Move the global baseline to `rev` after confirmation, recording the
+/// change in the audit file.
+fn rebaseline(project_dir: &Path, status_path: &Path, rev: &str, assume_yes: bool) {
+    let commit = resolve_baseline(&locate_in_repo(project_dir).repo_dir, rev).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: cannot use `{rev}` as baseline: {e}");
+        process::exit(2);
+    });
+    let mut status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    if status.baseline.as_deref() == Some(commit.as_str()) {
+        println!("tdd-ratchet: baseline is already {}", &commit[..8]);
+        return;
+    }
+
+    if !assume_yes {
+        eprint!(
+            "Move the baseline to {}? History before it will no longer be checked and \
+             every test tracked there is grandfathered. [y/N] ",
+            &commit[..8]
+        );
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer).ok();
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            eprintln!("tdd-ratchet: rebaseline cancelled");
+            process::exit(1);
+        }
+    }
+
+    let previous = status.baseline.replace(commit.clone());
+    save_declared(
+        project_dir,
+        status_path,
+        &mut status,
+        format!("rebaseline to {}", &commit[..8]),
+    );
+    let record = AuditRecord::Rebaseline {
+        recorded_at: unix_now(),
+        commit: commit.clone(),
+        previous,
+    };
+    if let Err(e) = append_record(&load_config(project_dir).audit.path(project_dir), &record) {
+        eprintln!("tdd-ratchet: failed to record rebaseline in audit file: {e}");
+    }
+    println!(
+        "tdd-ratchet: baseline moved to {}; commit .test-status.json without running `cargo ratchet` first",
+        &commit[..8]
+    );
+}
+
+/// Remap baselines and recover pending history after a rebase, then reseal
+/// the status file.
+fn repair(project_dir: &Path, status_path: &Path, dry_run: bool) {
+    let mut status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let snapshots = load_history_snapshots(project_dir);
+    let location = locate_in_repo(project_dir);
+    let evidence = gather_repair_evidence_at(&location.repo_dir, &location.status_path())
         .unwrap_or_else(|e| {
-            eprintln!("tdd-ratchet: failed to read committed status file: {e}");
+            eprintln!("tdd-ratchet: failed to read git history: {e}");
+            process::exit(1);
+        });
+    let changes = plan_repair(&status, &snapshots, &evidence);
+    if changes.is_empty() {
+        println!("tdd-ratchet: nothing to repair");
+        return;
+    }
+    for change in &changes {
+        println!("  {change}");
+    }
+    if dry_run {
+        println!("tdd-ratchet: dry run, .test-status.json not changed");
+        return;
+    }
+
+    apply_repair(&mut status, &changes);
+    let mut integrity = seal(latest_digest(&snapshots), &status);
+    integrity.override_reason = Some("repair after history rewrite".to_string());
+    status.integrity = Some(integrity);
+    status.save(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    println!(
+        "tdd-ratchet: repaired .test-status.json; commit it without running `cargo ratchet` first"
+    );
+}
+
+/// Prune status-file entries that no longer do anything, listing each.
+fn gc(project_dir: &Path, status_path: &Path, retention_days: Option<&str>, dry_run: bool) {
+    let retention_days = match retention_days {
+        None => DEFAULT_RETENTION_DAYS,
+        Some(days) => days.parse().unwrap_or_else(|_| {
+            eprintln!("tdd-ratchet: --retention-days expects a number of days, got `{days}`");
+            process::exit(2);
+        }),
+    };
+    let mut status = StatusFile::load(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let snapshots = load_history_snapshots(project_dir);
+    let evidence = gather_gc_evidence(&locate_in_repo(project_dir).repo_dir, &status)
+        .unwrap_or_else(|e| {
+            eprintln!("tdd-ratchet: failed to read git history: {e}");
+            process::exit(1);
+        });
+    let changes = plan_gc(&status, &snapshots, &evidence, unix_now(), retention_days);
+    if changes.is_empty() {
+        println!("tdd-ratchet: nothing to collect");
+        return;
+    }
+    for change in &changes {
+        println!("  {change}");
+    }
+    if dry_run {
+        println!("tdd-ratchet: dry run, .test-status.json not changed");
+        return;
+    }
+
+    apply_gc(&mut status, &changes);
+    // Dropping a per-test baseline changes what history checks, so it is
+    // declared like a manual edit.
+    let mut integrity = seal(latest_digest(&snapshots), &status);
+    if changes
+        .iter()
+        .any(|change| matches!(change, GcChange::RedundantBaseline { .. }))
+    {
+        integrity.override_reason = Some("gc of redundant per-test baselines".to_string());
+    }
+    status.integrity = Some(integrity);
+    status.save(status_path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    println!(
+        "tdd-ratchet: removed {} {} from .test-status.json; commit it",
+        changes.len(),
+        if changes.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        }
+    );
+}
+
+/// Validate a status file against its schema, printing diagnostics. Returns
+/// whether it is valid.
+fn validate(path: &Path) -> bool {
+    let contents = fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: failed to read {}: {e}", path.display());
+        process::exit(1);
+    });
+    let diagnostics = validate_status_json(&contents);
+    for diagnostic in &diagnostics {
+        eprintln!("{}:{diagnostic}", path.display());
+    }
+    if diagnostics.is_empty() {
+        println!("tdd-ratchet: {} is valid", path.display());
+    }
+    diagnostics.is_empty()
+}
+
+fn load_config(project_dir: &Path) -> RatchetConfig {
+    RatchetConfig::load(project_dir).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    })
+}
+
+/// Print the status at the tip of the state ref.
+fn state_show(project_dir: &Path) {
+    let location = locate_in_repo(project_dir);
+    let status = RefStatusStore::new(&location.repo_dir)
+        .with_status_path(location.status_path())
+        .load()
+        .unwrap_or_else(|e| {
+            eprintln!("tdd-ratchet: {e}");
             process::exit(1);
         })
-        .map(StatusFile::into_tracked_status)
-        .unwrap_or_else(TrackedStatus::empty)
+        .unwrap_or_else(|| {
+            eprintln!("tdd-ratchet: no ratchet state in {STATE_REF}");
+            process::exit(1);
+        });
+    match status.to_json(Path::new(STATUS_FILE_NAME)) {
+        Ok(json) => print!("{json}"),
+        Err(e) => {
+            eprintln!("tdd-ratchet: {e}");
+            process::exit(1);
+        }
+    }
 }
 
-fn load_working_tree_instructions(project_dir: &Path) -> WorkingTreeInstructions {
-    let status_path = project_dir.join(".test-status.json");
-    if !status_path.exists() {
-        return WorkingTreeInstructions::default();
+/// Commit the status file at `path` onto the state ref, e.g. when switching
+/// an existing project to ref storage.
+fn state_import(project_dir: &Path, path: &Path) {
+    let status = StatusFile::load(path).unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: {e}");
+        process::exit(1);
+    });
+    let written = status
+        .to_json(path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            let location = locate_in_repo(project_dir);
+            write_state(
+                &location.repo_dir,
+                STATE_REF,
+                &location.status_path(),
+                &json,
+            )
+            .map_err(|e| format!("failed to write {STATE_REF}: {e}"))
+        })
+        .unwrap_or_else(|e| {
+            eprintln!("tdd-ratchet: {e}");
+            process::exit(1);
+        });
+    if written {
+        eprintln!("tdd-ratchet: imported {} into {STATE_REF}", path.display());
+    } else {
+        eprintln!("tdd-ratchet: {STATE_REF} already holds {}", path.display());
+    }
+}
+
+/// `ratchet.toml` with the rule severities given on the command line.
+fn load_run_config(project_dir: &Path, levels: &[(Rule, RuleLevel)]) -> RatchetConfig {
+    let mut config = load_config(project_dir);
+    for (rule, level) in levels {
+        config.rules.set_level(*rule, *level);
     }
+    config
+}
+
+/// The configured history provider for the project at `project_dir`, reading
+/// its status file from the repository that contains it.
+fn history_provider(config: &RatchetConfig, project_dir: &Path) -> Box<dyn HistoryProvider> {
+    let location = locate_in_repo(project_dir);
+    config.history_provider(&location.repo_dir, &location.status_path())
+}
+
+fn load_history_snapshots(project_dir: &Path) -> Vec<HistorySnapshot> {
+    let history = history_provider(&load_config(project_dir), project_dir);
+    history.snapshots().unwrap_or_else(|e| {
+        eprintln!("tdd-ratchet: failed to inspect git history: {e}");
+        process::exit(1);
+    })
+}
 
-    StatusFile::load(&status_path)
-        .map(|status| status.working_tree_instructions())
+fn load_committed_status_input(project_dir: &Path) -> TrackedStatus {
+    history_provider(&load_config(project_dir), project_dir)
+        .head_status()
         .unwrap_or_else(|e| {
-            eprintln!("tdd-ratchet: failed to read working-tree instructions: {e}");
+            eprintln!("tdd-ratchet: failed to read committed status file: {e}");
             process::exit(1);
         })
+        .map(StatusFile::into_tracked_status)
+        .unwrap_or_else(TrackedStatus::empty)
 }
 
 fn status_entries_from_results(results: &[TestResult]) -> BTreeMap<String, TestEntry> {
     results
         .iter()
         .filter_map(|result| match result.outcome {
-            TestOutcome::Passed => {
-                Some((result.name.clone(), TestEntry::Simple(TestState::Passing)))
-            }
-            TestOutcome::Failed => {
-                Some((result.name.clone(), TestEntry::Simple(TestState::Pending)))
+            TestOutcome::Passed | TestOutcome::Flaky => {
+                Some((result.name.clone(), TestEntry::new(TestState::Passing)))
             }
+            TestOutcome::Failed => Some((result.name.clone(), TestEntry::new(TestState::Pending))),
             TestOutcome::Ignored => None,
         })
         .collect()
 }
-
-fn run_nextest(project_dir: &Path, inherit_stderr: bool) -> Vec<TestResult> {
-    let mut command = Command::new("cargo");
-    command
-        .args([
-            "nextest",
-            "run",
-            "--no-fail-fast",
-            "--message-format",
-            "libtest-json",
-        ])
-        .current_dir(project_dir)
-        .env("TDD_RATCHET", "1")
-        .env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1");
-
-    if inherit_stderr {
-        command.stderr(Stdio::inherit());
-    }
-
-    let output = command.output().unwrap_or_else(|e| {
-        eprintln!("tdd-ratchet: failed to run cargo nextest: {e}");
-        process::exit(1);
-    });
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_nextest_output(&stdout)
-}
@@ -0,0 +1,145 @@
+// Merge-queue evaluation: check a branch as the queue builds it, merged
+// into its target, with only the branch's own commits in the history check.
+// The tests run on a temporary checkout of the merge, so a test the target
+// fixed is not blamed on a branch that does not have the fix yet.
+
+use crate::error::{EvaluationError, RatchetError};
+use crate::staged::checkout_into_temp;
+use crate::status::{TestEntry, TestState};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a branch meets the target it will be merged into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeBase {
+    /// The merge base of `HEAD` and the target; history after it is the
+    /// branch's own.
+    pub base: String,
+    /// The commit the target names.
+    pub target: String,
+}
+
+/// The merge base of `HEAD` and `rev` in the repository at `repo_path`.
+pub fn merge_base(repo_path: &Path, rev: &str) -> Result<MergeBase, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let target = repo.revparse_single(rev)?.peel_to_commit()?.id();
+    let head = repo.head()?.peel_to_commit()?.id();
+    let base = repo.merge_base(head, target)?;
+    Ok(MergeBase {
+        base: base.to_string(),
+        target: target.to_string(),
+    })
+}
+
+/// The stage bits of an index entry's flags; zero for a resolved entry.
+const STAGE_MASK: u16 = 0x3000;
+
+/// `HEAD` merged into a target, checked out into a temporary directory.
+/// The repository's index and working tree are not touched. The directory
+/// is removed on drop.
+#[derive(Debug)]
+pub struct MergedTree {
+    dir: PathBuf,
+}
+
+impl MergedTree {
+    /// Merge `HEAD` of the repository at `repo_path` into `target` and check
+    /// out the result. A conflict in a status file listed in `status_paths`,
+    /// relative to the repository root, keeps `HEAD`'s side: the status is
+    /// merged test by test instead (see [`merge_tests`]). Any other conflict
+    /// is an error.
+    pub fn checkout(
+        repo_path: &Path,
+        target: &str,
+        status_paths: &[PathBuf],
+    ) -> Result<Self, RatchetError> {
+        let repo = git2::Repository::open(repo_path)?;
+        let head = repo.head()?.peel_to_commit()?;
+        let theirs = repo.revparse_single(target)?.peel_to_commit()?;
+        let mut index = repo.merge_commits(&head, &theirs, None)?;
+
+        let mut conflicts = Vec::new();
+        let mut resolved = Vec::new();
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let entry = conflict
+                .our
+                .as_ref()
+                .or(conflict.their.as_ref())
+                .or(conflict.ancestor.as_ref());
+            let Some(entry) = entry else { continue };
+            let path = PathBuf::from(String::from_utf8_lossy(&entry.path).into_owned());
+            if status_paths.contains(&path) {
+                resolved.push((path, conflict.our));
+            } else {
+                conflicts.push(path.display().to_string());
+            }
+        }
+        if !conflicts.is_empty() {
+            return Err(EvaluationError::MergeConflicts {
+                target: target.to_string(),
+                paths: conflicts,
+            }
+            .into());
+        }
+        for (path, ours) in resolved {
+            index.conflict_remove(&path)?;
+            if let Some(mut entry) = ours {
+                entry.flags &= !STAGE_MASK;
+                index.add(&entry)?;
+            }
+        }
+
+        let tree = repo.find_tree(index.write_tree_to(&repo)?)?;
+        Ok(MergedTree {
+            dir: checkout_into_temp(&repo, &tree, "merged")?,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for MergedTree {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Three-way merge of tracked tests, as merging the branch (`ours`) into
+/// the target (`theirs`) from their merge `base` would leave them. Pure
+/// function — no IO.
+///
+/// A test changed on one side only takes that side's entry, including
+/// its removal. A test changed on both sides is passing if either side
+/// has it passing, so the merged run must not regress a test either side
+/// promoted.
+pub fn merge_tests(
+    base: &BTreeMap<String, TestEntry>,
+    ours: &BTreeMap<String, TestEntry>,
+    theirs: &BTreeMap<String, TestEntry>,
+) -> BTreeMap<String, TestEntry> {
+    let state =
+        |tests: &BTreeMap<String, TestEntry>, name: &str| tests.get(name).map(TestEntry::state);
+    let names: BTreeSet<&String> = base
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (b, o, t) = (state(base, name), state(ours, name), state(theirs, name));
+            let entry = if o == b {
+                theirs.get(name)
+            } else if t == b || o == Some(TestState::Passing) || t != Some(TestState::Passing) {
+                ours.get(name)
+            } else {
+                theirs.get(name)
+            };
+            entry.map(|entry| (name.clone(), entry.clone()))
+        })
+        .collect()
+}
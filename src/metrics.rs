@@ -0,0 +1,120 @@
+// Prometheus metrics for `--metrics-out`, in the text exposition format read
+// by node_exporter's textfile collector, so ratchet health can be scraped
+// across many repositories.
+//
+// Every metric carries a `root` label: `.` for a single-root project, the
+// root's path under `[[roots]]`. Violation counts are reported for every
+// code, zeros included, so absent series never need special-casing in
+// queries.
+
+use crate::explain::EXPLANATIONS;
+use crate::ratchet::{EvalResult, Warning};
+use crate::status::TestState;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// The `root` label of a single-root project.
+pub const SINGLE_ROOT: &str = ".";
+
+/// The exposition-format metrics for a run over `roots`, each given as its
+/// label and evaluation, that took `duration`. Pure function — no IO.
+pub fn format_metrics<'r>(
+    roots: impl IntoIterator<Item = (&'r str, &'r EvalResult)>,
+    duration: Duration,
+) -> String {
+    let roots: Vec<(&str, &EvalResult)> = roots.into_iter().collect();
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        "tdd_ratchet_tests",
+        "Tracked tests by state after the run.",
+    );
+    for (root, result) in &roots {
+        for (state, label) in [
+            (TestState::Passing, "passing"),
+            (TestState::Pending, "pending"),
+        ] {
+            let count = result
+                .updated
+                .tests
+                .values()
+                .filter(|entry| entry.state() == state)
+                .count();
+            let root = escape_label(root);
+            writeln!(
+                out,
+                "tdd_ratchet_tests{{root=\"{root}\",state=\"{label}\"}} {count}"
+            )
+            .unwrap();
+        }
+    }
+
+    header(
+        &mut out,
+        "tdd_ratchet_flaky_tests",
+        "Tests that failed and then passed on retry in the run.",
+    );
+    for (root, result) in &roots {
+        let count = result
+            .warnings
+            .iter()
+            .filter(|warning| matches!(warning, Warning::FlakyTest { .. }))
+            .count();
+        writeln!(
+            out,
+            "tdd_ratchet_flaky_tests{{root=\"{}\"}} {count}",
+            escape_label(root)
+        )
+        .unwrap();
+    }
+
+    header(
+        &mut out,
+        "tdd_ratchet_violations",
+        "Violations in the run by code.",
+    );
+    for (root, result) in &roots {
+        for explanation in &EXPLANATIONS {
+            let count = result
+                .violations
+                .iter()
+                .filter(|violation| violation.code() == explanation.code)
+                .count();
+            writeln!(
+                out,
+                "tdd_ratchet_violations{{root=\"{}\",code=\"{}\",rule=\"{}\"}} {count}",
+                escape_label(root),
+                explanation.code,
+                explanation.name
+            )
+            .unwrap();
+        }
+    }
+
+    header(
+        &mut out,
+        "tdd_ratchet_run_duration_seconds",
+        "Wall-clock time of the run, tests included.",
+    );
+    writeln!(
+        out,
+        "tdd_ratchet_run_duration_seconds {:.3}",
+        duration.as_secs_f64()
+    )
+    .unwrap();
+    out
+}
+
+fn header(out: &mut String, name: &str, help: &str) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} gauge").unwrap();
+}
+
+/// Escape a label value as the exposition format requires.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
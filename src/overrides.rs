@@ -0,0 +1,88 @@
+// Commit-trailer escape hatch: `Ratchet-Override: <test> -- <reason>` in a
+// commit message suppresses violations for that test at that commit.
+
+use crate::errors::violation_summary;
+use crate::history::HistorySnapshot;
+use crate::ratchet::{Violation, Warning};
+
+pub const OVERRIDE_TRAILER: &str = "Ratchet-Override";
+
+/// One `Ratchet-Override` trailer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    pub test: String,
+    pub reason: String,
+}
+
+/// Parse `Ratchet-Override: <test> -- <reason>` trailers from a commit
+/// message. Trailers without both a test and a reason are ignored.
+pub fn parse_overrides(message: &str) -> Vec<Override> {
+    message
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            if !key.trim().eq_ignore_ascii_case(OVERRIDE_TRAILER) {
+                return None;
+            }
+            let (test, reason) = value.split_once(" -- ")?;
+            let (test, reason) = (test.trim(), reason.trim());
+            (!test.is_empty() && !reason.is_empty()).then(|| Override {
+                test: test.to_string(),
+                reason: reason.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Remove violations covered by an override trailer, returning the rest and
+/// a warning for each override used.
+///
+/// History violations are matched against the commit they point at. Current
+/// run violations (regressions, new passing tests, disappeared or ignored
+/// tests) are matched against the newest snapshot's commit, normally `HEAD`.
+pub fn apply_overrides(
+    violations: Vec<Violation>,
+    snapshots: &[HistorySnapshot],
+) -> (Vec<Violation>, Vec<Warning>) {
+    let head = snapshots.last().map(|s| s.commit.as_str());
+    let mut remaining = Vec::new();
+    let mut used = Vec::new();
+
+    for violation in violations {
+        let target = match &violation {
+            Violation::SkippedPending { test, commit }
+            | Violation::PromotionEditedTest { test, commit, .. } => {
+                Some((test.as_str(), Some(commit.as_str())))
+            }
+            Violation::Regression { test }
+            | Violation::NewTestPassed { test }
+            | Violation::TestDisappeared { test }
+            | Violation::PassingTestIgnored { test } => Some((test.as_str(), head)),
+            _ => None,
+        };
+        let found = target.and_then(|(test, commit)| {
+            let snapshot = snapshots
+                .iter()
+                .find(|s| Some(s.commit.as_str()) == commit)?;
+            let reason = snapshot
+                .info
+                .overrides
+                .iter()
+                .find(|o| o.test == test)?
+                .reason
+                .clone();
+            Some(Warning::OverrideApplied {
+                commit: snapshot.commit.clone(),
+                test: test.to_string(),
+                rule: violation_summary(&violation).0.to_string(),
+                reason,
+            })
+        });
+        match found {
+            Some(warning) => used.push(warning),
+            None => remaining.push(violation),
+        }
+    }
+
+    (remaining, used)
+}
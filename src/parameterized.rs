@@ -0,0 +1,150 @@
+// Collapsing the generated cases of parameterized tests (rstest, test-case)
+// into one logical test.
+
+use crate::grandfather::test_matches;
+use crate::runner::{TestOutcome, TestResult};
+use crate::status::{TestEntry, TestState};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Case-name patterns from `parameterized` in `ratchet.toml`:
+///
+/// ```toml
+/// parameterized = ["*::case_*"]
+/// ```
+///
+/// A test whose name matches is one case of a parameterized test, tracked
+/// under its name without the last `::` segment: `parse::case_1` and
+/// `parse::case_2` are both `parse`. Patterns match like
+/// [`GrandfatherPatterns`](crate::grandfather::GrandfatherPatterns): the full
+/// tracked name or the test path after the `$`, with `*` matching any run of
+/// characters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct ParameterizedGroups(Vec<String>);
+
+impl ParameterizedGroups {
+    pub fn new(patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        ParameterizedGroups(patterns.into_iter().map(Into::into).collect())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The logical test `test` is a case of, or `None` if it is not a case.
+    pub fn group_of<'t>(&self, test: &'t str) -> Option<&'t str> {
+        let start = test.find('$').map_or(0, |dollar| dollar + 1);
+        let path = &test[start..];
+        if !self.0.iter().any(|pattern| test_matches(pattern, test)) {
+            return None;
+        }
+        let last = path.rfind("::")?;
+        Some(&test[..start + last])
+    }
+
+    /// `test` under its logical name.
+    pub fn logical_name<'t>(&self, test: &'t str) -> &'t str {
+        self.group_of(test).unwrap_or(test)
+    }
+
+    /// One result per logical test, in the order each first appears. A
+    /// parameterized test fails if any case fails, is flaky if any case is,
+    /// is ignored if every case is, and passes otherwise. Failed cases'
+    /// output is kept under their case names; case times add up.
+    /// Pure function — no IO.
+    pub fn collapse_results(&self, results: &[TestResult]) -> Vec<TestResult> {
+        let mut collapsed: Vec<TestResult> = Vec::new();
+        let mut index: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut cases: Vec<Vec<&TestResult>> = Vec::new();
+        for result in results {
+            let name = self.logical_name(&result.name);
+            let i = *index.entry(name).or_insert_with(|| {
+                collapsed.push(TestResult {
+                    name: name.to_string(),
+                    outcome: result.outcome,
+                    output: None,
+                    exec_time_ms: None,
+                });
+                cases.push(Vec::new());
+                collapsed.len() - 1
+            });
+            cases[i].push(result);
+        }
+
+        for (result, cases) in collapsed.iter_mut().zip(cases) {
+            if let [case] = cases.as_slice()
+                && case.name == result.name
+            {
+                *result = (*case).clone();
+                continue;
+            }
+            let any = |outcome| cases.iter().any(|case| case.outcome == outcome);
+            result.outcome = if any(TestOutcome::Failed) {
+                TestOutcome::Failed
+            } else if any(TestOutcome::Flaky) {
+                TestOutcome::Flaky
+            } else if cases
+                .iter()
+                .all(|case| case.outcome == TestOutcome::Ignored)
+            {
+                TestOutcome::Ignored
+            } else {
+                TestOutcome::Passed
+            };
+            let failures: Vec<String> = cases
+                .iter()
+                .filter(|case| case.outcome == TestOutcome::Failed)
+                .map(|case| match &case.output {
+                    Some(output) => format!("{}:\n{output}", case.name),
+                    None => case.name.clone(),
+                })
+                .collect();
+            if !failures.is_empty() {
+                result.output = Some(failures.join("\n"));
+            }
+            result.exec_time_ms = cases
+                .iter()
+                .filter_map(|case| case.exec_time_ms)
+                .reduce(|total, ms| total + ms);
+        }
+        collapsed
+    }
+
+    /// One entry per logical test. A parameterized test is pending while any
+    /// of its cases is, and its entry is that of its first case in that
+    /// state, with the earliest `added_at` and the cases' flakes added up.
+    /// Pure function — no IO.
+    pub fn collapse_tests(
+        &self,
+        tests: &BTreeMap<String, TestEntry>,
+    ) -> BTreeMap<String, TestEntry> {
+        let mut groups: BTreeMap<&str, Vec<&TestEntry>> = BTreeMap::new();
+        for (name, entry) in tests {
+            groups
+                .entry(self.logical_name(name))
+                .or_default()
+                .push(entry);
+        }
+        groups
+            .into_iter()
+            .map(|(name, entries)| {
+                let state = if entries.iter().any(|e| e.state() == TestState::Pending) {
+                    TestState::Pending
+                } else {
+                    TestState::Passing
+                };
+                let mut entry = entries
+                    .iter()
+                    .find(|e| e.state() == state)
+                    .map(|e| (*e).clone())
+                    .expect("a case is in the group's state");
+                if entries.len() > 1 {
+                    entry.added_at = entries.iter().filter_map(|e| e.added_at).min();
+                    entry.flakes = entries.iter().map(|e| e.flakes).sum();
+                }
+                (name.to_string(), entry)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,176 @@
+// Sharded CI runs: each job runs one nextest partition and emits its partial
+// results; a final job merges them and evaluates the ratchet once.
+//
+// Evaluating a single shard would report every test in the other shards as
+// disappeared, so partial results are only ever written to disk here and
+// evaluated after `merge_partial_results` has put the whole run back together.
+
+use crate::error::{EvaluationError, ParseError, RatchetError};
+use crate::runner::{RunnerError, TestOutcome, TestResult, TestRunner, parse_nextest_output};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+/// One shard of a run: partition `index` of `count`, both 1-based as in
+/// nextest's `--partition count:1/4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Partition {
+    pub index: u32,
+    pub count: u32,
+}
+
+impl Partition {
+    /// Parse `M/N`, optionally prefixed with nextest's `count:`.
+    pub fn parse(s: &str) -> Option<Partition> {
+        let (index, count) = s.strip_prefix("count:").unwrap_or(s).split_once('/')?;
+        let partition = Partition {
+            index: index.trim().parse().ok()?,
+            count: count.trim().parse().ok()?,
+        };
+        (1..=partition.count)
+            .contains(&partition.index)
+            .then_some(partition)
+    }
+
+    /// The value of nextest's `--partition` flag.
+    pub fn nextest_arg(&self) -> String {
+        format!("count:{self}")
+    }
+}
+
+impl fmt::Display for Partition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+impl TryFrom<String> for Partition {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Partition::parse(&s).ok_or_else(|| format!("invalid partition `{s}` (expected M/N)"))
+    }
+}
+
+impl From<Partition> for String {
+    fn from(partition: Partition) -> String {
+        partition.to_string()
+    }
+}
+
+/// The results of one shard, as written by `run --emit-results`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialResults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<Partition>,
+    pub results: Vec<TestResult>,
+}
+
+/// Write one shard's results to `path`.
+pub fn save_partial_results(path: &Path, partial: &PartialResults) -> Result<(), RatchetError> {
+    let json =
+        serde_json::to_string_pretty(partial).map_err(|source| ParseError::PartialResults {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    std::fs::write(path, json + "\n").map_err(|e| RatchetError::io(path, e))
+}
+
+/// Read one shard's results from `path`.
+pub fn load_partial_results(path: &Path) -> Result<PartialResults, RatchetError> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|source| RatchetError::io(path, source))?;
+    serde_json::from_str(&contents).map_err(|source| {
+        RatchetError::from(ParseError::PartialResults {
+            path: path.to_path_buf(),
+            source,
+        })
+    })
+}
+
+/// Read the results of a run captured earlier: the partial results that
+/// `run --emit-results` writes, or nextest's libtest-json event stream. A
+/// single shard of a partitioned run is refused, as it is by
+/// [`merge_partial_results`].
+pub fn load_results_file(path: &Path) -> Result<Vec<TestResult>, RatchetError> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|source| RatchetError::io(path, source))?;
+    match serde_json::from_str::<PartialResults>(&contents) {
+        Ok(partial) => merge_partial_results(&[partial]),
+        Err(_) => Ok(parse_nextest_output(&contents)),
+    }
+}
+
+/// Combine shards into the results of the whole run. Partitioned shards must
+/// cover every partition of one run exactly once. A test reported by more
+/// than one shard keeps its worst outcome, so a failure is never hidden.
+pub fn merge_partial_results(parts: &[PartialResults]) -> Result<Vec<TestResult>, RatchetError> {
+    let partitions: Vec<Partition> = parts.iter().filter_map(|part| part.partition).collect();
+    let counts: BTreeSet<u32> = partitions.iter().map(|p| p.count).collect();
+    if counts.len() > 1 {
+        return Err(EvaluationError::MixedPartitionCounts { counts }.into());
+    }
+    let mut seen = BTreeSet::new();
+    for partition in &partitions {
+        if !seen.insert(*partition) {
+            return Err(EvaluationError::DuplicatePartition(*partition).into());
+        }
+    }
+    if let Some(count) = counts.first() {
+        let missing: Vec<Partition> = (1..=*count)
+            .map(|index| Partition {
+                index,
+                count: *count,
+            })
+            .filter(|partition| !seen.contains(partition))
+            .collect();
+        if !missing.is_empty() {
+            return Err(EvaluationError::MissingPartitions(missing).into());
+        }
+    }
+
+    let mut merged: Vec<TestResult> = Vec::new();
+    let mut positions: BTreeMap<String, usize> = BTreeMap::new();
+    for result in parts.iter().flat_map(|part| &part.results) {
+        match positions.get(&result.name) {
+            Some(&i) if severity(result.outcome) > severity(merged[i].outcome) => {
+                merged[i] = result.clone();
+            }
+            Some(_) => {}
+            None => {
+                positions.insert(result.name.clone(), merged.len());
+                merged.push(result.clone());
+            }
+        }
+    }
+    Ok(merged)
+}
+
+fn severity(outcome: TestOutcome) -> u8 {
+    match outcome {
+        TestOutcome::Ignored => 0,
+        TestOutcome::Passed => 1,
+        TestOutcome::Flaky => 2,
+        TestOutcome::Failed => 3,
+    }
+}
+
+/// A runner that replays results gathered elsewhere, e.g. merged shards.
+#[derive(Debug, Clone)]
+pub struct RecordedRunner {
+    results: Vec<TestResult>,
+}
+
+impl RecordedRunner {
+    pub fn new(results: Vec<TestResult>) -> Self {
+        RecordedRunner { results }
+    }
+}
+
+impl TestRunner for RecordedRunner {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        Ok(self.results.clone())
+    }
+}
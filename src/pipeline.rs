@@ -0,0 +1,591 @@
+// Library entry point: the gather → evaluate → save pipeline behind
+// injectable components.
+
+use crate::config::{DEFAULT_FLAKE_THRESHOLD, Rule, RuleLevel, RulesConfig};
+use crate::error::RatchetError;
+use crate::grandfather::GrandfatherPatterns;
+use crate::history::{GitHistory, HistoryProvider, HistorySnapshot};
+use crate::integrity::{latest_digest, seal};
+use crate::overrides::apply_overrides;
+use crate::parameterized::ParameterizedGroups;
+use crate::ratchet::{
+    EvalResult, GATEKEEPER_TEST_NAME, Violation, Warning, duplicate_test_names, evaluate_with,
+    flaky_tests, ignored_passing_tests, integrity_findings, mistyped_pending_additions,
+    oversized_promotions, record_exec_times, record_toolchain, record_transitions, toolchain_drift,
+};
+use crate::run_filter::RunFilter;
+use crate::runner::{NextestRunner, TestOutcome, TestResult, TestRunner};
+use crate::status::{
+    StatusFile, StatusStore, TestState, Toolchain, TrackedStatus, WorkingTreeInstructions,
+    WorkingTreeStatusFile, binary_selected, split_binary, unix_now,
+};
+use crate::test_edits::{TestEdit, promotions};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{debug, info, info_span};
+
+/// Everything a ratchet evaluation needs, gathered from the outside world.
+#[derive(Debug, Clone, Default)]
+pub struct GatheredRun {
+    /// Tracked status committed at `HEAD` (empty before the first commit).
+    pub status: TrackedStatus,
+    pub instructions: WorkingTreeInstructions,
+    pub results: Vec<TestResult>,
+    pub history_snapshots: Vec<HistorySnapshot>,
+    /// Promotions that edited their own test; gathered only when the
+    /// `promotion-must-not-edit-test` rule is enabled.
+    pub test_edits: Vec<TestEdit>,
+    /// Optional rules configured in `ratchet.toml`.
+    pub rules: RulesConfig,
+    /// Tests exempt from the test-first rules, from `ratchet.toml`.
+    pub grandfather: GrandfatherPatterns,
+    /// Gatekeeper tests that still passed when run without `TDD_RATCHET`;
+    /// gathered unless the `missing-gatekeeper` rule is off.
+    pub unguarded_gatekeepers: Vec<String>,
+    /// Whether the gatekeeper echoed the run's nonce; `None` unless
+    /// `[gatekeeper] handshake` is on.
+    pub gatekeeper_acknowledged: Option<bool>,
+    /// Status and test files with uncommitted changes; gathered unless the
+    /// `dirty-worktree` rule is off.
+    pub dirty_paths: Vec<String>,
+    /// How often the test run was retried after an infrastructure failure.
+    pub infrastructure_retries: u32,
+    /// How many state changes each entry keeps, from `[state]
+    /// transition-history`.
+    pub transition_history: usize,
+    /// Whether entries keep their latest execution time, from `[timing]
+    /// enabled`.
+    pub exec_times: bool,
+    /// The toolchain the tests ran with; detected when `[state] toolchain`
+    /// is set.
+    pub toolchain: Option<Toolchain>,
+    /// Case-name patterns of parameterized tests, from `ratchet.toml`.
+    pub parameterized: ParameterizedGroups,
+    /// Test binaries this run was limited to with `--test`; empty for all.
+    pub binaries: Vec<String>,
+    /// The `-p` and `-E` filters this run was limited to.
+    pub filter: RunFilter,
+}
+
+impl GatheredRun {
+    /// Apply all ratchet rules. Pure — no IO; each decision is only traced.
+    pub fn evaluate(&self) -> EvalResult {
+        let _span = info_span!("evaluate", results = self.results.len()).entered();
+        let result = self.evaluate_rules();
+        self.trace_decisions(&result);
+        result
+    }
+
+    /// Emit a `tracing` event for each state change and violation.
+    fn trace_decisions(&self, result: &EvalResult) {
+        for (test, entry) in &result.updated.tests {
+            let before = self.status.tests.get(test).map(|entry| entry.state());
+            if before != Some(entry.state()) {
+                debug!(test, from = ?before, to = %entry.state(), "state change");
+            }
+        }
+        for test in self.status.tests.keys() {
+            if !result.updated.tests.contains_key(test) {
+                debug!(test, "entry dropped");
+            }
+        }
+        for violation in &result.violations {
+            info!(code = violation.code(), ?violation, "violation");
+        }
+        for warning in &result.warnings {
+            debug!(?warning, "warning");
+        }
+    }
+
+    fn evaluate_rules(&self) -> EvalResult {
+        if !self.parameterized.is_empty() {
+            return self.evaluate_grouped();
+        }
+        if !self.binaries.is_empty() || !self.filter.is_empty() {
+            return self.evaluate_partial();
+        }
+        let mut result = evaluate_with(
+            &self.status,
+            &self.instructions,
+            &self.results,
+            &self.history_snapshots,
+            &self.grandfather,
+        );
+        result.infrastructure_retries = self.infrastructure_retries;
+        record_transitions(
+            &mut result.updated.tests,
+            &self.history_snapshots,
+            self.transition_history,
+        );
+        record_exec_times(&mut result.updated.tests, &self.results, self.exec_times);
+        record_toolchain(
+            &mut result.updated.tests,
+            &self.status,
+            self.toolchain.as_ref(),
+        );
+        if let Some(current) = &self.toolchain {
+            for (test, promoted) in toolchain_drift(&result.violations, &self.status, current) {
+                result.warnings.push(Warning::ToolchainChanged {
+                    test,
+                    promoted,
+                    current: current.clone(),
+                });
+            }
+        }
+        let mut rule_violations: Vec<Violation> = duplicate_test_names(&self.results)
+            .into_iter()
+            .map(|(test, count)| Violation::DuplicateTestName { test, count })
+            .collect();
+        if !self.dirty_paths.is_empty() {
+            rule_violations.push(Violation::DirtyWorktree {
+                paths: self.dirty_paths.clone(),
+            });
+        }
+        rule_violations.extend(
+            self.test_edits
+                .iter()
+                .map(|edit| Violation::PromotionEditedTest {
+                    test: edit.test.clone(),
+                    commit: edit.commit.clone(),
+                    path: edit.path.clone(),
+                }),
+        );
+        rule_violations.extend(
+            self.unguarded_gatekeepers
+                .iter()
+                .map(|test| Violation::GatekeeperDoesNotBlock { test: test.clone() }),
+        );
+        // Without a passing gatekeeper there is nothing to acknowledge;
+        // MissingGatekeeper or a regression covers that.
+        let gatekeeper_passed = self
+            .results
+            .iter()
+            .any(|r| r.name.ends_with(GATEKEEPER_TEST_NAME) && r.outcome == TestOutcome::Passed);
+        if gatekeeper_passed && self.gatekeeper_acknowledged == Some(false) {
+            rule_violations.push(Violation::GatekeeperHandshakeMissing);
+        }
+        let level = self.rules.passing_to_ignored;
+        if level != RuleLevel::Allow {
+            for test in ignored_passing_tests(&result.updated, &self.results) {
+                if level == RuleLevel::Deny {
+                    rule_violations.push(Violation::PassingTestIgnored { test });
+                } else {
+                    result.warnings.push(Warning::PassingTestIgnored { test });
+                }
+            }
+        }
+        let threshold = self
+            .rules
+            .flake_threshold
+            .unwrap_or(DEFAULT_FLAKE_THRESHOLD);
+        for (test, flakes) in flaky_tests(&result.updated, &self.results, threshold) {
+            result.warnings.push(Warning::FlakyTest { test, flakes });
+        }
+        if let Some(limit) = self.rules.max_promotions_per_commit {
+            rule_violations.extend(
+                oversized_promotions(
+                    &self.history_snapshots,
+                    result.updated.baseline.as_deref(),
+                    limit,
+                )
+                .into_iter()
+                .map(|(commit, tests)| Violation::TooManyPromotions {
+                    commit,
+                    tests,
+                    limit,
+                }),
+            );
+        }
+        if !self.rules.pending_test_commit_types.is_empty() {
+            let allowed = &self.rules.pending_test_commit_types;
+            rule_violations.extend(
+                mistyped_pending_additions(
+                    &self.history_snapshots,
+                    result.updated.baseline.as_deref(),
+                    allowed,
+                )
+                .into_iter()
+                .map(
+                    |(commit, summary, tests)| Violation::PendingTestCommitType {
+                        commit,
+                        summary,
+                        tests,
+                        allowed: allowed.clone(),
+                    },
+                ),
+            );
+        }
+        if let Some(limit) = self.rules.max_pending {
+            rule_violations.extend(self.enforce_pending_limit(&mut result, limit));
+        }
+        let (violations, used_overrides) =
+            apply_overrides(rule_violations, &self.history_snapshots);
+        result.violations.extend(violations);
+        result.warnings.extend(used_overrides);
+
+        // Only rules at `deny` fail the run.
+        for violation in std::mem::take(&mut result.violations) {
+            match violation.rule().map(|rule| self.rules.level(rule)) {
+                None | Some(RuleLevel::Deny) => result.violations.push(violation),
+                Some(RuleLevel::Warn) => result.warnings.push(Warning::RuleWarning { violation }),
+                Some(RuleLevel::Allow) => {}
+            }
+        }
+        result
+    }
+
+    /// [`evaluate`](Self::evaluate) with the cases of each parameterized
+    /// test collapsed into one logical test, in this run and in every
+    /// history snapshot, so all rules see the same grouping. Seals cover the
+    /// status files as committed, so integrity is checked on the original
+    /// snapshots and the updated status is sealed after the collapse.
+    fn evaluate_grouped(&self) -> EvalResult {
+        let groups = &self.parameterized;
+        let grouped = GatheredRun {
+            status: TrackedStatus::new(groups.collapse_tests(&self.status.tests)),
+            results: groups.collapse_results(&self.results),
+            history_snapshots: self
+                .history_snapshots
+                .iter()
+                .map(|snapshot| {
+                    let mut snapshot = snapshot.clone();
+                    snapshot.status.tests = groups.collapse_tests(&snapshot.status.tests);
+                    snapshot.status.integrity = None;
+                    snapshot
+                })
+                .collect(),
+            parameterized: ParameterizedGroups::default(),
+            ..self.clone()
+        };
+        let mut result = grouped.evaluate();
+
+        let (tampered, overridden) = integrity_findings(&self.history_snapshots);
+        let (tampered, used_overrides) = apply_overrides(tampered, &self.history_snapshots);
+        result.violations.extend(tampered);
+        result.warnings.extend(overridden);
+        result.warnings.extend(used_overrides);
+        result.updated.integrity = Some(seal(
+            latest_digest(&self.history_snapshots),
+            &result.updated,
+        ));
+        result
+    }
+
+    /// [`evaluate`](Self::evaluate) for the tests of the selected binaries
+    /// and filters only. Tests outside them did not run, so they are carried
+    /// over unchanged instead of disappearing or regressing; tests the filters
+    /// may select, tests outside any binary and the gatekeeper are always
+    /// evaluated. The result is labeled as a partial run.
+    fn evaluate_partial(&self) -> EvalResult {
+        let in_scope = |test: &str| {
+            test.ends_with(GATEKEEPER_TEST_NAME)
+                || (split_binary(test).is_none_or(|(binary, _)| {
+                    self.binaries.is_empty() || binary_selected(binary, &self.binaries)
+                }) && !self.filter.excludes(test))
+        };
+        let (selected, others): (BTreeMap<_, _>, BTreeMap<_, _>) = self
+            .status
+            .tests
+            .clone()
+            .into_iter()
+            .partition(|(name, _)| in_scope(name));
+        let scoped = GatheredRun {
+            status: TrackedStatus::new(selected),
+            results: self
+                .results
+                .iter()
+                .filter(|result| in_scope(&result.name))
+                .cloned()
+                .collect(),
+            binaries: Vec::new(),
+            filter: RunFilter::default(),
+            ..self.clone()
+        };
+        let mut result = scoped.evaluate();
+        result.partial = self.filter.label(&self.binaries);
+        result.updated.tests.extend(others);
+        result.updated.integrity = Some(seal(
+            latest_digest(&self.history_snapshots),
+            &result.updated,
+        ));
+        result
+    }
+
+    /// Keep the pending count within `limit`. When this run's new pending
+    /// tests would exceed it, they are left out of the updated status, so
+    /// they are reported again until existing work is finished.
+    fn enforce_pending_limit(&self, result: &mut EvalResult, limit: usize) -> Option<Violation> {
+        let pending: Vec<&String> = result
+            .updated
+            .tests
+            .iter()
+            .filter(|(_, entry)| entry.state() == TestState::Pending)
+            .map(|(name, _)| name)
+            .collect();
+        if pending.len() <= limit {
+            return None;
+        }
+        let new: Vec<String> = pending
+            .into_iter()
+            .filter(|name| {
+                !self.status.tests.contains_key(*name)
+                    && !self.instructions.renames.contains_key(*name)
+            })
+            .cloned()
+            .collect();
+        if new.is_empty() {
+            return None;
+        }
+
+        for name in &new {
+            result.updated.tests.remove(name);
+        }
+        result.updated.integrity = Some(seal(
+            latest_digest(&self.history_snapshots),
+            &result.updated,
+        ));
+        let pending = result
+            .updated
+            .tests
+            .values()
+            .filter(|entry| entry.state() == TestState::Pending)
+            .count();
+        Some(Violation::PendingLimitExceeded {
+            tests: new,
+            pending,
+            limit,
+        })
+    }
+}
+
+/// A configured ratchet: a test runner, a history provider, and a status
+/// store.
+///
+/// ```no_run
+/// use tdd_ratchet::Ratchet;
+///
+/// let result = Ratchet::builder(".").build().run()?;
+/// println!("{} violations", result.violations.len());
+/// # Ok::<(), tdd_ratchet::RatchetError>(())
+/// ```
+pub struct Ratchet<'a> {
+    runner: Box<dyn TestRunner + 'a>,
+    history: Box<dyn HistoryProvider + 'a>,
+    store: Box<dyn StatusStore + 'a>,
+    rules: RulesConfig,
+    grandfather: GrandfatherPatterns,
+    transition_history: usize,
+    exec_times: bool,
+    toolchain: Option<Toolchain>,
+    parameterized: ParameterizedGroups,
+    binaries: Vec<String>,
+    filter: RunFilter,
+}
+
+/// Builder for [`Ratchet`]. Starts from the standard components for a project
+/// directory (nextest, the git repository, `.test-status.json`); each can be
+/// replaced.
+pub struct RatchetBuilder<'a> {
+    runner: Box<dyn TestRunner + 'a>,
+    history: Box<dyn HistoryProvider + 'a>,
+    store: Box<dyn StatusStore + 'a>,
+    rules: RulesConfig,
+    grandfather: GrandfatherPatterns,
+    transition_history: usize,
+    exec_times: bool,
+    toolchain: Option<Toolchain>,
+    parameterized: ParameterizedGroups,
+    binaries: Vec<String>,
+    filter: RunFilter,
+}
+
+impl<'a> Ratchet<'a> {
+    pub fn builder(project_dir: impl AsRef<Path>) -> RatchetBuilder<'a> {
+        let project_dir = project_dir.as_ref();
+        RatchetBuilder {
+            runner: Box::new(NextestRunner::new(project_dir)),
+            history: Box::new(GitHistory::new(project_dir)),
+            store: Box::new(WorkingTreeStatusFile::new(
+                project_dir.join(".test-status.json"),
+            )),
+            rules: RulesConfig::default(),
+            grandfather: GrandfatherPatterns::default(),
+            transition_history: 0,
+            exec_times: false,
+            toolchain: None,
+            parameterized: ParameterizedGroups::default(),
+            binaries: Vec::new(),
+            filter: RunFilter::default(),
+        }
+    }
+
+    /// Gather the committed status, working-tree instructions, test results,
+    /// and history snapshots.
+    pub fn gather(&self) -> Result<GatheredRun, RatchetError> {
+        let _span = info_span!("gather").entered();
+        let dirty_paths = if self.rules.level(Rule::DirtyWorktree) == RuleLevel::Allow {
+            Vec::new()
+        } else {
+            self.history.dirty_paths()?
+        };
+        let status = self
+            .history
+            .head_status()?
+            .map(StatusFile::into_tracked_status)
+            .unwrap_or_else(TrackedStatus::empty);
+        let instructions = self.store.load_instructions()?;
+        let results = self.runner.run()?;
+        let gatekeeper_acknowledged = self.runner.gatekeeper_acknowledged();
+        let infrastructure_retries = self.runner.infrastructure_retries();
+        let history_snapshots = self.history.snapshots()?;
+
+        let mut test_edits = Vec::new();
+        if self.rules.promotion_must_not_edit_test {
+            for (test, commit) in promotions(&history_snapshots) {
+                test_edits.extend(self.history.edited_tests(&commit, &[test])?);
+            }
+        }
+
+        let mut unguarded_gatekeepers = Vec::new();
+        if self.rules.level(Rule::MissingGatekeeper) != RuleLevel::Allow {
+            for result in &results {
+                if !result.name.ends_with(GATEKEEPER_TEST_NAME)
+                    || result.outcome != TestOutcome::Passed
+                {
+                    continue;
+                }
+                let unguarded = self.runner.run_without_ratchet(&result.name)?;
+                if unguarded == Some(TestOutcome::Passed) {
+                    unguarded_gatekeepers.push(result.name.clone());
+                }
+            }
+        }
+
+        Ok(GatheredRun {
+            status,
+            instructions,
+            results,
+            history_snapshots,
+            test_edits,
+            rules: self.rules.clone(),
+            grandfather: self.grandfather.clone(),
+            unguarded_gatekeepers,
+            gatekeeper_acknowledged,
+            dirty_paths,
+            infrastructure_retries,
+            transition_history: self.transition_history,
+            exec_times: self.exec_times,
+            toolchain: self.toolchain.clone(),
+            parameterized: self.parameterized.clone(),
+            binaries: self.binaries.clone(),
+            filter: self.filter.clone(),
+        })
+    }
+
+    /// Persist the updated status from an evaluation, stamping new tests and
+    /// state changes with the current time.
+    pub fn save(&self, result: &EvalResult) -> Result<(), RatchetError> {
+        let mut updated = result.updated.clone();
+        updated.stamp(unix_now());
+        debug!(tests = updated.tests.len(), "saving status");
+        self.store.save(&updated)
+    }
+
+    /// Gather, evaluate, and save.
+    ///
+    /// The updated status is saved even when there are violations — valid
+    /// transitions (new pending tests, promotions) should persist so state
+    /// is not lost on partial runs.
+    pub fn run(&self) -> Result<EvalResult, RatchetError> {
+        let result = self.gather()?.evaluate();
+        self.save(&result)?;
+        Ok(result)
+    }
+}
+
+impl<'a> RatchetBuilder<'a> {
+    pub fn runner(mut self, runner: impl TestRunner + 'a) -> Self {
+        self.runner = Box::new(runner);
+        self
+    }
+
+    pub fn history(mut self, history: impl HistoryProvider + 'a) -> Self {
+        self.history = Box::new(history);
+        self
+    }
+
+    pub fn status_store(mut self, store: impl StatusStore + 'a) -> Self {
+        self.store = Box::new(store);
+        self
+    }
+
+    /// Enable optional rules from `ratchet.toml`.
+    pub fn rules(mut self, rules: RulesConfig) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Exempt tests matching `grandfather` patterns from the test-first
+    /// rules.
+    pub fn grandfather(mut self, grandfather: GrandfatherPatterns) -> Self {
+        self.grandfather = grandfather;
+        self
+    }
+
+    /// Keep each test's latest `keep` state changes in its status entry.
+    pub fn transition_history(mut self, keep: usize) -> Self {
+        self.transition_history = keep;
+        self
+    }
+
+    /// Keep each test's latest execution time in its status entry.
+    pub fn exec_times(mut self, record: bool) -> Self {
+        self.exec_times = record;
+        self
+    }
+
+    /// Record `toolchain` in the entries of tests whose state the run
+    /// changes, and warn when a test regresses under a different toolchain
+    /// than the one it was promoted under.
+    pub fn toolchain(mut self, toolchain: Option<Toolchain>) -> Self {
+        self.toolchain = toolchain;
+        self
+    }
+
+    /// Track the cases of parameterized tests matching `parameterized` as
+    /// one logical test each.
+    pub fn parameterized(mut self, parameterized: ParameterizedGroups) -> Self {
+        self.parameterized = parameterized;
+        self
+    }
+
+    /// Evaluate only the tests of the named test binaries, leaving other
+    /// binaries' entries as they are; see [`binary_selected`].
+    pub fn binaries(mut self, binaries: Vec<String>) -> Self {
+        self.binaries = binaries;
+        self
+    }
+
+    /// Evaluate only the tests `filter` may select, leaving the entries of
+    /// tests it provably excludes as they are; see [`RunFilter::excludes`].
+    pub fn filter(mut self, filter: RunFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn build(self) -> Ratchet<'a> {
+        Ratchet {
+            runner: self.runner,
+            history: self.history,
+            store: self.store,
+            rules: self.rules,
+            grandfather: self.grandfather,
+            transition_history: self.transition_history,
+            exec_times: self.exec_times,
+            toolchain: self.toolchain,
+            parameterized: self.parameterized,
+            binaries: self.binaries,
+            filter: self.filter,
+        }
+    }
+}
@@ -0,0 +1,270 @@
+// Rule plugins: programs named `ratchet-rule-<name>`, listed under
+// `[plugins]` in `ratchet.toml`, that add project-specific checks.
+//
+// The protocol is one JSON object each way. A plugin reads a
+// [`PluginContext`] on stdin and writes [`PluginFindings`] on stdout; its
+// stderr is passed through. A plugin that cannot be started, exits non-zero,
+// runs past its timeout, or answers with something other than findings is
+// reported as a warning and skipped, so a broken plugin never crashes or
+// blocks the run.
+
+use crate::audit::AuditViolation;
+use crate::config::PluginsConfig;
+use crate::history::HistorySnapshot;
+use crate::ratchet::{EvalResult, Violation, Warning};
+use crate::runner::TestResult;
+use crate::status::{StatusFile, TestEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Prefix of every plugin program's name.
+pub const PLUGIN_PREFIX: &str = "ratchet-rule-";
+
+/// Version of the JSON a plugin reads, sent as `protocol`. Bumped only for
+/// changes that would break an existing plugin.
+pub const PLUGIN_PROTOCOL_VERSION: u32 = 1;
+
+/// How often a running plugin is checked against its timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What a plugin reads on stdin.
+#[derive(Debug, Serialize)]
+pub struct PluginContext<'a> {
+    pub protocol: u32,
+    /// Commit checked out during the run, if any.
+    pub head: Option<&'a str>,
+    /// Tests tracked in the status committed at `HEAD`.
+    pub status: &'a BTreeMap<String, TestEntry>,
+    /// The status file this run writes.
+    pub updated: &'a StatusFile,
+    /// This run's test results.
+    pub results: &'a [TestResult],
+    /// Committed status files, oldest first.
+    pub snapshots: Vec<PluginSnapshot<'a>>,
+    /// Violations the built-in rules found.
+    pub violations: Vec<AuditViolation>,
+}
+
+/// The status file at one commit.
+#[derive(Debug, Serialize)]
+pub struct PluginSnapshot<'a> {
+    pub commit: &'a str,
+    pub author: &'a str,
+    /// Commit time in Unix seconds.
+    pub time: i64,
+    pub summary: &'a str,
+    pub status: &'a StatusFile,
+}
+
+impl<'a> PluginContext<'a> {
+    pub fn new(
+        head: Option<&'a str>,
+        status: &'a BTreeMap<String, TestEntry>,
+        results: &'a [TestResult],
+        snapshots: &'a [HistorySnapshot],
+        result: &'a EvalResult,
+    ) -> Self {
+        PluginContext {
+            protocol: PLUGIN_PROTOCOL_VERSION,
+            head,
+            status,
+            updated: &result.updated,
+            results,
+            snapshots: snapshots
+                .iter()
+                .map(|snapshot| PluginSnapshot {
+                    commit: &snapshot.commit,
+                    author: &snapshot.info.author,
+                    time: snapshot.info.time,
+                    summary: &snapshot.info.summary,
+                    status: &snapshot.status,
+                })
+                .collect(),
+            violations: result.violations.iter().map(AuditViolation::of).collect(),
+        }
+    }
+}
+
+/// What a plugin writes on stdout. Both lists may be left out, and empty
+/// output means nothing to report.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PluginFindings {
+    /// Problems that fail the run.
+    #[serde(default)]
+    pub violations: Vec<PluginFinding>,
+    /// Problems reported without failing the run.
+    #[serde(default)]
+    pub warnings: Vec<PluginFinding>,
+}
+
+/// One problem a plugin found.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PluginFinding {
+    /// What the problem is about, such as a test name or a path.
+    #[serde(default)]
+    pub subject: String,
+    pub message: String,
+}
+
+/// Why a plugin's checks were skipped.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PluginError {
+    /// The program could not be started, e.g. because it is not on `PATH`.
+    #[error("could not run {program}: {source}")]
+    Spawn {
+        program: String,
+        #[source]
+        source: io::Error,
+    },
+    /// It was still running at its timeout and was stopped.
+    #[error("still running after {}s, stopped", .timeout.as_secs())]
+    TimedOut { timeout: Duration },
+    /// It exited unsuccessfully.
+    #[error("exited with {status}")]
+    Failed { status: ExitStatus },
+    /// Its output was not valid findings.
+    #[error("output is not valid findings JSON: {source}")]
+    BadOutput {
+        #[source]
+        source: serde_json::Error,
+    },
+    /// Reading its output or waiting for it failed.
+    #[error(transparent)]
+    Io { source: io::Error },
+}
+
+/// The program that implements the plugin called `name`.
+pub fn plugin_program(name: &str) -> String {
+    format!("{PLUGIN_PREFIX}{name}")
+}
+
+/// Run `program` in `dir` with `input` on stdin and read its findings,
+/// stopping it after `timeout`.
+pub fn run_plugin(
+    program: &str,
+    dir: &Path,
+    input: Vec<u8>,
+    timeout: Duration,
+) -> Result<PluginFindings, PluginError> {
+    let deadline = Instant::now() + timeout;
+    let mut child = Command::new(program)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|source| PluginError::Spawn {
+            program: program.to_string(),
+            source,
+        })?;
+
+    // Write and read on other threads, so a plugin that ignores its input or
+    // never closes its output cannot block the run past the deadline.
+    let mut stdin = child.stdin.take().expect("child stdin should be piped");
+    thread::spawn(move || {
+        // A plugin that ignores its input may exit before reading it.
+        let _ = stdin.write_all(&input);
+    });
+    let mut stdout = child.stdout.take().expect("child stdout should be piped");
+    let (output_tx, output) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = output_tx.send(stdout.read_to_end(&mut buf).map(|_| buf));
+    });
+
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|source| PluginError::Io { source })?
+        {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(PluginError::TimedOut { timeout });
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    if !status.success() {
+        return Err(PluginError::Failed { status });
+    }
+    let output = match output.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(output) => output.map_err(|source| PluginError::Io { source })?,
+        Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => {
+            return Err(PluginError::TimedOut { timeout });
+        }
+    };
+    parse_findings(&output)
+}
+
+/// Read a plugin's answer. Pure function — no IO.
+pub fn parse_findings(output: &[u8]) -> Result<PluginFindings, PluginError> {
+    if output.iter().all(u8::is_ascii_whitespace) {
+        return Ok(PluginFindings::default());
+    }
+    serde_json::from_slice(output).map_err(|source| PluginError::BadOutput { source })
+}
+
+/// Run every plugin in `config` with `context`, in order, and return what
+/// each one found or why it was skipped.
+pub fn run_plugins(
+    config: &PluginsConfig,
+    dir: &Path,
+    context: &PluginContext<'_>,
+) -> Vec<(String, Result<PluginFindings, PluginError>)> {
+    let input = serde_json::to_vec(context).expect("plugin context should serialize");
+    config
+        .rules
+        .iter()
+        .map(|name| {
+            let outcome = run_plugin(&plugin_program(name), dir, input.clone(), config.timeout());
+            (name.clone(), outcome)
+        })
+        .collect()
+}
+
+/// Merge what the plugin `name` reported into `result`: its violations and
+/// warnings, or a warning that it failed. Pure function — no IO.
+pub fn apply_findings(
+    result: &mut EvalResult,
+    name: &str,
+    outcome: Result<PluginFindings, PluginError>,
+) {
+    match outcome {
+        Ok(findings) => {
+            result
+                .violations
+                .extend(findings.violations.into_iter().map(|finding| {
+                    Violation::PluginViolation {
+                        plugin: name.to_string(),
+                        subject: finding.subject,
+                        message: finding.message,
+                    }
+                }));
+            result
+                .warnings
+                .extend(
+                    findings
+                        .warnings
+                        .into_iter()
+                        .map(|finding| Warning::PluginWarning {
+                            plugin: name.to_string(),
+                            subject: finding.subject,
+                            message: finding.message,
+                        }),
+                );
+        }
+        Err(e) => result.warnings.push(Warning::PluginFailed {
+            plugin: name.to_string(),
+            reason: e.to_string(),
+        }),
+    }
+}
@@ -0,0 +1,143 @@
+// Auto-quarantine: a passing test whose outcome keeps flipping between runs
+// of the same source is quarantined as flaky.
+//
+// Each run's outcome per test is kept in an untracked sidecar
+// (`.ratchet-flips.json`) with the commit it ran at. Runs with uncommitted
+// source changes are not recorded, since a change explains a new outcome.
+// Once a test has flipped `quarantine-after` times in a row, its entry in the
+// status file is marked, and its failures are tolerated until `cargo ratchet
+// unquarantine` clears the mark.
+
+use crate::error::{ParseError, RatchetError};
+use crate::ratchet::{EvalResult, Violation, Warning};
+use crate::runner::{TestOutcome, TestResult};
+use crate::status::{Quarantine, TestState, is_status_file_name};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const FLIPS_FILE_NAME: &str = ".ratchet-flips.json";
+
+/// The latest recorded outcome of each test.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlipRecord {
+    pub tests: BTreeMap<String, Observation>,
+}
+
+/// A test's outcome in the latest recorded run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Observation {
+    /// Commit the run was at.
+    pub commit: String,
+    pub passed: bool,
+    /// Consecutive runs at this commit whose outcome differed from the run
+    /// before.
+    pub flips: u32,
+}
+
+/// `previous` updated with a run at `commit`. A test whose outcome differs
+/// from its last one at the same commit has flipped once more; any other
+/// run starts the count again. Ignored tests and tests the run did not
+/// include keep their record. Pure function — no IO.
+pub fn observe_flips(previous: &FlipRecord, commit: &str, results: &[TestResult]) -> FlipRecord {
+    let mut record = previous.clone();
+    for result in results {
+        let passed = match result.outcome {
+            TestOutcome::Passed | TestOutcome::Flaky => true,
+            TestOutcome::Failed => false,
+            TestOutcome::Ignored => continue,
+        };
+        let flips = match previous.tests.get(&result.name) {
+            Some(last) if last.commit == commit && last.passed != passed => last.flips + 1,
+            _ => 0,
+        };
+        record.tests.insert(
+            result.name.clone(),
+            Observation {
+                commit: commit.to_string(),
+                passed,
+                flips,
+            },
+        );
+    }
+    record
+}
+
+/// Quarantine each passing test of `result` that has flipped at least
+/// `after` times in `record` and is not quarantined yet, with a
+/// [`Warning::Quarantined`]. Its regression in this run, if any, becomes a
+/// [`Warning::QuarantinedTestFailed`]. Pure function — no IO.
+pub fn quarantine_flaky_tests(result: &mut EvalResult, record: &FlipRecord, after: u32) {
+    let mut quarantined = Vec::new();
+    for (test, observation) in &record.tests {
+        if observation.flips < after.max(1) {
+            continue;
+        }
+        let Some(entry) = result.updated.tests.get_mut(test) else {
+            continue;
+        };
+        if entry.state() != TestState::Passing || entry.quarantine.is_some() {
+            continue;
+        }
+        entry.quarantine = Some(Quarantine {
+            flips: observation.flips,
+            commit: Some(observation.commit.clone()),
+        });
+        result.warnings.push(Warning::Quarantined {
+            test: test.clone(),
+            flips: observation.flips,
+        });
+        quarantined.push(test.clone());
+    }
+    result.violations.retain(|violation| match violation {
+        Violation::Regression { test } if quarantined.contains(test) => {
+            result
+                .warnings
+                .push(Warning::QuarantinedTestFailed { test: test.clone() });
+            false
+        }
+        _ => true,
+    });
+}
+
+/// The commit `HEAD` points at in the repository at `repo_path`, when the
+/// working tree has no uncommitted changes besides the ratchet's own files
+/// (status files and `.ratchet-*` sidecars); `None` otherwise.
+pub fn unchanged_commit(repo_path: &Path) -> Option<String> {
+    let repo = git2::Repository::open(repo_path).ok()?;
+    let head = repo.head().ok()?.peel_to_commit().ok()?.id().to_string();
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let changed = repo
+        .statuses(Some(&mut options))
+        .ok()?
+        .iter()
+        .filter(|entry| entry.status() != git2::Status::CURRENT)
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .any(|path| {
+            let name = Path::new(&path).file_name().and_then(|name| name.to_str());
+            !name.is_some_and(|name| is_status_file_name(name) || name.starts_with(".ratchet-"))
+        });
+    (!changed).then_some(head)
+}
+
+/// Read the flip record. A missing or unreadable file has none: the sidecar
+/// starts over rather than failing a run.
+pub fn load_flips(path: &Path) -> FlipRecord {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Write the flip record.
+pub fn save_flips(path: &Path, record: &FlipRecord) -> Result<(), RatchetError> {
+    let json = serde_json::to_string_pretty(record).map_err(|source| ParseError::Sidecar {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    std::fs::write(path, json + "\n").map_err(|e| RatchetError::io(path, e))
+}
@@ -1,9 +1,20 @@
 // Core ratchet logic: compare status file against test results, produce violations.
 
-use crate::history::check_history_snapshots;
+use crate::accepted_failures::acceptance_expired;
+use crate::config::Rule;
+use crate::grandfather::GrandfatherPatterns;
+use crate::history::check_history_snapshots_with;
 use crate::history::{HistorySnapshot, HistoryViolation};
+use crate::integrity::{IntegrityFinding, TamperProblem, check_integrity, latest_digest, seal};
+use crate::last_run::RunDelta;
+use crate::overrides::apply_overrides;
 use crate::runner::{TestOutcome, TestResult};
-use crate::status::{StatusFile, TestState, TrackedStatus, WorkingTreeInstructions};
+use crate::status::{
+    AcceptedFailure, Promotion, StatusFile, TestEntry, TestState, Toolchain, TrackedStatus,
+    Transition, WorkingTreeInstructions, is_wildcard, tracking_entry, wildcard_matches,
+};
+use crate::test_locations::TestLocation;
+use crate::timeline::state_changes;
 use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug, Clone)]
@@ -31,6 +42,34 @@ pub struct EvalResult {
     pub violations: Vec<Violation>,
     pub warnings: Vec<Warning>,
     pub updated: StatusFile,
+    /// Captured output of each failed test that reported some, by name.
+    pub failure_output: BTreeMap<String, String>,
+    /// Source location of tests named by violations, when known. Filled in
+    /// by [`crate::test_locations::annotate_violations`].
+    pub locations: BTreeMap<String, TestLocation>,
+    /// Change since the previous run, when one was recorded. Filled in from
+    /// the sidecar in [`crate::last_run`].
+    pub since_last_run: Option<RunDelta>,
+    /// Commit a `--since` check was bounded to; history before it was not
+    /// checked.
+    pub history_since: Option<String>,
+    /// How often the test run was retried after an infrastructure failure
+    /// before these results came in.
+    pub infrastructure_retries: u32,
+    /// How a partial run was limited (`--test`, `-p`, `-E`); tests outside
+    /// it were not checked.
+    pub partial: Option<String>,
+    /// The last commit that recorded each regressed test as passing, when
+    /// known. See [`last_green`].
+    pub last_green: BTreeMap<String, LastGreen>,
+}
+
+impl EvalResult {
+    /// Whether the run fails: on any violation, and with `deny_warnings` on
+    /// any warning too. Warnings are advisory otherwise.
+    pub fn is_failure(&self, deny_warnings: bool) -> bool {
+        !self.violations.is_empty() || (deny_warnings && !self.warnings.is_empty())
+    }
 }
 
 /// A unified violation type covering all ratchet checks.
@@ -62,12 +101,198 @@ pub enum Violation {
     RemovalTestStillPresent { test: String },
     /// Removal declared for a test that also participates in a rename
     RemovalConflictsWithRename { test: String },
+    /// A test was promoted in the same commit that changed its body
+    PromotionEditedTest {
+        test: String,
+        commit: String,
+        path: String,
+    },
+    /// A committed status file failed the integrity check
+    StatusTampered {
+        commit: String,
+        problem: TamperProblem,
+    },
+    /// A passing test is now ignored without `allow-ignore`
+    PassingTestIgnored { test: String },
+    /// A commit promoted more tests than `max-promotions-per-commit`
+    TooManyPromotions {
+        commit: String,
+        tests: Vec<String>,
+        limit: usize,
+    },
+    /// A commit added pending tests without one of the
+    /// `pending-test-commit-types`
+    PendingTestCommitType {
+        commit: String,
+        summary: String,
+        tests: Vec<String>,
+        allowed: Vec<String>,
+    },
+    /// New pending tests would take the pending count over `max-pending`
+    PendingLimitExceeded {
+        tests: Vec<String>,
+        pending: usize,
+        limit: usize,
+    },
+    /// A test failed after its accepted failure expired
+    AcceptedFailureExpired {
+        test: String,
+        acceptance: AcceptedFailure,
+    },
+    /// A gatekeeper test passed when run without `TDD_RATCHET`
+    GatekeeperDoesNotBlock { test: String },
+    /// No gatekeeper echoed the run's handshake nonce
+    GatekeeperHandshakeMissing,
+    /// The status file or test files have uncommitted changes
+    DirtyWorktree { paths: Vec<String> },
+    /// A `ratchet-rule-*` plugin reported a violation
+    PluginViolation {
+        plugin: String,
+        subject: String,
+        message: String,
+    },
+    /// Several results in the run share one test name
+    DuplicateTestName { test: String, count: usize },
+}
+
+impl Violation {
+    /// The configurable rule this violation belongs to, if any. Rename,
+    /// removal, integrity, and promotion-edit violations are always errors.
+    pub fn rule(&self) -> Option<Rule> {
+        match self {
+            Violation::NewTestPassed { .. } => Some(Rule::NewTestPassed),
+            Violation::Regression { .. } | Violation::AcceptedFailureExpired { .. } => {
+                Some(Rule::Regression)
+            }
+            Violation::TestDisappeared { .. } => Some(Rule::TestDisappeared),
+            Violation::SkippedPending { .. } => Some(Rule::SkippedPending),
+            Violation::MissingGatekeeper
+            | Violation::GatekeeperDoesNotBlock { .. }
+            | Violation::GatekeeperHandshakeMissing => Some(Rule::MissingGatekeeper),
+            Violation::PassingTestIgnored { .. } => Some(Rule::PassingToIgnored),
+            Violation::DirtyWorktree { .. } => Some(Rule::DirtyWorktree),
+            _ => None,
+        }
+    }
+
+    /// Stable code identifying this kind of violation, e.g. `RATCHET001`.
+    /// `cargo ratchet explain <code>` describes it in full.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Violation::NewTestPassed { .. } => "RATCHET001",
+            Violation::Regression { .. } => "RATCHET002",
+            Violation::TestDisappeared { .. } => "RATCHET003",
+            Violation::SkippedPending { .. } => "RATCHET004",
+            Violation::MissingGatekeeper => "RATCHET005",
+            Violation::RenameOldNameMissing { .. } => "RATCHET006",
+            Violation::RenameNewNameMissing { .. } => "RATCHET007",
+            Violation::RenameOldNameStillPresent { .. } => "RATCHET008",
+            Violation::RenameNewNameAlreadyTracked { .. } => "RATCHET009",
+            Violation::RenameOldNameMappedMultipleTimes { .. } => "RATCHET010",
+            Violation::RemovalMissingTrackedTest { .. } => "RATCHET011",
+            Violation::RemovalTestStillPresent { .. } => "RATCHET012",
+            Violation::RemovalConflictsWithRename { .. } => "RATCHET013",
+            Violation::PromotionEditedTest { .. } => "RATCHET014",
+            Violation::StatusTampered { .. } => "RATCHET015",
+            Violation::PassingTestIgnored { .. } => "RATCHET016",
+            Violation::TooManyPromotions { .. } => "RATCHET017",
+            Violation::PendingLimitExceeded { .. } => "RATCHET018",
+            Violation::AcceptedFailureExpired { .. } => "RATCHET019",
+            Violation::GatekeeperDoesNotBlock { .. } => "RATCHET020",
+            Violation::GatekeeperHandshakeMissing => "RATCHET021",
+            Violation::DirtyWorktree { .. } => "RATCHET022",
+            Violation::PluginViolation { .. } => "RATCHET023",
+            Violation::PendingTestCommitType { .. } => "RATCHET024",
+            Violation::DuplicateTestName { .. } => "RATCHET025",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Warning {
-    RenameApplied { new_name: String, old_name: String },
-    StaleRename { new_name: String, old_name: String },
+    RenameApplied {
+        new_name: String,
+        old_name: String,
+    },
+    StaleRename {
+        new_name: String,
+        old_name: String,
+    },
+    /// The newest committed status was resealed after a manual edit.
+    IntegrityOverride {
+        commit: String,
+        reason: String,
+    },
+    /// A `Ratchet-Override` commit trailer suppressed a violation.
+    OverrideApplied {
+        commit: String,
+        test: String,
+        rule: String,
+        reason: String,
+    },
+    /// A passing test is now ignored without `allow-ignore`.
+    PassingTestIgnored {
+        test: String,
+    },
+    /// A test passed only on retry and has flaked more often than allowed.
+    FlakyTest {
+        test: String,
+        flakes: u32,
+    },
+    /// A test kept flipping between passing and failing on the same source
+    /// and was quarantined.
+    Quarantined {
+        test: String,
+        flips: u32,
+    },
+    /// A quarantined test failed; the failure is tolerated.
+    QuarantinedTestFailed {
+        test: String,
+    },
+    /// A draft test passed; it stays a draft until promoted.
+    DraftPassing {
+        test: String,
+    },
+    /// A test promoted to passing whose body asserts nothing.
+    TrivialTest {
+        test: String,
+    },
+    /// A test promoted to passing that covers no line other recorded tests
+    /// do not already cover. `covered` counts the lines it covers at all.
+    NoNewCoverage {
+        test: String,
+        covered: usize,
+    },
+    /// A test regressed under a different toolchain than the one it was
+    /// promoted under.
+    ToolchainChanged {
+        test: String,
+        promoted: Toolchain,
+        current: Toolchain,
+    },
+    /// The recorded execution times add up to more than `max_growth`
+    /// percent above the last run's.
+    SuiteSlower {
+        previous_ms: u64,
+        current_ms: u64,
+        max_growth: u32,
+    },
+    /// A violation of a rule whose severity is set to `warn`.
+    RuleWarning {
+        violation: Violation,
+    },
+    /// A `ratchet-rule-*` plugin reported a warning.
+    PluginWarning {
+        plugin: String,
+        subject: String,
+        message: String,
+    },
+    /// A plugin could not be run or gave an unreadable answer; its checks
+    /// were skipped.
+    PluginFailed {
+        plugin: String,
+        reason: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -94,6 +319,43 @@ pub fn evaluate(
     instructions: &WorkingTreeInstructions,
     results: &[TestResult],
     history_snapshots: &[HistorySnapshot],
+) -> EvalResult {
+    evaluate_with(
+        status,
+        instructions,
+        results,
+        history_snapshots,
+        &GrandfatherPatterns::default(),
+    )
+}
+
+/// The integrity check over `history_snapshots`: a violation for each
+/// tampered status file and a warning for a deliberate edit. Pure function —
+/// no IO.
+pub fn integrity_findings(history_snapshots: &[HistorySnapshot]) -> (Vec<Violation>, Vec<Warning>) {
+    let mut violations = Vec::new();
+    let mut warnings = Vec::new();
+    for finding in check_integrity(history_snapshots) {
+        match finding {
+            IntegrityFinding::Tampered { commit, problem } => {
+                violations.push(Violation::StatusTampered { commit, problem });
+            }
+            IntegrityFinding::Overridden { commit, reason } => {
+                warnings.push(Warning::IntegrityOverride { commit, reason });
+            }
+        }
+    }
+    (violations, warnings)
+}
+
+/// [`evaluate`] with `grandfather` patterns from `ratchet.toml`: matching
+/// tests may pass without ever being pending.
+pub fn evaluate_with(
+    status: &TrackedStatus,
+    instructions: &WorkingTreeInstructions,
+    results: &[TestResult],
+    history_snapshots: &[HistorySnapshot],
+    grandfather: &GrandfatherPatterns,
 ) -> EvalResult {
     let mut violations = Vec::new();
     let mut warnings = Vec::new();
@@ -114,16 +376,58 @@ pub fn evaluate(
     violations.extend(removals.violations);
 
     // 2. Apply ratchet rules (state transitions)
-    let transition_outcome = apply_transitions(&removals.status, &identity.results);
-    violations.extend(
-        transition_outcome
-            .violations
-            .into_iter()
-            .map(map_transition_violation),
-    );
+    let mut before_run = removals.status;
+    for test in &instructions.ignore_disallowed {
+        if let Some(entry) = before_run.tests.get_mut(test) {
+            entry.allow_ignore = false;
+        }
+    }
+    for test in &instructions.unaccepted {
+        if let Some(entry) = before_run.tests.get_mut(test) {
+            entry.accepted_failure = None;
+        }
+    }
+    for test in &instructions.unquarantined {
+        if let Some(entry) = before_run.tests.get_mut(test) {
+            entry.quarantine = None;
+        }
+    }
+    apply_draft_instructions(&mut before_run, instructions);
+    let transition_outcome = apply_transitions(&before_run, &identity.results, grandfather);
+    for result in &identity.results {
+        if matches!(result.outcome, TestOutcome::Passed | TestOutcome::Flaky)
+            && tracked_test_state_in(&before_run, &result.name) == Some(TestState::Draft)
+        {
+            warnings.push(Warning::DraftPassing {
+                test: result.name.clone(),
+            });
+        }
+    }
+    for violation in transition_outcome.violations {
+        if let TransitionViolation::Regression { test } = &violation
+            && before_run
+                .tests
+                .get(test)
+                .is_some_and(|entry| entry.quarantine.is_some())
+        {
+            warnings.push(Warning::QuarantinedTestFailed { test: test.clone() });
+            continue;
+        }
+        violations.extend(accept_transition_violation(
+            violation,
+            &before_run,
+            history_snapshots,
+        ));
+    }
 
-    // 3. Check git history
-    let history_violations = check_history_snapshots(history_snapshots);
+    // 3. Check git history. The baseline is only ever taken from the
+    // committed status: `rebaseline` declares its change in the seal, and
+    // the move takes effect once that is committed.
+    let baseline = history_snapshots
+        .last()
+        .and_then(|snapshot| snapshot.status.baseline.clone());
+    let history_violations =
+        check_history_snapshots_with(history_snapshots, baseline.as_deref(), grandfather);
     for hv in history_violations {
         match hv {
             HistoryViolation::SkippedPending { test, commit } => {
@@ -132,13 +436,404 @@ pub fn evaluate(
         }
     }
 
+    // 4. Check status file integrity
+    let (tampered, overridden) = integrity_findings(history_snapshots);
+    violations.extend(tampered);
+    warnings.extend(overridden);
+
+    // 5. Drop violations covered by `Ratchet-Override` trailers
+    let (violations, used_overrides) = apply_overrides(violations, history_snapshots);
+    warnings.extend(used_overrides);
+
+    let mut tests = transition_outcome.updated;
+    record_promotions(&mut tests, history_snapshots);
+    let last_green = last_green(&violations, &tests, history_snapshots);
+    let mut updated = StatusFile::from_parts(tests, instructions.clone());
+    updated.baseline = baseline;
+    updated.integrity = Some(seal(latest_digest(history_snapshots), &updated));
+
+    let failure_output = identity
+        .results
+        .iter()
+        .filter(|r| r.outcome == TestOutcome::Failed)
+        .filter_map(|r| Some((r.name.clone(), r.output.clone()?)))
+        .collect();
+
     EvalResult {
         violations,
         warnings,
-        updated: StatusFile::from_parts(transition_outcome.updated, instructions.clone()),
+        updated,
+        failure_output,
+        locations: BTreeMap::new(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green,
+    }
+}
+
+/// Put tests in the draft state the working tree gives them. A test drafted
+/// there before any run tracks it starts as a draft; a draft the working
+/// tree no longer has was promoted and becomes pending. Passing tests are
+/// never drafted.
+fn apply_draft_instructions(status: &mut TrackedStatus, instructions: &WorkingTreeInstructions) {
+    for (test, entry) in &mut status.tests {
+        let drafted = instructions.drafts.contains(test);
+        match entry.state() {
+            TestState::Pending if drafted => *entry = entry.with_state(TestState::Draft),
+            TestState::Draft if !drafted => *entry = entry.with_state(TestState::Pending),
+            _ => {}
+        }
+    }
+    for test in &instructions.drafts {
+        if !status.tests.contains_key(test) {
+            status.set_test_state(test.clone(), TestState::Draft);
+        }
     }
 }
 
+/// Record the promoting commit of each passing test that has none yet. Only
+/// tests passing at the newest snapshot qualify, so a promotion still in the
+/// working tree is recorded by the first run after it is committed.
+fn record_promotions(status: &mut TrackedStatus, history_snapshots: &[HistorySnapshot]) {
+    let Some(head) = history_snapshots.last() else {
+        return;
+    };
+    let committed_passing = |name: &str| {
+        head.status
+            .tests
+            .get(name)
+            .is_some_and(|entry| entry.state() == TestState::Passing)
+    };
+    let mut unrecorded: Vec<(&String, &mut TestEntry)> = status
+        .tests
+        .iter_mut()
+        .filter(|(name, entry)| {
+            entry.state() == TestState::Passing
+                && entry.promoted.is_none()
+                && committed_passing(name)
+        })
+        .collect();
+    if unrecorded.is_empty() {
+        return;
+    }
+
+    let mut latest = BTreeMap::new();
+    for change in state_changes(history_snapshots) {
+        if change.is_promotion() {
+            latest.insert(change.test.clone(), change);
+        }
+    }
+    for (name, entry) in &mut unrecorded {
+        if let Some(change) = latest.get(*name) {
+            entry.promoted = Some(Promotion {
+                commit: change.commit.clone(),
+                author: change.info.author.clone(),
+                time: change.info.time,
+            });
+        }
+    }
+}
+
+/// Keep the newest `keep` committed state changes of each test in its
+/// entry, oldest first. Changes already recorded are kept, so a history that
+/// no longer reaches them, such as a shallow clone's, does not lose them;
+/// with `keep` at 0 nothing is recorded. Pure function — no IO.
+///
+/// A test with recorded changes that seems added at the oldest snapshot was
+/// only cut off there, so that change is not recorded.
+pub fn record_transitions(
+    tests: &mut BTreeMap<String, TestEntry>,
+    history_snapshots: &[HistorySnapshot],
+    keep: usize,
+) {
+    if keep == 0 {
+        for entry in tests.values_mut() {
+            entry.transitions.clear();
+        }
+        return;
+    }
+    let mut committed: BTreeMap<String, Vec<Transition>> = BTreeMap::new();
+    for change in state_changes(history_snapshots) {
+        let Some(state) = change.to.filter(|to| change.from != Some(*to)) else {
+            continue;
+        };
+        let cut_off = change.snapshot_index == 0
+            && change.from.is_none()
+            && tests
+                .get(&change.test)
+                .is_some_and(|entry| !entry.transitions.is_empty());
+        if cut_off {
+            continue;
+        }
+        let toolchain = history_snapshots[change.snapshot_index]
+            .status
+            .tests
+            .get(&change.test)
+            .and_then(|entry| entry.toolchain.clone());
+        committed.entry(change.test).or_default().push(Transition {
+            from: change.from,
+            state,
+            commit: change.commit,
+            author: change.info.author,
+            time: change.info.time,
+            toolchain,
+        });
+    }
+    for (name, entry) in tests.iter_mut() {
+        for transition in committed.remove(name).into_iter().flatten() {
+            if !entry
+                .transitions
+                .iter()
+                .any(|recorded| recorded.commit == transition.commit)
+            {
+                entry.transitions.push(transition);
+            }
+        }
+        let excess = entry.transitions.len().saturating_sub(keep);
+        entry.transitions.drain(..excess);
+    }
+}
+
+/// Store each test's execution time from `results` in its entry; a test
+/// the harness did not time keeps its previous one. With `record` off, all
+/// recorded times are dropped. Pure function — no IO.
+pub fn record_exec_times(
+    tests: &mut BTreeMap<String, TestEntry>,
+    results: &[TestResult],
+    record: bool,
+) {
+    if !record {
+        for entry in tests.values_mut() {
+            entry.exec_time_ms = None;
+        }
+        return;
+    }
+    for result in results {
+        if let Some(ms) = result.exec_time_ms
+            && let Some(entry) = tests.get_mut(&result.name)
+        {
+            entry.exec_time_ms = Some(ms);
+        }
+    }
+}
+
+/// Store `toolchain` in the entries of tests this run added or changed the
+/// state of, compared with `before`; a renamed test keeps its own. When the
+/// toolchain is unknown, changed entries record none. Pure function — no IO.
+pub fn record_toolchain(
+    tests: &mut BTreeMap<String, TestEntry>,
+    before: &TrackedStatus,
+    toolchain: Option<&Toolchain>,
+) {
+    for (name, entry) in tests.iter_mut() {
+        let changed = match before.tests.get(name) {
+            Some(previous) => previous.state() != entry.state(),
+            None => entry.toolchain.is_none(),
+        };
+        if changed {
+            entry.toolchain = toolchain.cloned();
+        }
+    }
+}
+
+/// Regressed tests in `violations` that `before` records as promoted under
+/// a toolchain other than `current`, with that toolchain. Such a failure
+/// may be toolchain drift rather than a change to the code. Pure function —
+/// no IO.
+pub fn toolchain_drift(
+    violations: &[Violation],
+    before: &TrackedStatus,
+    current: &Toolchain,
+) -> Vec<(String, Toolchain)> {
+    violations
+        .iter()
+        .filter_map(|violation| match violation {
+            Violation::Regression { test } => {
+                let promoted = before.tests.get(test)?.toolchain.as_ref()?;
+                (promoted != current).then(|| (test.clone(), promoted.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// A commit that recorded a test as passing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastGreen {
+    pub commit: String,
+    /// Commit time, Unix seconds.
+    pub time: i64,
+}
+
+/// The last commit that recorded each regressed test in `violations` as
+/// passing: the newest snapshot that changed the status file while the test
+/// was passing in it, or else the test's promotion in `status`. A failure
+/// was introduced after that commit, so it is where to start looking. Pure
+/// function — no IO.
+pub fn last_green(
+    violations: &[Violation],
+    status: &TrackedStatus,
+    history_snapshots: &[HistorySnapshot],
+) -> BTreeMap<String, LastGreen> {
+    violations
+        .iter()
+        .filter_map(|violation| {
+            let Violation::Regression { test } = violation else {
+                return None;
+            };
+            let recorded = history_snapshots
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(index, snapshot)| {
+                    let passing = snapshot
+                        .status
+                        .tests
+                        .get(test)
+                        .is_some_and(|entry| entry.state() == TestState::Passing);
+                    let changed =
+                        *index == 0 || history_snapshots[index - 1].status != snapshot.status;
+                    passing && changed
+                })
+                .map(|(_, snapshot)| LastGreen {
+                    commit: snapshot.commit.clone(),
+                    time: snapshot.info.time,
+                });
+            let promoted = || {
+                let promotion = status.tests.get(test)?.promoted.as_ref()?;
+                Some(LastGreen {
+                    commit: promotion.commit.clone(),
+                    time: promotion.time,
+                })
+            };
+            Some((test.clone(), recorded.or_else(promoted)?))
+        })
+        .collect()
+}
+
+/// Test names `results` reports more than once, with how often. Names
+/// without a binary prefix, such as plain libtest output, collide when two
+/// test binaries have a test at the same path, and the tests would then
+/// share one status entry. Pure function — no IO.
+pub fn duplicate_test_names(results: &[TestResult]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for result in results {
+        *counts.entry(&result.name).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(test, count)| (test.to_string(), count))
+        .collect()
+}
+
+/// Tracked passing tests that `results` reports as ignored, excluding those
+/// marked with `allow-ignore`. `status` is the status after renames, such as
+/// [`EvalResult::updated`].
+pub fn ignored_passing_tests(status: &StatusFile, results: &[TestResult]) -> Vec<String> {
+    results
+        .iter()
+        .filter(|r| r.outcome == TestOutcome::Ignored)
+        .filter(|r| {
+            status
+                .tests
+                .get(&r.name)
+                .is_some_and(|e| e.state() == TestState::Passing && !e.allow_ignore)
+        })
+        .map(|r| r.name.clone())
+        .collect()
+}
+
+/// Tests that flaked in `results` and whose flake count in `status` (after
+/// this run, such as [`EvalResult::updated`]) exceeds `threshold`.
+pub fn flaky_tests(
+    status: &StatusFile,
+    results: &[TestResult],
+    threshold: u32,
+) -> Vec<(String, u32)> {
+    results
+        .iter()
+        .filter(|r| r.outcome == TestOutcome::Flaky)
+        .filter_map(|r| {
+            let flakes = status.tests.get(&r.name)?.flakes;
+            (flakes > threshold).then(|| (r.name.clone(), flakes))
+        })
+        .collect()
+}
+
+/// Commits after `baseline` that promote more than `limit` tests, with the
+/// tests each one promoted, oldest first.
+pub fn oversized_promotions(
+    history_snapshots: &[HistorySnapshot],
+    baseline: Option<&str>,
+    limit: usize,
+) -> Vec<(String, Vec<String>)> {
+    let start = baseline
+        .and_then(|b| history_snapshots.iter().position(|s| s.commit == b))
+        .unwrap_or(0);
+    let mut by_commit: Vec<(String, Vec<String>)> = Vec::new();
+    for change in state_changes(&history_snapshots[start..]) {
+        if !change.is_promotion() {
+            continue;
+        }
+        match by_commit.last_mut() {
+            Some((commit, tests)) if *commit == change.commit => tests.push(change.test),
+            _ => by_commit.push((change.commit, vec![change.test])),
+        }
+    }
+    by_commit.retain(|(_, tests)| tests.len() > limit);
+    by_commit
+}
+
+/// Commits after `baseline` that add pending tests without a
+/// conventional-commit type in `allowed`, with their summary and the tests
+/// each one added, oldest first. Pure function — no IO.
+///
+/// The oldest snapshot is not checked: every test seems added there, as
+/// the history before it is not read.
+pub fn mistyped_pending_additions(
+    history_snapshots: &[HistorySnapshot],
+    baseline: Option<&str>,
+    allowed: &[String],
+) -> Vec<(String, String, Vec<String>)> {
+    let start = baseline
+        .and_then(|b| history_snapshots.iter().position(|s| s.commit == b))
+        .unwrap_or(0);
+    let mut by_commit: Vec<(String, String, Vec<String>)> = Vec::new();
+    for change in state_changes(&history_snapshots[start..]) {
+        let added_pending = change.snapshot_index > 0
+            && change.from.is_none()
+            && change.to == Some(TestState::Pending);
+        let typed = commit_type(&change.info.summary)
+            .is_some_and(|kind| allowed.iter().any(|a| a.eq_ignore_ascii_case(kind)));
+        if !added_pending || typed {
+            continue;
+        }
+        match by_commit.last_mut() {
+            Some((commit, _, tests)) if *commit == change.commit => tests.push(change.test),
+            _ => by_commit.push((change.commit, change.info.summary, vec![change.test])),
+        }
+    }
+    by_commit
+}
+
+/// The type of a conventional-commit summary: `test` for
+/// `test(parser)!: cover empty input`. `None` when the summary has no
+/// `type:` prefix.
+pub fn commit_type(summary: &str) -> Option<&str> {
+    let (prefix, _) = summary.split_once(':')?;
+    let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+    let kind = match prefix.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return None,
+        None => prefix,
+    };
+    (!kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .then_some(kind)
+}
+
 // --- Legacy API kept for existing unit tests ---
 
 #[derive(Debug, Clone)]
@@ -166,7 +861,11 @@ pub fn check_ratchet(status: &StatusFile, results: &[TestResult]) -> RatchetOutc
     let instructions = status.working_tree_instructions();
     let identity = apply_rename_instructions(&tracked_status, &instructions, results);
     let removals = apply_removal_instructions(&identity.status, &instructions, &identity.results);
-    let transition_outcome = apply_transitions(&removals.status, &identity.results);
+    let transition_outcome = apply_transitions(
+        &removals.status,
+        &identity.results,
+        &GrandfatherPatterns::default(),
+    );
 
     let violations = transition_outcome
         .violations
@@ -180,9 +879,11 @@ pub fn check_ratchet(status: &StatusFile, results: &[TestResult]) -> RatchetOutc
         })
         .collect();
 
+    let mut updated = StatusFile::from_parts(transition_outcome.updated, instructions);
+    updated.baseline = status.baseline.clone();
     RatchetOutcome {
         violations,
-        updated: StatusFile::from_parts(transition_outcome.updated, instructions),
+        updated,
     }
 }
 
@@ -276,6 +977,8 @@ fn apply_rename_instructions(
                 .cloned()
                 .unwrap_or_else(|| result.name.clone()),
             outcome: result.outcome,
+            output: result.output.clone(),
+            exec_time_ms: None,
         })
         .collect();
 
@@ -312,7 +1015,12 @@ fn apply_removal_instructions(
             continue;
         }
 
-        if result_names.contains(test.as_str()) {
+        let present = if is_wildcard(test) {
+            result_names.iter().any(|name| wildcard_matches(test, name))
+        } else {
+            result_names.contains(test.as_str())
+        };
+        if present {
             violations.push(Violation::RemovalTestStillPresent { test: test.clone() });
             continue;
         }
@@ -335,21 +1043,24 @@ fn rename_participants(instructions: &WorkingTreeInstructions) -> BTreeSet<&str>
     names
 }
 
+/// The state of the entry tracking `test_name`: its own, or a matching
+/// wildcard's. A pending wildcard's test that passes is promoted to an exact
+/// entry of its own.
 fn tracked_test_state_in(tracked_status: &TrackedStatus, test_name: &str) -> Option<TestState> {
-    tracked_status
-        .tests
-        .get(test_name)
-        .map(|entry| entry.state())
+    tracking_entry(&tracked_status.tests, test_name).map(|(_, entry)| entry.state())
 }
 
 fn missing_tracked_tests<'a>(
     status: &'a TrackedStatus,
     seen_names: &BTreeSet<&str>,
 ) -> impl Iterator<Item = &'a String> {
-    status
-        .tests
-        .keys()
-        .filter(move |name| !seen_names.contains(name.as_str()))
+    status.tests.keys().filter(move |name| {
+        if is_wildcard(name) {
+            !seen_names.iter().any(|seen| wildcard_matches(name, seen))
+        } else {
+            !seen_names.contains(name.as_str())
+        }
+    })
 }
 
 fn map_transition_violation(violation: TransitionViolation) -> Violation {
@@ -360,19 +1071,53 @@ fn map_transition_violation(violation: TransitionViolation) -> Violation {
     }
 }
 
-fn apply_transitions(status: &TrackedStatus, results: &[TestResult]) -> TransitionOutcome {
+/// Map a transition violation, dropping a regression covered by an
+/// unexpired accepted failure and escalating one whose acceptance expired.
+fn accept_transition_violation(
+    violation: TransitionViolation,
+    status: &TrackedStatus,
+    history_snapshots: &[HistorySnapshot],
+) -> Option<Violation> {
+    if let TransitionViolation::Regression { test } = &violation
+        && let Some(acceptance) = status
+            .tests
+            .get(test)
+            .and_then(|entry| entry.accepted_failure.as_ref())
+    {
+        if !acceptance_expired(test, acceptance, history_snapshots) {
+            return None;
+        }
+        return Some(Violation::AcceptedFailureExpired {
+            test: test.clone(),
+            acceptance: acceptance.clone(),
+        });
+    }
+    Some(map_transition_violation(violation))
+}
+
+fn apply_transitions(
+    status: &TrackedStatus,
+    results: &[TestResult],
+    grandfather: &GrandfatherPatterns,
+) -> TransitionOutcome {
     let mut violations = Vec::new();
     let mut updated = status.clone();
 
     let seen_names = observed_test_names(results);
 
     for result in results {
-        match (tracked_test_state_in(status, &result.name), result.outcome) {
+        // A flaky test did pass; its flake is counted below.
+        let outcome = match result.outcome {
+            TestOutcome::Flaky => TestOutcome::Passed,
+            outcome => outcome,
+        };
+        match (tracked_test_state_in(status, &result.name), outcome) {
             (None, TestOutcome::Failed) => {
                 updated.set_test_state(result.name.clone(), TestState::Pending);
             }
             (None, TestOutcome::Passed) => {
-                if result.name.ends_with(GATEKEEPER_TEST_NAME) {
+                if result.name.ends_with(GATEKEEPER_TEST_NAME) || grandfather.matches(&result.name)
+                {
                     updated.set_test_state(result.name.clone(), TestState::Passing);
                 } else {
                     violations.push(TransitionViolation::NewTestPassed {
@@ -393,6 +1138,20 @@ fn apply_transitions(status: &TrackedStatus, results: &[TestResult]) -> Transiti
                 });
             }
             (Some(TestState::Passing), TestOutcome::Ignored) => {}
+            // A draft waits for `promote-drafts` whatever its outcome.
+            (Some(TestState::Draft), _) => {}
+            (_, TestOutcome::Flaky) => unreachable!("flaky results count as passed"),
+        }
+        if let Some(entry) = updated.tests.get_mut(&result.name) {
+            if result.outcome != TestOutcome::Ignored {
+                entry.allow_ignore = false;
+            }
+            if outcome == TestOutcome::Passed {
+                entry.accepted_failure = None;
+            }
+            if result.outcome == TestOutcome::Flaky {
+                entry.flakes += 1;
+            }
         }
     }
 
@@ -0,0 +1,235 @@
+// `cargo ratchet repair`: fix up baselines after an interactive rebase
+// rewrites history.
+//
+// A rebase leaves baseline commits unreachable from `HEAD`, and a squash can
+// fold a test's pending commit into the one that makes it pass, so the test
+// shows up as SkippedPending. The pre-rebase commits are still in the `HEAD`
+// reflog; they are the evidence used to remap baselines and to recognise
+// tests that really did go through pending.
+
+use crate::error::RatchetError;
+use crate::history::{
+    HistorySnapshot, HistoryViolation, check_history_snapshots_from, status_file_at_commit,
+};
+use crate::status::{STATUS_FILE_NAME, StatusFile, TestState};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+/// What the repository says about history that is no longer reachable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairEvidence {
+    /// Every commit reachable from `HEAD`.
+    pub reachable: BTreeSet<String>,
+    /// Rewritten commits from the reflog, mapped to the reachable commit with
+    /// the same author, author time, and summary.
+    pub rewritten: BTreeMap<String, String>,
+    /// Tests recorded as pending in some reflog commit that is no longer
+    /// reachable.
+    pub went_pending: BTreeSet<String>,
+}
+
+/// One change `repair` makes to the status file. `test` is `None` for the
+/// global baseline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairChange {
+    /// An unreachable baseline now points at its rewritten commit.
+    BaselineRemapped {
+        test: Option<String>,
+        from: String,
+        to: String,
+    },
+    /// An unreachable baseline with no rewritten counterpart was removed.
+    BaselineDropped { test: Option<String>, from: String },
+    /// A SkippedPending test went through pending before the rewrite; it is
+    /// grandfathered at the commit it now first appears passing.
+    PendingRecovered { test: String, commit: String },
+}
+
+impl fmt::Display for RepairChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let subject = |test: &Option<String>| {
+            test.as_ref().map_or("global baseline".to_string(), |t| {
+                format!("baseline of {t}")
+            })
+        };
+        match self {
+            RepairChange::BaselineRemapped { test, from, to } => write!(
+                f,
+                "{}: {} -> {}",
+                subject(test),
+                &from[..8.min(from.len())],
+                &to[..8.min(to.len())]
+            ),
+            RepairChange::BaselineDropped { test, from } => write!(
+                f,
+                "{}: {} is unreachable, removed",
+                subject(test),
+                &from[..8.min(from.len())]
+            ),
+            RepairChange::PendingRecovered { test, commit } => write!(
+                f,
+                "{test}: was pending before the rewrite, grandfathered at {}",
+                &commit[..8.min(commit.len())]
+            ),
+        }
+    }
+}
+
+/// Work out the changes that make `status` consistent with rewritten
+/// history. Pure function — no IO.
+///
+/// Unreachable baselines are remapped through `evidence.rewritten`. A
+/// per-test baseline without a counterpart moves to the test's first
+/// appearance in `snapshots`; a global one is dropped. Remaining
+/// SkippedPending violations are recovered only for tests the reflog shows as
+/// pending.
+pub fn plan_repair(
+    status: &StatusFile,
+    snapshots: &[HistorySnapshot],
+    evidence: &RepairEvidence,
+) -> Vec<RepairChange> {
+    let mut changes = Vec::new();
+    let unreachable = |commit: &str| !evidence.reachable.contains(commit);
+
+    if let Some(baseline) = status.baseline.as_deref().filter(|b| unreachable(b)) {
+        changes.push(match evidence.rewritten.get(baseline) {
+            Some(to) => RepairChange::BaselineRemapped {
+                test: None,
+                from: baseline.to_string(),
+                to: to.clone(),
+            },
+            None => RepairChange::BaselineDropped {
+                test: None,
+                from: baseline.to_string(),
+            },
+        });
+    }
+
+    for (test, entry) in &status.tests {
+        let Some(baseline) = entry.baseline().filter(|b| unreachable(b)) else {
+            continue;
+        };
+        let replacement = evidence.rewritten.get(baseline).cloned().or_else(|| {
+            snapshots
+                .iter()
+                .find(|s| s.status.tests.contains_key(test))
+                .map(|s| s.commit.clone())
+        });
+        changes.push(match replacement {
+            Some(to) => RepairChange::BaselineRemapped {
+                test: Some(test.clone()),
+                from: baseline.to_string(),
+                to,
+            },
+            None => RepairChange::BaselineDropped {
+                test: Some(test.clone()),
+                from: baseline.to_string(),
+            },
+        });
+    }
+
+    // Check history as it will look once the baselines above are committed.
+    let mut repaired = status.clone();
+    apply_repair(&mut repaired, &changes);
+    let mut projected = snapshots.to_vec();
+    if let Some(latest) = projected.last_mut() {
+        latest.status.tests = repaired.tests.clone();
+    }
+    for violation in check_history_snapshots_from(&projected, repaired.baseline.as_deref()) {
+        let HistoryViolation::SkippedPending { test, commit } = violation;
+        let tracked_passing = repaired
+            .tests
+            .get(&test)
+            .is_some_and(|e| e.state() == TestState::Passing);
+        if tracked_passing && evidence.went_pending.contains(&test) {
+            changes.push(RepairChange::PendingRecovered { test, commit });
+        }
+    }
+
+    changes
+}
+
+/// Apply planned changes to a status file.
+pub fn apply_repair(status: &mut StatusFile, changes: &[RepairChange]) {
+    for change in changes {
+        let (test, baseline) = match change {
+            RepairChange::BaselineRemapped { test, to, .. } => (test.as_ref(), Some(to.clone())),
+            RepairChange::BaselineDropped { test, .. } => (test.as_ref(), None),
+            RepairChange::PendingRecovered { test, commit } => (Some(test), Some(commit.clone())),
+        };
+        match test {
+            None => status.baseline = baseline,
+            Some(test) => {
+                if let Some(entry) = status.tests.get_mut(test) {
+                    entry.baseline = baseline;
+                }
+            }
+        }
+    }
+}
+
+/// Collect [`RepairEvidence`] from the repository and its `HEAD` reflog.
+pub fn gather_repair_evidence(repo_path: &Path) -> Result<RepairEvidence, RatchetError> {
+    gather_repair_evidence_at(repo_path, Path::new(STATUS_FILE_NAME))
+}
+
+/// [`gather_repair_evidence`] for the status file at `status_path`,
+/// relative to the repository root.
+pub fn gather_repair_evidence_at(
+    repo_path: &Path,
+    status_path: &Path,
+) -> Result<RepairEvidence, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let head = repo.head()?.peel_to_commit()?.id();
+
+    let mut evidence = RepairEvidence::default();
+    let mut by_key: BTreeMap<(String, i64, String), String> = BTreeMap::new();
+    let mut walk = repo.revwalk()?;
+    walk.push(head)?;
+    for oid in walk {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        by_key
+            .entry(commit_key(&commit))
+            .or_insert_with(|| oid.to_string());
+        evidence.reachable.insert(oid.to_string());
+    }
+
+    let mut old = repo.revwalk()?;
+    for entry in repo.reflog("HEAD")?.iter() {
+        if repo.find_commit(entry.id_new()).is_ok() {
+            old.push(entry.id_new())?;
+        }
+    }
+    old.hide(head)?;
+    for oid in old {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        if let Some(new) = by_key.get(&commit_key(&commit)) {
+            evidence.rewritten.insert(oid.to_string(), new.clone());
+        }
+        if let Some(status) = status_file_at_commit(&repo, oid, status_path)? {
+            evidence.went_pending.extend(
+                status
+                    .tests
+                    .iter()
+                    .filter(|(_, e)| e.state() == TestState::Pending)
+                    .map(|(name, _)| name.clone()),
+            );
+        }
+    }
+
+    Ok(evidence)
+}
+
+/// Identity of a commit across a rebase: author email, author time, and
+/// summary are preserved when commits are reordered or picked.
+fn commit_key(commit: &git2::Commit<'_>) -> (String, i64, String) {
+    let author = commit.author();
+    (
+        author.email().unwrap_or_default().to_string(),
+        author.when().seconds(),
+        commit.summary().unwrap_or_default().to_string(),
+    )
+}
@@ -0,0 +1,208 @@
+// `cargo ratchet replay <A>..<B>`: when did each history violation first
+// appear?
+//
+// The history rules are re-run over the committed status snapshots as they
+// stood at each commit in the range, with that commit's baseline, so a
+// finding is reported at the commit where a run would first have failed on
+// it. No test is re-run: only rules that read history are replayed, not the
+// ones that need a test run.
+
+use crate::config::{RuleLevel, RulesConfig};
+use crate::errors::violation_summary;
+use crate::grandfather::GrandfatherPatterns;
+use crate::history::{CommitInfo, HistorySnapshot, HistoryViolation, check_history_snapshots_with};
+use crate::overrides::apply_overrides;
+use crate::ratchet::{
+    Violation, integrity_findings, mistyped_pending_additions, oversized_promotions,
+};
+use crate::timeline::{format_date, short_commit};
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+
+/// The history violations found at each commit of a range.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// Commits in the range that carry the status file.
+    pub commits: usize,
+    /// Violations already present before the range.
+    pub before: Vec<Violation>,
+    /// Commits where the violations changed, oldest first.
+    pub steps: Vec<ReplayStep>,
+    /// Violations present at the end of the range.
+    pub after: Vec<Violation>,
+}
+
+/// A commit where violations appeared or went away.
+#[derive(Debug, Clone)]
+pub struct ReplayStep {
+    pub commit: String,
+    pub info: CommitInfo,
+    /// Violations first present at this commit.
+    pub introduced: Vec<Violation>,
+    /// Violations present at the commit before and gone at this one.
+    pub resolved: Vec<Violation>,
+}
+
+/// The history violations a run at the newest of `snapshots` would report:
+/// skipped pending states, tampered status files, and the per-commit rules
+/// configured in `rules`, less those covered by `Ratchet-Override`
+/// trailers or set to `allow`. Pure function — no IO.
+pub fn history_violations(
+    snapshots: &[HistorySnapshot],
+    grandfather: &GrandfatherPatterns,
+    rules: &RulesConfig,
+) -> Vec<Violation> {
+    let baseline = snapshots
+        .last()
+        .and_then(|snapshot| snapshot.status.baseline.as_deref());
+    let mut violations: Vec<Violation> =
+        check_history_snapshots_with(snapshots, baseline, grandfather)
+            .into_iter()
+            .map(|violation| match violation {
+                HistoryViolation::SkippedPending { test, commit } => {
+                    Violation::SkippedPending { test, commit }
+                }
+            })
+            .collect();
+    violations.extend(integrity_findings(snapshots).0);
+    if let Some(limit) = rules.max_promotions_per_commit {
+        violations.extend(
+            oversized_promotions(snapshots, baseline, limit)
+                .into_iter()
+                .map(|(commit, tests)| Violation::TooManyPromotions {
+                    commit,
+                    tests,
+                    limit,
+                }),
+        );
+    }
+    if !rules.pending_test_commit_types.is_empty() {
+        let allowed = &rules.pending_test_commit_types;
+        violations.extend(
+            mistyped_pending_additions(snapshots, baseline, allowed)
+                .into_iter()
+                .map(
+                    |(commit, summary, tests)| Violation::PendingTestCommitType {
+                        commit,
+                        summary,
+                        tests,
+                        allowed: allowed.clone(),
+                    },
+                ),
+        );
+    }
+    let (violations, _) = apply_overrides(violations, snapshots);
+    violations
+        .into_iter()
+        .filter(|violation| {
+            violation
+                .rule()
+                .is_none_or(|rule| rules.level(rule) != RuleLevel::Allow)
+        })
+        .collect()
+}
+
+/// Replay the history rules at each snapshot whose commit is in `range`.
+/// `snapshots` is the whole history up to the end of the range, oldest
+/// first, so baselines and grandfathering work as they did at the time.
+/// Pure function — no IO.
+pub fn replay(
+    snapshots: &[HistorySnapshot],
+    range: &BTreeSet<String>,
+    grandfather: &GrandfatherPatterns,
+    rules: &RulesConfig,
+) -> Replay {
+    let points: Vec<usize> = snapshots
+        .iter()
+        .enumerate()
+        .filter(|(_, snapshot)| range.contains(&snapshot.commit))
+        .map(|(index, _)| index)
+        .collect();
+    let before = match points.first() {
+        Some(&first) if first > 0 => history_violations(&snapshots[..first], grandfather, rules),
+        _ => Vec::new(),
+    };
+    // Each point is independent of the others, so they are checked in
+    // parallel.
+    let found: Vec<Vec<Violation>> = points
+        .par_iter()
+        .map(|&index| history_violations(&snapshots[..=index], grandfather, rules))
+        .collect();
+
+    let mut steps = Vec::new();
+    let mut previous = &before;
+    for (&index, violations) in points.iter().zip(&found) {
+        let introduced = missing_from(violations, previous);
+        let resolved = missing_from(previous, violations);
+        if !introduced.is_empty() || !resolved.is_empty() {
+            steps.push(ReplayStep {
+                commit: snapshots[index].commit.clone(),
+                info: snapshots[index].info.clone(),
+                introduced,
+                resolved,
+            });
+        }
+        previous = violations;
+    }
+    let after = found.last().cloned().unwrap_or_else(|| before.clone());
+    Replay {
+        commits: points.len(),
+        before,
+        steps,
+        after,
+    }
+}
+
+/// The violations in `violations` that are not in `other`.
+fn missing_from(violations: &[Violation], other: &[Violation]) -> Vec<Violation> {
+    let other: BTreeSet<_> = other.iter().map(violation_summary).collect();
+    violations
+        .iter()
+        .filter(|violation| !other.contains(&violation_summary(violation)))
+        .cloned()
+        .collect()
+}
+
+/// Render a replay of `range` (as given, e.g. `v1.0..main`) as text.
+pub fn format_replay(range: &str, replay: &Replay) -> String {
+    let mut out = format!(
+        "tdd-ratchet replay {range}: {} commit{} with .test-status.json\n",
+        replay.commits,
+        if replay.commits == 1 { "" } else { "s" }
+    );
+    if !replay.before.is_empty() {
+        out.push_str(&format!(
+            "\nAlready present before the range ({}):\n",
+            replay.before.len()
+        ));
+        for violation in &replay.before {
+            out.push_str(&violation_line("  ", violation));
+        }
+    }
+    for step in &replay.steps {
+        out.push_str(&format!(
+            "\n{} {} {} — {}\n",
+            short_commit(&step.commit),
+            format_date(step.info.time),
+            step.info.author,
+            step.info.summary
+        ));
+        for violation in &step.introduced {
+            out.push_str(&violation_line("  + ", violation));
+        }
+        for violation in &step.resolved {
+            out.push_str(&violation_line("  - ", violation));
+        }
+    }
+    let introduced: usize = replay.steps.iter().map(|step| step.introduced.len()).sum();
+    out.push_str(&format!(
+        "\n{introduced} introduced in the range, {} present at its end\n",
+        replay.after.len()
+    ));
+    out
+}
+
+fn violation_line(prefix: &str, violation: &Violation) -> String {
+    let (_, subject, detail) = violation_summary(violation);
+    format!("{prefix}{} {subject}: {detail}\n", violation.code())
+}
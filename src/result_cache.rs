@@ -0,0 +1,562 @@
+// Result caching: reuse a test binary's results while nothing it is built
+// from has changed.
+//
+// Each workspace package gets a fingerprint of its inputs: the toolchain
+// (`rustc -vV`), `RUSTFLAGS`, the workspace `Cargo.toml` and `Cargo.lock`
+// (which pins every dependency's version and checksum), the contents of its
+// own files, and those of every workspace package it depends on. A binary's
+// results are stored under its package's fingerprint and reused on a later
+// run as long as that fingerprint is unchanged. The gatekeeper always runs.
+//
+// A remote store shares results between machines, e.g. CI jobs retried on
+// the same commit. It holds one object per package fingerprint with the
+// results of all the package's binaries, read and written by a GET and a PUT
+// command; the HTTP, S3 and GCS kinds are such commands. Results read from
+// the store are trusted like results of a local run, so anyone who can write
+// to it can make tests pass. With a signing key, objects carry an
+// HMAC-SHA256 over their name and results, and objects that fail to verify
+// are ignored.
+
+use crate::affected::{WorkspacePackage, workspace_packages};
+use crate::error::{ParseError, RatchetError};
+use crate::ratchet::GATEKEEPER_TEST_NAME;
+use crate::runner::TestResult;
+use crate::status::split_binary;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+/// Sidecar file holding cached results, next to `.test-status.json`.
+pub const RESULT_CACHE_FILE_NAME: &str = ".ratchet-cache.json";
+
+/// One test binary's cached results.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedBinary {
+    /// Fingerprint of the binary's package when the results were recorded.
+    pub fingerprint: String,
+    pub results: Vec<TestResult>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    #[serde(default)]
+    binaries: BTreeMap<String, CachedBinary>,
+}
+
+/// What a run can reuse, found by [`ResultCache::lookup`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheLookup {
+    /// Current fingerprint of each workspace package.
+    pub fingerprints: BTreeMap<String, String>,
+    /// Every binary in the cache file.
+    pub entries: BTreeMap<String, CachedBinary>,
+    /// Results of the binaries whose package is unchanged, without the
+    /// gatekeeper, by binary ID.
+    pub reused: BTreeMap<String, Vec<TestResult>>,
+}
+
+/// The cache file of a project, from `[cache]` in `ratchet.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultCache {
+    project_dir: PathBuf,
+    remote: Option<RemoteCache>,
+}
+
+impl ResultCache {
+    pub fn new(project_dir: impl Into<PathBuf>) -> Self {
+        ResultCache {
+            project_dir: project_dir.into(),
+            remote: None,
+        }
+    }
+
+    /// Also read results missing locally from `remote`, and write this
+    /// run's there.
+    pub fn with_remote(mut self, remote: RemoteCache) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
+    fn path(&self) -> PathBuf {
+        self.project_dir.join(RESULT_CACHE_FILE_NAME)
+    }
+
+    /// Fingerprint the workspace and find the binaries whose results can be
+    /// reused. A missing or unreadable cache file reuses nothing; packages
+    /// it has no results for are then looked up in the remote store.
+    pub fn lookup(&self) -> Result<CacheLookup, RatchetError> {
+        let fingerprints = package_fingerprints(&self.project_dir)?;
+        let mut entries = fs::read_to_string(self.path())
+            .ok()
+            .and_then(|contents| serde_json::from_str::<CacheFile>(&contents).ok())
+            .unwrap_or_default()
+            .binaries;
+        if let Some(remote) = &self.remote {
+            for (package, fingerprint) in &fingerprints {
+                let cached = entries.iter().any(|(binary, entry)| {
+                    package_of(binary) == package && entry.fingerprint == *fingerprint
+                });
+                if cached {
+                    continue;
+                }
+                let Some(binaries) =
+                    remote.fetch(&self.project_dir, &remote_key(package, fingerprint))
+                else {
+                    continue;
+                };
+                for (binary, results) in binaries {
+                    if package_of(&binary) == package {
+                        let fingerprint = fingerprint.clone();
+                        entries.insert(
+                            binary,
+                            CachedBinary {
+                                fingerprint,
+                                results,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        let reused = reusable_binaries(&entries, &fingerprints);
+        Ok(CacheLookup {
+            fingerprints,
+            entries,
+            reused,
+        })
+    }
+
+    /// Record the results of this run's binaries next to the reused ones,
+    /// and upload the packages that ran to the remote store.
+    pub fn store(&self, lookup: &CacheLookup, fresh: &[TestResult]) -> Result<(), RatchetError> {
+        let path = self.path();
+        let file = CacheFile {
+            binaries: updated_entries(lookup, fresh),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(|source| ParseError::Sidecar {
+            path: path.clone(),
+            source,
+        })?;
+        fs::write(&path, json + "\n").map_err(|e| RatchetError::io(&path, e))?;
+        let Some(remote) = &self.remote else {
+            return Ok(());
+        };
+        for ((package, fingerprint), binaries) in remote_uploads(lookup, &file.binaries) {
+            let key = remote_key(&package, &fingerprint);
+            let json = remote
+                .encode(&key, binaries)
+                .map_err(|source| ParseError::Sidecar {
+                    path: path.clone(),
+                    source,
+                })?;
+            remote
+                .put(&self.project_dir, &key, &json)
+                .map_err(|e| RatchetError::command("remote cache put", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// A remote store of cached results: `get` prints the object named by
+/// `{key}` on stdout, failing if there is none, and `put` stores its stdin
+/// under `{key}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteCache {
+    pub get: Vec<String>,
+    pub put: Vec<String>,
+    /// Credentials both commands read from stdin, kept out of their argv
+    /// where other users could read them. With a secret, `put` reads the
+    /// object from the file `{file}` names instead of from stdin.
+    pub secret: Option<String>,
+    /// Key that signs stored objects and verifies fetched ones; see
+    /// [`sign_object`]. Without one, fetched results are not checked.
+    pub signing_key: Option<String>,
+}
+
+/// A remote object signed with the store's signing key.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedObject {
+    signature: String,
+    results: BTreeMap<String, Vec<TestResult>>,
+}
+
+/// HMAC-SHA256, in hex, of the object named `key` holding `results`, under
+/// `signing_key`. Pure function — no IO.
+pub fn sign_object(
+    signing_key: &str,
+    key: &str,
+    results: &BTreeMap<String, Vec<TestResult>>,
+) -> String {
+    object_mac(signing_key, key, results)
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn object_mac(
+    signing_key: &str,
+    key: &str,
+    results: &BTreeMap<String, Vec<TestResult>>,
+) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(key.as_bytes());
+    mac.update(b"\n");
+    mac.update(&serde_json::to_vec(results).expect("test results serialize"));
+    mac
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+impl RemoteCache {
+    /// The object to store under `key` for `results`: signed when there is
+    /// a signing key.
+    fn encode(
+        &self,
+        key: &str,
+        results: BTreeMap<String, Vec<TestResult>>,
+    ) -> serde_json::Result<Vec<u8>> {
+        match &self.signing_key {
+            None => serde_json::to_vec(&results),
+            Some(signing_key) => serde_json::to_vec(&SignedObject {
+                signature: sign_object(signing_key, key, &results),
+                results,
+            }),
+        }
+    }
+
+    /// The results in the object fetched for `key`; `None` when it does not
+    /// parse or, with a signing key, its signature does not match.
+    fn decode(&self, key: &str, bytes: &[u8]) -> Option<BTreeMap<String, Vec<TestResult>>> {
+        let Some(signing_key) = &self.signing_key else {
+            return serde_json::from_slice(bytes).ok();
+        };
+        let object: SignedObject = serde_json::from_slice(bytes).ok()?;
+        let signature = decode_hex(&object.signature)?;
+        match object_mac(signing_key, key, &object.results).verify_slice(&signature) {
+            Ok(()) => Some(object.results),
+            Err(_) => {
+                warn!(key, "ignoring remote cache object with a bad signature");
+                None
+            }
+        }
+    }
+
+    /// The binaries and results stored under `key`; `None` when the store
+    /// has none, cannot be reached, or holds an object that fails to
+    /// verify, so the tests run instead.
+    fn fetch(&self, dir: &Path, key: &str) -> Option<BTreeMap<String, Vec<TestResult>>> {
+        let (program, args) = self.get.split_first()?;
+        let mut child = Command::new(program)
+            .args(args.iter().map(|arg| arg.replace("{key}", key)))
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut stdin = child.stdin.take().expect("child stdin should be piped");
+        if let Some(secret) = &self.secret {
+            stdin.write_all(secret.as_bytes()).ok()?;
+        }
+        drop(stdin);
+        let output = child.wait_with_output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        self.decode(key, &output.stdout)
+    }
+
+    fn put(&self, dir: &Path, key: &str, contents: &[u8]) -> io::Result<()> {
+        let (program, args) = self.put.split_first().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "remote cache put command is empty",
+            )
+        })?;
+        // With a secret on stdin, the object goes through a file.
+        let file = match &self.secret {
+            Some(_) => Some(write_private_temp(key, contents)?),
+            None => None,
+        };
+        let file_arg = file
+            .as_ref()
+            .map(|file| file.display().to_string())
+            .unwrap_or_default();
+        let spawned = Command::new(program)
+            .args(
+                args.iter()
+                    .map(|arg| arg.replace("{key}", key).replace("{file}", &file_arg)),
+            )
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .and_then(|mut child| {
+                let mut stdin = child.stdin.take().expect("child stdin should be piped");
+                stdin.write_all(self.secret.as_deref().map_or(contents, str::as_bytes))?;
+                drop(stdin);
+                child.wait()
+            });
+        if let Some(file) = &file {
+            let _ = fs::remove_file(file);
+        }
+        let status = spawned?;
+        if !status.success() {
+            return Err(io::Error::other(format!("{program} exited with {status}")));
+        }
+        Ok(())
+    }
+}
+
+/// Write `contents` to a new file in the temporary directory that only this
+/// user can read. The file must not exist yet, so a planted file or symlink
+/// is never followed.
+fn write_private_temp(key: &str, contents: &[u8]) -> io::Result<PathBuf> {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let path = std::env::temp_dir().join(format!(
+        "tdd-ratchet-cache-{}-{nanos}-{key}",
+        std::process::id()
+    ));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    if let Err(e) = file.write_all(contents) {
+        let _ = fs::remove_file(&path);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+/// The remote object holding `package`'s results at `fingerprint`.
+pub fn remote_key(package: &str, fingerprint: &str) -> String {
+    format!("{package}-{fingerprint}.json")
+}
+
+/// What to upload after a run: for each package with a binary that ran,
+/// keyed by package and fingerprint, the results of all its binaries.
+/// Packages whose binaries were all reused are already stored. Pure
+/// function — no IO.
+pub fn remote_uploads(
+    lookup: &CacheLookup,
+    entries: &BTreeMap<String, CachedBinary>,
+) -> BTreeMap<(String, String), BTreeMap<String, Vec<TestResult>>> {
+    let ran: BTreeSet<&str> = entries
+        .keys()
+        .filter(|binary| !lookup.reused.contains_key(*binary))
+        .map(|binary| package_of(binary))
+        .collect();
+    let mut uploads: BTreeMap<(String, String), BTreeMap<String, Vec<TestResult>>> =
+        BTreeMap::new();
+    for (binary, entry) in entries {
+        let package = package_of(binary);
+        if ran.contains(package) {
+            uploads
+                .entry((package.to_string(), entry.fingerprint.clone()))
+                .or_default()
+                .insert(binary.clone(), entry.results.clone());
+        }
+    }
+    uploads
+}
+
+/// The package a binary ID such as `my-crate::integration` belongs to.
+fn package_of(binary: &str) -> &str {
+    binary.split("::").next().unwrap_or(binary)
+}
+
+/// The cached results of each binary whose package fingerprint is unchanged,
+/// without the gatekeeper test, which always runs. Pure function — no IO.
+pub fn reusable_binaries(
+    entries: &BTreeMap<String, CachedBinary>,
+    fingerprints: &BTreeMap<String, String>,
+) -> BTreeMap<String, Vec<TestResult>> {
+    entries
+        .iter()
+        .filter(|(binary, entry)| fingerprints.get(package_of(binary)) == Some(&entry.fingerprint))
+        .map(|(binary, entry)| {
+            let results = entry
+                .results
+                .iter()
+                .filter(|result| !result.name.ends_with(GATEKEEPER_TEST_NAME))
+                .cloned()
+                .collect();
+            (binary.clone(), results)
+        })
+        .collect()
+}
+
+/// The cache after a run: reused binaries keep their entries, and each
+/// binary that ran is stored under its package's current fingerprint.
+/// Binaries that neither ran nor were reused are dropped. Pure function —
+/// no IO.
+pub fn updated_entries(
+    lookup: &CacheLookup,
+    fresh: &[TestResult],
+) -> BTreeMap<String, CachedBinary> {
+    let mut entries: BTreeMap<String, CachedBinary> = lookup
+        .entries
+        .iter()
+        .filter(|(binary, _)| lookup.reused.contains_key(*binary))
+        .map(|(binary, entry)| (binary.clone(), entry.clone()))
+        .collect();
+    for result in fresh {
+        let Some((binary, _)) = split_binary(&result.name) else {
+            continue;
+        };
+        if lookup.reused.contains_key(binary) {
+            continue;
+        }
+        let Some(fingerprint) = lookup.fingerprints.get(package_of(binary)) else {
+            continue;
+        };
+        entries
+            .entry(binary.to_string())
+            .or_insert_with(|| CachedBinary {
+                fingerprint: fingerprint.clone(),
+                results: Vec::new(),
+            })
+            .results
+            .push(result.clone());
+    }
+    entries
+}
+
+/// `scope` (a nextest filterset, or `None` for every test) without the
+/// `cached` binaries, keeping the gatekeeper test. Pure function — no IO.
+pub fn uncached_filterset(scope: Option<String>, cached: &[String]) -> Option<String> {
+    if cached.is_empty() {
+        return scope;
+    }
+    let skipped: Vec<String> = cached
+        .iter()
+        .map(|binary| format!("binary_id(={binary})"))
+        .collect();
+    let uncached = format!("not ({})", skipped.join(" | "));
+    let filterset = match scope {
+        Some(scope) => format!("({scope}) & {uncached}"),
+        None => uncached,
+    };
+    Some(format!("{filterset} | test(/{GATEKEEPER_TEST_NAME}$/)"))
+}
+
+/// Fingerprint of each package from `common` (the inputs every package
+/// shares) and the `sources` digest of each package's own files, covering
+/// the workspace packages it depends on, directly or not. Pure function —
+/// no IO.
+pub fn fingerprint_packages(
+    common: &str,
+    packages: &[WorkspacePackage],
+    sources: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let by_name: BTreeMap<&str, &WorkspacePackage> =
+        packages.iter().map(|p| (p.name.as_str(), p)).collect();
+    packages
+        .iter()
+        .map(|package| {
+            let mut covered = BTreeSet::new();
+            let mut queue = vec![package.name.as_str()];
+            while let Some(name) = queue.pop() {
+                if let Some(p) = by_name.get(name)
+                    && covered.insert(name)
+                {
+                    queue.extend(p.dependencies.iter().map(String::as_str));
+                }
+            }
+            let mut canonical = format!("tdd-ratchet cache v1\n{common}\n");
+            for name in covered {
+                let digest = sources.get(name).map_or("-", String::as_str);
+                canonical.push_str(&format!("{name} {digest}\n"));
+            }
+            (package.name.clone(), digest(canonical.as_bytes()))
+        })
+        .collect()
+}
+
+fn digest(bytes: &[u8]) -> String {
+    git2::Oid::hash_object(git2::ObjectType::Blob, bytes)
+        .expect("hashing a buffer does not touch the repository")
+        .to_string()
+}
+
+/// Fingerprint every package of the workspace at `project_dir`.
+pub fn package_fingerprints(project_dir: &Path) -> Result<BTreeMap<String, String>, RatchetError> {
+    let rustc = Command::new("rustc")
+        .arg("-vV")
+        .current_dir(project_dir)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| RatchetError::command("rustc -vV", e))?;
+    let mut common = String::from_utf8_lossy(&rustc.stdout).into_owned();
+    common.push_str(&format!(
+        "RUSTFLAGS={}\n",
+        std::env::var("RUSTFLAGS").unwrap_or_default()
+    ));
+    for file in ["Cargo.toml", "Cargo.lock"] {
+        let contents = fs::read(project_dir.join(file)).unwrap_or_default();
+        common.push_str(&format!("{file} {}\n", digest(&contents)));
+    }
+
+    let packages = workspace_packages(project_dir, project_dir)?;
+    let mut sources = BTreeMap::new();
+    for package in &packages {
+        let dir = project_dir.join(&package.dir);
+        let mut files = Vec::new();
+        collect_files(&dir, &mut files).map_err(|e| RatchetError::io(&dir, e))?;
+        files.sort();
+        let mut listing = String::new();
+        for file in files {
+            let contents = fs::read(&file).map_err(|e| RatchetError::io(&file, e))?;
+            let file = file.strip_prefix(project_dir).unwrap_or(&file);
+            listing.push_str(&format!("{} {}\n", file.display(), digest(&contents)));
+        }
+        sources.insert(package.name.clone(), digest(listing.as_bytes()));
+    }
+    Ok(fingerprint_packages(&common, &packages, &sources))
+}
+
+/// Every file under `dir`, skipping `target` and hidden files and
+/// directories, such as the status file.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let path = entry.path();
+        if name.starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            if name != "target" {
+                collect_files(&path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,341 @@
+// Partial runs: `-p <PACKAGE>` and `-E <FILTERSET>` limit a run to some of
+// the tests, as they do for nextest.
+//
+// Tests outside the filter do not run, so they would be reported as
+// disappeared. A test is only left out of the ratchet when the filter
+// provably excludes it: filtersets are evaluated against the tracked name
+// with three-valued logic, and any predicate that cannot be decided from the
+// name alone (regexes, `deps()`, `kind()`, ...) keeps the test in scope.
+
+use crate::grandfather::glob_matches;
+use crate::ratchet::GATEKEEPER_TEST_NAME;
+use crate::status::split_binary;
+
+/// The `-p` and `-E` filters a run was limited to. Empty for a full run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunFilter {
+    /// Packages from `-p`/`--package`; a test runs if it is in any of them.
+    pub packages: Vec<String>,
+    /// Nextest filtersets from `-E`/`--filterset`; a test runs if any of
+    /// them selects it.
+    pub filtersets: Vec<String>,
+}
+
+impl RunFilter {
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty() && self.filtersets.is_empty()
+    }
+
+    /// Whether the filter provably leaves `test` out of the run. A test the
+    /// filter may or may not select, or one it cannot be checked against,
+    /// is not excluded. Pure function — no IO.
+    pub fn excludes(&self, test: &str) -> bool {
+        let outside_packages = !self.packages.is_empty()
+            && package_of(test).is_some_and(|package| {
+                !self
+                    .packages
+                    .iter()
+                    .any(|p| p.split('@').next() == Some(package))
+            });
+        let outside_filtersets = !self.filtersets.is_empty()
+            && self
+                .filtersets
+                .iter()
+                .all(|filterset| parse(filterset).is_some_and(|expr| expr.eval(test) == Tri::No));
+        outside_packages || outside_filtersets
+    }
+
+    /// How the run was limited, as given on the command line, e.g.
+    /// `--test integration -p core -E 'test(parse)'`; `None` for a full run.
+    pub fn label(&self, binaries: &[String]) -> Option<String> {
+        let parts: Vec<String> = binaries
+            .iter()
+            .map(|binary| format!("--test {binary}"))
+            .chain(self.packages.iter().map(|package| format!("-p {package}")))
+            .chain(
+                self.filtersets
+                    .iter()
+                    .map(|filterset| format!("-E '{filterset}'")),
+            )
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// The nextest filterset running the selected test binaries, packages
+    /// and filtersets, and the gatekeeper test wherever it lives; `None` for
+    /// a full run. Packages are selected in the filterset rather than with
+    /// `-p`, so the gatekeeper's package is still built. Binaries are as in
+    /// [`binary_selected`](crate::status::binary_selected). Pure function —
+    /// no IO.
+    pub fn nextest_filterset(&self, binaries: &[String]) -> Option<String> {
+        let binary_terms: Vec<String> = binaries
+            .iter()
+            .map(|binary| {
+                if binary.contains("::") {
+                    format!("binary_id(={binary})")
+                } else {
+                    format!("binary(={binary})")
+                }
+            })
+            .collect();
+        let package_terms: Vec<String> = self
+            .packages
+            .iter()
+            .map(|package| format!("package(={package})"))
+            .collect();
+        let filterset_terms: Vec<String> = self
+            .filtersets
+            .iter()
+            .map(|filterset| format!("({filterset})"))
+            .collect();
+        let terms: Vec<String> = [binary_terms, package_terms, filterset_terms]
+            .into_iter()
+            .filter(|terms| !terms.is_empty())
+            .map(|terms| terms.join(" | "))
+            .collect();
+        let scope = match terms.as_slice() {
+            [] => return None,
+            [term] => term.clone(),
+            _ => terms
+                .iter()
+                .map(|term| format!("({term})"))
+                .collect::<Vec<_>>()
+                .join(" & "),
+        };
+        Some(format!("{scope} | test(/{GATEKEEPER_TEST_NAME}$/)"))
+    }
+}
+
+/// The package of a test run from a test binary: the binary ID up to its
+/// first `::`.
+fn package_of(test: &str) -> Option<&str> {
+    let (binary, _) = split_binary(test)?;
+    binary.split("::").next()
+}
+
+/// Whether a filterset selects a test: `Maybe` when that cannot be decided
+/// from the test's name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tri {
+    Yes,
+    No,
+    Maybe,
+}
+
+impl Tri {
+    fn from_bool(b: bool) -> Tri {
+        if b { Tri::Yes } else { Tri::No }
+    }
+
+    fn not(self) -> Tri {
+        match self {
+            Tri::Yes => Tri::No,
+            Tri::No => Tri::Yes,
+            Tri::Maybe => Tri::Maybe,
+        }
+    }
+
+    fn and(self, other: Tri) -> Tri {
+        match (self, other) {
+            (Tri::No, _) | (_, Tri::No) => Tri::No,
+            (Tri::Yes, Tri::Yes) => Tri::Yes,
+            _ => Tri::Maybe,
+        }
+    }
+
+    fn or(self, other: Tri) -> Tri {
+        self.not().and(other.not()).not()
+    }
+}
+
+/// A parsed nextest filterset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Predicate { name: String, arg: String },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, test: &str) -> Tri {
+        match self {
+            Expr::Predicate { name, arg } => predicate(name, arg, test),
+            Expr::Not(expr) => expr.eval(test).not(),
+            Expr::And(a, b) => a.eval(test).and(b.eval(test)),
+            Expr::Or(a, b) => a.eval(test).or(b.eval(test)),
+        }
+    }
+}
+
+/// Evaluate one predicate such as `test(parse)` against a tracked name.
+fn predicate(name: &str, arg: &str, test: &str) -> Tri {
+    let binary = split_binary(test).map(|(binary, _)| binary);
+    let path = split_binary(test).map_or(test, |(_, path)| path);
+    match name {
+        "all" => Tri::Yes,
+        "none" => Tri::No,
+        "test" => matches(arg, path, Fallback::Contains),
+        "package" => binary.map_or(Tri::Maybe, |binary| {
+            let package = binary.split("::").next().unwrap_or(binary);
+            matches(arg, package, Fallback::Glob)
+        }),
+        "binary_id" => binary.map_or(Tri::Maybe, |binary| matches(arg, binary, Fallback::Glob)),
+        "binary" => binary.map_or(Tri::Maybe, |binary| {
+            let name = match binary.split_once("::") {
+                Some((_, target)) => target.rsplit('/').next().unwrap_or(target).to_string(),
+                // A library's unit tests: the binary is named after the crate.
+                None => binary.replace('-', "_"),
+            };
+            matches(arg, &name, Fallback::Glob)
+        }),
+        _ => Tri::Maybe,
+    }
+}
+
+/// The matcher a predicate uses when its argument has no `=`, `~`, `#` or
+/// `/` prefix.
+#[derive(Debug, Clone, Copy)]
+enum Fallback {
+    Contains,
+    Glob,
+}
+
+/// Match `text` against a nextest name matcher. Regexes, and globs using
+/// more than `*`, cannot be checked here.
+fn matches(matcher: &str, text: &str, fallback: Fallback) -> Tri {
+    let glob = |pattern: &str| {
+        if pattern.contains(['?', '[', '{']) {
+            Tri::Maybe
+        } else {
+            Tri::from_bool(glob_matches(pattern, text))
+        }
+    };
+    if let Some(exact) = matcher.strip_prefix('=') {
+        Tri::from_bool(text == exact)
+    } else if let Some(part) = matcher.strip_prefix('~') {
+        Tri::from_bool(text.contains(part))
+    } else if let Some(pattern) = matcher.strip_prefix('#') {
+        glob(pattern)
+    } else if matcher.starts_with('/') {
+        Tri::Maybe
+    } else {
+        match fallback {
+            Fallback::Contains => Tri::from_bool(text.contains(matcher)),
+            Fallback::Glob => glob(matcher),
+        }
+    }
+}
+
+/// Parse a filterset; `None` if it is not valid.
+fn parse(filterset: &str) -> Option<Expr> {
+    let mut parser = Parser {
+        chars: filterset.chars().collect(),
+        pos: 0,
+    };
+    let expr = parser.or()?;
+    parser.skip_whitespace();
+    (parser.pos == parser.chars.len()).then_some(expr)
+}
+
+/// Recursive-descent parser for filtersets: `not`/`!` binds tightest, then
+/// `and`/`&` and the difference `-`, then `or`/`|`/`+`.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume `token` if it comes next. A word only matches a whole word.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let len = token.chars().count();
+        let next: String = self.chars.iter().skip(self.pos).take(len).collect();
+        let is_word = token.chars().all(|c| c.is_ascii_alphabetic());
+        let boundary = !is_word
+            || !self
+                .chars
+                .get(self.pos + len)
+                .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_');
+        if next == token && boundary {
+            self.pos += len;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Option<Expr> {
+        let mut expr = self.and()?;
+        while self.eat("|") || self.eat("+") || self.eat("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Some(expr)
+    }
+
+    fn and(&mut self) -> Option<Expr> {
+        let mut expr = self.unary()?;
+        loop {
+            if self.eat("&") || self.eat("and") {
+                expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+            } else if self.eat("-") {
+                let rhs = Expr::Not(Box::new(self.unary()?));
+                expr = Expr::And(Box::new(expr), Box::new(rhs));
+            } else {
+                return Some(expr);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        if self.eat("!") || self.eat("not") {
+            return Some(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expr = self.or()?;
+            return self.eat(")").then_some(expr);
+        }
+        self.predicate()
+    }
+
+    fn predicate(&mut self) -> Option<Expr> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+        {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+        if name.is_empty() || !self.eat("(") {
+            return None;
+        }
+        let start = self.pos;
+        // A regex may contain `)`, so it runs to its closing `/`.
+        let regex = self.chars.get(self.pos) == Some(&'/');
+        loop {
+            let c = *self.chars.get(self.pos)?;
+            let closes_regex = self.pos > start + 1
+                && self.chars[self.pos - 1] == '/'
+                && self.chars[self.pos - 2] != '\\';
+            if c == ')' && (!regex || closes_regex) {
+                break;
+            }
+            self.pos += 1;
+        }
+        let arg: String = self.chars[start..self.pos].iter().collect();
+        self.pos += 1;
+        Some(Expr::Predicate {
+            name,
+            arg: arg.trim().to_string(),
+        })
+    }
+}
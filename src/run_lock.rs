@@ -0,0 +1,85 @@
+// Run lock: one ratchet run at a time per project.
+//
+// Two overlapping runs (a watcher and a manual run, or parallel CI steps)
+// would interleave their test runs and overwrite each other's status file.
+// Each run holds an advisory lock on an untracked `.ratchet-lock` sidecar
+// from gathering until the status is saved. The operating system drops the
+// lock when the process exits, however it exits, so a crashed run never
+// leaves the project locked.
+
+use crate::error::RatchetError;
+use std::fmt;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+
+pub const LOCK_FILE_NAME: &str = ".ratchet-lock";
+
+/// The lock on a project, held until dropped.
+#[derive(Debug)]
+pub struct RunLock {
+    _file: File,
+}
+
+/// Another run holds the lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockHeld {
+    /// Process ID the holder recorded, if it could be read.
+    pub pid: Option<u32>,
+}
+
+impl fmt::Display for LockHeld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "another ratchet run is in progress")?;
+        if let Some(pid) = self.pid {
+            write!(f, " (process {pid})")?;
+        }
+        Ok(())
+    }
+}
+
+impl RunLock {
+    /// Take the lock of the project at `dir`, or report who holds it.
+    pub fn try_acquire(dir: &Path) -> Result<Result<RunLock, LockHeld>, RatchetError> {
+        let failed = |e| RatchetError::io(dir.join(LOCK_FILE_NAME), e);
+        let mut file = open(dir).map_err(failed)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Ok(RunLock::holding(file).map_err(failed)?)),
+            Err(TryLockError::WouldBlock) => {
+                let mut contents = String::new();
+                let pid = file
+                    .read_to_string(&mut contents)
+                    .ok()
+                    .and_then(|_| contents.trim().parse().ok());
+                Ok(Err(LockHeld { pid }))
+            }
+            Err(TryLockError::Error(e)) => Err(failed(e)),
+        }
+    }
+
+    /// Take the lock of the project at `dir`, waiting for the run holding
+    /// it to finish.
+    pub fn acquire(dir: &Path) -> Result<RunLock, RatchetError> {
+        let failed = |e| RatchetError::io(dir.join(LOCK_FILE_NAME), e);
+        let file = open(dir).map_err(failed)?;
+        file.lock().map_err(failed)?;
+        RunLock::holding(file).map_err(failed)
+    }
+
+    /// Record this process as the holder, for the message other runs show.
+    fn holding(mut file: File) -> io::Result<RunLock> {
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(RunLock { _file: file })
+    }
+}
+
+fn open(dir: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(dir.join(LOCK_FILE_NAME))
+}
@@ -1,57 +1,1618 @@
-// Test runner output parsing: extracts per-test results from nextest
-// libtest-json structured output.
+// Test runner: invokes the test harness and extracts per-test results from
+// nextest libtest-json structured output.
 
-use serde::Deserialize;
+use crate::gatekeeper::Handshake;
+use crate::partition::Partition;
+use crate::ratchet::GATEKEEPER_TEST_NAME;
+use crate::result_cache::{ResultCache, uncached_filterset};
+use crate::run_filter::RunFilter;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, ExitStatus, Stdio};
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tracing::{debug, info, info_span, warn};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TestResult {
     pub name: String,
     pub outcome: TestOutcome,
+    /// Captured output of a failed test, when the harness reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// How long the test took in milliseconds, when the harness reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec_time_ms: Option<u64>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TestOutcome {
     Passed,
     Failed,
     Ignored,
+    /// Passed on a retry after failing an earlier attempt.
+    Flaky,
 }
 
+/// The fields of a libtest-json event needed to classify it. `stdout` is
+/// skipped without being copied; see [`FailedTestEvent`].
 #[derive(Deserialize)]
-struct TestEvent {
-    #[serde(rename = "type")]
-    kind: String,
-    event: String,
+struct TestEvent<'a> {
+    #[serde(rename = "type", borrow)]
+    kind: Cow<'a, str>,
+    #[serde(borrow)]
+    event: Cow<'a, str>,
     name: Option<String>,
+    /// Seconds the test took; present on terminal events.
+    exec_time: Option<f64>,
+}
+
+/// The captured output of a failed test, read in a second pass so passing
+/// tests' output is never allocated.
+#[derive(Deserialize)]
+struct FailedTestEvent {
+    stdout: Option<String>,
 }
 
 /// Parse nextest libtest-json output into per-test results.
 ///
 /// Each JSON line with `"type":"test"` and `"event":"ok"|"failed"|"ignored"`
 /// produces a TestResult. The full nextest name is preserved as-is
-/// (e.g. `my-crate::tests$test_name`).
+/// (e.g. `my-crate::tests$test_name`), and a failed test keeps its captured
+/// `stdout`. Retried tests are merged with [`merge_retries`].
 pub fn parse_nextest_output(output: &str) -> Vec<TestResult> {
+    merge_retries(output.lines().filter_map(parse_nextest_line))
+}
+
+/// Longest line buffer a [`NextestEvents`] keeps between lines; one grown
+/// past it by a huge captured output is released.
+const RETAINED_LINE_CAPACITY: usize = 64 * 1024;
+
+/// Parse nextest libtest-json output from `reader` line by line, yielding a
+/// result for every test attempt as it is read.
+///
+/// Only one line is held at a time, so memory stays bounded however long
+/// the run is. Retried tests are reported once per attempt; collect them
+/// with [`merge_retries`].
+pub fn parse_nextest_events_stream<R: BufRead>(reader: R) -> NextestEvents<R> {
+    NextestEvents {
+        reader,
+        line: Vec::new(),
+    }
+}
+
+/// Iterator returned by [`parse_nextest_events_stream`].
+#[derive(Debug)]
+pub struct NextestEvents<R> {
+    reader: R,
+    line: Vec<u8>,
+}
+
+impl<R: BufRead> Iterator for NextestEvents<R> {
+    type Item = io::Result<TestResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.line.capacity() > RETAINED_LINE_CAPACITY {
+                self.line = Vec::new();
+            }
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+            let line = String::from_utf8_lossy(&self.line);
+            if let Some(result) = parse_nextest_line(line.trim_end_matches(['\n', '\r'])) {
+                return Some(Ok(result));
+            }
+        }
+    }
+}
+
+/// Collapse repeated results for the same test, as nextest reports each
+/// attempt when retries are enabled, into one result per test.
+///
+/// The last attempt decides the outcome and execution time; a test that passes after a failed
+/// attempt becomes [`TestOutcome::Flaky`]. Tests keep the order of their
+/// first attempt.
+pub fn merge_retries(results: impl IntoIterator<Item = TestResult>) -> Vec<TestResult> {
+    let mut merged: Vec<TestResult> = Vec::new();
+    let mut index: BTreeMap<String, usize> = BTreeMap::new();
+    for result in results {
+        let Some(&at) = index.get(&result.name) else {
+            index.insert(result.name.clone(), merged.len());
+            merged.push(result);
+            continue;
+        };
+        let previous = merged[at].outcome;
+        merged[at].outcome = match (previous, result.outcome) {
+            (TestOutcome::Failed | TestOutcome::Flaky, TestOutcome::Passed) => TestOutcome::Flaky,
+            (_, outcome) => outcome,
+        };
+        merged[at].output = result.output;
+        merged[at].exec_time_ms = result.exec_time_ms;
+    }
+    merged
+}
+
+/// Parse a single line of nextest libtest-json output.
+///
+/// Returns `None` for non-JSON lines, suite events, and `started` events, so
+/// callers reading the child's stdout incrementally can feed every line in.
+pub fn parse_nextest_line(line: &str) -> Option<TestResult> {
+    let event = serde_json::from_str::<TestEvent>(line).ok()?;
+    if event.kind != "test" {
+        return None;
+    }
+    let outcome = match &*event.event {
+        "ok" => TestOutcome::Passed,
+        "failed" => TestOutcome::Failed,
+        "ignored" => TestOutcome::Ignored,
+        _ => return None, // "started" etc.
+    };
+    let output = match outcome {
+        TestOutcome::Failed => serde_json::from_str::<FailedTestEvent>(line)
+            .ok()
+            .and_then(|failed| failed.stdout),
+        _ => None,
+    };
+    // Keep the full nextest name as-is (e.g. "my-crate::tests$test_one")
+    Some(TestResult {
+        name: event.name?,
+        outcome,
+        output,
+        exec_time_ms: event.exec_time.map(seconds_to_ms),
+    })
+}
+
+/// Convert a harness-reported duration in seconds to whole milliseconds.
+fn seconds_to_ms(seconds: f64) -> u64 {
+    (seconds.max(0.0) * 1000.0).round() as u64
+}
+
+/// Parse plain libtest output (`cargo test` without JSON), e.g.
+/// `test tests::my_test ... ok`.
+///
+/// Names are whatever the harness prints; they carry no binary prefix.
+pub fn parse_libtest_output(output: &str) -> Vec<TestResult> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("test ")?;
+            let (name, status) = rest.rsplit_once(" ... ")?;
+            let outcome = match status.trim() {
+                "ok" => TestOutcome::Passed,
+                "FAILED" => TestOutcome::Failed,
+                s if s.starts_with("ignored") => TestOutcome::Ignored,
+                _ => return None,
+            };
+            Some(TestResult {
+                name: name.trim().to_string(),
+                outcome,
+                output: None,
+                exec_time_ms: None,
+            })
+        })
+        .collect()
+}
+
+/// Parse `pytest -v` output into results in the status-file namespace.
+///
+/// Lines like `tests/test_api.py::TestUsers::test_create PASSED [ 50%]`
+/// become `pytest::tests/test_api.py$TestUsers::test_create`, mirroring the
+/// `binary$test` shape of nextest names. Skipped and xfail tests are treated
+/// as ignored; errors count as failures.
+pub fn parse_pytest_output(output: &str) -> Vec<TestResult> {
+    const STATUSES: [(&str, TestOutcome); 6] = [
+        (" PASSED", TestOutcome::Passed),
+        (" XPASS", TestOutcome::Passed),
+        (" FAILED", TestOutcome::Failed),
+        (" ERROR", TestOutcome::Failed),
+        (" SKIPPED", TestOutcome::Ignored),
+        (" XFAIL", TestOutcome::Ignored),
+    ];
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let (node_id, outcome) = STATUSES.iter().find_map(|(marker, outcome)| {
+                line.rfind(marker).map(|at| (line[..at].trim(), *outcome))
+            })?;
+            let (file, test) = node_id.split_once("::")?;
+            if file.contains(char::is_whitespace) {
+                return None;
+            }
+            Some(TestResult {
+                name: format!("pytest::{file}${test}"),
+                outcome,
+                output: None,
+                exec_time_ms: None,
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JestReport {
+    test_results: Vec<JestFileResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JestFileResult {
+    name: String,
+    assertion_results: Vec<JestAssertion>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JestAssertion {
+    #[serde(default)]
+    ancestor_titles: Vec<String>,
+    title: String,
+    status: String,
+    /// Milliseconds; Jest reports `null` for tests that did not run.
+    duration: Option<f64>,
+}
+
+/// Parse a Jest `--json` report (also produced by Vitest's `json` reporter).
+///
+/// Each assertion becomes `js::<file>$<describe> › <title>`, with the file
+/// path made relative to `root` so names are stable across checkouts.
+/// Pending, skipped, and todo tests are treated as ignored.
+pub fn parse_jest_json(output: &str, root: &Path) -> Vec<TestResult> {
+    // Tools sometimes print banners around the report; take the outermost
+    // JSON object.
+    let report = match (output.find('{'), output.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &output[start..=end],
+        _ => return Vec::new(),
+    };
+    let Ok(report) = serde_json::from_str::<JestReport>(report) else {
+        return Vec::new();
+    };
+
     let mut results = Vec::new();
+    for file in report.test_results {
+        let path = Path::new(&file.name);
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let file_name = relative.to_string_lossy().replace('\\', "/");
+        for assertion in file.assertion_results {
+            let outcome = match assertion.status.as_str() {
+                "passed" => TestOutcome::Passed,
+                "failed" => TestOutcome::Failed,
+                _ => TestOutcome::Ignored,
+            };
+            let title = assertion
+                .ancestor_titles
+                .iter()
+                .chain(std::iter::once(&assertion.title))
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" › ");
+            results.push(TestResult {
+                name: format!("js::{file_name}${title}"),
+                outcome,
+                output: None,
+                exec_time_ms: assertion.duration.map(|ms| ms.max(0.0).round() as u64),
+            });
+        }
+    }
+    results
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GoTestEvent {
+    action: String,
+    package: Option<String>,
+    test: Option<String>,
+    /// Seconds the test took; present on terminal events.
+    elapsed: Option<f64>,
+}
+
+/// Parse the `go test -json` event stream.
+///
+/// Terminal `pass`/`fail`/`skip` events for individual tests become
+/// `go::<package>$<test>` (subtests keep their `Parent/child` names).
+/// Package-level events are ignored.
+pub fn parse_go_test_json(output: &str) -> Vec<TestResult> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let event = serde_json::from_str::<GoTestEvent>(line).ok()?;
+            let outcome = match event.action.as_str() {
+                "pass" => TestOutcome::Passed,
+                "fail" => TestOutcome::Failed,
+                "skip" => TestOutcome::Ignored,
+                _ => return None,
+            };
+            Some(TestResult {
+                name: format!("go::{}${}", event.package?, event.test?),
+                outcome,
+                output: None,
+                exec_time_ms: event.elapsed.map(seconds_to_ms),
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct CucumberFeature {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    uri: String,
+    #[serde(default)]
+    elements: Vec<CucumberElement>,
+}
+
+#[derive(Deserialize)]
+struct CucumberElement {
+    #[serde(default)]
+    name: String,
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    before: Vec<CucumberStep>,
+    #[serde(default)]
+    steps: Vec<CucumberStep>,
+    #[serde(default)]
+    after: Vec<CucumberStep>,
+}
+
+/// A step or hook; hooks have no keyword or name.
+#[derive(Deserialize)]
+struct CucumberStep {
+    #[serde(default)]
+    keyword: String,
+    #[serde(default)]
+    name: String,
+    result: CucumberStepResult,
+}
+
+#[derive(Deserialize)]
+struct CucumberStepResult {
+    status: String,
+    /// Nanoseconds.
+    duration: Option<u64>,
+    error_message: Option<String>,
+}
+
+/// Parse a Cucumber JSON report, as written by cucumber-rs's
+/// `writer::Json` and other Cucumber implementations.
+///
+/// Each scenario becomes `cucumber::<feature>$<scenario>`, named by
+/// [`cucumber_test_name`]; a background's steps count towards the scenario
+/// that follows it. A scenario with an undefined or pending step fails, so a
+/// scenario written before its steps starts out pending; one whose steps
+/// were all skipped is ignored.
+pub fn parse_cucumber_json(output: &str) -> Vec<TestResult> {
+    let report = match (output.find('['), output.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &output[start..=end],
+        _ => return Vec::new(),
+    };
+    let Ok(features) = serde_json::from_str::<Vec<CucumberFeature>>(report) else {
+        return Vec::new();
+    };
+
+    let mut results = Vec::new();
+    for feature in features {
+        let feature_name = if feature.name.trim().is_empty() {
+            &feature.uri
+        } else {
+            &feature.name
+        };
+        let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+        let mut background: Vec<CucumberStep> = Vec::new();
+        for element in feature.elements {
+            if element.kind == "background" {
+                background = [element.before, element.steps, element.after]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                continue;
+            }
+            let steps: Vec<CucumberStep> = std::mem::take(&mut background)
+                .into_iter()
+                .chain(element.before)
+                .chain(element.steps)
+                .chain(element.after)
+                .collect();
+            let occurrence = seen.entry(element.name.clone()).or_default();
+            *occurrence += 1;
+            results.push(TestResult {
+                name: cucumber_test_name(feature_name, &element.name, *occurrence),
+                outcome: cucumber_outcome(&steps),
+                output: cucumber_failure(&steps),
+                exec_time_ms: steps
+                    .iter()
+                    .filter_map(|step| step.result.duration)
+                    .reduce(|total, ns| total + ns)
+                    .map(|ns| ns.div_ceil(1_000_000)),
+            });
+        }
+    }
+    results
+}
+
+/// The tracked name of a Cucumber scenario:
+/// `cucumber::<feature>$<scenario>`, with runs of whitespace collapsed so
+/// reformatting a `.feature` file keeps the name. `occurrence` counts
+/// scenarios of the same name in the feature, such as the example rows of a
+/// scenario outline; every one after the first gets a ` #<occurrence>`
+/// suffix.
+pub fn cucumber_test_name(feature: &str, scenario: &str, occurrence: usize) -> String {
+    let collapse = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut name = format!("cucumber::{}${}", collapse(feature), collapse(scenario));
+    if occurrence > 1 {
+        name.push_str(&format!(" #{occurrence}"));
+    }
+    name
+}
+
+fn cucumber_outcome(steps: &[CucumberStep]) -> TestOutcome {
+    let status = |wanted: &[&str]| {
+        steps
+            .iter()
+            .any(|step| wanted.contains(&step.result.status.as_str()))
+    };
+    if status(&["failed", "undefined", "pending", "ambiguous"]) {
+        TestOutcome::Failed
+    } else if !steps.is_empty() && !status(&["passed"]) {
+        TestOutcome::Ignored
+    } else {
+        TestOutcome::Passed
+    }
+}
+
+/// The steps that did not pass or get skipped, with their error messages.
+fn cucumber_failure(steps: &[CucumberStep]) -> Option<String> {
+    let lines: Vec<String> = steps
+        .iter()
+        .filter(|step| !matches!(step.result.status.as_str(), "passed" | "skipped"))
+        .map(|step| {
+            let step_name = format!("{}{}", step.keyword, step.name);
+            let step_name = if step_name.trim().is_empty() {
+                "hook"
+            } else {
+                step_name.trim()
+            };
+            match &step.result.error_message {
+                Some(message) => format!("{step_name}: {}\n{message}", step.result.status),
+                None => format!("{step_name}: {}", step.result.status),
+            }
+        })
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Parse the output of `wasm-pack test` (wasm-bindgen-test-runner), e.g.
+/// `test web::renders ... ok`.
+///
+/// Names are `<crate or test target>::<path>`, so each test becomes
+/// `wasm::<target>$<path>`, named by [`wasm_test_name`]. `FAIL` and `FAILED`
+/// both count as failures, and a failure keeps the lines of its
+/// `---- <name> output ----` section.
+pub fn parse_wasm_bindgen_output(output: &str) -> Vec<TestResult> {
+    let mut results: Vec<TestResult> = Vec::new();
+    let mut failure: Option<(usize, Vec<&str>)> = None;
     for line in output.lines() {
-        let Ok(event) = serde_json::from_str::<TestEvent>(line) else {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" output ----"))
+        {
+            flush_wasm_failure(&mut results, failure.take());
+            let name = wasm_test_name(name);
+            failure = results
+                .iter()
+                .position(|result| result.name == name)
+                .map(|index| (index, Vec::new()));
             continue;
-        };
-        if event.kind != "test" {
+        }
+        if trimmed == "failures:" || trimmed.starts_with("test result:") {
+            flush_wasm_failure(&mut results, failure.take());
+        }
+        if let Some((_, lines)) = &mut failure {
+            lines.push(line);
             continue;
         }
-        let outcome = match event.event.as_str() {
-            "ok" => TestOutcome::Passed,
-            "failed" => TestOutcome::Failed,
-            "ignored" => TestOutcome::Ignored,
-            _ => continue, // "started" etc.
-        };
-        let Some(full_name) = event.name else {
+        let Some((name, status)) = trimmed
+            .strip_prefix("test ")
+            .and_then(|rest| rest.rsplit_once(" ... "))
+        else {
             continue;
         };
-        // Keep the full nextest name as-is (e.g. "my-crate::tests$test_one")
+        let outcome = match status.trim() {
+            "ok" => TestOutcome::Passed,
+            "FAIL" | "FAILED" => TestOutcome::Failed,
+            s if s.starts_with("ignored") => TestOutcome::Ignored,
+            _ => continue,
+        };
         results.push(TestResult {
-            name: full_name,
+            name: wasm_test_name(name.trim()),
             outcome,
+            output: None,
+            exec_time_ms: None,
         });
     }
+    flush_wasm_failure(&mut results, failure);
+    results
+}
+
+/// The status-file name of a wasm-bindgen test:
+/// `wasm::<target>$<path>`, splitting the printed name at its first `::`.
+/// Pure function — no IO.
+pub fn wasm_test_name(name: &str) -> String {
+    match name.split_once("::") {
+        Some((target, path)) => format!("wasm::{target}${path}"),
+        None => format!("wasm${name}"),
+    }
+}
+
+fn flush_wasm_failure(results: &mut [TestResult], failure: Option<(usize, Vec<&str>)>) {
+    let Some((index, lines)) = failure else {
+        return;
+    };
+    let text = lines.join("\n").trim().to_string();
+    if !text.is_empty() {
+        results[index].output = Some(text);
+    }
+}
+
+/// Parse defmt-test output as printed by `probe-rs run` (usually as the
+/// cargo runner of `cargo test` for an embedded target).
+///
+/// Each `(i/n) running `name`...` log starts a test, which passes when the
+/// next test starts or the binary logs `all tests passed!`. defmt-test stops
+/// at the first failure, so a test still running when its binary's output
+/// ends (or when the count starts again for the next binary) failed, and
+/// keeps the log lines printed after it, such as the panic. `ignoring`
+/// logs are ignored tests. Names come from probe-rs's location line under
+/// each log, `└─ <target>::<module>::__defmt_test_entry @ file:line`, as
+/// `embedded::<target>$<module>::<test>`; without locations
+/// (`--no-location`) they are `embedded$<test>`.
+pub fn parse_defmt_test_output(output: &str) -> Vec<TestResult> {
+    let mut results = Vec::new();
+    // The test started last: its index in `results`, its count, and the log
+    // lines printed since.
+    let mut running: Option<(usize, usize, Vec<&str>)> = None;
+    for line in output.lines() {
+        if let Some(module) = defmt_location(line) {
+            if let Some(result) = results.last_mut() {
+                rename_defmt_test(result, module);
+            }
+            continue;
+        }
+        if let Some((count, verb, name)) = defmt_test_log(line) {
+            if let Some((index, previous, log)) = running.take() {
+                let finished = count > previous;
+                finish_defmt_test(&mut results[index], finished, log);
+            }
+            let outcome = if verb == "running" {
+                running = Some((results.len(), count, Vec::new()));
+                TestOutcome::Failed
+            } else {
+                TestOutcome::Ignored
+            };
+            results.push(TestResult {
+                name: format!("embedded${name}"),
+                outcome,
+                output: None,
+                exec_time_ms: None,
+            });
+            continue;
+        }
+        if line.contains("all tests passed!") {
+            if let Some((index, _, log)) = running.take() {
+                finish_defmt_test(&mut results[index], true, log);
+            }
+            continue;
+        }
+        if let Some((_, _, log)) = &mut running {
+            log.push(line.trim());
+        }
+    }
+    if let Some((index, _, log)) = running {
+        finish_defmt_test(&mut results[index], false, log);
+    }
     results
 }
+
+/// `(count, verb, name)` of a defmt-test `(i/n) running `name`...` or
+/// `(i/n) ignoring `name`...` log line, after any timestamp and level.
+fn defmt_test_log(line: &str) -> Option<(usize, &str, &str)> {
+    let start = line.find('(')?;
+    let (counter, rest) = line[start + 1..].split_once(") ")?;
+    let (count, _total) = counter.split_once('/')?;
+    let count = count.parse().ok()?;
+    let (verb, rest) = rest.split_once(" `")?;
+    if verb != "running" && verb != "ignoring" {
+        return None;
+    }
+    let (name, _) = rest.split_once('`')?;
+    Some((count, verb, name))
+}
+
+/// The module of a probe-rs location line under a defmt-test log.
+fn defmt_location(line: &str) -> Option<&str> {
+    let (path, _) = line.trim().strip_prefix("└─ ")?.split_once(" @ ")?;
+    path.strip_suffix("::__defmt_test_entry")
+}
+
+fn rename_defmt_test(result: &mut TestResult, module: &str) {
+    let Some(test) = result.name.strip_prefix("embedded$") else {
+        return;
+    };
+    result.name = match module.split_once("::") {
+        Some((target, path)) => format!("embedded::{target}${path}::{test}"),
+        None => format!("embedded::{module}${test}"),
+    };
+}
+
+fn finish_defmt_test(result: &mut TestResult, passed: bool, log: Vec<&str>) {
+    if passed {
+        result.outcome = TestOutcome::Passed;
+        return;
+    }
+    let log: Vec<&str> = log
+        .into_iter()
+        .filter(|line| !line.is_empty() && !line.starts_with("└─ "))
+        .collect();
+    result.output = (!log.is_empty()).then(|| log.join("\n"));
+}
+
+/// Output format understood by [`CommandRunner`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputParser {
+    /// libtest-json event lines, as emitted by nextest or
+    /// `cargo test -- -Z unstable-options --format json`.
+    #[default]
+    LibtestJson,
+    /// Plain libtest lines: `test name ... ok|FAILED|ignored`.
+    Libtest,
+    /// `pytest -v` lines, normalized to `pytest::file$test` names.
+    Pytest,
+    /// A Jest/Vitest JSON report, normalized to `js::file$title` names.
+    JestJson,
+    /// `go test -json` events, normalized to `go::package$Test` names.
+    GoTestJson,
+    /// A Cucumber JSON report, normalized to `cucumber::feature$scenario`
+    /// names.
+    CucumberJson,
+    /// `wasm-pack test` output, normalized to `wasm::target$test` names.
+    WasmBindgen,
+    /// defmt-test logs from `probe-rs run`, normalized to
+    /// `embedded::target$test` names.
+    DefmtTest,
+}
+
+impl OutputParser {
+    /// Parse captured stdout. `working_dir` is where the command ran, used to
+    /// relativize file paths in reports that contain absolute paths.
+    pub fn parse(self, output: &str, working_dir: &Path) -> Vec<TestResult> {
+        match self {
+            OutputParser::LibtestJson => parse_nextest_output(output),
+            OutputParser::Libtest => parse_libtest_output(output),
+            OutputParser::Pytest => parse_pytest_output(output),
+            OutputParser::JestJson => parse_jest_json(output, working_dir),
+            OutputParser::GoTestJson => parse_go_test_json(output),
+            OutputParser::CucumberJson => parse_cucumber_json(output),
+            OutputParser::WasmBindgen => parse_wasm_bindgen_output(output),
+            OutputParser::DefmtTest => parse_defmt_test_output(output),
+        }
+    }
+}
+
+/// Running tally shown while a test run is still in progress.
+///
+/// Failures of tests that are already tracked as pending are expected in a
+/// TDD workflow, so they are counted separately from unexpected failures.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunProgress {
+    pub passed: usize,
+    pub failed: usize,
+    pub pending_expected: usize,
+    pub ignored: usize,
+}
+
+impl RunProgress {
+    pub fn record(&mut self, result: &TestResult, tracked_as_pending: bool) {
+        match result.outcome {
+            TestOutcome::Passed | TestOutcome::Flaky => self.passed += 1,
+            TestOutcome::Failed if tracked_as_pending => self.pending_expected += 1,
+            TestOutcome::Failed => self.failed += 1,
+            TestOutcome::Ignored => self.ignored += 1,
+        }
+    }
+
+    pub fn summary_line(&self) -> String {
+        format!(
+            "{} passed / {} failed / {} pending expected",
+            self.passed, self.failed, self.pending_expected
+        )
+    }
+}
+
+/// Source of per-test results for a ratchet run.
+///
+/// The default implementation is [`NextestRunner`]; embedders can supply their
+/// own to feed results from any harness into the ratchet.
+pub trait TestRunner {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError>;
+
+    /// Run only `test`, without `TDD_RATCHET` set, as a plain `cargo test`
+    /// would. `None` when this runner cannot run a single test or did not
+    /// report it.
+    fn run_without_ratchet(&self, _test: &str) -> Result<Option<TestOutcome>, RunnerError> {
+        Ok(None)
+    }
+
+    /// Whether a gatekeeper echoed the nonce of the last run. `None` when
+    /// this runner takes no part in a [`Handshake`].
+    fn gatekeeper_acknowledged(&self) -> Option<bool> {
+        None
+    }
+
+    /// How often the last run was retried after an infrastructure failure.
+    fn infrastructure_retries(&self) -> u32 {
+        0
+    }
+}
+
+impl<T: TestRunner + ?Sized> TestRunner for &T {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        (**self).run()
+    }
+
+    fn run_without_ratchet(&self, test: &str) -> Result<Option<TestOutcome>, RunnerError> {
+        (**self).run_without_ratchet(test)
+    }
+
+    fn gatekeeper_acknowledged(&self) -> Option<bool> {
+        (**self).gatekeeper_acknowledged()
+    }
+
+    fn infrastructure_retries(&self) -> u32 {
+        (**self).infrastructure_retries()
+    }
+}
+
+impl<T: TestRunner + ?Sized> TestRunner for Box<T> {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        (**self).run()
+    }
+
+    fn run_without_ratchet(&self, test: &str) -> Result<Option<TestOutcome>, RunnerError> {
+        (**self).run_without_ratchet(test)
+    }
+
+    fn gatekeeper_acknowledged(&self) -> Option<bool> {
+        (**self).gatekeeper_acknowledged()
+    }
+
+    fn infrastructure_retries(&self) -> u32 {
+        (**self).infrastructure_retries()
+    }
+}
+
+/// Read `child`'s stdout line by line into `on_line` until it closes, then
+/// wait for it to exit.
+///
+/// If `deadline` passes first, the child is killed and the run reported as
+/// timed out: its results are incomplete.
+fn read_lines(
+    child: &mut Child,
+    command: &str,
+    deadline: Option<Instant>,
+    on_line: impl FnMut(String),
+) -> Result<ExitStatus, RunnerError> {
+    read_items(
+        child,
+        command,
+        deadline,
+        |stdout| {
+            stdout.split(b'\n').map(|line| {
+                line.map(|line| {
+                    String::from_utf8_lossy(&line)
+                        .trim_end_matches('\r')
+                        .to_string()
+                })
+            })
+        },
+        on_line,
+    )
+}
+
+/// Items read from a child's stdout and not yet handled. Bounds memory when
+/// the child writes faster than its output is handled.
+const READ_AHEAD: usize = 1024;
+
+/// [`read_lines`] for any items `parse` reads from the child's stdout.
+fn read_items<T, I>(
+    child: &mut Child,
+    command: &str,
+    deadline: Option<Instant>,
+    parse: impl FnOnce(BufReader<ChildStdout>) -> I + Send + 'static,
+    mut on_item: impl FnMut(T),
+) -> Result<ExitStatus, RunnerError>
+where
+    T: Send + 'static,
+    I: Iterator<Item = io::Result<T>>,
+{
+    let stdout = child.stdout.take().expect("child stdout should be piped");
+    // Read on another thread so the deadline is checked even while the
+    // child is silent.
+    let (items_tx, items) = mpsc::sync_channel(READ_AHEAD);
+    thread::spawn(move || {
+        for item in parse(BufReader::new(stdout)) {
+            let Ok(item) = item else {
+                break;
+            };
+            if items_tx.send(item).is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        let item = match deadline {
+            Some(deadline) => {
+                items.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => items.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match item {
+            Ok(item) => on_item(item),
+            Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(RunnerError::TimedOut {
+                    command: command.to_string(),
+                });
+            }
+        }
+    }
+
+    child.wait().map_err(|source| RunnerError::Wait {
+        command: command.to_string(),
+        source,
+    })
+}
+
+/// nextest's exit code when building the test binaries failed.
+const NEXTEST_BUILD_FAILED: i32 = 101;
+
+/// Output that marks a failure of the machine rather than of the code: the
+/// linker or compiler running out of memory, or the compiler cache failing.
+const INFRASTRUCTURE_MARKERS: &[&str] = &[
+    "SIGKILL",
+    "ld terminated with signal",
+    "collect2: fatal error",
+    "memory allocation of",
+    "Cannot allocate memory",
+    "sccache: error",
+    "sccache: encountered fatal error",
+    "failed to connect to the sccache server",
+];
+
+/// Why a command that exited with `status` after printing `output` failed
+/// for reasons outside the code under test, if it did: it was killed by a
+/// signal, or its output names a linker, memory or compiler-cache failure.
+/// Such a run says nothing about the tests and is worth retrying.
+pub fn infrastructure_failure(status: ExitStatus, output: &str) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return Some(format!("killed by signal {signal}"));
+        }
+    }
+    if status.success() {
+        return None;
+    }
+    INFRASTRUCTURE_MARKERS
+        .iter()
+        .find(|marker| output.contains(*marker))
+        .map(|marker| format!("output mentions `{marker}`"))
+}
+
+/// A command killed by a signal, or one that failed without reporting any
+/// test, has no usable results. Its output tells an infrastructure failure
+/// from the common case, a project that did not compile.
+fn check_compiled(
+    status: ExitStatus,
+    results: &[TestResult],
+    command: &str,
+    output: String,
+) -> Result<(), RunnerError> {
+    let killed = status.code().is_none();
+    if !killed && (status.success() || !results.is_empty()) {
+        return Ok(());
+    }
+    if let Some(reason) = infrastructure_failure(status, &output) {
+        return Err(RunnerError::Infrastructure {
+            command: command.to_string(),
+            reason,
+            retries: 0,
+        });
+    }
+    if status.success() || !results.is_empty() {
+        return Ok(());
+    }
+    Err(RunnerError::CompileFailed {
+        command: command.to_string(),
+        status,
+        output,
+    })
+}
+
+/// Runs `cargo nextest run` with libtest-json output in a project directory.
+#[derive(Debug, Clone)]
+pub struct NextestRunner {
+    project_dir: PathBuf,
+    pending: BTreeSet<String>,
+    inherit_stderr: bool,
+    deadline: Option<Instant>,
+    test_timeout: Option<Duration>,
+    partition: Option<Partition>,
+    handshake: Option<Arc<Handshake>>,
+    binaries: Vec<String>,
+    filter: RunFilter,
+    cache: Option<ResultCache>,
+    args: Vec<String>,
+    target_dir: Option<PathBuf>,
+}
+
+impl NextestRunner {
+    pub fn new(project_dir: impl Into<PathBuf>) -> Self {
+        NextestRunner {
+            project_dir: project_dir.into(),
+            pending: BTreeSet::new(),
+            inherit_stderr: true,
+            deadline: None,
+            test_timeout: None,
+            partition: None,
+            handshake: None,
+            binaries: Vec::new(),
+            filter: RunFilter::default(),
+            cache: None,
+            args: Vec::new(),
+            target_dir: None,
+        }
+    }
+
+    /// Hand the tests `handshake`'s nonce, for a gatekeeper to echo.
+    pub fn with_handshake(mut self, handshake: Arc<Handshake>) -> Self {
+        self.handshake = Some(handshake);
+        self
+    }
+
+    /// Run only one partition of the tests, as one shard of a split run.
+    pub fn with_partition(mut self, partition: Partition) -> Self {
+        self.partition = Some(partition);
+        self
+    }
+
+    /// Run only the test binaries named in `binaries`, and the gatekeeper
+    /// test wherever it lives; see [`binaries_filterset`].
+    pub fn with_binaries(mut self, binaries: Vec<String>) -> Self {
+        self.binaries = binaries;
+        self
+    }
+
+    /// Run only the tests `filter` selects, and the gatekeeper test; see
+    /// [`RunFilter::nextest_filterset`].
+    pub fn with_filter(mut self, filter: RunFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Reuse the cached results of test binaries whose inputs are unchanged
+    /// instead of running them, and cache this run's. Partial runs neither
+    /// read nor write the cache.
+    pub fn with_cache(mut self, cache: ResultCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Pass `args` on to `cargo nextest run`, e.g. `--profile ci`; see
+    /// [`crate::config::NextestConfig`].
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Build into `dir` rather than the project's own target directory, by
+    /// setting `CARGO_TARGET_DIR`.
+    pub fn with_target_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.target_dir = Some(dir.into());
+        self
+    }
+
+    /// Kill the run if it is still going at `deadline`; the run then fails
+    /// with [`RunnerError::TimedOut`].
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Have nextest terminate any single test that runs longer than
+    /// `timeout`; it is then reported as failed.
+    pub fn with_test_timeout(mut self, timeout: Duration) -> Self {
+        self.test_timeout = Some(timeout);
+        self
+    }
+
+    /// Tests tracked as pending or draft, so their failures count as expected
+    /// in the live progress line.
+    pub fn with_pending(mut self, pending: BTreeSet<String>) -> Self {
+        self.pending = pending;
+        self
+    }
+
+    /// Whether nextest's own stderr (build output, failure details) and the
+    /// live progress line are shown. On by default.
+    pub fn inherit_stderr(mut self, inherit: bool) -> Self {
+        self.inherit_stderr = inherit;
+        self
+    }
+
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+}
+
+impl TestRunner for NextestRunner {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        let _span = info_span!("nextest", dir = %self.project_dir.display()).entered();
+        let mut command = Command::new("cargo");
+        command
+            .args([
+                "nextest",
+                "run",
+                "--no-fail-fast",
+                "--message-format",
+                "libtest-json",
+            ])
+            .args(&self.args)
+            .current_dir(&self.project_dir)
+            .env("TDD_RATCHET", "1")
+            .env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1")
+            .stdout(Stdio::piped());
+        if let Some(dir) = &self.target_dir {
+            command.env("CARGO_TARGET_DIR", dir);
+        }
+        if let Some(handshake) = &self.handshake {
+            handshake.reset();
+            command.envs(handshake.env());
+        }
+
+        // nextest has no command-line flag for a per-test timeout, so pass
+        // one as a tool config file for the profile in use; the project's
+        // own config still wins.
+        let tool_config = match self.test_timeout {
+            Some(timeout) => Some(write_timeout_config(timeout, &nextest_profile(&self.args))?),
+            None => None,
+        };
+        if let Some(partition) = &self.partition {
+            command.arg("--partition").arg(partition.nextest_arg());
+        }
+        let cached = self
+            .cache
+            .as_ref()
+            .filter(|_| {
+                self.binaries.is_empty() && self.filter.is_empty() && self.partition.is_none()
+            })
+            .and_then(|cache| match cache.lookup() {
+                Ok(lookup) => Some((cache, lookup)),
+                Err(e) => {
+                    warn!(error = %e, "failed to look up cached results");
+                    eprintln!("tdd-ratchet: warning: failed to look up cached results: {e}");
+                    None
+                }
+            });
+        let skipped: Vec<String> = cached
+            .iter()
+            .flat_map(|(_, lookup)| lookup.reused.keys().cloned())
+            .collect();
+        if cached.is_some() {
+            debug!(reused = ?skipped, "looked up cached results");
+        }
+        if !skipped.is_empty() && self.inherit_stderr {
+            eprintln!(
+                "tdd-ratchet: reusing cached results of {} unchanged test binar{} (--no-cache to rerun)",
+                skipped.len(),
+                if skipped.len() == 1 { "y" } else { "ies" }
+            );
+        }
+        let scope = self.filter.nextest_filterset(&self.binaries);
+        if let Some(filterset) = uncached_filterset(scope, &skipped) {
+            command.arg("-E").arg(filterset);
+        }
+        if let Some(path) = &tool_config {
+            command
+                .arg("--tool-config-file")
+                .arg(format!("tdd-ratchet:{}", path.display()));
+        }
+
+        // Compiler errors arrive on stderr. Shown live, or kept for the
+        // compile failure report when stderr is not inherited.
+        command.stderr(Stdio::piped());
+
+        info!(command = ?command, "running cargo nextest");
+        let spawned = command.spawn();
+        let run = spawned
+            .map_err(|source| RunnerError::Spawn {
+                command: "cargo nextest".into(),
+                source,
+            })
+            .and_then(|mut child| {
+                let stderr = child.stderr.take().expect("child stderr should be piped");
+                let inherit = self.inherit_stderr;
+                let captured = thread::spawn(move || forward_stderr(stderr, inherit));
+                // Parse events as they arrive so the user sees progress
+                // during long runs instead of silence until the whole suite
+                // finishes.
+                let show_progress = self.inherit_stderr && io::stderr().is_terminal();
+                let mut progress = RunProgress::default();
+                let mut results = Vec::new();
+                let read = read_items(
+                    &mut child,
+                    "cargo nextest",
+                    self.deadline,
+                    parse_nextest_events_stream,
+                    |result: TestResult| {
+                        progress.record(&result, self.pending.contains(&result.name));
+                        if show_progress {
+                            eprint!("\r\x1b[2Ktdd-ratchet: {}", progress.summary_line());
+                        }
+                        results.push(result);
+                    },
+                );
+                if show_progress {
+                    eprintln!();
+                }
+                let status = read?;
+                let stderr = captured.join().unwrap_or_default();
+                info!(%status, results = results.len(), "cargo nextest finished");
+                // nextest exits 0 with no tests and 4 when none matched, so
+                // only its build failure code counts. Cargo uses the same
+                // code when nextest is not installed at all.
+                let build_failed = status.code() == Some(NEXTEST_BUILD_FAILED);
+                if build_failed && results.is_empty() && stderr.contains("no such command") {
+                    return Err(RunnerError::NextestMissing);
+                }
+                // A killed run's results are incomplete even when there
+                // are some.
+                let killed = status.code().is_none();
+                if (killed || (build_failed && results.is_empty()))
+                    && let Some(reason) = infrastructure_failure(status, &stderr)
+                {
+                    return Err(RunnerError::Infrastructure {
+                        command: "cargo nextest".into(),
+                        reason,
+                        retries: 0,
+                    });
+                }
+                if build_failed && results.is_empty() {
+                    return Err(RunnerError::CompileFailed {
+                        command: "cargo nextest".into(),
+                        status,
+                        output: if inherit { String::new() } else { stderr },
+                    });
+                }
+                Ok(merge_retries(results))
+            });
+
+        if let Some(path) = tool_config {
+            let _ = std::fs::remove_file(path);
+        }
+        let Some((cache, lookup)) = cached else {
+            return run;
+        };
+        let mut results = run?;
+        // A cache that cannot be written only costs the next run time.
+        if let Err(e) = cache.store(&lookup, &results) {
+            warn!(error = %e, "failed to store cached results");
+            eprintln!("tdd-ratchet: warning: failed to store cached results: {e}");
+        }
+        results.extend(lookup.reused.into_values().flatten());
+        Ok(results)
+    }
+
+    fn run_without_ratchet(&self, test: &str) -> Result<Option<TestOutcome>, RunnerError> {
+        let Some((binary, path)) = test.split_once('$') else {
+            return Ok(None);
+        };
+        let mut command = Command::new("cargo");
+        command
+            .args([
+                "nextest",
+                "run",
+                "--no-fail-fast",
+                "--message-format",
+                "libtest-json",
+            ])
+            .args(&self.args)
+            .arg("-E")
+            .arg(format!("binary_id(={binary}) & test(={path})"))
+            .current_dir(&self.project_dir)
+            .env_remove("TDD_RATCHET")
+            .env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1");
+        if let Some(dir) = &self.target_dir {
+            command.env("CARGO_TARGET_DIR", dir);
+        }
+        let output = command
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|source| RunnerError::Spawn {
+                command: "cargo nextest".into(),
+                source,
+            })?;
+        let outcome = parse_nextest_output(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .find(|result| result.name == test)
+            .map(|result| result.outcome);
+        debug!(test, ?outcome, "ran gatekeeper without TDD_RATCHET");
+        Ok(outcome)
+    }
+}
+
+/// Collect the child's stderr, also copying it to ours when `inherit` is set.
+fn forward_stderr(mut stderr: impl Read, inherit: bool) -> String {
+    let mut collected = Vec::new();
+    let mut buf = [0; 8192];
+    while let Ok(n) = stderr.read(&mut buf) {
+        if n == 0 {
+            break;
+        }
+        if inherit {
+            let mut ours = io::stderr().lock();
+            let _ = ours.write_all(&buf[..n]);
+            let _ = ours.flush();
+        }
+        collected.extend_from_slice(&buf[..n]);
+    }
+    String::from_utf8_lossy(&collected).into_owned()
+}
+
+/// The nextest filterset selecting the test binaries in `binaries` and the
+/// gatekeeper test: a name with `::` is a full binary ID, any other name a
+/// binary name. Pure function — no IO.
+pub fn binaries_filterset(binaries: &[String]) -> String {
+    RunFilter::default()
+        .nextest_filterset(binaries)
+        .unwrap_or_else(|| format!("test(/{GATEKEEPER_TEST_NAME}$/)"))
+}
+
+/// The nextest profile `args` select: the last `--profile`/`-P`, else
+/// `NEXTEST_PROFILE`, else `default`.
+fn nextest_profile(args: &[String]) -> String {
+    let mut profile = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" || arg == "-P" {
+            profile = args.next().cloned();
+        } else if let Some(name) = arg.strip_prefix("--profile=") {
+            profile = Some(name.to_string());
+        }
+    }
+    profile
+        .or_else(|| std::env::var("NEXTEST_PROFILE").ok())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+/// Write a nextest tool config that terminates tests after `timeout` under
+/// `profile`, the profile the run uses.
+fn write_timeout_config(timeout: Duration, profile: &str) -> Result<PathBuf, RunnerError> {
+    let path =
+        std::env::temp_dir().join(format!("tdd-ratchet-nextest-{}.toml", std::process::id()));
+    let config = format!(
+        "[profile.{}]\nslow-timeout = {{ period = \"{}ms\", terminate-after = 1 }}\n",
+        toml::Value::String(profile.to_string()),
+        timeout.as_millis().max(1)
+    );
+    std::fs::write(&path, config).map_err(|source| RunnerError::Spawn {
+        command: "cargo nextest".into(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// Runs an arbitrary command and parses its stdout with a selectable parser.
+///
+/// Lets the ratchet govern test suites that are not driven by cargo. Like the
+/// nextest runner, the command runs with `TDD_RATCHET=1` so gatekeepers pass.
+#[derive(Debug, Clone)]
+pub struct CommandRunner {
+    program: String,
+    args: Vec<String>,
+    working_dir: PathBuf,
+    parser: OutputParser,
+    deadline: Option<Instant>,
+    handshake: Option<Arc<Handshake>>,
+    target_dir: Option<PathBuf>,
+}
+
+impl CommandRunner {
+    /// `command` is the program followed by its arguments.
+    pub fn new(
+        command: &[String],
+        working_dir: impl Into<PathBuf>,
+        parser: OutputParser,
+    ) -> Result<Self, RunnerError> {
+        let (program, args) = command.split_first().ok_or(RunnerError::EmptyCommand)?;
+        Ok(CommandRunner {
+            program: program.clone(),
+            args: args.to_vec(),
+            working_dir: working_dir.into(),
+            parser,
+            deadline: None,
+            handshake: None,
+            target_dir: None,
+        })
+    }
+
+    /// Hand the tests `handshake`'s nonce, for a gatekeeper to echo.
+    pub fn with_handshake(mut self, handshake: Arc<Handshake>) -> Self {
+        self.handshake = Some(handshake);
+        self
+    }
+
+    /// Kill the command if it is still running at `deadline`; the run then
+    /// fails with [`RunnerError::TimedOut`].
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Run the command with `CARGO_TARGET_DIR` set to `dir`, for commands
+    /// that build with cargo.
+    pub fn with_target_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.target_dir = Some(dir.into());
+        self
+    }
+
+    fn display_command(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl TestRunner for CommandRunner {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        let command = self.display_command();
+        let _span = info_span!("command", %command).entered();
+        info!(dir = %self.working_dir.display(), parser = ?self.parser, "running test command");
+        let mut child = Command::new(&self.program);
+        child
+            .args(&self.args)
+            .current_dir(&self.working_dir)
+            .env("TDD_RATCHET", "1");
+        if let Some(dir) = &self.target_dir {
+            child.env("CARGO_TARGET_DIR", dir);
+        }
+        if let Some(handshake) = &self.handshake {
+            handshake.reset();
+            child.envs(handshake.env());
+        }
+        let mut child = child
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|source| RunnerError::Spawn {
+                command: command.clone(),
+                source,
+            })?;
+        let mut output = String::new();
+        let (status, results) = if self.parser == OutputParser::LibtestJson {
+            // Parse event lines as they arrive rather than holding the
+            // whole output; only other lines are kept, for a compile error.
+            let mut results = Vec::new();
+            let status =
+                read_lines(
+                    &mut child,
+                    &command,
+                    self.deadline,
+                    |line| match parse_nextest_line(&line) {
+                        Some(result) => results.push(result),
+                        None if !line.starts_with('{') => {
+                            output.push_str(&line);
+                            output.push('\n');
+                        }
+                        None => {}
+                    },
+                )?;
+            (status, merge_retries(results))
+        } else {
+            let status = read_lines(&mut child, &command, self.deadline, |line| {
+                output.push_str(&line);
+                output.push('\n');
+            })?;
+            (status, self.parser.parse(&output, &self.working_dir))
+        };
+        info!(%status, results = results.len(), "test command finished");
+        check_compiled(status, &results, &command, output)?;
+        Ok(results)
+    }
+
+    fn gatekeeper_acknowledged(&self) -> Option<bool> {
+        self.handshake
+            .as_ref()
+            .map(|handshake| handshake.acknowledged())
+    }
+}
+
+/// Runs several runners in order and concatenates their results.
+pub struct CompositeRunner<'a> {
+    runners: Vec<Box<dyn TestRunner + 'a>>,
+}
+
+impl<'a> CompositeRunner<'a> {
+    pub fn new(runners: Vec<Box<dyn TestRunner + 'a>>) -> Self {
+        CompositeRunner { runners }
+    }
+}
+
+impl TestRunner for CompositeRunner<'_> {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        let mut results = Vec::new();
+        for runner in &self.runners {
+            results.extend(runner.run()?);
+        }
+        Ok(results)
+    }
+
+    fn run_without_ratchet(&self, test: &str) -> Result<Option<TestOutcome>, RunnerError> {
+        for runner in &self.runners {
+            if let Some(outcome) = runner.run_without_ratchet(test)? {
+                return Ok(Some(outcome));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The runners share one handshake, so any of them can report it.
+    fn gatekeeper_acknowledged(&self) -> Option<bool> {
+        self.runners
+            .iter()
+            .find_map(|runner| runner.gatekeeper_acknowledged())
+    }
+}
+
+/// Retries a runner's whole invocation after an infrastructure failure
+/// ([`RunnerError::Infrastructure`]), up to `retries` times, so a linker
+/// running out of memory does not end the run.
+pub struct RetryingRunner<'a> {
+    runner: Box<dyn TestRunner + 'a>,
+    retries: u32,
+    retried: Cell<u32>,
+}
+
+impl<'a> RetryingRunner<'a> {
+    pub fn new(runner: Box<dyn TestRunner + 'a>, retries: u32) -> Self {
+        RetryingRunner {
+            runner,
+            retries,
+            retried: Cell::new(0),
+        }
+    }
+}
+
+impl TestRunner for RetryingRunner<'_> {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        self.retried.set(0);
+        loop {
+            match self.runner.run() {
+                Err(RunnerError::Infrastructure {
+                    command, reason, ..
+                }) => {
+                    let retried = self.retried.get();
+                    if retried == self.retries {
+                        return Err(RunnerError::Infrastructure {
+                            command,
+                            reason,
+                            retries: retried,
+                        });
+                    }
+                    self.retried.set(retried + 1);
+                    warn!(%command, %reason, retry = retried + 1, "infrastructure failure; retrying");
+                    eprintln!(
+                        "tdd-ratchet: {command} failed outside the tests ({reason}); retrying ({}/{})",
+                        retried + 1,
+                        self.retries
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn run_without_ratchet(&self, test: &str) -> Result<Option<TestOutcome>, RunnerError> {
+        self.runner.run_without_ratchet(test)
+    }
+
+    fn gatekeeper_acknowledged(&self) -> Option<bool> {
+        self.runner.gatekeeper_acknowledged()
+    }
+
+    fn infrastructure_retries(&self) -> u32 {
+        self.retried.get()
+    }
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RunnerError {
+    #[error("runner command is empty")]
+    EmptyCommand,
+    #[error("failed to run {command}: {source}")]
+    Spawn {
+        command: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("failed to wait for {command}: {source}")]
+    Wait {
+        command: String,
+        #[source]
+        source: io::Error,
+    },
+    /// The run hit its `[timeouts] run` deadline and was killed.
+    #[error(
+        "run timed out, results incomplete: {command} was still running at the `[timeouts] run` deadline and was stopped; the status file was not updated"
+    )]
+    TimedOut { command: String },
+    /// The command failed without reporting any test, so the project most
+    /// likely did not compile. `output` holds whatever the command printed
+    /// that was not already shown.
+    #[error(
+        "build failed: {command} exited with {status} before running any test; fix the compile errors above, the status file was not updated"
+    )]
+    CompileFailed {
+        command: String,
+        status: ExitStatus,
+        output: String,
+    },
+    /// The command was killed or failed for reasons outside the code under
+    /// test, such as the linker running out of memory. `retries` counts the
+    /// attempts made before giving up.
+    #[error(
+        "infrastructure failure: {command} failed outside the tests ({reason}) after {retries} {}; the status file was not updated",
+        if *.retries == 1 { "retry" } else { "retries" }
+    )]
+    Infrastructure {
+        command: String,
+        reason: String,
+        retries: u32,
+    },
+    /// `cargo nextest` is not a cargo subcommand on this machine.
+    #[error(
+        "cargo-nextest not found: install it with `cargo install cargo-nextest --locked`, or configure another runner in ratchet.toml; the status file was not updated"
+    )]
+    NextestMissing,
+    /// The nonce files for the gatekeeper handshake could not be written.
+    #[error("failed to set up the gatekeeper handshake: {source}")]
+    Handshake {
+        #[source]
+        source: io::Error,
+    },
+}
@@ -0,0 +1,106 @@
+// SARIF 2.1.0 output (`--format sarif`) for GitHub code scanning and other
+// SARIF consumers.
+//
+// Each violation becomes a result whose `ruleId` is its violation code, so
+// code-scanning alerts link to the same explanations as
+// `cargo ratchet explain`. Violations are `error`s; violations of rules set
+// to `warn` are included as `warning`s. A result points at the test's
+// `#[test]` function when its source was found, and at the status file
+// otherwise, since consumers require every result to have a location. The
+// test file's CODEOWNERS owners, when known, are in the result's
+// `properties.owners`.
+
+use crate::errors::violation_summary;
+use crate::explain::EXPLANATIONS;
+use crate::ratchet::{EvalResult, Violation, Warning};
+use serde_json::{Value, json};
+use std::path::Path;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A SARIF log with one run covering every `(result, status_path)` pair.
+/// `status_path` is the status file's repository-relative path, used as the
+/// location of violations that cannot be traced to a source line.
+pub fn format_sarif<'r>(results: impl IntoIterator<Item = (&'r EvalResult, &'r Path)>) -> String {
+    let mut sarif_results = Vec::new();
+    for (result, status_path) in results {
+        for violation in &result.violations {
+            sarif_results.push(sarif_result(violation, "error", result, status_path));
+        }
+        for warning in &result.warnings {
+            if let Warning::RuleWarning { violation } = warning {
+                sarif_results.push(sarif_result(violation, "warning", result, status_path));
+            }
+        }
+    }
+
+    let rules: Vec<Value> = EXPLANATIONS
+        .iter()
+        .map(|explanation| {
+            json!({
+                "id": explanation.code,
+                "name": explanation.name,
+                "shortDescription": {"text": explanation.summary},
+                "fullDescription": {"text": explanation.rationale},
+                "help": {"text": explanation.remediation.join("\n")},
+                "defaultConfiguration": {"level": "error"},
+            })
+        })
+        .collect();
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "tdd-ratchet",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                },
+            },
+            "results": sarif_results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).expect("SARIF log serializes") + "\n"
+}
+
+fn sarif_result(
+    violation: &Violation,
+    level: &str,
+    result: &EvalResult,
+    status_path: &Path,
+) -> Value {
+    let (rule, subject, detail) = violation_summary(violation);
+    let code = violation.code();
+    let location = result.locations.get(&subject);
+    let (path, line) = match location {
+        Some(location) => (location.path.as_path(), location.line),
+        None => (status_path, 1),
+    };
+    let message = format!("{rule}: {subject}: {detail}");
+    let mut sarif = json!({
+        "ruleId": code,
+        "ruleIndex": EXPLANATIONS.iter().position(|e| e.code == code),
+        "level": level,
+        "message": {"text": message},
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": {"uri": uri(path)},
+                "region": {"startLine": line},
+            },
+        }],
+    });
+    if let Some(location) = location.filter(|location| !location.owners.is_empty()) {
+        sarif["properties"] = json!({"owners": location.owners});
+    }
+    sarif
+}
+
+/// A relative URI for `path`, with `/` separators on every platform.
+fn uri(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
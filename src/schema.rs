@@ -0,0 +1,252 @@
+// JSON Schemas for the status file, embedded in the binary, and validation
+// with line-precise diagnostics.
+
+use crate::status::{CURRENT_VERSION, strip_jsonc};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const SCHEMA_V1: &str = include_str!("../docs/schema/test-status.v1.json");
+pub const SCHEMA_V2: &str = include_str!("../docs/schema/test-status.v2.json");
+pub const SCHEMA_V3: &str = include_str!("../docs/schema/test-status.v3.json");
+
+/// The schema for a status file format version.
+pub fn schema_for_version(version: u32) -> Option<&'static str> {
+    match version {
+        1 => Some(SCHEMA_V1),
+        2 => Some(SCHEMA_V2),
+        3 => Some(SCHEMA_V3),
+        _ => None,
+    }
+}
+
+/// The schema for the format written by this version of tdd-ratchet.
+pub fn current_schema() -> &'static str {
+    schema_for_version(CURRENT_VERSION).expect("current version has a schema")
+}
+
+/// One validation problem, located in the source text (1-based).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    /// JSON pointer to the offending value; empty for the document root.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)?;
+        if !self.pointer.is_empty() {
+            write!(f, " (at {})", self.pointer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate status file contents against the schema for its declared
+/// `version` (files without one are version 1). Comments and trailing
+/// commas are tolerated, as on load. Returns no diagnostics when the file is
+/// valid.
+pub fn validate_status_json(contents: &str) -> Vec<Diagnostic> {
+    let contents = &*strip_jsonc(contents);
+    let instance: Value = match serde_json::from_str(contents) {
+        Ok(value) => value,
+        Err(e) => {
+            return vec![Diagnostic {
+                line: e.line(),
+                column: e.column(),
+                pointer: String::new(),
+                message: format!("invalid JSON: {e}"),
+            }];
+        }
+    };
+    let positions = value_positions(contents);
+    let locate = |pointer: &str, message: String| {
+        let (line, column) = position_of(&positions, pointer);
+        Diagnostic {
+            line,
+            column,
+            pointer: pointer.to_string(),
+            message,
+        }
+    };
+
+    let version = match instance.get("version") {
+        None => 1,
+        Some(v) => match v.as_u64().and_then(|v| u32::try_from(v).ok()) {
+            Some(v) => v,
+            None => return vec![locate("/version", format!("invalid version {v}"))],
+        },
+    };
+    let Some(schema) = schema_for_version(version) else {
+        return vec![locate(
+            "/version",
+            format!(
+                "unsupported status file version {version} (this tdd-ratchet understands up to {CURRENT_VERSION})"
+            ),
+        )];
+    };
+
+    let schema: Value = serde_json::from_str(schema).expect("embedded schema is valid JSON");
+    let validator = jsonschema::validator_for(&schema).expect("embedded schema is a valid schema");
+    let mut diagnostics: Vec<Diagnostic> = validator
+        .iter_errors(&instance)
+        .map(|e| locate(e.instance_path.as_str(), e.to_string()))
+        .collect();
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics
+}
+
+/// Position of `pointer`, falling back to its nearest located ancestor.
+fn position_of(positions: &BTreeMap<String, (usize, usize)>, pointer: &str) -> (usize, usize) {
+    let mut pointer = pointer;
+    loop {
+        if let Some(&position) = positions.get(pointer) {
+            return position;
+        }
+        match pointer.rfind('/') {
+            Some(i) => pointer = &pointer[..i],
+            None => return (1, 1),
+        }
+    }
+}
+
+/// Map every JSON pointer in well-formed `contents` to the line and column
+/// where it starts. Object members are located at their key.
+fn value_positions(contents: &str) -> BTreeMap<String, (usize, usize)> {
+    let mut scanner = Scanner {
+        text: contents,
+        pos: 0,
+        line: 1,
+        column: 1,
+        positions: BTreeMap::new(),
+    };
+    scanner.value(String::new());
+    scanner.positions
+}
+
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+    positions: BTreeMap<String, (usize, usize)>,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn bump(&mut self) {
+        let Some(c) = self.text[self.pos..].chars().next() else {
+            return;
+        };
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn value(&mut self, pointer: String) {
+        self.skip_whitespace();
+        self.positions
+            .entry(pointer.clone())
+            .or_insert((self.line, self.column));
+        match self.peek() {
+            Some(b'{') => self.object(&pointer),
+            Some(b'[') => self.array(&pointer),
+            Some(b'"') => {
+                self.string();
+            }
+            _ => {
+                while self
+                    .peek()
+                    .is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace())
+                {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn object(&mut self, pointer: &str) {
+        self.bump();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'"') => {}
+                Some(b'}') | None => {
+                    self.bump();
+                    return;
+                }
+                Some(_) => {
+                    self.bump();
+                    continue;
+                }
+            }
+            let position = (self.line, self.column);
+            let key = self.string();
+            let child = format!("{pointer}/{}", escape_pointer_segment(&key));
+            self.positions.insert(child.clone(), position);
+            self.skip_whitespace();
+            if self.peek() == Some(b':') {
+                self.bump();
+            }
+            self.value(child);
+            self.skip_whitespace();
+            if self.peek() == Some(b',') {
+                self.bump();
+            }
+        }
+    }
+
+    fn array(&mut self, pointer: &str) {
+        self.bump();
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b']') | None => {
+                    self.bump();
+                    return;
+                }
+                Some(b',') => self.bump(),
+                Some(_) => {
+                    self.value(format!("{pointer}/{index}"));
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Consume a string literal and return its decoded value.
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        self.bump();
+        while let Some(b) = self.peek() {
+            self.bump();
+            match b {
+                b'\\' => self.bump(),
+                b'"' => break,
+                _ => {}
+            }
+        }
+        serde_json::from_str(&self.text[start..self.pos]).unwrap_or_default()
+    }
+}
+
+fn escape_pointer_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,238 @@
+// `cargo ratchet self update [--check]`: replace a globally installed binary
+// with the latest GitHub release.
+//
+// The release metadata and assets are fetched with `curl`. Each release
+// publishes one binary per platform, `cargo-ratchet-<arch>-<os>`, and a
+// `SHA256SUMS` file; a downloaded binary is only installed when its SHA-256
+// matches the one listed there. `SHA256SUMS` comes from the same release as
+// the binary, so the check catches a corrupted or truncated download, not a
+// tampered release. The new binary is written next to the running one and
+// renamed over it, so an interrupted update leaves the old binary in place.
+
+use crate::error::RatchetError;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// The GitHub API endpoint for the latest release.
+pub const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/maxeonyx/tdd-ratchet-rs/releases/latest";
+
+/// Environment variable that points the update at another URL serving the
+/// same release JSON, e.g. an internal mirror.
+pub const RELEASE_URL_ENV: &str = "TDD_RATCHET_RELEASE_URL";
+
+/// The release asset listing each binary's SHA-256.
+pub const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SelfUpdateError {
+    #[error("failed to download {url}: {reason}")]
+    Download { url: String, reason: String },
+    #[error("unexpected release metadata from {url}: {source}")]
+    Release {
+        url: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("release {version} has no {asset} asset")]
+    NoAsset { version: String, asset: String },
+    /// The release publishes no checksum for the binary, so it cannot be
+    /// verified and is not installed.
+    #[error(
+        "release {version} lists no SHA-256 for {asset} in {CHECKSUMS_ASSET}; not installing it"
+    )]
+    NoChecksum { version: String, asset: String },
+    #[error(
+        "{asset} does not match its SHA-256 in {CHECKSUMS_ASSET} (expected {expected}, got {actual}); not installing it"
+    )]
+    ChecksumMismatch {
+        asset: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// A GitHub release, as returned by the releases API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// The release's version: its tag without a leading `v`.
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// The name of the release binary for the platform this binary was built
+/// for, e.g. `cargo-ratchet-x86_64-linux`.
+pub fn binary_asset_name() -> String {
+    format!(
+        "cargo-ratchet-{}-{}{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// Whether version `candidate` is newer than `current`. Versions compare by
+/// their numeric `major.minor.patch`; a pre-release (`1.0.0-rc.1`) is older
+/// than the release it precedes. Pure function — no IO.
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    fn key(version: &str) -> (Vec<u64>, bool) {
+        let (core, released) = match version.split_once('-') {
+            Some((core, _)) => (core, false),
+            None => (version, true),
+        };
+        let numbers = core
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect();
+        (numbers, released)
+    }
+    key(candidate) > key(current)
+}
+
+/// The SHA-256 listed for `asset` in a `sha256sum`-style file: one
+/// `<hex>  <name>` line per file, `*<name>` for binary mode. Pure function —
+/// no IO.
+pub fn expected_checksum<'a>(sums: &'a str, asset: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        let name = name.trim_start();
+        (name.strip_prefix('*').unwrap_or(name) == asset).then_some(hash)
+    })
+}
+
+/// The lowercase hex SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Check the downloaded `asset` of release `version` against `sums`.
+/// Pure function — no IO.
+pub fn verify_checksum(
+    version: &str,
+    asset: &str,
+    bytes: &[u8],
+    sums: &str,
+) -> Result<(), SelfUpdateError> {
+    let expected = expected_checksum(sums, asset).ok_or_else(|| SelfUpdateError::NoChecksum {
+        version: version.to_string(),
+        asset: asset.to_string(),
+    })?;
+    let actual = sha256_hex(bytes);
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(SelfUpdateError::ChecksumMismatch {
+            asset: asset.to_string(),
+            expected: expected.to_string(),
+            actual,
+        })
+    }
+}
+
+/// Fetch the latest release's metadata from GitHub, or from
+/// `$TDD_RATCHET_RELEASE_URL` when set.
+pub fn latest_release() -> Result<Release, RatchetError> {
+    let url = std::env::var(RELEASE_URL_ENV).unwrap_or_else(|_| LATEST_RELEASE_URL.to_string());
+    let body = download(&url)?;
+    Ok(serde_json::from_slice(&body).map_err(|source| SelfUpdateError::Release { url, source })?)
+}
+
+/// Download this platform's binary from `release`, verify it against the
+/// release's checksums, and install it over the binary at `exe`.
+///
+/// The checksums are published alongside the binary, so this guards against
+/// a broken download only; anyone who can change the release can change both.
+pub fn update(release: &Release, exe: &Path) -> Result<(), RatchetError> {
+    let name = binary_asset_name();
+    let missing = |asset: &str| SelfUpdateError::NoAsset {
+        version: release.version().to_string(),
+        asset: asset.to_string(),
+    };
+    let binary = release.asset(&name).ok_or_else(|| missing(&name))?;
+    let sums = release
+        .asset(CHECKSUMS_ASSET)
+        .ok_or_else(|| missing(CHECKSUMS_ASSET))?;
+    let bytes = download(&binary.browser_download_url)?;
+    let sums = download(&sums.browser_download_url)?;
+    verify_checksum(
+        release.version(),
+        &name,
+        &bytes,
+        &String::from_utf8_lossy(&sums),
+    )?;
+    install(&bytes, exe)
+}
+
+/// Replace the binary at `exe` with `bytes`. The new binary is written to a
+/// temporary file in the same directory and renamed into place.
+pub fn install(bytes: &[u8], exe: &Path) -> Result<(), RatchetError> {
+    let staged = sibling(exe, "new");
+    fs::write(&staged, bytes).map_err(|e| RatchetError::io(&staged, e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))
+            .map_err(|e| RatchetError::io(&staged, e))?;
+    }
+    // Windows cannot overwrite a running executable, but can rename it.
+    #[cfg(windows)]
+    {
+        let old = sibling(exe, "old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old).map_err(|e| RatchetError::io(exe, e))?;
+    }
+    fs::rename(&staged, exe).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        RatchetError::io(exe, e)
+    })
+}
+
+fn sibling(exe: &Path, extension: &str) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{extension}"));
+    exe.with_file_name(name)
+}
+
+fn download(url: &str) -> Result<Vec<u8>, SelfUpdateError> {
+    let failed = |reason: String| SelfUpdateError::Download {
+        url: url.to_string(),
+        reason,
+    };
+    let output = Command::new("curl")
+        .args(["-fsSL", "--", url])
+        .output()
+        .map_err(|e| failed(format!("cannot run curl: {e}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(failed(match stderr.trim() {
+            "" => format!("curl exited with {}", output.status),
+            message => message.to_string(),
+        }));
+    }
+    Ok(output.stdout)
+}
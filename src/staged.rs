@@ -0,0 +1,70 @@
+// Staged-changes evaluation: a temporary checkout of the index, so a
+// pre-commit hook checks exactly what is about to be committed.
+
+use crate::error::RatchetError;
+use git2::build::CheckoutBuilder;
+use git2::{Repository, Tree};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The staged tree of a repository, checked out into a temporary
+/// directory. Unstaged edits and untracked files are left behind. The
+/// directory is removed on drop.
+#[derive(Debug)]
+pub struct StagedTree {
+    dir: PathBuf,
+}
+
+impl StagedTree {
+    /// Check out the index of the repository at `repo_path`. The index and
+    /// the working tree are not touched.
+    pub fn checkout(repo_path: &Path) -> Result<Self, RatchetError> {
+        let repo = Repository::open(repo_path)?;
+        let tree_id = repo.index()?.write_tree()?;
+        let tree = repo.find_tree(tree_id)?;
+        Ok(StagedTree {
+            dir: checkout_into_temp(&repo, &tree, "staged")?,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for StagedTree {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Check out `tree` into a new `tdd-ratchet-<kind>-*` temporary directory,
+/// without touching the repository's index or working tree. The caller
+/// removes the directory.
+pub(crate) fn checkout_into_temp(
+    repo: &Repository,
+    tree: &Tree,
+    kind: &str,
+) -> Result<PathBuf, RatchetError> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let dir = std::env::temp_dir().join(format!("tdd-ratchet-{kind}-{}-{nanos}", process::id()));
+    fs::create_dir_all(&dir)
+        .map_err(|e| git2::Error::from_str(&format!("cannot create {}: {e}", dir.display())))?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout
+        .target_dir(&dir)
+        .update_index(false)
+        .force()
+        .recreate_missing(true);
+    if let Err(e) = repo.checkout_tree(tree.as_object(), Some(&mut checkout)) {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(e.into());
+    }
+    Ok(dir)
+}
@@ -0,0 +1,326 @@
+// `cargo ratchet report --stale`: a digest of state that needs a human,
+// meant to run from a cron job and be posted to chat or mailed.
+//
+// It lists pending tests nobody has promoted for a while, tests quarantined
+// as flaky, and accepted failures that have expired or are about to. With
+// nothing to report the text formats print nothing, so cron sends no mail.
+
+use crate::accepted_failures::commits_since_acceptance;
+use crate::history::HistorySnapshot;
+use crate::status::{StatusFile, TestState};
+use crate::timeline::format_date;
+use serde::Serialize;
+
+/// Days a test may stay pending before it is reported, unless
+/// `--older-than` says otherwise.
+pub const DEFAULT_OLDER_THAN_DAYS: u64 = 14;
+
+/// Days ahead of a dated acceptance's expiry to start reporting it, unless
+/// `--expiring-within` says otherwise.
+pub const DEFAULT_EXPIRING_WITHIN_DAYS: u64 = 7;
+
+/// Commits left on an acceptance at which it is reported as expiring.
+pub const EXPIRING_WITHIN_COMMITS: u32 = 3;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Thresholds for [`stale_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleOptions {
+    pub older_than_days: u64,
+    pub expiring_within_days: u64,
+}
+
+impl Default for StaleOptions {
+    fn default() -> Self {
+        StaleOptions {
+            older_than_days: DEFAULT_OLDER_THAN_DAYS,
+            expiring_within_days: DEFAULT_EXPIRING_WITHIN_DAYS,
+        }
+    }
+}
+
+/// Output format for `cargo ratchet report --stale`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StaleFormat {
+    /// Plain text, for email (the default).
+    #[default]
+    Text,
+    /// Markdown, for chat.
+    Markdown,
+    /// The report as a JSON object.
+    Json,
+}
+
+impl StaleFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(StaleFormat::Text),
+            "markdown" => Some(StaleFormat::Markdown),
+            "json" => Some(StaleFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Everything stale in a status file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaleReport {
+    /// The report's date, `YYYY-MM-DD` in UTC.
+    pub date: String,
+    pub older_than_days: u64,
+    pub expiring_within_days: u64,
+    /// Pending tests older than the threshold, oldest first.
+    pub pending: Vec<StalePending>,
+    pub quarantined: Vec<StaleQuarantine>,
+    pub expiring: Vec<ExpiringAcceptance>,
+}
+
+impl StaleReport {
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty() && self.quarantined.is_empty() && self.expiring.is_empty()
+    }
+}
+
+/// A test pending for longer than the threshold.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StalePending {
+    pub test: String,
+    pub age_days: i64,
+}
+
+/// A test quarantined as flaky.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaleQuarantine {
+    pub test: String,
+    pub flips: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+/// An accepted failure that has expired or is about to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExpiringAcceptance {
+    pub test: String,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Commits still allowed to fail, for an acceptance counted in commits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commits_left: Option<u32>,
+    pub expired: bool,
+}
+
+/// Collect the stale state of `status` at `now` (Unix seconds). Pure function
+/// — no IO.
+///
+/// A pending test's age runs from its last state change, or from when it was
+/// added if it never changed. Commits on an acceptance are counted through
+/// `snapshots` the same way the ratchet counts them; an acceptance without
+/// any expiry is reported as expired.
+pub fn stale_report(
+    status: &StatusFile,
+    snapshots: &[HistorySnapshot],
+    now: i64,
+    options: StaleOptions,
+) -> StaleReport {
+    let threshold = i64::try_from(options.older_than_days)
+        .unwrap_or(i64::MAX)
+        .saturating_mul(SECONDS_PER_DAY);
+    let mut pending: Vec<StalePending> = status
+        .tests
+        .iter()
+        .filter(|(_, entry)| entry.state() == TestState::Pending)
+        .filter_map(|(test, entry)| {
+            let since = entry.changed_at.or(entry.added_at)?;
+            let age = now - since;
+            (age > threshold).then(|| StalePending {
+                test: test.clone(),
+                age_days: age / SECONDS_PER_DAY,
+            })
+        })
+        .collect();
+    pending.sort_by(|a, b| b.age_days.cmp(&a.age_days).then(a.test.cmp(&b.test)));
+
+    let quarantined = status
+        .tests
+        .iter()
+        .filter_map(|(test, entry)| {
+            let quarantine = entry.quarantine.as_ref()?;
+            Some(StaleQuarantine {
+                test: test.clone(),
+                flips: quarantine.flips,
+                commit: quarantine.commit.clone(),
+            })
+        })
+        .collect();
+
+    let today = format_date(now);
+    let horizon = format_date(
+        now.saturating_add(
+            i64::try_from(options.expiring_within_days)
+                .unwrap_or(i64::MAX)
+                .saturating_mul(SECONDS_PER_DAY),
+        ),
+    );
+    let expiring = status
+        .tests
+        .iter()
+        .filter_map(|(test, entry)| {
+            let acceptance = entry.accepted_failure.as_ref()?;
+            let (commits_left, expired) = match (&acceptance.until, acceptance.commits) {
+                (Some(until), _) if until.as_str() > horizon.as_str() => return None,
+                (Some(until), _) => (None, until.as_str() < today.as_str()),
+                (None, Some(commits)) => {
+                    let used = commits_since_acceptance(test, acceptance, snapshots);
+                    let left = (commits as usize).checked_sub(used);
+                    if left.is_some_and(|left| left > EXPIRING_WITHIN_COMMITS as usize) {
+                        return None;
+                    }
+                    (Some(left.unwrap_or(0) as u32), left.is_none())
+                }
+                (None, None) => (None, true),
+            };
+            Some(ExpiringAcceptance {
+                test: test.clone(),
+                reason: acceptance.reason.clone(),
+                until: acceptance.until.clone(),
+                commits_left,
+                expired,
+            })
+        })
+        .collect();
+
+    StaleReport {
+        date: today,
+        older_than_days: options.older_than_days,
+        expiring_within_days: options.expiring_within_days,
+        pending,
+        quarantined,
+        expiring,
+    }
+}
+
+/// Render the report as plain text, or nothing when it is empty.
+pub fn format_stale_text(report: &StaleReport) -> String {
+    if report.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("tdd-ratchet stale report ({})\n", report.date);
+    if !report.pending.is_empty() {
+        out.push_str(&format!(
+            "\nPending for more than {} days ({}):\n",
+            report.older_than_days,
+            report.pending.len()
+        ));
+        for pending in &report.pending {
+            out.push_str(&format!("  - {}: {}\n", pending.test, pending_age(pending)));
+        }
+    }
+    if !report.quarantined.is_empty() {
+        out.push_str(&format!(
+            "\nQuarantined as flaky ({}):\n",
+            report.quarantined.len()
+        ));
+        for quarantine in &report.quarantined {
+            out.push_str(&format!(
+                "  - {}: {}\n",
+                quarantine.test,
+                quarantine_detail(quarantine)
+            ));
+        }
+    }
+    if !report.expiring.is_empty() {
+        out.push_str(&format!(
+            "\nAccepted failures expiring within {} days or {EXPIRING_WITHIN_COMMITS} commits ({}):\n",
+            report.expiring_within_days,
+            report.expiring.len()
+        ));
+        for acceptance in &report.expiring {
+            out.push_str(&format!(
+                "  - {}: {} ({})\n",
+                acceptance.test,
+                acceptance.reason,
+                expiry_detail(acceptance)
+            ));
+        }
+    }
+    out
+}
+
+/// Render the report as Markdown, or nothing when it is empty.
+pub fn format_stale_markdown(report: &StaleReport) -> String {
+    if report.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("**tdd-ratchet stale report** ({})\n", report.date);
+    if !report.pending.is_empty() {
+        out.push_str(&format!(
+            "\n**Pending for more than {} days** ({})\n",
+            report.older_than_days,
+            report.pending.len()
+        ));
+        for pending in &report.pending {
+            out.push_str(&format!(
+                "- `{}` — {}\n",
+                pending.test,
+                pending_age(pending)
+            ));
+        }
+    }
+    if !report.quarantined.is_empty() {
+        out.push_str(&format!(
+            "\n**Quarantined as flaky** ({})\n",
+            report.quarantined.len()
+        ));
+        for quarantine in &report.quarantined {
+            out.push_str(&format!(
+                "- `{}` — {}\n",
+                quarantine.test,
+                quarantine_detail(quarantine)
+            ));
+        }
+    }
+    if !report.expiring.is_empty() {
+        out.push_str(&format!(
+            "\n**Accepted failures expiring within {} days or {EXPIRING_WITHIN_COMMITS} commits** ({})\n",
+            report.expiring_within_days,
+            report.expiring.len()
+        ));
+        for acceptance in &report.expiring {
+            out.push_str(&format!(
+                "- `{}` — {} ({})\n",
+                acceptance.test,
+                acceptance.reason,
+                expiry_detail(acceptance)
+            ));
+        }
+    }
+    out
+}
+
+fn pending_age(pending: &StalePending) -> String {
+    format!("pending for {} days", pending.age_days)
+}
+
+fn quarantine_detail(quarantine: &StaleQuarantine) -> String {
+    match &quarantine.commit {
+        Some(commit) => format!(
+            "{} flips at {}",
+            quarantine.flips,
+            &commit[..8.min(commit.len())]
+        ),
+        None => format!("{} flips", quarantine.flips),
+    }
+}
+
+fn expiry_detail(acceptance: &ExpiringAcceptance) -> String {
+    match (&acceptance.until, acceptance.commits_left) {
+        (Some(until), _) if acceptance.expired => format!("expired after {until}"),
+        (Some(until), _) => format!("until {until}"),
+        (None, _) if acceptance.expired => "expired".to_string(),
+        (None, Some(1)) => "1 commit left".to_string(),
+        (None, Some(left)) => format!("{left} commits left"),
+        (None, None) => "expired".to_string(),
+    }
+}
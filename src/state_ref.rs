@@ -0,0 +1,199 @@
+// Ratchet state kept in a dedicated git ref (`refs/ratchet/state`) instead of
+// a tracked file, selected with `[state] storage = "ref"`.
+//
+// Each save appends a commit to the ref whose tree holds the status file at
+// its usual path, so history checks walk the ref's log exactly as they walk
+// `HEAD` in file mode. The working tree never changes and code merges never
+// conflict on the status file.
+
+use crate::error::RatchetError;
+use crate::history::{GitHistory, HistoryProvider, HistorySnapshot};
+use crate::status::{STATUS_FILE_NAME, StatusFile, StatusStore, WorkingTreeInstructions};
+use git2::build::TreeUpdateBuilder;
+use git2::{FileMode, Repository, Signature};
+use std::path::{Path, PathBuf};
+
+/// The ref that holds the ratchet state.
+pub const STATE_REF: &str = "refs/ratchet/state";
+
+/// Message of the commits the ratchet appends to the state ref.
+const STATE_COMMIT_MESSAGE: &str = "tdd-ratchet: update state";
+
+/// History of the status file along the state ref. A missing ref has no
+/// history yet.
+#[derive(Debug, Clone)]
+pub struct StateRefHistory {
+    repo_path: PathBuf,
+    status_path: PathBuf,
+    reference: String,
+}
+
+impl StateRefHistory {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        StateRefHistory {
+            repo_path: repo_path.into(),
+            status_path: PathBuf::from(STATUS_FILE_NAME),
+            reference: STATE_REF.to_string(),
+        }
+    }
+
+    /// Read the status file at `status_path` within the state tree instead
+    /// of `.test-status.json`.
+    pub fn with_status_path(mut self, status_path: impl Into<PathBuf>) -> Self {
+        self.status_path = status_path.into();
+        self
+    }
+
+    /// Use `reference` instead of `refs/ratchet/state`.
+    pub fn with_reference(mut self, reference: impl Into<String>) -> Self {
+        self.reference = reference.into();
+        self
+    }
+
+    /// The git history along the ref, or `None` before the first save.
+    fn git_history(&self) -> Result<Option<GitHistory>, git2::Error> {
+        let repo = Repository::open(&self.repo_path)?;
+        if repo.find_reference(&self.reference).is_err() {
+            return Ok(None);
+        }
+        Ok(Some(
+            GitHistory::new(&self.repo_path)
+                .with_status_path(&self.status_path)
+                .with_start(&self.reference),
+        ))
+    }
+}
+
+impl HistoryProvider for StateRefHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        match self.git_history()? {
+            Some(history) => history.head_status(),
+            None => Ok(None),
+        }
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        match self.git_history()? {
+            Some(history) => history.snapshots(),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Status store that appends a commit to the state ref on every change.
+#[derive(Debug, Clone)]
+pub struct RefStatusStore {
+    history: StateRefHistory,
+}
+
+impl RefStatusStore {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        RefStatusStore {
+            history: StateRefHistory::new(repo_path),
+        }
+    }
+
+    /// Store the status file at `status_path` within the state tree.
+    pub fn with_status_path(mut self, status_path: impl Into<PathBuf>) -> Self {
+        self.history = self.history.with_status_path(status_path);
+        self
+    }
+
+    /// Use `reference` instead of `refs/ratchet/state`.
+    pub fn with_reference(mut self, reference: impl Into<String>) -> Self {
+        self.history = self.history.with_reference(reference);
+        self
+    }
+
+    /// The status at the tip of the ref, if any.
+    pub fn load(&self) -> Result<Option<StatusFile>, RatchetError> {
+        self.history.head_status().map_err(|e| self.error(e))
+    }
+
+    /// `error` with a git failure attributed to the state ref.
+    fn error(&self, error: RatchetError) -> RatchetError {
+        match error {
+            RatchetError::Git(source) => RatchetError::StateRef {
+                reference: self.history.reference.clone(),
+                source,
+            },
+            other => other,
+        }
+    }
+}
+
+impl StatusStore for RefStatusStore {
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError> {
+        Ok(self
+            .load()?
+            .map(|status| status.working_tree_instructions())
+            .unwrap_or_default())
+    }
+
+    /// Unchanged state is not committed again, even though its seal now
+    /// chains from the tip: otherwise every run would add a commit.
+    fn save(&self, status: &StatusFile) -> Result<(), RatchetError> {
+        if self.load()?.is_some_and(|tip| {
+            tip.tests == status.tests
+                && tip.renames == status.renames
+                && tip.baseline == status.baseline
+        }) {
+            return Ok(());
+        }
+        let contents = status.to_json(&self.history.status_path)?;
+        write_state(
+            &self.history.repo_path,
+            &self.history.reference,
+            &self.history.status_path,
+            &contents,
+        )
+        .map(|_| ())
+        .map_err(|e| self.error(e))
+    }
+}
+
+/// Commit `contents` as the file at `status_path` onto `reference`, keeping
+/// the rest of the tip's tree. Returns whether a commit was made: unchanged
+/// contents leave the ref alone.
+pub fn write_state(
+    repo_path: &Path,
+    reference: &str,
+    status_path: &Path,
+    contents: &str,
+) -> Result<bool, RatchetError> {
+    let repo = Repository::open(repo_path)?;
+    let parent = match repo.find_reference(reference) {
+        Ok(tip) => Some(tip.peel_to_commit()?),
+        Err(_) => None,
+    };
+    let base_tree = match &parent {
+        Some(commit) => commit.tree()?,
+        None => repo.find_tree(repo.treebuilder(None)?.write()?)?,
+    };
+
+    let blob = repo.blob(contents.as_bytes())?;
+    if base_tree
+        .get_path(status_path)
+        .is_ok_and(|entry| entry.id() == blob)
+    {
+        return Ok(false);
+    }
+    let tree_id = TreeUpdateBuilder::new()
+        .upsert(status_path, blob, FileMode::Blob)
+        .create_updated(&repo, &base_tree)?;
+    let tree = repo.find_tree(tree_id)?;
+
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("tdd-ratchet", "tdd-ratchet@localhost"))?;
+    let parents: Vec<&git2::Commit<'_>> = parent.iter().collect();
+    repo.commit(
+        Some(reference),
+        &signature,
+        &signature,
+        STATE_COMMIT_MESSAGE,
+        &tree,
+        &parents,
+    )?;
+    Ok(true)
+}
@@ -0,0 +1,167 @@
+// TDD hygiene metrics computed from the committed status history.
+
+use crate::audit::AuditRecord;
+use crate::history::HistorySnapshot;
+use crate::status::TestState;
+use crate::timeline::{short_commit, state_changes};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const SECONDS_PER_WEEK: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+/// Aggregate metrics describing how closely a project follows the ratchet's
+/// pending → passing workflow.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HygieneStats {
+    /// Number of committed status snapshots in history.
+    pub snapshots: usize,
+    /// Tests tracked in the latest snapshot.
+    pub tracked: usize,
+    /// Tests currently tracked as pending.
+    pub pending_backlog: usize,
+    /// Tests that entered the status file as passing via the first snapshot
+    /// or a per-test baseline, without going through pending.
+    pub grandfathered: usize,
+    /// Observed pending → passing transitions across history.
+    pub promotions: usize,
+    /// Mean number of commits a test spent pending before its promotion.
+    pub average_commits_to_promotion: Option<f64>,
+    /// Promotions divided by the number of weeks the history spans (at least
+    /// one week).
+    pub promotions_per_week: f64,
+    /// Violations suppressed by `Ratchet-Override` trailers, from the audit
+    /// file.
+    pub overrides: Vec<AuditRecord>,
+}
+
+/// Compute hygiene metrics from history snapshots (oldest first). Pure
+/// function — no IO.
+pub fn compute_stats(snapshots: &[HistorySnapshot]) -> HygieneStats {
+    compute_stats_with_audit(snapshots, &[])
+}
+
+/// [`compute_stats`], also surfacing the overrides recorded in the audit
+/// file.
+pub fn compute_stats_with_audit(
+    snapshots: &[HistorySnapshot],
+    audit: &[AuditRecord],
+) -> HygieneStats {
+    let latest = snapshots.last();
+
+    let tracked = latest.map_or(0, |s| s.status.tests.len());
+    let pending_backlog = latest.map_or(0, |s| {
+        s.status
+            .tests
+            .values()
+            .filter(|e| e.state() == TestState::Pending)
+            .count()
+    });
+
+    let grandfathered = latest.map_or(0, |latest| {
+        latest
+            .status
+            .tests
+            .iter()
+            .filter(|(name, entry)| {
+                entry.baseline().is_some()
+                    || snapshots.first().is_some_and(|first| {
+                        first
+                            .status
+                            .tests
+                            .get(*name)
+                            .is_some_and(|e| e.state() == TestState::Passing)
+                    })
+            })
+            .count()
+    });
+
+    // Index of the snapshot where each test's current pending streak began.
+    let mut pending_since: BTreeMap<String, usize> = BTreeMap::new();
+    let mut commits_to_promotion = Vec::new();
+    for change in state_changes(snapshots) {
+        if let Some(old) = &change.renamed_from
+            && let Some(start) = pending_since.remove(old)
+        {
+            pending_since.insert(change.test.clone(), start);
+        }
+        if change.is_promotion() {
+            if let Some(start) = pending_since.remove(&change.test) {
+                commits_to_promotion.push(change.snapshot_index - start);
+            }
+        } else if change.to == Some(TestState::Pending) {
+            pending_since
+                .entry(change.test)
+                .or_insert(change.snapshot_index);
+        } else {
+            pending_since.remove(&change.test);
+        }
+    }
+
+    let promotions = commits_to_promotion.len();
+    let average_commits_to_promotion = (promotions > 0)
+        .then(|| commits_to_promotion.iter().sum::<usize>() as f64 / promotions as f64);
+
+    let span_seconds = match (snapshots.first(), latest) {
+        (Some(first), Some(last)) => (last.info.time - first.info.time).max(0) as f64,
+        _ => 0.0,
+    };
+    let weeks = (span_seconds / SECONDS_PER_WEEK).max(1.0);
+
+    HygieneStats {
+        snapshots: snapshots.len(),
+        tracked,
+        pending_backlog,
+        grandfathered,
+        promotions,
+        average_commits_to_promotion,
+        promotions_per_week: promotions as f64 / weeks,
+        overrides: audit
+            .iter()
+            .filter(|r| matches!(r, AuditRecord::Override { .. }))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Render the metrics as human-readable text.
+pub fn format_stats(stats: &HygieneStats) -> String {
+    let average = stats
+        .average_commits_to_promotion
+        .map_or_else(|| "n/a".to_string(), |avg| format!("{avg:.1}"));
+    let mut out = format!(
+        "tdd-ratchet stats\n\
+         \x20 snapshots in history:         {}\n\
+         \x20 tracked tests:                {}\n\
+         \x20 pending backlog:              {}\n\
+         \x20 grandfathered tests:          {}\n\
+         \x20 promotions:                   {}\n\
+         \x20 avg commits pending→passing:  {}\n\
+         \x20 promotions per week:          {:.2}\n\
+         \x20 overrides used:               {}\n",
+        stats.snapshots,
+        stats.tracked,
+        stats.pending_backlog,
+        stats.grandfathered,
+        stats.promotions,
+        average,
+        stats.promotions_per_week,
+        stats.overrides.len(),
+    );
+    for record in &stats.overrides {
+        let AuditRecord::Override {
+            commit,
+            test,
+            rule,
+            reason,
+            ..
+        } = record
+        else {
+            continue;
+        };
+        out.push_str(&format!(
+            "    {} {rule} {test}: {reason}\n",
+            short_commit(commit)
+        ));
+    }
+    out
+}
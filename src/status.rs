@@ -1,18 +1,41 @@
 // Status file: tracks per-test expected states in .test-status.json
 
+use crate::error::{ParseError, RatchetError};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub const SCHEMA_URL: &str = "https://tdd-ratchet.maxeonyx.com/schema/test-status.v1.json";
+pub const SCHEMA_URL: &str = "https://tdd-ratchet.maxeonyx.com/schema/test-status.v3.json";
+
+/// The status file format version written by [`StatusFile::save`].
+pub const CURRENT_VERSION: u32 = 3;
+
+/// Name of the status file, at the project root or a configured ratchet root.
+pub const STATUS_FILE_NAME: &str = ".test-status.json";
+
+/// Name of the status file tracking the tests of one compilation target,
+/// with `[state] per-target`, e.g. `.test-status.wasm32-unknown-unknown.json`.
+pub fn target_status_file_name(target: &str) -> String {
+    format!(".test-status.{target}.json")
+}
+
+/// Whether `name` is the status file or a per-target one.
+pub fn is_status_file_name(name: &str) -> bool {
+    name == STATUS_FILE_NAME || (name.starts_with(".test-status.") && name.ends_with(".json"))
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TestState {
     Pending,
     Passing,
+    /// A skeleton whose failure means nothing yet, such as a `todo!()` body.
+    /// Not counted as pending until promoted.
+    Draft,
 }
 
 impl fmt::Display for TestState {
@@ -20,50 +43,292 @@ impl fmt::Display for TestState {
         match self {
             TestState::Pending => write!(f, "pending"),
             TestState::Passing => write!(f, "passing"),
+            TestState::Draft => write!(f, "draft"),
         }
     }
 }
 
-/// A test entry in the status file. Either a bare state string or an object
-/// with state + per-test baseline for grandfathering.
+/// A test entry in the status file.
+///
+/// Version 2 files always write the object form; the bare state string of
+/// version 1 is still accepted on load.
 ///
 /// JSON forms:
 ///   "passing"
-///   { "state": "passing", "baseline": "abc123..." }
+///   { "state": "passing", "baseline": "abc123...", "added_at": 1700000000 }
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "EntryRepr")]
+pub struct TestEntry {
+    pub state: TestState,
+    /// Commit at which this test is grandfathered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<String>,
+    /// Unix time the test was first tracked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_at: Option<i64>,
+    /// Unix time the test last changed state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_at: Option<i64>,
+    /// Set by `cargo ratchet allow-ignore`: this passing test may be
+    /// `#[ignore]`d. Cleared once the test runs again.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub allow_ignore: bool,
+    /// Runs in which this test passed only on a retry.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub flakes: u32,
+    /// The commit that promoted this test to passing, recorded by the first
+    /// run after the promotion is committed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promoted: Option<Promotion>,
+    /// Set by `cargo ratchet accept-failure`: failures of this passing test
+    /// are tolerated until the acceptance expires. Cleared once it passes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted_failure: Option<AcceptedFailure>,
+    /// Set when the test kept flipping between passing and failing on the
+    /// same source: its failures are tolerated until `cargo ratchet
+    /// unquarantine` clears it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantine: Option<Quarantine>,
+    /// Free-form annotations; preserved across runs.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// The test's most recent committed state changes, oldest first. Kept
+    /// when `[state] transition-history` is set, so `log` and `blame` work
+    /// without the history that recorded them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<Transition>,
+    /// Milliseconds the test took in the latest run that timed it. Kept
+    /// when `[timing] enabled` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec_time_ms: Option<u64>,
+    /// The toolchain of the run that last changed the test's state. Kept
+    /// when `[state] toolchain` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<Toolchain>,
+}
+
+/// Where a test was promoted from pending to passing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Promotion {
+    pub commit: String,
+    pub author: String,
+    /// Commit time, Unix seconds.
+    pub time: i64,
+}
+
+/// A committed change in a test's state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    /// State before the change; left out when the change added the test.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<TestState>,
+    pub state: TestState,
+    pub commit: String,
+    pub author: String,
+    /// Commit time, Unix seconds.
+    pub time: i64,
+    /// The toolchain of the run that made the change, when recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<Toolchain>,
+}
+
+/// The toolchain a run built and ran the tests with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Toolchain {
+    /// `rustc` release, e.g. `1.86.0`.
+    pub rustc: String,
+    /// `cargo-nextest` version; absent when the tests ran without nextest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nextest: Option<String>,
+    /// Target triple the tests were built for.
+    pub target: String,
+}
+
+impl fmt::Display for Toolchain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rustc {}", self.rustc)?;
+        if let Some(nextest) = &self.nextest {
+            write!(f, ", nextest {nextest}")?;
+        }
+        write!(f, ", {}", self.target)
+    }
+}
+
+/// A known failure shipped on purpose, tolerated until it expires. Exactly
+/// one of `until` and `commits` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptedFailure {
+    pub reason: String,
+    /// Last day, `YYYY-MM-DD` in UTC, of the commits that may still fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// How many commits after the one recording the acceptance may still
+    /// fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commits: Option<u32>,
+}
+
+impl fmt::Display for AcceptedFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)?;
+        match (&self.until, self.commits) {
+            (Some(until), _) => write!(f, " (until {until})"),
+            (None, Some(1)) => write!(f, " (for 1 commit)"),
+            (None, Some(commits)) => write!(f, " (for {commits} commits)"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Why a test was quarantined as flaky.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quarantine {
+    /// Consecutive flips between passing and failing that triggered it.
+    pub flips: u32,
+    /// Commit the flips were observed at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+#[derive(Deserialize)]
 #[serde(untagged)]
-pub enum TestEntry {
-    Simple(TestState),
-    WithBaseline { state: TestState, baseline: String },
+enum EntryRepr {
+    State(TestState),
+    Record(Box<EntryRecord>),
+}
+
+/// The object form of an entry.
+#[derive(Deserialize)]
+struct EntryRecord {
+    state: TestState,
+    #[serde(default)]
+    baseline: Option<String>,
+    #[serde(default)]
+    added_at: Option<i64>,
+    #[serde(default)]
+    changed_at: Option<i64>,
+    #[serde(default)]
+    allow_ignore: bool,
+    #[serde(default)]
+    flakes: u32,
+    #[serde(default)]
+    promoted: Option<Promotion>,
+    #[serde(default)]
+    accepted_failure: Option<AcceptedFailure>,
+    #[serde(default)]
+    quarantine: Option<Quarantine>,
+    #[serde(default)]
+    metadata: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    transitions: Vec<Transition>,
+    #[serde(default)]
+    exec_time_ms: Option<u64>,
+    #[serde(default)]
+    toolchain: Option<Toolchain>,
+}
+
+impl From<EntryRepr> for TestEntry {
+    fn from(repr: EntryRepr) -> Self {
+        let record = match repr {
+            EntryRepr::State(state) => return TestEntry::new(state),
+            EntryRepr::Record(record) => *record,
+        };
+        let EntryRecord {
+            state,
+            baseline,
+            added_at,
+            changed_at,
+            allow_ignore,
+            flakes,
+            promoted,
+            accepted_failure,
+            quarantine,
+            metadata,
+            transitions,
+            exec_time_ms,
+            toolchain,
+        } = record;
+        TestEntry {
+            state,
+            baseline,
+            added_at,
+            changed_at,
+            allow_ignore,
+            flakes,
+            promoted,
+            accepted_failure,
+            quarantine,
+            metadata,
+            transitions,
+            exec_time_ms,
+            toolchain,
+        }
+    }
 }
 
 impl TestEntry {
-    pub fn state(&self) -> TestState {
-        match self {
-            TestEntry::Simple(s) => *s,
-            TestEntry::WithBaseline { state, .. } => *state,
+    pub fn new(state: TestState) -> Self {
+        TestEntry {
+            state,
+            baseline: None,
+            added_at: None,
+            changed_at: None,
+            allow_ignore: false,
+            flakes: 0,
+            promoted: None,
+            accepted_failure: None,
+            quarantine: None,
+            metadata: BTreeMap::new(),
+            transitions: Vec::new(),
+            exec_time_ms: None,
+            toolchain: None,
+        }
+    }
+
+    /// An entry grandfathered at `baseline`.
+    pub fn grandfathered(state: TestState, baseline: impl Into<String>) -> Self {
+        TestEntry {
+            baseline: Some(baseline.into()),
+            ..TestEntry::new(state)
         }
     }
 
+    pub fn state(&self) -> TestState {
+        self.state
+    }
+
+    /// The same entry in `state`. A real state change clears `changed_at` so
+    /// the next save stamps it, and the promotion it no longer reflects.
     pub fn with_state(&self, state: TestState) -> Self {
-        match self {
-            TestEntry::Simple(_) => TestEntry::Simple(state),
-            TestEntry::WithBaseline { baseline, .. } => TestEntry::WithBaseline {
-                state,
-                baseline: baseline.clone(),
-            },
+        let mut entry = self.clone();
+        if entry.state != state {
+            entry.state = state;
+            entry.changed_at = None;
+            entry.promoted = None;
         }
+        entry
     }
 
     pub fn baseline(&self) -> Option<&str> {
-        match self {
-            TestEntry::Simple(_) => None,
-            TestEntry::WithBaseline { baseline, .. } => Some(baseline),
-        }
+        self.baseline.as_deref()
+    }
+
+    /// Fill in missing timestamps with `now`.
+    fn stamp(&mut self, now: i64) {
+        self.added_at.get_or_insert(now);
+        self.changed_at.get_or_insert(now);
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct TrackedStatus {
     pub tests: BTreeMap<String, TestEntry>,
 }
@@ -83,37 +348,264 @@ impl TrackedStatus {
             .tests
             .get(&test_name)
             .map(|existing| existing.with_state(state))
-            .unwrap_or(TestEntry::Simple(state));
+            .unwrap_or_else(|| TestEntry::new(state));
         self.tests.insert(test_name, entry);
     }
 }
 
+/// Whether `name` is a wildcard entry such as `"proptest_regressions::*"`,
+/// which tracks every test it matches instead of one test. Wildcards match
+/// like `grandfather` patterns.
+pub fn is_wildcard(name: &str) -> bool {
+    name.contains('*')
+}
+
+/// The entry tracking `test`, with its name. An exact entry takes
+/// precedence; otherwise the matching wildcard with the most literal
+/// characters wins, the first by name on a tie.
+pub fn tracking_entry<'a>(
+    tests: &'a BTreeMap<String, TestEntry>,
+    test: &str,
+) -> Option<(&'a String, &'a TestEntry)> {
+    if let Some(exact) = tests.get_key_value(test) {
+        return Some(exact);
+    }
+    tests
+        .iter()
+        .filter(|(name, _)| is_wildcard(name) && wildcard_matches(name, test))
+        .min_by_key(|(name, _)| std::cmp::Reverse(name.chars().filter(|&c| c != '*').count()))
+}
+
+/// Whether the wildcard entry `wildcard` tracks `test`.
+pub fn wildcard_matches(wildcard: &str, test: &str) -> bool {
+    crate::grandfather::test_matches(wildcard, test)
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct WorkingTreeInstructions {
     pub renames: BTreeMap<String, String>,
     pub removals: BTreeSet<String>,
+    /// Tests the working tree tracks without `allow_ignore`. The working
+    /// tree can only withdraw a committed permission; one granted by `cargo
+    /// ratchet allow-ignore` takes effect once it is committed.
+    pub ignore_disallowed: BTreeSet<String>,
+    /// Tests the working tree tracks without an accepted failure. The
+    /// working tree can only withdraw a committed acceptance; one recorded
+    /// by `cargo ratchet accept-failure` takes effect once it is committed.
+    pub unaccepted: BTreeSet<String>,
+    /// Tests the working tree tracks without a quarantine mark. The working
+    /// tree can only lift a committed quarantine, so `cargo ratchet
+    /// unquarantine` takes effect before it is committed while a mark added
+    /// by hand has none.
+    pub unquarantined: BTreeSet<String>,
+    /// Tests in the draft state in the working tree. The working tree
+    /// decides, so `cargo ratchet draft` and `promote-drafts` take effect
+    /// before they are committed.
+    pub drafts: BTreeSet<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
+/// The status file. Files of earlier versions are upgraded in memory on
+/// load; saving always writes [`CURRENT_VERSION`].
+///
+/// Since version 3, tests run from a nextest binary are written in a
+/// section per binary, `{"binaries": {"crate::integration": {"tests":
+/// {...}}}}`, and only names without a `$` stay in the top-level `tests`.
+/// In memory, `tests` always holds every test under its full name.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "StatusFileRepr")]
 pub struct StatusFile {
     /// JSON Schema reference — always set to the canonical URL on save.
-    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
     schema: Option<String>,
+    /// Format version the file was read as; set to the current one on save.
+    version: u32,
     pub tests: BTreeMap<String, TestEntry>,
-    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub renames: BTreeMap<String, String>,
-    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
     pub removals: BTreeSet<String>,
+    /// Global baseline commit: history before it is not checked, and tests
+    /// in the status at this commit are grandfathered.
+    pub baseline: Option<String>,
+    /// Chained fingerprint over `tests`; see [`crate::integrity`].
+    pub integrity: Option<Integrity>,
+}
+
+/// The test binary of a full test name such as `crate::integration$test`,
+/// and the test's path within it; `None` for a name without a `$`.
+pub fn split_binary(test: &str) -> Option<(&str, &str)> {
+    test.split_once('$')
+}
+
+/// Whether the nextest binary ID `binary` (such as `my-crate::integration`)
+/// is named in `names`, by its full ID or by the binary name after the
+/// crate and target kind (`integration`; `server` for
+/// `my-crate::bin/server`).
+pub fn binary_selected(binary: &str, names: &[String]) -> bool {
+    let target = binary.rsplit("::").next().unwrap_or(binary);
+    let name = target.rsplit('/').next().unwrap_or(target);
+    names.iter().any(|n| n == binary || n == name)
+}
+
+/// One binary's section of the status file.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BinarySection<T> {
+    tests: T,
+}
+
+/// The on-disk layout [`StatusFile`] is written in.
+#[derive(Serialize)]
+struct StatusFileOut<'a> {
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    schema: &'a Option<String>,
+    version: u32,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    tests: BTreeMap<&'a str, &'a TestEntry>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    binaries: BTreeMap<&'a str, BinarySection<BTreeMap<&'a str, &'a TestEntry>>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    renames: &'a BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    removals: &'a BTreeSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    integrity: &'a Option<Integrity>,
+}
+
+impl Serialize for StatusFile {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tests = BTreeMap::new();
+        let mut binaries: BTreeMap<&str, BinarySection<BTreeMap<&str, &TestEntry>>> =
+            BTreeMap::new();
+        for (name, entry) in &self.tests {
+            match split_binary(name) {
+                Some((binary, path)) if self.version >= 3 => {
+                    binaries
+                        .entry(binary)
+                        .or_insert_with(|| BinarySection {
+                            tests: BTreeMap::new(),
+                        })
+                        .tests
+                        .insert(path, entry);
+                }
+                _ => {
+                    tests.insert(name.as_str(), entry);
+                }
+            }
+        }
+        StatusFileOut {
+            schema: &self.schema,
+            version: self.version,
+            tests,
+            binaries,
+            renames: &self.renames,
+            removals: &self.removals,
+            baseline: &self.baseline,
+            integrity: &self.integrity,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Every test under its full name: the top-level `tests` and each binary's
+/// section.
+fn flatten_binaries(
+    mut tests: BTreeMap<String, TestEntry>,
+    binaries: BTreeMap<String, BinarySection<BTreeMap<String, TestEntry>>>,
+) -> BTreeMap<String, TestEntry> {
+    for (binary, section) in binaries {
+        tests.extend(
+            section
+                .tests
+                .into_iter()
+                .map(|(path, entry)| (format!("{binary}${path}"), entry)),
+        );
+    }
+    tests
+}
+
+/// Tamper-evidence seal written by the ratchet on every save.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Integrity {
+    /// Digest of the committed status this one was derived from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
+    pub digest: String,
+    /// Fingerprint format the digest was computed with; see
+    /// [`crate::integrity::SEAL_VERSION`].
+    #[serde(default = "first_seal_version")]
+    pub version: u32,
+    /// Reason for a deliberate manual edit, recorded by `cargo ratchet reseal`.
+    #[serde(rename = "override", default, skip_serializing_if = "Option::is_none")]
+    pub override_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StatusFileRepr {
+    #[serde(rename = "$schema", default)]
+    schema: Option<String>,
+    #[serde(default = "legacy_version")]
+    version: u32,
+    #[serde(default)]
+    tests: BTreeMap<String, TestEntry>,
+    #[serde(default)]
+    binaries: BTreeMap<String, BinarySection<BTreeMap<String, TestEntry>>>,
+    #[serde(default)]
+    renames: BTreeMap<String, String>,
+    #[serde(default)]
+    removals: BTreeSet<String>,
+    #[serde(default)]
+    baseline: Option<String>,
+    #[serde(default)]
+    integrity: Option<Integrity>,
+}
+
+fn legacy_version() -> u32 {
+    1
+}
+
+fn first_seal_version() -> u32 {
+    1
+}
+
+impl TryFrom<StatusFileRepr> for StatusFile {
+    type Error = String;
+
+    fn try_from(repr: StatusFileRepr) -> Result<Self, Self::Error> {
+        if repr.version == 0 || repr.version > CURRENT_VERSION {
+            return Err(format!(
+                "unsupported status file version {} (this tdd-ratchet understands up to {})",
+                repr.version, CURRENT_VERSION
+            ));
+        }
+        Ok(StatusFile {
+            schema: repr.schema,
+            version: repr.version,
+            tests: flatten_binaries(repr.tests, repr.binaries),
+            renames: repr.renames,
+            removals: repr.removals,
+            baseline: repr.baseline,
+            integrity: repr.integrity,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct HistoricalStatusFile {
     #[serde(rename = "$schema", default)]
     schema: Option<String>,
+    #[serde(default = "legacy_version")]
+    version: u32,
+    #[serde(default)]
     tests: BTreeMap<String, TestEntry>,
     #[serde(default)]
+    binaries: BTreeMap<String, BinarySection<BTreeMap<String, TestEntry>>>,
+    #[serde(default)]
     renames: BTreeMap<String, String>,
+    #[serde(default)]
+    baseline: Option<String>,
+    #[serde(default)]
+    integrity: Option<Integrity>,
 }
 
 impl StatusFile {
@@ -127,9 +619,12 @@ impl StatusFile {
     pub fn from_parts(status: TrackedStatus, instructions: WorkingTreeInstructions) -> Self {
         StatusFile {
             schema: None,
+            version: CURRENT_VERSION,
             tests: status.tests,
             renames: instructions.renames,
             removals: BTreeSet::new(),
+            baseline: None,
+            integrity: None,
         }
     }
 
@@ -137,6 +632,26 @@ impl StatusFile {
         Self::new(BTreeMap::new())
     }
 
+    /// The format version this file was read as.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Whether saving would upgrade the on-disk format.
+    pub fn needs_migration(&self) -> bool {
+        self.version < CURRENT_VERSION
+    }
+
+    /// Fill in missing `added_at`/`changed_at` timestamps with `now` (Unix
+    /// seconds). New tests and state changes arrive unstamped from
+    /// evaluation; entries migrated from version 1 are stamped on their
+    /// first save.
+    pub fn stamp(&mut self, now: i64) {
+        for entry in self.tests.values_mut() {
+            entry.stamp(now);
+        }
+    }
+
     pub fn tracked_status(&self) -> TrackedStatus {
         TrackedStatus {
             tests: self.tests.clone(),
@@ -151,6 +666,30 @@ impl StatusFile {
         WorkingTreeInstructions {
             renames: self.renames.clone(),
             removals: self.removals.clone(),
+            ignore_disallowed: self
+                .tests
+                .iter()
+                .filter(|(_, entry)| !entry.allow_ignore)
+                .map(|(name, _)| name.clone())
+                .collect(),
+            unaccepted: self
+                .tests
+                .iter()
+                .filter(|(_, entry)| entry.accepted_failure.is_none())
+                .map(|(name, _)| name.clone())
+                .collect(),
+            unquarantined: self
+                .tests
+                .iter()
+                .filter(|(_, entry)| entry.quarantine.is_none())
+                .map(|(name, _)| name.clone())
+                .collect(),
+            drafts: self
+                .tests
+                .iter()
+                .filter(|(_, entry)| entry.state() == TestState::Draft)
+                .map(|(name, _)| name.clone())
+                .collect(),
         }
     }
 
@@ -160,116 +699,280 @@ impl StatusFile {
         self.tests = tracked.tests;
     }
 
-    pub fn read_from_path(path: &Path) -> Result<Self, StatusFileError> {
-        let contents = std::fs::read_to_string(path).map_err(|e| StatusFileError::Io {
-            path: path.to_path_buf(),
-            source: e,
-        })?;
+    pub fn read_from_path(path: &Path) -> Result<Self, RatchetError> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|source| RatchetError::io(path, source))?;
         Self::parse_from_str(&contents, path)
     }
 
-    pub fn write_to_path(&self, path: &Path) -> Result<(), StatusFileError> {
-        // Always write the $schema key. Working-tree removals are transient and
-        // never persisted into the ratchet-generated output.
+    /// Write the status file atomically: the new contents replace the old
+    /// ones whole, or not at all, even if the process dies mid-write. A file
+    /// with comments that already holds this status is left as it is, so
+    /// the comments survive runs that change nothing.
+    pub fn write_to_path(&self, path: &Path) -> Result<(), RatchetError> {
+        let contents = self.to_json(path)?;
+        if let Ok(existing) = std::fs::read_to_string(path)
+            && annotated_copy_of(&existing, &contents, path)
+        {
+            return Ok(());
+        }
+        write_atomically(path, contents.as_bytes()).map_err(|source| RatchetError::io(path, source))
+    }
+
+    /// The contents [`StatusFile::write_to_path`] writes; `path` is only
+    /// used in errors.
+    pub fn to_json(&self, path: &Path) -> Result<String, RatchetError> {
+        // Always write the $schema key and the current version. Working-tree
+        // removals are transient and never persisted into the
+        // ratchet-generated output.
         let mut with_schema = self.clone();
         with_schema.schema = Some(SCHEMA_URL.to_string());
+        with_schema.version = CURRENT_VERSION;
         with_schema.removals.clear();
-        let contents =
-            serde_json::to_string_pretty(&with_schema).map_err(|e| StatusFileError::Serialize {
+        let contents = serde_json::to_string_pretty(&with_schema).map_err(|source| {
+            ParseError::SerializeStatus {
                 path: path.to_path_buf(),
-                source: e,
-            })?;
-        std::fs::write(path, contents + "\n").map_err(|e| StatusFileError::Io {
-            path: path.to_path_buf(),
-            source: e,
+                source,
+            }
         })?;
-        Ok(())
+        Ok(contents + "\n")
     }
 
-    pub fn parse_from_str(contents: &str, path: &Path) -> Result<Self, StatusFileError> {
-        serde_json::from_str(contents).map_err(|e| StatusFileError::Parse {
-            path: path.to_path_buf(),
-            source: e,
-        })
+    /// Parse status file contents. Comments and trailing commas are
+    /// tolerated; see [`strip_jsonc`].
+    pub fn parse_from_str(contents: &str, path: &Path) -> Result<Self, RatchetError> {
+        serde_json::from_str(&strip_jsonc(contents)).map_err(|source| parse_error(path, source))
     }
 
-    pub fn parse_historical_from_str(contents: &str, path: &Path) -> Result<Self, StatusFileError> {
-        let historical: HistoricalStatusFile =
-            serde_json::from_str(contents).map_err(|e| StatusFileError::Parse {
-                path: path.to_path_buf(),
-                source: e,
-            })?;
+    pub fn parse_historical_from_str(contents: &str, path: &Path) -> Result<Self, RatchetError> {
+        let historical: HistoricalStatusFile = serde_json::from_str(&strip_jsonc(contents))
+            .map_err(|source| parse_error(path, source))?;
 
         Ok(StatusFile {
             schema: historical.schema,
-            tests: historical.tests,
+            version: historical.version,
+            tests: flatten_binaries(historical.tests, historical.binaries),
             renames: historical.renames,
             removals: BTreeSet::new(),
+            baseline: historical.baseline,
+            integrity: historical.integrity,
         })
     }
 
-    pub fn load(path: &Path) -> Result<Self, StatusFileError> {
+    pub fn load(path: &Path) -> Result<Self, RatchetError> {
         Self::read_from_path(path)
     }
 
-    pub fn save(&self, path: &Path) -> Result<(), StatusFileError> {
+    pub fn save(&self, path: &Path) -> Result<(), RatchetError> {
         self.write_to_path(path)
     }
 }
 
-#[derive(Debug)]
-pub enum StatusFileError {
-    Io {
-        path: std::path::PathBuf,
-        source: io::Error,
-    },
-    Parse {
-        path: std::path::PathBuf,
-        source: serde_json::Error,
-    },
-    Serialize {
-        path: std::path::PathBuf,
-        source: serde_json::Error,
-    },
-}
-
-impl fmt::Display for StatusFileError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            StatusFileError::Io { path, source } => {
-                write!(
-                    f,
-                    "Failed to read/write status file {}: {}",
-                    path.display(),
-                    source
-                )
+/// `contents` with `//` and `/* */` comments and trailing commas blanked
+/// out, so it parses as strict JSON. Everything else, including newlines,
+/// stays where it was, so parse errors point at the original line and
+/// column. Strict JSON is returned as it is. Pure function — no IO.
+pub fn strip_jsonc(contents: &str) -> Cow<'_, str> {
+    let bytes = contents.as_bytes();
+    let mut blanked: Vec<(usize, usize)> = Vec::new();
+    let mut last_comma = None;
+    let mut previous = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'"', _) => {
+                last_comma = None;
+                previous = b'"';
+                i += 1;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' => i += 2,
+                        b'"' => {
+                            i += 1;
+                            break;
+                        }
+                        _ => i += 1,
+                    }
+                }
+            }
+            (b'/', Some(b'/')) => {
+                let start = i;
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                blanked.push((start, i));
+            }
+            (b'/', Some(b'*')) => {
+                let start = i;
+                i += 2;
+                while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+                blanked.push((start, i));
             }
-            StatusFileError::Parse { path, source } => {
-                write!(
-                    f,
-                    "Failed to parse JSON in status file {}: {}",
-                    path.display(),
-                    source
-                )
+            // Only a comma after a value is trailing; `{,}` stays invalid.
+            (b',', _) => {
+                last_comma = (!matches!(previous, b'{' | b'[' | b',' | b':')).then_some(i);
+                previous = b',';
+                i += 1;
             }
-            StatusFileError::Serialize { path, source } => {
-                write!(
-                    f,
-                    "Failed to serialize status file {}: {}",
-                    path.display(),
-                    source
-                )
+            (b'}' | b']', _) => {
+                if let Some(comma) = last_comma.take() {
+                    blanked.push((comma, comma + 1));
+                }
+                previous = bytes[i];
+                i += 1;
+            }
+            (byte, _) => {
+                if !byte.is_ascii_whitespace() {
+                    last_comma = None;
+                    previous = byte;
+                }
+                i += 1;
             }
         }
     }
+    if blanked.is_empty() {
+        return Cow::Borrowed(contents);
+    }
+    blanked.sort_unstable();
+    let mut stripped = String::with_capacity(contents.len());
+    let mut ranges = blanked.iter().peekable();
+    for (index, c) in contents.char_indices() {
+        while ranges.next_if(|(_, end)| *end <= index).is_some() {}
+        let blank = ranges
+            .peek()
+            .is_some_and(|(start, end)| (*start..*end).contains(&index));
+        stripped.push(if blank && c != '\n' { ' ' } else { c });
+    }
+    Cow::Owned(stripped)
 }
 
-impl std::error::Error for StatusFileError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            StatusFileError::Io { source, .. } => Some(source),
-            StatusFileError::Parse { source, .. } => Some(source),
-            StatusFileError::Serialize { source, .. } => Some(source),
+/// Whether `existing`, the file at `path`, has comments or trailing commas
+/// and otherwise holds exactly what `contents` would write.
+fn annotated_copy_of(existing: &str, contents: &str, path: &Path) -> bool {
+    if matches!(strip_jsonc(existing), Cow::Borrowed(_)) {
+        return false;
+    }
+    StatusFile::parse_from_str(existing, path).is_ok_and(|status| {
+        status.version == CURRENT_VERSION
+            && status.removals.is_empty()
+            && status.to_json(path).is_ok_and(|json| json == contents)
+    })
+}
+
+fn parse_error(path: &Path, source: serde_json::Error) -> RatchetError {
+    ParseError::Status {
+        path: path.to_path_buf(),
+        source,
+    }
+    .into()
+}
+
+/// Write `contents` to a temporary file next to `path`, flush it to disk and
+/// rename it over `path`, keeping the permissions of the file it replaces.
+/// The temporary file is removed if any step fails.
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    // Write through a symlink rather than replacing it.
+    let path = match std::fs::canonicalize(path) {
+        Ok(target) => target,
+        Err(_) => path.to_path_buf(),
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path.file_name().map_or_else(
+        || STATUS_FILE_NAME.into(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let temp = dir.join(format!(".{name}.{}.tmp", std::process::id()));
+
+    let write = || -> io::Result<()> {
+        let mut file = std::fs::File::create(&temp)?;
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            file.set_permissions(metadata.permissions())?;
         }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, &path)
+    };
+    if let Err(e) = write() {
+        let _ = std::fs::remove_file(&temp);
+        return Err(e);
+    }
+    // Make the rename itself durable. Not every platform can open or sync a
+    // directory, and the file is already in place, so this is best effort.
+    if let Ok(dir) = std::fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Current Unix time in seconds, for [`StatusFile::stamp`].
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Where a ratchet run reads its working-tree instructions from and writes
+/// the updated status to.
+pub trait StatusStore {
+    /// Working-tree `renames`/`removals` for the current run.
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError>;
+    /// Persist the updated status.
+    fn save(&self, status: &StatusFile) -> Result<(), RatchetError>;
+}
+
+impl<T: StatusStore + ?Sized> StatusStore for &T {
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError> {
+        (**self).load_instructions()
+    }
+
+    fn save(&self, status: &StatusFile) -> Result<(), RatchetError> {
+        (**self).save(status)
+    }
+}
+
+impl<T: StatusStore + ?Sized> StatusStore for Box<T> {
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError> {
+        (**self).load_instructions()
+    }
+
+    fn save(&self, status: &StatusFile) -> Result<(), RatchetError> {
+        (**self).save(status)
+    }
+}
+
+/// The `.test-status.json` file in the working tree.
+#[derive(Debug, Clone)]
+pub struct WorkingTreeStatusFile {
+    path: PathBuf,
+}
+
+impl WorkingTreeStatusFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        WorkingTreeStatusFile { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl StatusStore for WorkingTreeStatusFile {
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError> {
+        if !self.path.exists() {
+            return Ok(WorkingTreeInstructions::default());
+        }
+        StatusFile::load(&self.path).map(|status| status.working_tree_instructions())
+    }
+
+    fn save(&self, status: &StatusFile) -> Result<(), RatchetError> {
+        status.save(&self.path)
     }
 }
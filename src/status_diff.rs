@@ -0,0 +1,138 @@
+// Status diffs: how the tracked tests differ between two status files, e.g.
+// a branch point and the working tree, for `cargo ratchet diff`.
+
+use crate::error::RatchetError;
+use crate::history::status_file_at_commit;
+use crate::status::{StatusFile, TestState};
+use crate::timeline::short_commit;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// The differences between an older and a newer status file, by test name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusDiff {
+    /// Tests tracked only in the newer status, with their state.
+    pub added: BTreeMap<String, TestState>,
+    /// Tests pending in the older status and passing in the newer one.
+    pub promoted: Vec<String>,
+    /// Tests passing in the older status and pending in the newer one. The
+    /// ratchet reports these as regressions when they come from a run.
+    pub regressed: Vec<String>,
+    /// Tests tracked only in the older status, with their last state.
+    pub retired: BTreeMap<String, TestState>,
+    /// New names of tests carried over by a `renames` entry, mapped to the
+    /// old ones. A renamed test that also changed state is listed as
+    /// promoted or regressed under its new name too.
+    pub renamed: BTreeMap<String, String>,
+}
+
+impl StatusDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.promoted.is_empty()
+            && self.regressed.is_empty()
+            && self.retired.is_empty()
+            && self.renamed.is_empty()
+    }
+}
+
+impl fmt::Display for StatusDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "  (no changes)");
+        }
+        for (test, state) in &self.added {
+            writeln!(f, "  added:     {test} ({state})")?;
+        }
+        for (new_name, old_name) in &self.renamed {
+            writeln!(f, "  renamed:   {old_name} -> {new_name}")?;
+        }
+        for test in &self.promoted {
+            writeln!(f, "  promoted:  {test}")?;
+        }
+        for test in &self.regressed {
+            writeln!(f, "  regressed: {test} (passing -> pending)")?;
+        }
+        for (test, state) in &self.retired {
+            writeln!(f, "  retired:   {test} (was {state})")?;
+        }
+        Ok(())
+    }
+}
+
+/// How the tracked tests in `new` differ from those in `old`. Pure function
+/// — no IO.
+///
+/// A test that appears under a name `new` maps through its `renames` to a
+/// name only `old` tracks counts as renamed, not as one test retired and
+/// another added.
+pub fn diff_status(old: &StatusFile, new: &StatusFile) -> StatusDiff {
+    let mut diff = StatusDiff::default();
+
+    for (name, entry) in &new.tests {
+        let previous = match old.tests.get(name) {
+            Some(previous) => previous,
+            None => match new
+                .renames
+                .get(name)
+                .filter(|old_name| !new.tests.contains_key(*old_name))
+                .and_then(|old_name| Some((old_name, old.tests.get(old_name)?)))
+            {
+                Some((old_name, previous)) => {
+                    diff.renamed.insert(name.clone(), old_name.clone());
+                    previous
+                }
+                None => {
+                    diff.added.insert(name.clone(), entry.state());
+                    continue;
+                }
+            },
+        };
+        match (previous.state(), entry.state()) {
+            (TestState::Pending, TestState::Passing) => diff.promoted.push(name.clone()),
+            (TestState::Passing, TestState::Pending) => diff.regressed.push(name.clone()),
+            _ => {}
+        }
+    }
+
+    for (name, entry) in &old.tests {
+        let carried_over = new.tests.contains_key(name) || diff.renamed.values().any(|o| o == name);
+        if !carried_over {
+            diff.retired.insert(name.clone(), entry.state());
+        }
+    }
+
+    diff
+}
+
+/// The status file at `status_path` in the commit `rev` names, in the
+/// repository at `repo_path`. `None` when that commit has no status file.
+pub fn status_at_rev(
+    repo_path: &Path,
+    rev: &str,
+    status_path: &Path,
+) -> Result<Option<StatusFile>, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let oid = repo.revparse_single(rev)?.peel_to_commit()?.id();
+    Ok(status_file_at_commit(&repo, oid, status_path)?)
+}
+
+/// Render a diff between the status at `from` and at `to`, both labels such
+/// as a commit or "working tree".
+pub fn format_status_diff(from: &str, to: &str, diff: &StatusDiff) -> String {
+    format!(
+        "tdd-ratchet diff: {}..{}\n{diff}",
+        short_label(from),
+        short_label(to)
+    )
+}
+
+/// Commit ids are shortened; other labels are shown as given.
+fn short_label(label: &str) -> &str {
+    if label.len() == 40 && label.chars().all(|c| c.is_ascii_hexdigit()) {
+        short_commit(label)
+    } else {
+        label
+    }
+}
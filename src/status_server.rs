@@ -0,0 +1,235 @@
+// Read-only HTTP endpoints for wallboards and dashboards, served by
+// `cargo ratchet serve`.
+//
+// Every endpoint answers `GET` with JSON, recomputed from disk and git
+// history on each request so the server never serves a stale view:
+//
+// - `/status`: every tracked test and its state, plus counts
+// - `/last-run`: the summary of the last ratchet run
+// - `/pending`: pending tests and how long each has been pending
+// - `/trend`: passing and pending counts at each committed snapshot
+//
+// The server is deliberately minimal: one connection at a time, no
+// keep-alive, and no writes. Each connection gets a read and write timeout
+// and the request head is capped in size, so an idle or slow client holds
+// the endpoint for at most the timeout.
+
+use crate::dashboard::build_dashboard;
+use crate::error::RatchetError;
+use crate::history::HistoryProvider;
+use crate::last_run::load_last_run;
+use crate::status::{StatusFile, TestEntry, TestState, TrackedStatus};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long a connection may stay idle before it is dropped.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The most bytes read for the request line and headers together.
+const MAX_HEAD_BYTES: u64 = 8 * 1024;
+
+/// An HTTP response: status code and JSON body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Response { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/// Where the endpoints read from.
+pub struct StatusEndpoints<'a> {
+    history: Box<dyn HistoryProvider + 'a>,
+    status_path: PathBuf,
+    last_run_path: PathBuf,
+    timeout: Duration,
+}
+
+impl<'a> StatusEndpoints<'a> {
+    /// `status_path` is the working-tree status file, read in preference to
+    /// the committed one; `last_run_path` is the last-run summary.
+    pub fn new(
+        history: impl HistoryProvider + 'a,
+        status_path: impl Into<PathBuf>,
+        last_run_path: impl Into<PathBuf>,
+    ) -> Self {
+        StatusEndpoints {
+            history: Box::new(history),
+            status_path: status_path.into(),
+            last_run_path: last_run_path.into(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Drop connections that stay idle for longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Answer one request. Query strings are ignored.
+    pub fn respond(&self, method: &str, target: &str) -> Response {
+        if method != "GET" {
+            return Response::error(405, "only GET is supported");
+        }
+        let path = target.split('?').next().unwrap_or(target);
+        let outcome = match path.trim_end_matches('/') {
+            "" => Ok(Response::ok(json!({
+                "endpoints": ["/status", "/last-run", "/pending", "/trend"],
+            }))),
+            "/status" => self.status().map(Response::ok),
+            "/last-run" => Ok(match load_last_run(&self.last_run_path) {
+                Some(summary) => Response::ok(json!(summary)),
+                None => Response::error(404, "no run recorded yet"),
+            }),
+            "/pending" => self.pending().map(Response::ok),
+            "/trend" => self.trend().map(Response::ok),
+            _ => Ok(Response::error(404, "not found")),
+        };
+        outcome.unwrap_or_else(|message| Response::error(500, &message))
+    }
+
+    /// The latest ratchet output if there is one, else the committed status.
+    fn current_status(&self) -> Result<TrackedStatus, String> {
+        if self.status_path.exists() {
+            return StatusFile::load(&self.status_path)
+                .map(StatusFile::into_tracked_status)
+                .map_err(|e| e.to_string());
+        }
+        Ok(self
+            .history
+            .head_status()
+            .map_err(|e| e.to_string())?
+            .map(StatusFile::into_tracked_status)
+            .unwrap_or_else(TrackedStatus::empty))
+    }
+
+    fn status(&self) -> Result<Value, String> {
+        let status = self.current_status()?;
+        let tests: BTreeMap<&String, TestState> = status
+            .tests
+            .iter()
+            .map(|(name, entry)| (name, entry.state()))
+            .collect();
+        let (passing, pending) = counts(status.tests.values());
+        Ok(json!({ "tests": tests, "passing": passing, "pending": pending }))
+    }
+
+    fn pending(&self) -> Result<Value, String> {
+        let status = self.current_status()?;
+        let snapshots = self.history.snapshots().map_err(|e| e.to_string())?;
+        let pending: Vec<Value> = build_dashboard(&status, &snapshots, 0)
+            .pending
+            .into_iter()
+            .map(|test| {
+                json!({
+                    "name": test.name,
+                    "since_commit": test.since_commit,
+                    "commits_pending": test.commits_pending,
+                })
+            })
+            .collect();
+        Ok(json!({ "pending": pending }))
+    }
+
+    fn trend(&self) -> Result<Value, String> {
+        let snapshots = self.history.snapshots().map_err(|e| e.to_string())?;
+        let points: Vec<Value> = snapshots
+            .iter()
+            .map(|snapshot| {
+                let (passing, pending) = counts(snapshot.status.tests.values());
+                json!({
+                    "commit": snapshot.commit,
+                    "time": snapshot.info.time,
+                    "passing": passing,
+                    "pending": pending,
+                })
+            })
+            .collect();
+        Ok(json!({ "snapshots": points }))
+    }
+
+    /// Serve requests on `listener` one connection at a time, forever.
+    /// A failed connection is handed to `on_error` and skipped.
+    pub fn serve(&self, listener: TcpListener, mut on_error: impl FnMut(RatchetError)) {
+        for stream in listener.incoming() {
+            if let Err(e) = stream.and_then(|stream| self.handle_connection(stream)) {
+                on_error(RatchetError::Connection(e));
+            }
+        }
+    }
+
+    fn handle_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let mut reader = BufReader::new(stream.try_clone()?.take(MAX_HEAD_BYTES));
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Headers are read and ignored.
+        let mut header = String::new();
+        let mut complete = false;
+        while reader.read_line(&mut header)? > 0 {
+            if header.trim().is_empty() {
+                complete = true;
+                break;
+            }
+            header.clear();
+        }
+        if !complete && reader.get_ref().limit() == 0 {
+            return write_response(&mut stream, &Response::error(431, "request head too large"));
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let response = match (parts.next(), parts.next()) {
+            (Some(method), Some(target)) => self.respond(method, target),
+            _ => Response::error(400, "malformed request"),
+        };
+        write_response(&mut stream, &response)
+    }
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    let body = serde_json::to_string_pretty(&response.body).map_err(io::Error::other)? + "\n";
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        reason(response.status),
+        body.len()
+    )?;
+    stream.flush()
+}
+
+fn counts<'e>(entries: impl Iterator<Item = &'e TestEntry>) -> (usize, usize) {
+    entries.fold((0, 0), |(passing, pending), entry| match entry.state() {
+        TestState::Passing => (passing + 1, pending),
+        TestState::Pending => (passing, pending + 1),
+        TestState::Draft => (passing, pending),
+    })
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    }
+}
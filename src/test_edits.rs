@@ -0,0 +1,196 @@
+// Promotion edit rule: flag commits that promote a test to passing while
+// also changing that test's own body (e.g. weakening its assertion).
+//
+// Only Rust sources are inspected: a `#[test]` function whose name matches the
+// last path segment of the test ID, in any `.rs` file changed by the
+// promoting commit.
+
+use crate::error::RatchetError;
+use crate::history::HistorySnapshot;
+use crate::timeline::state_changes;
+use std::path::Path;
+
+/// A promoting commit that also edited the promoted test's body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestEdit {
+    pub test: String,
+    pub commit: String,
+    /// Repository-relative path of the file containing the edited test.
+    pub path: String,
+}
+
+/// Every committed pending → passing promotion as `(test, commit)`.
+pub fn promotions(snapshots: &[HistorySnapshot]) -> Vec<(String, String)> {
+    state_changes(snapshots)
+        .into_iter()
+        .filter(|change| change.is_promotion())
+        .map(|change| (change.test, change.commit))
+        .collect()
+}
+
+/// The function name a test ID refers to: `crate::bin$mod::tests::name` →
+/// `name`.
+pub fn test_fn_name(test: &str) -> &str {
+    let path = test.rsplit_once('$').map_or(test, |(_, path)| path);
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Body (including braces) of the `#[test]` function `name` in `source`.
+pub fn test_fn_body<'a>(source: &'a str, name: &str) -> Option<&'a str> {
+    let after = test_fn_start(source, name)? + "fn ".len() + name.len();
+    let open = after + source[after..].find('{')?;
+    let close = matching_brace(&source[open..])?;
+    Some(&source[open..=open + close])
+}
+
+/// Byte offset of the `fn` keyword of the `#[test]` function `name` in
+/// `source`.
+pub fn test_fn_start(source: &str, name: &str) -> Option<usize> {
+    let needle = format!("fn {name}");
+    let mut search_from = 0;
+    while let Some(offset) = source[search_from..].find(&needle) {
+        let start = search_from + offset;
+        let after = start + needle.len();
+        search_from = after;
+
+        let boundary_before = source[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !(c.is_alphanumeric() || c == '_'));
+        let boundary_after = source[after..].trim_start().starts_with(['(', '<']);
+        if boundary_before && boundary_after && has_test_attribute(&source[..start]) {
+            return Some(start);
+        }
+    }
+    None
+}
+
+/// Whether the attributes directly before an item include a test attribute
+/// (`#[test]`, `#[tokio::test]`, ...).
+fn has_test_attribute(before_item: &str) -> bool {
+    let item_start = before_item.rfind(['}', ';']).map_or(0, |i| i + 1);
+    before_item[item_start..]
+        .split("#[")
+        .skip(1)
+        .any(|attribute| {
+            let attribute = attribute.split(']').next().unwrap_or("");
+            attribute == "test" || attribute.ends_with("::test")
+        })
+}
+
+/// Offset of the `}` matching the `{` at the start of `text`, skipping
+/// string and char literals and comments.
+fn matching_brace(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i += text[i..].find('\n').unwrap_or(text.len() - i);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += text[i..].find("*/").map_or(text.len() - i, |end| end + 1);
+            }
+            b'r' if matches!(bytes.get(i + 1), Some(b'"' | b'#'))
+                && !bytes
+                    .get(i.wrapping_sub(1))
+                    .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_') =>
+            {
+                let hashes = text[i + 1..].bytes().take_while(|b| *b == b'#').count();
+                if bytes.get(i + 1 + hashes) == Some(&b'"') {
+                    let terminator = format!("\"{}", "#".repeat(hashes));
+                    let body = i + 2 + hashes;
+                    i = body
+                        + text[body..]
+                            .find(&terminator)
+                            .map_or(text.len() - body, |end| end + terminator.len() - 1);
+                }
+            }
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'\'' => {
+                // Char literals ('{', '\n'); lifetimes have no closing quote.
+                if bytes.get(i + 1) == Some(&b'\\') {
+                    i += text[i + 3..].find('\'').map_or(0, |end| end + 3);
+                } else if bytes.get(i + 2) == Some(&b'\'') {
+                    i += 2;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Whether two bodies differ beyond whitespace.
+fn bodies_differ(old: &str, new: &str) -> bool {
+    let strip = |s: &str| s.split_whitespace().collect::<String>();
+    strip(old) != strip(new)
+}
+
+/// Tests among `tests` whose `#[test]` body changed in `commit` relative to
+/// its first parent. Root commits are skipped: every test there is new.
+pub fn edited_tests_in_commit(
+    repo_path: &Path,
+    commit: &str,
+    tests: &[String],
+) -> Result<Vec<TestEdit>, RatchetError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let commit_obj = repo.find_commit(git2::Oid::from_str(commit)?)?;
+    let Ok(parent) = commit_obj.parent(0) else {
+        return Ok(Vec::new());
+    };
+    let diff = repo.diff_tree_to_tree(Some(&parent.tree()?), Some(&commit_obj.tree()?), None)?;
+
+    let mut edits = Vec::new();
+    for delta in diff.deltas() {
+        let (Some(path), git2::Delta::Modified | git2::Delta::Renamed) =
+            (delta.new_file().path(), delta.status())
+        else {
+            continue;
+        };
+        if path.extension().is_none_or(|ext| ext != "rs") {
+            continue;
+        }
+        let old_blob = repo.find_blob(delta.old_file().id())?;
+        let new_blob = repo.find_blob(delta.new_file().id())?;
+        let (Ok(old_source), Ok(new_source)) = (
+            std::str::from_utf8(old_blob.content()),
+            std::str::from_utf8(new_blob.content()),
+        ) else {
+            continue;
+        };
+
+        for test in tests {
+            let name = test_fn_name(test);
+            if let (Some(old), Some(new)) = (
+                test_fn_body(old_source, name),
+                test_fn_body(new_source, name),
+            ) && bodies_differ(old, new)
+            {
+                edits.push(TestEdit {
+                    test: test.clone(),
+                    commit: commit.to_string(),
+                    path: path.to_string_lossy().into_owned(),
+                });
+            }
+        }
+    }
+    Ok(edits)
+}
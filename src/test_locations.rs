@@ -0,0 +1,150 @@
+// Source locations of tests, so violation messages can point at
+// `tests/cheater.rs:4` instead of leaving the reader to grep for the name.
+//
+// Like the promotion edit rule this scans Rust sources textually: every `.rs`
+// file under `src/` and `tests/` is searched for a `#[test]` function named
+// after the last path segment of the test ID. When several files define one,
+// the test ID's binary and module path pick between them.
+
+use crate::ratchet::{EvalResult, Violation};
+use crate::test_edits::{test_fn_name, test_fn_start};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories, relative to the crate root, that are searched for tests.
+const SOURCE_DIRS: [&str; 2] = ["src", "tests"];
+
+/// Where a test function is defined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestLocation {
+    /// Path of the file defining the test.
+    pub path: PathBuf,
+    /// 1-based line of the test's `fn`.
+    pub line: usize,
+    /// Teams or people that own the file, from CODEOWNERS; filled in by
+    /// [`crate::codeowners::assign_owners`].
+    pub owners: Vec<String>,
+}
+
+impl fmt::Display for TestLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)
+    }
+}
+
+/// Locate each of `tests` in the Rust sources of the crate at `crate_dir`.
+/// Paths are relative to `crate_dir`. Tests that cannot be found, or that
+/// are defined in several files the test ID cannot tell apart, are left out.
+pub fn locate_tests<'a>(
+    crate_dir: &Path,
+    tests: impl IntoIterator<Item = &'a str>,
+) -> BTreeMap<String, TestLocation> {
+    let mut sources = Vec::new();
+    for dir in SOURCE_DIRS {
+        collect_sources(crate_dir, Path::new(dir), &mut sources);
+    }
+
+    let mut locations = BTreeMap::new();
+    for test in tests {
+        let name = test_fn_name(test);
+        let candidates: Vec<TestLocation> = sources
+            .iter()
+            .filter_map(|(path, source)| {
+                let start = test_fn_start(source, name)?;
+                Some(TestLocation {
+                    path: path.clone(),
+                    line: source[..start].matches('\n').count() + 1,
+                    owners: Vec::new(),
+                })
+            })
+            .collect();
+        if let Some(location) = pick_candidate(test, candidates) {
+            locations.insert(test.to_string(), location);
+        }
+    }
+    locations
+}
+
+/// Fill in `result.locations` for every test a violation names. `prefix` is
+/// prepended to each path, e.g. the root's directory in a multi-root
+/// project so paths stay relative to where the ratchet was run.
+pub fn annotate_violations(result: &mut EvalResult, crate_dir: &Path, prefix: &Path) {
+    let tests: Vec<&str> = result
+        .violations
+        .iter()
+        .filter_map(|violation| match violation {
+            Violation::NewTestPassed { test }
+            | Violation::Regression { test }
+            | Violation::SkippedPending { test, .. }
+            | Violation::PassingTestIgnored { test } => Some(test.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut locations = locate_tests(crate_dir, tests);
+    for location in locations.values_mut() {
+        location.path = prefix.join(&location.path);
+    }
+    result.locations = locations;
+}
+
+/// Every `.rs` file under `crate_dir/dir`, in path order, with its path
+/// relative to `crate_dir`.
+fn collect_sources(crate_dir: &Path, dir: &Path, sources: &mut Vec<(PathBuf, String)>) {
+    let Ok(entries) = fs::read_dir(crate_dir.join(dir)) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| dir.join(entry.file_name()))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let full = crate_dir.join(&path);
+        if full.is_dir() {
+            collect_sources(crate_dir, &path, sources);
+        } else if path.extension().is_some_and(|ext| ext == "rs")
+            && let Ok(source) = fs::read_to_string(&full)
+        {
+            sources.push((path, source));
+        }
+    }
+}
+
+/// The single candidate that best matches the test ID's binary and module
+/// path, or `None` when there is no clear winner.
+fn pick_candidate(test: &str, mut candidates: Vec<TestLocation>) -> Option<TestLocation> {
+    if candidates.len() <= 1 {
+        return candidates.pop();
+    }
+
+    // `crate::binary$module::path::name` or libtest's `module::path::name`.
+    let (binary, path) = match test.split_once('$') {
+        Some((binary_id, path)) => (binary_id.split_once("::").map(|(_, bin)| bin), path),
+        None => (None, test),
+    };
+    let mut hints: Vec<&str> = path.split("::").collect();
+    hints.pop();
+    hints.extend(binary);
+
+    let score = |location: &TestLocation| {
+        location
+            .path
+            .components()
+            .filter_map(|component| Path::new(component.as_os_str()).file_stem())
+            .filter(|stem| hints.iter().any(|hint| stem.to_str() == Some(hint)))
+            .count()
+    };
+    let best = candidates.iter().map(score).max()?;
+    let mut winners: Vec<TestLocation> = candidates
+        .into_iter()
+        .filter(|location| score(location) == best)
+        .collect();
+    if winners.len() == 1 {
+        winners.pop()
+    } else {
+        None
+    }
+}
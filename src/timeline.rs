@@ -0,0 +1,273 @@
+// Timeline queries over history snapshots: per-test state changes with the
+// commit that caused them.
+
+use crate::history::{CommitInfo, HistorySnapshot};
+use crate::status::{StatusFile, TestEntry, TestState};
+use std::collections::BTreeSet;
+
+/// One change in a test's tracked state between consecutive snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    pub test: String,
+    /// Index of the snapshot (oldest first) where the change appeared.
+    pub snapshot_index: usize,
+    pub commit: String,
+    pub info: CommitInfo,
+    /// State before the change; `None` if the test was not tracked.
+    pub from: Option<TestState>,
+    /// State after the change; `None` if the test was removed.
+    pub to: Option<TestState>,
+    /// Previous name when the change came through a committed rename.
+    pub renamed_from: Option<String>,
+}
+
+impl StateChange {
+    pub fn is_promotion(&self) -> bool {
+        self.from == Some(TestState::Pending) && self.to == Some(TestState::Passing)
+    }
+
+    /// Short human description, e.g. "promoted to passing".
+    pub fn describe(&self) -> String {
+        let base = match (self.from, self.to) {
+            (None, Some(state)) => format!("added as {state}"),
+            (Some(TestState::Pending), Some(TestState::Passing)) => "promoted to passing".into(),
+            (Some(TestState::Passing), Some(TestState::Pending)) => "demoted to pending".into(),
+            (Some(TestState::Draft), Some(TestState::Pending)) => "promoted from draft".into(),
+            (Some(from), Some(to)) => format!("{from} → {to}"),
+            (Some(_), None) => "removed".into(),
+            (None, None) => "untracked".into(),
+        };
+        match (&self.renamed_from, self.to) {
+            (Some(old), Some(state)) if self.from == self.to => {
+                format!("renamed from {old} (still {state})")
+            }
+            (Some(old), _) => format!("renamed from {old}, {base}"),
+            (None, _) => base,
+        }
+    }
+}
+
+/// Every state change across history, in snapshot order. Pure function — no
+/// IO.
+///
+/// A test renamed via a committed `renames` entry carries its previous state
+/// across the rename instead of appearing as a brand-new test.
+pub fn state_changes(snapshots: &[HistorySnapshot]) -> Vec<StateChange> {
+    let mut changes = Vec::new();
+    let empty = StatusFile::empty();
+
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        let previous = index
+            .checked_sub(1)
+            .map_or(&empty, |i| &snapshots[i].status);
+        let current = &snapshot.status;
+        let mut consumed_by_rename = BTreeSet::new();
+
+        for (name, entry) in &current.tests {
+            let (from, renamed_from) = match previous.tests.get(name) {
+                Some(prev) => (Some(prev.state()), None),
+                None => match current
+                    .renames
+                    .get(name)
+                    .and_then(|old| previous.tests.get(old).map(|prev| (old, prev)))
+                {
+                    Some((old, prev)) => {
+                        consumed_by_rename.insert(old.as_str());
+                        (Some(prev.state()), Some(old.clone()))
+                    }
+                    None => (None, None),
+                },
+            };
+            let to = Some(entry.state());
+            if from != to || renamed_from.is_some() {
+                changes.push(StateChange {
+                    test: name.clone(),
+                    snapshot_index: index,
+                    commit: snapshot.commit.clone(),
+                    info: snapshot.info.clone(),
+                    from,
+                    to,
+                    renamed_from,
+                });
+            }
+        }
+
+        for (name, entry) in &previous.tests {
+            if !current.tests.contains_key(name) && !consumed_by_rename.contains(name.as_str()) {
+                changes.push(StateChange {
+                    test: name.clone(),
+                    snapshot_index: index,
+                    commit: snapshot.commit.clone(),
+                    info: snapshot.info.clone(),
+                    from: Some(entry.state()),
+                    to: None,
+                    renamed_from: None,
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+/// The state changes of a single test, following committed renames back to
+/// earlier names.
+pub fn test_timeline(snapshots: &[HistorySnapshot], test: &str) -> Vec<StateChange> {
+    let changes = state_changes(snapshots);
+    let mut names = BTreeSet::from([test.to_string()]);
+    // Walk backwards so each rename adds the older name before it is needed.
+    for change in changes.iter().rev() {
+        if names.contains(&change.test)
+            && let Some(old) = &change.renamed_from
+        {
+            names.insert(old.clone());
+        }
+    }
+    changes
+        .into_iter()
+        .filter(|change| names.contains(&change.test))
+        .collect()
+}
+
+/// `changes`, a test's [`test_timeline`], preceded by the transitions
+/// recorded in its status `entry` that the history walk did not reach, such
+/// as those before a shallow clone's first commit. Pure function — no IO.
+///
+/// When transitions are recorded, a test that seems added at the first
+/// walked commit was only cut off there, so that change is replaced by the
+/// recorded ones.
+pub fn with_recorded_transitions(
+    test: &str,
+    entry: Option<&TestEntry>,
+    changes: Vec<StateChange>,
+) -> Vec<StateChange> {
+    let recorded = entry.map_or(&[][..], |entry| &entry.transitions[..]);
+    let walked: Vec<StateChange> = changes
+        .into_iter()
+        .filter(|change| recorded.is_empty() || change.snapshot_index > 0 || change.from.is_some())
+        .collect();
+    let walked_commits: BTreeSet<&str> = walked.iter().map(|c| c.commit.as_str()).collect();
+    let mut timeline: Vec<StateChange> = recorded
+        .iter()
+        .filter(|transition| !walked_commits.contains(transition.commit.as_str()))
+        .map(|transition| StateChange {
+            test: test.to_string(),
+            snapshot_index: 0,
+            commit: transition.commit.clone(),
+            info: CommitInfo {
+                author: transition.author.clone(),
+                time: transition.time,
+                summary: String::new(),
+                overrides: Vec::new(),
+                parents: Vec::new(),
+            },
+            from: transition.from,
+            to: Some(transition.state),
+            renamed_from: None,
+        })
+        .collect();
+    timeline.extend(walked);
+    timeline
+}
+
+/// Render a test's timeline, one line per change.
+pub fn format_timeline(test: &str, changes: &[StateChange]) -> String {
+    let mut out = format!("tdd-ratchet log: {test}\n");
+    if changes.is_empty() {
+        out.push_str("  (no committed history for this test)\n");
+        return out;
+    }
+    for change in changes {
+        out.push_str(&format!(
+            "  {}  {}  {}  {}{}\n",
+            short_commit(&change.commit),
+            format_date(change.info.time),
+            change.info.author,
+            change.describe(),
+            quoted_summary(change)
+        ));
+    }
+    out
+}
+
+/// Render `blame` for a test: its current state, the commit that introduced
+/// it, and the commit that promoted it. `entry` is the working-tree entry,
+/// `None` if untracked; `changes` is its [`test_timeline`].
+pub fn format_blame(test: &str, entry: Option<&TestEntry>, changes: &[StateChange]) -> String {
+    let mut out = format!("tdd-ratchet blame: {test}\n");
+    out.push_str(&format!(
+        "  state:     {}\n",
+        entry.map_or("untracked".to_string(), |e| e.state().to_string())
+    ));
+
+    let introduced = changes
+        .iter()
+        .rev()
+        .find(|change| change.from.is_none() && change.to.is_some());
+    match introduced {
+        Some(change) => out.push_str(&format!(
+            "  added:     {} (as {})\n",
+            blame_commit(change),
+            change.to.expect("added changes have a state")
+        )),
+        None => out.push_str("  added:     (no committed history)\n"),
+    }
+
+    let promoted = if entry.is_some_and(|e| e.state() == TestState::Pending) {
+        "not yet".to_string()
+    } else if let Some(change) = changes.iter().rev().find(|change| change.is_promotion()) {
+        blame_commit(change)
+    } else if let Some(promotion) = entry.and_then(|e| e.promoted.as_ref()) {
+        // Recorded in the entry, but the history walk did not reach it.
+        format!(
+            "{}  {}  {}",
+            short_commit(&promotion.commit),
+            format_date(promotion.time),
+            promotion.author
+        )
+    } else {
+        "never (added as passing)".to_string()
+    };
+    out.push_str(&format!("  promoted:  {promoted}\n"));
+    out
+}
+
+fn blame_commit(change: &StateChange) -> String {
+    format!(
+        "{}  {}  {}{}",
+        short_commit(&change.commit),
+        format_date(change.info.time),
+        change.info.author,
+        quoted_summary(change)
+    )
+}
+
+/// The commit summary, quoted after two spaces; empty for a recorded
+/// transition, whose summary is not kept.
+fn quoted_summary(change: &StateChange) -> String {
+    if change.info.summary.is_empty() {
+        String::new()
+    } else {
+        format!("  \"{}\"", change.info.summary)
+    }
+}
+
+pub(crate) fn short_commit(commit: &str) -> &str {
+    &commit[..8.min(commit.len())]
+}
+
+/// Format seconds since the Unix epoch as a UTC `YYYY-MM-DD` date.
+pub fn format_date(seconds: i64) -> String {
+    // Civil-from-days conversion (Howard Hinnant's algorithm).
+    let days = seconds.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,65 @@
+// The toolchain a run builds and runs the tests with: the rustc release,
+// the nextest version, and the target triple. Recorded with `[state]
+// toolchain`, so a regression under a newer compiler or runner can be told
+// apart from a regression in the code.
+
+use crate::status::Toolchain;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The release and host triple from `rustc -vV`, e.g. `1.86.0` and
+/// `x86_64-unknown-linux-gnu`. Pure function — no IO.
+pub fn parse_rustc_version(output: &str) -> Option<(String, String)> {
+    let field = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(|value| value.trim().to_string())
+    };
+    Some((field("release")?, field("host")?))
+}
+
+/// The version from `cargo nextest --version`, e.g. `0.9.72` from
+/// `cargo-nextest 0.9.72 (abc123 2024-07-01)`. Pure function — no IO.
+pub fn parse_nextest_version(output: &str) -> Option<String> {
+    let mut words = output.lines().next()?.split_whitespace();
+    (words.next()? == "cargo-nextest")
+        .then(|| words.next())
+        .flatten()
+        .map(str::to_string)
+}
+
+/// Detect the toolchain for `project_dir`, whose `rust-toolchain.toml` the
+/// `rustc` and `cargo` proxies honour. The tests are built for `target`,
+/// or the host; `nextest` says whether they run under nextest. `None` when
+/// `rustc` cannot be run.
+pub fn detect_toolchain(
+    project_dir: &Path,
+    target: Option<&str>,
+    nextest: bool,
+) -> Option<Toolchain> {
+    let output = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .current_dir(project_dir)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let (rustc, host) = parse_rustc_version(&output("rustc", &["-vV"])?)?;
+    let nextest = if nextest {
+        output("cargo", &["nextest", "--version"])
+            .as_deref()
+            .and_then(parse_nextest_version)
+    } else {
+        None
+    };
+    Some(Toolchain {
+        rustc,
+        nextest,
+        target: target.map_or(host, str::to_string),
+    })
+}
@@ -0,0 +1,134 @@
+// Trend data: passing and pending counts at each commit that carries the
+// status file, for burn-up charts of test growth and the pending backlog.
+//
+// Counts at a commit never change, so they are cached by commit in an
+// untracked sidecar (`.ratchet-trend-cache.json`); a later `trend` only
+// reads the status files of commits it has not seen.
+
+use crate::error::{ParseError, RatchetError};
+use crate::history::{status_file_at_commit, status_file_commits};
+use crate::status::{StatusFile, TestState};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const TREND_CACHE_FILE_NAME: &str = ".ratchet-trend-cache.json";
+
+/// Output format for `cargo ratchet trend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrendFormat {
+    /// A JSON array of points (the default).
+    #[default]
+    Json,
+    /// CSV with a header row.
+    Csv,
+}
+
+impl TrendFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(TrendFormat::Json),
+            "csv" => Some(TrendFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Tracked test counts at one commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrendPoint {
+    pub commit: String,
+    /// Commit time in Unix seconds.
+    pub time: i64,
+    pub passing: usize,
+    pub pending: usize,
+}
+
+impl TrendPoint {
+    /// The counts of `status`, committed at `commit`. Pure function — no IO.
+    pub fn of(commit: &str, time: i64, status: &StatusFile) -> Self {
+        let passing = status
+            .tests
+            .values()
+            .filter(|entry| entry.state() == TestState::Passing)
+            .count();
+        TrendPoint {
+            commit: commit.to_string(),
+            time,
+            passing,
+            pending: status.tests.len() - passing,
+        }
+    }
+}
+
+/// Trend points already computed, by commit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrendCache {
+    pub points: BTreeMap<String, TrendPoint>,
+}
+
+impl TrendCache {
+    /// Read the cache. A missing or unreadable file is an empty cache: it
+    /// only saves work, so it is never a reason to fail.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), RatchetError> {
+        let json = serde_json::to_string(self).map_err(|source| ParseError::Sidecar {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        std::fs::write(path, json + "\n").map_err(|e| RatchetError::io(path, e))
+    }
+}
+
+/// Trend points for the newest `last` commits reachable from `HEAD` that
+/// carry the status file at `status_path`, oldest first; all of them when
+/// `last` is `None`. Points missing from `cache` are computed and added.
+pub fn collect_trend(
+    repo_path: &Path,
+    status_path: &Path,
+    last: Option<usize>,
+    cache: &mut TrendCache,
+) -> Result<Vec<TrendPoint>, RatchetError> {
+    let commits = status_file_commits(repo_path, status_path, "HEAD")?;
+    let skip = last.map_or(0, |last| commits.len().saturating_sub(last));
+    let mut repo = None;
+    let mut points = Vec::new();
+    for (commit, info) in commits.into_iter().skip(skip) {
+        if let Some(point) = cache.points.get(&commit) {
+            points.push(point.clone());
+            continue;
+        }
+        let repo = match &repo {
+            Some(repo) => repo,
+            None => repo.insert(git2::Repository::open(repo_path)?),
+        };
+        let status = status_file_at_commit(repo, git2::Oid::from_str(&commit)?, status_path)?
+            .unwrap_or_else(StatusFile::empty);
+        let point = TrendPoint::of(&commit, info.time, &status);
+        cache.points.insert(commit, point.clone());
+        points.push(point);
+    }
+    Ok(points)
+}
+
+/// Column names, in order.
+pub const TREND_CSV_HEADER: [&str; 4] = ["commit", "time", "passing", "pending"];
+
+/// Trend points as CSV. Pure function — no IO.
+pub fn trend_csv(points: &[TrendPoint]) -> String {
+    let mut out = TREND_CSV_HEADER.join(",");
+    out.push('\n');
+    for point in points {
+        out.push_str(&format!(
+            "{},{},{},{}\n",
+            point.commit, point.time, point.passing, point.pending
+        ));
+    }
+    out
+}
@@ -0,0 +1,154 @@
+// Trivial test lint: warn when a test promoted to passing asserts nothing.
+//
+// A test with an empty body, or one whose only check is `assert!(true)`, can
+// be written to fail first and then promoted without ever testing anything.
+// Unlike the textual scans in `test_edits`, this parses the test's file with
+// `syn` so that assertions inside nested blocks and closures are found.
+//
+// A body counts as asserting when it contains an assertion macro (any macro
+// whose name contains `assert`) with a non-constant condition, a panicking
+// macro, `.unwrap()`/`.expect()`, the `?` operator, or a call to a helper
+// whose name contains `assert`, `check`, or `verify`. `#[should_panic]`
+// tests always count as asserting.
+
+use crate::ratchet::{EvalResult, Warning};
+use crate::status::{TestState, TrackedStatus};
+use crate::test_edits::test_fn_name;
+use crate::test_locations::locate_tests;
+use std::fs;
+use std::path::Path;
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Attribute, Expr, ExprLit, Item, ItemFn, Lit, Macro, Token};
+
+/// Macros that fail the test when reached.
+const PANIC_MACROS: [&str; 4] = ["panic", "unreachable", "todo", "unimplemented"];
+/// Methods that panic on an error or `None`.
+const PANIC_METHODS: [&str; 4] = ["unwrap", "expect", "unwrap_err", "expect_err"];
+/// Name fragments of helper functions assumed to assert.
+const HELPER_NAMES: [&str; 3] = ["assert", "check", "verify"];
+
+/// Whether the `#[test]` function `name` in `source` asserts nothing.
+/// `None` when the source does not parse or defines no such test.
+pub fn asserts_nothing(source: &str, name: &str) -> Option<bool> {
+    let file = syn::parse_file(source).ok()?;
+    let test = find_test(&file.items, name)?;
+    if has_attribute(&test.attrs, "should_panic") {
+        return Some(false);
+    }
+    let mut finder = AssertionFinder { found: false };
+    finder.visit_block(&test.block);
+    Some(!finder.found)
+}
+
+/// Add a [`Warning::TrivialTest`] for each test this run promoted from
+/// pending to passing whose body asserts nothing. `previous` is the committed
+/// status the run started from; `prefix` is prepended to reported paths as
+/// in [`annotate_violations`](crate::test_locations::annotate_violations).
+pub fn warn_trivial_promotions(
+    result: &mut EvalResult,
+    previous: &TrackedStatus,
+    crate_dir: &Path,
+    prefix: &Path,
+) {
+    let promoted = result.updated.tests.iter().filter(|(test, entry)| {
+        entry.state() == TestState::Passing
+            && previous
+                .tests
+                .get(*test)
+                .is_some_and(|before| before.state() == TestState::Pending)
+    });
+    let locations = locate_tests(crate_dir, promoted.map(|(test, _)| test.as_str()));
+    for (test, mut location) in locations {
+        let Ok(source) = fs::read_to_string(crate_dir.join(&location.path)) else {
+            continue;
+        };
+        if asserts_nothing(&source, test_fn_name(&test)) == Some(true) {
+            location.path = prefix.join(&location.path);
+            result.locations.insert(test.clone(), location);
+            result.warnings.push(Warning::TrivialTest { test });
+        }
+    }
+}
+
+fn find_test<'a>(items: &'a [Item], name: &str) -> Option<&'a ItemFn> {
+    items.iter().find_map(|item| match item {
+        Item::Fn(function)
+            if function.sig.ident == name && has_attribute(&function.attrs, "test") =>
+        {
+            Some(function)
+        }
+        Item::Mod(module) => find_test(&module.content.as_ref()?.1, name),
+        _ => None,
+    })
+}
+
+/// Whether `attrs` include `#[name]` or a path ending in `::name`, e.g.
+/// `#[tokio::test]`.
+fn has_attribute(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name)
+    })
+}
+
+struct AssertionFinder {
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for AssertionFinder {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let Some(name) = mac.path.segments.last().map(|s| s.ident.to_string()) else {
+            return;
+        };
+        if PANIC_MACROS.contains(&name.as_str())
+            || (name.contains("assert") && !constant_assertion(&name, mac))
+        {
+            self.found = true;
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match expr {
+            Expr::Try(_) => self.found = true,
+            Expr::MethodCall(call) if PANIC_METHODS.contains(&call.method.to_string().as_str()) => {
+                self.found = true;
+            }
+            Expr::Call(call) => {
+                if let Expr::Path(path) = &*call.func
+                    && let Some(segment) = path.path.segments.last()
+                {
+                    let name = segment.ident.to_string();
+                    if HELPER_NAMES.iter().any(|helper| name.contains(helper)) {
+                        self.found = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+        visit::visit_expr(self, expr);
+    }
+}
+
+/// `assert!(true)`, or `assert_eq!`/`assert_ne!` comparing an expression
+/// with itself: assertions whose outcome does not depend on the code under
+/// test.
+fn constant_assertion(name: &str, mac: &Macro) -> bool {
+    let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+        return false;
+    };
+    let args: Vec<&Expr> = args.iter().collect();
+    match args.as_slice() {
+        [first, ..] if !name.contains("_eq") && !name.contains("_ne") => matches!(
+            first,
+            Expr::Lit(ExprLit {
+                lit: Lit::Bool(_),
+                ..
+            })
+        ),
+        [left, right, ..] => left == right,
+        _ => false,
+    }
+}
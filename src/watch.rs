@@ -0,0 +1,75 @@
+// Polling file watcher for `cargo ratchet daemon --watch`.
+//
+// Each poll fingerprints the modification time and size of every file under
+// the project and compares it with the previous poll. Polling needs no
+// platform support or extra dependencies. `target` and hidden directories
+// such as `.git` are skipped, so a build or a commit alone is not a change.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Modification time and size of each file, by path.
+type Fingerprint = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// Notices when files under a directory change between polls.
+pub struct Watcher {
+    root: PathBuf,
+    last: Fingerprint,
+}
+
+impl Watcher {
+    /// Watch the files under `root`, as they are now.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let last = fingerprint(&root);
+        Watcher { root, last }
+    }
+
+    /// Whether any file was added, removed, or modified since the last call
+    /// or [`Watcher::reset`].
+    pub fn changed(&mut self) -> bool {
+        let current = fingerprint(&self.root);
+        if current == self.last {
+            return false;
+        }
+        self.last = current;
+        true
+    }
+
+    /// Take the files as they are now as unchanged, e.g. after a run that
+    /// wrote its own status and sidecar files.
+    pub fn reset(&mut self) {
+        self.last = fingerprint(&self.root);
+    }
+}
+
+fn fingerprint(root: &Path) -> Fingerprint {
+    let mut files = Fingerprint::new();
+    collect(root, &mut files);
+    files
+}
+
+/// Unreadable directories and files are left out rather than failing the
+/// poll; they show up once they can be read.
+fn collect(dir: &Path, files: &mut Fingerprint) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name != "target" && !name.starts_with('.') {
+                collect(&path, files);
+            }
+        } else if let Ok(metadata) = entry.metadata() {
+            files.insert(path, (metadata.modified().ok(), metadata.len()));
+        }
+    }
+}
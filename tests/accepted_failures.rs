@@ -0,0 +1,241 @@
+// tests/accepted_failures.rs
+//
+// Story 64: A passing test can be accepted as failing for a limited time,
+// with a reason and an expiry date or commit count. Failures are tolerated
+// until the acceptance expires and are regressions after it; reports always
+// list the acceptances in force.
+
+mod common;
+
+use common::result;
+use tdd_ratchet::errors::{format_markdown_report, format_report};
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::ratchet::{EvalResult, Violation, evaluate};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{AcceptedFailure, StatusFile, TestEntry, TestState, TrackedStatus};
+
+const GATEKEEPER: &str = "app::tests$tdd_ratchet_gatekeeper";
+const BROKEN: &str = "app::tests$broken";
+
+// 2026-03-01T12:00:00Z and a day later.
+const MARCH_1: i64 = 1_772_366_400;
+const DAY: i64 = 86_400;
+
+fn until(date: &str) -> AcceptedFailure {
+    AcceptedFailure {
+        reason: "upstream outage".into(),
+        until: Some(date.into()),
+        commits: None,
+    }
+}
+
+fn for_commits(commits: u32) -> AcceptedFailure {
+    AcceptedFailure {
+        reason: "upstream outage".into(),
+        until: None,
+        commits: Some(commits),
+    }
+}
+
+fn accepted_status(acceptance: &AcceptedFailure) -> StatusFile {
+    let mut entry = TestEntry::new(TestState::Passing);
+    entry.accepted_failure = Some(acceptance.clone());
+    StatusFile::new(
+        [
+            (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+            (BROKEN.to_string(), entry),
+        ]
+        .into_iter()
+        .collect(),
+    )
+}
+
+fn snapshot(commit: &str, time: i64, acceptance: &AcceptedFailure) -> HistorySnapshot {
+    HistorySnapshot {
+        info: CommitInfo {
+            time,
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, accepted_status(acceptance))
+    }
+}
+
+fn run(
+    acceptance: &AcceptedFailure,
+    outcome: TestOutcome,
+    snapshots: &[HistorySnapshot],
+) -> EvalResult {
+    let status = accepted_status(acceptance);
+    evaluate(
+        &status.tracked_status(),
+        &status.working_tree_instructions(),
+        &[
+            result(GATEKEEPER, TestOutcome::Passed),
+            result(BROKEN, outcome),
+        ],
+        snapshots,
+    )
+}
+
+fn expired(result: &EvalResult) -> Vec<&str> {
+    result
+        .violations
+        .iter()
+        .filter_map(|v| match v {
+            Violation::AcceptedFailureExpired { test, .. } => Some(test.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn failures_are_tolerated_until_the_date() {
+    let acceptance = until("2026-03-02");
+    let snapshots = [
+        snapshot("c1", MARCH_1, &acceptance),
+        snapshot("c2", MARCH_1 + DAY, &acceptance),
+    ];
+
+    let result = run(&acceptance, TestOutcome::Failed, &snapshots);
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    let entry = &result.updated.tests[BROKEN];
+    assert_eq!(entry.state(), TestState::Passing);
+    assert_eq!(entry.accepted_failure.as_ref(), Some(&acceptance));
+}
+
+#[test]
+fn failures_after_the_date_are_violations() {
+    let acceptance = until("2026-03-01");
+    let snapshots = [
+        snapshot("c1", MARCH_1, &acceptance),
+        snapshot("c2", MARCH_1 + DAY, &acceptance),
+    ];
+
+    let result = run(&acceptance, TestOutcome::Failed, &snapshots);
+
+    assert_eq!(expired(&result), [BROKEN]);
+    assert_eq!(result.violations[0].code(), "RATCHET019");
+}
+
+#[test]
+fn failures_are_tolerated_for_the_given_number_of_commits() {
+    let acceptance = for_commits(2);
+    let mut snapshots = vec![
+        snapshot("c1", MARCH_1, &until("2026-12-31")),
+        snapshot("c2", MARCH_1, &acceptance),
+        snapshot("c3", MARCH_1, &acceptance),
+        snapshot("c4", MARCH_1, &acceptance),
+    ];
+
+    let within = run(&acceptance, TestOutcome::Failed, &snapshots);
+    assert!(within.violations.is_empty(), "{:?}", within.violations);
+
+    snapshots.push(snapshot("c5", MARCH_1, &acceptance));
+    let after = run(&acceptance, TestOutcome::Failed, &snapshots);
+    assert_eq!(expired(&after), [BROKEN]);
+}
+
+#[test]
+fn an_uncommitted_acceptance_has_no_effect() {
+    let committed = StatusFile::new(
+        [
+            (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+            (BROKEN.to_string(), TestEntry::new(TestState::Passing)),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    let snapshots = [HistorySnapshot {
+        commit: "c1".into(),
+        info: CommitInfo::default(),
+        status: committed.clone(),
+    }];
+    // An acceptance in the working tree only, whether added by hand or by
+    // `accept-failure` before it was committed.
+    let working_tree = accepted_status(&for_commits(1));
+
+    let result = evaluate(
+        &committed.tracked_status(),
+        &working_tree.working_tree_instructions(),
+        &[
+            result(GATEKEEPER, TestOutcome::Passed),
+            result(BROKEN, TestOutcome::Failed),
+        ],
+        &snapshots,
+    );
+
+    assert!(
+        matches!(result.violations.as_slice(), [Violation::Regression { test }] if test == BROKEN),
+        "{:?}",
+        result.violations
+    );
+    assert_eq!(result.updated.tests[BROKEN].accepted_failure, None);
+}
+
+#[test]
+fn the_working_tree_can_withdraw_a_committed_acceptance() {
+    let acceptance = until("2026-03-02");
+    let snapshots = [snapshot("c1", MARCH_1, &acceptance)];
+    let withdrawn = StatusFile::new(
+        [
+            (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+            (BROKEN.to_string(), TestEntry::new(TestState::Passing)),
+        ]
+        .into_iter()
+        .collect(),
+    );
+
+    let result = evaluate(
+        &accepted_status(&acceptance).tracked_status(),
+        &withdrawn.working_tree_instructions(),
+        &[
+            result(GATEKEEPER, TestOutcome::Passed),
+            result(BROKEN, TestOutcome::Failed),
+        ],
+        &snapshots,
+    );
+
+    assert!(
+        matches!(result.violations.as_slice(), [Violation::Regression { test }] if test == BROKEN),
+        "{:?}",
+        result.violations
+    );
+}
+
+#[test]
+fn passing_again_clears_the_acceptance() {
+    let acceptance = until("2026-03-02");
+    let snapshots = [snapshot("c1", MARCH_1, &acceptance)];
+
+    let result = run(&acceptance, TestOutcome::Passed, &snapshots);
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(result.updated.tests[BROKEN].accepted_failure, None);
+}
+
+#[test]
+fn reports_list_active_acceptances() {
+    let acceptance = until("2026-03-02");
+    let snapshots = [snapshot("c1", MARCH_1, &acceptance)];
+    let result = run(&acceptance, TestOutcome::Failed, &snapshots);
+
+    let text = format_report(&result);
+    assert!(text.contains("tdd-ratchet: 1 accepted failure\n"), "{text}");
+    assert!(
+        text.contains(&format!(
+            "  ~ {BROKEN}: upstream outage (until 2026-03-02)\n"
+        )),
+        "{text}"
+    );
+
+    let markdown = format_markdown_report(&result, &TrackedStatus::empty());
+    assert!(
+        markdown.contains("<summary>Accepted failures (1)</summary>"),
+        "{markdown}"
+    );
+    assert!(
+        markdown.contains(&format!("- `{BROKEN}`: upstream outage (until 2026-03-02)")),
+        "{markdown}"
+    );
+}
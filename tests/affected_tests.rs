@@ -0,0 +1,223 @@
+// tests/affected_tests.rs
+//
+// Story 89: `--affected` runs and checks only the packages changed since the
+// merge base, and the packages depending on them.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::affected::{
+    Affected, WorkspacePackage, affected_packages, changed_paths, workspace_packages,
+};
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+
+fn package(name: &str, dir: &str, dependencies: &[&str]) -> WorkspacePackage {
+    WorkspacePackage {
+        name: name.to_string(),
+        dir: dir.to_string(),
+        dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+    }
+}
+
+fn packages(names: &[&str]) -> Affected {
+    Affected::Packages(names.iter().map(|n| n.to_string()).collect())
+}
+
+fn changed(paths: &[&str]) -> Vec<String> {
+    paths.iter().map(|p| p.to_string()).collect()
+}
+
+/// A workspace of `engine`, `web` (depending on `engine`), `cli`
+/// (depending on `web`) and `docs`, with `engine/macros` nested inside
+/// `engine`.
+fn workspace() -> Vec<WorkspacePackage> {
+    vec![
+        package("engine", "engine", &["serde"]),
+        package("engine-macros", "engine/macros", &[]),
+        package("web", "crates/web", &["engine", "serde"]),
+        package("cli", "crates/cli", &["web"]),
+        package("docs", "docs", &[]),
+    ]
+}
+
+#[test]
+fn changed_files_affect_their_package_and_its_dependents() {
+    let workspace = workspace();
+
+    assert_eq!(
+        affected_packages(&workspace, &changed(&["crates/web/src/lib.rs"])),
+        packages(&["cli", "web"])
+    );
+    assert_eq!(
+        affected_packages(&workspace, &changed(&["engine/src/lib.rs"])),
+        packages(&["cli", "engine", "web"])
+    );
+    assert_eq!(
+        affected_packages(&workspace, &changed(&["engine/macros/src/lib.rs"])),
+        packages(&["engine-macros"])
+    );
+    assert_eq!(
+        affected_packages(
+            &workspace,
+            &changed(&["docs/guide.md", "docs/.test-status.json"])
+        ),
+        packages(&["docs"])
+    );
+}
+
+#[test]
+fn changes_outside_every_package_affect_everything() {
+    let workspace = workspace();
+
+    assert_eq!(
+        affected_packages(&workspace, &changed(&["docs/a.md", "Cargo.lock"])),
+        Affected::Everything
+    );
+    assert_eq!(
+        affected_packages(&workspace, &changed(&["crates/webby/src/lib.rs"])),
+        Affected::Everything
+    );
+    assert_eq!(
+        affected_packages(
+            &workspace,
+            &changed(&[".test-status.json", ".ratchet-last-run.json"])
+        ),
+        Affected::Packages(BTreeSet::new())
+    );
+
+    let single = [package("app", "", &[])];
+    assert_eq!(
+        affected_packages(&single, &changed(&["README.md"])),
+        packages(&["app"])
+    );
+}
+
+#[test]
+fn changed_paths_include_uncommitted_and_untracked_files() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::create_dir_all(path.join("a")).unwrap();
+    fs::write(path.join("a/one.rs"), "1").unwrap();
+    fs::write(path.join("two.rs"), "2").unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Base"]);
+
+    fs::write(path.join("a/one.rs"), "one").unwrap();
+    git(path, &["commit", "-am", "Change one"]);
+    fs::write(path.join("two.rs"), "two").unwrap();
+    fs::create_dir_all(path.join("b")).unwrap();
+    fs::write(path.join("b/three.rs"), "3").unwrap();
+
+    assert_eq!(
+        changed_paths(path, "HEAD~1").unwrap(),
+        ["a/one.rs", "b/three.rs", "two.rs"]
+    );
+    assert_eq!(
+        changed_paths(path, "HEAD").unwrap(),
+        ["b/three.rs", "two.rs"]
+    );
+    dir.pass();
+}
+
+/// Write a workspace of `engine` and `web`, which depends on `engine`.
+fn write_workspace(path: &Path) {
+    fs::write(
+        path.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"engine\", \"web\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+    for (name, deps) in [
+        ("engine", ""),
+        ("web", "engine = { path = \"../engine\" }\n"),
+    ] {
+        fs::create_dir_all(path.join(name).join("src")).unwrap();
+        fs::write(
+            path.join(name).join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                 [dependencies]\n{deps}"
+            ),
+        )
+        .unwrap();
+        fs::write(path.join(name).join("src/lib.rs"), "").unwrap();
+    }
+}
+
+#[test]
+fn workspace_packages_come_from_cargo_metadata() {
+    let dir = TestDir::new();
+    write_workspace(dir.path());
+
+    let mut packages = workspace_packages(dir.path(), dir.path()).unwrap();
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert_eq!(
+        packages,
+        [
+            package("engine", "engine", &[]),
+            package("web", "web", &["engine"]),
+        ]
+    );
+    dir.pass();
+}
+
+#[test]
+fn affected_flag_checks_only_changed_packages() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    write_workspace(path);
+    fs::write(path.join(".gitignore"), "target/\nresults.json\n").unwrap();
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"version":3,"binaries":{
+            "app::gatekeeper":{"tests":{"tdd_ratchet_gatekeeper":{"state":"passing"}}},
+            "engine":{"tests":{"parse":{"state":"passing"}}},
+            "web":{"tests":{"render":{"state":"passing"}}}}}"#,
+    )
+    .unwrap();
+    let set_results = |names: &[&str]| {
+        let lines: Vec<String> = names
+            .iter()
+            .map(|name| format!("{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"{name}\"}}"))
+            .collect();
+        fs::write(path.join("results.json"), lines.join("\n") + "\n").unwrap();
+    };
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+    git(path, &["checkout", "-b", "feature"]);
+
+    fs::write(path.join("web/src/lib.rs"), "pub fn render() {}\n").unwrap();
+    set_results(&[GATEKEEPER, "web$render"]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("engine$parse"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--affected"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("affected since"), "{out}");
+    assert!(out.contains("partial run"), "{out}");
+    assert!(out.contains("-p web"), "{out}");
+
+    fs::write(path.join("engine/src/lib.rs"), "pub fn parse() {}\n").unwrap();
+    let (code, out) = run_ratchet(path, &["--affected"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("engine$parse"), "{out}");
+    dir.pass();
+}
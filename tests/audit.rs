@@ -0,0 +1,194 @@
+// tests/audit.rs
+//
+// Story 31: An opt-in audit log records every ratchet run — HEAD,
+// violations, transitions, overrides — and `audit show` queries it.
+
+use std::path::Path;
+use tdd_ratchet::audit::{
+    AuditOverride, AuditRecord, AuditTransition, append_record, format_audit, read_audit,
+    run_record,
+};
+use tdd_ratchet::config::{AuditConfig, RatchetConfig};
+use tdd_ratchet::ratchet::{EvalResult, Violation, Warning};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, TrackedStatus};
+use tempfile::TempDir;
+
+fn tracked(tests: &[(&str, TestState)]) -> TrackedStatus {
+    TrackedStatus::new(
+        tests
+            .iter()
+            .map(|(n, s)| (n.to_string(), TestEntry::new(*s)))
+            .collect(),
+    )
+}
+
+fn result(tests: &[(&str, TestState)], violations: Vec<Violation>) -> EvalResult {
+    EvalResult {
+        violations,
+        warnings: vec![Warning::OverrideApplied {
+            commit: "c1aaaaaaaa".into(),
+            test: "flaky".into(),
+            rule: "Regression".into(),
+            reason: "vendor API down".into(),
+        }],
+        updated: StatusFile::from_parts(tracked(tests), Default::default()),
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+#[test]
+fn run_record_captures_head_violations_transitions_and_overrides() {
+    let before = tracked(&[
+        ("promoted", TestState::Pending),
+        ("flaky", TestState::Passing),
+        ("removed", TestState::Pending),
+    ]);
+    let after = result(
+        &[
+            ("promoted", TestState::Passing),
+            ("flaky", TestState::Passing),
+            ("added", TestState::Pending),
+        ],
+        vec![Violation::NewTestPassed {
+            test: "sneaky".into(),
+        }],
+    );
+
+    let record = run_record(Some("c2bbbbbbbb"), &before, &after, 42);
+
+    let AuditRecord::Run {
+        recorded_at,
+        head,
+        violations,
+        transitions,
+        overrides,
+    } = record
+    else {
+        panic!("expected a run record");
+    };
+    assert_eq!(recorded_at, 42);
+    assert_eq!(head.as_deref(), Some("c2bbbbbbbb"));
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].rule, "NewTestPassed");
+    assert_eq!(violations[0].subject, "sneaky");
+    assert_eq!(
+        transitions,
+        vec![
+            AuditTransition {
+                test: "added".into(),
+                from: None,
+                to: Some(TestState::Pending),
+            },
+            AuditTransition {
+                test: "promoted".into(),
+                from: Some(TestState::Pending),
+                to: Some(TestState::Passing),
+            },
+            AuditTransition {
+                test: "removed".into(),
+                from: Some(TestState::Pending),
+                to: None,
+            },
+        ]
+    );
+    assert_eq!(
+        overrides,
+        vec![AuditOverride {
+            commit: "c1aaaaaaaa".into(),
+            test: "flaky".into(),
+            rule: "Regression".into(),
+            reason: "vendor API down".into(),
+        }]
+    );
+}
+
+#[test]
+fn run_records_append_and_read_back() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let before = tracked(&[("t", TestState::Pending)]);
+    let first = run_record(
+        None,
+        &before,
+        &result(&[("t", TestState::Pending)], vec![]),
+        1,
+    );
+    let second = run_record(
+        Some("c2bbbbbbbb"),
+        &before,
+        &result(&[("t", TestState::Passing)], vec![]),
+        2,
+    );
+
+    append_record(&path, &first).unwrap();
+    append_record(&path, &second).unwrap();
+
+    let records = read_audit(&path).unwrap();
+    assert_eq!(records, vec![first, second]);
+    assert!(records[1].mentions("t"));
+    assert!(!records[0].mentions("t"));
+    assert!(records[0].mentions("flaky"));
+    let line = std::fs::read_to_string(&path).unwrap();
+    assert!(line.starts_with(r#"{"kind":"run","#), "{line}");
+}
+
+#[test]
+fn format_audit_summarizes_runs_and_overrides() {
+    let before = tracked(&[("t", TestState::Pending)]);
+    let run = run_record(
+        Some("c2bbbbbbbb"),
+        &before,
+        &result(
+            &[("t", TestState::Passing)],
+            vec![Violation::Regression { test: "r".into() }],
+        ),
+        0,
+    );
+    let override_record = AuditRecord::Override {
+        commit: "c1aaaaaaaa".into(),
+        test: "flaky".into(),
+        rule: "Regression".into(),
+        reason: "vendor API down".into(),
+        recorded_at: 86_400,
+    };
+
+    let text = format_audit(&[run, override_record]);
+
+    assert!(
+        text.contains("1970-01-01 run c2bbbbbb 1 violation, 1 transition, 1 override"),
+        "{text}"
+    );
+    assert!(text.contains("    t: pending -> passing"), "{text}");
+    assert!(text.contains("    Regression r:"), "{text}");
+    assert!(
+        text.contains("1970-01-02 override c1aaaaaa Regression flaky: vendor API down"),
+        "{text}"
+    );
+}
+
+#[test]
+fn audit_table_parses_and_defaults_to_hidden_file() {
+    let config = RatchetConfig::parse_from_str(
+        "[audit]\nenabled = true\nfile = \"audit/ratchet.jsonl\"\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert!(config.audit.enabled);
+    assert_eq!(
+        config.audit.path(Path::new("/p")),
+        Path::new("/p/audit/ratchet.jsonl")
+    );
+
+    let default = AuditConfig::default();
+    assert!(!default.enabled);
+    assert_eq!(
+        default.path(Path::new("/p")),
+        Path::new("/p/.ratchet-audit.jsonl")
+    );
+}
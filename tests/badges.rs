@@ -0,0 +1,107 @@
+// tests/badges.rs
+//
+// Story 60: `cargo ratchet badge` renders passing and pending counts and the
+// last run's violations as an SVG badge or shields.io endpoint JSON, for
+// repos to show ratchet health in their README.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, status};
+use serde_json::Value;
+use std::process::Command;
+use tdd_ratchet::badge::{BadgeColor, build_badge, format_shields_json, format_svg};
+use tdd_ratchet::last_run::{RunSummary, save_last_run};
+use tdd_ratchet::status::{TestState, TrackedStatus};
+
+fn last_run(violations: usize) -> RunSummary {
+    RunSummary {
+        recorded_at: 1_700_000_000,
+        head: None,
+        passing: 0,
+        pending: Default::default(),
+        regressions: 0,
+        violations,
+        suite_time_ms: None,
+        failing_drafts: Default::default(),
+    }
+}
+
+#[test]
+fn badge_color_follows_pending_and_violations() {
+    let all_passing = status(&[("a", TestState::Passing), ("b", TestState::Passing)]);
+    let badge = build_badge(&all_passing.into_tracked_status(), None);
+    assert_eq!(badge.label, "ratchet");
+    assert_eq!(badge.message, "2 passing, 0 pending");
+    assert_eq!(badge.color, BadgeColor::Green);
+
+    let some_pending =
+        status(&[("a", TestState::Passing), ("b", TestState::Pending)]).into_tracked_status();
+    let badge = build_badge(&some_pending, Some(&last_run(0)));
+    assert_eq!(badge.message, "1 passing, 1 pending");
+    assert_eq!(badge.color, BadgeColor::Yellow);
+
+    let badge = build_badge(&some_pending, Some(&last_run(2)));
+    assert_eq!(badge.message, "1 passing, 1 pending, 2 violations");
+    assert_eq!(badge.color, BadgeColor::Red);
+}
+
+#[test]
+fn shields_json_is_an_endpoint_badge() {
+    let badge = build_badge(&TrackedStatus::empty(), Some(&last_run(1)));
+    let document: Value = serde_json::from_str(&format_shields_json(&badge)).unwrap();
+    assert_eq!(document["schemaVersion"], 1);
+    assert_eq!(document["label"], "ratchet");
+    assert_eq!(document["message"], "0 passing, 0 pending, 1 violation");
+    assert_eq!(document["color"], "red");
+}
+
+#[test]
+fn svg_shows_label_and_message() {
+    let badge = build_badge(
+        &status(&[("a", TestState::Pending)]).into_tracked_status(),
+        None,
+    );
+    let svg = format_svg(&badge);
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""),
+        "{svg}"
+    );
+    assert!(svg.contains(">ratchet</text>"), "{svg}");
+    assert!(svg.contains(">0 passing, 1 pending</text>"), "{svg}");
+    assert!(svg.contains("#dfb317"), "{svg}");
+    assert!(svg.trim_end().ends_with("</svg>"), "{svg}");
+}
+
+#[test]
+fn badge_command_writes_the_badge() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    status(&[("a", TestState::Passing), ("b", TestState::Pending)])
+        .save(&dir.path().join(".test-status.json"))
+        .unwrap();
+    save_last_run(&dir.path().join(".ratchet-last-run.json"), &last_run(0)).unwrap();
+
+    let output = Command::new(cargo_bin())
+        .args(["badge", "--format", "shields-json", "--out", "badge.json"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let written = std::fs::read_to_string(dir.path().join("badge.json")).unwrap();
+    let document: Value = serde_json::from_str(&written).unwrap();
+    assert_eq!(document["message"], "1 passing, 1 pending");
+    assert_eq!(document["color"], "yellow");
+
+    let output = Command::new(cargo_bin())
+        .args(["badge", "--format", "png"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+
+    dir.pass();
+}
@@ -0,0 +1,241 @@
+// tests/binary_sections.rs
+//
+// Story 87: the status file keeps a section per test binary, and `--test
+// <BINARY>` runs and evaluates a subset of binaries without reporting the
+// others' tests as disappeared.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::runner::{TestOutcome, TestResult, binaries_filterset};
+use tdd_ratchet::schema::validate_status_json;
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, binary_selected};
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+
+fn tests(names: &[(&str, TestState)]) -> std::collections::BTreeMap<String, TestEntry> {
+    names
+        .iter()
+        .map(|(name, state)| (name.to_string(), TestEntry::new(*state)))
+        .collect()
+}
+
+fn gathered(binaries: &[&str], results: Vec<TestResult>) -> GatheredRun {
+    use TestState::{Passing, Pending};
+    GatheredRun {
+        binaries: binaries.iter().map(|b| b.to_string()).collect(),
+        ..common::gathered(
+            &[
+                (GATEKEEPER, Passing),
+                ("app::integration$flow", Passing),
+                ("app::integration$next", Pending),
+                ("app$unit", Passing),
+            ],
+            results,
+        )
+    }
+}
+
+#[test]
+fn tests_are_saved_in_a_section_per_binary() {
+    let mut status = StatusFile::new(tests(&[
+        ("app::integration$flow::works", TestState::Passing),
+        ("app::integration$edge", TestState::Pending),
+        ("app$unit", TestState::Passing),
+        ("pytest_case", TestState::Passing),
+    ]));
+    status.baseline = Some("0123456789abcdef0123456789abcdef01234567".into());
+
+    let json = status.to_json(Path::new(".test-status.json")).unwrap();
+    let saved: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(saved["version"], 3);
+    assert_eq!(
+        saved["tests"],
+        serde_json::json!({"pytest_case": {"state": "passing"}})
+    );
+    assert_eq!(
+        saved["binaries"],
+        serde_json::json!({
+            "app": {"tests": {"unit": {"state": "passing"}}},
+            "app::integration": {"tests": {
+                "edge": {"state": "pending"},
+                "flow::works": {"state": "passing"},
+            }},
+        })
+    );
+    assert!(validate_status_json(&json).is_empty(), "{json}");
+
+    let reloaded = StatusFile::parse_from_str(&json, Path::new(".test-status.json")).unwrap();
+    assert_eq!(reloaded.tests, status.tests);
+    assert!(!reloaded.needs_migration());
+}
+
+#[test]
+fn version_2_files_are_upgraded_to_binary_sections() {
+    let v2 = r#"{"version":2,"tests":{"app::integration$flow":{"state":"passing"}}}"#;
+    let status = StatusFile::parse_from_str(v2, Path::new(".test-status.json")).unwrap();
+    assert!(status.needs_migration());
+    assert!(status.tests.contains_key("app::integration$flow"));
+
+    let json = status.to_json(Path::new(".test-status.json")).unwrap();
+    let saved: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        saved["binaries"]["app::integration"]["tests"]["flow"]["state"],
+        "passing"
+    );
+    assert!(saved.get("tests").is_none(), "{json}");
+
+    let historical =
+        StatusFile::parse_historical_from_str(&json, Path::new(".test-status.json")).unwrap();
+    assert_eq!(historical.tests, status.tests);
+}
+
+#[test]
+fn binaries_are_selected_by_id_or_name() {
+    let names = |names: &[&str]| -> Vec<String> { names.iter().map(|n| n.to_string()).collect() };
+
+    assert!(binary_selected(
+        "app::integration",
+        &names(&["integration"])
+    ));
+    assert!(binary_selected(
+        "app::integration",
+        &names(&["app::integration"])
+    ));
+    assert!(binary_selected("app::bin/server", &names(&["server"])));
+    assert!(binary_selected("app", &names(&["app"])));
+    assert!(!binary_selected("app::integration", &names(&["app"])));
+    assert!(!binary_selected(
+        "other::integration",
+        &names(&["app::integration"])
+    ));
+}
+
+#[test]
+fn nextest_runs_the_selected_binaries_and_the_gatekeeper() {
+    assert_eq!(
+        binaries_filterset(&["integration".into(), "app::bin/server".into()]),
+        "binary(=integration) | binary_id(=app::bin/server) | test(/tdd_ratchet_gatekeeper$/)"
+    );
+}
+
+#[test]
+fn unselected_binaries_are_kept_instead_of_disappearing() {
+    let results = vec![
+        result(GATEKEEPER, TestOutcome::Passed),
+        result("app::integration$flow", TestOutcome::Passed),
+        result("app::integration$next", TestOutcome::Passed),
+    ];
+
+    let everything = gathered(&[], results.clone()).evaluate();
+    assert!(
+        matches!(
+            everything.violations.as_slice(),
+            [Violation::TestDisappeared { test }] if test == "app$unit"
+        ),
+        "{:?}",
+        everything.violations
+    );
+
+    let result = gathered(&["integration"], results).evaluate();
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(result.updated.tests["app$unit"].state(), TestState::Passing);
+    assert_eq!(
+        result.updated.tests["app::integration$next"].state(),
+        TestState::Passing
+    );
+    assert_eq!(
+        result.updated.integrity.as_ref().unwrap().digest,
+        tdd_ratchet::integrity::seal(None, &result.updated).digest
+    );
+}
+
+#[test]
+fn selected_binaries_are_still_checked() {
+    let result = gathered(
+        &["integration"],
+        vec![
+            result(GATEKEEPER, TestOutcome::Passed),
+            result("app::integration$flow", TestOutcome::Failed),
+            result("app$unit", TestOutcome::Failed),
+        ],
+    )
+    .evaluate();
+
+    let mut flagged: Vec<String> = result
+        .violations
+        .iter()
+        .map(|v| match v {
+            Violation::Regression { test } => format!("regression {test}"),
+            Violation::TestDisappeared { test } => format!("disappeared {test}"),
+            other => format!("{other:?}"),
+        })
+        .collect();
+    flagged.sort();
+    assert_eq!(
+        flagged,
+        [
+            "disappeared app::integration$next",
+            "regression app::integration$flow"
+        ]
+    );
+}
+
+#[test]
+fn test_flag_limits_the_run_to_one_binary() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"version":2,"tests":{
+            "app::gatekeeper$tdd_ratchet_gatekeeper":{"state":"passing"},
+            "app::integration$flow":{"state":"passing"},
+            "app$unit":{"state":"passing"}}}"#,
+    )
+    .unwrap();
+    let set_results = |names: &[&str]| {
+        let lines: Vec<String> = names
+            .iter()
+            .map(|name| format!("{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"{name}\"}}"))
+            .collect();
+        fs::write(path.join("results.json"), lines.join("\n") + "\n").unwrap();
+    };
+    set_results(&[GATEKEEPER, "app::integration$flow", "app$unit"]);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    set_results(&[GATEKEEPER, "app::integration$flow"]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("app$unit"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--test", "integration"]);
+    assert_eq!(code, Some(0), "{out}");
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(path.join(".test-status.json")).unwrap()).unwrap();
+    assert_eq!(saved["version"], 3);
+    assert_eq!(
+        saved["binaries"]["app"]["tests"]["unit"]["state"],
+        "passing"
+    );
+    assert_eq!(
+        saved["binaries"]["app::integration"]["tests"]["flow"]["state"],
+        "passing"
+    );
+    dir.pass();
+}
@@ -0,0 +1,151 @@
+// tests/blame.rs
+//
+// Story 46: `cargo ratchet blame <test>` shows a test's current state, the
+// commit that introduced it, and the commit that promoted it, with authors.
+
+mod common;
+
+use common::status;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::status::{TestEntry, TestState};
+use tdd_ratchet::timeline::{format_blame, test_timeline};
+
+fn snapshot(
+    commit: &str,
+    author: &str,
+    summary: &str,
+    tests: &[(&str, TestState)],
+    renames: &[(&str, &str)],
+) -> HistorySnapshot {
+    let mut status = status(tests);
+    status.renames = renames
+        .iter()
+        .map(|(new, old)| (new.to_string(), old.to_string()))
+        .collect();
+    HistorySnapshot {
+        info: CommitInfo {
+            author: author.into(),
+            time: 86_400,
+            summary: summary.into(),
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status)
+    }
+}
+
+#[test]
+fn blame_shows_who_added_and_who_promoted() {
+    let snapshots = vec![
+        snapshot(
+            "aaaaaaaa11",
+            "Ada",
+            "Add failing test",
+            &[("t", TestState::Pending)],
+            &[],
+        ),
+        snapshot(
+            "bbbbbbbb22",
+            "Grace",
+            "Implement",
+            &[("t", TestState::Passing)],
+            &[],
+        ),
+    ];
+    let entry = TestEntry::new(TestState::Passing);
+
+    let blame = format_blame("t", Some(&entry), &test_timeline(&snapshots, "t"));
+
+    assert_eq!(
+        blame,
+        "tdd-ratchet blame: t\n\
+         \x20 state:     passing\n\
+         \x20 added:     aaaaaaaa  1970-01-02  Ada  \"Add failing test\" (as pending)\n\
+         \x20 promoted:  bbbbbbbb  1970-01-02  Grace  \"Implement\"\n"
+    );
+}
+
+#[test]
+fn blame_of_a_pending_test_is_not_yet_promoted() {
+    let snapshots = vec![snapshot(
+        "aaaaaaaa11",
+        "Ada",
+        "Add failing test",
+        &[("t", TestState::Pending)],
+        &[],
+    )];
+    let entry = TestEntry::new(TestState::Pending);
+
+    let blame = format_blame("t", Some(&entry), &test_timeline(&snapshots, "t"));
+
+    assert!(blame.contains("state:     pending"), "{blame}");
+    assert!(blame.contains("promoted:  not yet"), "{blame}");
+}
+
+#[test]
+fn blame_follows_renames_to_the_original_commit() {
+    let snapshots = vec![
+        snapshot(
+            "aaaaaaaa11",
+            "Ada",
+            "Add old",
+            &[("old", TestState::Pending)],
+            &[],
+        ),
+        snapshot(
+            "bbbbbbbb22",
+            "Grace",
+            "Pass old",
+            &[("old", TestState::Passing)],
+            &[],
+        ),
+        snapshot(
+            "cccccccc33",
+            "Linus",
+            "Rename",
+            &[("new", TestState::Passing)],
+            &[("new", "old")],
+        ),
+    ];
+    let entry = TestEntry::new(TestState::Passing);
+
+    let blame = format_blame("new", Some(&entry), &test_timeline(&snapshots, "new"));
+
+    assert!(blame.contains("added:     aaaaaaaa"), "{blame}");
+    assert!(blame.contains("promoted:  bbbbbbbb"), "{blame}");
+}
+
+#[test]
+fn blame_of_a_test_added_as_passing_was_never_promoted() {
+    let snapshots = vec![snapshot(
+        "aaaaaaaa11",
+        "Ada",
+        "Initialize",
+        &[("t", TestState::Passing)],
+        &[],
+    )];
+    let entry = TestEntry::new(TestState::Passing);
+
+    let blame = format_blame("t", Some(&entry), &test_timeline(&snapshots, "t"));
+
+    assert!(blame.contains("(as passing)"), "{blame}");
+    assert!(blame.contains("promoted:  never"), "{blame}");
+}
+
+#[test]
+fn blame_of_a_removed_test_reports_it_untracked() {
+    let snapshots = vec![
+        snapshot(
+            "aaaaaaaa11",
+            "Ada",
+            "Add",
+            &[("t", TestState::Pending)],
+            &[],
+        ),
+        snapshot("bbbbbbbb22", "Grace", "Remove", &[], &[]),
+    ];
+
+    let blame = format_blame("t", None, &test_timeline(&snapshots, "t"));
+
+    assert!(blame.contains("state:     untracked"), "{blame}");
+    assert!(blame.contains("added:     aaaaaaaa"), "{blame}");
+}
@@ -0,0 +1,151 @@
+// tests/build_failures.rs
+//
+// Story 41: When the project does not compile, the ratchet reports the build
+// failure with the compiler output and a dedicated exit code, instead of
+// evaluating an empty run.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, git};
+use std::fs;
+use std::process::Command;
+use tdd_ratchet::runner::{CommandRunner, OutputParser, RunnerError, TestOutcome, TestRunner};
+
+fn sh(script: &str) -> Vec<String> {
+    vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_command_without_test_results_is_a_build_failure() {
+    let runner = CommandRunner::new(
+        &sh("echo 'error[E0425]: cannot find value `x`'; exit 101"),
+        ".",
+        OutputParser::Libtest,
+    )
+    .unwrap();
+
+    let err = runner.run().unwrap_err();
+
+    let RunnerError::CompileFailed { status, output, .. } = &err else {
+        panic!("expected a build failure, got {err}");
+    };
+    assert_eq!(status.code(), Some(101));
+    assert!(output.contains("cannot find value"), "{output}");
+    assert!(err.to_string().contains("build failed"), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_tests_are_not_a_build_failure() {
+    let runner = CommandRunner::new(
+        &sh("echo 'test broken ... FAILED'; exit 101"),
+        ".",
+        OutputParser::Libtest,
+    )
+    .unwrap();
+
+    let results = runner.run().unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].outcome, TestOutcome::Failed);
+}
+
+#[cfg(unix)]
+#[test]
+fn successful_command_without_tests_is_not_a_build_failure() {
+    let runner = CommandRunner::new(&sh("true"), ".", OutputParser::Libtest).unwrap();
+    assert!(runner.run().unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn build_failure_prints_the_compiler_output_and_exits_with_code_3() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[[runners]]\nkind = \"command\"\ncommand = [\"sh\", \"-c\", \"echo 'error: could not compile `demo`'; exit 101\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    let status = r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","a":"passing"}}"#;
+    fs::write(path.join(".test-status.json"), status).unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Track tests"]);
+
+    let output = Command::new(cargo_bin())
+        .current_dir(path)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(3), "{stderr}");
+    assert!(stderr.contains("could not compile `demo`"), "{stderr}");
+    assert!(stderr.contains("build failed"), "{stderr}");
+    assert!(!stderr.contains("missing from the run"), "{stderr}");
+    assert_eq!(
+        fs::read_to_string(path.join(".test-status.json")).unwrap(),
+        status
+    );
+    dir.pass();
+}
+
+#[cfg(unix)]
+#[test]
+fn missing_nextest_is_a_setup_error_not_disappeared_tests() {
+    use std::os::unix::fs::PermissionsExt;
+
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    let status = r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","a":"passing"}}"#;
+    fs::write(path.join(".test-status.json"), status).unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Track tests"]);
+
+    // A `cargo` that has every subcommand but nextest, as cargo reports it.
+    let bin = path.join("bin");
+    fs::create_dir(&bin).unwrap();
+    let stub = bin.join("cargo");
+    fs::write(
+        &stub,
+        format!(
+            "#!/bin/sh\nif [ \"$1\" = nextest ]; then\n  echo 'error: no such command: `nextest`' >&2\n  exit 101\nfi\nexec '{}' \"$@\"\n",
+            env!("CARGO")
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+    let search_path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let output = Command::new(cargo_bin())
+        .current_dir(path)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", path)
+        .env("PATH", search_path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(2), "{stderr}");
+    assert!(stderr.contains("cargo-nextest not found"), "{stderr}");
+    assert!(!stderr.contains("RATCHET003"), "{stderr}");
+    assert_eq!(
+        fs::read_to_string(path.join(".test-status.json")).unwrap(),
+        status
+    );
+    dir.pass();
+}
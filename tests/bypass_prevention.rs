@@ -4,30 +4,11 @@
 
 mod common;
 
-use common::TestDir;
+use common::{TestDir, cargo_home, rustup_home};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::process::Command;
 
-/// Resolve RUSTUP_HOME for subprocess isolation.
-fn rustup_home() -> PathBuf {
-    if let Ok(val) = std::env::var("RUSTUP_HOME") {
-        PathBuf::from(val)
-    } else {
-        let real_home = std::env::var("HOME").unwrap_or_else(|_| "/root".into());
-        PathBuf::from(real_home).join(".rustup")
-    }
-}
-
-fn cargo_home() -> PathBuf {
-    if let Ok(val) = std::env::var("CARGO_HOME") {
-        PathBuf::from(val)
-    } else {
-        let real_home = std::env::var("HOME").unwrap_or_else(|_| "/root".into());
-        PathBuf::from(real_home).join(".cargo")
-    }
-}
-
 /// Create a minimal Rust project in a temp dir with a gatekeeper test.
 fn create_project_with_gatekeeper(dir: &Path) {
     // Cargo.toml
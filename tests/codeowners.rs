@@ -0,0 +1,226 @@
+// tests/codeowners.rs
+//
+// Story 80: violations name the CODEOWNERS owners of the failing test's file,
+// and `--owner` limits the report to one team's violations.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tdd_ratchet::codeowners::{Codeowners, assign_owners, retain_owned};
+use tdd_ratchet::ratchet::{EvalResult, Violation, Warning};
+use tdd_ratchet::status::StatusFile;
+use tdd_ratchet::test_locations::TestLocation;
+
+fn owners<'a>(codeowners: &'a Codeowners, path: &str) -> &'a [String] {
+    codeowners.owners(Path::new(path))
+}
+
+fn regression(test: &str) -> Violation {
+    Violation::Regression { test: test.into() }
+}
+
+fn located(tests: &[(&str, &str)], violations: Vec<Violation>) -> EvalResult {
+    EvalResult {
+        violations,
+        warnings: Vec::new(),
+        updated: StatusFile::empty(),
+        failure_output: Default::default(),
+        locations: tests
+            .iter()
+            .map(|(test, path)| {
+                let location = TestLocation {
+                    path: PathBuf::from(path),
+                    line: 1,
+                    owners: Vec::new(),
+                };
+                (test.to_string(), location)
+            })
+            .collect(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+#[test]
+fn the_last_matching_line_decides_the_owners() {
+    let codeowners = Codeowners::parse(
+        "# Default owners\n\
+         *       @org/core\n\
+         \n\
+         *.rs    @org/rust @alice\n\
+         /tests/ @org/qa # integration tests\n\
+         /tests/generated.rs\n",
+    );
+
+    assert_eq!(owners(&codeowners, "README.md"), ["@org/core"]);
+    assert_eq!(owners(&codeowners, "src/lib.rs"), ["@org/rust", "@alice"]);
+    assert_eq!(owners(&codeowners, "tests/api.rs"), ["@org/qa"]);
+    assert!(owners(&codeowners, "tests/generated.rs").is_empty());
+}
+
+#[test]
+fn patterns_follow_codeowners_anchoring_and_wildcards() {
+    let codeowners = Codeowners::parse(
+        "backend/      @org/backend\n\
+         /docs/*       @org/docs\n\
+         src/**/api.rs @org/api\n\
+         test_?.rs     @org/single\n",
+    );
+
+    assert_eq!(owners(&codeowners, "backend/tests/a.rs"), ["@org/backend"]);
+    assert_eq!(
+        owners(&codeowners, "crates/backend/src/lib.rs"),
+        ["@org/backend"]
+    );
+    assert_eq!(owners(&codeowners, "docs/guide.md"), ["@org/docs"]);
+    assert!(owners(&codeowners, "docs/api/guide.md").is_empty());
+    assert_eq!(owners(&codeowners, "src/api.rs"), ["@org/api"]);
+    assert_eq!(owners(&codeowners, "src/http/v1/api.rs"), ["@org/api"]);
+    assert!(owners(&codeowners, "lib/src/api.rs").is_empty());
+    assert_eq!(owners(&codeowners, "tests/test_a.rs"), ["@org/single"]);
+    assert!(owners(&codeowners, "tests/test_ab.rs").is_empty());
+}
+
+#[test]
+fn the_github_directory_is_searched_first() {
+    let dir = TestDir::new();
+    fs::create_dir(dir.path().join(".github")).unwrap();
+    fs::write(dir.path().join("CODEOWNERS"), "* @root\n").unwrap();
+    assert_eq!(
+        owners(&Codeowners::load(dir.path()).unwrap(), "a.rs"),
+        ["@root"]
+    );
+
+    fs::write(dir.path().join(".github/CODEOWNERS"), "* @github\n").unwrap();
+    assert_eq!(
+        owners(&Codeowners::load(dir.path()).unwrap(), "a.rs"),
+        ["@github"]
+    );
+
+    fs::remove_file(dir.path().join("CODEOWNERS")).unwrap();
+    fs::remove_file(dir.path().join(".github/CODEOWNERS")).unwrap();
+    assert_eq!(Codeowners::load(dir.path()), None);
+    dir.pass();
+}
+
+#[test]
+fn owner_filter_keeps_owned_and_unattributed_violations() {
+    let mut result = located(
+        &[("backend", "backend/tests/a.rs"), ("web", "web/tests/a.rs")],
+        vec![
+            regression("backend"),
+            regression("web"),
+            regression("unlocated"),
+            Violation::DirtyWorktree {
+                paths: vec![".test-status.json".into()],
+            },
+        ],
+    );
+    result.warnings.push(Warning::RuleWarning {
+        violation: regression("web"),
+    });
+    assign_owners(
+        &mut result,
+        &Codeowners::parse("/backend/ @org/backend\n/web/ @org/web\n"),
+    );
+
+    assert_eq!(retain_owned(&mut result, "@org/backend"), 2);
+    let kept: Vec<String> = result
+        .violations
+        .iter()
+        .map(|violation| format!("{violation:?}"))
+        .collect();
+    assert_eq!(
+        kept,
+        [
+            format!("{:?}", regression("backend")),
+            format!("{:?}", regression("unlocated")),
+            format!(
+                "{:?}",
+                Violation::DirtyWorktree {
+                    paths: vec![".test-status.json".into()],
+                }
+            ),
+        ]
+    );
+    assert!(result.warnings.is_empty());
+}
+
+#[test]
+fn report_names_owners_and_owner_flag_filters_it() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(path.join(".github")).unwrap();
+    fs::write(
+        path.join(".github/CODEOWNERS"),
+        "tests/backend.rs @org/backend\ntests/web.rs @org/web\n",
+    )
+    .unwrap();
+    fs::create_dir(path.join("tests")).unwrap();
+    fs::write(
+        path.join("tests/backend.rs"),
+        "#[test]\nfn backend_works() {}\n",
+    )
+    .unwrap();
+    fs::write(path.join("tests/web.rs"), "#[test]\nfn web_works() {}\n").unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest backend_works ... ok\ntest web_works ... ok\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","backend_works":"passing","web_works":"passing"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest backend_works ... FAILED\ntest web_works ... FAILED\n",
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(
+        out.contains("tests/backend.rs:2 backend_works (owned by @org/backend)"),
+        "{out}"
+    );
+    assert!(
+        out.contains("tests/web.rs:2 web_works (owned by @org/web)"),
+        "{out}"
+    );
+
+    let (code, out) = run_ratchet(path, &["--owner", "@org/backend"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(
+        out.contains("backend_works (owned by @org/backend)"),
+        "{out}"
+    );
+    assert!(!out.contains("web_works (owned"), "{out}");
+    assert!(
+        out.contains("1 violation(s) in files not owned by @org/backend"),
+        "{out}"
+    );
+
+    let (code, out) = run_ratchet(path, &["--format", "sarif"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains(r#""@org/web""#), "{out}");
+    dir.pass();
+}
@@ -1,9 +1,16 @@
 // tests/common/mod.rs
 //
 // Shared test helpers. Imported via `mod common;` in integration test files.
+// Each test binary uses only some of them.
+#![allow(dead_code)]
 
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState};
 
 /// A temp directory that persists on test failure (for debugging) but
 /// cleans up on success. Call `.pass()` at the end of a passing test.
@@ -35,3 +42,155 @@ impl TestDir {
         fs::remove_dir_all(&self.path).ok();
     }
 }
+
+/// Path to the `cargo-ratchet` binary built by [`build_ratchet_binary`].
+pub fn cargo_bin() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("target");
+    path.push("debug");
+    path.push("cargo-ratchet");
+    path
+}
+
+pub fn build_ratchet_binary() {
+    let status = Command::new("cargo")
+        .args(["build"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .status()
+        .unwrap();
+    assert!(status.success(), "Failed to build tdd-ratchet binary");
+}
+
+/// Resolve RUSTUP_HOME for subprocess isolation. When HOME is overridden
+/// for git config isolation, Rustup can't find the toolchain unless we
+/// explicitly pass through the real RUSTUP_HOME / CARGO_HOME.
+pub fn rustup_home() -> PathBuf {
+    if let Ok(val) = std::env::var("RUSTUP_HOME") {
+        PathBuf::from(val)
+    } else {
+        // Default: $HOME/.rustup
+        let real_home = std::env::var("HOME").unwrap_or_else(|_| "/root".into());
+        PathBuf::from(real_home).join(".rustup")
+    }
+}
+
+pub fn cargo_home() -> PathBuf {
+    if let Ok(val) = std::env::var("CARGO_HOME") {
+        PathBuf::from(val)
+    } else {
+        let real_home = std::env::var("HOME").unwrap_or_else(|_| "/root".into());
+        PathBuf::from(real_home).join(".cargo")
+    }
+}
+
+/// Run git in `dir` without any ambient config. Returns its trimmed stdout.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let out = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", dir)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8_lossy(&out.stdout).trim().to_string()
+}
+
+/// Write `json` as the status file and commit everything in `dir`.
+pub fn commit_status(dir: &Path, json: &str, msg: &str) {
+    fs::write(dir.join(".test-status.json"), json).unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-m", msg]);
+}
+
+/// The ratchet binary with `args`, set up to run in `dir` without any
+/// ambient git config or log filter.
+pub fn ratchet_command(dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(cargo_bin());
+    command
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", dir)
+        .env_remove("RUST_LOG");
+    command
+}
+
+/// Run `command`; returns its exit code and stdout and stderr combined.
+pub fn combined_output(command: &mut Command) -> (Option<i32>, String) {
+    let output = command.output().unwrap();
+    let out = String::from_utf8_lossy(&output.stdout).to_string()
+        + &String::from_utf8_lossy(&output.stderr);
+    (output.status.code(), out)
+}
+
+/// Run the ratchet in `dir`; returns the exit code and stdout and stderr
+/// combined.
+pub fn run_ratchet(dir: &Path, args: &[&str]) -> (Option<i32>, String) {
+    combined_output(&mut ratchet_command(dir, args))
+}
+
+/// [`run_ratchet`], keeping stdout and stderr apart.
+pub fn run_ratchet_split(dir: &Path, args: &[&str]) -> (Option<i32>, String, String) {
+    let output = ratchet_command(dir, args).output().unwrap();
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
+}
+
+/// [`run_ratchet`] with `bin` ahead of the usual `PATH`.
+pub fn run_ratchet_with_path(dir: &Path, bin: &Path, args: &[&str]) -> (Option<i32>, String) {
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    combined_output(ratchet_command(dir, args).env("PATH", path))
+}
+
+/// A status file tracking each of `tests` in its state.
+pub fn status(tests: &[(&str, TestState)]) -> StatusFile {
+    StatusFile::new(
+        tests
+            .iter()
+            .map(|(name, state)| (name.to_string(), TestEntry::new(*state)))
+            .collect(),
+    )
+}
+
+/// `status` as committed in `commit`, with default commit info. Set other
+/// info with struct update syntax: `HistorySnapshot { info, ..snapshot(..) }`.
+pub fn snapshot(commit: &str, status: StatusFile) -> HistorySnapshot {
+    HistorySnapshot {
+        commit: commit.to_string(),
+        info: CommitInfo::default(),
+        status,
+    }
+}
+
+/// A result for `name` with no captured output or execution time.
+pub fn result(name: &str, outcome: TestOutcome) -> TestResult {
+    TestResult {
+        name: name.to_string(),
+        outcome,
+        output: None,
+        exec_time_ms: None,
+    }
+}
+
+/// A run of `results` against `tests` committed at `HEAD`, with every other
+/// input left at its default.
+pub fn gathered(tests: &[(&str, TestState)], results: Vec<TestResult>) -> GatheredRun {
+    GatheredRun {
+        status: status(tests).into_tracked_status(),
+        results,
+        ..GatheredRun::default()
+    }
+}
@@ -0,0 +1,108 @@
+// tests/config.rs
+//
+// Story 22: Optional `ratchet.toml` configures which test runners feed the
+// ratchet.
+
+mod common;
+
+use common::TestDir;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RunnerConfig};
+use tdd_ratchet::runner::OutputParser;
+
+#[test]
+fn missing_config_file_means_defaults() {
+    let dir = TestDir::new();
+    let config = RatchetConfig::load(dir.path()).unwrap();
+    assert_eq!(config, RatchetConfig::default());
+    assert!(config.runners.is_empty());
+    dir.pass();
+}
+
+#[test]
+fn command_runner_entries_parse_with_parser_selection() {
+    let dir = TestDir::new();
+    fs::write(
+        dir.path().join("ratchet.toml"),
+        r#"
+[[runners]]
+kind = "nextest"
+
+[[runners]]
+kind = "command"
+command = ["make", "test"]
+parser = "libtest"
+workdir = "legacy"
+"#,
+    )
+    .unwrap();
+
+    let config = RatchetConfig::load(dir.path()).unwrap();
+
+    assert_eq!(
+        config.runners,
+        vec![
+            RunnerConfig::Nextest,
+            RunnerConfig::Command {
+                command: vec!["make".into(), "test".into()],
+                parser: OutputParser::Libtest,
+                workdir: Some("legacy".into()),
+            },
+        ]
+    );
+    dir.pass();
+}
+
+#[test]
+fn unknown_config_keys_are_rejected() {
+    let err = RatchetConfig::parse_from_str("colour = \"red\"\n", Path::new("ratchet.toml"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("ratchet.toml"), "{err}");
+}
+
+#[test]
+fn pytest_runner_entry_parses() {
+    let config = RatchetConfig::parse_from_str(
+        r#"
+[[runners]]
+kind = "nextest"
+
+[[runners]]
+kind = "pytest"
+args = ["python/tests"]
+"#,
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.runners[1],
+        RunnerConfig::Pytest {
+            args: vec!["python/tests".into()],
+            workdir: None,
+        }
+    );
+}
+
+#[test]
+fn go_runner_entry_parses() {
+    let config = RatchetConfig::parse_from_str(
+        r#"
+[[runners]]
+kind = "go"
+workdir = "services/api"
+"#,
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.runners[0],
+        RunnerConfig::Go {
+            args: vec![],
+            workdir: Some("services/api".into()),
+        }
+    );
+}
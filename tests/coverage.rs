@@ -0,0 +1,140 @@
+// tests/coverage.rs
+//
+// Story 78: with `[coverage]` enabled, each promoted test is measured alone
+// with `cargo llvm-cov`, its lines are recorded, and a promotion that covers
+// no line other recorded tests do not already cover is flagged.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::coverage::{
+    CoverageRecord, CoveredLines, coverage_filter, format_ranges, parse_llvm_cov_json, parse_ranges,
+};
+
+/// An llvm-cov export of one file with a region from line `start` to `end`
+/// run `count` times.
+fn export(filename: &str, start: u32, end: u32, count: u64) -> String {
+    format!(
+        r#"{{"data":[{{"files":[{{"filename":"{filename}","segments":[[{start},5,{count},true,true,false],[{end},2,0,false,false,false]]}}],"totals":{{}}}}],"type":"llvm.coverage.json.export","version":"2.0.1"}}"#
+    )
+}
+
+fn lines(file: &str, lines: impl IntoIterator<Item = u32>) -> CoveredLines {
+    [(file.to_string(), lines.into_iter().collect())].into()
+}
+
+#[test]
+fn llvm_cov_export_gives_covered_lines_by_project_file() {
+    let json = r#"{"data":[{"files":[
+        {"filename":"/work/app/src/lib.rs","segments":[
+            [3,20,2,true,true,false],[5,2,0,false,false],
+            [8,1,0,true,true,false],[9,1,0,false,false,false],
+            [12,10,1,true,true,true],[13,1,0,false,false,false],
+            [20,5,1,true,true,false]]},
+        {"filename":"/home/me/.cargo/registry/dep/src/lib.rs","segments":[[1,1,5,true,true,false]]}
+    ]}],"type":"llvm.coverage.json.export","version":"2.0.1"}"#;
+
+    let covered = parse_llvm_cov_json(json, Path::new("/work/app")).unwrap();
+
+    assert_eq!(covered, lines("src/lib.rs", [3, 4, 5, 20]));
+}
+
+#[test]
+fn line_ranges_round_trip() {
+    let set: BTreeSet<u32> = [1, 2, 3, 7, 9, 10].into();
+
+    assert_eq!(format_ranges(&set), "1-3,7,9-10");
+    assert_eq!(parse_ranges("1-3,7,9-10"), set);
+    assert_eq!(parse_ranges("4, x, 6-5"), [4].into());
+}
+
+#[test]
+fn new_lines_ignore_the_test_own_record() {
+    let mut record = CoverageRecord::default();
+    record.insert("other", &lines("src/lib.rs", 1..=10));
+    record.insert("feature", &lines("src/lib.rs", 20..=30));
+
+    assert_eq!(record.new_lines("feature", &lines("src/lib.rs", 5..=12)), 2);
+    assert_eq!(
+        record.new_lines("feature", &lines("src/lib.rs", 20..=21)),
+        2
+    );
+    assert_eq!(record.new_lines("new", &lines("src/lib.rs", 20..=21)), 0);
+    assert_eq!(record.new_lines("new", &lines("src/main.rs", [1])), 1);
+    assert_eq!(record.new_lines("new", &CoveredLines::new()), 0);
+    assert_eq!(record.lines("other"), lines("src/lib.rs", 1..=10));
+}
+
+#[test]
+fn coverage_runs_select_one_test_with_a_filterset() {
+    assert_eq!(
+        coverage_filter("my-crate::api$tests::it_works"),
+        "binary_id(=my-crate::api) & test(=tests::it_works)"
+    );
+    assert_eq!(coverage_filter("it_works"), "test(=it_works)");
+}
+
+#[test]
+fn promotions_covering_no_new_lines_are_flagged_and_recorded() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [coverage]\nenabled = true\ncommand = [\"sh\", \"cover.sh\"]\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("cover.sh"),
+        "case \"$1\" in\n  *vacuous*) cat vacuous.json ;;\n  *) cat useful.json ;;\nesac\n",
+    )
+    .unwrap();
+    fs::write(path.join("vacuous.json"), export("src/lib.rs", 2, 5, 1)).unwrap();
+    fs::write(path.join("useful.json"), export("src/lib.rs", 20, 24, 3)).unwrap();
+    fs::write(
+        path.join(".ratchet-coverage.json"),
+        r#"{"tests":{"existing":{"src/lib.rs":"1-10"},"retired":{"src/lib.rs":"40"}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest existing ... ok\ntest vacuous ... FAILED\ntest useful ... FAILED\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","existing":"passing","vacuous":"pending","useful":"pending"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Pending tests"]);
+
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest existing ... ok\ntest vacuous ... ok\ntest useful ... ok\n",
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+
+    assert_eq!(code, Some(0), "{out}");
+    assert!(
+        out.contains("Promoted test covers no new lines: vacuous (4 lines"),
+        "{out}"
+    );
+    assert!(!out.contains("new lines: useful"), "{out}");
+    let record = CoverageRecord::load(&path.join(".ratchet-coverage.json")).unwrap();
+    assert_eq!(
+        record.tests.keys().collect::<Vec<_>>(),
+        ["existing", "useful", "vacuous"]
+    );
+    assert_eq!(record.tests["useful"]["src/lib.rs"], "20-24");
+    dir.pass();
+}
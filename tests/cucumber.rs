@@ -0,0 +1,179 @@
+// tests/cucumber.rs
+//
+// Story 83: Cucumber scenarios are read from Cucumber JSON reports and
+// tracked by feature and scenario name, so they go through the usual
+// pending → passing transitions.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RunnerConfig};
+use tdd_ratchet::runner::{OutputParser, TestOutcome, cucumber_test_name, parse_cucumber_json};
+use tdd_ratchet::status::{StatusFile, TestState};
+
+/// A one-feature Cucumber JSON report whose `Eating` scenario has a step
+/// with `status`.
+fn report(status: &str) -> String {
+    format!(
+        r#"[{{"uri":"tests/features/animal.feature","name":"Animal  feature","elements":[
+  {{"type":"scenario","name":"Eating","steps":[
+    {{"keyword":"Given ","name":"a hungry cat","result":{{"status":"passed","duration":2000000}}}},
+    {{"keyword":"When ","name":"it eats","result":{{"status":"{status}"}}}}]}}]}}]"#
+    )
+}
+
+#[test]
+fn scenarios_are_named_by_feature_and_scenario() {
+    let results = parse_cucumber_json(&format!("cargo banner\n{}\n", report("passed")));
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "cucumber::Animal feature$Eating");
+    assert_eq!(results[0].outcome, TestOutcome::Passed);
+    assert_eq!(results[0].exec_time_ms, Some(2));
+    assert_eq!(
+        cucumber_test_name("Animal\n  feature", " Eating   fish ", 1),
+        "cucumber::Animal feature$Eating fish"
+    );
+}
+
+#[test]
+fn unimplemented_steps_fail_and_skipped_scenarios_are_ignored() {
+    for status in ["failed", "undefined", "pending", "ambiguous"] {
+        let results = parse_cucumber_json(&report(status));
+        assert_eq!(results[0].outcome, TestOutcome::Failed, "{status}");
+        let output = results[0].output.clone().unwrap();
+        assert_eq!(output, format!("When it eats: {status}"));
+    }
+
+    let skipped = parse_cucumber_json(
+        r#"[{"name":"F","elements":[{"type":"scenario","name":"S","steps":[
+            {"keyword":"Given ","name":"x","result":{"status":"skipped"}}]}]}]"#,
+    );
+    assert_eq!(skipped[0].outcome, TestOutcome::Ignored);
+    assert_eq!(skipped[0].output, None);
+}
+
+#[test]
+fn backgrounds_and_hooks_count_towards_their_scenario() {
+    let results = parse_cucumber_json(
+        r#"[{"uri":"a.feature","name":"","elements":[
+  {"type":"background","name":"","steps":[
+    {"keyword":"Given ","name":"a database","result":{"status":"failed","error_message":"connection refused"}}]},
+  {"type":"scenario","name":"First","steps":[
+    {"keyword":"Then ","name":"it works","result":{"status":"skipped"}}]},
+  {"type":"scenario","name":"Second","steps":[
+    {"keyword":"Then ","name":"it works","result":{"status":"passed"}}],
+   "after":[{"result":{"status":"failed","error_message":"cleanup"}}]}]}]"#,
+    );
+
+    assert_eq!(results[0].name, "cucumber::a.feature$First");
+    assert_eq!(results[0].outcome, TestOutcome::Failed);
+    assert_eq!(
+        results[0].output.as_deref(),
+        Some("Given a database: failed\nconnection refused")
+    );
+    assert_eq!(results[1].outcome, TestOutcome::Failed);
+    assert_eq!(results[1].output.as_deref(), Some("hook: failed\ncleanup"));
+}
+
+#[test]
+fn outline_rows_with_the_same_name_are_numbered() {
+    let row = r#"{"type":"scenario","name":"Eating <food>","steps":[
+        {"keyword":"Given ","name":"x","result":{"status":"passed"}}]}"#;
+    let results = parse_cucumber_json(&format!(
+        r#"[{{"name":"Animal","elements":[{row},{row},{row}]}}]"#
+    ));
+
+    let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "cucumber::Animal$Eating <food>",
+            "cucumber::Animal$Eating <food> #2",
+            "cucumber::Animal$Eating <food> #3",
+        ]
+    );
+}
+
+#[test]
+fn cucumber_runner_and_parser_entries_parse() {
+    let config = RatchetConfig::parse_from_str(
+        r#"
+[[runners]]
+kind = "cucumber"
+
+[[runners]]
+kind = "command"
+command = ["cat", "cucumber.json"]
+parser = "cucumber-json"
+"#,
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.runners[0],
+        RunnerConfig::Cucumber {
+            args: vec![],
+            workdir: None,
+        }
+    );
+    assert!(matches!(
+        config.runners[1],
+        RunnerConfig::Command {
+            parser: OutputParser::CucumberJson,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn a_new_scenario_is_pending_until_its_steps_pass() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"cucumber.json\"]\nparser = \"cucumber-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\n",
+    )
+    .unwrap();
+    fs::write(path.join("cucumber.json"), "[]\n").unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let scenario = "cucumber::Animal feature$Eating";
+    fs::write(path.join("cucumber.json"), report("undefined")).unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[scenario].state(), TestState::Pending);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add eating scenario"]);
+
+    fs::write(path.join("cucumber.json"), report("passed")).unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[scenario].state(), TestState::Passing);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Implement eating"]);
+
+    fs::write(path.join("cucumber.json"), report("failed")).unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains(scenario), "{out}");
+    assert!(out.contains("When it eats: failed"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,379 @@
+// tests/daemon.rs
+//
+// Story 57: `cargo ratchet daemon` answers JSON-RPC requests (`evaluate`,
+// `status`, `logTest`) for editor integrations, caches history between
+// requests, and notifies clients when a run changes test states, including
+// runs started by a file change under `--watch`.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, git, status};
+use serde_json::{Value, json};
+use std::cell::Cell;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+use tdd_ratchet::Ratchet;
+use tdd_ratchet::daemon::{CachedHistory, Daemon, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR};
+use tdd_ratchet::error::RatchetError;
+use tdd_ratchet::history::{CommitInfo, HistoryProvider, HistorySnapshot};
+use tdd_ratchet::partition::RecordedRunner;
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::status::{StatusFile, StatusStore, TestState, WorkingTreeInstructions};
+
+/// History whose `HEAD` tracks the gatekeeper as passing and `feature` as
+/// pending. Counts how often snapshots are read.
+#[derive(Default)]
+struct FakeHistory {
+    reads: Cell<usize>,
+}
+
+impl HistoryProvider for FakeHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        Ok(Some(status(&[
+            ("tdd_ratchet_gatekeeper", TestState::Passing),
+            ("feature", TestState::Pending),
+        ])))
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        self.reads.set(self.reads.get() + 1);
+        Ok(vec![HistorySnapshot {
+            commit: "c1".into(),
+            info: CommitInfo {
+                author: "Ada".into(),
+                ..CommitInfo::default()
+            },
+            status: self.head_status()?.unwrap(),
+        }])
+    }
+}
+
+struct DiscardStore;
+
+impl StatusStore for DiscardStore {
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError> {
+        Ok(WorkingTreeInstructions::default())
+    }
+
+    fn save(&self, _status: &StatusFile) -> Result<(), RatchetError> {
+        Ok(())
+    }
+}
+
+/// A daemon over `FakeHistory` whose test runs pass every test.
+fn daemon(dir: &Path) -> Daemon<'static> {
+    let project_dir = dir.to_path_buf();
+    Daemon::new(
+        dir,
+        FakeHistory::default(),
+        Box::new(move |history| {
+            let results = ["tdd_ratchet_gatekeeper", "feature"]
+                .into_iter()
+                .map(|name| TestResult {
+                    name: name.to_string(),
+                    outcome: TestOutcome::Passed,
+                    output: None,
+                    exec_time_ms: None,
+                })
+                .collect();
+            let ratchet = Ratchet::builder(&project_dir)
+                .runner(RecordedRunner::new(results))
+                .history(history)
+                .status_store(DiscardStore)
+                .build();
+            let gathered = ratchet.gather()?;
+            let result = gathered.evaluate();
+            ratchet.save(&result)?;
+            Ok((gathered.status, result))
+        }),
+    )
+}
+
+fn request(id: u64, method: &str, params: Value) -> String {
+    json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}).to_string()
+}
+
+#[test]
+fn evaluate_reports_and_notifies_state_changes() {
+    let dir = TestDir::new();
+    let mut daemon = daemon(dir.path());
+
+    let messages = daemon.handle(&request(1, "evaluate", Value::Null));
+    assert_eq!(messages.len(), 2, "{messages:?}");
+    let result = &messages[0]["result"];
+    assert_eq!(messages[0]["id"], 1);
+    assert_eq!(result["ok"], true);
+    assert_eq!(result["passing"], 2);
+    assert_eq!(result["violations"], json!([]));
+    assert!(
+        result["report"]
+            .as_str()
+            .unwrap()
+            .contains("tdd-ratchet: ok")
+    );
+    assert_eq!(messages[1]["method"], "stateChanged");
+    assert_eq!(
+        messages[1]["params"]["changes"],
+        json!([{"test": "feature", "from": "pending", "to": "passing"}])
+    );
+
+    let messages = daemon.handle(&request(2, "evaluate", Value::Null));
+    assert_eq!(
+        messages.len(),
+        1,
+        "no change, no notification: {messages:?}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn status_reflects_the_last_evaluation() {
+    let dir = TestDir::new();
+    let mut daemon = daemon(dir.path());
+
+    let before = daemon.handle(&request(1, "status", Value::Null));
+    assert_eq!(before[0]["result"]["tests"]["feature"], "pending");
+
+    daemon.handle(&request(2, "evaluate", Value::Null));
+    let after = daemon.handle(&request(3, "status", Value::Null));
+    assert_eq!(after[0]["result"]["tests"]["feature"], "passing");
+    dir.pass();
+}
+
+#[test]
+fn log_test_lists_committed_changes() {
+    let dir = TestDir::new();
+    let mut daemon = daemon(dir.path());
+
+    let messages = daemon.handle(&request(1, "logTest", json!({"test": "feature"})));
+    let changes = &messages[0]["result"]["changes"];
+    assert_eq!(changes[0]["commit"], "c1");
+    assert_eq!(changes[0]["author"], "Ada");
+    assert_eq!(changes[0]["from"], Value::Null);
+    assert_eq!(changes[0]["to"], "pending");
+
+    let messages = daemon.handle(&request(2, "logTest", json!({})));
+    assert_eq!(messages[0]["error"]["code"], INVALID_PARAMS);
+    dir.pass();
+}
+
+#[test]
+fn malformed_and_unknown_requests_get_errors() {
+    let dir = TestDir::new();
+    let mut daemon = daemon(dir.path());
+
+    let messages = daemon.handle("{not json");
+    assert_eq!(messages[0]["error"]["code"], PARSE_ERROR);
+    assert_eq!(messages[0]["id"], Value::Null);
+
+    let messages = daemon.handle(&request(7, "fly", Value::Null));
+    assert_eq!(messages[0]["id"], 7);
+    assert_eq!(messages[0]["error"]["code"], METHOD_NOT_FOUND);
+
+    let notification = json!({"jsonrpc": "2.0", "method": "status"}).to_string();
+    assert!(daemon.handle(&notification).is_empty());
+    dir.pass();
+}
+
+#[test]
+fn serve_answers_one_line_per_message() {
+    let dir = TestDir::new();
+    let mut daemon = daemon(dir.path());
+    let input = format!(
+        "{}\n\n{}\n",
+        request(1, "status", Value::Null),
+        request(2, "evaluate", Value::Null)
+    );
+    let mut output = Vec::new();
+
+    daemon.serve(input.as_bytes(), &mut output).unwrap();
+
+    let lines: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3, "{lines:?}");
+    assert_eq!(lines[0]["id"], 1);
+    assert_eq!(lines[1]["id"], 2);
+    assert_eq!(lines[2]["method"], "stateChanged");
+    dir.pass();
+}
+
+#[test]
+fn history_is_cached_until_head_moves() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    git(path, &["commit", "--allow-empty", "-m", "first"]);
+    let inner = FakeHistory::default();
+    let history = CachedHistory::new(&inner, path);
+
+    history.snapshots().unwrap();
+    history.snapshots().unwrap();
+    assert_eq!(inner.reads.get(), 1);
+    git(path, &["commit", "--allow-empty", "-m", "second"]);
+    history.snapshots().unwrap();
+    assert_eq!(inner.reads.get(), 2);
+
+    let outside = TestDir::new();
+    let inner = FakeHistory::default();
+    let uncached = CachedHistory::new(&inner, outside.path());
+    uncached.snapshots().unwrap();
+    uncached.snapshots().unwrap();
+    assert_eq!(
+        inner.reads.get(),
+        2,
+        "nothing is cached outside a repository"
+    );
+    outside.pass();
+    dir.pass();
+}
+
+/// A committed project whose command runner reads `results.txt`, with
+/// `extra` appended to its `ratchet.toml`.
+fn command_project(path: &Path, status: &str, results: &str, extra: &str) {
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        format!(
+            "{extra}[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n"
+        ),
+    )
+    .unwrap();
+    fs::write(path.join("results.txt"), results).unwrap();
+    fs::write(path.join(".test-status.json"), status).unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+}
+
+fn spawn_daemon(path: &Path, args: &[&str]) -> Child {
+    Command::new(cargo_bin())
+        .arg("daemon")
+        .args(args)
+        .current_dir(path)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+fn output_lines(child: Child) -> (String, Vec<Value>) {
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    (stdout, lines)
+}
+
+#[test]
+fn daemon_command_serves_stdio() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    command_project(
+        path,
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... FAILED\n",
+        "",
+    );
+
+    let mut child = spawn_daemon(path, &[]);
+    writeln!(
+        child.stdin.take().unwrap(),
+        "{}\n{}",
+        request(1, "evaluate", Value::Null),
+        request(2, "logTest", json!({"test": "feature"}))
+    )
+    .unwrap();
+    let (stdout, lines) = output_lines(child);
+
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert_eq!(lines[0]["result"]["ok"], false, "{stdout}");
+    assert_eq!(
+        lines[0]["result"]["violations"][0]["code"], "RATCHET002",
+        "{stdout}"
+    );
+    assert_eq!(
+        lines[1]["result"]["changes"][0]["to"], "passing",
+        "{stdout}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn daemon_evaluations_run_the_configured_hooks() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    command_project(
+        path,
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"pending"}}"#,
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+        "[hooks]\non-promotion = [\"sh\", \"-c\", \"cat > promoted.json\"]\n\n",
+    );
+
+    let mut child = spawn_daemon(path, &[]);
+    writeln!(
+        child.stdin.take().unwrap(),
+        "{}",
+        request(1, "evaluate", Value::Null)
+    )
+    .unwrap();
+    let (stdout, lines) = output_lines(child);
+
+    assert_eq!(lines[0]["result"]["ok"], true, "{stdout}");
+    let event: Value =
+        serde_json::from_str(&fs::read_to_string(path.join("promoted.json")).unwrap()).unwrap();
+    assert_eq!(event["tests"], json!(["feature"]));
+    dir.pass();
+}
+
+#[test]
+fn watch_mode_re_evaluates_and_notifies_when_files_change() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    command_project(
+        path,
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"pending"}}"#,
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... FAILED\n",
+        "",
+    );
+
+    let mut child = spawn_daemon(path, &["--watch"]);
+    let mut stdin = child.stdin.take().unwrap();
+    writeln!(stdin, "{}", request(1, "evaluate", Value::Null)).unwrap();
+    thread::sleep(Duration::from_secs(2));
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+    )
+    .unwrap();
+    thread::sleep(Duration::from_secs(3));
+    drop(stdin);
+    let (stdout, lines) = output_lines(child);
+
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert_eq!(lines[0]["id"], 1, "{stdout}");
+    assert_eq!(lines[1]["method"], "stateChanged", "{stdout}");
+    assert_eq!(
+        lines[1]["params"]["changes"],
+        json!([{"test": "feature", "from": "pending", "to": "passing"}])
+    );
+    dir.pass();
+}
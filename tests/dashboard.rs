@@ -0,0 +1,74 @@
+// tests/dashboard.rs
+//
+// Story 17: The interactive dashboard groups tracked tests by state and
+// summarizes pending ages and recent promotions from git history.
+
+mod common;
+
+use common::{snapshot, status};
+use tdd_ratchet::dashboard::{build_dashboard, render_dashboard};
+use tdd_ratchet::status::TestState;
+
+#[test]
+fn pending_age_counts_the_current_pending_streak() {
+    let snapshots = vec![
+        snapshot("c1", status(&[("done", TestState::Pending)])),
+        snapshot(
+            "c2",
+            status(&[("done", TestState::Passing), ("wip", TestState::Pending)]),
+        ),
+        snapshot(
+            "c3",
+            status(&[("done", TestState::Passing), ("wip", TestState::Pending)]),
+        ),
+    ];
+    let status = snapshots[2].status.tracked_status();
+
+    let dashboard = build_dashboard(&status, &snapshots, 10);
+
+    assert_eq!(dashboard.passing, vec!["done".to_string()]);
+    assert_eq!(dashboard.pending.len(), 1);
+    assert_eq!(dashboard.pending[0].name, "wip");
+    assert_eq!(dashboard.pending[0].since_commit.as_deref(), Some("c2"));
+    assert_eq!(dashboard.pending[0].commits_pending, 2);
+}
+
+#[test]
+fn recent_promotions_are_listed_newest_first() {
+    let snapshots = vec![
+        snapshot(
+            "c1",
+            status(&[
+                ("first", TestState::Pending),
+                ("second", TestState::Pending),
+            ]),
+        ),
+        snapshot(
+            "c2",
+            status(&[
+                ("first", TestState::Passing),
+                ("second", TestState::Pending),
+            ]),
+        ),
+        snapshot(
+            "c3",
+            status(&[
+                ("first", TestState::Passing),
+                ("second", TestState::Passing),
+            ]),
+        ),
+    ];
+    let status = snapshots[2].status.tracked_status();
+
+    let dashboard = build_dashboard(&status, &snapshots, 10);
+    let promoted: Vec<(&str, &str)> = dashboard
+        .recent_promotions
+        .iter()
+        .map(|p| (p.test.as_str(), p.commit.as_str()))
+        .collect();
+    assert_eq!(promoted, vec![("second", "c3"), ("first", "c2")]);
+
+    let rendered = render_dashboard(&dashboard);
+    assert!(rendered.contains("Passing (2)"), "{rendered}");
+    assert!(rendered.contains("↑ second  (c3)"), "{rendered}");
+}
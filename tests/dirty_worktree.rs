@@ -0,0 +1,188 @@
+// tests/dirty_worktree.rs
+//
+// Story 68: Uncommitted changes to `.test-status.json` or test files are
+// reported with the exact paths, as a warning by default and as an error
+// with `--strict`.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{Rule, RuleLevel, RulesConfig};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::history::{GitHistory, HistoryProvider};
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::{Violation, Warning};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::TestState;
+
+/// A committed project whose tests are read from `results.txt`.
+fn committed_project(dir: &Path) {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    fs::write(
+        dir.join("ratchet.toml"),
+        "[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    fs::create_dir_all(dir.join("tests")).unwrap();
+    fs::write(dir.join("tests/feature.rs"), "#[test]\nfn feature() {}\n").unwrap();
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/lib.rs"), "").unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-m", "Initial project"]);
+}
+
+fn gathered(dirty_paths: &[&str], level: Option<RuleLevel>) -> GatheredRun {
+    GatheredRun {
+        rules: RulesConfig {
+            dirty_worktree: level,
+            ..RulesConfig::default()
+        },
+        dirty_paths: dirty_paths.iter().map(|path| path.to_string()).collect(),
+        ..common::gathered(
+            &[("tdd_ratchet_gatekeeper", TestState::Passing)],
+            vec![result("tdd_ratchet_gatekeeper", TestOutcome::Passed)],
+        )
+    }
+}
+
+#[test]
+fn dirty_worktree_warns_by_default() {
+    assert_eq!(
+        RulesConfig::default().level(Rule::DirtyWorktree),
+        RuleLevel::Warn
+    );
+    let result = gathered(&[".test-status.json"], None).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(
+        matches!(
+            result.warnings.as_slice(),
+            [Warning::RuleWarning {
+                violation: Violation::DirtyWorktree { .. }
+            }]
+        ),
+        "{:?}",
+        result.warnings
+    );
+}
+
+#[test]
+fn denied_dirty_worktree_is_a_violation() {
+    let result = gathered(&["tests/feature.rs"], Some(RuleLevel::Deny)).evaluate();
+
+    assert!(
+        matches!(
+            result.violations.as_slice(),
+            [Violation::DirtyWorktree { paths }] if paths == &["tests/feature.rs"]
+        ),
+        "{:?}",
+        result.violations
+    );
+}
+
+#[test]
+fn clean_worktree_reports_nothing() {
+    let result = gathered(&[], Some(RuleLevel::Deny)).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+}
+
+#[test]
+fn report_lists_each_dirty_path() {
+    let result = gathered(
+        &[".test-status.json", "tests/feature.rs"],
+        Some(RuleLevel::Deny),
+    )
+    .evaluate();
+    let report = format_report(&result);
+
+    assert!(
+        report.contains("uncommitted status or test files"),
+        "{report}"
+    );
+    assert!(
+        report.contains("Uncommitted changes: .test-status.json"),
+        "{report}"
+    );
+    assert!(
+        report.contains("Uncommitted changes: tests/feature.rs"),
+        "{report}"
+    );
+}
+
+#[test]
+fn git_history_lists_only_dirty_status_and_test_files() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+    fs::write(path.join(".test-status.json"), r#"{"tests":{}}"#).unwrap();
+    fs::write(path.join("tests/feature.rs"), "#[test]\nfn feature() { }\n").unwrap();
+    fs::create_dir_all(path.join("tests/fixtures")).unwrap();
+    fs::write(path.join("tests/fixtures/input.txt"), "new").unwrap();
+    fs::write(path.join("src/parser_tests.rs"), "").unwrap();
+    fs::write(path.join("src/lib.rs"), "pub fn changed() {}\n").unwrap();
+    fs::write(path.join("notes.txt"), "untracked").unwrap();
+
+    let mut dirty = GitHistory::new(path).dirty_paths().unwrap();
+    dirty.sort();
+
+    assert_eq!(
+        dirty,
+        [
+            ".test-status.json",
+            "src/parser_tests.rs",
+            "tests/feature.rs",
+            "tests/fixtures/input.txt",
+        ]
+    );
+    dir.pass();
+}
+
+#[test]
+fn clean_repository_has_no_dirty_paths() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+
+    assert!(GitHistory::new(path).dirty_paths().unwrap().is_empty());
+    dir.pass();
+}
+
+#[test]
+fn strict_flag_fails_a_dirty_run() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+    fs::write(path.join("tests/feature.rs"), "#[test]\nfn feature() { }\n").unwrap();
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(
+        out.contains("[RATCHET022] DirtyWorktree: tests/feature.rs: uncommitted changes"),
+        "{out}"
+    );
+
+    let (code, out) = run_ratchet(path, &["--strict"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(
+        out.contains("Uncommitted changes: tests/feature.rs"),
+        "{out}"
+    );
+    dir.pass();
+}
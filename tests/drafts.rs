@@ -0,0 +1,295 @@
+// tests/drafts.rs
+//
+// Story 101: sketched tests with `todo!()` bodies can be drafts. A draft is
+// not pending, so it is left out of summaries and the pending limit, and it
+// stays a draft whatever its outcome until `cargo ratchet promote-drafts`
+// moves it to pending.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::ratchet::{EvalResult, Warning, evaluate};
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, WorkingTreeInstructions};
+
+const GATEKEEPER: &str = "app::tests$tdd_ratchet_gatekeeper";
+const SKETCH: &str = "app::tests$parses_headers";
+const OTHER: &str = "app::tests$parses_body";
+
+fn committed(sketch: TestState) -> StatusFile {
+    StatusFile::new(
+        [
+            (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+            (SKETCH.to_string(), TestEntry::new(sketch)),
+        ]
+        .into_iter()
+        .collect(),
+    )
+}
+
+fn drafts(tests: &[&str]) -> WorkingTreeInstructions {
+    WorkingTreeInstructions {
+        drafts: tests.iter().map(|test| test.to_string()).collect(),
+        ..WorkingTreeInstructions::default()
+    }
+}
+
+fn run(
+    sketch: TestState,
+    instructions: &WorkingTreeInstructions,
+    results: &[TestResult],
+) -> EvalResult {
+    let mut all = vec![result(GATEKEEPER, TestOutcome::Passed)];
+    all.extend_from_slice(results);
+    evaluate(&committed(sketch).tracked_status(), instructions, &all, &[])
+}
+
+#[test]
+fn drafts_stay_drafts_whatever_their_outcome() {
+    let failed = run(
+        TestState::Pending,
+        &drafts(&[SKETCH]),
+        &[result(SKETCH, TestOutcome::Failed)],
+    );
+    assert!(failed.violations.is_empty(), "{:?}", failed.violations);
+    assert_eq!(failed.updated.tests[SKETCH].state(), TestState::Draft);
+
+    let passed = run(
+        TestState::Draft,
+        &drafts(&[SKETCH]),
+        &[result(SKETCH, TestOutcome::Passed)],
+    );
+    assert!(passed.violations.is_empty(), "{:?}", passed.violations);
+    assert_eq!(passed.updated.tests[SKETCH].state(), TestState::Draft);
+    assert!(matches!(
+        passed.warnings.as_slice(),
+        [Warning::DraftPassing { test }] if test == SKETCH
+    ));
+    let text = format_report(&passed);
+    assert!(text.contains("Draft test passes"), "{text}");
+    assert!(text.contains("cargo ratchet promote-drafts"), "{text}");
+}
+
+#[test]
+fn drafts_promoted_in_the_working_tree_are_pending() {
+    let promoted = run(
+        TestState::Draft,
+        &WorkingTreeInstructions::default(),
+        &[result(SKETCH, TestOutcome::Failed)],
+    );
+    assert!(promoted.violations.is_empty(), "{:?}", promoted.violations);
+    assert_eq!(promoted.updated.tests[SKETCH].state(), TestState::Pending);
+
+    let new_draft = run(
+        TestState::Pending,
+        &drafts(&[OTHER]),
+        &[
+            result(SKETCH, TestOutcome::Failed),
+            result(OTHER, TestOutcome::Failed),
+        ],
+    );
+    assert_eq!(new_draft.updated.tests[OTHER].state(), TestState::Draft);
+    assert_eq!(new_draft.updated.tests[SKETCH].state(), TestState::Pending);
+
+    let passing = StatusFile::new(
+        [(SKETCH.to_string(), TestEntry::new(TestState::Passing))]
+            .into_iter()
+            .collect(),
+    );
+    let kept = evaluate(
+        &passing.tracked_status(),
+        &drafts(&[SKETCH]),
+        &[
+            result(GATEKEEPER, TestOutcome::Passed),
+            result(SKETCH, TestOutcome::Passed),
+        ],
+        &[],
+    );
+    assert_eq!(kept.updated.tests[SKETCH].state(), TestState::Passing);
+}
+
+#[test]
+fn drafts_are_not_counted_as_pending() {
+    let result = run(
+        TestState::Draft,
+        &drafts(&[SKETCH]),
+        &[result(SKETCH, TestOutcome::Failed)],
+    );
+    let text = format_report(&result);
+    assert!(
+        text.contains("tdd-ratchet: ok (1 passing, 1 draft)"),
+        "{text}"
+    );
+    assert!(!text.contains(SKETCH), "{text}");
+}
+
+fn state(path: &Path, test: &str) -> TestState {
+    StatusFile::load(&path.join(".test-status.json"))
+        .unwrap()
+        .tests[test]
+        .state()
+}
+
+#[test]
+fn sketches_are_drafted_then_promoted_to_pending() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\nmax-pending = 0\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        format!("test {GATEKEEPER} ... ok\n"),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    // Two skeletons: drafted before their first run, they stay clear of
+    // the pending limit.
+    let (code, out) = run_ratchet(path, &["draft", SKETCH, OTHER]);
+    assert_eq!(code, Some(0), "{out}");
+    fs::write(
+        path.join("results.txt"),
+        format!("test {GATEKEEPER} ... ok\ntest {SKETCH} ... FAILED\ntest {OTHER} ... FAILED\n"),
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("ok (1 passing, 2 drafts)"), "{out}");
+    assert_eq!(state(path, SKETCH), TestState::Draft);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Sketch parser tests"]);
+
+    let (code, out) = run_ratchet(path, &["promote-drafts", "app::tests$missing"]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("is not a draft"), "{out}");
+    let (code, out) = run_ratchet(path, &["draft", GATEKEEPER]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("is passing"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["promote-drafts"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("promoted"), "{out}");
+    assert_eq!(state(path, SKETCH), TestState::Pending);
+    assert_eq!(state(path, OTHER), TestState::Pending);
+    let (code, out) = run_ratchet(path, &["promote-drafts"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("no draft tests"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn a_draft_that_never_failed_cannot_be_promoted() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    // The sketch passes from the start: it asserts nothing.
+    fs::write(
+        path.join("results.txt"),
+        format!("test {GATEKEEPER} ... ok\ntest {SKETCH} ... ok\n"),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+    let (code, out) = run_ratchet(path, &["draft", SKETCH]);
+    assert_eq!(code, Some(0), "{out}");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("Draft test passes"), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Sketch a test"]);
+
+    let (code, out) = run_ratchet(path, &["promote-drafts", SKETCH]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("has not been seen failing"), "{out}");
+    let (code, out) = run_ratchet(path, &["promote-drafts"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("not promoting"), "{out}");
+    assert_eq!(state(path, SKETCH), TestState::Draft);
+
+    // Moving it to pending by hand lets the next run pass it, but history
+    // shows it went from draft to passing.
+    fs::write(
+        path.join(".test-status.json"),
+        format!(r#"{{"tests":{{"{GATEKEEPER}":"passing","{SKETCH}":"pending"}}}}"#),
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(state(path, SKETCH), TestState::Passing);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Promote the sketch"]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("RATCHET004"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn a_draft_that_fails_is_promoted_and_may_then_pass() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        format!("test {GATEKEEPER} ... ok\ntest {SKETCH} ... FAILED\n"),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+    let (code, out) = run_ratchet(path, &["draft", SKETCH]);
+    assert_eq!(code, Some(0), "{out}");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Sketch a test"]);
+
+    let (code, out) = run_ratchet(path, &["promote-drafts", SKETCH]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(state(path, SKETCH), TestState::Pending);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Promote the sketch"]);
+
+    fs::write(
+        path.join("results.txt"),
+        format!("test {GATEKEEPER} ... ok\ntest {SKETCH} ... ok\n"),
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Implement it"]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(state(path, SKETCH), TestState::Passing);
+    dir.pass();
+}
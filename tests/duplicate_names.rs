@@ -0,0 +1,129 @@
+// tests/duplicate_names.rs
+//
+// Story 110: names without a binary prefix, such as plain libtest output,
+// collide when two test binaries have a test at the same path, and the two
+// tests would share one status entry. A run reporting a name more than once
+// fails with its own violation, which suggests switching to full test IDs.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, gathered, git, run_ratchet};
+use std::fs;
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::{Violation, duplicate_test_names};
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::status::TestState;
+
+const GATEKEEPER: &str = "tdd_ratchet_gatekeeper";
+
+fn test_result(name: &str, outcome: TestOutcome) -> TestResult {
+    TestResult {
+        name: name.to_string(),
+        outcome,
+        output: None,
+        exec_time_ms: None,
+    }
+}
+
+fn run(results: Vec<TestResult>) -> GatheredRun {
+    gathered(
+        &[
+            (GATEKEEPER, TestState::Passing),
+            ("tests::parses", TestState::Passing),
+        ],
+        results,
+    )
+}
+
+#[test]
+fn names_reported_more_than_once_are_found() {
+    let results = vec![
+        test_result("tests::parses", TestOutcome::Passed),
+        test_result("tests::renders", TestOutcome::Passed),
+        test_result("tests::parses", TestOutcome::Failed),
+        test_result("tests::parses", TestOutcome::Passed),
+        test_result("a$tests::renders", TestOutcome::Passed),
+    ];
+    assert_eq!(
+        duplicate_test_names(&results),
+        vec![("tests::parses".to_string(), 3)]
+    );
+    assert!(duplicate_test_names(&results[1..2]).is_empty());
+}
+
+#[test]
+fn duplicate_names_fail_the_run_and_suggest_full_ids() {
+    let result = run(vec![
+        test_result(GATEKEEPER, TestOutcome::Passed),
+        test_result("tests::parses", TestOutcome::Passed),
+        test_result("tests::parses", TestOutcome::Passed),
+    ])
+    .evaluate();
+
+    assert!(result.violations.iter().any(|v| matches!(
+        v,
+        Violation::DuplicateTestName { test, count: 2 } if test == "tests::parses"
+    )));
+    let report = format_report(&result);
+    assert!(report.contains("duplicate test names"), "{report}");
+    assert!(
+        report.contains("Reported 2 times: tests::parses"),
+        "{report}"
+    );
+    assert!(report.contains("full test IDs"), "{report}");
+    assert!(report.contains("RATCHET025"), "{report}");
+
+    let result = run(vec![
+        test_result(GATEKEEPER, TestOutcome::Passed),
+        test_result("a$tests::parses", TestOutcome::Passed),
+        test_result("b$tests::parses", TestOutcome::Passed),
+    ])
+    .evaluate();
+    assert!(
+        !result
+            .violations
+            .iter()
+            .any(|v| matches!(v, Violation::DuplicateTestName { .. }))
+    );
+}
+
+#[test]
+fn plain_libtest_names_shared_by_two_binaries_are_reported() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        format!(r#"{{"tests":{{"{GATEKEEPER}":"passing","tests::parses":"passing"}}}}"#),
+    )
+    .unwrap();
+    // Two binaries, each with a `tests::parses`; one of them fails.
+    fs::write(
+        path.join("results.txt"),
+        "running 2 tests\n\
+         test tdd_ratchet_gatekeeper ... ok\n\
+         test tests::parses ... ok\n\
+         \n\
+         running 1 test\n\
+         test tests::parses ... FAILED\n",
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("duplicate test names"), "{out}");
+    assert!(out.contains("Reported 2 times: tests::parses"), "{out}");
+    dir.pass();
+}
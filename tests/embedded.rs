@@ -0,0 +1,215 @@
+// tests/embedded.rs
+//
+// Story 98: firmware tests run on hardware with defmt-test and `probe-rs run`
+// are parsed from their logs and go through the same ratchet as host tests.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet_with_path};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tdd_ratchet::config::{RatchetConfig, RunnerConfig};
+use tdd_ratchet::runner::{OutputParser, TestOutcome, parse_defmt_test_output};
+use tdd_ratchet::status::{StatusFile, TestState};
+
+/// probe-rs output for the `integration` test binary, whose `reads_sensor`
+/// test panics when `fails`.
+fn output(fails: bool) -> String {
+    let second = if fails {
+        "0.000004 ERROR panicked at 'sensor not responding', tests/integration.rs:21:9\n\
+         └─ panic_probe::print_defmt::print @ src/lib.rs:104\n"
+    } else {
+        "0.000004 INFO  (3/3) ignoring `needs_radio`...\n\
+         └─ integration::tests::__defmt_test_entry @ tests/integration.rs:24\n\
+         0.000005 INFO  all tests passed!\n\
+         └─ integration::tests::__defmt_test_entry @ tests/integration.rs:8\n"
+    };
+    format!(
+        "      Erasing ✔ [00:00:00] [####] 8.00 KiB/8.00 KiB\n\
+         \x20 Programming ✔ [00:00:00] [####] 8.00 KiB/8.00 KiB\n\
+         0.000000 INFO  (1/3) running `boots`...\n\
+         └─ integration::tests::__defmt_test_entry @ tests/integration.rs:12\n\
+         0.000001 INFO  (2/3) running `reads_sensor`...\n\
+         └─ integration::tests::__defmt_test_entry @ tests/integration.rs:18\n\
+         {second}"
+    )
+}
+
+#[test]
+fn tests_are_named_by_target_and_module() {
+    let results = parse_defmt_test_output(&output(false));
+
+    let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "embedded::integration$tests::boots",
+            "embedded::integration$tests::reads_sensor",
+            "embedded::integration$tests::needs_radio",
+        ]
+    );
+    let outcomes: Vec<TestOutcome> = results.iter().map(|r| r.outcome).collect();
+    assert_eq!(
+        outcomes,
+        [
+            TestOutcome::Passed,
+            TestOutcome::Passed,
+            TestOutcome::Ignored
+        ]
+    );
+
+    let bare = parse_defmt_test_output("INFO  (1/1) running `boots`...\nINFO  all tests passed!\n");
+    assert_eq!(bare[0].name, "embedded$boots");
+    assert_eq!(bare[0].outcome, TestOutcome::Passed);
+}
+
+#[test]
+fn the_test_running_when_output_stops_failed() {
+    let results = parse_defmt_test_output(&output(true));
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].outcome, TestOutcome::Passed);
+    assert_eq!(results[1].outcome, TestOutcome::Failed);
+    assert_eq!(
+        results[1].output.as_deref(),
+        Some("0.000004 ERROR panicked at 'sensor not responding', tests/integration.rs:21:9")
+    );
+
+    // With --no-fail-fast the next binary starts counting again.
+    let results = parse_defmt_test_output(
+        "INFO  (1/2) running `a`...\n\
+         INFO  (2/2) running `b`...\n\
+         ERROR panicked\n\
+         INFO  (1/1) running `c`...\n\
+         INFO  all tests passed!\n",
+    );
+    let outcomes: Vec<TestOutcome> = results.iter().map(|r| r.outcome).collect();
+    assert_eq!(
+        outcomes,
+        [
+            TestOutcome::Passed,
+            TestOutcome::Failed,
+            TestOutcome::Passed
+        ]
+    );
+    assert_eq!(results[1].output.as_deref(), Some("ERROR panicked"));
+}
+
+#[test]
+fn defmt_test_runner_and_parser_entries_parse() {
+    let config = RatchetConfig::parse_from_str(
+        r#"
+[[runners]]
+kind = "defmt-test"
+args = ["--test", "integration"]
+workdir = "firmware"
+
+[[runners]]
+kind = "command"
+command = ["probe-rs", "run", "--chip", "nRF52840_xxAA", "target/test.elf"]
+parser = "defmt-test"
+"#,
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.runners[0],
+        RunnerConfig::DefmtTest {
+            args: vec!["--test".to_string(), "integration".to_string()],
+            workdir: Some(PathBuf::from("firmware")),
+        }
+    );
+    assert!(matches!(
+        config.runners[1],
+        RunnerConfig::Command {
+            parser: OutputParser::DefmtTest,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn firmware_tests_ratchet_alongside_host_tests() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let project = dir.path().join("project");
+    let bin = dir.path().join("bin");
+    fs::create_dir_all(project.join("firmware")).unwrap();
+    fs::create_dir_all(&bin).unwrap();
+    let log = dir.path().join("cargo.log");
+
+    // Stands in for `cargo test` with probe-rs as the runner: records where
+    // and how it ran and prints the log the test has left in `probe.log`.
+    let cargo = bin.join("cargo");
+    fs::write(
+        &cargo,
+        format!(
+            "#!/bin/sh
+echo \"$(basename \"$PWD\") $@\" >> '{}'\ncat probe.log\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&cargo, fs::Permissions::from_mode(0o755)).unwrap();
+
+    git(&project, &["init", "-b", "main"]);
+    git(&project, &["config", "user.email", "test@test.com"]);
+    git(&project, &["config", "user.name", "Test"]);
+    fs::write(
+        project.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n\n\
+         [[runners]]\nkind = \"defmt-test\"\nworkdir = \"firmware\"\nargs = [\"--test\", \"integration\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        project.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\n",
+    )
+    .unwrap();
+    let probe_log = project.join("firmware/probe.log");
+    fs::write(&probe_log, "").unwrap();
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Initial project"]);
+    let sensor_log = |end: &str| {
+        format!(
+            "0.000000 INFO  (1/1) running `reads_sensor`...\n\
+             └─ integration::tests::__defmt_test_entry @ tests/integration.rs:18\n\
+             {end}\n"
+        )
+    };
+    let failing = sensor_log("0.000001 ERROR panicked at 'sensor not responding'");
+    let passing = sensor_log("0.000001 INFO  all tests passed!");
+
+    let test = "embedded::integration$tests::reads_sensor";
+    fs::write(&probe_log, &failing).unwrap();
+    let (code, out) = run_ratchet_with_path(&project, &bin, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(
+        fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .all(|line| line == "firmware test --test integration")
+    );
+    let status = StatusFile::load(&project.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[test].state(), TestState::Pending);
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Add sensor test"]);
+
+    fs::write(&probe_log, passing).unwrap();
+    let (code, out) = run_ratchet_with_path(&project, &bin, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&project.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[test].state(), TestState::Passing);
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Implement sensor"]);
+
+    fs::write(&probe_log, failing).unwrap();
+    let (code, out) = run_ratchet_with_path(&project, &bin, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains(test), "{out}");
+    assert!(out.contains("sensor not responding"), "{out}");
+    dir.pass();
+}
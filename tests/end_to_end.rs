@@ -4,51 +4,11 @@
 
 mod common;
 
-use common::TestDir;
+use common::{TestDir, build_ratchet_binary, cargo_bin, cargo_home, rustup_home};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::process::Command;
 
-fn cargo_bin() -> PathBuf {
-    // Build path to our binary
-    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    path.push("target");
-    path.push("debug");
-    path.push("cargo-ratchet");
-    path
-}
-
-/// Resolve RUSTUP_HOME for subprocess isolation. When HOME is overridden
-/// for git config isolation, Rustup can't find the toolchain unless we
-/// explicitly pass through the real RUSTUP_HOME / CARGO_HOME.
-fn rustup_home() -> PathBuf {
-    if let Ok(val) = std::env::var("RUSTUP_HOME") {
-        PathBuf::from(val)
-    } else {
-        // Default: $HOME/.rustup
-        let real_home = std::env::var("HOME").unwrap_or_else(|_| "/root".into());
-        PathBuf::from(real_home).join(".rustup")
-    }
-}
-
-fn cargo_home() -> PathBuf {
-    if let Ok(val) = std::env::var("CARGO_HOME") {
-        PathBuf::from(val)
-    } else {
-        let real_home = std::env::var("HOME").unwrap_or_else(|_| "/root".into());
-        PathBuf::from(real_home).join(".cargo")
-    }
-}
-
-fn build_ratchet_binary() {
-    let status = Command::new("cargo")
-        .args(["build"])
-        .current_dir(env!("CARGO_MANIFEST_DIR"))
-        .status()
-        .unwrap();
-    assert!(status.success(), "Failed to build tdd-ratchet binary");
-}
-
 fn set_test_file(dir: &Path, file_name: &str, body: &str) {
     fs::write(dir.join("tests").join(file_name), body).unwrap();
 }
@@ -233,6 +193,27 @@ fn init_creates_empty_status_file() {
     dir.pass();
 }
 
+#[test]
+fn init_scaffolds_gatekeeper_so_the_first_run_passes() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    create_test_project(dir.path());
+
+    let (ok, out) = run_ratchet_init(dir.path());
+    assert!(ok, "init should succeed: {out}");
+    assert!(out.contains("wrote tests/gatekeeper.rs"), "{out}");
+    let status = fs::read_to_string(dir.path().join(".test-status.json")).unwrap();
+    assert!(
+        status.contains("test-project::gatekeeper$tdd_ratchet_gatekeeper"),
+        "{status}"
+    );
+    git_add_commit(dir.path(), "Initialize ratchet");
+
+    let (ok, out) = run_ratchet(dir.path());
+    assert!(ok, "first run after init should pass: {out}");
+    dir.pass();
+}
+
 #[test]
 fn version_flag_prints_version_without_running_ratchet() {
     build_ratchet_binary();
@@ -269,6 +250,55 @@ fn help_flag_prints_usage_without_running_ratchet() {
     dir.pass();
 }
 
+#[test]
+fn validate_command_reports_schema_errors_with_line_numbers() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    create_test_project(dir.path());
+    fs::write(
+        dir.path().join(".test-status.json"),
+        "{\n  \"version\": 2,\n  \"tests\": {\n    \"a\": { \"state\": \"bogus\" }\n  }\n}\n",
+    )
+    .unwrap();
+
+    let (ok, out) = run_ratchet_args(dir.path(), &["validate"]);
+    assert!(!ok, "invalid status file should fail validation: {out}");
+    assert!(out.contains(".test-status.json:4:"), "{out}");
+
+    let (ok, out) = run_ratchet_args(dir.path(), &["schema"]);
+    assert!(ok, "{out}");
+    assert!(out.contains("test-status.v3.json"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn audit_show_filters_records_by_test() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    create_test_project(dir.path());
+    fs::write(
+        dir.path().join(".ratchet-audit.jsonl"),
+        concat!(
+            r#"{"kind":"override","commit":"c1aaaaaaaa","test":"flaky","rule":"Regression","reason":"vendor API down","recorded_at":0}"#,
+            "\n",
+            r#"{"kind":"run","recorded_at":0,"head":"c2bbbbbbbb","violations":[],"transitions":[{"test":"other","from":"pending","to":"passing"}],"overrides":[]}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let (ok, out) = run_ratchet_args(dir.path(), &["audit", "show"]);
+    assert!(ok, "{out}");
+    assert!(out.contains("override c1aaaaaa Regression flaky"), "{out}");
+    assert!(out.contains("other: pending -> passing"), "{out}");
+
+    let (ok, out) = run_ratchet_args(dir.path(), &["audit", "show", "--test", "flaky"]);
+    assert!(ok, "{out}");
+    assert!(out.contains("flaky"), "{out}");
+    assert!(!out.contains("other"), "{out}");
+    dir.pass();
+}
+
 #[test]
 fn happy_path_tdd_workflow() {
     build_ratchet_binary();
@@ -908,6 +938,30 @@ fn zero_tests_project_succeeds() {
     dir.pass();
 }
 
+#[test]
+fn gatekeeper_edited_to_always_pass_is_reported() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    create_test_project(dir.path());
+
+    let (ok, out) = run_ratchet_init(dir.path());
+    assert!(ok, "Init should succeed: {out}");
+    set_test_file(
+        dir.path(),
+        "gatekeeper.rs",
+        "#[test]\nfn tdd_ratchet_gatekeeper() {}\n",
+    );
+    git_add_commit(dir.path(), "Init ratchet with a disabled gatekeeper");
+
+    let (ok, out) = run_ratchet(dir.path());
+    assert!(
+        !ok,
+        "a gatekeeper that always passes should fail the run: {out}"
+    );
+    assert!(out.contains("[RATCHET020]"), "{out}");
+    dir.pass();
+}
+
 #[test]
 fn two_new_tests_one_passes_one_fails() {
     build_ratchet_binary();
@@ -0,0 +1,88 @@
+// tests/error_types.rs
+//
+// Story 102: library functions return one `RatchetError`, whose variant says
+// whether IO, git, parsing, the runner or evaluation failed, and which keeps
+// the underlying error as its source.
+
+mod common;
+
+use common::TestDir;
+use std::error::Error;
+use std::fs;
+use tdd_ratchet::RatchetError;
+use tdd_ratchet::audit::read_audit;
+use tdd_ratchet::config::RatchetConfig;
+use tdd_ratchet::error::{EvaluationError, ParseError};
+use tdd_ratchet::history::read_head_status;
+use tdd_ratchet::partition::{PartialResults, Partition, merge_partial_results};
+use tdd_ratchet::status::StatusFile;
+
+#[test]
+fn unreadable_and_malformed_files_are_io_and_parse_errors() {
+    let dir = TestDir::new();
+    let path = dir.path();
+
+    let missing = path.join(".test-status.json");
+    let err = StatusFile::load(&missing).unwrap_err();
+    assert!(
+        matches!(&err, RatchetError::Io { path, .. } if path == &missing),
+        "{err:?}"
+    );
+    assert!(err.source().is_some());
+
+    fs::write(&missing, "{ not json").unwrap();
+    let err = StatusFile::load(&missing).unwrap_err();
+    assert!(
+        matches!(&err, RatchetError::Parse(ParseError::Status { .. })),
+        "{err:?}"
+    );
+    assert!(err.to_string().contains(".test-status.json"), "{err}");
+
+    fs::write(path.join("ratchet.toml"), "[rules\n").unwrap();
+    let err = RatchetConfig::load(path).unwrap_err();
+    assert!(
+        matches!(&err, RatchetError::Parse(ParseError::Config { .. })),
+        "{err:?}"
+    );
+
+    let audit = path.join(".ratchet-audit.jsonl");
+    fs::write(&audit, "{}\nnot json\n").unwrap();
+    let err = read_audit(&audit).unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            RatchetError::Parse(ParseError::Audit { line: 1 | 2, .. })
+        ),
+        "{err:?}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn git_failures_are_git_errors() {
+    let dir = TestDir::new();
+    let err = read_head_status(dir.path()).unwrap_err();
+    assert!(matches!(&err, RatchetError::Git(_)), "{err:?}");
+    dir.pass();
+}
+
+#[test]
+fn unusable_inputs_are_evaluation_errors() {
+    let shard = |index| PartialResults {
+        partition: Some(Partition { index, count: 2 }),
+        results: Vec::new(),
+    };
+    let err = merge_partial_results(&[shard(1)]).unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            RatchetError::Evaluation(EvaluationError::MissingPartitions(_))
+        ),
+        "{err:?}"
+    );
+    assert!(err.source().is_none());
+    assert_eq!(
+        err.to_string(),
+        "missing partition 2/2; their tests would be reported as disappeared"
+    );
+}
@@ -0,0 +1,135 @@
+// tests/eval_stdin.rs
+//
+// Story 103: a build system that runs the tests itself pipes their
+// libtest-json events into `cargo ratchet eval --stdin`, which evaluates
+// them against the status file and history, and prints the result as JSON
+// with `--format json`.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, ratchet_command, result};
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+use tdd_ratchet::json_report::{evaluation_json, format_json};
+use tdd_ratchet::ratchet::evaluate;
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, WorkingTreeInstructions};
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+const PARSER: &str = "app::tests$parses_headers";
+
+/// Run the ratchet with `stdin` piped in; returns the exit code, stdout and
+/// stderr.
+fn run_ratchet_with_stdin(dir: &Path, args: &[&str], stdin: &str) -> (Option<i32>, String, String) {
+    let mut child = ratchet_command(dir, args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
+}
+
+fn event(name: &str, event: &str) -> String {
+    format!(r#"{{"type":"test","event":"{event}","name":"{name}","exec_time":0.01}}"#) + "\n"
+}
+
+#[test]
+fn json_report_lists_counts_violations_and_states() {
+    let status = StatusFile::new(
+        [(GATEKEEPER.to_string(), TestEntry::new(TestState::Passing))]
+            .into_iter()
+            .collect(),
+    );
+    let result = evaluate(
+        &status.tracked_status(),
+        &WorkingTreeInstructions::default(),
+        &[
+            result(GATEKEEPER, TestOutcome::Passed),
+            result(PARSER, TestOutcome::Passed),
+        ],
+        &[],
+    );
+
+    let json = evaluation_json(&result, false);
+    assert_eq!(json["ok"], false);
+    assert_eq!(json["violations"][0]["code"], "RATCHET001");
+    assert_eq!(json["violations"][0]["subject"], PARSER);
+    assert_eq!(json["tests"][GATEKEEPER], "passing");
+    assert_eq!(json["passing"], 1);
+
+    let printed: Value = serde_json::from_str(&format_json(&result, false)).unwrap();
+    assert_eq!(printed, json);
+}
+
+#[test]
+fn eval_stdin_evaluates_piped_results() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    // The configured runner is never run: results come from stdin.
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"false\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        format!(r#"{{"tests":{{"{GATEKEEPER}":"passing"}}}}"#),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let piped = format!(
+        "not json, e.g. build output\n{}{}{}",
+        event(GATEKEEPER, "started"),
+        event(GATEKEEPER, "ok"),
+        event(PARSER, "failed"),
+    );
+    let (code, stdout, stderr) =
+        run_ratchet_with_stdin(path, &["eval", "--stdin", "--format", "json"], &piped);
+    assert_eq!(code, Some(0), "{stderr}");
+    let json: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["ok"], true, "{stdout}");
+    assert_eq!(json["pending"], 1, "{stdout}");
+    assert_eq!(json["tests"][PARSER], "pending", "{stdout}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[PARSER].state(), TestState::Pending);
+
+    // A test the status file does not know passing straight away.
+    let piped = event(GATEKEEPER, "ok") + &event(PARSER, "failed") + &event("app$new", "ok");
+    let (code, stdout, _) =
+        run_ratchet_with_stdin(path, &["eval", "--stdin", "--format", "json"], &piped);
+    assert_eq!(code, Some(1), "{stdout}");
+    let json: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["ok"], false);
+    assert_eq!(json["violations"][0]["code"], "RATCHET001", "{stdout}");
+
+    let (code, _, stderr) = run_ratchet_with_stdin(path, &["eval", "--stdin"], "");
+    assert_eq!(code, Some(1));
+    assert!(stderr.contains("no libtest-json test events"), "{stderr}");
+
+    let (code, _, stderr) = run_ratchet_with_stdin(path, &["eval"], "");
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("usage:"), "{stderr}");
+    dir.pass();
+}
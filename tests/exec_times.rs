@@ -0,0 +1,225 @@
+// tests/exec_times.rs
+//
+// Story 82: with `[timing] enabled`, each test's latest execution time is
+// kept in the status file, the report lists the slowest tests, and a suite
+// that slows down too much between runs is warned about.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::errors::{ReportOptions, format_report_with, slowest_tests};
+use tdd_ratchet::last_run::{RunSummary, suite_slowdown};
+use tdd_ratchet::ratchet::{EvalResult, Warning, record_exec_times};
+use tdd_ratchet::runner::{
+    TestOutcome, TestResult, merge_retries, parse_go_test_json, parse_jest_json,
+    parse_libtest_output, parse_nextest_output,
+};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState};
+
+fn timed(name: &str, outcome: TestOutcome, exec_time_ms: Option<u64>) -> TestResult {
+    TestResult {
+        name: name.into(),
+        outcome,
+        output: None,
+        exec_time_ms,
+    }
+}
+
+fn entry(exec_time_ms: Option<u64>) -> TestEntry {
+    let mut entry = TestEntry::new(TestState::Passing);
+    entry.exec_time_ms = exec_time_ms;
+    entry
+}
+
+fn summary(suite_time_ms: Option<u64>) -> RunSummary {
+    RunSummary {
+        recorded_at: 0,
+        head: None,
+        passing: 0,
+        pending: Default::default(),
+        regressions: 0,
+        violations: 0,
+        suite_time_ms,
+        failing_drafts: Default::default(),
+    }
+}
+
+#[test]
+fn parsers_read_the_reported_execution_times() {
+    let nextest = parse_nextest_output(
+        r#"{"type":"test","event":"ok","name":"crate::tests$fast","exec_time":0.0125}
+{"type":"test","event":"started","name":"crate::tests$untimed"}"#,
+    );
+    assert_eq!(nextest[0].exec_time_ms, Some(13));
+
+    let go = parse_go_test_json(
+        r#"{"Action":"pass","Package":"example.com/api","Test":"TestGet","Elapsed":1.5}"#,
+    );
+    assert_eq!(go[0].exec_time_ms, Some(1500));
+
+    let jest = parse_jest_json(
+        r#"{"testResults":[{"name":"/repo/a.test.js","assertionResults":[
+            {"title":"runs","status":"passed","duration":42},
+            {"title":"skipped","status":"pending","duration":null}]}]}"#,
+        Path::new("/repo"),
+    );
+    assert_eq!(jest[0].exec_time_ms, Some(42));
+    assert_eq!(jest[1].exec_time_ms, None);
+
+    let libtest = parse_libtest_output("test plain ... ok\n");
+    assert_eq!(libtest[0].exec_time_ms, None);
+}
+
+#[test]
+fn the_last_attempt_of_a_retried_test_sets_its_time() {
+    let merged = merge_retries([
+        timed("a", TestOutcome::Failed, Some(900)),
+        timed("a", TestOutcome::Passed, Some(40)),
+    ]);
+    assert_eq!(merged[0].outcome, TestOutcome::Flaky);
+    assert_eq!(merged[0].exec_time_ms, Some(40));
+}
+
+#[test]
+fn entries_keep_their_latest_time_until_timing_is_off() {
+    let mut tests = [
+        ("a".to_string(), entry(Some(10))),
+        ("b".to_string(), entry(Some(20))),
+    ]
+    .into();
+
+    record_exec_times(
+        &mut tests,
+        &[
+            timed("a", TestOutcome::Passed, Some(15)),
+            timed("b", TestOutcome::Passed, None),
+            timed("untracked", TestOutcome::Passed, Some(5)),
+        ],
+        true,
+    );
+    assert_eq!(tests["a"].exec_time_ms, Some(15));
+    assert_eq!(tests["b"].exec_time_ms, Some(20));
+    assert!(!tests.contains_key("untracked"));
+
+    record_exec_times(&mut tests, &[], false);
+    assert!(tests.values().all(|entry| entry.exec_time_ms.is_none()));
+}
+
+#[test]
+fn report_lists_the_slowest_tests_first() {
+    let status = StatusFile::new(
+        [
+            ("quick".to_string(), entry(Some(3))),
+            ("slow".to_string(), entry(Some(2_500))),
+            ("medium".to_string(), entry(Some(250))),
+            ("untimed".to_string(), entry(None)),
+        ]
+        .into(),
+    );
+    assert_eq!(
+        slowest_tests(&status, 2),
+        [("slow", 2_500), ("medium", 250)]
+    );
+
+    let result = EvalResult {
+        violations: Vec::new(),
+        warnings: Vec::new(),
+        updated: status,
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    };
+    let options = ReportOptions {
+        slowest: 2,
+        ..ReportOptions::default()
+    };
+    let report = format_report_with(&result, options);
+    assert!(
+        report.contains("tdd-ratchet: 2 slowest tests\n     2.500s  slow\n     0.250s  medium\n"),
+        "{report}"
+    );
+    assert!(!format_report_with(&result, ReportOptions::default()).contains("slowest"));
+}
+
+#[test]
+fn suite_growth_past_the_limit_is_a_warning() {
+    let previous = summary(Some(1_000));
+
+    assert!(suite_slowdown(&previous, &summary(Some(1_200)), 20).is_none());
+    assert!(suite_slowdown(&previous, &summary(None), 20).is_none());
+    assert!(suite_slowdown(&summary(None), &summary(Some(5_000)), 20).is_none());
+    let warning = suite_slowdown(&previous, &summary(Some(1_201)), 20);
+    assert!(
+        matches!(
+            warning,
+            Some(Warning::SuiteSlower {
+                previous_ms: 1_000,
+                current_ms: 1_201,
+                max_growth: 20,
+            })
+        ),
+        "{warning:?}"
+    );
+}
+
+#[test]
+fn timed_runs_record_times_and_warn_when_the_suite_slows_down() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [timing]\nenabled = true\nmax-growth = 50\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    let set_times = |gatekeeper: f64, feature: f64| {
+        fs::write(
+            path.join("results.json"),
+            format!(
+                "{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"tdd_ratchet_gatekeeper\",\"exec_time\":{gatekeeper}}}\n\
+                 {{\"type\":\"test\",\"event\":\"ok\",\"name\":\"feature\",\"exec_time\":{feature}}}\n"
+            ),
+        )
+        .unwrap();
+    };
+    set_times(0.001, 0.1);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("2 slowest tests"), "{out}");
+    assert!(out.contains("0.100s  feature"), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests["feature"].exec_time_ms, Some(100));
+
+    set_times(0.001, 0.3);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("test suite got slower"), "{out}");
+    assert!(
+        out.contains("suite took 0.301s against 0.101s on the last run"),
+        "{out}"
+    );
+
+    let (code, out) = run_ratchet(path, &["--deny", "warnings"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!out.contains("test suite got slower"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,114 @@
+// tests/export.rs
+//
+// Story 47: `cargo ratchet export --format csv` writes one row per commit,
+// test, and state from the history walk for analysis in other tools.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, git, status};
+use std::fs;
+use std::process::Command;
+use tdd_ratchet::export::{ExportFormat, export_csv};
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::status::TestState;
+
+fn snapshot(commit: &str, author: &str, time: i64, tests: &[(&str, TestState)]) -> HistorySnapshot {
+    HistorySnapshot {
+        info: CommitInfo {
+            author: author.into(),
+            time,
+            summary: format!("commit {commit}"),
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status(tests))
+    }
+}
+
+#[test]
+fn csv_has_a_row_per_commit_and_test() {
+    let snapshots = vec![
+        snapshot("aaa", "Ada", 100, &[("t", TestState::Pending)]),
+        snapshot(
+            "bbb",
+            "Grace",
+            200,
+            &[("t", TestState::Passing), ("u", TestState::Pending)],
+        ),
+    ];
+
+    assert_eq!(
+        export_csv(&snapshots),
+        "commit,time,author,test,state\n\
+         aaa,100,Ada,t,pending\n\
+         bbb,200,Grace,t,passing\n\
+         bbb,200,Grace,u,pending\n"
+    );
+}
+
+#[test]
+fn csv_quotes_fields_that_need_it() {
+    let snapshots = vec![snapshot(
+        "aaa",
+        "Hopper, \"Amazing\" Grace",
+        100,
+        &[("tests::with,comma", TestState::Passing)],
+    )];
+
+    assert_eq!(
+        export_csv(&snapshots).lines().nth(1).unwrap(),
+        "aaa,100,\"Hopper, \"\"Amazing\"\" Grace\",\"tests::with,comma\",passing"
+    );
+}
+
+#[test]
+fn empty_history_exports_only_the_header() {
+    assert_eq!(export_csv(&[]), "commit,time,author,test,state\n");
+}
+
+#[test]
+fn only_csv_is_a_known_format() {
+    assert_eq!(ExportFormat::parse("csv"), Some(ExportFormat::Csv));
+    assert_eq!(ExportFormat::parse("parquet"), None);
+}
+
+#[test]
+fn export_command_prints_committed_history() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"a":"pending"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add a"]);
+
+    let run = |args: &[&str]| {
+        Command::new(cargo_bin())
+            .args(args)
+            .current_dir(path)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("HOME", path)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["export", "--format", "csv"]);
+    assert!(output.status.success());
+    let csv = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "commit,time,author,test,state");
+    assert_eq!(lines.len(), 2, "{csv}");
+    assert!(lines[1].ends_with(",Test,a,pending"), "{csv}");
+
+    let output = run(&["export"]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), csv);
+
+    let output = run(&["export", "--format", "xlsx"]);
+    assert_eq!(output.status.code(), Some(2));
+    dir.pass();
+}
@@ -0,0 +1,109 @@
+// tests/failure_output.rs
+//
+// Story 42: A regression report shows what the failing test printed, trimmed
+// to a short excerpt unless `--full-output` asks for all of it.
+
+mod common;
+
+use common::gathered;
+use tdd_ratchet::errors::{
+    FAILURE_EXCERPT_LINES, ReportOptions, format_report, format_report_with,
+};
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::runner::{TestOutcome, TestResult, parse_nextest_output};
+use tdd_ratchet::status::TestState;
+
+fn result(name: &str, outcome: TestOutcome, output: Option<&str>) -> TestResult {
+    TestResult {
+        output: output.map(str::to_string),
+        ..common::result(name, outcome)
+    }
+}
+
+fn regressed(output: &str) -> GatheredRun {
+    gathered(
+        &[
+            ("tdd_ratchet_gatekeeper", TestState::Passing),
+            ("feature", TestState::Passing),
+        ],
+        vec![
+            result("tdd_ratchet_gatekeeper", TestOutcome::Passed, None),
+            result("feature", TestOutcome::Failed, Some(output)),
+        ],
+    )
+}
+
+fn numbered_lines(count: usize) -> String {
+    (1..=count).map(|i| format!("line {i}\n")).collect()
+}
+
+#[test]
+fn nextest_failed_events_keep_their_stdout() {
+    let output = r#"{"type":"test","event":"failed","name":"my-crate::tests$broken","stdout":"assertion failed\n"}
+{"type":"test","event":"ok","name":"my-crate::tests$fine","stdout":"chatter\n"}"#;
+
+    assert_eq!(
+        parse_nextest_output(output),
+        vec![
+            result(
+                "my-crate::tests$broken",
+                TestOutcome::Failed,
+                Some("assertion failed\n"),
+            ),
+            result("my-crate::tests$fine", TestOutcome::Passed, None),
+        ]
+    );
+}
+
+#[test]
+fn evaluation_keeps_the_output_of_failed_tests() {
+    let result = regressed("boom\n").evaluate();
+
+    assert_eq!(result.failure_output.len(), 1);
+    assert_eq!(result.failure_output["feature"], "boom\n");
+}
+
+#[test]
+fn regression_report_shows_a_truncated_excerpt() {
+    let total = FAILURE_EXCERPT_LINES + 5;
+    let result = regressed(&numbered_lines(total)).evaluate();
+    let report = format_report(&result);
+
+    assert!(report.contains("Output of feature:"), "{report}");
+    assert!(report.contains("│ line 1\n"), "{report}");
+    assert!(
+        report.contains(&format!("│ line {FAILURE_EXCERPT_LINES}\n")),
+        "{report}"
+    );
+    assert!(
+        !report.contains(&format!("│ line {}\n", FAILURE_EXCERPT_LINES + 1)),
+        "{report}"
+    );
+    assert!(report.contains("5 more lines"), "{report}");
+    assert!(report.contains("--full-output"), "{report}");
+}
+
+#[test]
+fn full_output_disables_truncation() {
+    let total = FAILURE_EXCERPT_LINES + 5;
+    let result = regressed(&numbered_lines(total)).evaluate();
+    let report = format_report_with(
+        &result,
+        ReportOptions {
+            full_output: true,
+            ..ReportOptions::default()
+        },
+    );
+
+    assert!(report.contains(&format!("│ line {total}\n")), "{report}");
+    assert!(!report.contains("more lines"), "{report}");
+}
+
+#[test]
+fn short_output_is_shown_whole() {
+    let result = regressed("only line\n").evaluate();
+    let report = format_report(&result);
+
+    assert!(report.contains("│ only line\n"), "{report}");
+    assert!(!report.contains("more lines"), "{report}");
+}
@@ -0,0 +1,143 @@
+// tests/flaky_tests.rs
+//
+// Story 39: Tests that pass only on a nextest retry are recorded as flaky,
+// their flake count is kept in the status file, and frequent flakers are
+// reported.
+
+mod common;
+
+use common::result;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RulesConfig};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::Warning;
+use tdd_ratchet::runner::{TestOutcome, merge_retries, parse_nextest_output};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, TrackedStatus};
+
+fn gathered(state: TestState, flakes: u32, threshold: Option<u32>) -> GatheredRun {
+    let mut entry = TestEntry::new(state);
+    entry.flakes = flakes;
+    GatheredRun {
+        status: TrackedStatus::new(
+            [
+                (
+                    "tdd_ratchet_gatekeeper".to_string(),
+                    TestEntry::new(TestState::Passing),
+                ),
+                ("feature".to_string(), entry),
+            ]
+            .into(),
+        ),
+        results: vec![
+            result("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+            result("feature", TestOutcome::Flaky),
+        ],
+        rules: RulesConfig {
+            flake_threshold: threshold,
+            ..RulesConfig::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn retried_attempts_are_merged_into_one_result() {
+    let merged = merge_retries(vec![
+        result("flaky", TestOutcome::Failed),
+        result("steady", TestOutcome::Passed),
+        result("broken", TestOutcome::Failed),
+        result("flaky", TestOutcome::Failed),
+        result("broken", TestOutcome::Failed),
+        result("flaky", TestOutcome::Passed),
+    ]);
+
+    assert_eq!(
+        merged,
+        vec![
+            result("flaky", TestOutcome::Flaky),
+            result("steady", TestOutcome::Passed),
+            result("broken", TestOutcome::Failed),
+        ]
+    );
+}
+
+#[test]
+fn nextest_output_with_a_retry_yields_a_flaky_result() {
+    let output = r#"{"type":"test","event":"started","name":"my-crate::tests$wobbly"}
+{"type":"test","event":"failed","name":"my-crate::tests$wobbly","stdout":"timing"}
+{"type":"test","event":"started","name":"my-crate::tests$wobbly"}
+{"type":"test","event":"ok","name":"my-crate::tests$wobbly"}
+{"type":"test","event":"ok","name":"my-crate::tests$solid"}"#;
+
+    assert_eq!(
+        parse_nextest_output(output),
+        vec![
+            result("my-crate::tests$wobbly", TestOutcome::Flaky),
+            result("my-crate::tests$solid", TestOutcome::Passed),
+        ]
+    );
+}
+
+#[test]
+fn flaky_pass_counts_as_passing_and_records_the_flake() {
+    let result = gathered(TestState::Passing, 0, None).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    assert_eq!(result.updated.tests["feature"].flakes, 1);
+    assert_eq!(result.updated.tests["feature"].state(), TestState::Passing);
+}
+
+#[test]
+fn flaky_pass_promotes_a_pending_test() {
+    let result = gathered(TestState::Pending, 0, None).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(result.updated.tests["feature"].state(), TestState::Passing);
+    assert_eq!(result.updated.tests["feature"].flakes, 1);
+}
+
+#[test]
+fn tests_over_the_flake_threshold_are_reported() {
+    let result = gathered(TestState::Passing, 2, None).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(
+        matches!(
+            result.warnings.as_slice(),
+            [Warning::FlakyTest { test, flakes: 3 }] if test == "feature"
+        ),
+        "{:?}",
+        result.warnings
+    );
+    let report = format_report(&result);
+    assert!(report.contains("flaky test"), "{report}");
+    assert!(report.contains("feature passed on retry"), "{report}");
+    assert!(report.contains("tdd-ratchet: ok"), "{report}");
+}
+
+#[test]
+fn flake_threshold_is_configurable() {
+    let config =
+        RatchetConfig::parse_from_str("[rules]\nflake-threshold = 10\n", Path::new("ratchet.toml"))
+            .unwrap();
+    assert_eq!(config.rules.flake_threshold, Some(10));
+
+    let result = gathered(TestState::Passing, 5, Some(10)).evaluate();
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    let result = gathered(TestState::Passing, 0, Some(0)).evaluate();
+    assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+}
+
+#[test]
+fn flake_counts_round_trip_through_the_status_file() {
+    let json = r#"{"version":2,"tests":{"a":{"state":"passing","flakes":4},"b":"passing"}}"#;
+    let status = StatusFile::parse_from_str(json, Path::new(".test-status.json")).unwrap();
+    assert_eq!(status.tests["a"].flakes, 4);
+    assert_eq!(status.tests["b"].flakes, 0);
+
+    let written = serde_json::to_string(&status).unwrap();
+    assert!(written.contains("\"flakes\":4"), "{written}");
+    assert_eq!(written.matches("flakes").count(), 1, "{written}");
+}
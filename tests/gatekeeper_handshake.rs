@@ -0,0 +1,148 @@
+// tests/gatekeeper_handshake.rs
+//
+// Story 67: With `[gatekeeper] handshake = true`, every run hands the tests a
+// fresh nonce that the gatekeeper must echo back, so exporting
+// `TDD_RATCHET=1` no longer bypasses the ratchet.
+
+use std::path::Path;
+use std::sync::Arc;
+use tdd_ratchet::Ratchet;
+use tdd_ratchet::config::RatchetConfig;
+use tdd_ratchet::error::RatchetError;
+use tdd_ratchet::gatekeeper::{
+    ACK_FILE_ENV, HANDSHAKE_GATEKEEPER_SOURCE, Handshake, NONCE_FILE_ENV, gatekeeper_source,
+};
+use tdd_ratchet::history::{HistoryProvider, HistorySnapshot};
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::runner::{
+    CommandRunner, OutputParser, RunnerError, TestOutcome, TestResult, TestRunner,
+};
+use tdd_ratchet::status::{StatusFile, StatusStore, WorkingTreeInstructions};
+
+/// A gatekeeper that passes, and whose handshake answer is `acknowledged`.
+struct HandshakeRunner(Option<bool>);
+
+impl TestRunner for HandshakeRunner {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        Ok(vec![TestResult {
+            name: "app::gatekeeper$tdd_ratchet_gatekeeper".into(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        }])
+    }
+
+    fn gatekeeper_acknowledged(&self) -> Option<bool> {
+        self.0
+    }
+}
+
+struct NoHistory;
+
+impl HistoryProvider for NoHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        Ok(None)
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        Ok(Vec::new())
+    }
+}
+
+struct NullStore;
+
+impl StatusStore for NullStore {
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError> {
+        Ok(WorkingTreeInstructions::default())
+    }
+
+    fn save(&self, _status: &StatusFile) -> Result<(), RatchetError> {
+        Ok(())
+    }
+}
+
+fn violations(runner: HandshakeRunner) -> Vec<Violation> {
+    let store = NullStore;
+    Ratchet::builder("/nonexistent")
+        .runner(runner)
+        .history(NoHistory)
+        .status_store(&store)
+        .build()
+        .run()
+        .unwrap()
+        .violations
+}
+
+#[test]
+fn handshake_is_configured_under_gatekeeper() {
+    let config = RatchetConfig::parse_from_str(
+        "[gatekeeper]\nhandshake = true\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert!(config.gatekeeper.handshake);
+    assert!(!RatchetConfig::default().gatekeeper.handshake);
+    assert_eq!(gatekeeper_source(true), HANDSHAKE_GATEKEEPER_SOURCE);
+}
+
+#[test]
+fn a_gatekeeper_that_does_not_echo_the_nonce_is_a_violation() {
+    let violations = violations(HandshakeRunner(Some(false)));
+
+    assert!(
+        matches!(
+            violations.as_slice(),
+            [Violation::GatekeeperHandshakeMissing]
+        ),
+        "{violations:?}"
+    );
+    assert_eq!(violations[0].code(), "RATCHET021");
+}
+
+#[test]
+fn an_echoed_nonce_or_no_handshake_is_fine() {
+    assert!(violations(HandshakeRunner(Some(true))).is_empty());
+    assert!(violations(HandshakeRunner(None)).is_empty());
+}
+
+#[test]
+fn each_handshake_has_its_own_nonce_and_cleans_up() {
+    let first = Handshake::begin().unwrap();
+    let second = Handshake::begin().unwrap();
+    let [(nonce_var, nonce_file), (ack_var, _)] = first.env();
+    assert_eq!((nonce_var, ack_var), (NONCE_FILE_ENV, ACK_FILE_ENV));
+    let nonce = std::fs::read_to_string(&nonce_file).unwrap();
+    assert_ne!(nonce, std::fs::read_to_string(&second.env()[0].1).unwrap());
+
+    drop(first);
+    assert!(!nonce_file.exists(), "the nonce should not outlive the run");
+}
+
+#[cfg(unix)]
+#[test]
+fn runners_hand_the_nonce_to_the_tests() {
+    let handshake = Arc::new(Handshake::begin().unwrap());
+    let echo = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "cat \"$TDD_RATCHET_NONCE_FILE\" > \"$TDD_RATCHET_ACK_FILE\"; \
+         echo 'test tdd_ratchet_gatekeeper ... ok'"
+            .to_string(),
+    ];
+    let runner = CommandRunner::new(&echo, ".", OutputParser::Libtest)
+        .unwrap()
+        .with_handshake(Arc::clone(&handshake));
+    runner.run().unwrap();
+    assert_eq!(runner.gatekeeper_acknowledged(), Some(true));
+
+    let silent = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "echo 'test tdd_ratchet_gatekeeper ... ok'".to_string(),
+    ];
+    let runner = CommandRunner::new(&silent, ".", OutputParser::Libtest)
+        .unwrap()
+        .with_handshake(handshake);
+    runner.run().unwrap();
+    assert_eq!(runner.gatekeeper_acknowledged(), Some(false));
+}
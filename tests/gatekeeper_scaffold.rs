@@ -0,0 +1,85 @@
+// tests/gatekeeper_scaffold.rs
+//
+// Story 65: `cargo ratchet --init` writes the gatekeeper test, so a new
+// project does not start with MissingGatekeeper.
+
+use std::fs;
+use tdd_ratchet::error::{EvaluationError, RatchetError};
+use tdd_ratchet::gatekeeper::{GATEKEEPER_PATH, GATEKEEPER_SOURCE, Scaffold, scaffold_gatekeeper};
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+
+fn project(manifest: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+    dir
+}
+
+const MANIFEST: &str = "[package]\nname = \"my-app\"\nversion = \"0.1.0\"\n";
+
+#[test]
+fn init_writes_the_gatekeeper_and_names_it() {
+    let dir = project(MANIFEST);
+
+    let scaffold = scaffold_gatekeeper(dir.path(), &[]).unwrap();
+
+    assert_eq!(
+        scaffold,
+        Scaffold::Written {
+            test: "my-app::gatekeeper$tdd_ratchet_gatekeeper".into()
+        }
+    );
+    let written = fs::read_to_string(dir.path().join(GATEKEEPER_PATH)).unwrap();
+    assert_eq!(written, GATEKEEPER_SOURCE);
+    assert!(written.contains("fn tdd_ratchet_gatekeeper()"), "{written}");
+    assert!(written.contains("TDD_RATCHET"), "{written}");
+}
+
+#[test]
+fn init_skips_scaffolding_when_the_run_has_a_gatekeeper() {
+    let dir = project(MANIFEST);
+    let results = [TestResult {
+        name: "my-app::bypass$tdd_ratchet_gatekeeper".into(),
+        outcome: TestOutcome::Passed,
+        output: None,
+        exec_time_ms: None,
+    }];
+
+    let scaffold = scaffold_gatekeeper(dir.path(), &results).unwrap();
+
+    assert_eq!(scaffold, Scaffold::AlreadyPresent);
+    assert!(!dir.path().join(GATEKEEPER_PATH).exists());
+}
+
+#[test]
+fn init_never_overwrites_an_existing_file() {
+    let dir = project(MANIFEST);
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    fs::write(dir.path().join(GATEKEEPER_PATH), "// mine\n").unwrap();
+
+    let err = scaffold_gatekeeper(dir.path(), &[]).unwrap_err();
+
+    assert!(
+        matches!(
+            err,
+            RatchetError::Evaluation(EvaluationError::GatekeeperPathTaken)
+        ),
+        "{err}"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join(GATEKEEPER_PATH)).unwrap(),
+        "// mine\n"
+    );
+}
+
+#[test]
+fn init_cannot_scaffold_in_a_virtual_workspace() {
+    let dir = project("[workspace]\nmembers = [\"app\"]\n");
+
+    let err = scaffold_gatekeeper(dir.path(), &[]).unwrap_err();
+
+    assert!(
+        matches!(err, RatchetError::Evaluation(EvaluationError::NoPackage)),
+        "{err}"
+    );
+    assert!(!dir.path().join(GATEKEEPER_PATH).exists());
+}
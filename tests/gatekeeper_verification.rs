@@ -0,0 +1,134 @@
+// tests/gatekeeper_verification.rs
+//
+// Story 66: The ratchet runs the gatekeeper once without TDD_RATCHET set and
+// reports a violation when it passes, since such a gatekeeper no longer
+// blocks a direct `cargo test`.
+
+use std::cell::RefCell;
+use tdd_ratchet::Ratchet;
+use tdd_ratchet::config::{Rule, RuleLevel, RulesConfig};
+use tdd_ratchet::error::RatchetError;
+use tdd_ratchet::history::{HistoryProvider, HistorySnapshot};
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::runner::{RunnerError, TestOutcome, TestResult, TestRunner};
+use tdd_ratchet::status::{StatusFile, StatusStore, WorkingTreeInstructions};
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+
+/// Reports the gatekeeper as passing under the ratchet and with `unguarded`
+/// without it, recording which tests were run unguarded.
+struct GatekeeperRunner {
+    unguarded: Option<TestOutcome>,
+    verified: RefCell<Vec<String>>,
+}
+
+impl GatekeeperRunner {
+    fn new(unguarded: Option<TestOutcome>) -> Self {
+        GatekeeperRunner {
+            unguarded,
+            verified: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl TestRunner for GatekeeperRunner {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        Ok(vec![TestResult {
+            name: GATEKEEPER.into(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        }])
+    }
+
+    fn run_without_ratchet(&self, test: &str) -> Result<Option<TestOutcome>, RunnerError> {
+        self.verified.borrow_mut().push(test.to_string());
+        Ok(self.unguarded)
+    }
+}
+
+struct NoHistory;
+
+impl HistoryProvider for NoHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        Ok(None)
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        Ok(Vec::new())
+    }
+}
+
+struct NullStore;
+
+impl StatusStore for NullStore {
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError> {
+        Ok(WorkingTreeInstructions::default())
+    }
+
+    fn save(&self, _status: &StatusFile) -> Result<(), RatchetError> {
+        Ok(())
+    }
+}
+
+fn gather(runner: &GatekeeperRunner, rules: RulesConfig) -> Vec<Violation> {
+    let store = NullStore;
+    Ratchet::builder("/nonexistent")
+        .runner(runner)
+        .history(NoHistory)
+        .rules(rules)
+        .status_store(&store)
+        .build()
+        .gather()
+        .unwrap()
+        .evaluate()
+        .violations
+}
+
+#[test]
+fn a_gatekeeper_passing_without_tdd_ratchet_is_a_violation() {
+    let runner = GatekeeperRunner::new(Some(TestOutcome::Passed));
+
+    let violations = gather(&runner, RulesConfig::default());
+
+    assert_eq!(*runner.verified.borrow(), [GATEKEEPER]);
+    assert!(
+        matches!(
+            violations.as_slice(),
+            [Violation::GatekeeperDoesNotBlock { test }] if test == GATEKEEPER
+        ),
+        "{violations:?}"
+    );
+    assert_eq!(violations[0].code(), "RATCHET020");
+}
+
+#[test]
+fn a_gatekeeper_failing_without_tdd_ratchet_is_fine() {
+    let runner = GatekeeperRunner::new(Some(TestOutcome::Failed));
+
+    let violations = gather(&runner, RulesConfig::default());
+
+    assert_eq!(*runner.verified.borrow(), [GATEKEEPER]);
+    assert!(violations.is_empty(), "{violations:?}");
+}
+
+#[test]
+fn runners_that_cannot_run_one_test_skip_the_check() {
+    let runner = GatekeeperRunner::new(None);
+
+    let violations = gather(&runner, RulesConfig::default());
+
+    assert!(violations.is_empty(), "{violations:?}");
+}
+
+#[test]
+fn the_check_is_skipped_when_missing_gatekeeper_is_allowed() {
+    let runner = GatekeeperRunner::new(Some(TestOutcome::Passed));
+    let mut rules = RulesConfig::default();
+    rules.set_level(Rule::MissingGatekeeper, RuleLevel::Allow);
+
+    let violations = gather(&runner, rules);
+
+    assert!(runner.verified.borrow().is_empty());
+    assert!(violations.is_empty(), "{violations:?}");
+}
@@ -0,0 +1,220 @@
+// tests/gc.rs
+//
+// Story 73: `gc` prunes per-test baselines the global baseline already
+// covers and renames carried out longer ago than a retention window, and
+// lists what it removed.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use tdd_ratchet::gc::{GcChange, GcEvidence, apply_gc, gather_gc_evidence, plan_gc};
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState};
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// Passing tests, each with an optional per-test baseline.
+fn status(tests: &[(&str, Option<&str>)]) -> StatusFile {
+    StatusFile::new(
+        tests
+            .iter()
+            .map(|(name, baseline)| {
+                let mut entry = TestEntry::new(TestState::Passing);
+                entry.baseline = baseline.map(str::to_string);
+                (name.to_string(), entry)
+            })
+            .collect(),
+    )
+}
+
+fn snapshot(commit: &str, time: i64, status: StatusFile) -> HistorySnapshot {
+    HistorySnapshot {
+        info: CommitInfo {
+            time,
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status)
+    }
+}
+
+fn before_baseline(commits: &[&str]) -> GcEvidence {
+    GcEvidence {
+        before_baseline: commits.iter().map(|c| c.to_string()).collect(),
+    }
+}
+
+#[test]
+fn baselines_before_the_global_baseline_are_redundant_for_tests_tracked_there() {
+    let mut current = status(&[
+        ("old", Some("c1")),
+        ("added_later", Some("c1")),
+        ("rebased", Some("c3")),
+    ]);
+    current.baseline = Some("c2".into());
+    let snapshots = [
+        snapshot("c1", 0, status(&[("old", None)])),
+        snapshot("c2", 0, status(&[("old", None), ("rebased", None)])),
+        snapshot("c3", 0, current.clone()),
+    ];
+
+    let changes = plan_gc(&current, &snapshots, &before_baseline(&["c1"]), 0, 90);
+
+    assert_eq!(
+        changes,
+        [GcChange::RedundantBaseline {
+            test: "old".into(),
+            baseline: "c1".into(),
+        }]
+    );
+}
+
+#[test]
+fn without_a_global_baseline_per_test_baselines_are_kept() {
+    let current = status(&[("old", Some("c1"))]);
+    let snapshots = [snapshot("c1", 0, current.clone())];
+
+    assert!(plan_gc(&current, &snapshots, &before_baseline(&["c1"]), 0, 90).is_empty());
+}
+
+#[test]
+fn carried_out_renames_expire_after_the_retention_window() {
+    let mut current = status(&[
+        ("new_name", None),
+        ("fresh_new", None),
+        ("pending_new", None),
+        ("pending_old", None),
+    ]);
+    current.renames.insert("new_name".into(), "old_name".into());
+    current
+        .renames
+        .insert("fresh_new".into(), "fresh_old".into());
+    current
+        .renames
+        .insert("pending_new".into(), "pending_old".into());
+    let mut older = current.clone();
+    older.renames.remove("fresh_new");
+    let snapshots = [
+        snapshot("c1", 0, older),
+        snapshot("c2", 95 * DAY, current.clone()),
+    ];
+
+    let changes = plan_gc(&current, &snapshots, &GcEvidence::default(), 100 * DAY, 90);
+
+    assert_eq!(
+        changes,
+        [GcChange::ExpiredRename {
+            new_name: "new_name".into(),
+            old_name: "old_name".into(),
+            age_days: 100,
+        }]
+    );
+}
+
+#[test]
+fn uncommitted_renames_are_kept() {
+    let mut current = status(&[("new_name", None)]);
+    current.renames.insert("new_name".into(), "old_name".into());
+
+    assert!(plan_gc(&current, &[], &GcEvidence::default(), 1000 * DAY, 0).is_empty());
+}
+
+#[test]
+fn orphaned_renames_are_dropped_at_once() {
+    let mut current = status(&[("other", None)]);
+    current.renames.insert("gone_new".into(), "gone_old".into());
+
+    let changes = plan_gc(&current, &[], &GcEvidence::default(), 0, 90);
+    assert_eq!(
+        changes,
+        [GcChange::OrphanedRename {
+            new_name: "gone_new".into(),
+            old_name: "gone_old".into(),
+        }]
+    );
+
+    apply_gc(&mut current, &changes);
+    assert!(current.renames.is_empty());
+}
+
+#[test]
+fn evidence_names_baselines_that_are_ancestors_of_the_global_baseline() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    for message in ["first", "second", "third"] {
+        git(path, &["commit", "--allow-empty", "-m", message]);
+    }
+    let first = git(path, &["rev-parse", "HEAD~2"]);
+    let second = git(path, &["rev-parse", "HEAD~1"]);
+    let third = git(path, &["rev-parse", "HEAD"]);
+    let mut current = status(&[
+        ("a", Some(&first)),
+        ("b", Some(&second)),
+        ("c", Some(&third)),
+    ]);
+    current.baseline = Some(second.clone());
+
+    let evidence = gather_gc_evidence(path, &current).unwrap();
+
+    assert_eq!(evidence, before_baseline(&[&first, &second]));
+    dir.pass();
+}
+
+#[test]
+fn gc_lists_and_removes_redundant_entries() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+    let first = git(path, &["rev-parse", "HEAD"]);
+    fs::write(
+        path.join(".test-status.json"),
+        format!(
+            r#"{{"baseline":"{first}","renames":{{"gone_new":"gone_old"}},"tests":{{"tdd_ratchet_gatekeeper":"passing","feature":{{"state":"passing","baseline":"{first}"}}}}}}"#
+        ),
+    )
+    .unwrap();
+
+    let (code, out) = run_ratchet(path, &["gc", "--dry-run"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("baseline of feature"), "{out}");
+    assert!(out.contains("rename gone_old -> gone_new"), "{out}");
+    assert!(out.contains("dry run"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["gc"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("removed 2 entries"), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests["feature"].baseline, None);
+    assert!(status.renames.is_empty());
+    assert!(status.integrity.unwrap().override_reason.is_some());
+
+    let (code, out) = run_ratchet(path, &["gc"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("nothing to collect"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn gc_rejects_a_bad_retention() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+
+    let (code, out) = run_ratchet(path, &["gc", "--retention-days", "soon"]);
+    assert_eq!(code, Some(2), "{out}");
+    dir.pass();
+}
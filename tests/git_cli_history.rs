@@ -0,0 +1,148 @@
+// tests/git_cli_history.rs
+//
+// Story 36: When libgit2 cannot open the repository, history is read through
+// the `git` CLI instead, with the same results.
+
+mod common;
+
+use common::{TestDir, commit_status, git};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{HistoryBackend, HistoryConfig, RatchetConfig};
+use tdd_ratchet::git_cli::GitCliHistory;
+use tdd_ratchet::history::{GitHistory, HistoryProvider, HistorySnapshot};
+use tdd_ratchet::status::TestState;
+
+fn init_repo(dir: &Path) {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+}
+
+fn summarize(snapshots: &[HistorySnapshot]) -> Vec<(String, String, String, Vec<String>)> {
+    snapshots
+        .iter()
+        .map(|s| {
+            (
+                s.commit.clone(),
+                s.info.author.clone(),
+                s.info.summary.clone(),
+                s.status.tests.keys().cloned().collect(),
+            )
+        })
+        .collect()
+}
+
+/// Mark the repository as a partial clone, which git understands but
+/// libgit2 refuses to open.
+fn break_libgit2(dir: &Path) {
+    git(dir, &["config", "core.repositoryformatversion", "1"]);
+    git(dir, &["config", "extensions.partialclone", "origin"]);
+    assert!(git2::Repository::open(dir).is_err());
+}
+
+#[test]
+fn git_cli_matches_libgit2_history() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    init_repo(path);
+    commit_status(path, r#"{"tests":{"a":"pending"}}"#, "Add a");
+    git(path, &["checkout", "-b", "side"]);
+    commit_status(path, r#"{"tests":{"a":"pending","b":"pending"}}"#, "Add b");
+    git(path, &["checkout", "main"]);
+    fs::write(path.join("notes.txt"), "x").unwrap();
+    git(path, &["add", "-A"]);
+    git(
+        path,
+        &[
+            "commit",
+            "-m",
+            "Pass nothing yet",
+            "-m",
+            "Ratchet-Override: a skipped-pending -- imported",
+        ],
+    );
+    git(path, &["merge", "--no-ff", "side", "-m", "Merge side"]);
+    commit_status(
+        path,
+        r#"{"tests":{"a":"passing","b":"passing"}}"#,
+        "Pass both",
+    );
+
+    let cli = GitCliHistory::new(path);
+    let git2 = GitHistory::new(path);
+
+    let snapshots = cli.snapshots().unwrap();
+    assert_eq!(summarize(&snapshots), summarize(&git2.snapshots().unwrap()));
+    assert_eq!(snapshots.len(), 5);
+    let with_trailer = snapshots
+        .iter()
+        .find(|s| s.info.summary == "Pass nothing yet")
+        .unwrap();
+    assert_eq!(with_trailer.info.overrides.len(), 1);
+    assert_eq!(
+        cli.head_status().unwrap().unwrap().tests["b"].state(),
+        TestState::Passing
+    );
+    dir.pass();
+}
+
+#[test]
+fn git_cli_reads_a_nested_status_file_and_skips_commits_without_it() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    init_repo(path);
+    git(path, &["commit", "--allow-empty", "-m", "Empty"]);
+    fs::create_dir_all(path.join("api")).unwrap();
+    fs::write(
+        path.join("api/.test-status.json"),
+        r#"{"tests":{"t":"pending"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add api"]);
+
+    let api = GitCliHistory::new(path).with_status_path("api/.test-status.json");
+
+    assert_eq!(api.snapshots().unwrap().len(), 1);
+    assert!(api.head_status().unwrap().is_some());
+    assert!(GitCliHistory::new(path).head_status().unwrap().is_none());
+    dir.pass();
+}
+
+#[test]
+fn git_cli_reports_missing_head() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+
+    assert!(GitCliHistory::new(dir.path()).snapshots().is_err());
+    assert!(GitCliHistory::new(dir.path()).head_status().is_err());
+    dir.pass();
+}
+
+#[test]
+fn default_backend_falls_back_to_git_cli() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    init_repo(path);
+    commit_status(path, r#"{"tests":{"a":"pending"}}"#, "Add a");
+    break_libgit2(path);
+
+    let history = HistoryConfig::default().provider(path, Path::new(".test-status.json"));
+
+    assert!(GitHistory::new(path).snapshots().is_err());
+    let snapshots = history.snapshots().unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].info.summary, "Add a");
+    dir.pass();
+}
+
+#[test]
+fn git_cli_backend_is_selectable() {
+    let config = RatchetConfig::parse_from_str(
+        "[history]\nbackend = \"git-cli\"\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(config.history.backend, HistoryBackend::GitCli);
+}
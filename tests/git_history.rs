@@ -4,28 +4,14 @@
 
 mod common;
 
-use common::TestDir;
+use common::{TestDir, git};
 use std::fs;
 use std::path::Path;
 use std::process::Command;
 
-use tdd_ratchet::history::{HistoryViolation, check_history};
-
-fn git(dir: &Path, args: &[&str]) {
-    let out = Command::new("git")
-        .args(args)
-        .current_dir(dir)
-        .env("GIT_CONFIG_NOSYSTEM", "1")
-        .env("HOME", dir)
-        .output()
-        .unwrap();
-    assert!(
-        out.status.success(),
-        "git {:?} failed: {}",
-        args,
-        String::from_utf8_lossy(&out.stderr)
-    );
-}
+use tdd_ratchet::history::{
+    GitHistory, HistoryProvider, HistoryViolation, check_history, check_history_snapshots,
+};
 
 fn init_repo(dir: &Path) {
     git(dir, &["init"]);
@@ -288,3 +274,140 @@ fn later_removed_tests_do_not_keep_old_history_violations_alive() {
     );
     dir.pass();
 }
+
+/// Remove the loose object `rev` names, so reading it fails.
+fn delete_object(dir: &Path, rev: &str) {
+    let out = Command::new("git")
+        .args(["rev-parse", rev])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    let oid = String::from_utf8(out.stdout).unwrap();
+    let oid = oid.trim();
+    fs::remove_file(dir.join(".git/objects").join(&oid[..2]).join(&oid[2..])).unwrap();
+}
+
+#[test]
+fn history_before_the_status_file_is_not_read() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+
+    fs::write(dir.path().join("README.md"), "one").unwrap();
+    commit(dir.path(), "Ancient history");
+    fs::write(dir.path().join("README.md"), "two").unwrap();
+    commit(dir.path(), "Last commit before the ratchet");
+    write_status(dir.path(), r#"{"tests":{"my_test":"pending"}}"#);
+    commit(dir.path(), "Adopt the ratchet");
+    write_status(dir.path(), r#"{"tests":{"my_test":"passing"}}"#);
+    commit(dir.path(), "Test now passes");
+
+    // A walk that searched the oldest commit's tree would fail on it.
+    delete_object(dir.path(), "HEAD~3^{tree}");
+
+    let snapshots = GitHistory::new(dir.path()).snapshots().unwrap();
+    let summaries: Vec<&str> = snapshots.iter().map(|s| s.info.summary.as_str()).collect();
+    assert_eq!(summaries, ["Adopt the ratchet", "Test now passes"]);
+    dir.pass();
+}
+
+#[test]
+fn snapshots_keep_commit_order_when_status_files_repeat() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+
+    // Identical status files are parsed once but reported at every commit.
+    let states = [
+        r#"{"tests":{"a":"pending"}}"#,
+        r#"{"tests":{"a":"pending","b":"pending"}}"#,
+        r#"{"tests":{"a":"pending"}}"#,
+        r#"{"tests":{"a":"passing"}}"#,
+        r#"{"tests":{"a":"pending","b":"pending"}}"#,
+    ];
+    for (i, json) in states.iter().enumerate() {
+        write_status(dir.path(), json);
+        commit(dir.path(), &format!("Commit {i}"));
+    }
+
+    let snapshots = GitHistory::new(dir.path()).snapshots().unwrap();
+    let seen: Vec<String> = snapshots
+        .iter()
+        .map(|s| {
+            let tests: Vec<String> = s
+                .status
+                .tests
+                .iter()
+                .map(|(name, entry)| format!("\"{name}\":\"{}\"", entry.state()))
+                .collect();
+            format!("{{\"tests\":{{{}}}}}", tests.join(","))
+        })
+        .collect();
+    assert_eq!(seen, states);
+    let summaries: Vec<&str> = snapshots.iter().map(|s| s.info.summary.as_str()).collect();
+    assert_eq!(
+        summaries,
+        ["Commit 0", "Commit 1", "Commit 2", "Commit 3", "Commit 4"]
+    );
+    dir.pass();
+}
+
+#[test]
+fn since_bounds_the_history_check_to_later_commits() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+
+    write_status(dir.path(), r#"{"tests":{"a":"pending"}}"#);
+    commit(dir.path(), "Adopt the ratchet");
+    write_status(
+        dir.path(),
+        r#"{"tests":{"a":"pending","old_cheater":"passing"}}"#,
+    );
+    commit(dir.path(), "Old cheater");
+    write_status(
+        dir.path(),
+        r#"{"tests":{"a":"passing","old_cheater":"passing"}}"#,
+    );
+    commit(dir.path(), "Since here");
+    write_status(
+        dir.path(),
+        r#"{"tests":{"a":"passing","old_cheater":"passing","new_cheater":"passing"}}"#,
+    );
+    commit(dir.path(), "New cheater");
+
+    let snapshots = GitHistory::new(dir.path())
+        .with_since("HEAD~1")
+        .snapshots()
+        .unwrap();
+    let summaries: Vec<&str> = snapshots.iter().map(|s| s.info.summary.as_str()).collect();
+    assert_eq!(summaries, ["Since here", "New cheater"]);
+
+    let flagged: Vec<String> = check_history_snapshots(&snapshots)
+        .into_iter()
+        .map(|v| match v {
+            HistoryViolation::SkippedPending { test, .. } => test,
+        })
+        .collect();
+    assert_eq!(flagged, ["new_cheater"]);
+    dir.pass();
+}
+
+#[test]
+fn since_must_be_an_ancestor_of_the_start() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+
+    write_status(dir.path(), r#"{"tests":{"a":"pending"}}"#);
+    commit(dir.path(), "Adopt the ratchet");
+    git(dir.path(), &["checkout", "-b", "side"]);
+    commit(dir.path(), "Side work");
+    git(dir.path(), &["checkout", "-"]);
+
+    let error = GitHistory::new(dir.path())
+        .with_since("side")
+        .snapshots()
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("not an ancestor"),
+        "unexpected error: {error}"
+    );
+    dir.pass();
+}
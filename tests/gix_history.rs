@@ -0,0 +1,209 @@
+// tests/gix_history.rs
+//
+// Story 35: History can be read with gitoxide instead of libgit2, chosen with
+// `[history] backend`, and both backends see the same snapshots.
+
+#[cfg(feature = "gix")]
+mod common;
+
+use std::path::Path;
+use tdd_ratchet::config::{HistoryBackend, RatchetConfig};
+
+#[test]
+fn history_backend_defaults_to_git2() {
+    let config = RatchetConfig::parse_from_str("", Path::new("ratchet.toml")).unwrap();
+    assert_eq!(config.history.backend, HistoryBackend::Git2);
+
+    let config =
+        RatchetConfig::parse_from_str("[history]\nbackend = \"git2\"\n", Path::new("ratchet.toml"))
+            .unwrap();
+    assert_eq!(config.history.backend, HistoryBackend::Git2);
+}
+
+#[test]
+fn unknown_history_backend_is_rejected() {
+    let err =
+        RatchetConfig::parse_from_str("[history]\nbackend = \"svn\"\n", Path::new("ratchet.toml"))
+            .unwrap_err();
+    assert!(err.to_string().contains("svn"), "{err}");
+}
+
+#[cfg(feature = "gix")]
+mod gix_backend {
+    use super::common::TestDir;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use tdd_ratchet::config::{HistoryBackend, RatchetConfig};
+    use tdd_ratchet::gix_history::GixHistory;
+    use tdd_ratchet::history::{GitHistory, HistoryProvider, HistorySnapshot};
+
+    fn git(dir: &Path, args: &[&str]) {
+        let out = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("HOME", dir)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    fn commit_status(dir: &Path, path: &str, json: &str, msg: &str) {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, json).unwrap();
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-m", msg]);
+    }
+
+    fn summarize(snapshots: &[HistorySnapshot]) -> Vec<(String, String, Vec<String>)> {
+        snapshots
+            .iter()
+            .map(|s| {
+                (
+                    s.commit.clone(),
+                    s.info.summary.clone(),
+                    s.status.tests.keys().cloned().collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn gix_backend_is_selectable() {
+        let config = RatchetConfig::parse_from_str(
+            "[history]\nbackend = \"gix\"\n",
+            Path::new("ratchet.toml"),
+        )
+        .unwrap();
+        assert_eq!(config.history.backend, HistoryBackend::Gix);
+    }
+
+    #[test]
+    fn gix_and_git2_see_the_same_history_across_merges() {
+        let dir = TestDir::new();
+        let path = dir.path();
+        git(path, &["init", "-b", "main"]);
+        git(path, &["config", "user.email", "test@test.com"]);
+        git(path, &["config", "user.name", "Test"]);
+        commit_status(
+            path,
+            ".test-status.json",
+            r#"{"tests":{"a":"pending"}}"#,
+            "Add a",
+        );
+        git(path, &["checkout", "-b", "side"]);
+        commit_status(
+            path,
+            ".test-status.json",
+            r#"{"tests":{"a":"pending","b":"pending"}}"#,
+            "Add b",
+        );
+        git(path, &["checkout", "main"]);
+        commit_status(
+            path,
+            ".test-status.json",
+            r#"{"tests":{"a":"passing"}}"#,
+            "Pass a",
+        );
+        fs::write(path.join("notes.txt"), "x").unwrap();
+        git(path, &["add", "-A"]);
+        git(path, &["commit", "-m", "Notes without status change"]);
+        git(
+            path,
+            &[
+                "merge",
+                "--no-ff",
+                "-X",
+                "theirs",
+                "side",
+                "-m",
+                "Merge side",
+            ],
+        );
+
+        let git2 = GitHistory::new(path);
+        let gix = GixHistory::new(path);
+
+        let expected = summarize(&git2.snapshots().unwrap());
+        assert_eq!(expected.len(), 5);
+        assert_eq!(summarize(&gix.snapshots().unwrap()), expected);
+        assert_eq!(
+            gix.head_status()
+                .unwrap()
+                .unwrap()
+                .tests
+                .keys()
+                .collect::<Vec<_>>(),
+            git2.head_status()
+                .unwrap()
+                .unwrap()
+                .tests
+                .keys()
+                .collect::<Vec<_>>()
+        );
+        dir.pass();
+    }
+
+    #[test]
+    fn gix_backend_stops_where_the_status_file_was_introduced() {
+        let dir = TestDir::new();
+        let path = dir.path();
+        git(path, &["init"]);
+        git(path, &["config", "user.email", "test@test.com"]);
+        git(path, &["config", "user.name", "Test"]);
+        commit_status(path, "README.md", "one", "Ancient history");
+        commit_status(path, "README.md", "two", "Before the ratchet");
+        commit_status(
+            path,
+            ".test-status.json",
+            r#"{"tests":{"t":"pending"}}"#,
+            "Adopt the ratchet",
+        );
+
+        // Reading the oldest commit's tree would fail.
+        let out = Command::new("git")
+            .args(["rev-parse", "HEAD~2^{tree}"])
+            .current_dir(path)
+            .output()
+            .unwrap();
+        let oid = String::from_utf8(out.stdout).unwrap();
+        let oid = oid.trim();
+        fs::remove_file(path.join(".git/objects").join(&oid[..2]).join(&oid[2..])).unwrap();
+
+        let gix = GixHistory::new(path);
+        assert_eq!(
+            summarize(&gix.snapshots().unwrap()),
+            summarize(&GitHistory::new(path).snapshots().unwrap())
+        );
+        assert_eq!(gix.snapshots().unwrap().len(), 1);
+        dir.pass();
+    }
+
+    #[test]
+    fn gix_backend_reads_a_nested_status_file() {
+        let dir = TestDir::new();
+        let path = dir.path();
+        git(path, &["init"]);
+        git(path, &["config", "user.email", "test@test.com"]);
+        git(path, &["config", "user.name", "Test"]);
+        commit_status(
+            path,
+            "api/.test-status.json",
+            r#"{"tests":{"t":"pending"}}"#,
+            "Add t",
+        );
+
+        let gix = GixHistory::new(path).with_status_path("api/.test-status.json");
+
+        assert_eq!(gix.snapshots().unwrap().len(), 1);
+        assert!(GixHistory::new(path).head_status().unwrap().is_none());
+        dir.pass();
+    }
+}
@@ -0,0 +1,106 @@
+// tests/grandfather_patterns.rs
+//
+// Story 63: `grandfather` patterns in `ratchet.toml` exempt whole families of
+// pre-existing tests from NewTestPassed and SkippedPending.
+
+mod common;
+
+use common::{result, snapshot, status};
+use std::path::Path;
+use tdd_ratchet::config::RatchetConfig;
+use tdd_ratchet::grandfather::GrandfatherPatterns;
+use tdd_ratchet::history::{HistoryViolation, check_history_snapshots_with};
+use tdd_ratchet::ratchet::{Violation, evaluate_with};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{TestEntry, TestState, TrackedStatus, WorkingTreeInstructions};
+
+const GATEKEEPER: &str = "app::tests$tdd_ratchet_gatekeeper";
+
+fn patterns() -> GrandfatherPatterns {
+    GrandfatherPatterns::new(["legacy::*", "vendor_*"])
+}
+
+#[test]
+fn grandfather_patterns_are_configurable() {
+    let config = RatchetConfig::parse_from_str(
+        "grandfather = [\"legacy::*\", \"vendor_*\"]\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(config.grandfather, patterns());
+    assert!(RatchetConfig::default().grandfather.is_empty());
+}
+
+#[test]
+fn patterns_match_the_full_name_or_the_test_path() {
+    let patterns = patterns();
+    assert!(patterns.matches("app::tests$legacy::parser::parses"));
+    assert!(patterns.matches("app::tests$vendor_zlib"));
+    assert!(patterns.matches("legacy::old$anything"));
+    assert!(!patterns.matches("app::tests$modern::legacy::parses"));
+    assert!(!patterns.matches("app::tests$my_vendor_test"));
+
+    let exact = GrandfatherPatterns::new(["app::*$*_compat"]);
+    assert!(exact.matches("app::tests$io_compat"));
+    assert!(!exact.matches("app::tests$io_compat_more"));
+}
+
+#[test]
+fn new_passing_tests_matching_a_pattern_are_recorded_as_passing() {
+    let results = [
+        result(GATEKEEPER, TestOutcome::Passed),
+        result("app::tests$legacy::parses", TestOutcome::Passed),
+        result("app::tests$vendor_zlib", TestOutcome::Passed),
+        result("app::tests$new_feature", TestOutcome::Passed),
+    ];
+
+    let result = evaluate_with(
+        &TrackedStatus::empty(),
+        &WorkingTreeInstructions::default(),
+        &results,
+        &[],
+        &patterns(),
+    );
+
+    let flagged: Vec<&str> = result
+        .violations
+        .iter()
+        .filter_map(|v| match v {
+            Violation::NewTestPassed { test } => Some(test.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(flagged, ["app::tests$new_feature"]);
+    for test in ["app::tests$legacy::parses", "app::tests$vendor_zlib"] {
+        assert_eq!(
+            result.updated.tests.get(test).map(TestEntry::state),
+            Some(TestState::Passing),
+            "{test} should be recorded as passing"
+        );
+    }
+}
+
+#[test]
+fn history_exempts_tests_matching_a_pattern_from_skipped_pending() {
+    use TestState::{Passing, Pending};
+    let snapshots = [
+        snapshot("c1", status(&[("app::tests$existing", Passing)])),
+        snapshot(
+            "c2",
+            status(&[
+                ("app::tests$existing", Passing),
+                ("app::tests$legacy::parses", Passing),
+                ("app::tests$cheater", Passing),
+                ("app::tests$honest", Pending),
+            ]),
+        ),
+    ];
+
+    let flagged: Vec<String> = check_history_snapshots_with(&snapshots, None, &patterns())
+        .into_iter()
+        .map(|v| match v {
+            HistoryViolation::SkippedPending { test, .. } => test,
+        })
+        .collect();
+    assert_eq!(flagged, ["app::tests$cheater"]);
+}
@@ -0,0 +1,190 @@
+// tests/hooks.rs
+//
+// Story 76: commands under `[hooks]` run after evaluation with the event as
+// JSON on stdin, for promotions, violations, and new pending tests.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use tdd_ratchet::error::{EvaluationError, RatchetError};
+use tdd_ratchet::hooks::{HookEvent, hook_events, run_hook};
+use tdd_ratchet::ratchet::{EvalResult, Violation};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, TrackedStatus};
+
+fn tracked(tests: &[(&str, TestState)]) -> TrackedStatus {
+    TrackedStatus::new(
+        tests
+            .iter()
+            .map(|(n, s)| (n.to_string(), TestEntry::new(*s)))
+            .collect(),
+    )
+}
+
+fn result(tests: &[(&str, TestState)], violations: Vec<Violation>) -> EvalResult {
+    EvalResult {
+        violations,
+        warnings: Vec::new(),
+        updated: StatusFile::from_parts(tracked(tests), Default::default()),
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+fn sh(script: &str) -> Vec<String> {
+    vec!["sh".into(), "-c".into(), script.into()]
+}
+
+#[test]
+fn events_name_promoted_and_new_pending_tests() {
+    let before = tracked(&[
+        ("promoted", TestState::Pending),
+        ("kept", TestState::Passing),
+    ]);
+    let after = result(
+        &[
+            ("promoted", TestState::Passing),
+            ("kept", TestState::Passing),
+            ("new_pending", TestState::Pending),
+            ("new_passing", TestState::Passing),
+        ],
+        Vec::new(),
+    );
+
+    assert_eq!(
+        hook_events(&before, &after),
+        [
+            HookEvent::Promotion {
+                tests: vec!["promoted".into()]
+            },
+            HookEvent::NewPending {
+                tests: vec!["new_pending".into()]
+            },
+        ]
+    );
+}
+
+#[test]
+fn violations_are_one_event_with_their_codes() {
+    let before = tracked(&[("kept", TestState::Passing)]);
+    let after = result(
+        &[("kept", TestState::Passing)],
+        vec![Violation::Regression {
+            test: "kept".into(),
+        }],
+    );
+
+    let events = hook_events(&before, &after);
+
+    let [HookEvent::Violation { violations }] = events.as_slice() else {
+        panic!("expected one violation event: {events:?}");
+    };
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].code, "RATCHET002");
+    assert_eq!(violations[0].subject, "kept");
+}
+
+#[test]
+fn quiet_runs_have_no_events() {
+    let before = tracked(&[("kept", TestState::Passing)]);
+
+    assert!(
+        hook_events(
+            &before,
+            &result(&[("kept", TestState::Passing)], Vec::new())
+        )
+        .is_empty()
+    );
+}
+
+#[test]
+fn hook_reads_the_event_as_json_on_stdin() {
+    let dir = TestDir::new();
+    let event = HookEvent::Promotion {
+        tests: vec!["a".into()],
+    };
+
+    run_hook(&sh("cat > event.json"), dir.path(), &event, Some("abc123")).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("event.json")).unwrap()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"event": "promotion", "tests": ["a"], "head": "abc123"})
+    );
+    dir.pass();
+}
+
+#[test]
+fn failing_or_empty_hooks_are_errors() {
+    let dir = TestDir::new();
+    let event = HookEvent::NewPending {
+        tests: vec!["a".into()],
+    };
+
+    assert!(matches!(
+        run_hook(&sh("exit 3"), dir.path(), &event, None),
+        Err(RatchetError::CommandExit { .. })
+    ));
+    assert!(matches!(
+        run_hook(&[], dir.path(), &event, None),
+        Err(RatchetError::Evaluation(EvaluationError::EmptyCommand(_)))
+    ));
+    assert!(run_hook(&sh("true"), dir.path(), &event, None).is_ok());
+    dir.pass();
+}
+
+#[test]
+fn configured_hooks_run_after_evaluation_without_failing_it() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [hooks]\non-new-pending = [\"sh\", \"-c\", \"cat > new-pending.json\"]\non-violation = [\"false\"]\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\ntest next ... FAILED\n",
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    let event = fs::read_to_string(path.join("new-pending.json")).unwrap();
+    assert!(event.contains(r#""event":"new-pending""#), "{event}");
+    assert!(event.contains(r#""tests":["next"]"#), "{event}");
+
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... FAILED\ntest next ... FAILED\n",
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("on-violation hook failed"), "{out}");
+    dir.pass();
+}
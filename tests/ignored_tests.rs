@@ -0,0 +1,229 @@
+// tests/ignored_tests.rs
+//
+// Story 38: Optional rule — a passing test that becomes `#[ignore]`d is
+// reported, unless it was explicitly allowed with `cargo ratchet allow-ignore`.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RuleLevel, RulesConfig};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::{Violation, Warning};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, TrackedStatus};
+
+fn set_results(dir: &Path, results: &[(&str, &str)]) {
+    let lines: String = results
+        .iter()
+        .map(|(name, outcome)| format!("test {name} ... {outcome}\n"))
+        .collect();
+    fs::write(dir.join("results.txt"), lines).unwrap();
+}
+
+fn gathered(level: RuleLevel, allow_ignore: bool, outcome: TestOutcome) -> GatheredRun {
+    let mut entry = TestEntry::new(TestState::Passing);
+    entry.allow_ignore = allow_ignore;
+    let status = TrackedStatus::new(
+        [
+            (
+                "tdd_ratchet_gatekeeper".to_string(),
+                TestEntry::new(TestState::Passing),
+            ),
+            ("feature".to_string(), entry),
+        ]
+        .into(),
+    );
+    GatheredRun {
+        status,
+        results: [
+            ("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+            ("feature", outcome),
+        ]
+        .iter()
+        .map(|(name, outcome)| result(name, *outcome))
+        .collect(),
+        rules: RulesConfig {
+            passing_to_ignored: level,
+            ..RulesConfig::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn passing_to_ignored_defaults_to_allow() {
+    let config = RatchetConfig::parse_from_str("", Path::new("ratchet.toml")).unwrap();
+    assert_eq!(config.rules.passing_to_ignored, RuleLevel::Allow);
+
+    let config = RatchetConfig::parse_from_str(
+        "[rules]\npassing-to-ignored = \"warn\"\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(config.rules.passing_to_ignored, RuleLevel::Warn);
+
+    assert!(
+        RatchetConfig::parse_from_str(
+            "[rules]\npassing-to-ignored = \"sometimes\"\n",
+            Path::new("ratchet.toml"),
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn ignored_passing_test_is_not_reported_by_default() {
+    let result = gathered(RuleLevel::Allow, false, TestOutcome::Ignored).evaluate();
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+}
+
+#[test]
+fn ignored_passing_test_is_a_violation_when_denied() {
+    let result = gathered(RuleLevel::Deny, false, TestOutcome::Ignored).evaluate();
+
+    assert!(
+        matches!(
+            result.violations.as_slice(),
+            [Violation::PassingTestIgnored { test }] if test == "feature"
+        ),
+        "{:?}",
+        result.violations
+    );
+    let report = format_report(&result);
+    assert!(report.contains("passing test ignored"), "{report}");
+    assert!(report.contains("cargo ratchet allow-ignore"), "{report}");
+}
+
+#[test]
+fn ignored_passing_test_is_a_warning_when_warned() {
+    let result = gathered(RuleLevel::Warn, false, TestOutcome::Ignored).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(
+        matches!(
+            result.warnings.as_slice(),
+            [Warning::PassingTestIgnored { test }] if test == "feature"
+        ),
+        "{:?}",
+        result.warnings
+    );
+    let report = format_report(&result);
+    assert!(report.contains("now ignored: feature"), "{report}");
+    assert!(report.contains("tdd-ratchet: ok"), "{report}");
+}
+
+#[test]
+fn allowed_ignore_is_not_reported_and_is_kept_while_ignored() {
+    let result = gathered(RuleLevel::Deny, true, TestOutcome::Ignored).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(result.updated.tests["feature"].allow_ignore);
+}
+
+#[test]
+fn allowed_ignore_is_cleared_once_the_test_runs_again() {
+    let result = gathered(RuleLevel::Deny, true, TestOutcome::Passed).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(!result.updated.tests["feature"].allow_ignore);
+}
+
+#[test]
+fn working_tree_allow_ignore_has_no_effect_until_committed() {
+    let mut entry = TestEntry::new(TestState::Passing);
+    entry.allow_ignore = true;
+    let marked = StatusFile::new([("feature".to_string(), entry)].into());
+    let mut run = gathered(RuleLevel::Deny, false, TestOutcome::Ignored);
+    run.instructions = marked.working_tree_instructions();
+
+    let result = run.evaluate();
+
+    assert!(
+        result
+            .violations
+            .iter()
+            .any(|v| matches!(v, Violation::PassingTestIgnored { test } if test == "feature")),
+        "{:?}",
+        result.violations
+    );
+    assert!(!result.updated.tests["feature"].allow_ignore);
+}
+
+#[test]
+fn working_tree_can_withdraw_a_committed_allow_ignore() {
+    let unmarked =
+        StatusFile::new([("feature".to_string(), TestEntry::new(TestState::Passing))].into());
+    let mut run = gathered(RuleLevel::Deny, true, TestOutcome::Ignored);
+    run.instructions = unmarked.working_tree_instructions();
+
+    let result = run.evaluate();
+
+    assert!(!result.violations.is_empty());
+    assert!(!result.updated.tests["feature"].allow_ignore);
+}
+
+#[test]
+fn allow_ignore_round_trips_through_the_status_file() {
+    let json =
+        r#"{"version":2,"tests":{"a":{"state":"passing","allow_ignore":true},"b":"passing"}}"#;
+    let status = StatusFile::parse_from_str(json, Path::new(".test-status.json")).unwrap();
+    assert!(status.tests["a"].allow_ignore);
+    assert!(!status.tests["b"].allow_ignore);
+
+    let written = serde_json::to_string(&status).unwrap();
+    assert!(written.contains("\"allow_ignore\":true"), "{written}");
+    assert_eq!(written.matches("allow_ignore").count(), 1, "{written}");
+}
+
+#[test]
+fn allow_ignore_command_marks_the_test_and_the_ratchet_accepts_it() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n\n[rules]\npassing-to-ignored = \"deny\"\n",
+    )
+    .unwrap();
+    let status = StatusFile::new(
+        [
+            (
+                "tdd_ratchet_gatekeeper".to_string(),
+                TestEntry::new(TestState::Passing),
+            ),
+            ("feature".to_string(), TestEntry::new(TestState::Passing)),
+        ]
+        .into(),
+    );
+    status.save(&path.join(".test-status.json")).unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Track feature"]);
+
+    set_results(
+        path,
+        &[("tdd_ratchet_gatekeeper", "ok"), ("feature", "ignored")],
+    );
+    let (code, out) = run_ratchet(path, &[]);
+    assert_ne!(code, Some(0), "ignoring a passing test is denied: {out}");
+    assert!(out.contains("feature"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["allow-ignore", "missing"]);
+    assert_ne!(code, Some(0), "{out}");
+    assert!(out.contains("not tracked"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["allow-ignore", "feature"]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["commit", "-am", "Allow feature to be ignored"]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "allowed ignore is accepted: {out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert!(status.tests["feature"].allow_ignore);
+    dir.pass();
+}
@@ -0,0 +1,192 @@
+// tests/infrastructure_retries.rs
+//
+// Story 72: a run killed by a signal, or whose build died in the linker or
+// the compiler cache, is retried as a whole before anything is reported,
+// and the report says how often it was retried.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::process::ExitStatus;
+use tdd_ratchet::runner::{
+    CommandRunner, OutputParser, RetryingRunner, RunnerError, TestOutcome, TestRunner,
+    infrastructure_failure,
+};
+
+/// A shell runner in `dir` that is killed by SIGKILL on its first
+/// `failures` attempts and reports one passing test after that. Each
+/// attempt appends a line to `attempts`.
+fn flaky_machine(dir: &Path, failures: usize) -> CommandRunner {
+    let script = format!(
+        "echo x >> attempts; \
+         if [ $(wc -l < attempts) -le {failures} ]; then kill -9 $$; fi; \
+         echo 'test feature ... ok'"
+    );
+    CommandRunner::new(
+        &["sh".into(), "-c".into(), script],
+        dir,
+        OutputParser::Libtest,
+    )
+    .unwrap()
+}
+
+fn attempts(dir: &Path) -> usize {
+    fs::read_to_string(dir.join("attempts"))
+        .unwrap()
+        .lines()
+        .count()
+}
+
+#[test]
+fn signal_kill_is_an_infrastructure_failure() {
+    let killed = ExitStatus::from_raw(9);
+
+    assert_eq!(
+        infrastructure_failure(killed, "").as_deref(),
+        Some("killed by signal 9")
+    );
+}
+
+#[test]
+fn linker_and_cache_errors_are_infrastructure_failures() {
+    let failed = ExitStatus::from_raw(101 << 8);
+
+    for output in [
+        "collect2: fatal error: ld terminated with signal 9 [Killed]",
+        "error: could not compile `big` (lib)\nCaused by: process didn't exit successfully (signal: 9, SIGKILL: kill)",
+        "sccache: error: Server startup failed: cache storage failed to read",
+    ] {
+        assert!(infrastructure_failure(failed, output).is_some(), "{output}");
+    }
+}
+
+#[test]
+fn compile_errors_are_not_infrastructure_failures() {
+    let failed = ExitStatus::from_raw(101 << 8);
+
+    assert_eq!(
+        infrastructure_failure(failed, "error[E0425]: cannot find value `x` in this scope"),
+        None
+    );
+    assert_eq!(
+        infrastructure_failure(ExitStatus::from_raw(0), "SIGKILL"),
+        None
+    );
+}
+
+#[test]
+fn killed_command_reports_an_infrastructure_failure() {
+    let dir = TestDir::new();
+    let runner = flaky_machine(dir.path(), 1);
+
+    let err = runner.run().unwrap_err();
+
+    assert!(
+        matches!(err, RunnerError::Infrastructure { retries: 0, .. }),
+        "{err}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn retrying_runner_retries_until_the_run_succeeds() {
+    let dir = TestDir::new();
+    let runner = RetryingRunner::new(Box::new(flaky_machine(dir.path(), 2)), 2);
+
+    let results = runner.run().unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].outcome, TestOutcome::Passed);
+    assert_eq!(runner.infrastructure_retries(), 2);
+    assert_eq!(attempts(dir.path()), 3);
+    dir.pass();
+}
+
+#[test]
+fn retrying_runner_gives_up_after_its_retries() {
+    let dir = TestDir::new();
+    let runner = RetryingRunner::new(Box::new(flaky_machine(dir.path(), 5)), 1);
+
+    let err = runner.run().unwrap_err();
+
+    assert!(
+        matches!(err, RunnerError::Infrastructure { retries: 1, .. }),
+        "{err}"
+    );
+    assert!(err.to_string().contains("after 1 retry"), "{err}");
+    assert_eq!(attempts(dir.path()), 2);
+    dir.pass();
+}
+
+#[test]
+fn compile_failures_are_not_retried() {
+    let dir = TestDir::new();
+    let script = "echo x >> attempts; echo 'error[E0425]: cannot find value'; exit 101";
+    let runner = RetryingRunner::new(
+        Box::new(
+            CommandRunner::new(
+                &["sh".into(), "-c".into(), script.into()],
+                dir.path(),
+                OutputParser::Libtest,
+            )
+            .unwrap(),
+        ),
+        2,
+    );
+
+    let err = runner.run().unwrap_err();
+
+    assert!(matches!(err, RunnerError::CompileFailed { .. }), "{err}");
+    assert_eq!(runner.infrastructure_retries(), 0);
+    assert_eq!(attempts(dir.path()), 1);
+    dir.pass();
+}
+
+#[test]
+fn report_is_tagged_with_the_retry_count() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n[[runners]]\nkind = \"command\"\ncommand = [\"sh\", \"-c\", \"echo x >> attempts; if [ $(wc -l < attempts) -le 1 ]; then kill -9 $$; fi; cat results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(path.join(".gitignore"), "attempts\n").unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(
+        out.contains("test run retried 1 time after infrastructure failures"),
+        "{out}"
+    );
+
+    fs::write(
+        path.join("ratchet.toml"),
+        "[retries]\ninfrastructure = 0\n\n[rules]\ndirty-worktree = \"allow\"\n\n[[runners]]\nkind = \"command\"\ncommand = [\"sh\", \"-c\", \"kill -9 $$\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("infrastructure failure"), "{out}");
+    assert!(out.contains("after 0 retries"), "{out}");
+    dir.pass();
+}
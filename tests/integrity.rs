@@ -0,0 +1,402 @@
+// tests/integrity.rs
+//
+// Story 28: Status files carry a chained fingerprint so hand edits to
+// committed status are detected in history.
+
+mod common;
+
+use common::{snapshot, status};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::history::HistorySnapshot;
+use tdd_ratchet::integrity::{
+    IntegrityFinding, SEAL_VERSION, TamperProblem, check_integrity, fingerprint_version, seal,
+};
+use tdd_ratchet::ratchet::{Violation, Warning, evaluate};
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::status::{
+    AcceptedFailure, Integrity, Quarantine, StatusFile, TestState, TrackedStatus,
+    WorkingTreeInstructions,
+};
+
+/// A status sealed onto the previous snapshot's digest, as the ratchet
+/// writes it.
+fn sealed(previous: Option<&HistorySnapshot>, tests: &[(&str, TestState)]) -> StatusFile {
+    let mut status = status(tests);
+    let previous = previous.and_then(|s| s.status.integrity.as_ref().map(|i| i.digest.as_str()));
+    status.integrity = Some(seal(previous, &status));
+    status
+}
+
+#[test]
+fn ratchet_written_history_passes_the_check() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Pending)]));
+    let second = snapshot("c2", sealed(Some(&first), &[("t", TestState::Passing)]));
+    let unchanged = snapshot("c3", second.status.clone());
+
+    assert_eq!(check_integrity(&[first, second, unchanged]), vec![]);
+}
+
+#[test]
+fn unsealed_legacy_history_is_not_checked() {
+    let legacy = snapshot("c1", status(&[("t", TestState::Pending)]));
+    let first_sealed = snapshot("c2", sealed(Some(&legacy), &[("t", TestState::Passing)]));
+
+    assert_eq!(check_integrity(&[legacy, first_sealed]), vec![]);
+}
+
+#[test]
+fn hand_promoted_test_is_flagged() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Pending)]));
+    let mut edited = first.status.clone();
+    edited.set_test_state("t", TestState::Passing);
+    let edited = snapshot("c2", edited);
+
+    assert_eq!(
+        check_integrity(&[first, edited]),
+        vec![IntegrityFinding::Tampered {
+            commit: "c2".into(),
+            problem: TamperProblem::DigestMismatch,
+        }]
+    );
+}
+
+#[test]
+fn recomputed_seal_that_skips_the_chain_is_flagged() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Pending)]));
+    let forged = snapshot("c2", sealed(None, &[("t", TestState::Passing)]));
+
+    assert_eq!(
+        check_integrity(&[first, forged]),
+        vec![IntegrityFinding::Tampered {
+            commit: "c2".into(),
+            problem: TamperProblem::BrokenChain,
+        }]
+    );
+}
+
+#[test]
+fn removed_seal_is_flagged() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Pending)]));
+    let stripped = snapshot("c2", status(&[("t", TestState::Passing)]));
+
+    assert_eq!(
+        check_integrity(&[first, stripped]),
+        vec![IntegrityFinding::Tampered {
+            commit: "c2".into(),
+            problem: TamperProblem::SealRemoved,
+        }]
+    );
+}
+
+#[test]
+fn timestamps_and_metadata_are_not_covered() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Pending)]));
+    let mut annotated = first.status.clone();
+    let entry = annotated.tests.get_mut("t").unwrap();
+    entry.added_at = Some(123);
+    entry.metadata.insert("owner".into(), "team-x".into());
+
+    assert_eq!(check_integrity(&[first, snapshot("c2", annotated)]), vec![]);
+}
+
+#[test]
+fn hand_added_quarantine_is_flagged() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Passing)]));
+    let mut edited = first.status.clone();
+    edited.tests.get_mut("t").unwrap().quarantine = Some(Quarantine {
+        flips: 3,
+        commit: None,
+    });
+
+    assert_eq!(
+        check_integrity(&[first, snapshot("c2", edited)]),
+        vec![IntegrityFinding::Tampered {
+            commit: "c2".into(),
+            problem: TamperProblem::DigestMismatch,
+        }]
+    );
+}
+
+#[test]
+fn hand_edits_that_weaken_enforcement_are_flagged() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Passing)]));
+    let edits: [fn(&mut StatusFile); 5] = [
+        |status| status.tests.get_mut("t").unwrap().allow_ignore = true,
+        |status| {
+            status.tests.get_mut("t").unwrap().accepted_failure = Some(AcceptedFailure {
+                reason: "flaky".into(),
+                until: Some("2099-01-01".into()),
+                commits: None,
+            })
+        },
+        |status| {
+            status.tests.get_mut("t").unwrap().quarantine = Some(Quarantine {
+                flips: 3,
+                commit: None,
+            })
+        },
+        |status| status.baseline = Some("c1".into()),
+        |status| {
+            status.renames.insert("t".into(), "old_t".into());
+        },
+    ];
+
+    for edit in edits {
+        let mut edited = first.status.clone();
+        edit(&mut edited);
+        assert_eq!(
+            check_integrity(&[first.clone(), snapshot("c2", edited)]),
+            vec![IntegrityFinding::Tampered {
+                commit: "c2".into(),
+                problem: TamperProblem::DigestMismatch,
+            }]
+        );
+    }
+}
+
+#[test]
+fn older_seals_are_checked_in_their_format_until_resealed() {
+    // Written by a ratchet that sealed in the first format.
+    let mut legacy = status(&[("t", TestState::Pending)]);
+    legacy.integrity = Some(Integrity {
+        previous: None,
+        digest: fingerprint_version(1, None, &legacy),
+        version: 1,
+        override_reason: None,
+    });
+    let legacy = snapshot("c1", legacy);
+    let resealed = snapshot("c2", sealed(Some(&legacy), &[("t", TestState::Passing)]));
+    assert_eq!(
+        resealed.status.integrity.as_ref().unwrap().version,
+        SEAL_VERSION
+    );
+    assert_eq!(check_integrity(&[legacy.clone(), resealed.clone()]), vec![]);
+
+    // Going back to the older format after a newer seal is flagged.
+    let mut downgraded = resealed.status.clone();
+    downgraded.set_test_state("u", TestState::Pending);
+    downgraded.integrity = Some(Integrity {
+        previous: Some(resealed.status.integrity.as_ref().unwrap().digest.clone()),
+        digest: fingerprint_version(1, None, &downgraded),
+        version: 1,
+        override_reason: None,
+    });
+    assert_eq!(
+        check_integrity(&[legacy, resealed, snapshot("c3", downgraded)]),
+        vec![IntegrityFinding::Tampered {
+            commit: "c3".into(),
+            problem: TamperProblem::Downgraded,
+        }]
+    );
+}
+
+#[test]
+fn reseal_override_waives_earlier_history_and_warns() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Pending)]));
+    let mut edited = first.status.clone();
+    edited.set_test_state("t", TestState::Passing);
+    let edited = snapshot("c2", edited);
+    let mut resealed = sealed(Some(&first), &[("t", TestState::Passing)]);
+    resealed.integrity.as_mut().unwrap().override_reason = Some("fix bad merge".into());
+    let resealed = snapshot("c3", resealed);
+
+    let snapshots = [first, edited, resealed];
+    assert_eq!(
+        check_integrity(&snapshots),
+        vec![IntegrityFinding::Overridden {
+            commit: "c3".into(),
+            reason: "fix bad merge".into(),
+        }]
+    );
+
+    let next = snapshot(
+        "c4",
+        sealed(Some(&snapshots[2]), &[("t", TestState::Passing)]),
+    );
+    let mut snapshots = snapshots.to_vec();
+    snapshots.push(next);
+    assert_eq!(
+        check_integrity(&snapshots),
+        vec![IntegrityFinding::Overridden {
+            commit: "c3".into(),
+            reason: "fix bad merge".into(),
+        }],
+        "the override stays on record after later commits"
+    );
+}
+
+#[test]
+fn every_override_in_history_is_reported_once() {
+    let first = snapshot("c1", sealed(None, &[("t", TestState::Pending)]));
+    let mut status = sealed(Some(&first), &[("t", TestState::Passing)]);
+    status.integrity.as_mut().unwrap().override_reason = Some("first edit".into());
+    let overridden = snapshot("c2", status);
+    let unchanged = snapshot("c3", overridden.status.clone());
+    let mut status = sealed(Some(&unchanged), &[("t", TestState::Pending)]);
+    status.integrity.as_mut().unwrap().override_reason = Some("second edit".into());
+    let overridden_again = snapshot("c4", status);
+    let next = snapshot(
+        "c5",
+        sealed(Some(&overridden_again), &[("t", TestState::Passing)]),
+    );
+
+    assert_eq!(
+        check_integrity(&[first, overridden, unchanged, overridden_again, next]),
+        vec![
+            IntegrityFinding::Overridden {
+                commit: "c2".into(),
+                reason: "first edit".into(),
+            },
+            IntegrityFinding::Overridden {
+                commit: "c4".into(),
+                reason: "second edit".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn evaluate_seals_onto_head_and_reports_tampering() {
+    let first = snapshot("c1aaaaaaaa", sealed(None, &[("t", TestState::Pending)]));
+    let mut edited = first.status.clone();
+    edited.set_test_state("t", TestState::Passing);
+    let snapshots = vec![first.clone(), snapshot("c2bbbbbbbb", edited.clone())];
+    let results = vec![
+        TestResult {
+            name: "tdd_ratchet_gatekeeper".into(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        },
+        TestResult {
+            name: "t".into(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        },
+    ];
+
+    let result = evaluate(
+        &TrackedStatus::new(edited.tests.clone()),
+        &WorkingTreeInstructions::default(),
+        &results,
+        &snapshots,
+    );
+
+    assert!(
+        result.violations.iter().any(|v| matches!(
+            v,
+            Violation::StatusTampered { commit, problem: TamperProblem::DigestMismatch }
+                if commit == "c2bbbbbbbb"
+        )),
+        "{:?}",
+        result.violations
+    );
+    let integrity = result
+        .updated
+        .integrity
+        .as_ref()
+        .expect("updated status is sealed");
+    assert_eq!(
+        integrity.previous,
+        edited.integrity.as_ref().map(|i| i.digest.clone())
+    );
+    let report = format_report(&result);
+    assert!(report.contains("integrity check failed"), "{report}");
+    assert!(report.contains("cargo ratchet reseal"), "{report}");
+}
+
+#[test]
+fn evaluate_does_not_seal_grants_edited_into_the_working_tree() {
+    let head = snapshot(
+        "c1aaaaaaaa",
+        sealed(
+            None,
+            &[
+                ("tdd_ratchet_gatekeeper", TestState::Passing),
+                ("t", TestState::Passing),
+            ],
+        ),
+    );
+    let mut working_tree = head.status.clone();
+    working_tree.baseline = Some("c1aaaaaaaa".into());
+    let entry = working_tree.tests.get_mut("t").unwrap();
+    entry.allow_ignore = true;
+    entry.accepted_failure = Some(AcceptedFailure {
+        reason: "flaky".into(),
+        until: Some("2099-01-01".into()),
+        commits: None,
+    });
+    let results = vec![
+        TestResult {
+            name: "tdd_ratchet_gatekeeper".into(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        },
+        TestResult {
+            name: "t".into(),
+            outcome: TestOutcome::Failed,
+            output: None,
+            exec_time_ms: None,
+        },
+    ];
+
+    let result = evaluate(
+        &head.status.tracked_status(),
+        &working_tree.working_tree_instructions(),
+        &results,
+        std::slice::from_ref(&head),
+    );
+
+    assert!(
+        result
+            .violations
+            .iter()
+            .any(|v| matches!(v, Violation::Regression { test } if test == "t")),
+        "{:?}",
+        result.violations
+    );
+    assert_eq!(result.updated.baseline, None);
+    assert!(!result.updated.tests["t"].allow_ignore);
+    assert_eq!(result.updated.tests["t"].accepted_failure, None);
+    // Only the ratchet's own output passes the check once committed.
+    assert_eq!(
+        check_integrity(&[head.clone(), snapshot("c2", result.updated)]),
+        vec![]
+    );
+    assert_eq!(
+        check_integrity(&[head, snapshot("c2", working_tree)]),
+        vec![IntegrityFinding::Tampered {
+            commit: "c2".into(),
+            problem: TamperProblem::DigestMismatch,
+        }]
+    );
+}
+
+#[test]
+fn override_is_reported_as_warning() {
+    let mut resealed = sealed(None, &[("tdd_ratchet_gatekeeper", TestState::Passing)]);
+    resealed.integrity.as_mut().unwrap().override_reason = Some("fix bad merge".into());
+    let snapshots = vec![snapshot("c1aaaaaaaa", resealed.clone())];
+
+    let result = evaluate(
+        &resealed.tracked_status(),
+        &WorkingTreeInstructions::default(),
+        &[TestResult {
+            name: "tdd_ratchet_gatekeeper".into(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        }],
+        &snapshots,
+    );
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(matches!(
+        &result.warnings[..],
+        [Warning::IntegrityOverride { reason, .. }] if reason == "fix bad merge"
+    ));
+    let report = format_report(&result);
+    assert!(report.contains("fix bad merge"), "{report}");
+}
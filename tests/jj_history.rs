@@ -0,0 +1,146 @@
+// tests/jj_history.rs
+//
+// Story 48: In a jj repository colocated with git, history is walked from the
+// tip jj reports, not from jj's auto-committed working copy.
+
+mod common;
+
+use common::{TestDir, commit_status, git};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tdd_ratchet::config::{HistoryBackend, RatchetConfig};
+use tdd_ratchet::history::HistoryProvider;
+use tdd_ratchet::jj_history::JjHistory;
+use tdd_ratchet::status::TestState;
+
+fn rev_parse(dir: &Path, rev: &str) -> String {
+    let out = Command::new("git")
+        .args(["rev-parse", rev])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8(out.stdout).unwrap().trim().to_string()
+}
+
+/// A stand-in for `jj` that records its arguments and prints `output`.
+#[cfg(unix)]
+fn fake_jj(dir: &Path, output: &str, exit_code: i32) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("fake-jj");
+    let args_file = dir.join("jj-args");
+    fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\necho \"$@\" > '{}'\nprintf '%s' '{output}'\necho 'no such revision' >&2\nexit {exit_code}\n",
+            args_file.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+/// A repo whose `HEAD` plays jj's working-copy commit: it holds an
+/// uncommitted promotion of `a` on top of the real tip.
+fn repo_with_working_copy_commit(dir: &Path) -> String {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    commit_status(dir, r#"{"tests":{"a":"pending"}}"#, "Add a");
+    let tip = rev_parse(dir, "HEAD");
+    commit_status(dir, r#"{"tests":{"a":"passing"}}"#, "(working copy)");
+    tip
+}
+
+#[cfg(unix)]
+#[test]
+fn history_starts_at_the_tip_jj_resolves() {
+    let dir = TestDir::new();
+    let repo = dir.path().join("repo");
+    fs::create_dir(&repo).unwrap();
+    let tip = repo_with_working_copy_commit(&repo);
+    let jj = fake_jj(dir.path(), &tip, 0);
+
+    let history = JjHistory::new(&repo).with_program(&jj);
+
+    assert_eq!(history.tip_commit().unwrap(), tip);
+    let snapshots = history.snapshots().unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].commit, tip);
+    assert_eq!(
+        history.head_status().unwrap().unwrap().tests["a"].state(),
+        TestState::Pending
+    );
+    dir.pass();
+}
+
+#[cfg(unix)]
+#[test]
+fn jj_is_asked_for_the_working_copy_parent_without_snapshotting() {
+    let dir = TestDir::new();
+    let repo = dir.path().join("repo");
+    fs::create_dir(&repo).unwrap();
+    let tip = repo_with_working_copy_commit(&repo);
+    let jj = fake_jj(dir.path(), &tip, 0);
+
+    JjHistory::new(&repo)
+        .with_program(&jj)
+        .tip_commit()
+        .unwrap();
+    let args = fs::read_to_string(dir.path().join("jj-args")).unwrap();
+    assert!(args.contains("--ignore-working-copy"), "{args}");
+    assert!(args.contains("-r @-"), "{args}");
+
+    JjHistory::new(&repo)
+        .with_program(&jj)
+        .with_start("main@origin")
+        .tip_commit()
+        .unwrap();
+    let args = fs::read_to_string(dir.path().join("jj-args")).unwrap();
+    assert!(args.contains("-r main@origin"), "{args}");
+    dir.pass();
+}
+
+#[cfg(unix)]
+#[test]
+fn jj_failures_are_reported() {
+    let dir = TestDir::new();
+    let repo = dir.path().join("repo");
+    fs::create_dir(&repo).unwrap();
+    repo_with_working_copy_commit(&repo);
+
+    let failing = fake_jj(dir.path(), "", 1);
+    let err = JjHistory::new(&repo)
+        .with_program(&failing)
+        .snapshots()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("cannot resolve jj revset `@-`"),
+        "{err}"
+    );
+    assert!(err.to_string().contains("no such revision"), "{err}");
+
+    let empty = fake_jj(dir.path(), "", 0);
+    let err = JjHistory::new(&repo)
+        .with_program(&empty)
+        .snapshots()
+        .unwrap_err();
+    assert!(err.to_string().contains("matched no commit"), "{err}");
+
+    let err = JjHistory::new(&repo)
+        .with_program(dir.path().join("no-such-jj"))
+        .snapshots()
+        .unwrap_err();
+    assert!(err.to_string().contains("failed to run jj"), "{err}");
+    dir.pass();
+}
+
+#[test]
+fn jj_backend_is_selected_in_config() {
+    let config =
+        RatchetConfig::parse_from_str("[history]\nbackend = \"jj\"\n", Path::new("ratchet.toml"))
+            .unwrap();
+    assert_eq!(config.history.backend, HistoryBackend::Jj);
+}
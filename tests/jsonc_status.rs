@@ -0,0 +1,122 @@
+// tests/jsonc_status.rs
+//
+// Story 108: humans annotate `.test-status.json` with comments, e.g. the
+// rationale for a per-test baseline, and leave trailing commas behind. Both
+// are tolerated wherever the status file is read, in the working tree and
+// in history; the ratchet itself only ever writes strict JSON.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::schema::validate_status_json;
+use tdd_ratchet::status::{StatusFile, TestState, strip_jsonc};
+
+const ANNOTATED: &str = r#"{
+  "version": 3,
+  // Tracked since the ratchet was adopted.
+  "tests": {
+    "tdd_ratchet_gatekeeper": {"state": "passing"},
+    /* Grandfathered: the parser predates the ratchet,
+       see https://example.com/adr/7 */
+    "parses": {
+      "state": "passing",
+      "metadata": {"link": "https://example.com/a//b", "note": "/* kept */"}, // inline
+    },
+  },
+}
+"#;
+
+#[test]
+fn comments_and_trailing_commas_are_blanked_in_place() {
+    let strict = r#"{"tests": {"a": "passing"}}"#;
+    assert!(matches!(strip_jsonc(strict), Cow::Borrowed(_)));
+
+    let stripped = strip_jsonc(ANNOTATED);
+    assert_eq!(stripped.lines().count(), ANNOTATED.lines().count());
+    let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+    assert_eq!(
+        value["tests"]["parses"]["metadata"]["link"],
+        "https://example.com/a//b"
+    );
+    assert_eq!(value["tests"]["parses"]["metadata"]["note"], "/* kept */");
+
+    let broken = "{\n  // note\n  \"tests\": {\"a\": passing}\n}\n";
+    let err = StatusFile::parse_from_str(broken, Path::new(".test-status.json")).unwrap_err();
+    assert!(err.to_string().contains("line 3"), "{err}");
+}
+
+#[test]
+fn annotated_status_files_load_validate_and_parse_from_history() {
+    let path = Path::new(".test-status.json");
+    let status = StatusFile::parse_from_str(ANNOTATED, path).unwrap();
+    assert_eq!(status.tests["parses"].state(), TestState::Passing);
+    let diagnostics = validate_status_json(ANNOTATED);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+    let historical = StatusFile::parse_historical_from_str(ANNOTATED, path).unwrap();
+    assert_eq!(historical.tests, status.tests);
+}
+
+#[test]
+fn comments_survive_saves_that_change_nothing() {
+    let dir = TestDir::new();
+    let path = dir.path().join(".test-status.json");
+    let status = StatusFile::parse_from_str(ANNOTATED, &path).unwrap();
+    let annotated = annotated_json(&status, &path);
+    fs::write(&path, &annotated).unwrap();
+
+    status.save(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), annotated);
+
+    let mut changed = status.clone();
+    changed.set_test_state("parses", TestState::Pending);
+    changed.save(&path).unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(!written.contains("Reviewed"), "{written}");
+    assert_eq!(
+        StatusFile::load(&path).unwrap().tests["parses"].state(),
+        TestState::Pending
+    );
+    dir.pass();
+}
+
+/// The file `status` would be written as, with a comment added.
+fn annotated_json(status: &StatusFile, path: &Path) -> String {
+    status
+        .to_json(path)
+        .unwrap()
+        .replacen('{', "{\n  // Reviewed in the adoption PR.", 1)
+}
+
+#[test]
+fn committed_annotated_status_files_are_checked_like_strict_ones() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.json"),
+        "{\"type\":\"test\",\"event\":\"ok\",\"name\":\"tdd_ratchet_gatekeeper\"}\n\
+         {\"type\":\"test\",\"event\":\"ok\",\"name\":\"parses\"}\n",
+    )
+    .unwrap();
+    fs::write(path.join(".test-status.json"), ANNOTATED).unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Adopt the ratchet"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests["parses"].state(), TestState::Passing);
+    dir.pass();
+}
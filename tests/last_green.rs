@@ -0,0 +1,171 @@
+// tests/last_green.rs
+//
+// Story 109: a regression names the last commit that recorded the test as
+// passing, "last green at <short-hash> (<date>)", so the developer knows
+// where to start looking for the change that broke it.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::LastGreen;
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{Promotion, StatusFile, TestEntry, TestState, TrackedStatus};
+
+const GATEKEEPER: &str = "tdd_ratchet_gatekeeper";
+const DAY: i64 = 86_400;
+
+fn snapshot(commit: &str, day: i64, feature: TestState, note: &str) -> HistorySnapshot {
+    let mut entry = TestEntry::new(feature);
+    if !note.is_empty() {
+        entry.metadata.insert("note".to_string(), note.into());
+    }
+    HistorySnapshot {
+        commit: commit.to_string(),
+        info: CommitInfo {
+            author: "Grace".into(),
+            time: day * DAY,
+            summary: format!("commit {commit}"),
+            ..CommitInfo::default()
+        },
+        status: StatusFile::new(
+            [
+                (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+                ("feature".to_string(), entry),
+            ]
+            .into(),
+        ),
+    }
+}
+
+/// A run over `history` in which the tracked `feature` fails.
+fn regressed(feature: TestEntry, history: Vec<HistorySnapshot>) -> GatheredRun {
+    GatheredRun {
+        status: TrackedStatus::new(
+            [
+                (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+                ("feature".to_string(), feature),
+            ]
+            .into(),
+        ),
+        results: [
+            (GATEKEEPER, TestOutcome::Passed),
+            ("feature", TestOutcome::Failed),
+        ]
+        .iter()
+        .map(|(name, outcome)| result(name, *outcome))
+        .collect(),
+        history_snapshots: history,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn last_green_is_the_newest_commit_that_recorded_the_test_passing() {
+    let history = vec![
+        snapshot("aaaaaaaa11", 1, TestState::Pending, ""),
+        snapshot("bbbbbbbb22", 2, TestState::Passing, ""),
+        snapshot("cccccccc33", 3, TestState::Passing, "reviewed"),
+        // Carries the status file over unchanged; nothing was recorded here.
+        snapshot("dddddddd44", 4, TestState::Passing, "reviewed"),
+    ];
+    let result = regressed(TestEntry::new(TestState::Passing), history).evaluate();
+
+    assert_eq!(
+        result.last_green.get("feature"),
+        Some(&LastGreen {
+            commit: "cccccccc33".to_string(),
+            time: 3 * DAY,
+        })
+    );
+    assert!(!result.last_green.contains_key(GATEKEEPER));
+    let report = format_report(&result);
+    assert!(
+        report.contains(
+            "now fails: feature (green since bbbbbbbb by Grace), last green at cccccccc (1970-01-04)"
+        ),
+        "{report}"
+    );
+}
+
+#[test]
+fn last_green_falls_back_to_the_recorded_promotion() {
+    let mut entry = TestEntry::new(TestState::Passing);
+    entry.promoted = Some(Promotion {
+        commit: "bbbbbbbb22".to_string(),
+        author: "Grace".to_string(),
+        time: 2 * DAY,
+    });
+    let result = regressed(entry, Vec::new()).evaluate();
+
+    let report = format_report(&result);
+    assert!(
+        report.contains("last green at bbbbbbbb (1970-01-03)"),
+        "{report}"
+    );
+
+    let result = regressed(TestEntry::new(TestState::Passing), Vec::new()).evaluate();
+    assert!(result.last_green.is_empty());
+    assert!(!format_report(&result).contains("last green"));
+}
+
+fn set_results(dir: &Path, feature: &str) {
+    fs::write(
+        dir.join("results.json"),
+        format!(
+            "{}\n{{\"type\":\"test\",\"event\":\"{feature}\",\"name\":\"feature\"}}\n",
+            r#"{"type":"test","event":"ok","name":"tdd_ratchet_gatekeeper"}"#
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn regressions_report_where_the_test_was_last_green() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        format!(r#"{{"tests":{{"{GATEKEEPER}":"passing"}}}}"#),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Adopt the ratchet"]);
+
+    set_results(path, "failed");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add a failing test"]);
+
+    set_results(path, "ok");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Make it pass"]);
+    let green = git(path, &["rev-parse", "--short=8", "HEAD"]);
+
+    fs::write(path.join("README"), "unrelated\n").unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Touch something else"]);
+
+    set_results(path, "failed");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains(&format!("last green at {green} (")), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,119 @@
+// tests/library_api.rs
+//
+// Story 21: The ratchet pipeline is usable as a library with injectable
+// test runner, history provider, and status store.
+
+use std::cell::RefCell;
+use tdd_ratchet::Ratchet;
+use tdd_ratchet::error::RatchetError;
+use tdd_ratchet::history::{HistoryProvider, HistorySnapshot};
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::runner::{RunnerError, TestOutcome, TestResult, TestRunner};
+use tdd_ratchet::status::{StatusFile, StatusStore, TestEntry, TestState, WorkingTreeInstructions};
+
+struct FixedResults(Vec<(&'static str, TestOutcome)>);
+
+impl TestRunner for FixedResults {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        Ok(self
+            .0
+            .iter()
+            .map(|(name, outcome)| TestResult {
+                name: name.to_string(),
+                outcome: *outcome,
+                output: None,
+                exec_time_ms: None,
+            })
+            .collect())
+    }
+}
+
+struct InMemoryHistory(Option<StatusFile>);
+
+impl HistoryProvider for InMemoryHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        Ok(self.0.clone())
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        Ok(Vec::new())
+    }
+}
+
+#[derive(Default)]
+struct InMemoryStore {
+    saved: RefCell<Option<StatusFile>>,
+}
+
+impl StatusStore for InMemoryStore {
+    fn load_instructions(&self) -> Result<WorkingTreeInstructions, RatchetError> {
+        Ok(WorkingTreeInstructions::default())
+    }
+
+    fn save(&self, status: &StatusFile) -> Result<(), RatchetError> {
+        *self.saved.borrow_mut() = Some(status.clone());
+        Ok(())
+    }
+}
+
+fn committed(tests: &[(&str, TestState)]) -> StatusFile {
+    StatusFile::new(
+        tests
+            .iter()
+            .map(|(n, s)| (n.to_string(), TestEntry::new(*s)))
+            .collect(),
+    )
+}
+
+#[test]
+fn builder_runs_the_pipeline_with_injected_components() {
+    let store = InMemoryStore::default();
+    let ratchet = Ratchet::builder("/nonexistent")
+        .runner(FixedResults(vec![
+            ("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+            ("feature", TestOutcome::Passed),
+            ("next_feature", TestOutcome::Failed),
+        ]))
+        .history(InMemoryHistory(Some(committed(&[
+            ("tdd_ratchet_gatekeeper", TestState::Passing),
+            ("feature", TestState::Pending),
+        ]))))
+        .status_store(&store)
+        .build();
+
+    let result = ratchet.run().unwrap();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    let saved = store
+        .saved
+        .borrow()
+        .clone()
+        .expect("status should be saved");
+    assert_eq!(saved.tests["feature"].state(), TestState::Passing);
+    assert_eq!(saved.tests["next_feature"].state(), TestState::Pending);
+}
+
+#[test]
+fn violations_are_returned_and_status_is_still_saved() {
+    let store = InMemoryStore::default();
+    let ratchet = Ratchet::builder("/nonexistent")
+        .runner(FixedResults(vec![
+            ("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+            ("cheater", TestOutcome::Passed),
+        ]))
+        .history(InMemoryHistory(None))
+        .status_store(&store)
+        .build();
+
+    let result = ratchet.run().unwrap();
+
+    assert!(
+        result
+            .violations
+            .iter()
+            .any(|v| matches!(v, Violation::NewTestPassed { test } if test == "cheater")),
+        "{:?}",
+        result.violations
+    );
+    assert!(store.saved.borrow().is_some());
+}
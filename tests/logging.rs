@@ -0,0 +1,108 @@
+// tests/logging.rs
+//
+// Story 93: runner invocations, the history walk and evaluation decisions
+// are logged through `tracing`, to stderr with `--log-level` or `RUST_LOG`,
+// and as JSON lines to the file given with `--log-file`.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::logging::{DEFAULT_LOG_FILE_FILTER, log_filters};
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+
+fn setup_project(path: &Path) {
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.json"),
+        format!("{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"{GATEKEEPER}\"}}\n"),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+}
+
+#[test]
+fn the_log_level_flag_wins_over_rust_log() {
+    assert_eq!(
+        log_filters(None, None),
+        ("off".to_string(), DEFAULT_LOG_FILE_FILTER.to_string())
+    );
+    assert_eq!(
+        log_filters(None, Some("tdd_ratchet=trace")),
+        (
+            "tdd_ratchet=trace".to_string(),
+            "tdd_ratchet=trace".to_string()
+        )
+    );
+    assert_eq!(
+        log_filters(Some("debug"), Some("tdd_ratchet=trace")),
+        ("debug".to_string(), "debug".to_string())
+    );
+}
+
+#[test]
+fn log_file_records_the_run_as_json_lines() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    setup_project(path);
+
+    let (code, out) = run_ratchet(path, &["--log-file", "ratchet-log.json"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!out.contains("running test command"), "{out}");
+
+    let log = fs::read_to_string(path.join("ratchet-log.json")).unwrap();
+    let events: Vec<serde_json::Value> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let message =
+        |event: &serde_json::Value| event["fields"]["message"].as_str().map(str::to_string);
+    let command = events
+        .iter()
+        .find(|event| message(event).as_deref() == Some("running test command"))
+        .unwrap_or_else(|| panic!("{log}"));
+    assert_eq!(command["span"]["command"], "cat results.json");
+    let change = events
+        .iter()
+        .find(|event| message(event).as_deref() == Some("state change"))
+        .unwrap_or_else(|| panic!("{log}"));
+    assert_eq!(change["fields"]["test"], GATEKEEPER);
+    assert_eq!(change["span"]["name"], "evaluate");
+    assert!(
+        events
+            .iter()
+            .any(|event| message(event).as_deref() == Some("walked status file history")),
+        "{log}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn log_level_prints_events_to_stderr() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    setup_project(path);
+
+    let (code, out) = run_ratchet(path, &["--log-level", "debug"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("running test command"), "{out}");
+    assert!(out.contains("state change"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--log-level", "tdd_ratchet=loud"]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("invalid log level"), "{out}");
+    dir.pass();
+}
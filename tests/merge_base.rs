@@ -0,0 +1,216 @@
+// tests/merge_base.rs
+//
+// Story 70: `--merge-base <REV>` checks a branch the way a merge queue
+// builds it: the tests run on the branch merged into REV, only the branch's
+// own commits are checked for skipped pending states, and regressions are
+// judged against the status merged with REV's.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::merge_queue::{merge_base, merge_tests};
+use tdd_ratchet::status::{TestEntry, TestState};
+
+fn tests(entries: &[(&str, TestState)]) -> BTreeMap<String, TestEntry> {
+    entries
+        .iter()
+        .map(|(name, state)| (name.to_string(), TestEntry::new(*state)))
+        .collect()
+}
+
+fn states(tests: &BTreeMap<String, TestEntry>) -> Vec<(&str, TestState)> {
+    tests
+        .iter()
+        .map(|(name, entry)| (name.as_str(), entry.state()))
+        .collect()
+}
+
+/// Commit `status` and `results` with `message`.
+fn commit(dir: &Path, status: &str, results: &str, message: &str) {
+    fs::write(dir.join(".test-status.json"), status).unwrap();
+    fs::write(dir.join("results.txt"), results).unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-m", message]);
+}
+
+/// `main` has a commit that skipped pending (`skipper`) and, after the
+/// branch `topic` forked, one that promoted `from_main`. `topic` adds a
+/// pending test and is checked out.
+fn branch_behind_main(dir: &Path) {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    fs::write(
+        dir.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    commit(
+        dir,
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","from_main":"pending"}}"#,
+        "test tdd_ratchet_gatekeeper ... ok\ntest from_main ... FAILED\n",
+        "Initial project",
+    );
+    commit(
+        dir,
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","from_main":"pending","skipper":"passing"}}"#,
+        "test tdd_ratchet_gatekeeper ... ok\ntest from_main ... FAILED\ntest skipper ... ok\n",
+        "Add skipper without a pending commit",
+    );
+    git(dir, &["checkout", "-b", "topic"]);
+    commit(
+        dir,
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","from_main":"pending","skipper":"passing","topic_test":"pending"}}"#,
+        "test tdd_ratchet_gatekeeper ... ok\ntest from_main ... FAILED\ntest skipper ... ok\ntest topic_test ... FAILED\n",
+        "Add topic_test",
+    );
+    git(dir, &["checkout", "main"]);
+    commit(
+        dir,
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","from_main":"passing","skipper":"passing"}}"#,
+        "test tdd_ratchet_gatekeeper ... ok\ntest from_main ... ok\ntest skipper ... ok\n",
+        "Implement from_main",
+    );
+    git(dir, &["checkout", "topic"]);
+}
+
+#[test]
+fn merged_tests_take_one_sided_changes() {
+    let base = tests(&[
+        ("kept", TestState::Pending),
+        ("removed", TestState::Passing),
+    ]);
+    let ours = tests(&[
+        ("kept", TestState::Pending),
+        ("ours_new", TestState::Pending),
+    ]);
+    let theirs = tests(&[
+        ("kept", TestState::Passing),
+        ("removed", TestState::Passing),
+        ("theirs_new", TestState::Passing),
+    ]);
+
+    let merged = merge_tests(&base, &ours, &theirs);
+
+    assert_eq!(
+        states(&merged),
+        [
+            ("kept", TestState::Passing),
+            ("ours_new", TestState::Pending),
+            ("theirs_new", TestState::Passing),
+        ]
+    );
+}
+
+#[test]
+fn test_changed_on_both_sides_is_passing_if_either_side_promoted_it() {
+    let base = BTreeMap::new();
+    let ours = tests(&[("a", TestState::Pending), ("b", TestState::Passing)]);
+    let theirs = tests(&[("a", TestState::Passing), ("b", TestState::Pending)]);
+
+    let merged = merge_tests(&base, &ours, &theirs);
+
+    assert_eq!(
+        states(&merged),
+        [("a", TestState::Passing), ("b", TestState::Passing)]
+    );
+}
+
+#[test]
+fn merge_base_names_the_fork_point_and_the_target() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    branch_behind_main(path);
+
+    let found = merge_base(path, "main").unwrap();
+
+    assert_eq!(found.base, git(path, &["rev-parse", "topic~1"]));
+    assert_eq!(found.target, git(path, &["rev-parse", "main"]));
+    dir.pass();
+}
+
+#[test]
+fn merge_base_runs_the_tests_on_the_merge() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    branch_behind_main(path);
+    let status = fs::read_to_string(path.join(".test-status.json")).unwrap();
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(
+        out.contains("skipper"),
+        "the full check sees main's history: {out}"
+    );
+    fs::write(path.join(".test-status.json"), &status).unwrap();
+
+    // `from_main` fails on the branch, which lacks main's fix, but passes
+    // once merged, so it is not blamed on the branch.
+    let (code, out) = run_ratchet(path, &["--merge-base", "main"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!out.contains("skipper"), "{out}");
+    assert!(!out.contains("from_main"), "{out}");
+    assert_eq!(
+        fs::read_to_string(path.join(".test-status.json")).unwrap(),
+        status,
+        "the branch's status file is left alone"
+    );
+    dir.pass();
+}
+
+#[test]
+fn merge_base_reports_a_regression_of_a_test_main_promoted() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    branch_behind_main(path);
+    // The branch's change makes `from_main` fail, whatever main did to it.
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n[[runners]]\nkind = \"command\"\n\
+         command = [\"sh\", \"-c\", \"sed 's/from_main ... ok/from_main ... FAILED/' results.txt\"]\n\
+         parser = \"libtest\"\n",
+    )
+    .unwrap();
+    git(path, &["commit", "-am", "Break from_main"]);
+
+    let (code, out) = run_ratchet(path, &["--merge-base", "main"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("from_main"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn merge_base_rejects_a_branch_that_does_not_merge_cleanly() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    branch_behind_main(path);
+    fs::write(path.join("ratchet.toml"), "# topic's config\n").unwrap();
+    git(path, &["commit", "-am", "Rewrite the config"]);
+    git(path, &["checkout", "main"]);
+    fs::write(path.join("ratchet.toml"), "# main's config\n").unwrap();
+    git(path, &["commit", "-am", "Rewrite the config differently"]);
+    git(path, &["checkout", "topic"]);
+
+    let (code, out) = run_ratchet(path, &["--merge-base", "main"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("conflicts in ratchet.toml"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn merge_base_and_since_cannot_be_combined() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    branch_behind_main(path);
+
+    let (code, out) = run_ratchet(path, &["--merge-base", "main", "--since", "main"]);
+    assert_eq!(code, Some(2), "{out}");
+    dir.pass();
+}
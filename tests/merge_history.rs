@@ -0,0 +1,194 @@
+// tests/merge_history.rs
+//
+// Story 111: history checks follow the commit graph, not the order the walk
+// happens to list commits in. A test made pending on a side branch and
+// promoted there is never a SkippedPending violation, however octopus or
+// criss-cross merges order it, and a pending entry on one branch does not
+// excuse a test added as passing on another.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, commit_status, git, run_ratchet, status};
+use std::fs;
+use tdd_ratchet::history::{
+    CommitInfo, HistorySnapshot, HistoryViolation, check_history_snapshots,
+    check_history_snapshots_from,
+};
+use tdd_ratchet::status::TestState;
+
+const GATEKEEPER: &str = "tdd_ratchet_gatekeeper";
+
+fn snapshot(commit: &str, parents: &[&str], tests: &[(&str, TestState)]) -> HistorySnapshot {
+    let tests: Vec<_> = [(GATEKEEPER, TestState::Passing)]
+        .into_iter()
+        .chain(tests.iter().copied())
+        .collect();
+    HistorySnapshot {
+        info: CommitInfo {
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status(&tests))
+    }
+}
+
+fn skipped(violations: &[HistoryViolation]) -> Vec<(&str, &str)> {
+    violations
+        .iter()
+        .map(|violation| match violation {
+            HistoryViolation::SkippedPending { test, commit } => (test.as_str(), commit.as_str()),
+        })
+        .collect()
+}
+
+#[test]
+fn a_pending_snapshot_listed_after_its_promotion_still_counts() {
+    use TestState::{Passing, Pending};
+    // The side branch's pending commit is listed after the commit that
+    // promotes the test on top of it.
+    let history = vec![
+        snapshot("root", &[], &[]),
+        snapshot("main1", &["root"], &[]),
+        snapshot("side2", &["side1"], &[("feature", Passing)]),
+        snapshot("side1", &["root"], &[("feature", Pending)]),
+        snapshot("merge", &["main1", "side2"], &[("feature", Passing)]),
+    ];
+    assert!(check_history_snapshots(&history).is_empty());
+}
+
+#[test]
+fn pending_on_one_branch_does_not_excuse_passing_on_another() {
+    use TestState::{Passing, Pending};
+    let history = vec![
+        snapshot("root", &[], &[]),
+        snapshot("a1", &["root"], &[("feature", Pending)]),
+        snapshot("b1", &["root"], &[("feature", Passing)]),
+        snapshot("c1", &["root"], &[("other", Pending)]),
+        // Octopus merge of all three branches.
+        snapshot(
+            "merge",
+            &["a1", "b1", "c1"],
+            &[("feature", Passing), ("other", Pending), ("fresh", Passing)],
+        ),
+    ];
+    assert_eq!(
+        skipped(&check_history_snapshots(&history)),
+        vec![("feature", "b1"), ("fresh", "merge")]
+    );
+}
+
+#[test]
+fn criss_cross_merges_see_pending_states_from_both_sides() {
+    use TestState::{Passing, Pending};
+    let history = vec![
+        snapshot("root", &[], &[]),
+        snapshot("a1", &["root"], &[("left", Pending)]),
+        snapshot("b1", &["root"], &[("right", Pending)]),
+        // Each branch merges the other's tip, then promotes its test.
+        snapshot(
+            "a2",
+            &["a1", "b1"],
+            &[("left", Passing), ("right", Pending)],
+        ),
+        snapshot(
+            "b2",
+            &["b1", "a1"],
+            &[("left", Pending), ("right", Passing)],
+        ),
+        snapshot(
+            "merge",
+            &["a2", "b2"],
+            &[("left", Passing), ("right", Passing)],
+        ),
+    ];
+    assert!(check_history_snapshots(&history).is_empty());
+}
+
+#[test]
+fn only_history_the_baseline_descends_from_is_skipped() {
+    use TestState::{Passing, Pending};
+    // `side` forked before the baseline but was merged after it; it is not
+    // behind the baseline, so it is checked, and it shows `feature` was
+    // pending before it passed.
+    let history = vec![
+        snapshot("root", &[], &[("early", Passing)]),
+        snapshot("side1", &["root"], &[("feature", Pending)]),
+        snapshot(
+            "side2",
+            &["side1"],
+            &[("feature", Passing), ("cheat", Passing)],
+        ),
+        snapshot("base", &["root"], &[("early", Passing)]),
+        snapshot(
+            "merge",
+            &["side2", "base"],
+            &[("early", Passing), ("feature", Passing), ("cheat", Passing)],
+        ),
+    ];
+    assert_eq!(
+        skipped(&check_history_snapshots_from(&history, Some("base"))),
+        vec![("cheat", "side2")]
+    );
+}
+
+#[test]
+fn a_branch_forked_before_the_baseline_is_checked_by_ancestry() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.json"),
+        "{\"type\":\"test\",\"event\":\"ok\",\"name\":\"tdd_ratchet_gatekeeper\"}\n\
+         {\"type\":\"test\",\"event\":\"ok\",\"name\":\"feature\"}\n",
+    )
+    .unwrap();
+    commit_status(
+        path,
+        &format!(r#"{{"tests":{{"{GATEKEEPER}":"passing"}}}}"#),
+        "Adopt the ratchet",
+    );
+
+    git(path, &["checkout", "-b", "side"]);
+    commit_status(
+        path,
+        &format!(r#"{{"tests":{{"{GATEKEEPER}":"passing","feature":"pending"}}}}"#),
+        "Add a failing test",
+    );
+    commit_status(
+        path,
+        &format!(r#"{{"tests":{{"{GATEKEEPER}":"passing","feature":"passing"}}}}"#),
+        "Make it pass",
+    );
+
+    git(path, &["checkout", "main"]);
+    fs::write(path.join("README"), "adopted\n").unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Document the ratchet"]);
+    let baseline = git(path, &["rev-parse", "HEAD"]);
+
+    // Merging main into the side branch lists the side branch's commits
+    // before the baseline in the walk.
+    git(path, &["checkout", "side"]);
+    git(path, &["merge", "--no-commit", "-s", "ours", "main"]);
+    commit_status(
+        path,
+        &format!(
+            r#"{{"baseline":"{baseline}","tests":{{"{GATEKEEPER}":"passing","feature":"passing"}}}}"#
+        ),
+        "Merge main",
+    );
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!out.contains("RATCHET004"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,144 @@
+// tests/metrics_export.rs
+//
+// Story 61: `--metrics-out <FILE>` writes Prometheus metrics (tests by state,
+// flaky tests, violations by code, run duration) for node_exporter's
+// textfile collector.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, git};
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+use tdd_ratchet::metrics::{SINGLE_ROOT, format_metrics};
+use tdd_ratchet::ratchet::{EvalResult, Violation, Warning};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState};
+
+fn eval_result(tests: &[(&str, TestState)], violations: Vec<Violation>) -> EvalResult {
+    EvalResult {
+        violations,
+        warnings: vec![Warning::FlakyTest {
+            test: "a".into(),
+            flakes: 1,
+        }],
+        updated: StatusFile::new(
+            tests
+                .iter()
+                .map(|(name, state)| (name.to_string(), TestEntry::new(*state)))
+                .collect(),
+        ),
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+#[test]
+fn metrics_count_tests_flakes_and_violations() {
+    let result = eval_result(
+        &[
+            ("a", TestState::Passing),
+            ("b", TestState::Passing),
+            ("c", TestState::Pending),
+        ],
+        vec![
+            Violation::Regression { test: "b".into() },
+            Violation::Regression { test: "d".into() },
+        ],
+    );
+    let metrics = format_metrics([(SINGLE_ROOT, &result)], Duration::from_millis(1500));
+    let lines: Vec<&str> = metrics.lines().collect();
+
+    for expected in [
+        "# TYPE tdd_ratchet_tests gauge",
+        r#"tdd_ratchet_tests{root=".",state="passing"} 2"#,
+        r#"tdd_ratchet_tests{root=".",state="pending"} 1"#,
+        r#"tdd_ratchet_flaky_tests{root="."} 1"#,
+        r#"tdd_ratchet_violations{root=".",code="RATCHET002",rule="Regression"} 2"#,
+        r#"tdd_ratchet_violations{root=".",code="RATCHET001",rule="NewTestPassed"} 0"#,
+        "tdd_ratchet_run_duration_seconds 1.500",
+    ] {
+        assert!(lines.contains(&expected), "missing {expected}:\n{metrics}");
+    }
+}
+
+#[test]
+fn each_root_gets_its_own_series() {
+    let first = eval_result(&[("a", TestState::Passing)], Vec::new());
+    let second = eval_result(&[("b", TestState::Pending)], Vec::new());
+    let metrics = format_metrics(
+        [("crates/one", &first), ("crates/\"two\"", &second)],
+        Duration::ZERO,
+    );
+
+    assert!(
+        metrics.contains(r#"tdd_ratchet_tests{root="crates/one",state="passing"} 1"#),
+        "{metrics}"
+    );
+    assert!(
+        metrics.contains(r#"tdd_ratchet_tests{root="crates/\"two\"",state="pending"} 1"#),
+        "{metrics}"
+    );
+    assert_eq!(
+        metrics
+            .lines()
+            .filter(|line| line.starts_with("# TYPE tdd_ratchet_tests "))
+            .count(),
+        1,
+        "{metrics}"
+    );
+}
+
+#[test]
+fn run_writes_the_metrics_file() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest a ... ok\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","a":"passing","b":"passing"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let output = Command::new(cargo_bin())
+        .args(["--metrics-out", "ratchet.prom"])
+        .current_dir(path)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", path)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let metrics = fs::read_to_string(path.join("ratchet.prom")).unwrap();
+    assert!(
+        metrics.contains(r#"tdd_ratchet_tests{root=".",state="passing"} 3"#),
+        "{metrics}"
+    );
+    assert!(
+        metrics.contains(
+            r#"tdd_ratchet_violations{root=".",code="RATCHET003",rule="TestDisappeared"} 1"#
+        ),
+        "{metrics}"
+    );
+    assert!(!path.join("ratchet.prom.tmp").exists());
+    dir.pass();
+}
@@ -0,0 +1,89 @@
+// tests/nextest_options.rs
+//
+// Story 95: a project's nextest tuning applies under the ratchet: `--profile`
+// and the `[nextest]` table's profile, retries, test threads and no-capture
+// are passed on to `cargo nextest run`.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet_with_path};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tdd_ratchet::config::{NextestConfig, RatchetConfig};
+
+#[test]
+fn nextest_table_maps_to_nextest_arguments() {
+    let config = RatchetConfig::parse_from_str(
+        "[nextest]\nprofile = \"ci\"\nretries = 2\ntest-threads = 4\nno-capture = true\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.nextest.args(),
+        [
+            "--profile",
+            "ci",
+            "--retries",
+            "2",
+            "--test-threads",
+            "4",
+            "--no-capture"
+        ]
+    );
+    assert!(NextestConfig::default().args().is_empty());
+    assert!(
+        RatchetConfig::parse_from_str("[nextest]\nthreads = 4\n", Path::new("ratchet.toml"))
+            .is_err()
+    );
+}
+
+#[test]
+fn profile_and_options_are_passed_to_cargo_nextest() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let project = dir.path().join("project");
+    let bin = dir.path().join("bin");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(&bin).unwrap();
+    let log = dir.path().join("cargo-args.log");
+
+    // A stand-in for cargo that records its arguments and reports a
+    // gatekeeper that only passes under the ratchet.
+    let cargo = bin.join("cargo");
+    fs::write(
+        &cargo,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n\
+             if [ -n \"$TDD_RATCHET\" ]; then event=ok; else event=failed; fi\n\
+             echo '{{\"type\":\"test\",\"event\":\"'$event'\",\"name\":\"app::gatekeeper$tdd_ratchet_gatekeeper\"}}'\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&cargo, fs::Permissions::from_mode(0o755)).unwrap();
+
+    git(&project, &["init", "-b", "main"]);
+    git(&project, &["config", "user.email", "test@test.com"]);
+    git(&project, &["config", "user.name", "Test"]);
+    fs::write(
+        project.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n[nextest]\nprofile = \"local\"\nretries = 1\n",
+    )
+    .unwrap();
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet_with_path(&project, &bin, &["--profile", "ci"]);
+    assert_eq!(code, Some(0), "{out}");
+
+    let calls = fs::read_to_string(&log).unwrap();
+    let run = calls
+        .lines()
+        .find(|line| line.starts_with("nextest run"))
+        .unwrap_or_else(|| panic!("{calls}"));
+    assert!(run.contains("--profile ci --retries 1"), "{calls}");
+    assert!(!run.contains("local"), "{calls}");
+    dir.pass();
+}
@@ -0,0 +1,201 @@
+// tests/overrides.rs
+//
+// Story 30: A `Ratchet-Override` commit trailer suppresses a violation for
+// the named test, and every override is recorded in an append-only audit
+// file.
+
+mod common;
+
+use common::{result, status};
+use tdd_ratchet::audit::{AuditRecord, read_audit, record_overrides};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::overrides::{Override, apply_overrides, parse_overrides};
+use tdd_ratchet::ratchet::{Violation, Warning, evaluate};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::stats::{compute_stats_with_audit, format_stats};
+use tdd_ratchet::status::{StatusFile, TestState, TrackedStatus, WorkingTreeInstructions};
+use tempfile::TempDir;
+
+fn snapshot(commit: &str, message: &str, status: StatusFile) -> HistorySnapshot {
+    HistorySnapshot {
+        info: CommitInfo {
+            overrides: parse_overrides(message),
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status)
+    }
+}
+
+#[test]
+fn trailer_names_test_and_reason() {
+    let message = "Hotfix\n\nBody text.\n\n\
+                   Ratchet-Override: crate$tests::flaky -- upstream outage\n\
+                   ratchet-override: crate$tests::other --  known issue  \n";
+    assert_eq!(
+        parse_overrides(message),
+        vec![
+            Override {
+                test: "crate$tests::flaky".into(),
+                reason: "upstream outage".into(),
+            },
+            Override {
+                test: "crate$tests::other".into(),
+                reason: "known issue".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn trailer_without_test_or_reason_is_ignored() {
+    let message = "Hotfix\n\n\
+                   Ratchet-Override: just because\n\
+                   Ratchet-Override:  -- no test named\n\
+                   Ratchet-Override: crate$t -- \n";
+    assert_eq!(parse_overrides(message), vec![]);
+}
+
+#[test]
+fn override_suppresses_skipped_pending_for_named_test_only() {
+    let snapshots = vec![snapshot(
+        "c1aaaaaaaa",
+        "Ratchet-Override: a -- imported from another repo",
+        status(&[("a", TestState::Passing), ("b", TestState::Passing)]),
+    )];
+    let violations = vec![
+        Violation::SkippedPending {
+            test: "a".into(),
+            commit: "c1aaaaaaaa".into(),
+        },
+        Violation::SkippedPending {
+            test: "b".into(),
+            commit: "c1aaaaaaaa".into(),
+        },
+    ];
+
+    let (remaining, warnings) = apply_overrides(violations, &snapshots);
+
+    assert!(
+        matches!(
+            remaining.as_slice(),
+            [Violation::SkippedPending { test, .. }] if test == "b"
+        ),
+        "{remaining:?}"
+    );
+    assert!(
+        matches!(
+            warnings.as_slice(),
+            [Warning::OverrideApplied { commit, test, rule, reason }]
+                if commit == "c1aaaaaaaa"
+                    && test == "a"
+                    && rule == "SkippedPending"
+                    && reason == "imported from another repo"
+        ),
+        "{warnings:?}"
+    );
+}
+
+#[test]
+fn override_on_older_commit_does_not_cover_head() {
+    let snapshots = vec![
+        snapshot(
+            "c1aaaaaaaa",
+            "Ratchet-Override: t -- old reason",
+            status(&[("t", TestState::Passing)]),
+        ),
+        snapshot("c2bbbbbbbb", "Later", status(&[("t", TestState::Passing)])),
+    ];
+    let violations = vec![Violation::Regression { test: "t".into() }];
+
+    let (remaining, warnings) = apply_overrides(violations, &snapshots);
+
+    assert_eq!(remaining.len(), 1);
+    assert!(warnings.is_empty(), "{warnings:?}");
+}
+
+#[test]
+fn head_trailer_overrides_regression_in_evaluate() {
+    let committed = status(&[
+        ("tdd_ratchet_gatekeeper", TestState::Passing),
+        ("t", TestState::Passing),
+    ]);
+    let snapshots = vec![snapshot(
+        "c1aaaaaaaa",
+        "Ratchet-Override: t -- vendor API down",
+        committed.clone(),
+    )];
+
+    let result = evaluate(
+        &TrackedStatus::new(committed.tests.clone()),
+        &WorkingTreeInstructions::default(),
+        &[
+            result("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+            result("t", TestOutcome::Failed),
+        ],
+        &snapshots,
+    );
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert!(
+        result.warnings.iter().any(|w| matches!(
+            w,
+            Warning::OverrideApplied { commit, test, rule, .. }
+                if commit == "c1aaaaaaaa" && test == "t" && rule == "Regression"
+        )),
+        "{:?}",
+        result.warnings
+    );
+    let report = format_report(&result);
+    assert!(report.contains("overridden by commit trailer"), "{report}");
+    assert!(report.contains("vendor API down"), "{report}");
+}
+
+#[test]
+fn overrides_are_appended_to_audit_file_once() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(".ratchet-audit.jsonl");
+    let warnings = vec![
+        Warning::OverrideApplied {
+            commit: "c1aaaaaaaa".into(),
+            test: "t".into(),
+            rule: "Regression".into(),
+            reason: "vendor API down".into(),
+        },
+        Warning::StaleRename {
+            old_name: "x".into(),
+            new_name: "y".into(),
+        },
+    ];
+
+    assert_eq!(read_audit(&path).unwrap(), vec![]);
+    assert_eq!(record_overrides(&path, &warnings, 100).unwrap(), 1);
+    assert_eq!(record_overrides(&path, &warnings, 200).unwrap(), 0);
+
+    let expected = AuditRecord::Override {
+        commit: "c1aaaaaaaa".into(),
+        test: "t".into(),
+        rule: "Regression".into(),
+        reason: "vendor API down".into(),
+        recorded_at: 100,
+    };
+    assert_eq!(read_audit(&path).unwrap(), vec![expected.clone()]);
+
+    let stats = compute_stats_with_audit(&[], &[expected]);
+    let text = format_stats(&stats);
+    assert!(text.contains("overrides used:               1"), "{text}");
+    assert!(
+        text.contains("c1aaaaaa Regression t: vendor API down"),
+        "{text}"
+    );
+}
+
+#[test]
+fn malformed_audit_line_reports_line_number() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(".ratchet-audit.jsonl");
+    std::fs::write(&path, "\nnot json\n").unwrap();
+
+    let err = read_audit(&path).unwrap_err();
+    assert!(err.to_string().contains("line 2"), "{err}");
+}
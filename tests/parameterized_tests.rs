@@ -0,0 +1,282 @@
+// tests/parameterized_tests.rs
+//
+// Story 85: cases of parameterized tests (rstest, test-case) matching a
+// `parameterized` pattern are tracked as one logical test, which passes only
+// when every case passes.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet, status};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::RatchetConfig;
+use tdd_ratchet::history::HistorySnapshot;
+use tdd_ratchet::integrity::seal;
+use tdd_ratchet::parameterized::ParameterizedGroups;
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, TrackedStatus};
+
+const GATEKEEPER: &str = "tdd_ratchet_gatekeeper";
+
+fn cases() -> ParameterizedGroups {
+    ParameterizedGroups::new(["*::case_*"])
+}
+
+fn result(name: &str, outcome: TestOutcome, output: Option<&str>) -> TestResult {
+    TestResult {
+        output: output.map(str::to_string),
+        ..common::result(name, outcome)
+    }
+}
+
+/// A sealed snapshot chained from `previous`, with the gatekeeper passing.
+fn snapshot(
+    commit: &str,
+    previous: Option<&HistorySnapshot>,
+    tests: &[(&str, TestState)],
+) -> HistorySnapshot {
+    let tests: Vec<_> = [(GATEKEEPER, TestState::Passing)]
+        .into_iter()
+        .chain(tests.iter().copied())
+        .collect();
+    let mut status = status(&tests);
+    let previous = previous.and_then(|p| p.status.integrity.as_ref());
+    status.integrity = Some(seal(previous.map(|i| i.digest.as_str()), &status));
+    common::snapshot(commit, status)
+}
+
+/// `c1` adds `parse::case_1` as pending; `c2` adds `parse::case_2` already
+/// passing and promotes `parse::case_1` with it.
+fn history() -> Vec<HistorySnapshot> {
+    use TestState::{Passing, Pending};
+    let c1 = snapshot("c1", None, &[("parse::case_1", Pending)]);
+    let c2 = snapshot(
+        "c2",
+        Some(&c1),
+        &[("parse::case_1", Passing), ("parse::case_2", Passing)],
+    );
+    vec![c1, c2]
+}
+
+fn gathered(parameterized: ParameterizedGroups, results: Vec<TestResult>) -> GatheredRun {
+    let history = history();
+    let status = TrackedStatus::new(history.last().unwrap().status.tests.clone());
+    GatheredRun {
+        status,
+        results,
+        history_snapshots: history,
+        parameterized,
+        ..Default::default()
+    }
+}
+
+fn all_passing() -> Vec<TestResult> {
+    vec![
+        result(GATEKEEPER, TestOutcome::Passed, None),
+        result("parse::case_1", TestOutcome::Passed, None),
+        result("parse::case_2", TestOutcome::Passed, None),
+    ]
+}
+
+#[test]
+fn parameterized_patterns_are_configurable() {
+    let config = RatchetConfig::parse_from_str(
+        "parameterized = [\"*::case_*\"]\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(config.parameterized, cases());
+    assert!(RatchetConfig::default().parameterized.is_empty());
+}
+
+#[test]
+fn matching_cases_belong_to_the_test_they_were_generated_from() {
+    let groups = cases();
+    assert_eq!(groups.group_of("parse::case_1"), Some("parse"));
+    assert_eq!(
+        groups.group_of("crate::bin$tests::parse::case_2_empty"),
+        Some("crate::bin$tests::parse")
+    );
+    assert_eq!(groups.group_of("parse::normal"), None);
+    assert_eq!(groups.group_of("crate$case_1"), None);
+    assert_eq!(groups.logical_name("parse::normal"), "parse::normal");
+}
+
+#[test]
+fn a_parameterized_test_passes_only_when_every_case_passes() {
+    let groups = cases();
+    let collapsed = groups.collapse_results(&[
+        result("parse::case_1", TestOutcome::Passed, None),
+        result("other", TestOutcome::Passed, None),
+        result("parse::case_2", TestOutcome::Failed, Some("boom")),
+        result("parse::case_3", TestOutcome::Failed, None),
+    ]);
+
+    assert_eq!(collapsed.len(), 2);
+    assert_eq!(collapsed[0].name, "parse");
+    assert_eq!(collapsed[0].outcome, TestOutcome::Failed);
+    assert_eq!(
+        collapsed[0].output.as_deref(),
+        Some("parse::case_2:\nboom\nparse::case_3")
+    );
+    assert_eq!(collapsed[0].exec_time_ms, Some(15));
+    assert_eq!(collapsed[1], result("other", TestOutcome::Passed, None));
+
+    let outcome = |outcomes: &[TestOutcome]| {
+        let results: Vec<TestResult> = outcomes
+            .iter()
+            .enumerate()
+            .map(|(i, outcome)| result(&format!("t::case_{i}"), *outcome, None))
+            .collect();
+        groups.collapse_results(&results)[0].outcome
+    };
+    use TestOutcome::{Flaky, Ignored, Passed};
+    assert_eq!(outcome(&[Passed, Flaky]), Flaky);
+    assert_eq!(outcome(&[Ignored, Passed]), Passed);
+    assert_eq!(outcome(&[Ignored, Ignored]), Ignored);
+}
+
+#[test]
+fn a_parameterized_test_is_pending_while_any_case_is() {
+    let mut early = TestEntry::new(TestState::Passing);
+    early.added_at = Some(100);
+    let mut pending = TestEntry::new(TestState::Pending);
+    pending.added_at = Some(200);
+    let tests = [
+        ("parse::case_1".to_string(), early),
+        ("parse::case_2".to_string(), pending),
+    ]
+    .into();
+
+    let collapsed = cases().collapse_tests(&tests);
+
+    assert_eq!(collapsed.len(), 1);
+    assert_eq!(collapsed["parse"].state(), TestState::Pending);
+    assert_eq!(collapsed["parse"].added_at, Some(100));
+}
+
+#[test]
+fn cases_added_to_a_pending_test_keep_the_pending_to_passing_story() {
+    let ungrouped = gathered(ParameterizedGroups::default(), all_passing()).evaluate();
+    assert!(
+        ungrouped.violations.iter().any(
+            |v| matches!(v, Violation::SkippedPending { test, .. } if test == "parse::case_2")
+        ),
+        "{:?}",
+        ungrouped.violations
+    );
+
+    let result = gathered(cases(), all_passing()).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    let tests: Vec<&String> = result.updated.tests.keys().collect();
+    assert_eq!(tests, ["parse", GATEKEEPER]);
+    assert_eq!(result.updated.tests["parse"].state(), TestState::Passing);
+    let previous = &history()[1].status.integrity.clone().unwrap().digest;
+    let integrity = result.updated.integrity.as_ref().unwrap();
+    assert_eq!(integrity.previous.as_ref(), Some(previous));
+    assert_eq!(
+        integrity.digest,
+        seal(Some(previous), &result.updated).digest
+    );
+}
+
+#[test]
+fn a_failing_case_is_a_regression_of_its_test() {
+    let mut results = all_passing();
+    results[2] = result("parse::case_2", TestOutcome::Failed, Some("boom"));
+
+    let result = gathered(cases(), results).evaluate();
+
+    assert!(
+        matches!(
+            result.violations.as_slice(),
+            [Violation::Regression { test }] if test == "parse"
+        ),
+        "{:?}",
+        result.violations
+    );
+    assert_eq!(result.failure_output["parse"], "parse::case_2:\nboom");
+}
+
+#[test]
+fn tampering_is_still_checked_against_the_committed_cases() {
+    let mut run = gathered(cases(), all_passing());
+    run.history_snapshots[1]
+        .status
+        .tests
+        .insert("parse::case_3".into(), TestEntry::new(TestState::Passing));
+
+    let result = run.evaluate();
+
+    assert!(
+        result
+            .violations
+            .iter()
+            .any(|v| matches!(v, Violation::StatusTampered { commit, .. } if commit == "c2")),
+        "{:?}",
+        result.violations
+    );
+}
+
+#[test]
+fn status_file_tracks_the_parameterized_test_once() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "parameterized = [\"*::case_*\"]\n\n\
+         [rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\n",
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+    let commit_run = |results: &str, message: &str| {
+        fs::write(path.join("results.txt"), results).unwrap();
+        let (code, out) = run_ratchet(path, &[]);
+        git(path, &["add", "-A"]);
+        git(path, &["commit", "-m", message]);
+        (code, out)
+    };
+
+    let (code, out) = commit_run(
+        "test tdd_ratchet_gatekeeper ... ok\ntest parse::case_1 ... FAILED\ntest parse::case_2 ... ok\n",
+        "Add parse cases",
+    );
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    let tests: Vec<&String> = status.tests.keys().collect();
+    assert_eq!(tests, ["parse", GATEKEEPER]);
+    assert_eq!(status.tests["parse"].state(), TestState::Pending);
+
+    let (code, out) = commit_run(
+        "test tdd_ratchet_gatekeeper ... ok\ntest parse::case_1 ... ok\ntest parse::case_2 ... ok\ntest parse::case_3 ... ok\n",
+        "Implement parse",
+    );
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests["parse"].state(), TestState::Passing);
+
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest parse::case_1 ... ok\ntest parse::case_2 ... ok\ntest parse::case_3 ... ok\ntest parse::case_4 ... FAILED\n",
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("parse"), "{out}");
+    assert!(!out.contains("Tamper") && !out.contains("tamper"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,223 @@
+// tests/partial_runs.rs
+//
+// Story 88: `-p <PACKAGE>` and `-E <FILTERSET>` run part of the suite. Tests
+// the filters provably leave out are neither disappeared nor regressed, and
+// the run is reported as partial.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use tdd_ratchet::errors::{format_markdown_report, format_report};
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::run_filter::RunFilter;
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::status::TestState;
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+
+fn filtersets(filtersets: &[&str]) -> RunFilter {
+    RunFilter {
+        packages: Vec::new(),
+        filtersets: filtersets.iter().map(|f| f.to_string()).collect(),
+    }
+}
+
+fn gathered(filter: RunFilter, results: Vec<TestResult>) -> GatheredRun {
+    GatheredRun {
+        filter,
+        ..common::gathered(
+            &[
+                (GATEKEEPER, TestState::Passing),
+                ("core$parse::ok", TestState::Passing),
+                ("core::integration$flow", TestState::Passing),
+                ("web$render", TestState::Passing),
+            ],
+            results,
+        )
+    }
+}
+
+#[test]
+fn packages_exclude_tests_of_other_packages() {
+    let filter = RunFilter {
+        packages: vec!["core".into()],
+        filtersets: Vec::new(),
+    };
+
+    assert!(!filter.excludes("core$parse::ok"));
+    assert!(!filter.excludes("core::integration$flow"));
+    assert!(filter.excludes("web$render"));
+    // Names without a binary cannot be placed in a package.
+    assert!(!filter.excludes("render"));
+}
+
+#[test]
+fn only_provably_unselected_tests_are_excluded() {
+    let cases = [
+        ("test(parse)", "core$parse::ok", false),
+        ("test(parse)", "web$render", true),
+        ("test(=render)", "web$render", false),
+        ("package(core) & not test(~ok)", "core$parse::ok", true),
+        (
+            "package(core) - binary(integration)",
+            "core::integration$flow",
+            true,
+        ),
+        ("binary_id(core::*)", "core::integration$flow", false),
+        ("binary_id(core::*)", "web$render", true),
+        ("binary(core)", "core$parse::ok", false),
+        ("test(/^parse/)", "web$render", false),
+        ("kind(test)", "web$render", false),
+        ("kind(test) & package(core)", "web$render", true),
+        ("test(parse) or kind(lib)", "web$render", false),
+        ("none()", "web$render", true),
+        ("test(parse", "web$render", false),
+    ];
+    for (filterset, test, excluded) in cases {
+        assert_eq!(
+            filtersets(&[filterset]).excludes(test),
+            excluded,
+            "{filterset} on {test}"
+        );
+    }
+
+    let either = filtersets(&["test(parse)", "package(web)"]);
+    assert!(!either.excludes("web$render"));
+    assert!(either.excludes("core::integration$flow"));
+}
+
+#[test]
+fn nextest_runs_the_filtered_tests_and_the_gatekeeper() {
+    let filter = RunFilter {
+        packages: vec!["core".into(), "web".into()],
+        filtersets: vec!["test(parse)".into()],
+    };
+
+    assert_eq!(RunFilter::default().nextest_filterset(&[]), None);
+    assert_eq!(
+        filtersets(&["test(a) | test(b)"]).nextest_filterset(&[]),
+        Some("(test(a) | test(b)) | test(/tdd_ratchet_gatekeeper$/)".into())
+    );
+    assert_eq!(
+        filter.nextest_filterset(&["integration".into()]),
+        Some(
+            "(binary(=integration)) & (package(=core) | package(=web)) & ((test(parse))) \
+             | test(/tdd_ratchet_gatekeeper$/)"
+                .into()
+        )
+    );
+    assert_eq!(
+        filter.label(&["integration".into()]).as_deref(),
+        Some("--test integration -p core -p web -E 'test(parse)'")
+    );
+}
+
+#[test]
+fn tests_outside_the_filter_are_kept_and_the_run_is_partial() {
+    let run = |filter| {
+        gathered(
+            filter,
+            vec![
+                result(GATEKEEPER, TestOutcome::Passed),
+                result("core$parse::ok", TestOutcome::Passed),
+                result("web$render", TestOutcome::Failed),
+            ],
+        )
+        .evaluate()
+    };
+
+    let full = run(RunFilter::default());
+    assert_eq!(full.partial, None);
+    assert_eq!(full.violations.len(), 2, "{:?}", full.violations);
+
+    let partial = run(filtersets(&["test(parse)"]));
+    assert!(partial.violations.is_empty(), "{:?}", partial.violations);
+    assert_eq!(partial.partial.as_deref(), Some("-E 'test(parse)'"));
+    assert_eq!(partial.updated.tests, full.updated.tests);
+
+    let report = format_report(&partial);
+    assert!(
+        report.contains("partial run: only tests selected by -E 'test(parse)' were checked"),
+        "{report}"
+    );
+    let markdown = format_markdown_report(&partial, &gathered(RunFilter::default(), vec![]).status);
+    assert!(markdown.contains("> Partial run:"), "{markdown}");
+}
+
+#[test]
+fn tests_the_filter_may_select_are_still_checked() {
+    let result = gathered(
+        filtersets(&["test(/parse/)"]),
+        vec![
+            result(GATEKEEPER, TestOutcome::Passed),
+            result("core$parse::ok", TestOutcome::Failed),
+        ],
+    )
+    .evaluate();
+
+    let mut flagged: Vec<String> = result
+        .violations
+        .iter()
+        .map(|v| match v {
+            Violation::Regression { test } => format!("regression {test}"),
+            Violation::TestDisappeared { test } => format!("disappeared {test}"),
+            other => format!("{other:?}"),
+        })
+        .collect();
+    flagged.sort();
+    assert_eq!(
+        flagged,
+        [
+            "disappeared core::integration$flow",
+            "disappeared web$render",
+            "regression core$parse::ok",
+        ]
+    );
+}
+
+#[test]
+fn package_flag_limits_the_check_to_one_package() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"version":3,"binaries":{
+            "app::gatekeeper":{"tests":{"tdd_ratchet_gatekeeper":{"state":"passing"}}},
+            "core":{"tests":{"parse":{"state":"passing"}}},
+            "web":{"tests":{"render":{"state":"passing"}}}}}"#,
+    )
+    .unwrap();
+    let set_results = |names: &[&str]| {
+        let lines: Vec<String> = names
+            .iter()
+            .map(|name| format!("{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"{name}\"}}"))
+            .collect();
+        fs::write(path.join("results.json"), lines.join("\n") + "\n").unwrap();
+    };
+    set_results(&[GATEKEEPER, "core$parse", "web$render"]);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    set_results(&[GATEKEEPER, "core$parse"]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("web$render"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["-p", "core"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("partial run"), "{out}");
+    assert!(out.contains("-p core"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,202 @@
+// tests/partitioned_runs.rs
+//
+// Story 50: CI shards emit the results of one nextest partition each, and
+// the ratchet evaluates the merged results once, so sharding does not make
+// tests look disappeared.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::error::{EvaluationError, RatchetError};
+use tdd_ratchet::partition::{
+    PartialResults, Partition, load_partial_results, merge_partial_results, save_partial_results,
+};
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+
+fn shard(partition: &str, results: Vec<TestResult>) -> PartialResults {
+    PartialResults {
+        partition: Partition::parse(partition),
+        results,
+    }
+}
+
+/// A committed project tracking `a` and `b` as passing, run through a
+/// command runner.
+fn project(dir: &Path) {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    fs::write(
+        dir.join("ratchet.toml"),
+        "[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest a ... ok\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","a":"passing","b":"passing"}}"#,
+    )
+    .unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-m", "Initial project"]);
+}
+
+#[test]
+fn partitions_parse_with_or_without_the_count_prefix() {
+    let partition = Partition::parse("1/4").unwrap();
+    assert_eq!(partition, Partition { index: 1, count: 4 });
+    assert_eq!(Partition::parse("count:2/4").unwrap().index, 2);
+    assert_eq!(partition.nextest_arg(), "count:1/4");
+    assert_eq!(Partition::parse("0/4"), None);
+    assert_eq!(Partition::parse("5/4"), None);
+    assert_eq!(Partition::parse("hash:1/4"), None);
+}
+
+#[test]
+fn shards_merge_into_one_run() {
+    let merged = merge_partial_results(&[
+        shard("2/2", vec![result("b", TestOutcome::Failed)]),
+        shard("1/2", vec![result("a", TestOutcome::Passed)]),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        merged,
+        vec![
+            result("b", TestOutcome::Failed),
+            result("a", TestOutcome::Passed)
+        ]
+    );
+}
+
+#[test]
+fn a_test_in_several_shards_keeps_its_worst_outcome() {
+    let merged = merge_partial_results(&[
+        shard("1/2", vec![result("a", TestOutcome::Passed)]),
+        shard("2/2", vec![result("a", TestOutcome::Failed)]),
+    ])
+    .unwrap();
+
+    assert_eq!(merged, vec![result("a", TestOutcome::Failed)]);
+}
+
+#[test]
+fn incomplete_or_mismatched_shards_are_rejected() {
+    let err =
+        merge_partial_results(&[shard("1/3", Vec::new()), shard("3/3", Vec::new())]).unwrap_err();
+    assert!(
+        matches!(&err, RatchetError::Evaluation(EvaluationError::MissingPartitions(missing)) if missing == &[Partition { index: 2, count: 3 }]),
+        "{err}"
+    );
+    assert!(err.to_string().contains("missing partition 2/3"), "{err}");
+
+    let err =
+        merge_partial_results(&[shard("1/2", Vec::new()), shard("1/2", Vec::new())]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            RatchetError::Evaluation(EvaluationError::DuplicatePartition(_))
+        ),
+        "{err}"
+    );
+
+    let err =
+        merge_partial_results(&[shard("1/2", Vec::new()), shard("2/3", Vec::new())]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            RatchetError::Evaluation(EvaluationError::MixedPartitionCounts { .. })
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn partial_results_round_trip_through_a_file() {
+    let dir = TestDir::new();
+    let path = dir.path().join("part1.json");
+    let partial = shard(
+        "1/4",
+        vec![
+            result("a", TestOutcome::Passed),
+            TestResult {
+                output: Some("boom".into()),
+                exec_time_ms: None,
+                ..result("b", TestOutcome::Failed)
+            },
+        ],
+    );
+
+    save_partial_results(&path, &partial).unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.contains("\"partition\": \"1/4\""), "{written}");
+    assert!(written.contains("\"outcome\": \"failed\""), "{written}");
+    assert_eq!(load_partial_results(&path).unwrap(), partial);
+    dir.pass();
+}
+
+#[test]
+fn merged_shards_do_not_report_tests_from_other_shards_as_disappeared() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    project(path);
+    save_partial_results(
+        &path.join("part1.json"),
+        &shard(
+            "1/2",
+            vec![
+                result("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+                result("a", TestOutcome::Passed),
+            ],
+        ),
+    )
+    .unwrap();
+    save_partial_results(
+        &path.join("part2.json"),
+        &shard("2/2", vec![result("b", TestOutcome::Passed)]),
+    )
+    .unwrap();
+
+    let (code, out) = run_ratchet(path, &["merge-results", "part1.json"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("missing partition 2/2"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["merge-results", "part1.json", "part2.json"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!out.contains("missing from the run"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn run_emits_results_without_evaluating_them() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    project(path);
+
+    // `b` is missing from this run, but nothing is evaluated yet.
+    let (code, out) = run_ratchet(path, &["run", "--emit-results", "part.json"]);
+    assert_eq!(code, Some(0), "{out}");
+    let partial = load_partial_results(&path.join("part.json")).unwrap();
+    assert_eq!(partial.partition, None);
+    assert_eq!(partial.results.len(), 2);
+
+    let (code, out) = run_ratchet(path, &["run", "--partition", "1/2"]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("--emit-results"), "{out}");
+
+    let (code, out) = run_ratchet(
+        path,
+        &["run", "--partition", "1/2", "--emit-results", "part.json"],
+    );
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("needs the nextest runner"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,218 @@
+// tests/pending_commit_types.rs
+//
+// Story 84: with `pending-test-commit-types`, commits in history that add
+// pending tests must have one of the listed conventional-commit types.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet, status};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RulesConfig};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::{Violation, commit_type};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{TestState, TrackedStatus};
+
+const GATEKEEPER: &str = "tdd_ratchet_gatekeeper";
+
+fn snapshot(commit: &str, summary: &str, tests: &[(&str, TestState)]) -> HistorySnapshot {
+    let tests: Vec<_> = [(GATEKEEPER, TestState::Passing)]
+        .into_iter()
+        .chain(tests.iter().copied())
+        .collect();
+    HistorySnapshot {
+        info: CommitInfo {
+            summary: summary.to_string(),
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status(&tests))
+    }
+}
+
+/// `c2` adds `a` under a `test:` type, `c3` adds `b` and `c` without one,
+/// and `c4` promotes `a`.
+fn history() -> Vec<HistorySnapshot> {
+    use TestState::{Passing, Pending};
+    vec![
+        snapshot("c1", "Initial project", &[]),
+        snapshot("c2", "test(parser): cover empty input", &[("a", Pending)]),
+        snapshot(
+            "c3",
+            "Add more tests",
+            &[("a", Pending), ("b", Pending), ("c", Pending)],
+        ),
+        snapshot(
+            "c4",
+            "feat: parse empty input",
+            &[("a", Passing), ("b", Pending), ("c", Pending)],
+        ),
+    ]
+}
+
+fn gathered(types: &[&str], baseline: Option<&str>) -> GatheredRun {
+    let mut history = history();
+    let head = history.last_mut().unwrap();
+    head.status.baseline = baseline.map(str::to_string);
+    let status = TrackedStatus::new(head.status.tests.clone());
+    let results = [
+        (GATEKEEPER, TestOutcome::Passed),
+        ("a", TestOutcome::Passed),
+        ("b", TestOutcome::Failed),
+        ("c", TestOutcome::Failed),
+    ]
+    .into_iter()
+    .map(|(name, outcome)| result(name, outcome))
+    .collect();
+    GatheredRun {
+        status,
+        results,
+        history_snapshots: history,
+        rules: RulesConfig {
+            pending_test_commit_types: types.iter().map(|t| t.to_string()).collect(),
+            ..RulesConfig::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn commit_types_follow_conventional_commits() {
+    assert_eq!(commit_type("test: cover empty input"), Some("test"));
+    assert_eq!(
+        commit_type("test(parser)!: cover empty input"),
+        Some("test")
+    );
+    assert_eq!(commit_type("Test: capitalized"), Some("Test"));
+    assert_eq!(commit_type("Add feature test"), None);
+    assert_eq!(commit_type("Fix bug: empty input"), None);
+    assert_eq!(commit_type("test(parser: unclosed scope"), None);
+}
+
+#[test]
+fn pending_test_commit_types_are_configurable() {
+    let config = RatchetConfig::parse_from_str(
+        "[rules]\npending-test-commit-types = [\"test\", \"spec\"]\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(config.rules.pending_test_commit_types, ["test", "spec"]);
+    assert!(RulesConfig::default().pending_test_commit_types.is_empty());
+}
+
+#[test]
+fn not_checked_by_default() {
+    let result = gathered(&[], None).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+}
+
+#[test]
+fn commits_adding_pending_tests_without_an_allowed_type_are_flagged() {
+    let result = gathered(&["test"], None).evaluate();
+
+    let [
+        Violation::PendingTestCommitType {
+            commit,
+            summary,
+            tests,
+            allowed,
+        },
+    ] = result.violations.as_slice()
+    else {
+        panic!("{:?}", result.violations);
+    };
+    assert_eq!(commit, "c3");
+    assert_eq!(summary, "Add more tests");
+    assert_eq!(tests, &["b", "c"]);
+    assert_eq!(allowed, &["test"]);
+    assert_eq!(result.violations[0].code(), "RATCHET024");
+
+    let report = format_report(&result);
+    assert!(
+        report.contains("commit adding pending tests has the wrong type"),
+        "{report}"
+    );
+    assert!(
+        report.contains("commit c3 \"Add more tests\" added pending: b, c"),
+        "{report}"
+    );
+    assert!(report.contains("does not start with `test:`"), "{report}");
+}
+
+#[test]
+fn any_listed_type_is_accepted_case_insensitively() {
+    let result = gathered(&["TEST", "chore"], None).evaluate();
+    assert_eq!(result.violations.len(), 1, "{:?}", result.violations);
+
+    let mut run = gathered(&["test"], None);
+    run.history_snapshots[2].info.summary = "Spec: add more tests".into();
+    run.rules.pending_test_commit_types.push("spec".into());
+    let result = run.evaluate();
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+}
+
+#[test]
+fn commits_before_the_baseline_are_not_checked() {
+    let result = gathered(&["test"], Some("c3")).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+}
+
+#[test]
+fn commit_messages_are_read_from_git_history() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\npending-test-commit-types = [\"test\"]\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\n",
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+    let commit_run = |results: &str, message: &str| {
+        fs::write(path.join("results.txt"), results).unwrap();
+        let (code, out) = run_ratchet(path, &[]);
+        git(path, &["add", "-A"]);
+        git(path, &["commit", "-m", message]);
+        (code, out)
+    };
+
+    let (code, out) = commit_run(
+        "test tdd_ratchet_gatekeeper ... ok\n",
+        "chore: track the gatekeeper",
+    );
+    assert_eq!(code, Some(0), "{out}");
+    let (code, out) = commit_run(
+        "test tdd_ratchet_gatekeeper ... ok\ntest typed ... FAILED\n",
+        "test: cover the typed feature",
+    );
+    assert_eq!(code, Some(0), "{out}");
+    let (code, out) = commit_run(
+        "test tdd_ratchet_gatekeeper ... ok\ntest typed ... FAILED\ntest untyped ... FAILED\n",
+        "Add untyped test",
+    );
+    assert_eq!(code, Some(0), "{out}");
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(
+        out.contains("\"Add untyped test\" added pending: untyped"),
+        "{out}"
+    );
+    assert!(!out.contains("added pending: typed"), "{out}");
+    assert!(out.contains("[RATCHET024]"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,106 @@
+// tests/pending_limit.rs
+//
+// Story 53: `max-pending` caps how many tests may be pending at once. A run
+// that would add pending tests beyond the cap is a violation and leaves the
+// new tests unrecorded.
+
+mod common;
+
+use common::result;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RulesConfig};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::integrity::seal;
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::TestState;
+
+/// `old` is already pending; `failing` tests are new and fail.
+fn gathered(failing: &[&str], max_pending: Option<usize>) -> GatheredRun {
+    let mut results = vec![
+        result("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+        result("old", TestOutcome::Failed),
+    ];
+    results.extend(failing.iter().map(|name| result(name, TestOutcome::Failed)));
+    GatheredRun {
+        rules: RulesConfig {
+            max_pending,
+            ..RulesConfig::default()
+        },
+        ..common::gathered(
+            &[
+                ("tdd_ratchet_gatekeeper", TestState::Passing),
+                ("old", TestState::Pending),
+            ],
+            results,
+        )
+    }
+}
+
+#[test]
+fn max_pending_is_configurable() {
+    let config =
+        RatchetConfig::parse_from_str("[rules]\nmax-pending = 5\n", Path::new("ratchet.toml"))
+            .unwrap();
+    assert_eq!(config.rules.max_pending, Some(5));
+    assert_eq!(RulesConfig::default().max_pending, None);
+}
+
+#[test]
+fn no_limit_by_default() {
+    let result = gathered(&["a", "b", "c"], None).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(result.updated.tests.len(), 5);
+}
+
+#[test]
+fn new_pending_tests_within_the_limit_are_recorded() {
+    let result = gathered(&["a"], Some(2)).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(result.updated.tests["a"].state(), TestState::Pending);
+}
+
+#[test]
+fn exceeding_the_limit_is_a_violation_and_new_tests_are_not_recorded() {
+    let result = gathered(&["a", "b"], Some(2)).evaluate();
+
+    assert!(
+        matches!(
+            result.violations.as_slice(),
+            [Violation::PendingLimitExceeded { tests, pending: 1, limit: 2 }]
+                if tests == &["a".to_string(), "b".to_string()]
+        ),
+        "{:?}",
+        result.violations
+    );
+    assert!(!result.updated.tests.contains_key("a"));
+    assert!(!result.updated.tests.contains_key("b"));
+    assert_eq!(result.updated.tests["old"].state(), TestState::Pending);
+    assert_eq!(
+        result.updated.integrity,
+        Some(seal(None, &result.updated)),
+        "the seal covers the status actually written"
+    );
+
+    let report = format_report(&result);
+    assert!(report.contains("too many pending tests"), "{report}");
+    assert!(
+        report.contains("1 test is already pending and the limit is 2"),
+        "{report}"
+    );
+    assert!(
+        report.contains("New pending test not recorded: a"),
+        "{report}"
+    );
+}
+
+#[test]
+fn tests_already_pending_over_the_limit_are_not_reported_again() {
+    let result = gathered(&[], Some(0)).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(result.updated.tests["old"].state(), TestState::Pending);
+}
@@ -0,0 +1,230 @@
+// tests/plugins.rs
+//
+// Story 77: `ratchet-rule-*` plugins listed under `[plugins]` read the run as
+// JSON on stdin and answer with extra violations and warnings. A broken
+// plugin is reported as a warning and never crashes the run.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet_with_path};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tdd_ratchet::plugins::{
+    PluginError, PluginFinding, PluginFindings, apply_findings, parse_findings, run_plugin,
+};
+use tdd_ratchet::ratchet::{EvalResult, Violation, Warning};
+use tdd_ratchet::status::StatusFile;
+
+fn write_script(path: &Path, body: &str) {
+    fs::write(path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn empty_result() -> EvalResult {
+    EvalResult {
+        violations: Vec::new(),
+        warnings: Vec::new(),
+        updated: StatusFile::empty(),
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+fn finding(subject: &str, message: &str) -> PluginFinding {
+    PluginFinding {
+        subject: subject.into(),
+        message: message.into(),
+    }
+}
+
+#[test]
+fn findings_parse_from_json_with_both_lists_optional() {
+    assert_eq!(parse_findings(b"").unwrap(), PluginFindings::default());
+    assert_eq!(parse_findings(b"\n").unwrap(), PluginFindings::default());
+    assert_eq!(
+        parse_findings(br#"{"warnings":[{"message":"slow"}]}"#).unwrap(),
+        PluginFindings {
+            violations: Vec::new(),
+            warnings: vec![finding("", "slow")],
+        }
+    );
+    assert!(matches!(
+        parse_findings(b"all good!"),
+        Err(PluginError::BadOutput { .. })
+    ));
+}
+
+#[test]
+fn findings_become_plugin_violations_and_warnings() {
+    let mut result = empty_result();
+    let findings = PluginFindings {
+        violations: vec![finding("src/lib.rs", "TODO left in code")],
+        warnings: vec![finding("it_works", "name is too vague")],
+    };
+
+    apply_findings(&mut result, "no-todo", Ok(findings));
+
+    assert_eq!(result.violations.len(), 1);
+    assert_eq!(result.violations[0].code(), "RATCHET023");
+    assert!(matches!(
+        &result.violations[0],
+        Violation::PluginViolation { plugin, subject, message }
+            if plugin == "no-todo" && subject == "src/lib.rs" && message == "TODO left in code"
+    ));
+    assert!(matches!(
+        &result.warnings[..],
+        [Warning::PluginWarning { plugin, subject, .. }] if plugin == "no-todo" && subject == "it_works"
+    ));
+}
+
+#[test]
+fn a_failed_plugin_is_a_warning_not_a_violation() {
+    let mut result = empty_result();
+
+    apply_findings(
+        &mut result,
+        "broken",
+        Err(PluginError::TimedOut {
+            timeout: Duration::from_secs(60),
+        }),
+    );
+
+    assert!(result.violations.is_empty());
+    assert!(matches!(
+        &result.warnings[..],
+        [Warning::PluginFailed { plugin, reason }] if plugin == "broken" && reason.contains("60s")
+    ));
+}
+
+#[test]
+fn plugin_reads_context_on_stdin_and_answers_on_stdout() {
+    let dir = TestDir::new();
+    let plugin = dir.path().join("ratchet-rule-echo");
+    write_script(
+        &plugin,
+        r#"cat > context.json
+echo '{"violations":[{"subject":"a","message":"bad"}]}'"#,
+    );
+
+    let findings = run_plugin(
+        plugin.to_str().unwrap(),
+        dir.path(),
+        br#"{"protocol":1}"#.to_vec(),
+        Duration::from_secs(10),
+    )
+    .unwrap();
+
+    assert_eq!(findings.violations, [finding("a", "bad")]);
+    assert_eq!(
+        fs::read_to_string(dir.path().join("context.json")).unwrap(),
+        r#"{"protocol":1}"#
+    );
+    dir.pass();
+}
+
+#[test]
+fn broken_plugins_are_errors() {
+    let dir = TestDir::new();
+    let failing = dir.path().join("ratchet-rule-failing");
+    write_script(&failing, "echo '{}'; exit 3");
+    let hanging = dir.path().join("ratchet-rule-hanging");
+    write_script(&hanging, "exec sleep 30");
+    let run = |program: &str, timeout| run_plugin(program, dir.path(), Vec::new(), timeout);
+
+    assert!(matches!(
+        run(failing.to_str().unwrap(), Duration::from_secs(10)),
+        Err(PluginError::Failed { .. })
+    ));
+    assert!(matches!(
+        run(hanging.to_str().unwrap(), Duration::from_millis(200)),
+        Err(PluginError::TimedOut { .. })
+    ));
+    assert!(matches!(
+        run("ratchet-rule-does-not-exist", Duration::from_secs(10)),
+        Err(PluginError::Spawn { .. })
+    ));
+    dir.pass();
+}
+
+/// A project whose tests all pass, with `plugins` listed under `[plugins]`
+/// and plugin programs to be written into `bin`.
+fn setup_project(plugins: &str) -> (TestDir, PathBuf) {
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        format!(
+            "[rules]\ndirty-worktree = \"allow\"\n\n\
+             [plugins]\nrules = [{plugins}]\n\n\
+             [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n"
+        ),
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    fs::write(path.join(".gitignore"), "/bin\n").unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+    let bin = path.join("bin");
+    fs::create_dir(&bin).unwrap();
+    (dir, bin)
+}
+
+#[test]
+fn plugin_violations_fail_the_run() {
+    build_ratchet_binary();
+    let (dir, bin) = setup_project(r#""feature-names""#);
+    write_script(
+        &bin.join("ratchet-rule-feature-names"),
+        r#"grep -q '"name":"feature"' || exit 1
+echo '{"violations":[{"subject":"feature","message":"test names must say what they check"}]}'"#,
+    );
+
+    let (code, out) = run_ratchet_with_path(dir.path(), &bin, &[]);
+
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("plugin rule violation [RATCHET023]"), "{out}");
+    assert!(
+        out.contains("[feature-names] feature: test names must say what they check"),
+        "{out}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn broken_plugins_are_reported_without_failing_the_run() {
+    build_ratchet_binary();
+    let (dir, bin) = setup_project(r#""garbled", "missing""#);
+    write_script(&bin.join("ratchet-rule-garbled"), "echo 'not json'");
+
+    let (code, out) = run_ratchet_with_path(dir.path(), &bin, &[]);
+
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("plugin garbled failed"), "{out}");
+    assert!(
+        out.contains(
+            "plugin missing failed, its checks were skipped: could not run ratchet-rule-missing"
+        ),
+        "{out}"
+    );
+    assert!(out.contains("tdd-ratchet: ok (2 passing)"), "{out}");
+    dir.pass();
+}
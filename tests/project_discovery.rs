@@ -0,0 +1,202 @@
+// tests/project_discovery.rs
+//
+// Story 94: the ratchet works from any subdirectory of a project, finding
+// the nearest status file or `ratchet.toml` up to the repository root, and
+// `--project-dir` checks a project elsewhere.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::discovery::{
+    RepoLocation, discover_project_dir, find_project_dir, locate_in_repo,
+};
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+
+fn setup_project(path: &Path) {
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    write_project_files(path);
+    fs::create_dir_all(path.join("src/nested")).unwrap();
+    fs::write(path.join("src/nested/lib.rs"), "").unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+}
+
+/// A `ratchet.toml` whose runner reports `results.json`, holding only the
+/// passing gatekeeper.
+fn write_project_files(path: &Path) {
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.json"),
+        format!("{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"{GATEKEEPER}\"}}\n"),
+    )
+    .unwrap();
+}
+
+fn write_results(path: &Path, test_event: &str) {
+    fs::write(
+        path.join("results.json"),
+        format!(
+            "{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"{GATEKEEPER}\"}}\n\
+             {{\"type\":\"test\",\"event\":\"{test_event}\",\"name\":\"app::tests::one\"}}\n"
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn the_nearest_project_up_to_the_repository_root_is_used() {
+    let projects = [Path::new("/repo"), Path::new("/repo/crates/app")];
+    let is_project = |dir: &Path| projects.contains(&dir);
+
+    assert_eq!(
+        find_project_dir(
+            Path::new("/repo/crates/app/src"),
+            Some(Path::new("/repo")),
+            is_project
+        ),
+        Path::new("/repo/crates/app")
+    );
+    assert_eq!(
+        find_project_dir(
+            Path::new("/repo/docs"),
+            Some(Path::new("/repo")),
+            is_project
+        ),
+        Path::new("/repo")
+    );
+    // Project files above the repository root belong to another project.
+    assert_eq!(
+        find_project_dir(
+            Path::new("/repo/vendor/lib/src"),
+            Some(Path::new("/repo/vendor/lib")),
+            is_project
+        ),
+        Path::new("/repo/vendor/lib")
+    );
+    assert_eq!(
+        find_project_dir(Path::new("/scratch/dir"), None, is_project),
+        Path::new("/scratch/dir")
+    );
+}
+
+#[test]
+fn a_repository_without_project_files_uses_its_root() {
+    let dir = TestDir::new();
+    let path = dir.path().canonicalize().unwrap();
+    git(&path, &["init", "-b", "main"]);
+    fs::create_dir_all(path.join("a/b")).unwrap();
+
+    assert_eq!(discover_project_dir(&path.join("a/b")), path);
+    fs::write(path.join("a/ratchet.toml"), "").unwrap();
+    assert_eq!(discover_project_dir(&path.join("a/b")), path.join("a"));
+    dir.pass();
+}
+
+#[test]
+fn runs_from_a_subdirectory_check_the_project_root() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    setup_project(path);
+
+    let (code, out) = run_ratchet(&path.join("src/nested"), &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(path.join(".test-status.json").exists());
+    assert!(!path.join("src/nested/.test-status.json").exists());
+    dir.pass();
+}
+
+#[test]
+fn project_dir_flag_checks_another_directory() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let project = dir.path().join("project");
+    let elsewhere = dir.path().join("elsewhere");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(&elsewhere).unwrap();
+    setup_project(&project);
+
+    let (code, out) = run_ratchet(&elsewhere, &["--project-dir", "../project"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(project.join(".test-status.json").exists());
+
+    let (code, out) = run_ratchet(&elsewhere, &["--project-dir", "missing"]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("is not a directory"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn a_nested_project_is_located_within_its_repository() {
+    let dir = TestDir::new();
+    let path = dir.path().canonicalize().unwrap();
+    git(&path, &["init", "-b", "main"]);
+    fs::create_dir_all(path.join("crates/app")).unwrap();
+
+    assert_eq!(
+        locate_in_repo(&path.join("crates/app")),
+        RepoLocation {
+            repo_dir: path.clone(),
+            prefix: Path::new("crates/app").to_path_buf(),
+        }
+    );
+    assert_eq!(
+        locate_in_repo(&path.join("crates/app")).status_path(),
+        Path::new("crates/app/.test-status.json")
+    );
+    assert_eq!(locate_in_repo(&path).prefix, Path::new(""));
+    dir.pass();
+}
+
+#[test]
+fn a_project_in_a_subdirectory_reads_history_from_the_enclosing_repository() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    let project = path.join("sub");
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::create_dir_all(&project).unwrap();
+    write_project_files(&project);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    write_results(&project, "failed");
+    let (code, out) = run_ratchet(&project, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add failing test"]);
+    write_results(&project, "ok");
+    let (code, out) = run_ratchet(&project, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!path.join(".test-status.json").exists());
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Make test pass"]);
+
+    let (code, out) = run_ratchet(&project, &["log", "app::tests::one"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!out.contains("no committed history"), "{out}");
+    assert!(out.contains("passing"), "{out}");
+
+    let (code, out) = run_ratchet(&project, &["stats", "--json"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("\"snapshots\": 2"), "{out}");
+
+    let (code, out) = run_ratchet(&project, &["trend"]);
+    assert_eq!(code, Some(0), "{out}");
+
+    let (code, out) = run_ratchet(&project, &["check", "--staged"]);
+    assert_eq!(code, Some(0), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,213 @@
+// tests/promotion_commits.rs
+//
+// Story 45: The commit that promoted a test is recorded in its status entry,
+// shown by `cargo ratchet blame`, and named when the test later regresses.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{Promotion, StatusFile, TestEntry, TestState, TrackedStatus};
+use tdd_ratchet::timeline::format_blame;
+
+const GATEKEEPER: &str = "tdd_ratchet_gatekeeper";
+
+fn set_results(dir: &Path, results: &[(&str, &str)]) {
+    let lines: String = results
+        .iter()
+        .map(|(name, outcome)| format!("test {name} ... {outcome}\n"))
+        .collect();
+    fs::write(dir.join("results.txt"), lines).unwrap();
+}
+
+fn snapshot(commit: &str, author: &str, feature: TestState) -> HistorySnapshot {
+    HistorySnapshot {
+        commit: commit.to_string(),
+        info: CommitInfo {
+            author: author.into(),
+            time: 86_400,
+            summary: format!("commit {commit}"),
+            ..CommitInfo::default()
+        },
+        status: StatusFile::new(
+            [
+                (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+                ("feature".to_string(), TestEntry::new(feature)),
+            ]
+            .into(),
+        ),
+    }
+}
+
+fn gathered(
+    feature: TestEntry,
+    outcome: TestOutcome,
+    history: Vec<HistorySnapshot>,
+) -> GatheredRun {
+    GatheredRun {
+        status: TrackedStatus::new(
+            [
+                (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+                ("feature".to_string(), feature),
+            ]
+            .into(),
+        ),
+        results: [(GATEKEEPER, TestOutcome::Passed), ("feature", outcome)]
+            .iter()
+            .map(|(name, outcome)| result(name, *outcome))
+            .collect(),
+        history_snapshots: history,
+        ..Default::default()
+    }
+}
+
+fn promotion(commit: &str, author: &str) -> Promotion {
+    Promotion {
+        commit: commit.to_string(),
+        author: author.to_string(),
+        time: 86_400,
+    }
+}
+
+#[test]
+fn committed_promotion_is_recorded_on_the_next_run() {
+    let history = vec![
+        snapshot("aaaaaaaa11", "Ada", TestState::Pending),
+        snapshot("bbbbbbbb22", "Grace", TestState::Passing),
+    ];
+    let result = gathered(
+        TestEntry::new(TestState::Passing),
+        TestOutcome::Passed,
+        history,
+    )
+    .evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(
+        result.updated.tests["feature"].promoted,
+        Some(promotion("bbbbbbbb22", "Grace"))
+    );
+    assert_eq!(result.updated.tests[GATEKEEPER].promoted, None);
+}
+
+#[test]
+fn uncommitted_promotion_is_not_recorded_yet() {
+    let history = vec![snapshot("aaaaaaaa11", "Ada", TestState::Pending)];
+    let result = gathered(
+        TestEntry::new(TestState::Pending),
+        TestOutcome::Passed,
+        history,
+    )
+    .evaluate();
+
+    assert_eq!(result.updated.tests["feature"].state(), TestState::Passing);
+    assert_eq!(result.updated.tests["feature"].promoted, None);
+}
+
+#[test]
+fn demotion_clears_the_recorded_promotion() {
+    let mut entry = TestEntry::new(TestState::Passing);
+    entry.promoted = Some(promotion("bbbbbbbb22", "Grace"));
+
+    assert_eq!(
+        entry.with_state(TestState::Passing).promoted,
+        entry.promoted
+    );
+    assert_eq!(entry.with_state(TestState::Pending).promoted, None);
+}
+
+#[test]
+fn regression_names_the_promoting_commit_and_author() {
+    let mut entry = TestEntry::new(TestState::Passing);
+    entry.promoted = Some(promotion("bbbbbbbb22", "Grace"));
+    let result = gathered(entry, TestOutcome::Failed, Vec::new()).evaluate();
+
+    let report = format_report(&result);
+    assert!(
+        report.contains("now fails: feature (green since bbbbbbbb by Grace)"),
+        "{report}"
+    );
+}
+
+#[test]
+fn blame_falls_back_to_the_recorded_promotion() {
+    let mut promoted = TestEntry::new(TestState::Passing);
+    promoted.promoted = Some(promotion("bbbbbbbb22", "Grace"));
+
+    let blame = format_blame("feature", Some(&promoted), &[]);
+    assert!(
+        blame.contains("promoted:  bbbbbbbb  1970-01-02  Grace\n"),
+        "{blame}"
+    );
+}
+
+#[test]
+fn promotion_round_trips_through_the_status_file() {
+    let json = r#"{"version":2,"tests":{"a":{"state":"passing","promoted":{"commit":"0123456789abcdef0123456789abcdef01234567","author":"Ada","time":86400}},"b":"passing"}}"#;
+    let status = StatusFile::parse_from_str(json, Path::new(".test-status.json")).unwrap();
+    assert_eq!(
+        status.tests["a"].promoted,
+        Some(promotion("0123456789abcdef0123456789abcdef01234567", "Ada"))
+    );
+    assert_eq!(status.tests["b"].promoted, None);
+
+    let written = serde_json::to_string(&status).unwrap();
+    assert_eq!(written.matches("promoted").count(), 1, "{written}");
+}
+
+#[test]
+fn blame_command_shows_the_commit_that_promoted_the_test() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    set_results(path, &[(GATEKEEPER, "ok"), ("feature", "FAILED")]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add failing feature test"]);
+
+    set_results(path, &[(GATEKEEPER, "ok"), ("feature", "ok")]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Implement feature"]);
+    let head = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(path)
+        .output()
+        .unwrap();
+    let head = String::from_utf8(head.stdout).unwrap().trim().to_string();
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    let recorded = status.tests["feature"].promoted.clone().unwrap();
+    assert_eq!(recorded.commit, head);
+    assert_eq!(recorded.author, "Test");
+
+    let (code, out) = run_ratchet(path, &["blame", "feature"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("state:     passing"), "{out}");
+    assert!(out.contains(&format!("promoted:  {}", &head[..8])), "{out}");
+    let (code, out) = run_ratchet(path, &["blame", "missing"]);
+    assert_ne!(code, Some(0), "{out}");
+    assert!(out.contains("not tracked"), "{out}");
+    dir.pass();
+}
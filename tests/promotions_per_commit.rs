@@ -0,0 +1,118 @@
+// tests/promotions_per_commit.rs
+//
+// Story 54: `max-promotions-per-commit` flags commits in history that move
+// more than N tests from pending to passing at once.
+
+mod common;
+
+use common::{result, status};
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RulesConfig};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::history::HistorySnapshot;
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{TestState, TrackedStatus};
+
+const GATEKEEPER: &str = "tdd_ratchet_gatekeeper";
+
+fn snapshot(commit: &str, tests: &[(&str, TestState)]) -> HistorySnapshot {
+    let tests: Vec<_> = [(GATEKEEPER, TestState::Passing)]
+        .into_iter()
+        .chain(tests.iter().copied())
+        .collect();
+    common::snapshot(commit, status(&tests))
+}
+
+/// `c1` adds `a`, `b` and `c` as pending; `c2` promotes `a` and `b`; `c3`
+/// promotes `c`.
+fn history() -> Vec<HistorySnapshot> {
+    use TestState::{Passing, Pending};
+    vec![
+        snapshot("c1", &[("a", Pending), ("b", Pending), ("c", Pending)]),
+        snapshot("c2", &[("a", Passing), ("b", Passing), ("c", Pending)]),
+        snapshot("c3", &[("a", Passing), ("b", Passing), ("c", Passing)]),
+    ]
+}
+
+fn gathered(rules: RulesConfig, baseline: Option<&str>) -> GatheredRun {
+    let mut history = history();
+    let head = history.last_mut().unwrap();
+    head.status.baseline = baseline.map(str::to_string);
+    let status = TrackedStatus::new(head.status.tests.clone());
+    let results = [GATEKEEPER, "a", "b", "c"]
+        .into_iter()
+        .map(|name| result(name, TestOutcome::Passed))
+        .collect();
+    GatheredRun {
+        status,
+        results,
+        history_snapshots: history,
+        rules,
+        ..Default::default()
+    }
+}
+
+fn limit(max: usize) -> RulesConfig {
+    RulesConfig {
+        max_promotions_per_commit: Some(max),
+        ..RulesConfig::default()
+    }
+}
+
+#[test]
+fn max_promotions_per_commit_is_configurable() {
+    let config = RatchetConfig::parse_from_str(
+        "[rules]\nmax-promotions-per-commit = 1\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(config.rules.max_promotions_per_commit, Some(1));
+    assert_eq!(RulesConfig::default().max_promotions_per_commit, None);
+}
+
+#[test]
+fn no_limit_by_default() {
+    let result = gathered(RulesConfig::default(), None).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+}
+
+#[test]
+fn commits_promoting_more_than_the_limit_are_flagged() {
+    let result = gathered(limit(1), None).evaluate();
+
+    assert!(
+        matches!(
+            result.violations.as_slice(),
+            [Violation::TooManyPromotions { commit, tests, limit: 1 }]
+                if commit == "c2" && tests == &["a".to_string(), "b".to_string()]
+        ),
+        "{:?}",
+        result.violations
+    );
+    let report = format_report(&result);
+    assert!(
+        report.contains("commit promotes too many tests"),
+        "{report}"
+    );
+    assert!(
+        report.contains("commit c2 promoted 2 tests: a, b"),
+        "{report}"
+    );
+}
+
+#[test]
+fn commits_within_the_limit_pass() {
+    let result = gathered(limit(2), None).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+}
+
+#[test]
+fn commits_before_the_baseline_are_not_checked() {
+    let result = gathered(limit(1), Some("c2")).evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+}
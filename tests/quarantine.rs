@@ -0,0 +1,249 @@
+// tests/quarantine.rs
+//
+// Story 99: a passing test whose outcome keeps flipping on the same commit
+// is quarantined as flaky after `quarantine-after` flips in a row. Its
+// failures are warnings until `cargo ratchet unquarantine` restores strict
+// enforcement.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::quarantine::{FlipRecord, Observation, observe_flips, quarantine_flaky_tests};
+use tdd_ratchet::ratchet::{EvalResult, Violation, Warning, evaluate};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{Quarantine, StatusFile, TestEntry, TestState, WorkingTreeInstructions};
+
+const GATEKEEPER: &str = "app::tests$tdd_ratchet_gatekeeper";
+const FLAPPY: &str = "app::tests$flappy";
+
+fn flips(record: &FlipRecord, test: &str) -> u32 {
+    record.tests[test].flips
+}
+
+#[test]
+fn only_alternating_outcomes_on_one_commit_count_as_flips() {
+    let mut record = FlipRecord::default();
+    for outcome in [
+        TestOutcome::Passed,
+        TestOutcome::Failed,
+        TestOutcome::Flaky,
+        TestOutcome::Failed,
+    ] {
+        record = observe_flips(&record, "c1", &[result(FLAPPY, outcome)]);
+    }
+    assert_eq!(flips(&record, FLAPPY), 3);
+
+    let ignored = observe_flips(&record, "c1", &[result(FLAPPY, TestOutcome::Ignored)]);
+    assert_eq!(ignored, record);
+
+    let repeated = observe_flips(&record, "c1", &[result(FLAPPY, TestOutcome::Failed)]);
+    assert_eq!(flips(&repeated, FLAPPY), 0);
+
+    let changed = observe_flips(&record, "c2", &[result(FLAPPY, TestOutcome::Passed)]);
+    assert_eq!(
+        changed.tests[FLAPPY],
+        Observation {
+            commit: "c2".into(),
+            passed: true,
+            flips: 0,
+        }
+    );
+}
+
+fn committed(quarantine: Option<Quarantine>) -> StatusFile {
+    let mut flappy = TestEntry::new(TestState::Passing);
+    flappy.quarantine = quarantine;
+    StatusFile::new(
+        [
+            (GATEKEEPER.to_string(), TestEntry::new(TestState::Passing)),
+            (FLAPPY.to_string(), flappy),
+        ]
+        .into_iter()
+        .collect(),
+    )
+}
+
+fn run(
+    committed: &StatusFile,
+    instructions: &WorkingTreeInstructions,
+    outcome: TestOutcome,
+) -> EvalResult {
+    evaluate(
+        &committed.tracked_status(),
+        instructions,
+        &[
+            result(GATEKEEPER, TestOutcome::Passed),
+            result(FLAPPY, outcome),
+        ],
+        &[],
+    )
+}
+
+fn regressions(result: &EvalResult) -> Vec<&str> {
+    result
+        .violations
+        .iter()
+        .filter_map(|v| match v {
+            Violation::Regression { test } => Some(test.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn a_test_that_flipped_enough_is_quarantined_and_its_failure_tolerated() {
+    let mut result = run(
+        &committed(None),
+        &WorkingTreeInstructions::default(),
+        TestOutcome::Failed,
+    );
+    assert_eq!(regressions(&result), [FLAPPY]);
+    let mut record = FlipRecord::default();
+    record.tests.insert(
+        FLAPPY.into(),
+        Observation {
+            commit: "c1".into(),
+            passed: false,
+            flips: 3,
+        },
+    );
+
+    let mut not_yet = result.clone();
+    quarantine_flaky_tests(&mut not_yet, &record, 4);
+    assert_eq!(regressions(&not_yet), [FLAPPY]);
+
+    quarantine_flaky_tests(&mut result, &record, 3);
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(
+        result.updated.tests[FLAPPY].quarantine,
+        Some(Quarantine {
+            flips: 3,
+            commit: Some("c1".into()),
+        })
+    );
+    assert!(matches!(
+        result.warnings.as_slice(),
+        [
+            Warning::Quarantined { flips: 3, .. },
+            Warning::QuarantinedTestFailed { .. }
+        ]
+    ));
+    let text = format_report(&result);
+    assert!(
+        text.contains("quarantined as flaky after 3 flips"),
+        "{text}"
+    );
+    assert!(text.contains("cargo ratchet unquarantine"), "{text}");
+}
+
+#[test]
+fn quarantined_failures_are_warnings_until_unquarantined() {
+    let quarantine = Quarantine {
+        flips: 3,
+        commit: None,
+    };
+    let quarantined = committed(Some(quarantine.clone()));
+    let instructions = quarantined.working_tree_instructions();
+
+    let failed = run(&quarantined, &instructions, TestOutcome::Failed);
+    assert!(failed.violations.is_empty(), "{:?}", failed.violations);
+    assert!(matches!(
+        failed.warnings.as_slice(),
+        [Warning::QuarantinedTestFailed { test }] if test == FLAPPY
+    ));
+    assert_eq!(failed.updated.tests[FLAPPY].quarantine, Some(quarantine));
+
+    let passed = run(&quarantined, &instructions, TestOutcome::Passed);
+    assert!(passed.updated.tests[FLAPPY].quarantine.is_some());
+
+    // `unquarantine` in the working tree lifts it before it is committed.
+    let lifted = committed(None).working_tree_instructions();
+    let unquarantined = run(&quarantined, &lifted, TestOutcome::Failed);
+    assert_eq!(regressions(&unquarantined), [FLAPPY]);
+    assert_eq!(unquarantined.updated.tests[FLAPPY].quarantine, None);
+}
+
+#[test]
+fn a_quarantine_added_in_the_working_tree_is_ignored() {
+    let hand_edited = committed(Some(Quarantine {
+        flips: 3,
+        commit: None,
+    }));
+    let result = run(
+        &committed(None),
+        &hand_edited.working_tree_instructions(),
+        TestOutcome::Failed,
+    );
+    assert_eq!(regressions(&result), [FLAPPY]);
+    assert_eq!(result.updated.tests[FLAPPY].quarantine, None);
+}
+
+fn set_outcome(path: &Path, flappy: &str) {
+    fs::write(
+        path.join("results.txt"),
+        format!("test {GATEKEEPER} ... ok\ntest {FLAPPY} ... {flappy}\n"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn a_flip_flopping_test_is_quarantined_until_unquarantined() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\nquarantine-after = 2\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    // The results stand in for a test run, not for source changes.
+    fs::write(path.join(".gitignore"), "results.txt\n").unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    set_outcome(path, "FAILED");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add flappy test"]);
+    set_outcome(path, "ok");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Make flappy pass"]);
+
+    // Rerunning the same commit: pass, fail (one flip), pass (two flips).
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    set_outcome(path, "FAILED");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    set_outcome(path, "ok");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("quarantined as flaky after 2 flips"), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[FLAPPY].quarantine.as_ref().unwrap().flips, 2);
+
+    set_outcome(path, "FAILED");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("Quarantined test failed (tolerated)"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["unquarantine", FLAPPY]);
+    assert_eq!(code, Some(0), "{out}");
+    let (code, out) = run_ratchet(path, &["unquarantine", FLAPPY]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("is not quarantined"), "{out}");
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains(FLAPPY), "{out}");
+    dir.pass();
+}
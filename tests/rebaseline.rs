@@ -0,0 +1,208 @@
+// tests/rebaseline.rs
+//
+// Story 32: An explicit global baseline commit — set at init or moved
+// forward with `rebaseline` — grandfathers everything before it.
+
+mod common;
+
+use common::{TestDir, git, snapshot};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tdd_ratchet::audit::{AuditRecord, format_audit};
+use tdd_ratchet::history::{
+    HistoryViolation, check_history_snapshots, check_history_snapshots_from, resolve_baseline,
+};
+use tdd_ratchet::ratchet::{Violation, evaluate};
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::schema::validate_status_json;
+use tdd_ratchet::status::{
+    StatusFile, TestEntry, TestState, TrackedStatus, WorkingTreeInstructions,
+};
+
+fn status(tests: &[&str], baseline: Option<&str>) -> StatusFile {
+    let mut status = StatusFile::new(
+        tests
+            .iter()
+            .map(|n| (n.to_string(), TestEntry::new(TestState::Passing)))
+            .collect(),
+    );
+    status.baseline = baseline.map(str::to_string);
+    status
+}
+
+fn skipped(violations: &[HistoryViolation]) -> Vec<(&str, &str)> {
+    violations
+        .iter()
+        .map(|v| match v {
+            HistoryViolation::SkippedPending { test, commit } => (test.as_str(), commit.as_str()),
+        })
+        .collect()
+}
+
+fn rev_parse(dir: &Path, rev: &str) -> String {
+    let out = Command::new("git")
+        .args(["rev-parse", rev])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8(out.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn baseline_grandfathers_history_before_it() {
+    let snapshots = vec![
+        snapshot("c1", status(&["a"], None)),
+        snapshot("c2", status(&["a", "b"], None)),
+        snapshot("c3", status(&["a", "b", "c"], Some("c2"))),
+    ];
+
+    assert_eq!(
+        skipped(&check_history_snapshots(&snapshots)),
+        vec![("c", "c3")]
+    );
+    assert_eq!(
+        skipped(&check_history_snapshots_from(&snapshots, None)),
+        vec![("b", "c2"), ("c", "c3")]
+    );
+}
+
+#[test]
+fn baseline_outside_history_has_no_effect() {
+    let snapshots = vec![
+        snapshot("c1", status(&["a"], None)),
+        snapshot("c2", status(&["a", "b"], Some("unknown"))),
+    ];
+
+    assert_eq!(
+        skipped(&check_history_snapshots(&snapshots)),
+        vec![("b", "c2")]
+    );
+}
+
+#[test]
+fn committed_baseline_applies_and_is_preserved() {
+    let gatekeeper = "tdd_ratchet_gatekeeper";
+    let snapshots = vec![
+        snapshot("c1", status(&[gatekeeper], None)),
+        snapshot("c2", status(&[gatekeeper, "b"], None)),
+        snapshot("c3", status(&[gatekeeper, "b"], Some("c2"))),
+    ];
+    let results: Vec<TestResult> = [gatekeeper, "b"]
+        .iter()
+        .map(|name| TestResult {
+            name: name.to_string(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        })
+        .collect();
+
+    let result = evaluate(
+        &TrackedStatus::new(snapshots[2].status.tests.clone()),
+        &WorkingTreeInstructions::default(),
+        &results,
+        &snapshots,
+    );
+
+    assert!(
+        !result
+            .violations
+            .iter()
+            .any(|v| matches!(v, Violation::SkippedPending { .. })),
+        "{:?}",
+        result.violations
+    );
+    assert_eq!(result.updated.baseline.as_deref(), Some("c2"));
+}
+
+#[test]
+fn working_tree_baseline_has_no_effect_until_committed() {
+    let gatekeeper = "tdd_ratchet_gatekeeper";
+    let snapshots = vec![
+        snapshot("c1", status(&[gatekeeper], None)),
+        snapshot("c2", status(&[gatekeeper, "b"], None)),
+    ];
+    // A baseline edited into the working tree, by hand or by `rebaseline`.
+    let working_tree = status(&[gatekeeper, "b"], Some("c2"));
+    let results: Vec<TestResult> = [gatekeeper, "b"]
+        .iter()
+        .map(|name| TestResult {
+            name: name.to_string(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        })
+        .collect();
+
+    let result = evaluate(
+        &TrackedStatus::new(snapshots[1].status.tests.clone()),
+        &working_tree.working_tree_instructions(),
+        &results,
+        &snapshots,
+    );
+
+    assert!(
+        result
+            .violations
+            .iter()
+            .any(|v| matches!(v, Violation::SkippedPending { test, .. } if test == "b")),
+        "{:?}",
+        result.violations
+    );
+    assert_eq!(result.updated.baseline, None);
+}
+
+#[test]
+fn baseline_round_trips_and_validates() {
+    let commit = "0123456789abcdef0123456789abcdef01234567";
+    let dir = TestDir::new();
+    let path = dir.path().join(".test-status.json");
+    status(&["a"], Some(commit)).save(&path).unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert_eq!(validate_status_json(&contents), vec![]);
+    let loaded = StatusFile::load(&path).unwrap();
+    assert_eq!(loaded.baseline.as_deref(), Some(commit));
+    dir.pass();
+}
+
+#[test]
+fn resolve_baseline_accepts_ancestors_of_head_only() {
+    let dir = TestDir::new();
+    git(dir.path(), &["init"]);
+    git(dir.path(), &["config", "user.email", "test@test.com"]);
+    git(dir.path(), &["config", "user.name", "Test"]);
+    git(dir.path(), &["commit", "--allow-empty", "-m", "first"]);
+    git(dir.path(), &["commit", "--allow-empty", "-m", "second"]);
+    git(dir.path(), &["branch", "side", "HEAD~1"]);
+    git(dir.path(), &["checkout", "side"]);
+    git(dir.path(), &["commit", "--allow-empty", "-m", "side"]);
+    git(dir.path(), &["checkout", "-"]);
+
+    assert_eq!(
+        resolve_baseline(dir.path(), "HEAD~1").unwrap(),
+        rev_parse(dir.path(), "HEAD~1")
+    );
+    assert_eq!(
+        resolve_baseline(dir.path(), "HEAD").unwrap(),
+        rev_parse(dir.path(), "HEAD")
+    );
+    let err = resolve_baseline(dir.path(), "side").unwrap_err();
+    assert!(err.to_string().contains("not an ancestor of HEAD"), "{err}");
+    assert!(resolve_baseline(dir.path(), "no-such-rev").is_err());
+    dir.pass();
+}
+
+#[test]
+fn rebaseline_is_shown_in_audit() {
+    let text = format_audit(&[AuditRecord::Rebaseline {
+        recorded_at: 0,
+        commit: "c2bbbbbbbb".into(),
+        previous: None,
+    }]);
+    assert_eq!(
+        text,
+        "1970-01-01 rebaseline c2bbbbbb (was first snapshot)\n"
+    );
+}
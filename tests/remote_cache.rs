@@ -0,0 +1,379 @@
+// tests/remote_cache.rs
+//
+// Story 91: with `[cache.remote]` configured, cached results are shared
+// through a remote store, so CI jobs and retries on the same commit reuse
+// the results another machine already produced.
+
+mod common;
+
+use common::{TestDir, result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RemoteCacheConfig};
+use tdd_ratchet::result_cache::{
+    CacheLookup, CachedBinary, RemoteCache, ResultCache, remote_key, remote_uploads, sign_object,
+};
+use tdd_ratchet::runner::{TestOutcome, TestResult};
+
+fn entry(fingerprint: &str, names: &[&str]) -> CachedBinary {
+    CachedBinary {
+        fingerprint: fingerprint.to_string(),
+        results: names
+            .iter()
+            .map(|name| result(name, TestOutcome::Passed))
+            .collect(),
+    }
+}
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn remote(toml: &str) -> RemoteCache {
+    let config = RatchetConfig::parse_from_str(toml, Path::new("ratchet.toml")).unwrap();
+    config
+        .cache
+        .remote
+        .expect("remote cache configured")
+        .remote()
+}
+
+#[test]
+fn remote_kinds_map_to_get_and_put_commands() {
+    let s3 = remote("[cache.remote]\nkind = \"s3\"\nurl = \"s3://ci/ratchet/\"\n");
+    assert_eq!(
+        s3.get,
+        strings(&["aws", "s3", "cp", "--quiet", "s3://ci/ratchet/{key}", "-"])
+    );
+    assert_eq!(
+        s3.put,
+        strings(&["aws", "s3", "cp", "--quiet", "-", "s3://ci/ratchet/{key}"])
+    );
+
+    let gcs = remote("[cache.remote]\nkind = \"gcs\"\nurl = \"gs://ci\"\n");
+    assert_eq!(
+        gcs.get,
+        strings(&["gcloud", "storage", "cat", "gs://ci/{key}"])
+    );
+
+    let http = remote("[cache.remote]\nkind = \"http\"\nurl = \"https://cache.example\"\n");
+    assert_eq!(
+        http.get,
+        strings(&["curl", "-fsS", "https://cache.example/{key}"])
+    );
+    assert_eq!(
+        http.put,
+        strings(&[
+            "curl",
+            "-fsS",
+            "-X",
+            "PUT",
+            "--data-binary",
+            "@-",
+            "https://cache.example/{key}"
+        ])
+    );
+
+    // Cargo sets `CARGO_PKG_NAME` for the tests it runs.
+    let authorized = remote(
+        "[cache.remote]\nkind = \"http\"\nurl = \"https://cache.example\"\ntoken-env = \"CARGO_PKG_NAME\"\n",
+    );
+    assert_eq!(
+        authorized.get,
+        strings(&["curl", "-fsS", "-H", "@-", "https://cache.example/{key}"])
+    );
+    assert_eq!(
+        authorized.put,
+        strings(&[
+            "curl",
+            "-fsS",
+            "-X",
+            "PUT",
+            "-H",
+            "@-",
+            "--data-binary",
+            "@{file}",
+            "https://cache.example/{key}"
+        ])
+    );
+    assert_eq!(
+        authorized.secret.as_deref(),
+        Some("Authorization: Bearer tdd-ratchet\n")
+    );
+
+    let command = remote(
+        "[cache.remote]\nkind = \"command\"\n\
+         get = [\"fetch\", \"{key}\"]\nput = [\"store\", \"{key}\"]\n",
+    );
+    assert_eq!(
+        command,
+        RemoteCache {
+            get: strings(&["fetch", "{key}"]),
+            put: strings(&["store", "{key}"]),
+            secret: None,
+            signing_key: None,
+        }
+    );
+
+    let unknown = RatchetConfig::parse_from_str(
+        "[cache.remote]\nkind = \"ftp\"\nurl = \"ftp://x\"\n",
+        Path::new("ratchet.toml"),
+    );
+    assert!(unknown.is_err());
+    assert_eq!(
+        RatchetConfig::parse_from_str("", Path::new("ratchet.toml"))
+            .unwrap()
+            .cache
+            .remote,
+        None::<RemoteCacheConfig>
+    );
+}
+
+#[test]
+fn packages_that_ran_are_uploaded_whole() {
+    let lookup = CacheLookup {
+        fingerprints: [
+            ("engine".to_string(), "e1".to_string()),
+            ("web".to_string(), "w2".to_string()),
+        ]
+        .into(),
+        entries: BTreeMap::new(),
+        reused: [
+            (
+                "engine".to_string(),
+                vec![result("engine$parse", TestOutcome::Passed)],
+            ),
+            (
+                "web::api".to_string(),
+                vec![result("web::api$get", TestOutcome::Passed)],
+            ),
+        ]
+        .into(),
+    };
+    let entries: BTreeMap<String, CachedBinary> = [
+        ("engine".to_string(), entry("e1", &["engine$parse"])),
+        ("web::api".to_string(), entry("w2", &["web::api$get"])),
+        (
+            "web::render".to_string(),
+            entry("w2", &["web::render$page"]),
+        ),
+    ]
+    .into();
+
+    let uploads = remote_uploads(&lookup, &entries);
+
+    assert_eq!(
+        uploads.keys().collect::<Vec<_>>(),
+        [&("web".to_string(), "w2".to_string())]
+    );
+    let binaries = &uploads[&("web".to_string(), "w2".to_string())];
+    assert_eq!(
+        binaries.keys().collect::<Vec<_>>(),
+        ["web::api", "web::render"]
+    );
+    assert_eq!(remote_key("web", "w2"), "web-w2.json");
+}
+
+fn write_workspace(path: &Path) {
+    fs::write(
+        path.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"engine\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(path.join("engine/src")).unwrap();
+    fs::write(
+        path.join("engine/Cargo.toml"),
+        "[package]\nname = \"engine\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    fs::write(path.join("engine/src/lib.rs"), "").unwrap();
+}
+
+#[test]
+fn another_checkout_reuses_results_from_the_remote_store() {
+    let dir = TestDir::new();
+    let store = dir.path().join("store");
+    fs::create_dir_all(&store).unwrap();
+    let store = store.to_str().unwrap();
+    let remote = RemoteCache {
+        get: strings(&["cat", &format!("{store}/{{key}}")]),
+        put: vec!["sh".into(), "-c".into(), format!("cat > '{store}/{{key}}'")],
+        secret: None,
+        signing_key: None,
+    };
+    let first = dir.path().join("first");
+    let second = dir.path().join("second");
+    for checkout in [&first, &second] {
+        fs::create_dir_all(checkout).unwrap();
+        write_workspace(checkout);
+    }
+
+    let cache = ResultCache::new(&first).with_remote(remote.clone());
+    let lookup = cache.lookup().unwrap();
+    assert!(lookup.reused.is_empty());
+    cache
+        .store(
+            &lookup,
+            &[
+                result("engine$parse", TestOutcome::Passed),
+                result(
+                    "engine::gatekeeper$tdd_ratchet_gatekeeper",
+                    TestOutcome::Passed,
+                ),
+            ],
+        )
+        .unwrap();
+    assert_eq!(fs::read_dir(dir.path().join("store")).unwrap().count(), 1);
+
+    let cache = ResultCache::new(&second).with_remote(remote);
+    let lookup = cache.lookup().unwrap();
+    assert_eq!(
+        lookup.reused["engine"],
+        [result("engine$parse", TestOutcome::Passed)]
+    );
+    assert!(lookup.reused["engine::gatekeeper"].is_empty());
+
+    fs::write(second.join("engine/src/lib.rs"), "pub fn f() {}").unwrap();
+    assert!(cache.lookup().unwrap().reused.is_empty());
+    dir.pass();
+}
+
+#[test]
+fn a_secret_is_sent_on_stdin_and_the_object_through_a_file() {
+    let dir = TestDir::new();
+    let store = dir.path().join("store");
+    fs::create_dir_all(&store).unwrap();
+    let store = store.to_str().unwrap();
+    let remote = RemoteCache {
+        get: vec![
+            "sh".into(),
+            "-c".into(),
+            format!("cat > '{store}/get-secret'; cat '{store}/{{key}}'"),
+        ],
+        put: vec![
+            "sh".into(),
+            "-c".into(),
+            format!(
+                "cat > '{store}/put-secret'; stat -c %a '{{file}}' > '{store}/mode'; \
+                 cp '{{file}}' '{store}/{{key}}'"
+            ),
+        ],
+        secret: Some("Authorization: Bearer s3cret\n".into()),
+        signing_key: None,
+    };
+    let first = dir.path().join("first");
+    let second = dir.path().join("second");
+    for checkout in [&first, &second] {
+        fs::create_dir_all(checkout).unwrap();
+        write_workspace(checkout);
+    }
+
+    let cache = ResultCache::new(&first).with_remote(remote.clone());
+    let lookup = cache.lookup().unwrap();
+    cache
+        .store(&lookup, &[result("engine$parse", TestOutcome::Passed)])
+        .unwrap();
+    let cache = ResultCache::new(&second).with_remote(remote);
+    let lookup = cache.lookup().unwrap();
+
+    assert_eq!(
+        lookup.reused["engine"],
+        [result("engine$parse", TestOutcome::Passed)]
+    );
+    for secret in ["put-secret", "get-secret"] {
+        assert_eq!(
+            fs::read_to_string(dir.path().join("store").join(secret)).unwrap(),
+            "Authorization: Bearer s3cret\n"
+        );
+    }
+    assert_eq!(
+        fs::read_to_string(dir.path().join("store/mode")).unwrap(),
+        "600\n",
+        "only this user can read the object on its way to the store"
+    );
+    dir.pass();
+}
+
+#[test]
+fn a_signing_key_rejects_objects_it_did_not_sign() {
+    let config = RatchetConfig::parse_from_str(
+        "[cache]\nsigning-key-env = \"CARGO_PKG_NAME\"\n\n\
+         [cache.remote]\nkind = \"s3\"\nurl = \"s3://ci\"\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(
+        config.cache.remote_cache().unwrap().signing_key.as_deref(),
+        Some("tdd-ratchet")
+    );
+
+    let dir = TestDir::new();
+    let store = dir.path().join("store");
+    fs::create_dir_all(&store).unwrap();
+    let store_arg = store.to_str().unwrap();
+    let remote = |signing_key: &str| RemoteCache {
+        get: strings(&["cat", &format!("{store_arg}/{{key}}")]),
+        put: vec![
+            "sh".into(),
+            "-c".into(),
+            format!("cat > '{store_arg}/{{key}}'"),
+        ],
+        secret: None,
+        signing_key: Some(signing_key.to_string()),
+    };
+    let first = dir.path().join("first");
+    let second = dir.path().join("second");
+    for checkout in [&first, &second] {
+        fs::create_dir_all(checkout).unwrap();
+        write_workspace(checkout);
+    }
+
+    let cache = ResultCache::new(&first).with_remote(remote("k1"));
+    let lookup = cache.lookup().unwrap();
+    cache
+        .store(&lookup, &[result("engine$parse", TestOutcome::Passed)])
+        .unwrap();
+
+    let cache = ResultCache::new(&second).with_remote(remote("k1"));
+    assert_eq!(
+        cache.lookup().unwrap().reused["engine"],
+        [result("engine$parse", TestOutcome::Passed)]
+    );
+    let cache = ResultCache::new(&second).with_remote(remote("k2"));
+    assert!(cache.lookup().unwrap().reused.is_empty());
+
+    // An object rewritten without the key no longer verifies.
+    let object = fs::read_dir(&store)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let forged = fs::read_to_string(&object)
+        .unwrap()
+        .replace("passed", "failed");
+    fs::write(&object, forged).unwrap();
+    let cache = ResultCache::new(&second).with_remote(remote("k1"));
+    assert!(cache.lookup().unwrap().reused.is_empty());
+    dir.pass();
+}
+
+#[test]
+fn signatures_cover_the_object_name_and_results() {
+    let results: BTreeMap<String, Vec<TestResult>> = [(
+        "engine".to_string(),
+        vec![result("engine$parse", TestOutcome::Passed)],
+    )]
+    .into();
+
+    let signature = sign_object("k1", "engine-e1.json", &results);
+    assert_eq!(signature.len(), 64);
+    assert_eq!(signature, sign_object("k1", "engine-e1.json", &results));
+    assert_ne!(signature, sign_object("k2", "engine-e1.json", &results));
+    assert_ne!(signature, sign_object("k1", "engine-e2.json", &results));
+    assert_ne!(
+        signature,
+        sign_object("k1", "engine-e1.json", &BTreeMap::new())
+    );
+}
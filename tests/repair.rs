@@ -0,0 +1,160 @@
+// tests/repair.rs
+//
+// Story 33: After an interactive rebase rewrites history, `repair` remaps
+// unreachable baselines and recovers tests that went through pending in the
+// pre-rebase commits.
+
+mod common;
+
+use common::{TestDir, commit_status, git, snapshot, status};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tdd_ratchet::repair::{
+    RepairChange, RepairEvidence, apply_repair, gather_repair_evidence, plan_repair,
+};
+use tdd_ratchet::status::TestState;
+
+fn evidence(
+    reachable: &[&str],
+    rewritten: &[(&str, &str)],
+    went_pending: &[&str],
+) -> RepairEvidence {
+    RepairEvidence {
+        reachable: reachable.iter().map(|c| c.to_string()).collect(),
+        rewritten: rewritten
+            .iter()
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect::<BTreeMap<_, _>>(),
+        went_pending: went_pending
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<BTreeSet<_>>(),
+    }
+}
+
+fn rev_parse(dir: &Path, rev: &str) -> String {
+    let out = Command::new("git")
+        .args(["rev-parse", rev])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8(out.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn unreachable_baselines_are_remapped_or_dropped() {
+    let mut current = status(&[("a", TestState::Passing), ("b", TestState::Passing)]);
+    current.baseline = Some("old-global".into());
+    current.tests.get_mut("a").unwrap().baseline = Some("old-a".into());
+    current.tests.get_mut("b").unwrap().baseline = Some("old-b".into());
+    let snapshots = vec![
+        snapshot("c1", status(&[("a", TestState::Passing)])),
+        snapshot("c2", current.clone()),
+    ];
+
+    let changes = plan_repair(
+        &current,
+        &snapshots,
+        &evidence(&["c1", "c2"], &[("old-a", "c1")], &[]),
+    );
+
+    assert_eq!(
+        changes,
+        vec![
+            RepairChange::BaselineDropped {
+                test: None,
+                from: "old-global".into(),
+            },
+            RepairChange::BaselineRemapped {
+                test: Some("a".into()),
+                from: "old-a".into(),
+                to: "c1".into(),
+            },
+            RepairChange::BaselineRemapped {
+                test: Some("b".into()),
+                from: "old-b".into(),
+                to: "c2".into(),
+            },
+        ]
+    );
+
+    apply_repair(&mut current, &changes);
+    assert_eq!(current.baseline, None);
+    assert_eq!(current.tests["a"].baseline(), Some("c1"));
+    assert_eq!(current.tests["b"].baseline(), Some("c2"));
+}
+
+#[test]
+fn only_tests_pending_before_the_rewrite_are_recovered() {
+    let current = status(&[
+        ("squashed", TestState::Passing),
+        ("sneaky", TestState::Passing),
+    ]);
+    let snapshots = vec![snapshot("c1", status(&[])), snapshot("c2", current.clone())];
+
+    let changes = plan_repair(
+        &current,
+        &snapshots,
+        &evidence(&["c1", "c2"], &[], &["squashed"]),
+    );
+
+    assert_eq!(
+        changes,
+        vec![RepairChange::PendingRecovered {
+            test: "squashed".into(),
+            commit: "c2".into(),
+        }]
+    );
+    assert!(
+        changes[0].to_string().contains("grandfathered at c2"),
+        "{}",
+        changes[0]
+    );
+}
+
+#[test]
+fn reachable_history_needs_no_repair() {
+    let mut current = status(&[("a", TestState::Passing)]);
+    current.tests.get_mut("a").unwrap().baseline = Some("c1".into());
+    let snapshots = vec![snapshot("c1", current.clone())];
+
+    assert_eq!(
+        plan_repair(&current, &snapshots, &evidence(&["c1"], &[], &[])),
+        vec![]
+    );
+}
+
+#[test]
+fn reflog_evidence_covers_squashed_and_picked_commits() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    commit_status(path, r#"{"tests":{}}"#, "Base");
+    let base = rev_parse(path, "HEAD");
+    commit_status(path, r#"{"tests":{"t":"pending"}}"#, "Add failing test");
+    commit_status(path, r#"{"tests":{"t":"passing"}}"#, "Make it pass");
+    fs::write(path.join("notes.txt"), "x").unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add notes"]);
+    let notes = rev_parse(path, "HEAD");
+
+    // Squash the two test commits, then re-pick the notes commit on top.
+    git(path, &["reset", "--hard", "HEAD~1"]);
+    git(path, &["reset", "--soft", &base]);
+    git(path, &["commit", "-m", "Add feature"]);
+    git(path, &["cherry-pick", &notes]);
+    let picked = rev_parse(path, "HEAD");
+
+    let evidence = gather_repair_evidence(path).unwrap();
+
+    assert!(evidence.reachable.contains(&base));
+    assert!(evidence.reachable.contains(&picked));
+    assert!(!evidence.reachable.contains(&notes));
+    assert_eq!(evidence.rewritten.get(&notes), Some(&picked));
+    assert!(evidence.went_pending.contains("t"), "{evidence:?}");
+    dir.pass();
+}
@@ -0,0 +1,165 @@
+// tests/replay.rs
+//
+// Story 105: auditing an existing repository, `cargo ratchet replay A..B`
+// re-runs the history rules as they applied at each commit in the range and
+// reports the commit where each violation first appeared.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, commit_status, git, run_ratchet_split, status};
+use std::collections::BTreeSet;
+use std::path::Path;
+use tdd_ratchet::config::RulesConfig;
+use tdd_ratchet::grandfather::GrandfatherPatterns;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::ratchet::Violation;
+use tdd_ratchet::replay::{format_replay, replay};
+use tdd_ratchet::status::TestState;
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+
+fn snapshot(commit: &str, tests: &[(&str, TestState)]) -> HistorySnapshot {
+    HistorySnapshot {
+        info: CommitInfo {
+            summary: format!("commit {commit}"),
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status(tests))
+    }
+}
+
+fn range(commits: &[&str]) -> BTreeSet<String> {
+    commits.iter().map(|commit| commit.to_string()).collect()
+}
+
+#[test]
+fn violations_are_reported_where_they_first_appear_and_go_away() {
+    use TestState::{Passing, Pending};
+    let snapshots = vec![
+        snapshot("c1", &[(GATEKEEPER, Passing)]),
+        snapshot("c2", &[(GATEKEEPER, Passing), ("app$parses", Pending)]),
+        snapshot(
+            "c3",
+            &[
+                (GATEKEEPER, Passing),
+                ("app$parses", Passing),
+                ("app$skipped", Passing),
+            ],
+        ),
+        snapshot("c4", &[(GATEKEEPER, Passing), ("app$parses", Passing)]),
+    ];
+
+    let replay = replay(
+        &snapshots,
+        &range(&["c2", "c3", "c4"]),
+        &GrandfatherPatterns::default(),
+        &RulesConfig::default(),
+    );
+
+    assert_eq!(replay.commits, 3);
+    assert!(replay.before.is_empty());
+    assert_eq!(replay.steps.len(), 2, "{:?}", replay.steps);
+    assert_eq!(replay.steps[0].commit, "c3");
+    assert!(matches!(
+        replay.steps[0].introduced.as_slice(),
+        [Violation::SkippedPending { test, commit }] if test == "app$skipped" && commit == "c3"
+    ));
+    assert_eq!(replay.steps[1].commit, "c4");
+    assert_eq!(replay.steps[1].resolved.len(), 1);
+    assert!(replay.after.is_empty());
+
+    let text = format_replay("c1..c4", &replay);
+    assert!(
+        text.starts_with("tdd-ratchet replay c1..c4: 3 commits"),
+        "{text}"
+    );
+    assert!(text.contains("  + RATCHET004 app$skipped"), "{text}");
+    assert!(text.contains("  - RATCHET004 app$skipped"), "{text}");
+    assert!(
+        text.contains("1 introduced in the range, 0 present at its end"),
+        "{text}"
+    );
+}
+
+#[test]
+fn violations_from_before_the_range_are_listed_separately() {
+    use TestState::{Passing, Pending};
+    let snapshots = vec![
+        snapshot("c1", &[(GATEKEEPER, Passing)]),
+        snapshot("c2", &[(GATEKEEPER, Passing), ("app$skipped", Passing)]),
+        snapshot(
+            "c3",
+            &[
+                (GATEKEEPER, Passing),
+                ("app$skipped", Passing),
+                ("app$parses", Pending),
+            ],
+        ),
+    ];
+
+    let replay = replay(
+        &snapshots,
+        &range(&["c3"]),
+        &GrandfatherPatterns::default(),
+        &RulesConfig::default(),
+    );
+
+    assert_eq!(replay.before.len(), 1);
+    assert!(replay.steps.is_empty());
+    assert_eq!(replay.after.len(), 1);
+    let text = format_replay("c2..c3", &replay);
+    assert!(
+        text.contains("Already present before the range (1):"),
+        "{text}"
+    );
+}
+
+fn commit_tests(dir: &Path, tests: &str, message: &str) {
+    commit_status(dir, &format!(r#"{{"tests":{{{tests}}}}}"#), message);
+}
+
+#[test]
+fn replay_command_prints_the_commit_that_introduced_a_violation() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    commit_tests(
+        path,
+        &format!(r#""{GATEKEEPER}":"passing""#),
+        "Adopt the ratchet",
+    );
+    git(path, &["tag", "adopted"]);
+    commit_tests(
+        path,
+        &format!(r#""{GATEKEEPER}":"passing","app$parses":"pending""#),
+        "Add a failing parser test",
+    );
+    commit_tests(
+        path,
+        &format!(r#""{GATEKEEPER}":"passing","app$parses":"passing","app$sneaky":"passing""#),
+        "Implement the parser",
+    );
+
+    let (code, stdout, stderr) = run_ratchet_split(path, &["replay", "adopted..HEAD"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    assert!(stdout.contains("2 commits"), "{stdout}");
+    assert!(stdout.contains("Test — Implement the parser"), "{stdout}");
+    assert!(stdout.contains("  + RATCHET004 app$sneaky"), "{stdout}");
+    assert!(!stdout.contains("app$parses"), "{stdout}");
+
+    let (code, stdout, _) = run_ratchet_split(path, &["replay", "adopted"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("1 introduced in the range"), "{stdout}");
+
+    let (code, _, stderr) = run_ratchet_split(path, &["replay", "nowhere..HEAD"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("cannot replay nowhere..HEAD"), "{stderr}");
+
+    let (code, _, stderr) = run_ratchet_split(path, &["replay"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("needs a commit range"), "{stderr}");
+    dir.pass();
+}
@@ -1,6 +1,7 @@
-use tdd_ratchet::errors::format_report;
+use std::collections::BTreeMap;
+use tdd_ratchet::errors::{format_markdown_report, format_report};
 use tdd_ratchet::ratchet::{EvalResult, Violation, Warning};
-use tdd_ratchet::status::{StatusFile, TestState};
+use tdd_ratchet::status::{StatusFile, TestState, TrackedStatus};
 
 const WHY_PREFIX: &str = "This project uses tdd-ratchet to enforce test-first discipline.";
 
@@ -12,6 +13,13 @@ fn report(violations: Vec<Violation>, warnings: Vec<Warning>) -> String {
         violations,
         warnings,
         updated,
+        failure_output: BTreeMap::new(),
+        locations: BTreeMap::new(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: BTreeMap::new(),
     })
 }
 
@@ -159,3 +167,74 @@ fn rename_warning_report_is_also_self_documenting() {
         ],
     );
 }
+
+#[test]
+fn markdown_report_has_counts_delta_violation_table_and_pending_list() {
+    let mut updated = StatusFile::empty();
+    updated.set_test_state("suite::passing_test", TestState::Passing);
+    updated.set_test_state("suite::wip|pipe", TestState::Pending);
+    let mut previous = TrackedStatus::empty();
+    previous.set_test_state("suite::passing_test", TestState::Pending);
+
+    let report = format_markdown_report(
+        &EvalResult {
+            violations: vec![Violation::Regression {
+                test: "suite::fragile_test".into(),
+            }],
+            warnings: Vec::new(),
+            updated,
+            failure_output: BTreeMap::new(),
+            locations: BTreeMap::new(),
+            since_last_run: None,
+            history_since: None,
+            infrastructure_retries: 0,
+            partial: None,
+            last_green: BTreeMap::new(),
+        },
+        &previous,
+    );
+
+    assert_contains_all(
+        &report,
+        &[
+            "## tdd-ratchet: ❌ 1 violation",
+            "| Before | 0 | 1 |",
+            "| After | 1 | 1 |",
+            "| Δ | +1 | 0 |",
+            "<details open><summary>Violations (1)</summary>",
+            "| Regression | `suite::fragile_test` | previously passing test now fails |",
+            "<details><summary>Pending tests (1)</summary>",
+            "- `suite::wip\\|pipe`",
+        ],
+    );
+}
+
+#[test]
+fn bounded_check_is_labeled_in_text_and_markdown_reports() {
+    let mut updated = StatusFile::empty();
+    updated.set_test_state("suite::passing_test", TestState::Passing);
+    let result = EvalResult {
+        violations: Vec::new(),
+        warnings: Vec::new(),
+        updated,
+        failure_output: BTreeMap::new(),
+        locations: BTreeMap::new(),
+        since_last_run: None,
+        history_since: Some("0123456789abcdef0123456789abcdef01234567".into()),
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: BTreeMap::new(),
+    };
+
+    assert_contains_all(
+        &format_report(&result),
+        &[
+            "tdd-ratchet: bounded check: history checked only after 01234567 (--since)",
+            "tdd-ratchet: ok (1 passing)",
+        ],
+    );
+    assert_contains_all(
+        &format_markdown_report(&result, &TrackedStatus::empty()),
+        &["> Bounded check: history checked only after `01234567` (`--since`)."],
+    );
+}
@@ -0,0 +1,251 @@
+// tests/result_cache.rs
+//
+// Story 90: with `[cache] enabled`, a test binary whose inputs (toolchain,
+// lockfile, its package's sources and those of the packages it depends on)
+// are unchanged is not rerun; its previous results are reused. `--no-cache`
+// bypasses the cache.
+
+mod common;
+
+use common::{TestDir, result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::affected::WorkspacePackage;
+use tdd_ratchet::config::RatchetConfig;
+use tdd_ratchet::result_cache::{
+    CacheLookup, CachedBinary, fingerprint_packages, package_fingerprints, reusable_binaries,
+    uncached_filterset, updated_entries,
+};
+use tdd_ratchet::runner::TestOutcome;
+
+const GATEKEEPER: &str = "web::gatekeeper$tdd_ratchet_gatekeeper";
+
+fn entry(fingerprint: &str, names: &[&str]) -> CachedBinary {
+    CachedBinary {
+        fingerprint: fingerprint.to_string(),
+        results: names
+            .iter()
+            .map(|name| result(name, TestOutcome::Passed))
+            .collect(),
+    }
+}
+
+fn fingerprints(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(package, fingerprint)| (package.to_string(), fingerprint.to_string()))
+        .collect()
+}
+
+fn package(name: &str, dir: &str, dependencies: &[&str]) -> WorkspacePackage {
+    WorkspacePackage {
+        name: name.to_string(),
+        dir: dir.to_string(),
+        dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+    }
+}
+
+#[test]
+fn binaries_with_an_unchanged_fingerprint_are_reused() {
+    let entries: BTreeMap<String, CachedBinary> = [
+        ("engine".to_string(), entry("e1", &["engine$parse"])),
+        (
+            "web::gatekeeper".to_string(),
+            entry("w1", &[GATEKEEPER, "web::gatekeeper$other"]),
+        ),
+        (
+            "web::render".to_string(),
+            entry("w0", &["web::render$page"]),
+        ),
+        ("gone".to_string(), entry("g1", &["gone$old"])),
+    ]
+    .into();
+
+    let reused = reusable_binaries(&entries, &fingerprints(&[("engine", "e1"), ("web", "w1")]));
+
+    let names: BTreeMap<&str, Vec<&str>> = reused
+        .iter()
+        .map(|(binary, results)| {
+            (
+                binary.as_str(),
+                results.iter().map(|r| r.name.as_str()).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        names,
+        BTreeMap::from([
+            ("engine", vec!["engine$parse"]),
+            ("web::gatekeeper", vec!["web::gatekeeper$other"]),
+        ])
+    );
+}
+
+#[test]
+fn the_cache_keeps_reused_binaries_and_stores_the_rest() {
+    let lookup = CacheLookup {
+        fingerprints: fingerprints(&[("engine", "e1"), ("web", "w2")]),
+        entries: [
+            ("engine".to_string(), entry("e1", &["engine$parse"])),
+            (
+                "web::render".to_string(),
+                entry("w1", &["web::render$page"]),
+            ),
+            ("gone".to_string(), entry("g1", &["gone$old"])),
+        ]
+        .into(),
+        reused: [(
+            "engine".to_string(),
+            vec![result("engine$parse", TestOutcome::Passed)],
+        )]
+        .into(),
+    };
+    let fresh = [
+        result(
+            "engine::gatekeeper$tdd_ratchet_gatekeeper",
+            TestOutcome::Passed,
+        ),
+        result("engine$tdd_ratchet_gatekeeper", TestOutcome::Passed),
+        result("web::render$page", TestOutcome::Passed),
+        result("web::render$layout", TestOutcome::Passed),
+        result("vendored::lib$unknown", TestOutcome::Passed),
+        result("pytest_case", TestOutcome::Passed),
+    ];
+
+    let entries = updated_entries(&lookup, &fresh);
+
+    assert_eq!(
+        entries,
+        BTreeMap::from([
+            ("engine".to_string(), entry("e1", &["engine$parse"])),
+            (
+                "engine::gatekeeper".to_string(),
+                entry("e1", &["engine::gatekeeper$tdd_ratchet_gatekeeper"])
+            ),
+            (
+                "web::render".to_string(),
+                entry("w2", &["web::render$page", "web::render$layout"])
+            ),
+        ])
+    );
+}
+
+#[test]
+fn nextest_skips_cached_binaries_but_runs_the_gatekeeper() {
+    assert_eq!(uncached_filterset(None, &[]), None);
+    assert_eq!(
+        uncached_filterset(Some("test(a)".into()), &[]),
+        Some("test(a)".into())
+    );
+    assert_eq!(
+        uncached_filterset(None, &["engine".into(), "web::render".into()]),
+        Some(
+            "not (binary_id(=engine) | binary_id(=web::render)) \
+             | test(/tdd_ratchet_gatekeeper$/)"
+                .into()
+        )
+    );
+}
+
+#[test]
+fn fingerprints_cover_workspace_dependencies() {
+    let packages = [
+        package("engine", "engine", &["serde"]),
+        package("web", "web", &["engine"]),
+        package("cli", "cli", &["web"]),
+        package("docs", "docs", &[]),
+    ];
+    let sources = |engine: &str, docs: &str| -> BTreeMap<String, String> {
+        fingerprints(&[
+            ("engine", engine),
+            ("web", "w"),
+            ("cli", "c"),
+            ("docs", docs),
+        ])
+    };
+
+    let before = fingerprint_packages("rustc 1.0", &packages, &sources("e1", "d1"));
+    let engine_changed = fingerprint_packages("rustc 1.0", &packages, &sources("e2", "d1"));
+    let docs_changed = fingerprint_packages("rustc 1.0", &packages, &sources("e1", "d2"));
+    let toolchain_changed = fingerprint_packages("rustc 1.1", &packages, &sources("e1", "d1"));
+
+    let changed = |after: &BTreeMap<String, String>| -> Vec<String> {
+        after
+            .iter()
+            .filter(|(name, fingerprint)| before[*name] != **fingerprint)
+            .map(|(name, _)| name.clone())
+            .collect()
+    };
+    assert_eq!(changed(&engine_changed), ["cli", "engine", "web"]);
+    assert_eq!(changed(&docs_changed), ["docs"]);
+    assert_eq!(
+        changed(&toolchain_changed),
+        ["cli", "docs", "engine", "web"]
+    );
+}
+
+fn write_workspace(path: &Path) {
+    fs::write(
+        path.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"engine\", \"web\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+    for (name, deps) in [
+        ("engine", ""),
+        ("web", "engine = { path = \"../engine\" }\n"),
+    ] {
+        fs::create_dir_all(path.join(name).join("src")).unwrap();
+        fs::write(
+            path.join(name).join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                 [dependencies]\n{deps}"
+            ),
+        )
+        .unwrap();
+        fs::write(path.join(name).join("src/lib.rs"), "").unwrap();
+    }
+}
+
+#[test]
+fn source_edits_change_the_fingerprint_of_their_package_and_dependents() {
+    let dir = TestDir::new();
+    write_workspace(dir.path());
+
+    let before = package_fingerprints(dir.path()).unwrap();
+    assert_eq!(before.keys().collect::<Vec<_>>(), ["engine", "web"]);
+
+    fs::write(dir.path().join("web/.test-status.json"), "{}").unwrap();
+    fs::create_dir_all(dir.path().join("web/target")).unwrap();
+    fs::write(dir.path().join("web/target/out"), "x").unwrap();
+    assert_eq!(package_fingerprints(dir.path()).unwrap(), before);
+
+    fs::write(dir.path().join("web/src/lib.rs"), "pub fn f() {}").unwrap();
+    let web_edited = package_fingerprints(dir.path()).unwrap();
+    assert_eq!(web_edited["engine"], before["engine"]);
+    assert_ne!(web_edited["web"], before["web"]);
+
+    fs::write(dir.path().join("engine/src/lib.rs"), "pub fn g() {}").unwrap();
+    let engine_edited = package_fingerprints(dir.path()).unwrap();
+    assert_ne!(engine_edited["engine"], web_edited["engine"]);
+    assert_ne!(engine_edited["web"], web_edited["web"]);
+    dir.pass();
+}
+
+#[test]
+fn the_cache_is_opt_in() {
+    let path = Path::new("ratchet.toml");
+    assert!(
+        !RatchetConfig::parse_from_str("", path)
+            .unwrap()
+            .cache
+            .enabled
+    );
+    assert!(
+        RatchetConfig::parse_from_str("[cache]\nenabled = true\n", path)
+            .unwrap()
+            .cache
+            .enabled
+    );
+}
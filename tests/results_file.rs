@@ -0,0 +1,109 @@
+// tests/results_file.rs
+//
+// Story 104: CI runs the tests once in a dedicated job and saves their
+// results; `cargo ratchet --results-file <FILE>` evaluates them later
+// without running any test.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet};
+use std::fs;
+use tdd_ratchet::error::{EvaluationError, RatchetError};
+use tdd_ratchet::partition::{PartialResults, Partition, load_results_file, save_partial_results};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{StatusFile, TestState};
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+const PARSER: &str = "app::tests$parses_headers";
+
+fn event(name: &str, event: &str) -> String {
+    format!(r#"{{"type":"test","event":"{event}","name":"{name}"}}"#) + "\n"
+}
+
+#[test]
+fn results_files_are_read_in_either_format() {
+    let dir = TestDir::new();
+    let stream = dir.path().join("nextest.jsonl");
+    fs::write(
+        &stream,
+        format!(
+            "{}{}{}{}",
+            event(GATEKEEPER, "started"),
+            event(GATEKEEPER, "ok"),
+            event(PARSER, "failed"),
+            event(PARSER, "ok"),
+        ),
+    )
+    .unwrap();
+    let results = load_results_file(&stream).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1].name, PARSER);
+    assert_eq!(results[1].outcome, TestOutcome::Flaky);
+
+    let emitted = dir.path().join("results.json");
+    let whole_run = PartialResults {
+        partition: None,
+        results: vec![result(GATEKEEPER, TestOutcome::Passed)],
+    };
+    save_partial_results(&emitted, &whole_run).unwrap();
+    assert_eq!(load_results_file(&emitted).unwrap(), whole_run.results);
+
+    let shard = PartialResults {
+        partition: Some(Partition { index: 1, count: 2 }),
+        ..whole_run
+    };
+    save_partial_results(&emitted, &shard).unwrap();
+    let err = load_results_file(&emitted).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            RatchetError::Evaluation(EvaluationError::MissingPartitions(_))
+        ),
+        "{err}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn results_file_is_evaluated_without_running_the_tests() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    // Running the configured runner would fail the run.
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"false\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        format!(r#"{{"tests":{{"{GATEKEEPER}":"passing"}}}}"#),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    fs::write(
+        path.join("nextest.jsonl"),
+        event(GATEKEEPER, "ok") + &event(PARSER, "failed"),
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &["--results-file", "nextest.jsonl"]);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[PARSER].state(), TestState::Pending);
+
+    fs::write(path.join("empty.jsonl"), "").unwrap();
+    let (code, out) = run_ratchet(path, &["--results-file", "empty.jsonl"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("no test results in empty.jsonl"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--results-file", "missing.jsonl"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("missing.jsonl"), "{out}");
+    dir.pass();
+}
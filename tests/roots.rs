@@ -0,0 +1,242 @@
+// tests/roots.rs
+//
+// Story 34: A monorepo configures several ratchet roots, each with its own
+// status file and history, and gets one aggregated report and exit code.
+
+mod common;
+
+use common::{TestDir, git};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tdd_ratchet::Ratchet;
+use tdd_ratchet::config::{RatchetConfig, RootConfig};
+use tdd_ratchet::errors::{RootResult, format_markdown_roots_report, format_roots_report};
+use tdd_ratchet::history::{GitHistory, HistoryProvider};
+use tdd_ratchet::ratchet::{EvalResult, Violation};
+use tdd_ratchet::runner::{RunnerError, TestOutcome, TestResult, TestRunner};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, TrackedStatus, WorkingTreeStatusFile};
+
+struct FixedResults(Vec<(&'static str, TestOutcome)>);
+
+impl TestRunner for FixedResults {
+    fn run(&self) -> Result<Vec<TestResult>, RunnerError> {
+        Ok(self
+            .0
+            .iter()
+            .map(|(name, outcome)| TestResult {
+                name: name.to_string(),
+                outcome: *outcome,
+                output: None,
+                exec_time_ms: None,
+            })
+            .collect())
+    }
+}
+
+fn write(dir: &Path, path: &str, contents: &str) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn root_result(root: &str, violations: Vec<Violation>) -> RootResult {
+    let tests = [(
+        "tdd_ratchet_gatekeeper".to_string(),
+        TestEntry::new(TestState::Passing),
+    )]
+    .into_iter()
+    .collect();
+    RootResult {
+        root: root.into(),
+        previous: TrackedStatus::new(tests),
+        result: EvalResult {
+            violations,
+            warnings: vec![],
+            updated: StatusFile::new(
+                [(
+                    "tdd_ratchet_gatekeeper".to_string(),
+                    TestEntry::new(TestState::Passing),
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            failure_output: Default::default(),
+            locations: Default::default(),
+            since_last_run: None,
+            history_since: None,
+            infrastructure_retries: 0,
+            partial: None,
+            last_green: Default::default(),
+        },
+    }
+}
+
+#[test]
+fn roots_parse_with_default_and_explicit_status_paths() {
+    let config = RatchetConfig::parse_from_str(
+        r#"
+[[roots]]
+path = "services/api"
+
+[[roots]]
+path = "web"
+status = "status/web.json"
+"#,
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.roots,
+        vec![
+            RootConfig {
+                path: "services/api".into(),
+                status: None,
+            },
+            RootConfig {
+                path: "web".into(),
+                status: Some("status/web.json".into()),
+            },
+        ]
+    );
+    assert_eq!(
+        config.roots[0].status_path(),
+        PathBuf::from("services/api/.test-status.json")
+    );
+    assert_eq!(
+        config.roots[1].status_path(),
+        PathBuf::from("status/web.json")
+    );
+}
+
+#[test]
+fn each_root_reads_its_own_status_history() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    write(
+        path,
+        "api/.test-status.json",
+        r#"{"tests":{"api_test":"pending"}}"#,
+    );
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add api root"]);
+    write(
+        path,
+        "web/.test-status.json",
+        r#"{"tests":{"web_test":"passing"}}"#,
+    );
+    write(
+        path,
+        "api/.test-status.json",
+        r#"{"tests":{"api_test":"passing"}}"#,
+    );
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add web root, promote api test"]);
+
+    let api = GitHistory::new(path).with_status_path("api/.test-status.json");
+    let web = GitHistory::new(path).with_status_path("web/.test-status.json");
+    let root = GitHistory::new(path);
+
+    let api_snapshots = api.snapshots().unwrap();
+    assert_eq!(api_snapshots.len(), 2);
+    assert_eq!(
+        api_snapshots[0].status.tests["api_test"].state(),
+        TestState::Pending
+    );
+    assert_eq!(web.snapshots().unwrap().len(), 1);
+    assert!(
+        web.head_status()
+            .unwrap()
+            .unwrap()
+            .tests
+            .contains_key("web_test")
+    );
+    assert!(root.snapshots().unwrap().is_empty());
+    assert!(root.head_status().unwrap().is_none());
+    dir.pass();
+}
+
+#[test]
+fn root_ratchet_saves_to_its_own_status_file() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    write(
+        path,
+        "api/.test-status.json",
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","api_test":"pending"}}"#,
+    );
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add api root"]);
+
+    let status_path = path.join("api/.test-status.json");
+    let ratchet = Ratchet::builder(path.join("api"))
+        .runner(FixedResults(vec![
+            ("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+            ("api_test", TestOutcome::Passed),
+        ]))
+        .history(GitHistory::new(path).with_status_path("api/.test-status.json"))
+        .status_store(WorkingTreeStatusFile::new(&status_path))
+        .build();
+
+    let result = ratchet.run().unwrap();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    let saved = StatusFile::load(&status_path).unwrap();
+    assert_eq!(saved.tests["api_test"].state(), TestState::Passing);
+    assert!(!path.join(".test-status.json").exists());
+    dir.pass();
+}
+
+#[test]
+fn aggregated_text_report_names_failing_roots() {
+    let roots = vec![
+        root_result("services/api", vec![]),
+        root_result(
+            "web",
+            vec![Violation::Regression {
+                test: "web_test".into(),
+            }],
+        ),
+    ];
+
+    let report = format_roots_report(&roots);
+
+    assert!(report.contains("═══ services/api ═══"), "{report}");
+    assert!(report.contains("═══ web ═══"), "{report}");
+    assert!(report.contains("web_test"), "{report}");
+    assert!(
+        report.ends_with("tdd-ratchet: 1 of 2 roots with violations (web)\n"),
+        "{report}"
+    );
+
+    let ok = format_roots_report(&roots[..1]);
+    assert!(ok.ends_with("tdd-ratchet: ok (1 root)\n"), "{ok}");
+}
+
+#[test]
+fn aggregated_markdown_report_nests_each_root() {
+    let roots = vec![
+        root_result("services/api", vec![]),
+        root_result(
+            "web",
+            vec![Violation::Regression {
+                test: "web_test".into(),
+            }],
+        ),
+    ];
+
+    let report = format_markdown_roots_report(&roots);
+
+    assert!(
+        report.starts_with("## tdd-ratchet: ❌ 1 of 2 roots with violations\n"),
+        "{report}"
+    );
+    assert!(report.contains("### `services/api`: ✅ ok"), "{report}");
+    assert!(report.contains("### `web`: ❌ 1 violation"), "{report}");
+}
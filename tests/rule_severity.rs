@@ -0,0 +1,173 @@
+// tests/rule_severity.rs
+//
+// Story 51: Each ratchet check is a named rule whose severity (error, warn,
+// or off) is set in `ratchet.toml` or with `--allow`/`--deny`. Only errors
+// fail the run.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, gathered, git, result, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, Rule, RuleLevel, RulesConfig};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::{Violation, Warning};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::TestState;
+
+/// `feature` was passing and now fails; `gone` is tracked but not run.
+fn regressed(rules: RulesConfig) -> GatheredRun {
+    GatheredRun {
+        rules,
+        ..gathered(
+            &[
+                ("tdd_ratchet_gatekeeper", TestState::Passing),
+                ("feature", TestState::Passing),
+                ("gone", TestState::Passing),
+            ],
+            vec![
+                result("tdd_ratchet_gatekeeper", TestOutcome::Passed),
+                result("feature", TestOutcome::Failed),
+            ],
+        )
+    }
+}
+
+fn rules(toml: &str) -> RulesConfig {
+    RatchetConfig::parse_from_str(toml, Path::new("ratchet.toml"))
+        .unwrap()
+        .rules
+}
+
+#[test]
+fn core_rules_are_errors_by_default() {
+    let result = regressed(RulesConfig::default()).evaluate();
+
+    assert_eq!(result.violations.len(), 2, "{:?}", result.violations);
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    for rule in Rule::ALL {
+        let expected = match rule {
+            Rule::PassingToIgnored => RuleLevel::Allow,
+            Rule::DirtyWorktree => RuleLevel::Warn,
+            _ => RuleLevel::Deny,
+        };
+        assert_eq!(RulesConfig::default().level(rule), expected, "{rule}");
+    }
+}
+
+#[test]
+fn warn_reports_the_violation_as_a_warning() {
+    let result = regressed(rules("[rules]\nregression = \"warn\"\n")).evaluate();
+
+    assert!(
+        matches!(
+            result.violations.as_slice(),
+            [Violation::TestDisappeared { test }] if test == "gone"
+        ),
+        "{:?}",
+        result.violations
+    );
+    assert!(
+        matches!(
+            result.warnings.as_slice(),
+            [Warning::RuleWarning { violation: Violation::Regression { test } }] if test == "feature"
+        ),
+        "{:?}",
+        result.warnings
+    );
+    let report = format_report(&result);
+    assert!(report.contains("rule violation (warning)"), "{report}");
+    assert!(
+        report.contains("Regression: feature: previously passing test now fails"),
+        "{report}"
+    );
+}
+
+#[test]
+fn off_drops_the_violation_and_only_errors_fail() {
+    let result = regressed(rules(
+        "[rules]\nregression = \"warn\"\ntest-disappeared = \"off\"\n",
+    ))
+    .evaluate();
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+    assert!(format_report(&result).contains("tdd-ratchet: ok"));
+}
+
+#[test]
+fn severities_accept_error_warn_off_and_allow_deny() {
+    let rules = rules(
+        "[rules]\nnew-test-passed = \"error\"\nregression = \"off\"\nskipped-pending = \"allow\"\nmissing-gatekeeper = \"deny\"\ntest-disappeared = \"warn\"\n",
+    );
+
+    assert_eq!(rules.level(Rule::NewTestPassed), RuleLevel::Deny);
+    assert_eq!(rules.level(Rule::Regression), RuleLevel::Allow);
+    assert_eq!(rules.level(Rule::SkippedPending), RuleLevel::Allow);
+    assert_eq!(rules.level(Rule::MissingGatekeeper), RuleLevel::Deny);
+    assert_eq!(rules.level(Rule::TestDisappeared), RuleLevel::Warn);
+}
+
+#[test]
+fn rules_are_named_by_key_or_violation_name() {
+    assert_eq!(Rule::parse("new-test-passed"), Some(Rule::NewTestPassed));
+    assert_eq!(Rule::parse("NewTestPassed"), Some(Rule::NewTestPassed));
+    assert_eq!(
+        Rule::parse("missing-gatekeeper"),
+        Some(Rule::MissingGatekeeper)
+    );
+    assert_eq!(Rule::parse("no-such-rule"), None);
+    assert_eq!(
+        Violation::Regression { test: "t".into() }.rule(),
+        Some(Rule::Regression)
+    );
+    assert_eq!(
+        Violation::RemovalMissingTrackedTest { test: "t".into() }.rule(),
+        None,
+        "rename and removal mistakes are always errors"
+    );
+}
+
+#[test]
+fn allow_and_deny_flags_override_the_config() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\nregression = \"warn\"\ndirty-worktree = \"allow\"\n\n[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... FAILED\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("rule violation (warning)"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--deny", "regression"]);
+    assert_eq!(code, Some(1), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--deny=regression", "--allow", "regression"]);
+    assert_eq!(code, Some(0), "the last flag wins: {out}");
+    assert!(!out.contains("rule violation"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--allow", "bogus"]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("test-disappeared"), "{out}");
+    dir.pass();
+}
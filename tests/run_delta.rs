@@ -0,0 +1,199 @@
+// tests/run_delta.rs
+//
+// Story 44: Each run ends with what changed since the previous run, kept in
+// an untracked sidecar so the committed status file does not churn.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::last_run::{
+    LAST_RUN_FILE_NAME, RunDelta, RunSummary, load_last_run, run_delta, save_last_run, summarize,
+};
+use tdd_ratchet::ratchet::{EvalResult, Violation};
+use tdd_ratchet::status::{StatusFile, TestState};
+
+fn set_results(dir: &Path, results: &[(&str, &str)]) {
+    let lines: String = results
+        .iter()
+        .map(|(name, outcome)| format!("test {name} ... {outcome}\n"))
+        .collect();
+    fs::write(dir.join("results.txt"), lines).unwrap();
+}
+
+fn eval_result(states: &[(&str, TestState)], violations: Vec<Violation>) -> EvalResult {
+    let mut updated = StatusFile::empty();
+    for (name, state) in states {
+        updated.set_test_state(*name, *state);
+    }
+    EvalResult {
+        violations,
+        warnings: Vec::new(),
+        updated,
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+fn previous(head: Option<&str>, pending: &[&str]) -> RunSummary {
+    RunSummary {
+        recorded_at: 1_700_000_000,
+        head: head.map(str::to_string),
+        passing: 1,
+        pending: pending.iter().map(|name| name.to_string()).collect(),
+        regressions: 0,
+        violations: 0,
+        suite_time_ms: None,
+        failing_drafts: Default::default(),
+    }
+}
+
+#[test]
+fn summary_counts_the_updated_status() {
+    let result = eval_result(
+        &[
+            ("a", TestState::Passing),
+            ("b", TestState::Pending),
+            ("c", TestState::Passing),
+        ],
+        vec![Violation::Regression { test: "c".into() }],
+    );
+
+    let summary = summarize(Some("abc"), &result, 42);
+
+    assert_eq!(summary.recorded_at, 42);
+    assert_eq!(summary.head.as_deref(), Some("abc"));
+    assert_eq!(summary.passing, 2);
+    assert_eq!(summary.pending, ["b".to_string()].into());
+    assert_eq!(summary.regressions, 1);
+}
+
+#[test]
+fn delta_counts_new_pending_promotions_and_regressions() {
+    let result = eval_result(
+        &[
+            ("a", TestState::Passing),
+            ("b", TestState::Passing),
+            ("c", TestState::Pending),
+            ("d", TestState::Pending),
+            ("e", TestState::Pending),
+        ],
+        Vec::new(),
+    );
+
+    let delta = run_delta(&previous(Some("abc"), &["b", "c"]), Some("abc"), &result);
+
+    assert_eq!(
+        delta,
+        RunDelta {
+            pending: 1,
+            promoted: 1,
+            regressions: 0,
+            previous_head: None,
+        }
+    );
+    assert_eq!(
+        delta.to_string(),
+        "since last run: +1 pending, 1 promoted, 0 regressions"
+    );
+}
+
+#[test]
+fn delta_mentions_the_previous_commit_when_head_moved() {
+    let result = eval_result(&[("a", TestState::Passing)], Vec::new());
+
+    let delta = run_delta(
+        &previous(Some("0123456789abcdef"), &["gone"]),
+        Some("fedcba9876543210"),
+        &result,
+    );
+
+    assert_eq!(delta.promoted, 0, "a removed pending test was not promoted");
+    assert_eq!(
+        delta.to_string(),
+        "since last run: -1 pending, 0 promoted, 0 regressions (last run at 01234567)"
+    );
+}
+
+#[test]
+fn report_ends_with_the_delta() {
+    let mut result = eval_result(
+        &[("tdd_ratchet_gatekeeper", TestState::Passing)],
+        Vec::new(),
+    );
+    assert!(!format_report(&result).contains("since last run"));
+
+    result.since_last_run = Some(RunDelta {
+        pending: 2,
+        promoted: 1,
+        regressions: 0,
+        previous_head: None,
+    });
+    let report = format_report(&result);
+
+    assert!(
+        report.ends_with("tdd-ratchet: since last run: +2 pending, 1 promoted, 0 regressions\n"),
+        "{report}"
+    );
+}
+
+#[test]
+fn summary_round_trips_and_bad_sidecars_are_ignored() {
+    let dir = TestDir::new();
+    let path = dir.path().join(LAST_RUN_FILE_NAME);
+    assert_eq!(load_last_run(&path), None);
+
+    let summary = previous(Some("abc"), &["b"]);
+    save_last_run(&path, &summary).unwrap();
+    assert_eq!(load_last_run(&path), Some(summary));
+
+    fs::write(&path, "not json").unwrap();
+    assert_eq!(load_last_run(&path), None);
+    dir.pass();
+}
+
+#[test]
+fn second_run_reports_the_change_since_the_first() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    set_results(path, &[("tdd_ratchet_gatekeeper", "ok")]);
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!out.contains("since last run"), "{out}");
+    assert!(path.join(LAST_RUN_FILE_NAME).exists());
+
+    set_results(
+        path,
+        &[
+            ("tdd_ratchet_gatekeeper", "ok"),
+            ("first", "FAILED"),
+            ("second", "FAILED"),
+        ],
+    );
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(
+        out.contains("since last run: +2 pending, 0 promoted, 0 regressions"),
+        "{out}"
+    );
+    dir.pass();
+}
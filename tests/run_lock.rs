@@ -0,0 +1,155 @@
+// tests/run_lock.rs
+//
+// Story 92: only one ratchet run at a time works on a project. A second run
+// fails with "another ratchet run is in progress", or with `--wait` waits
+// for the first to finish. The daemon's evaluations take the same lock.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, git, run_ratchet};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tdd_ratchet::run_lock::{LockHeld, RunLock};
+
+const GATEKEEPER: &str = "app::gatekeeper$tdd_ratchet_gatekeeper";
+
+#[test]
+fn a_held_lock_names_its_holder_until_released() {
+    let dir = TestDir::new();
+
+    let lock = RunLock::try_acquire(dir.path()).unwrap().unwrap();
+    let held = RunLock::try_acquire(dir.path()).unwrap().unwrap_err();
+    assert_eq!(
+        held,
+        LockHeld {
+            pid: Some(std::process::id())
+        }
+    );
+    assert_eq!(
+        held.to_string(),
+        format!(
+            "another ratchet run is in progress (process {})",
+            std::process::id()
+        )
+    );
+
+    drop(lock);
+    assert!(RunLock::try_acquire(dir.path()).unwrap().is_ok());
+    dir.pass();
+}
+
+#[test]
+fn acquire_waits_for_the_holder() {
+    let dir = TestDir::new();
+    let lock = RunLock::try_acquire(dir.path()).unwrap().unwrap();
+
+    let path = dir.path().to_path_buf();
+    let waiter = thread::spawn(move || RunLock::acquire(&path).map(|_| ()));
+    thread::sleep(Duration::from_millis(200));
+    assert!(!waiter.is_finished());
+
+    drop(lock);
+    waiter.join().unwrap().unwrap();
+    dir.pass();
+}
+
+fn setup_project(path: &Path) {
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.json"),
+        format!("{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"{GATEKEEPER}\"}}\n"),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+}
+
+#[test]
+fn a_second_run_fails_or_waits_while_one_is_in_progress() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    setup_project(path);
+
+    let lock = RunLock::try_acquire(path).unwrap().unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("another ratchet run is in progress"), "{out}");
+    assert!(out.contains("--wait"), "{out}");
+    assert!(!path.join(".test-status.json").exists());
+
+    let project = path.to_path_buf();
+    let waiting = thread::spawn(move || run_ratchet(&project, &["--wait"]));
+    thread::sleep(Duration::from_millis(500));
+    drop(lock);
+    let (code, out) = waiting.join().unwrap();
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("waiting for it to finish"), "{out}");
+    assert!(path.join(".test-status.json").exists());
+    dir.pass();
+}
+
+#[test]
+fn a_run_cannot_overlap_a_daemon_evaluation() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    setup_project(path);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"sh\", \"-c\", \"sleep 2 && cat results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+
+    let mut daemon = Command::new(cargo_bin())
+        .arg("daemon")
+        .current_dir(path)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    writeln!(
+        daemon.stdin.take().unwrap(),
+        r#"{{"jsonrpc":"2.0","id":1,"method":"evaluate"}}"#
+    )
+    .unwrap();
+    let started = Instant::now();
+    while RunLock::try_acquire(path).unwrap().is_ok() {
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "the daemon never took the lock"
+        );
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("another ratchet run is in progress"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--wait"]);
+    assert_eq!(code, Some(0), "{out}");
+    let output = daemon.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains(r#""ok":true"#),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    dir.pass();
+}
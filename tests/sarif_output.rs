@@ -0,0 +1,131 @@
+// tests/sarif_output.rs
+//
+// Story 59: `--format sarif` reports violations as a SARIF 2.1.0 log, so they
+// can be uploaded to GitHub code scanning and other SARIF consumers.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tdd_ratchet::explain::EXPLANATIONS;
+use tdd_ratchet::ratchet::{EvalResult, Violation, Warning};
+use tdd_ratchet::sarif::format_sarif;
+use tdd_ratchet::status::StatusFile;
+use tdd_ratchet::test_locations::TestLocation;
+
+fn result_with(violations: Vec<Violation>, warnings: Vec<Warning>) -> EvalResult {
+    EvalResult {
+        violations,
+        warnings,
+        updated: StatusFile::empty(),
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+fn sarif(results: &[(&EvalResult, &Path)]) -> Value {
+    let log = format_sarif(results.iter().copied());
+    serde_json::from_str(&log).expect("SARIF output is JSON")
+}
+
+fn results(log: &Value) -> &Vec<Value> {
+    log["runs"][0]["results"].as_array().unwrap()
+}
+
+#[test]
+fn log_declares_version_and_every_rule() {
+    let result = result_with(Vec::new(), Vec::new());
+    let log = sarif(&[(&result, Path::new(".test-status.json"))]);
+
+    assert_eq!(log["version"], "2.1.0");
+    let driver = &log["runs"][0]["tool"]["driver"];
+    assert_eq!(driver["name"], "tdd-ratchet");
+    let rules = driver["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), EXPLANATIONS.len());
+    assert_eq!(rules[1]["id"], "RATCHET002");
+    assert_eq!(rules[1]["name"], "Regression");
+    assert!(results(&log).is_empty());
+}
+
+#[test]
+fn violation_points_at_the_test_source() {
+    let mut result = result_with(
+        vec![Violation::Regression {
+            test: "tests/api.rs::lookup".into(),
+        }],
+        Vec::new(),
+    );
+    result.locations.insert(
+        "tests/api.rs::lookup".into(),
+        TestLocation {
+            path: PathBuf::from("tests").join("api.rs"),
+            line: 12,
+            owners: Vec::new(),
+        },
+    );
+    let log = sarif(&[(&result, Path::new(".test-status.json"))]);
+
+    let [finding] = results(&log).as_slice() else {
+        panic!("expected one result: {log}");
+    };
+    assert_eq!(finding["ruleId"], "RATCHET002");
+    assert_eq!(finding["ruleIndex"], 1);
+    assert_eq!(finding["level"], "error");
+    let location = &finding["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "tests/api.rs");
+    assert_eq!(location["region"]["startLine"], 12);
+    let message = finding["message"]["text"].as_str().unwrap();
+    assert!(message.contains("tests/api.rs::lookup"), "{message}");
+}
+
+#[test]
+fn violation_without_source_points_at_the_status_file() {
+    let result = result_with(vec![Violation::MissingGatekeeper], Vec::new());
+    let status = Path::new("crates").join("core").join(".test-status.json");
+    let log = sarif(&[(&result, &status)]);
+
+    let location = &results(&log)[0]["locations"][0]["physicalLocation"];
+    assert_eq!(
+        location["artifactLocation"]["uri"],
+        "crates/core/.test-status.json"
+    );
+    assert_eq!(location["region"]["startLine"], 1);
+}
+
+#[test]
+fn warned_rules_are_warnings_and_roots_are_combined() {
+    let first = result_with(
+        vec![Violation::NewTestPassed { test: "a".into() }],
+        vec![Warning::RuleWarning {
+            violation: Violation::PassingTestIgnored { test: "b".into() },
+        }],
+    );
+    let second = result_with(
+        vec![Violation::TestDisappeared { test: "c".into() }],
+        Vec::new(),
+    );
+    let log = sarif(&[
+        (&first, Path::new("one/.test-status.json")),
+        (&second, Path::new("two/.test-status.json")),
+    ]);
+
+    let levels: Vec<(&str, &str)> = results(&log)
+        .iter()
+        .map(|r| (r["ruleId"].as_str().unwrap(), r["level"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        levels,
+        [
+            ("RATCHET001", "error"),
+            ("RATCHET016", "warning"),
+            ("RATCHET003", "error"),
+        ]
+    );
+    assert_eq!(
+        results(&log)[2]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "two/.test-status.json"
+    );
+}
@@ -0,0 +1,78 @@
+// tests/schema.rs
+//
+// Story 27: The status file schema ships inside the binary and status files
+// can be validated against it with line-precise diagnostics.
+
+use std::fs;
+use tdd_ratchet::schema::{SCHEMA_V1, SCHEMA_V2, SCHEMA_V3, current_schema, validate_status_json};
+
+#[test]
+fn embedded_schemas_match_published_files() {
+    assert_eq!(
+        SCHEMA_V1,
+        fs::read_to_string("docs/schema/test-status.v1.json").unwrap()
+    );
+    assert_eq!(
+        SCHEMA_V2,
+        fs::read_to_string("docs/schema/test-status.v2.json").unwrap()
+    );
+    assert_eq!(
+        SCHEMA_V3,
+        fs::read_to_string("docs/schema/test-status.v3.json").unwrap()
+    );
+    assert_eq!(current_schema(), SCHEMA_V3);
+}
+
+#[test]
+fn dogfood_status_file_is_valid() {
+    let contents = fs::read_to_string(".test-status.json").unwrap();
+    let diagnostics = validate_status_json(&contents);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn schema_errors_point_at_the_offending_line() {
+    let contents = r#"{
+  "version": 2,
+  "tests": {
+    "good": { "state": "passing" },
+    "bad": { "state": "bogus" }
+  }
+}"#;
+    let diagnostics = validate_status_json(contents);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0].line, 5);
+    assert_eq!(diagnostics[0].pointer, "/tests/bad/state");
+    assert!(
+        diagnostics[0].to_string().starts_with("5:"),
+        "{}",
+        diagnostics[0]
+    );
+}
+
+#[test]
+fn invalid_json_reports_parser_position() {
+    let diagnostics = validate_status_json("{\n  \"tests\": {,}\n}");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 2);
+    assert!(diagnostics[0].message.contains("invalid JSON"));
+}
+
+#[test]
+fn unversioned_files_validate_against_v1_schema() {
+    let diagnostics = validate_status_json(r#"{"tests":{"a":"passing"}}"#);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+    // Bare strings are a version 1 form only.
+    let diagnostics = validate_status_json(r#"{"version":2,"tests":{"a":"passing"}}"#);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0].pointer, "/tests/a");
+}
+
+#[test]
+fn unsupported_version_is_reported_at_version_key() {
+    let diagnostics = validate_status_json("{\n  \"version\": 7,\n  \"tests\": {}\n}");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 2);
+    assert!(diagnostics[0].message.contains("version 7"));
+}
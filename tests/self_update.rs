@@ -0,0 +1,138 @@
+// tests/self_update.rs
+//
+// Story 106: a team that installs the ratchet globally keeps it current with
+// `cargo ratchet self update`, which installs the latest GitHub release only
+// once its checksum is verified; `--check` only reports whether there is one.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tdd_ratchet::self_update::{
+    Release, SelfUpdateError, binary_asset_name, expected_checksum, is_newer, sha256_hex,
+    verify_checksum,
+};
+
+/// Run the binary at `exe` against the release described by `release_json`.
+fn run_update(exe: &Path, release_json: &Path, args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(exe)
+        .args(args)
+        .current_dir(exe.parent().unwrap())
+        .env(
+            "TDD_RATCHET_RELEASE_URL",
+            format!("file://{}", release_json.display()),
+        )
+        .output()
+        .unwrap();
+    let out = String::from_utf8_lossy(&output.stdout).to_string()
+        + &String::from_utf8_lossy(&output.stderr);
+    (output.status.code(), out)
+}
+
+/// Publish a release `tag` with this platform's binary and `sums` in `dir`;
+/// returns the path of its metadata.
+fn publish(dir: &Path, tag: &str, binary: &[u8], sums: &str) -> PathBuf {
+    let asset = dir.join(binary_asset_name());
+    fs::write(&asset, binary).unwrap();
+    fs::write(dir.join("SHA256SUMS"), sums).unwrap();
+    let metadata = dir.join("release.json");
+    fs::write(
+        &metadata,
+        serde_json::json!({
+            "tag_name": tag,
+            "assets": [
+                {
+                    "name": binary_asset_name(),
+                    "browser_download_url": format!("file://{}", asset.display()),
+                },
+                {
+                    "name": "SHA256SUMS",
+                    "browser_download_url": format!("file://{}", dir.join("SHA256SUMS").display()),
+                },
+            ],
+        })
+        .to_string(),
+    )
+    .unwrap();
+    metadata
+}
+
+#[test]
+fn releases_are_compared_and_verified_before_installing() {
+    assert!(is_newer("0.4.0", "0.3.9"));
+    assert!(is_newer("0.10.0", "0.9.0"));
+    assert!(is_newer("1.0.0", "1.0.0-rc.1"));
+    assert!(!is_newer("1.0.0-rc.1", "1.0.0"));
+    assert!(!is_newer("0.3.0", "0.3.0"));
+
+    let release: Release = serde_json::from_str(
+        r#"{"tag_name":"v0.4.0","assets":[{"name":"SHA256SUMS","browser_download_url":"https://example.com/SHA256SUMS"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(release.version(), "0.4.0");
+    assert!(release.asset("SHA256SUMS").is_some());
+
+    let binary = b"new binary";
+    let hash = sha256_hex(binary);
+    let sums = format!(
+        "{}  other\n{hash} *cargo-ratchet-x86_64-linux\n",
+        "0".repeat(64)
+    );
+    assert_eq!(
+        expected_checksum(&sums, "cargo-ratchet-x86_64-linux"),
+        Some(hash.as_str())
+    );
+    assert!(verify_checksum("0.4.0", "cargo-ratchet-x86_64-linux", binary, &sums).is_ok());
+    assert!(matches!(
+        verify_checksum("0.4.0", "cargo-ratchet-x86_64-linux", b"tampered", &sums),
+        Err(SelfUpdateError::ChecksumMismatch { .. })
+    ));
+    assert!(matches!(
+        verify_checksum("0.4.0", "cargo-ratchet-aarch64-macos", binary, &sums),
+        Err(SelfUpdateError::NoChecksum { .. })
+    ));
+}
+
+#[test]
+fn self_update_replaces_the_binary_only_with_a_verified_release() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let installed = dir.path().join("bin");
+    fs::create_dir(&installed).unwrap();
+    let exe = installed.join("cargo-ratchet");
+    fs::copy(cargo_bin(), &exe).unwrap();
+    let original = fs::read(&exe).unwrap();
+    let releases = dir.path().join("releases");
+    fs::create_dir(&releases).unwrap();
+    let binary = b"#!/bin/sh\necho updated\n";
+    let good_sums = format!("{}  {}\n", sha256_hex(binary), binary_asset_name());
+
+    let current = publish(&releases, env!("CARGO_PKG_VERSION"), binary, &good_sums);
+    let (code, out) = run_update(&exe, &current, &["self", "update"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("is the latest version"), "{out}");
+
+    let newer = publish(&releases, "v99.0.0", binary, &good_sums);
+    let (code, out) = run_update(&exe, &newer, &["self", "update", "--check"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("99.0.0 is available"), "{out}");
+    assert_eq!(fs::read(&exe).unwrap(), original);
+
+    let bad_sums = format!("{}  {}\n", sha256_hex(b"other"), binary_asset_name());
+    let tampered = publish(&releases, "v99.0.0", binary, &bad_sums);
+    let (code, out) = run_update(&exe, &tampered, &["self", "update"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("does not match its SHA-256"), "{out}");
+    assert_eq!(fs::read(&exe).unwrap(), original);
+
+    let verified = publish(&releases, "v99.0.0", binary, &good_sums);
+    let (code, out) = run_update(&exe, &verified, &["self", "update"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("to 99.0.0"), "{out}");
+    assert_eq!(fs::read(&exe).unwrap(), binary);
+    let output = Command::new(&exe).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "updated\n");
+    dir.pass();
+}
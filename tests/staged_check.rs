@@ -0,0 +1,171 @@
+// tests/staged_check.rs
+//
+// Story 69: `cargo ratchet check --staged` evaluates the staged tree in a
+// temporary checkout, so a pre-commit hook checks exactly what is about to
+// be committed.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, combined_output, git, ratchet_command, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::staged::StagedTree;
+
+const RESULTS_OK: &str = "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n";
+const RESULTS_NEW_PASSING: &str =
+    "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\ntest untested ... ok\n";
+
+/// A committed project whose tests are read from `results.txt`.
+fn committed_project(dir: &Path) {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    fs::write(
+        dir.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("results.txt"), RESULTS_OK).unwrap();
+    fs::write(
+        dir.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-m", "Initial project"]);
+}
+
+#[test]
+fn staged_tree_holds_the_index_not_the_working_tree() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+    fs::write(path.join("results.txt"), RESULTS_NEW_PASSING).unwrap();
+    git(path, &["add", "results.txt"]);
+    fs::write(path.join("results.txt"), "unstaged\n").unwrap();
+    fs::write(path.join("untracked.txt"), "untracked\n").unwrap();
+    let status_before = git(path, &["status", "--porcelain"]);
+
+    let staged = StagedTree::checkout(path).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(staged.path().join("results.txt")).unwrap(),
+        RESULTS_NEW_PASSING
+    );
+    assert!(staged.path().join("ratchet.toml").exists());
+    assert!(!staged.path().join("untracked.txt").exists());
+    assert!(!staged.path().join(".git").exists());
+    assert_eq!(git(path, &["status", "--porcelain"]), status_before);
+    assert_eq!(
+        fs::read_to_string(path.join("results.txt")).unwrap(),
+        "unstaged\n"
+    );
+    dir.pass();
+}
+
+#[test]
+fn staged_tree_is_removed_on_drop() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+
+    let staged = StagedTree::checkout(path).unwrap();
+    let checkout = staged.path().to_path_buf();
+    assert!(checkout.is_dir());
+    drop(staged);
+
+    assert!(!checkout.exists());
+    dir.pass();
+}
+
+#[test]
+fn staged_violation_fails_even_when_the_working_tree_is_clean() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+    fs::write(path.join("results.txt"), RESULTS_NEW_PASSING).unwrap();
+    git(path, &["add", "results.txt"]);
+    fs::write(path.join("results.txt"), RESULTS_OK).unwrap();
+
+    let (code, out) = run_ratchet(path, &["check", "--staged"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("untested"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["check"]);
+    assert_eq!(code, Some(0), "the working tree is fine: {out}");
+    dir.pass();
+}
+
+#[test]
+fn unstaged_violation_does_not_fail_the_staged_check() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+    fs::write(path.join("results.txt"), RESULTS_NEW_PASSING).unwrap();
+
+    let (code, out) = run_ratchet(path, &["check", "--staged"]);
+    assert_eq!(code, Some(0), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn staged_check_leaves_the_status_file_alone() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+    fs::write(
+        path.join("results.txt"),
+        format!("{RESULTS_OK}test pending_one ... FAILED\n"),
+    )
+    .unwrap();
+    git(path, &["add", "results.txt"]);
+    let before = fs::read_to_string(path.join(".test-status.json")).unwrap();
+
+    let (code, out) = run_ratchet(path, &["check", "--staged"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(
+        fs::read_to_string(path.join(".test-status.json")).unwrap(),
+        before
+    );
+    dir.pass();
+}
+
+#[test]
+fn staged_check_builds_in_the_project_target_dir() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    committed_project(path);
+    fs::write(
+        path.join("Cargo.toml"),
+        "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    fs::create_dir(path.join("src")).unwrap();
+    fs::write(path.join("src/lib.rs"), "").unwrap();
+    fs::create_dir(path.join("target")).unwrap();
+    let seen = path.join("target-dir.txt");
+    fs::write(
+        path.join("ratchet.toml"),
+        format!(
+            "[rules]\ndirty-worktree = \"allow\"\n\n[[runners]]\nkind = \"command\"\ncommand = [\"sh\", \"-c\", \"cat results.txt; printf %s \\\"$CARGO_TARGET_DIR\\\" > {}\"]\nparser = \"libtest\"\n",
+            seen.display()
+        ),
+    )
+    .unwrap();
+    git(path, &["add", "Cargo.toml", "src", "ratchet.toml"]);
+
+    let (code, out) = combined_output(
+        ratchet_command(path, &["check", "--staged"]).env_remove("CARGO_TARGET_DIR"),
+    );
+    assert_eq!(code, Some(0), "{out}");
+    let target_dir = fs::read_to_string(&seen).unwrap();
+    assert_eq!(
+        Path::new(&target_dir).canonicalize().unwrap(),
+        path.join("target").canonicalize().unwrap()
+    );
+    dir.pass();
+}
@@ -0,0 +1,235 @@
+// tests/stale_report.rs
+//
+// Story 100: `cargo ratchet report --stale` prints a digest of pending tests
+// gone stale, active quarantines, and accepted failures nearing expiry, for a
+// cron job to post to chat or email.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet_split};
+use std::fs;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::stale::{
+    ExpiringAcceptance, StaleOptions, StalePending, StaleQuarantine, format_stale_markdown,
+    format_stale_text, stale_report,
+};
+use tdd_ratchet::status::{AcceptedFailure, Quarantine, StatusFile, TestEntry, TestState};
+
+const DAY: i64 = 24 * 60 * 60;
+// 2026-10-17 00:00:00 UTC.
+const NOW: i64 = 1_792_195_200;
+
+fn pending(since: i64) -> TestEntry {
+    let mut entry = TestEntry::new(TestState::Pending);
+    entry.added_at = Some(since);
+    entry
+}
+
+fn accepted(until: Option<&str>, commits: Option<u32>) -> TestEntry {
+    let mut entry = TestEntry::new(TestState::Passing);
+    entry.accepted_failure = Some(AcceptedFailure {
+        reason: "upstream outage".to_string(),
+        until: until.map(str::to_string),
+        commits,
+    });
+    entry
+}
+
+fn status(entries: Vec<(&str, TestEntry)>) -> StatusFile {
+    StatusFile::new(
+        entries
+            .into_iter()
+            .map(|(test, entry)| (test.to_string(), entry))
+            .collect(),
+    )
+}
+
+#[test]
+fn pending_tests_older_than_the_threshold_are_listed_oldest_first() {
+    let mut changed = pending(NOW - 60 * DAY);
+    changed.changed_at = Some(NOW - 2 * DAY);
+    let status = status(vec![
+        ("app$fresh", pending(NOW - 3 * DAY)),
+        ("app$old", pending(NOW - 20 * DAY)),
+        ("app$older", pending(NOW - 40 * DAY)),
+        ("app$recently_demoted", changed),
+        ("app$unstamped", TestEntry::new(TestState::Pending)),
+        ("app$passing", {
+            let mut entry = TestEntry::new(TestState::Passing);
+            entry.added_at = Some(NOW - 90 * DAY);
+            entry
+        }),
+    ]);
+
+    let report = stale_report(&status, &[], NOW, StaleOptions::default());
+
+    assert_eq!(
+        report.pending,
+        [
+            StalePending {
+                test: "app$older".to_string(),
+                age_days: 40,
+            },
+            StalePending {
+                test: "app$old".to_string(),
+                age_days: 20,
+            },
+        ]
+    );
+    assert_eq!(report.date, "2026-10-17");
+
+    let strict = stale_report(
+        &status,
+        &[],
+        NOW,
+        StaleOptions {
+            older_than_days: 2,
+            ..StaleOptions::default()
+        },
+    );
+    assert_eq!(strict.pending.len(), 3);
+}
+
+#[test]
+fn acceptances_are_listed_once_close_to_expiry() {
+    let counted = accepted(None, Some(5));
+    let snapshot = |commit: &str| HistorySnapshot {
+        commit: commit.to_string(),
+        info: CommitInfo::default(),
+        status: status(vec![("app$counted", counted.clone())]),
+    };
+    let snapshots: Vec<HistorySnapshot> =
+        ["c1", "c2", "c3", "c4"].into_iter().map(snapshot).collect();
+    let mut quarantined = TestEntry::new(TestState::Passing);
+    quarantined.quarantine = Some(Quarantine {
+        flips: 3,
+        commit: Some("0123456789abcdef".to_string()),
+    });
+    let status = status(vec![
+        ("app$counted", counted),
+        ("app$distant", accepted(Some("2026-12-01"), None)),
+        ("app$lapsed", accepted(Some("2026-10-10"), None)),
+        ("app$soon", accepted(Some("2026-10-20"), None)),
+        ("app$flappy", quarantined),
+    ]);
+
+    let report = stale_report(&status, &snapshots, NOW, StaleOptions::default());
+
+    let expiring = |test: &str, until: Option<&str>, commits_left, expired| ExpiringAcceptance {
+        test: test.to_string(),
+        reason: "upstream outage".to_string(),
+        until: until.map(str::to_string),
+        commits_left,
+        expired,
+    };
+    assert_eq!(
+        report.expiring,
+        [
+            expiring("app$counted", None, Some(2), false),
+            expiring("app$lapsed", Some("2026-10-10"), None, true),
+            expiring("app$soon", Some("2026-10-20"), None, false),
+        ]
+    );
+    assert_eq!(
+        report.quarantined,
+        [StaleQuarantine {
+            test: "app$flappy".to_string(),
+            flips: 3,
+            commit: Some("0123456789abcdef".to_string()),
+        }]
+    );
+}
+
+#[test]
+fn formats_are_empty_when_nothing_is_stale() {
+    let quiet = stale_report(
+        &status(vec![("app$fresh", pending(NOW - DAY))]),
+        &[],
+        NOW,
+        StaleOptions::default(),
+    );
+    assert!(quiet.is_empty());
+    assert_eq!(format_stale_text(&quiet), "");
+    assert_eq!(format_stale_markdown(&quiet), "");
+
+    let report = stale_report(
+        &status(vec![
+            ("app$old", pending(NOW - 20 * DAY)),
+            ("app$soon", accepted(Some("2026-10-20"), None)),
+        ]),
+        &[],
+        NOW,
+        StaleOptions::default(),
+    );
+    assert_eq!(
+        format_stale_text(&report),
+        "tdd-ratchet stale report (2026-10-17)\n\
+         \n\
+         Pending for more than 14 days (1):\n\
+         \x20 - app$old: pending for 20 days\n\
+         \n\
+         Accepted failures expiring within 7 days or 3 commits (1):\n\
+         \x20 - app$soon: upstream outage (until 2026-10-20)\n"
+    );
+    let markdown = format_stale_markdown(&report);
+    assert!(markdown.starts_with("**tdd-ratchet stale report** (2026-10-17)\n"));
+    assert!(markdown.contains("- `app$old` — pending for 20 days\n"));
+}
+
+#[test]
+fn report_stale_command_prints_a_digest_for_cron() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let project = dir.path();
+    git(project, &["init", "-b", "main"]);
+    git(project, &["config", "user.email", "test@test.com"]);
+    git(project, &["config", "user.name", "Test"]);
+    fs::write(project.join("README.md"), "app\n").unwrap();
+    git(project, &["add", "-A"]);
+    git(project, &["commit", "-m", "Initial project"]);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let status_path = project.join(".test-status.json");
+
+    status(vec![("app$fresh", pending(now - DAY))])
+        .save(&status_path)
+        .unwrap();
+    let (code, stdout, stderr) = run_ratchet_split(project, &["report", "--stale"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    assert_eq!(stdout, "");
+
+    status(vec![("app$old", pending(now - 30 * DAY))])
+        .save(&status_path)
+        .unwrap();
+    let (code, stdout, stderr) =
+        run_ratchet_split(project, &["report", "--stale", "--format", "markdown"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    assert!(
+        stdout.contains("**Pending for more than 14 days** (1)"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("`app$old`"), "{stdout}");
+
+    let (code, stdout, _) =
+        run_ratchet_split(project, &["report", "--stale", "--older-than", "40"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "");
+
+    let (code, _, stderr) =
+        run_ratchet_split(project, &["report", "--stale", "--older-than", "soon"]);
+    assert_eq!(code, Some(2));
+    assert!(
+        stderr.contains("--older-than expects a number of days"),
+        "{stderr}"
+    );
+
+    let (code, _, stderr) = run_ratchet_split(project, &["report"]);
+    assert_eq!(code, Some(2));
+    assert!(
+        stderr.contains("usage: cargo ratchet report --stale"),
+        "{stderr}"
+    );
+    dir.pass();
+}
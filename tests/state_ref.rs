@@ -0,0 +1,213 @@
+// tests/state_ref.rs
+//
+// Story 49: With `[state] storage = "ref"`, ratchet state lives in commits on
+// `refs/ratchet/state` instead of a tracked file, and history is checked
+// along that ref's log.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, StateStorage};
+use tdd_ratchet::history::HistoryProvider;
+use tdd_ratchet::state_ref::{RefStatusStore, STATE_REF, StateRefHistory, write_state};
+use tdd_ratchet::status::{StatusFile, StatusStore, TestState};
+
+fn init_repo(dir: &Path) {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+}
+
+fn status(states: &[(&str, TestState)]) -> StatusFile {
+    let mut status = StatusFile::empty();
+    for (name, state) in states {
+        status.set_test_state(*name, *state);
+    }
+    status
+}
+
+fn ref_commits(dir: &Path) -> usize {
+    git(dir, &["rev-list", "--count", STATE_REF])
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+fn set_results(dir: &Path, results: &[(&str, &str)]) {
+    let lines: String = results
+        .iter()
+        .map(|(name, outcome)| format!("test {name} ... {outcome}\n"))
+        .collect();
+    fs::write(dir.join("results.txt"), lines).unwrap();
+}
+
+#[test]
+fn missing_ref_has_no_state_or_history() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+
+    let history = StateRefHistory::new(dir.path());
+    assert!(history.head_status().unwrap().is_none());
+    assert!(history.snapshots().unwrap().is_empty());
+    assert!(RefStatusStore::new(dir.path()).load().unwrap().is_none());
+    dir.pass();
+}
+
+#[test]
+fn saved_state_round_trips_through_the_ref() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+    let store = RefStatusStore::new(dir.path());
+
+    store.save(&status(&[("a", TestState::Pending)])).unwrap();
+
+    let loaded = store.load().unwrap().unwrap();
+    assert_eq!(loaded.tests["a"].state(), TestState::Pending);
+    assert_eq!(ref_commits(dir.path()), 1);
+    assert!(
+        !dir.path().join(".test-status.json").exists(),
+        "the working tree is left alone"
+    );
+    dir.pass();
+}
+
+#[test]
+fn unchanged_state_is_not_committed_again() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+    let store = RefStatusStore::new(dir.path());
+    let pending = status(&[("a", TestState::Pending)]);
+
+    store.save(&pending).unwrap();
+    store.save(&pending).unwrap();
+    assert_eq!(ref_commits(dir.path()), 1);
+
+    let json = pending.to_json(Path::new(".test-status.json")).unwrap();
+    let written = write_state(dir.path(), STATE_REF, Path::new(".test-status.json"), &json);
+    assert!(!written.unwrap());
+    assert_eq!(ref_commits(dir.path()), 1);
+    dir.pass();
+}
+
+#[test]
+fn history_follows_the_ref_log_not_head() {
+    let dir = TestDir::new();
+    init_repo(dir.path());
+    fs::write(dir.path().join("README"), "code\n").unwrap();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-m", "Code"]);
+    let store = RefStatusStore::new(dir.path());
+
+    store.save(&status(&[("a", TestState::Pending)])).unwrap();
+    store.save(&status(&[("a", TestState::Passing)])).unwrap();
+
+    let snapshots = StateRefHistory::new(dir.path()).snapshots().unwrap();
+    let states: Vec<TestState> = snapshots
+        .iter()
+        .map(|snapshot| snapshot.status.tests["a"].state())
+        .collect();
+    assert_eq!(states, [TestState::Pending, TestState::Passing]);
+    assert_eq!(
+        git(dir.path(), &["rev-list", "--count", "HEAD"]).trim(),
+        "1"
+    );
+    dir.pass();
+}
+
+#[test]
+fn ref_storage_is_selected_in_config() {
+    let config = RatchetConfig::parse_from_str("", Path::new("ratchet.toml")).unwrap();
+    assert_eq!(config.state.storage, StateStorage::File);
+
+    let config =
+        RatchetConfig::parse_from_str("[state]\nstorage = \"ref\"\n", Path::new("ratchet.toml"))
+            .unwrap();
+    assert_eq!(config.state.storage, StateStorage::Ref);
+}
+
+#[test]
+fn ratchet_in_ref_mode_keeps_state_out_of_the_working_tree() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    init_repo(path);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[state]\nstorage = \"ref\"\n\n[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    set_results(
+        path,
+        &[("tdd_ratchet_gatekeeper", "ok"), ("feature", "FAILED")],
+    );
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(!path.join(".test-status.json").exists());
+    assert_eq!(ref_commits(path), 1);
+
+    set_results(path, &[("tdd_ratchet_gatekeeper", "ok"), ("feature", "ok")]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(
+        code,
+        Some(0),
+        "promotion is checked against the ref's history: {out}"
+    );
+    assert_eq!(ref_commits(path), 2);
+
+    // The next run records which state commit promoted `feature`; after
+    // that, nothing changes.
+    run_ratchet(path, &[]);
+    let settled = ref_commits(path);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(
+        ref_commits(path),
+        settled,
+        "an unchanged run adds no commit"
+    );
+
+    let (code, shown) = run_ratchet(path, &["state", "show"]);
+    assert_eq!(code, Some(0), "{shown}");
+    let shown = StatusFile::parse_from_str(&shown, Path::new(STATE_REF)).unwrap();
+    assert_eq!(shown.tests["feature"].state(), TestState::Passing);
+
+    set_results(
+        path,
+        &[
+            ("tdd_ratchet_gatekeeper", "ok"),
+            ("feature", "ok"),
+            ("cheater", "ok"),
+        ],
+    );
+    let (code, out) = run_ratchet(path, &[]);
+    assert_ne!(
+        code,
+        Some(0),
+        "a test that never failed is still caught: {out}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn state_import_seeds_the_ref_from_a_file() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    init_repo(path);
+    fs::write(path.join("old-status.json"), r#"{"tests":{"a":"pending"}}"#).unwrap();
+
+    let (code, out) = run_ratchet(path, &["state", "show"]);
+    assert_ne!(code, Some(0), "{out}");
+    assert!(out.contains("no ratchet state"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["state", "import", "old-status.json"]);
+    assert_eq!(code, Some(0), "{out}");
+    let loaded = RefStatusStore::new(path).load().unwrap().unwrap();
+    assert_eq!(loaded.tests["a"].state(), TestState::Pending);
+    dir.pass();
+}
@@ -2,25 +2,21 @@
 //
 // Stories 5, 6, 7: The core ratchet rules.
 
+mod common;
+
+use common::status;
 use tdd_ratchet::ratchet::{RatchetViolation, check_ratchet, evaluate};
-use tdd_ratchet::runner::{TestOutcome, TestResult};
+use tdd_ratchet::runner::{TestOutcome, TestResult, parse_nextest_output};
 use tdd_ratchet::status::{StatusFile, TestEntry, TestState};
 
-fn status(tests: &[(&str, TestState)]) -> StatusFile {
-    StatusFile::new(
-        tests
-            .iter()
-            .map(|(n, s)| (n.to_string(), TestEntry::Simple(*s)))
-            .collect(),
-    )
-}
-
 fn results(tests: &[(&str, TestOutcome)]) -> Vec<TestResult> {
     tests
         .iter()
         .map(|(n, o)| TestResult {
             name: n.to_string(),
             outcome: *o,
+            output: None,
+            exec_time_ms: None,
         })
         .collect()
 }
@@ -194,10 +190,7 @@ fn promoting_test_preserves_baseline_metadata() {
     let sf = StatusFile::new(
         [(
             "my_test".to_string(),
-            TestEntry::WithBaseline {
-                state: TestState::Pending,
-                baseline: "abc123".to_string(),
-            },
+            TestEntry::grandfathered(TestState::Pending, "abc123"),
         )]
         .into_iter()
         .collect(),
@@ -209,10 +202,7 @@ fn promoting_test_preserves_baseline_metadata() {
     assert!(outcome.violations.is_empty());
     assert_eq!(
         outcome.updated.tests["my_test"],
-        TestEntry::WithBaseline {
-            state: TestState::Passing,
-            baseline: "abc123".to_string(),
-        }
+        TestEntry::grandfathered(TestState::Passing, "abc123")
     );
 }
 
@@ -254,10 +244,7 @@ fn renamed_test_is_not_treated_as_new_or_missing() {
     assert!(outcome.updated.tests.contains_key("new_test"));
     assert_eq!(
         outcome.updated.tests["new_test"],
-        TestEntry::WithBaseline {
-            state: TestState::Passing,
-            baseline: "abc123".to_string(),
-        }
+        TestEntry::grandfathered(TestState::Passing, "abc123")
     );
 }
 
@@ -504,3 +491,22 @@ fn successful_removal_is_transient_in_output() {
         "Successful removal should not persist removals: {output_json}"
     );
 }
+
+#[test]
+fn same_named_tests_in_different_binaries_are_tracked_separately() {
+    let output = r#"{"type":"test","event":"ok","name":"app::api$smoke"}
+{"type":"test","event":"failed","name":"app::web$smoke"}
+"#;
+    let sf = status(&[("app::api$smoke", TestState::Pending)]);
+    let outcome = check_ratchet(&sf, &parse_nextest_output(output));
+
+    assert!(outcome.violations.is_empty(), "{:?}", outcome.violations);
+    assert_eq!(
+        outcome.updated.tests["app::api$smoke"].state(),
+        TestState::Passing
+    );
+    assert_eq!(
+        outcome.updated.tests["app::web$smoke"].state(),
+        TestState::Pending
+    );
+}
@@ -0,0 +1,78 @@
+// tests/stats.rs
+//
+// Story 18: TDD hygiene metrics computed from the committed status history.
+
+mod common;
+
+use common::status;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::stats::compute_stats;
+use tdd_ratchet::status::TestState;
+
+const WEEK: i64 = 7 * 24 * 60 * 60;
+
+fn snapshot(commit: &str, time: i64, tests: &[(&str, TestState)]) -> HistorySnapshot {
+    HistorySnapshot {
+        info: CommitInfo {
+            time,
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status(tests))
+    }
+}
+
+#[test]
+fn empty_history_has_no_metrics() {
+    let stats = compute_stats(&[]);
+    assert_eq!(stats.snapshots, 0);
+    assert_eq!(stats.promotions, 0);
+    assert_eq!(stats.average_commits_to_promotion, None);
+}
+
+#[test]
+fn promotions_grandfathering_and_backlog_are_counted() {
+    use TestState::{Passing, Pending};
+    let snapshots = vec![
+        snapshot("c1", 0, &[("legacy", Passing), ("a", Pending)]),
+        snapshot("c2", WEEK, &[("legacy", Passing), ("a", Passing)]),
+        snapshot(
+            "c3",
+            2 * WEEK,
+            &[("legacy", Passing), ("a", Passing), ("b", Pending)],
+        ),
+        snapshot(
+            "c4",
+            3 * WEEK,
+            &[
+                ("legacy", Passing),
+                ("a", Passing),
+                ("b", Pending),
+                ("c", Pending),
+            ],
+        ),
+        snapshot(
+            "c5",
+            4 * WEEK,
+            &[
+                ("legacy", Passing),
+                ("a", Passing),
+                ("b", Passing),
+                ("c", Pending),
+            ],
+        ),
+    ];
+
+    let stats = compute_stats(&snapshots);
+
+    assert_eq!(stats.snapshots, 5);
+    assert_eq!(stats.tracked, 4);
+    assert_eq!(stats.pending_backlog, 1);
+    assert_eq!(stats.grandfathered, 1);
+    assert_eq!(stats.promotions, 2);
+    // a: pending at c1, promoted at c2 (1); b: pending at c3, promoted at c5 (2)
+    assert_eq!(stats.average_commits_to_promotion, Some(1.5));
+    assert_eq!(stats.promotions_per_week, 0.5);
+
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["pending_backlog"], 1);
+}
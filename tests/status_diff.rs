@@ -0,0 +1,133 @@
+// tests/status_diff.rs
+//
+// Story 74: `cargo ratchet diff <REV>` lists the tests added, promoted,
+// regressed, renamed, and retired in the status file since REV.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, commit_status, git, run_ratchet, status};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::status::{STATUS_FILE_NAME, TestState};
+use tdd_ratchet::status_diff::{StatusDiff, diff_status, status_at_rev};
+
+#[test]
+fn diff_sorts_tests_by_what_happened_to_them() {
+    let old = status(&[
+        ("kept", TestState::Passing),
+        ("promoted", TestState::Pending),
+        ("regressed", TestState::Passing),
+        ("retired", TestState::Pending),
+    ]);
+    let new = status(&[
+        ("kept", TestState::Passing),
+        ("promoted", TestState::Passing),
+        ("regressed", TestState::Pending),
+        ("added", TestState::Pending),
+    ]);
+
+    assert_eq!(
+        diff_status(&old, &new),
+        StatusDiff {
+            added: BTreeMap::from([("added".into(), TestState::Pending)]),
+            promoted: vec!["promoted".into()],
+            regressed: vec!["regressed".into()],
+            retired: BTreeMap::from([("retired".into(), TestState::Pending)]),
+            renamed: BTreeMap::new(),
+        }
+    );
+}
+
+#[test]
+fn renamed_tests_are_not_added_and_retired() {
+    let old = status(&[("old_name", TestState::Pending)]);
+    let mut new = status(&[("new_name", TestState::Passing)]);
+    new.renames.insert("new_name".into(), "old_name".into());
+
+    let diff = diff_status(&old, &new);
+
+    assert_eq!(
+        diff.renamed,
+        BTreeMap::from([("new_name".into(), "old_name".into())])
+    );
+    assert_eq!(diff.promoted, ["new_name"]);
+    assert!(diff.added.is_empty());
+    assert!(diff.retired.is_empty());
+}
+
+#[test]
+fn identical_status_has_an_empty_diff() {
+    let old = status(&[("kept", TestState::Passing)]);
+
+    let diff = diff_status(&old, &old.clone());
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "  (no changes)\n");
+}
+
+#[test]
+fn status_at_rev_reads_the_committed_file() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    git(path, &["commit", "--allow-empty", "-m", "Empty"]);
+    commit_status(path, r#"{"tests":{"a":"pending"}}"#, "Add a");
+    commit_status(path, r#"{"tests":{"a":"passing"}}"#, "Promote a");
+
+    let at = |rev| status_at_rev(path, rev, Path::new(STATUS_FILE_NAME)).unwrap();
+
+    assert_eq!(at("HEAD~2"), None);
+    assert_eq!(at("HEAD~1").unwrap().tests["a"].state(), TestState::Pending);
+    assert_eq!(at("main").unwrap().tests["a"].state(), TestState::Passing);
+    dir.pass();
+}
+
+#[test]
+fn diff_command_compares_a_branch_with_the_working_tree_or_head() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    commit_status(
+        path,
+        r#"{"tests":{"feature":"pending","legacy":"passing"}}"#,
+        "Initial",
+    );
+    git(path, &["checkout", "-b", "topic"]);
+    commit_status(
+        path,
+        r#"{"tests":{"feature":"passing","legacy":"passing","next":"pending"}}"#,
+        "Promote feature",
+    );
+    fs::write(
+        path.join(STATUS_FILE_NAME),
+        r#"{"tests":{"feature":"passing","next":"pending"}}"#,
+    )
+    .unwrap();
+
+    let (code, out) = run_ratchet(path, &["diff", "main"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(
+        out.contains("tdd-ratchet diff: main..working tree"),
+        "{out}"
+    );
+    assert!(out.contains("added:     next (pending)"), "{out}");
+    assert!(out.contains("promoted:  feature"), "{out}");
+    assert!(out.contains("retired:   legacy (was passing)"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["diff", "main", "--head"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("main..HEAD"), "{out}");
+    assert!(!out.contains("legacy"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["diff", "no-such-branch"]);
+    assert_eq!(code, Some(2), "{out}");
+    let (code, out) = run_ratchet(path, &["diff"]);
+    assert_eq!(code, Some(2), "{out}");
+    dir.pass();
+}
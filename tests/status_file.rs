@@ -7,12 +7,12 @@ mod common;
 use common::TestDir;
 use std::collections::BTreeMap;
 use std::fs;
-use tdd_ratchet::status::{StatusFile, TestEntry, TestState};
+use tdd_ratchet::status::{CURRENT_VERSION, StatusFile, TestEntry, TestState};
 
 fn make_status(tests: &[(&str, TestState)]) -> StatusFile {
     let mut map = BTreeMap::new();
     for (name, state) in tests {
-        map.insert(name.to_string(), TestEntry::Simple(*state));
+        map.insert(name.to_string(), TestEntry::new(*state));
     }
     StatusFile::new(map)
 }
@@ -83,12 +83,12 @@ fn unknown_fields_are_rejected() {
 }
 
 #[test]
-fn legacy_global_baseline_field_is_rejected() {
+fn global_baseline_field_is_accepted() {
     let json = r#"{"tests":{"a":"passing"},"baseline":"0123456789abcdef0123456789abcdef01234567"}"#;
-    let result: Result<StatusFile, _> = serde_json::from_str(json);
-    assert!(
-        result.is_err(),
-        "Global baseline should no longer be accepted"
+    let status: StatusFile = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        status.baseline.as_deref(),
+        Some("0123456789abcdef0123456789abcdef01234567")
     );
 }
 
@@ -182,19 +182,17 @@ fn per_test_baseline_mixed_with_simple_entries() {
 }
 
 #[test]
-fn save_normalizes_simple_entries_as_strings() {
+fn save_writes_simple_entries_as_objects() {
     let dir = TestDir::new();
     let path = dir.path().join(".test-status.json");
 
     let status = make_status(&[("a", TestState::Passing)]);
     status.save(&path).unwrap();
 
-    let contents = fs::read_to_string(&path).unwrap();
-    // Simple entries should be bare strings, not objects
-    assert!(
-        contents.contains(r#""a": "passing""#),
-        "Simple entries should serialize as bare strings: {contents}"
-    );
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    // Version 2 always writes the object form
+    assert_eq!(saved["tests"]["a"], serde_json::json!({"state": "passing"}));
     dir.pass();
 }
 
@@ -204,13 +202,10 @@ fn save_preserves_per_test_baseline_as_object() {
     let path = dir.path().join(".test-status.json");
 
     let mut tests = BTreeMap::new();
-    tests.insert("simple".to_string(), TestEntry::Simple(TestState::Passing));
+    tests.insert("simple".to_string(), TestEntry::new(TestState::Passing));
     tests.insert(
         "grandfathered".to_string(),
-        TestEntry::WithBaseline {
-            state: TestState::Passing,
-            baseline: "abc123".to_string(),
-        },
+        TestEntry::grandfathered(TestState::Passing, "abc123"),
     );
     let status = StatusFile::new(tests);
     status.save(&path).unwrap();
@@ -351,8 +346,8 @@ fn schema_accepts_removals_section() {
 
 #[test]
 fn schema_validates_status_file() {
-    let schema_str = fs::read_to_string("docs/schema/test-status.v1.json")
-        .expect("Schema file should exist at docs/schema/test-status.v1.json");
+    let schema_str = fs::read_to_string("docs/schema/test-status.v3.json")
+        .expect("Schema file should exist at docs/schema/test-status.v3.json");
     let schema: serde_json::Value = serde_json::from_str(&schema_str).unwrap();
 
     let status_str = fs::read_to_string(".test-status.json")
@@ -373,3 +368,127 @@ fn schema_validates_status_file() {
             .join("\n")
     );
 }
+
+#[test]
+fn v1_file_is_upgraded_on_load_and_saved_as_v2() {
+    let dir = TestDir::new();
+    let path = dir.path().join(".test-status.json");
+    fs::write(
+        &path,
+        r#"{"tests":{"a":"passing","b":{"state":"pending","baseline":"abc123"}}}"#,
+    )
+    .unwrap();
+
+    let status = StatusFile::load(&path).unwrap();
+    assert_eq!(status.version(), 1);
+    assert!(status.needs_migration());
+    assert_eq!(status.tests["a"], TestEntry::new(TestState::Passing));
+    assert_eq!(
+        status.tests["b"],
+        TestEntry::grandfathered(TestState::Pending, "abc123")
+    );
+
+    status.save(&path).unwrap();
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["version"], CURRENT_VERSION);
+    assert_eq!(saved["tests"]["a"], serde_json::json!({"state": "passing"}));
+
+    let reloaded = StatusFile::load(&path).unwrap();
+    assert!(!reloaded.needs_migration());
+    assert_eq!(reloaded.tests, status.tests);
+    dir.pass();
+}
+
+#[test]
+fn newer_version_is_rejected() {
+    let json = r#"{"version":99,"tests":{}}"#;
+    let err = StatusFile::parse_from_str(json, std::path::Path::new(".test-status.json"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("version 99"), "{err}");
+}
+
+#[test]
+fn v2_entry_metadata_and_timestamps_round_trip() {
+    let json = r#"{"version":2,"tests":{"a":{"state":"passing","added_at":100,"changed_at":200,"metadata":{"owner":"team-x"}}}}"#;
+    let status: StatusFile = serde_json::from_str(json).unwrap();
+    let entry = &status.tests["a"];
+    assert_eq!(entry.added_at, Some(100));
+    assert_eq!(entry.changed_at, Some(200));
+    assert_eq!(entry.metadata["owner"], "team-x");
+    assert_eq!(
+        serde_json::to_value(entry).unwrap(),
+        serde_json::json!({"state": "passing", "added_at": 100, "changed_at": 200, "metadata": {"owner": "team-x"}})
+    );
+}
+
+#[test]
+fn stamp_fills_new_entries_and_state_changes_only() {
+    let json = r#"{"version":2,"tests":{"kept":{"state":"passing","added_at":100,"changed_at":100},"promoted":{"state":"pending","added_at":100,"changed_at":100}}}"#;
+    let mut status: StatusFile = serde_json::from_str(json).unwrap();
+    status.set_test_state("promoted", TestState::Passing);
+    status.set_test_state("kept", TestState::Passing);
+    status.set_test_state("new", TestState::Pending);
+
+    status.stamp(500);
+
+    assert_eq!(status.tests["kept"].changed_at, Some(100));
+    assert_eq!(status.tests["promoted"].added_at, Some(100));
+    assert_eq!(status.tests["promoted"].changed_at, Some(500));
+    assert_eq!(status.tests["new"].added_at, Some(500));
+    assert_eq!(status.tests["new"].changed_at, Some(500));
+}
+
+#[test]
+fn save_replaces_the_file_atomically_and_keeps_its_permissions() {
+    let dir = TestDir::new();
+    let path = dir.path().join(".test-status.json");
+    make_status(&[("old", TestState::Pending)])
+        .save(&path)
+        .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    }
+
+    make_status(&[("new", TestState::Passing)])
+        .save(&path)
+        .unwrap();
+
+    let loaded = StatusFile::load(&path).unwrap();
+    assert_eq!(loaded.tests.keys().collect::<Vec<_>>(), ["new"]);
+    let entries: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(entries, [".test-status.json"]);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+    dir.pass();
+}
+
+#[test]
+fn failed_save_leaves_no_temporary_file_behind() {
+    let dir = TestDir::new();
+    // A non-empty directory where the status file should be: the final
+    // rename fails after the contents were written.
+    let path = dir.path().join(".test-status.json");
+    fs::create_dir_all(path.join("inside")).unwrap();
+
+    let result = make_status(&[("a", TestState::Passing)]).save(&path);
+
+    assert!(result.is_err());
+    let entries: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(entries, [".test-status.json"]);
+    assert!(path.join("inside").is_dir());
+    dir.pass();
+}
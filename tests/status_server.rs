@@ -0,0 +1,247 @@
+// tests/status_server.rs
+//
+// Story 58: `cargo ratchet serve` exposes read-only JSON endpoints (status,
+// last run, pending tests, history trend) for wallboards.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, git, status};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tdd_ratchet::error::RatchetError;
+use tdd_ratchet::history::{CommitInfo, HistoryProvider, HistorySnapshot};
+use tdd_ratchet::last_run::{RunSummary, save_last_run};
+use tdd_ratchet::status::{StatusFile, TestState};
+use tdd_ratchet::status_server::StatusEndpoints;
+
+/// `c1` adds `a` as pending; `c2` promotes it and adds `b` as pending.
+struct FakeHistory;
+
+impl HistoryProvider for FakeHistory {
+    fn head_status(&self) -> Result<Option<StatusFile>, RatchetError> {
+        Ok(self.snapshots()?.pop().map(|snapshot| snapshot.status))
+    }
+
+    fn snapshots(&self) -> Result<Vec<HistorySnapshot>, RatchetError> {
+        let snapshot = |commit: &str, time, tests| HistorySnapshot {
+            commit: commit.into(),
+            info: CommitInfo {
+                time,
+                ..CommitInfo::default()
+            },
+            status: status(tests),
+        };
+        Ok(vec![
+            snapshot("c1", 100, &[("a", TestState::Pending)]),
+            snapshot(
+                "c2",
+                200,
+                &[("a", TestState::Passing), ("b", TestState::Pending)],
+            ),
+        ])
+    }
+}
+
+fn endpoints(dir: &Path) -> StatusEndpoints<'static> {
+    StatusEndpoints::new(
+        FakeHistory,
+        dir.join(".test-status.json"),
+        dir.join(".ratchet-last-run.json"),
+    )
+}
+
+#[test]
+fn status_falls_back_to_the_committed_status() {
+    let dir = TestDir::new();
+    let endpoints = endpoints(dir.path());
+
+    let response = endpoints.respond("GET", "/status");
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        response.body,
+        json!({"tests": {"a": "passing", "b": "pending"}, "passing": 1, "pending": 1})
+    );
+
+    status(&[("a", TestState::Passing), ("b", TestState::Passing)])
+        .save(&dir.path().join(".test-status.json"))
+        .unwrap();
+    let response = endpoints.respond("GET", "/status/");
+    assert_eq!(response.body["passing"], 2, "the working tree file wins");
+    dir.pass();
+}
+
+#[test]
+fn last_run_is_served_once_recorded() {
+    let dir = TestDir::new();
+    let endpoints = endpoints(dir.path());
+
+    assert_eq!(endpoints.respond("GET", "/last-run").status, 404);
+
+    save_last_run(
+        &dir.path().join(".ratchet-last-run.json"),
+        &RunSummary {
+            recorded_at: 5,
+            head: Some("c2".into()),
+            passing: 1,
+            pending: ["b".to_string()].into(),
+            regressions: 0,
+            violations: 0,
+            suite_time_ms: None,
+            failing_drafts: Default::default(),
+        },
+    )
+    .unwrap();
+    let response = endpoints.respond("GET", "/last-run");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body["head"], "c2");
+    assert_eq!(response.body["pending"], json!(["b"]));
+    dir.pass();
+}
+
+#[test]
+fn pending_and_trend_come_from_history() {
+    let dir = TestDir::new();
+    let endpoints = endpoints(dir.path());
+
+    let pending = endpoints.respond("GET", "/pending?team=core").body;
+    assert_eq!(
+        pending,
+        json!({"pending": [{"name": "b", "since_commit": "c2", "commits_pending": 1}]})
+    );
+
+    let trend = endpoints.respond("GET", "/trend").body;
+    assert_eq!(
+        trend["snapshots"],
+        json!([
+            {"commit": "c1", "time": 100, "passing": 0, "pending": 1},
+            {"commit": "c2", "time": 200, "passing": 1, "pending": 1},
+        ])
+    );
+    dir.pass();
+}
+
+#[test]
+fn only_get_on_known_paths_succeeds() {
+    let dir = TestDir::new();
+    let endpoints = endpoints(dir.path());
+
+    assert_eq!(endpoints.respond("GET", "/").status, 200);
+    assert_eq!(endpoints.respond("GET", "/nope").status, 404);
+    assert_eq!(endpoints.respond("POST", "/status").status, 405);
+    dir.pass();
+}
+
+/// Serve `endpoints` with a short timeout on a background thread and
+/// return its address.
+fn spawn_server(dir: &Path) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let dir = dir.to_path_buf();
+    thread::spawn(move || {
+        endpoints(&dir)
+            .with_timeout(Duration::from_millis(300))
+            .serve(listener, |_| {});
+    });
+    address
+}
+
+fn get(address: &str, path: &str) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "GET {path} HTTP/1.1\r\nHost: {address}\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn an_idle_client_does_not_block_the_endpoint() {
+    let dir = TestDir::new();
+    let address = spawn_server(dir.path());
+
+    let _idle = TcpStream::connect(&address).unwrap();
+    let started = Instant::now();
+    let response = get(&address, "/trend");
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+    assert!(started.elapsed() < Duration::from_secs(5));
+    dir.pass();
+}
+
+#[test]
+fn an_oversized_request_head_is_rejected() {
+    let dir = TestDir::new();
+    let address = spawn_server(dir.path());
+
+    let mut stream = TcpStream::connect(&address).unwrap();
+    // Exactly the 8 KiB cap, with no end to the headers in sight. Sending
+    // more than the server reads would make it reset the connection.
+    let head = "GET /trend HTTP/1.1\r\nX-Padding: ";
+    let padding = "a".repeat(8 * 1024 - head.len());
+    write!(stream, "{head}{padding}").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    assert!(
+        response.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"),
+        "{response}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn serve_command_answers_http_requests() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"pending"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let mut child = Command::new(cargo_bin())
+        .args(["serve", "--port", "0"])
+        .current_dir(path)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", path)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut banner = String::new();
+    BufReader::new(child.stderr.take().unwrap())
+        .read_line(&mut banner)
+        .unwrap();
+    let address = banner
+        .trim()
+        .strip_prefix("tdd-ratchet: serving status on http://")
+        .and_then(|rest| rest.strip_suffix('/'))
+        .unwrap_or_else(|| panic!("unexpected banner: {banner}"))
+        .to_string();
+
+    let mut stream = TcpStream::connect(&address).unwrap();
+    write!(stream, "GET /pending HTTP/1.1\r\nHost: {address}\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+    assert!(
+        response.contains("Content-Type: application/json"),
+        "{response}"
+    );
+    let body: Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+    assert_eq!(body["pending"][0]["name"], "feature");
+    dir.pass();
+}
@@ -0,0 +1,146 @@
+// tests/streamed_parsing.rs
+//
+// Story 71: nextest output is parsed line by line as it is read, so a suite
+// with hundreds of thousands of events is never held in memory whole.
+
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use tdd_ratchet::runner::{
+    CommandRunner, OutputParser, TestOutcome, TestResult, TestRunner, merge_retries,
+    parse_nextest_events_stream,
+};
+
+/// An endless libtest-json stream: one passing test after another.
+struct EndlessEvents {
+    next: usize,
+    pending: Vec<u8>,
+}
+
+impl Read for EndlessEvents {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            self.pending = format!(
+                "{{\"type\":\"test\",\"event\":\"started\",\"name\":\"big::t$test_{0}\"}}\n\
+                 {{\"type\":\"test\",\"event\":\"ok\",\"name\":\"big::t$test_{0}\",\"stdout\":\"noise\"}}\n",
+                self.next
+            )
+            .into_bytes();
+            self.next += 1;
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// A reader that hands out one byte per read.
+struct Trickle<R>(R);
+
+impl<R: Read> Read for Trickle<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(1);
+        self.0.read(&mut buf[..len])
+    }
+}
+
+fn results<R: BufRead>(reader: R) -> Vec<TestResult> {
+    parse_nextest_events_stream(reader)
+        .collect::<io::Result<_>>()
+        .unwrap()
+}
+
+#[test]
+fn stream_yields_each_finished_test() {
+    let output = "   Compiling my-crate v0.1.0\n\
+{\"type\":\"suite\",\"event\":\"started\",\"test_count\":2}\n\
+{\"type\":\"test\",\"event\":\"started\",\"name\":\"c::t$one\"}\n\
+{\"type\":\"test\",\"event\":\"ok\",\"name\":\"c::t$one\",\"stdout\":\"fine\"}\n\
+{\"type\":\"test\",\"event\":\"failed\",\"name\":\"c::t$two\",\"stdout\":\"boom\\nat line 3\"}\r\n\
+{\"type\":\"test\",\"event\":\"ignored\",\"name\":\"c::t$three\"}";
+
+    assert_eq!(
+        results(Cursor::new(output)),
+        [
+            TestResult {
+                name: "c::t$one".into(),
+                outcome: TestOutcome::Passed,
+                output: None,
+                exec_time_ms: None,
+            },
+            TestResult {
+                name: "c::t$two".into(),
+                outcome: TestOutcome::Failed,
+                output: Some("boom\nat line 3".into()),
+                exec_time_ms: None,
+            },
+            TestResult {
+                name: "c::t$three".into(),
+                outcome: TestOutcome::Ignored,
+                output: None,
+                exec_time_ms: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn stream_reports_every_retry_attempt() {
+    let output = "{\"type\":\"test\",\"event\":\"failed\",\"name\":\"c::t$wobbly\"}\n\
+{\"type\":\"test\",\"event\":\"ok\",\"name\":\"c::t$wobbly\"}\n";
+
+    let attempts = results(Cursor::new(output));
+    assert_eq!(attempts.len(), 2);
+
+    let merged = merge_retries(attempts);
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].outcome, TestOutcome::Flaky);
+}
+
+#[test]
+fn stream_handles_lines_split_across_reads() {
+    let output = "{\"type\":\"test\",\"event\":\"ok\",\"name\":\"c::t$one\"}\n\
+{\"type\":\"test\",\"event\":\"failed\",\"name\":\"c::t$two\",\"stdout\":\"é\"}\n";
+
+    let parsed = results(BufReader::new(Trickle(Cursor::new(output))));
+
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[1].output.as_deref(), Some("é"));
+}
+
+#[test]
+fn stream_is_lazy_over_an_endless_run() {
+    let events = EndlessEvents {
+        next: 0,
+        pending: Vec::new(),
+    };
+    let parsed: Vec<TestResult> = parse_nextest_events_stream(BufReader::new(events))
+        .skip(200_000)
+        .take(2)
+        .collect::<io::Result<_>>()
+        .unwrap();
+
+    assert_eq!(parsed[0].name, "big::t$test_200000");
+    assert_eq!(parsed[1].name, "big::t$test_200001");
+    assert!(parsed.iter().all(|r| r.output.is_none()));
+}
+
+#[test]
+fn command_runner_streams_libtest_json_output() {
+    let script = "for i in $(seq 1 2000); do \
+        echo '{\"type\":\"test\",\"event\":\"ok\",\"name\":\"c::t$test_'$i'\"}'; \
+        done; \
+        echo '{\"type\":\"test\",\"event\":\"failed\",\"name\":\"c::t$test_1\",\"stdout\":\"retry\"}'";
+    let runner = CommandRunner::new(
+        &["sh".into(), "-c".into(), script.into()],
+        ".",
+        OutputParser::LibtestJson,
+    )
+    .unwrap();
+
+    let results = runner.run().unwrap();
+
+    assert_eq!(results.len(), 2000);
+    assert_eq!(results[0].name, "c::t$test_1");
+    assert_eq!(results[0].outcome, TestOutcome::Failed);
+    assert_eq!(results[0].output.as_deref(), Some("retry"));
+}
@@ -0,0 +1,134 @@
+// tests/strictness.rs
+//
+// Story 79: `strictness = "relaxed" | "standard" | "strict"` at the top of
+// `ratchet.toml` sets every `[rules]` setting the table leaves out, so a
+// team can adopt the ratchet a step at a time.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, Rule, RuleLevel, RulesConfig, Strictness};
+
+fn rules(toml: &str) -> RulesConfig {
+    RatchetConfig::parse_from_str(toml, Path::new("ratchet.toml"))
+        .unwrap()
+        .rules
+}
+
+fn levels(rules: &RulesConfig) -> Vec<(Rule, RuleLevel)> {
+    Rule::ALL.map(|rule| (rule, rules.level(rule))).to_vec()
+}
+
+#[test]
+fn standard_is_the_defaults() {
+    let standard = rules("strictness = \"standard\"\n");
+    let default = RulesConfig::default();
+
+    assert_eq!(levels(&standard), levels(&default));
+    assert_eq!(
+        standard.promotion_must_not_edit_test,
+        default.promotion_must_not_edit_test
+    );
+    assert_eq!(
+        standard.promoted_tests_must_assert,
+        default.promoted_tests_must_assert
+    );
+    assert_eq!(standard.max_promotions_per_commit, None);
+    assert_eq!(standard.max_pending, None);
+    assert_eq!(rules(""), default);
+}
+
+#[test]
+fn strict_turns_on_every_check() {
+    let strict = rules("strictness = \"strict\"\n");
+
+    for rule in Rule::ALL {
+        assert_eq!(strict.level(rule), RuleLevel::Deny, "{rule}");
+    }
+    assert!(strict.promotion_must_not_edit_test);
+    assert!(strict.promoted_tests_must_assert);
+    assert_eq!(strict.max_promotions_per_commit, Some(1));
+}
+
+#[test]
+fn relaxed_only_fails_on_regressions_and_a_missing_gatekeeper() {
+    let relaxed = rules("strictness = \"relaxed\"\n");
+
+    assert_eq!(relaxed.level(Rule::Regression), RuleLevel::Deny);
+    assert_eq!(relaxed.level(Rule::MissingGatekeeper), RuleLevel::Deny);
+    assert_eq!(relaxed.level(Rule::NewTestPassed), RuleLevel::Warn);
+    assert_eq!(relaxed.level(Rule::SkippedPending), RuleLevel::Warn);
+    assert_eq!(relaxed.level(Rule::TestDisappeared), RuleLevel::Warn);
+    assert_eq!(relaxed.level(Rule::PassingToIgnored), RuleLevel::Warn);
+    assert_eq!(relaxed.level(Rule::DirtyWorktree), RuleLevel::Allow);
+    assert!(!relaxed.promotion_must_not_edit_test);
+    assert_eq!(
+        levels(&relaxed),
+        levels(&Strictness::Relaxed.rules()),
+        "the preset is applied as written"
+    );
+}
+
+#[test]
+fn rules_set_in_the_table_override_the_preset() {
+    let strict = rules(
+        "strictness = \"strict\"\n\n[rules]\nmax-promotions-per-commit = 3\n\
+         dirty-worktree = \"warn\"\npromoted-tests-must-assert = false\n",
+    );
+
+    assert_eq!(strict.max_promotions_per_commit, Some(3));
+    assert_eq!(strict.level(Rule::DirtyWorktree), RuleLevel::Warn);
+    assert!(!strict.promoted_tests_must_assert);
+    assert!(strict.promotion_must_not_edit_test);
+    assert_eq!(strict.level(Rule::PassingToIgnored), RuleLevel::Deny);
+}
+
+#[test]
+fn unknown_presets_are_rejected() {
+    let err =
+        RatchetConfig::parse_from_str("strictness = \"extreme\"\n", Path::new("ratchet.toml"))
+            .unwrap_err();
+
+    assert!(err.to_string().contains("extreme"), "{err}");
+}
+
+#[test]
+fn relaxed_projects_warn_about_new_passing_tests() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "strictness = \"relaxed\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\ntest shortcut ... ok\n",
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("NewTestPassed: shortcut"), "{out}");
+    dir.pass();
+}
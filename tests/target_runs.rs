@@ -0,0 +1,129 @@
+// tests/target_runs.rs
+//
+// Story 96: `--release` and `--target <TRIPLE>` are passed on to nextest,
+// and with `[state] per-target` each target's tests have a status file and
+// pending/passing lifecycle of their own.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet_with_path};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tdd_ratchet::affected::{Affected, WorkspacePackage, affected_packages};
+use tdd_ratchet::config::{NextestConfig, RatchetConfig};
+use tdd_ratchet::status::{is_status_file_name, target_status_file_name};
+
+const TARGET: &str = "wasm32-unknown-unknown";
+
+#[test]
+fn release_and_target_are_nextest_arguments() {
+    let nextest = NextestConfig {
+        release: true,
+        target: Some(TARGET.into()),
+        ..NextestConfig::default()
+    };
+    assert_eq!(nextest.args(), ["--release", "--target", TARGET]);
+}
+
+#[test]
+fn per_target_runs_use_their_own_status_file() {
+    let mut config =
+        RatchetConfig::parse_from_str("[state]\nper-target = true\n", Path::new("ratchet.toml"))
+            .unwrap();
+    let root = Path::new("crates/core/.test-status.json");
+    assert_eq!(config.run_status_path(root), root);
+
+    config.nextest.target = Some(TARGET.into());
+    assert_eq!(
+        config.run_status_path(root),
+        Path::new("crates/core/.test-status.wasm32-unknown-unknown.json")
+    );
+
+    config.state.per_target = false;
+    assert_eq!(config.run_status_path(root), root);
+}
+
+#[test]
+fn target_status_files_are_the_ratchets_own() {
+    let name = target_status_file_name(TARGET);
+    assert!(is_status_file_name(&name));
+    assert!(is_status_file_name(".test-status.json"));
+    assert!(!is_status_file_name("status.json"));
+
+    let packages = [WorkspacePackage {
+        name: "app".into(),
+        dir: "app".into(),
+        dependencies: Vec::new(),
+    }];
+    assert_eq!(
+        affected_packages(&packages, &[name]),
+        Affected::Packages(Default::default())
+    );
+}
+
+#[test]
+fn a_target_run_tracks_its_tests_apart_from_the_host_run() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let project = dir.path().join("project");
+    let bin = dir.path().join("bin");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(&bin).unwrap();
+    let log = dir.path().join("cargo-args.log");
+
+    // A stand-in for cargo that records its arguments and runs a different
+    // (still failing, so pending) test on the wasm target than on the host.
+    let cargo = bin.join("cargo");
+    fs::write(
+        &cargo,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n\
+             if [ -n \"$TDD_RATCHET\" ]; then event=ok; else event=failed; fi\n\
+             echo '{{\"type\":\"test\",\"event\":\"'$event'\",\"name\":\"app::gatekeeper$tdd_ratchet_gatekeeper\"}}'\n\
+             case \"$*\" in\n\
+             *--target*) echo '{{\"type\":\"test\",\"event\":\"failed\",\"name\":\"app$wasm_only\"}}' ;;\n\
+             *) echo '{{\"type\":\"test\",\"event\":\"failed\",\"name\":\"app$native\"}}' ;;\n\
+             esac\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&cargo, fs::Permissions::from_mode(0o755)).unwrap();
+
+    git(&project, &["init", "-b", "main"]);
+    git(&project, &["config", "user.email", "test@test.com"]);
+    git(&project, &["config", "user.name", "Test"]);
+    fs::write(
+        project.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n[state]\nper-target = true\n",
+    )
+    .unwrap();
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet_with_path(&project, &bin, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Track host tests"]);
+    let host_status = fs::read_to_string(project.join(".test-status.json")).unwrap();
+
+    let (code, out) = run_ratchet_with_path(&project, &bin, &["--release", "--target", TARGET]);
+    assert_eq!(code, Some(0), "{out}");
+
+    let calls = fs::read_to_string(&log).unwrap();
+    assert!(
+        calls
+            .lines()
+            .any(|line| line.starts_with("nextest run") && line.contains("--release --target")),
+        "{calls}"
+    );
+    assert_eq!(
+        fs::read_to_string(project.join(".test-status.json")).unwrap(),
+        host_status
+    );
+    let target_status = fs::read_to_string(project.join(target_status_file_name(TARGET))).unwrap();
+    assert!(target_status.contains("wasm_only"), "{target_status}");
+    assert!(!target_status.contains("native"), "{target_status}");
+    dir.pass();
+}
@@ -0,0 +1,157 @@
+// tests/test_edits.rs
+//
+// Story 29: Optional rule — the commit that promotes a test to passing must
+// not also change that test's body.
+
+mod common;
+
+use common::{TestDir, git};
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RulesConfig};
+use tdd_ratchet::history::{GitHistory, HistoryProvider};
+use tdd_ratchet::test_edits::{TestEdit, promotions, test_fn_body, test_fn_name};
+
+fn commit_files(dir: &Path, status: &str, test_source: &str, msg: &str) {
+    fs::create_dir_all(dir.join("tests")).unwrap();
+    fs::write(dir.join(".test-status.json"), status).unwrap();
+    fs::write(dir.join("tests/feature.rs"), test_source).unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-m", msg]);
+}
+
+const STRICT_TEST: &str = r#"
+#[test]
+fn adds_numbers() {
+    assert_eq!(add(2, 2), 4);
+}
+"#;
+
+const WEAKENED_TEST: &str = r#"
+#[test]
+fn adds_numbers() {
+    assert!(true);
+}
+"#;
+
+fn edits_for_promotions(dir: &Path) -> Vec<TestEdit> {
+    let history = GitHistory::new(dir);
+    let snapshots = history.snapshots().unwrap();
+    promotions(&snapshots)
+        .into_iter()
+        .flat_map(|(test, commit)| history.edited_tests(&commit, &[test]).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_fn_name_takes_last_path_segment() {
+    assert_eq!(
+        test_fn_name("my-crate::feature$adds_numbers"),
+        "adds_numbers"
+    );
+    assert_eq!(
+        test_fn_name("my-crate$math::tests::adds_numbers"),
+        "adds_numbers"
+    );
+    assert_eq!(test_fn_name("plain"), "plain");
+}
+
+#[test]
+fn test_fn_body_finds_test_functions_only() {
+    let source = r#"
+fn adds_numbers_helper() { 1 }
+
+fn adds_numbers() { not_a_test() }
+
+#[test]
+fn adds_numbers() {
+    let s = "}";
+    let c = '}';
+    // }
+    assert_eq!(s.len(), 1);
+}
+"#;
+    let body = test_fn_body(source, "adds_numbers").expect("test fn body");
+    assert!(body.starts_with('{') && body.ends_with('}'), "{body}");
+    assert!(body.contains("assert_eq!(s.len(), 1);"), "{body}");
+    assert!(test_fn_body(source, "adds_numbers_helper").is_none());
+}
+
+#[test]
+fn test_fn_body_skips_raw_strings_with_braces() {
+    let source = "#[tokio::test]\nasync fn parses() {\n    let json = r#\"{\"a\":}\"#;\n    check(json);\n}\n";
+    let body = test_fn_body(source, "parses").expect("test fn body");
+    assert!(body.contains("check(json);"), "{body}");
+}
+
+#[test]
+fn promotion_that_weakens_the_test_is_flagged() {
+    let dir = TestDir::new();
+    git(dir.path(), &["init"]);
+    git(dir.path(), &["config", "user.email", "test@test.com"]);
+    git(dir.path(), &["config", "user.name", "Test"]);
+
+    commit_files(
+        dir.path(),
+        r#"{"tests":{"my-crate::feature$adds_numbers":"pending"}}"#,
+        STRICT_TEST,
+        "Add failing test",
+    );
+    commit_files(
+        dir.path(),
+        r#"{"tests":{"my-crate::feature$adds_numbers":"passing"}}"#,
+        WEAKENED_TEST,
+        "Make it pass",
+    );
+
+    let edits = edits_for_promotions(dir.path());
+    assert_eq!(edits.len(), 1, "{edits:?}");
+    assert_eq!(edits[0].test, "my-crate::feature$adds_numbers");
+    assert_eq!(edits[0].path, "tests/feature.rs");
+    dir.pass();
+}
+
+#[test]
+fn promotion_with_untouched_test_is_not_flagged() {
+    let dir = TestDir::new();
+    git(dir.path(), &["init"]);
+    git(dir.path(), &["config", "user.email", "test@test.com"]);
+    git(dir.path(), &["config", "user.name", "Test"]);
+
+    commit_files(
+        dir.path(),
+        r#"{"tests":{"my-crate::feature$adds_numbers":"pending"}}"#,
+        STRICT_TEST,
+        "Add failing test",
+    );
+    // Reformatting the test and adding the implementation is fine.
+    commit_files(
+        dir.path(),
+        r#"{"tests":{"my-crate::feature$adds_numbers":"passing"}}"#,
+        &format!(
+            "{}\nfn add(a: i32, b: i32) -> i32 {{ a + b }}\n",
+            STRICT_TEST.replace("    assert_eq!", "        assert_eq!")
+        ),
+        "Implement add",
+    );
+
+    assert_eq!(edits_for_promotions(dir.path()), vec![]);
+    dir.pass();
+}
+
+#[test]
+fn rule_is_configured_under_rules_table() {
+    let config = RatchetConfig::parse_from_str(
+        "[rules]\npromotion-must-not-edit-test = true\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(
+        config.rules,
+        RulesConfig {
+            promotion_must_not_edit_test: true,
+            ..RulesConfig::default()
+        }
+    );
+    assert!(!RatchetConfig::default().rules.promotion_must_not_edit_test);
+}
@@ -0,0 +1,169 @@
+// tests/test_locations.rs
+//
+// Story 43: Violation messages point at the file and line defining the test,
+// so they can be clicked straight to the source.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, git};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::ratchet::{EvalResult, Violation};
+use tdd_ratchet::status::StatusFile;
+use tdd_ratchet::test_locations::{TestLocation, annotate_violations, locate_tests};
+
+fn write(dir: &Path, path: &str, contents: &str) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn location(path: &str, line: usize) -> TestLocation {
+    TestLocation {
+        path: PathBuf::from(path),
+        line,
+        owners: Vec::new(),
+    }
+}
+
+const CHEATER: &str = "use std::fs;\n\n#[test]\nfn cheater_test() {\n    assert!(true);\n}\n";
+
+#[test]
+fn tests_are_found_in_tests_and_src() {
+    let dir = TestDir::new();
+    write(dir.path(), "tests/cheater.rs", CHEATER);
+    write(
+        dir.path(),
+        "src/parser/mod.rs",
+        "fn helper() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn parses() {}\n}\n",
+    );
+
+    let locations = locate_tests(
+        dir.path(),
+        [
+            "my-crate::cheater$cheater_test",
+            "parser::tests::parses",
+            "missing_test",
+        ],
+    );
+
+    assert_eq!(
+        locations["my-crate::cheater$cheater_test"],
+        location("tests/cheater.rs", 4)
+    );
+    assert_eq!(
+        locations["parser::tests::parses"],
+        location("src/parser/mod.rs", 6)
+    );
+    assert!(!locations.contains_key("missing_test"));
+    dir.pass();
+}
+
+#[test]
+fn functions_without_a_test_attribute_are_not_matched() {
+    let dir = TestDir::new();
+    write(dir.path(), "src/lib.rs", "fn cheater_test() {}\n");
+    write(dir.path(), "tests/cheater.rs", CHEATER);
+
+    let locations = locate_tests(dir.path(), ["cheater_test"]);
+
+    assert_eq!(locations["cheater_test"], location("tests/cheater.rs", 4));
+    dir.pass();
+}
+
+#[test]
+fn same_named_tests_are_told_apart_by_binary() {
+    let dir = TestDir::new();
+    write(dir.path(), "tests/alpha.rs", "#[test]\nfn shared() {}\n");
+    write(dir.path(), "tests/beta.rs", "\n#[test]\nfn shared() {}\n");
+
+    let locations = locate_tests(
+        dir.path(),
+        ["my-crate::alpha$shared", "my-crate::beta$shared", "shared"],
+    );
+
+    assert_eq!(
+        locations["my-crate::alpha$shared"],
+        location("tests/alpha.rs", 2)
+    );
+    assert_eq!(
+        locations["my-crate::beta$shared"],
+        location("tests/beta.rs", 3)
+    );
+    assert!(
+        !locations.contains_key("shared"),
+        "an ambiguous name has no location"
+    );
+    dir.pass();
+}
+
+#[test]
+fn report_prefixes_violations_with_their_location() {
+    let dir = TestDir::new();
+    write(dir.path(), "tests/cheater.rs", CHEATER);
+    let mut result = EvalResult {
+        violations: vec![Violation::NewTestPassed {
+            test: "cheater_test".into(),
+        }],
+        warnings: Vec::new(),
+        updated: StatusFile::empty(),
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    };
+
+    annotate_violations(&mut result, dir.path(), Path::new("crates/app"));
+    let report = format_report(&result);
+
+    assert!(
+        report.contains(
+            "New test passed without failing first: crates/app/tests/cheater.rs:4 cheater_test"
+        ),
+        "{report}"
+    );
+    dir.pass();
+}
+
+#[test]
+fn ratchet_output_names_the_cheating_tests_file_and_line() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    write(
+        path,
+        "ratchet.toml",
+        "[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    );
+    write(path, "tests/cheater.rs", CHEATER);
+    write(
+        path,
+        "results.txt",
+        "test tdd_ratchet_gatekeeper ... ok\ntest cheater_test ... ok\n",
+    );
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Add cheater"]);
+
+    let output = Command::new(cargo_bin())
+        .current_dir(path)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("tests/cheater.rs:4 cheater_test"),
+        "{stderr}"
+    );
+    dir.pass();
+}
@@ -3,7 +3,12 @@
 // Stories 2, 3: The ratchet invokes cargo nextest and parses per-test results
 // from libtest-json structured output.
 
-use tdd_ratchet::runner::{TestOutcome, TestResult, parse_nextest_output};
+use std::path::Path;
+use tdd_ratchet::runner::{
+    CommandRunner, OutputParser, RunProgress, TestOutcome, TestResult, TestRunner,
+    parse_go_test_json, parse_jest_json, parse_libtest_output, parse_nextest_line,
+    parse_nextest_output, parse_pytest_output,
+};
 
 #[test]
 fn parses_mixed_pass_and_fail() {
@@ -22,21 +27,27 @@ fn parses_mixed_pass_and_fail() {
         results[0],
         TestResult {
             name: "my-crate::tests$test_one".into(),
-            outcome: TestOutcome::Passed
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: Some(1),
         }
     );
     assert_eq!(
         results[1],
         TestResult {
             name: "my-crate::tests$test_two".into(),
-            outcome: TestOutcome::Failed
+            outcome: TestOutcome::Failed,
+            output: Some("assertion failed".into()),
+            exec_time_ms: Some(2),
         }
     );
     assert_eq!(
         results[2],
         TestResult {
             name: "my-crate::tests$test_three".into(),
-            outcome: TestOutcome::Passed
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: Some(1),
         }
     );
 }
@@ -93,7 +104,9 @@ fn ignored_tests_are_tracked_as_ignored() {
         results[1],
         TestResult {
             name: "my-crate::lib$slow_test".into(),
-            outcome: TestOutcome::Ignored
+            outcome: TestOutcome::Ignored,
+            output: None,
+            exec_time_ms: None,
         }
     );
 }
@@ -135,7 +148,206 @@ fn multiple_suites_combined() {
         results[2],
         TestResult {
             name: "my-crate::integration$test_b".into(),
-            outcome: TestOutcome::Failed
+            outcome: TestOutcome::Failed,
+            output: Some("boom".into()),
+            exec_time_ms: Some(2),
         }
     );
 }
+
+#[test]
+fn single_lines_parse_incrementally() {
+    assert_eq!(
+        parse_nextest_line(r#"{"type":"test","event":"ok","name":"my-crate::lib$alpha"}"#),
+        Some(TestResult {
+            name: "my-crate::lib$alpha".into(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        })
+    );
+    assert_eq!(
+        parse_nextest_line(r#"{"type":"test","event":"started","name":"my-crate::lib$alpha"}"#),
+        None
+    );
+    assert_eq!(parse_nextest_line("   Compiling my-crate v0.1.0"), None);
+}
+
+#[test]
+fn progress_counts_expected_pending_failures_separately() {
+    let mut progress = RunProgress::default();
+    let failed = TestResult {
+        name: "my-crate::lib$wip".into(),
+        outcome: TestOutcome::Failed,
+        output: None,
+        exec_time_ms: None,
+    };
+    let passed = TestResult {
+        name: "my-crate::lib$done".into(),
+        outcome: TestOutcome::Passed,
+        output: None,
+        exec_time_ms: None,
+    };
+
+    progress.record(&passed, false);
+    progress.record(&failed, true);
+    progress.record(&failed, false);
+
+    assert_eq!(progress.passed, 1);
+    assert_eq!(progress.pending_expected, 1);
+    assert_eq!(progress.failed, 1);
+    assert_eq!(
+        progress.summary_line(),
+        "1 passed / 1 failed / 1 pending expected"
+    );
+}
+
+#[test]
+fn plain_libtest_output_is_parsed() {
+    let output = "running 3 tests
+test tests::alpha ... ok
+test tests::beta ... FAILED
+test tests::gamma ... ignored, slow
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+";
+    let results = parse_libtest_output(output);
+    assert_eq!(
+        results,
+        vec![
+            TestResult {
+                name: "tests::alpha".into(),
+                outcome: TestOutcome::Passed,
+                output: None,
+                exec_time_ms: None,
+            },
+            TestResult {
+                name: "tests::beta".into(),
+                outcome: TestOutcome::Failed,
+                output: None,
+                exec_time_ms: None,
+            },
+            TestResult {
+                name: "tests::gamma".into(),
+                outcome: TestOutcome::Ignored,
+                output: None,
+                exec_time_ms: None,
+            },
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn command_runner_parses_the_commands_stdout() {
+    let command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "test \"$TDD_RATCHET\" = 1 && echo 'test ext::works ... ok'".to_string(),
+    ];
+    let runner = CommandRunner::new(&command, ".", OutputParser::Libtest).unwrap();
+
+    let results = runner.run().unwrap();
+
+    assert_eq!(
+        results,
+        vec![TestResult {
+            name: "ext::works".into(),
+            outcome: TestOutcome::Passed,
+            output: None,
+            exec_time_ms: None,
+        }]
+    );
+}
+
+#[test]
+fn pytest_verbose_output_is_normalized_into_the_status_namespace() {
+    let output = "============================= test session starts ==============================
+collected 4 items
+
+tests/test_api.py::test_create PASSED                                     [ 25%]
+tests/test_api.py::TestUsers::test_delete FAILED                          [ 50%]
+tests/test_api.py::test_param[a b] SKIPPED (not ready)                    [ 75%]
+tests/test_api.py::test_known_bug XFAIL                                   [100%]
+
+=========================== short test summary info ============================
+FAILED tests/test_api.py::TestUsers::test_delete - assert 1 == 2
+==================== 1 failed, 1 passed, 1 skipped, 1 xfailed in 0.05s =========
+";
+    let results = parse_pytest_output(output);
+    let summary: Vec<(&str, TestOutcome)> = results
+        .iter()
+        .map(|r| (r.name.as_str(), r.outcome))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("pytest::tests/test_api.py$test_create", TestOutcome::Passed),
+            (
+                "pytest::tests/test_api.py$TestUsers::test_delete",
+                TestOutcome::Failed
+            ),
+            (
+                "pytest::tests/test_api.py$test_param[a b]",
+                TestOutcome::Ignored
+            ),
+            (
+                "pytest::tests/test_api.py$test_known_bug",
+                TestOutcome::Ignored
+            ),
+        ]
+    );
+}
+
+#[test]
+fn jest_json_report_maps_file_and_titles_to_stable_names() {
+    let output = r#"Determining test suites to run...
+{"numFailedTests":1,"testResults":[{"name":"/work/app/src/sum.test.ts","status":"failed",
+"assertionResults":[
+ {"ancestorTitles":["sum"],"fullName":"sum adds","title":"adds","status":"passed"},
+ {"ancestorTitles":["sum","edge cases"],"fullName":"sum edge cases overflows","title":"overflows","status":"failed"},
+ {"ancestorTitles":[],"fullName":"later","title":"later","status":"todo"}
+]}]}
+"#;
+    let results = parse_jest_json(output, Path::new("/work/app"));
+    let summary: Vec<(&str, TestOutcome)> = results
+        .iter()
+        .map(|r| (r.name.as_str(), r.outcome))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("js::src/sum.test.ts$sum › adds", TestOutcome::Passed),
+            (
+                "js::src/sum.test.ts$sum › edge cases › overflows",
+                TestOutcome::Failed
+            ),
+            ("js::src/sum.test.ts$later", TestOutcome::Ignored),
+        ]
+    );
+}
+
+#[test]
+fn go_test_json_events_become_namespaced_results() {
+    let output = r#"{"Action":"start","Package":"example.com/calc"}
+{"Action":"run","Package":"example.com/calc","Test":"TestAdd"}
+{"Action":"output","Package":"example.com/calc","Test":"TestAdd","Output":"=== RUN   TestAdd\n"}
+{"Action":"pass","Package":"example.com/calc","Test":"TestAdd","Elapsed":0}
+{"Action":"run","Package":"example.com/calc","Test":"TestDiv/by_zero"}
+{"Action":"fail","Package":"example.com/calc","Test":"TestDiv/by_zero","Elapsed":0}
+{"Action":"skip","Package":"example.com/calc","Test":"TestSlow","Elapsed":0}
+{"Action":"fail","Package":"example.com/calc","Elapsed":0.01}
+"#;
+    let results = parse_go_test_json(output);
+    let summary: Vec<(&str, TestOutcome)> = results
+        .iter()
+        .map(|r| (r.name.as_str(), r.outcome))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("go::example.com/calc$TestAdd", TestOutcome::Passed),
+            ("go::example.com/calc$TestDiv/by_zero", TestOutcome::Failed),
+            ("go::example.com/calc$TestSlow", TestOutcome::Ignored),
+        ]
+    );
+}
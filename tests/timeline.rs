@@ -0,0 +1,82 @@
+// tests/timeline.rs
+//
+// Story 19: Per-test timeline of committed state changes.
+
+mod common;
+
+use common::status;
+use std::collections::BTreeMap;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::status::TestState;
+use tdd_ratchet::timeline::{format_date, format_timeline, test_timeline};
+
+fn snapshot(
+    commit: &str,
+    tests: &[(&str, TestState)],
+    renames: &[(&str, &str)],
+) -> HistorySnapshot {
+    let mut status = status(tests);
+    status.renames = renames
+        .iter()
+        .map(|(new, old)| (new.to_string(), old.to_string()))
+        .collect::<BTreeMap<_, _>>();
+    HistorySnapshot {
+        info: CommitInfo {
+            author: "Ada".into(),
+            time: 86_400,
+            summary: format!("commit {commit}"),
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status)
+    }
+}
+
+#[test]
+fn timeline_reports_pending_then_promotion() {
+    let snapshots = vec![
+        snapshot("aaaaaaaa11", &[("t", TestState::Pending)], &[]),
+        snapshot(
+            "bbbbbbbb22",
+            &[("t", TestState::Pending), ("u", TestState::Pending)],
+            &[],
+        ),
+        snapshot("cccccccc33", &[("t", TestState::Passing)], &[]),
+    ];
+
+    let timeline = test_timeline(&snapshots, "t");
+    let described: Vec<String> = timeline.iter().map(|c| c.describe()).collect();
+    assert_eq!(described, vec!["added as pending", "promoted to passing"]);
+
+    let rendered = format_timeline("t", &timeline);
+    assert!(
+        rendered.contains("aaaaaaaa  1970-01-02  Ada  added as pending  \"commit aaaaaaaa11\""),
+        "{rendered}"
+    );
+    assert!(rendered.contains("cccccccc"), "{rendered}");
+}
+
+#[test]
+fn timeline_follows_committed_renames() {
+    let snapshots = vec![
+        snapshot("c1", &[("old", TestState::Pending)], &[]),
+        snapshot("c2", &[("new", TestState::Pending)], &[("new", "old")]),
+        snapshot("c3", &[("new", TestState::Passing)], &[]),
+    ];
+
+    let timeline = test_timeline(&snapshots, "new");
+    let described: Vec<String> = timeline.iter().map(|c| c.describe()).collect();
+    assert_eq!(
+        described,
+        vec![
+            "added as pending",
+            "renamed from old (still pending)",
+            "promoted to passing"
+        ]
+    );
+}
+
+#[test]
+fn dates_are_formatted_in_utc() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(1_700_000_000), "2023-11-14");
+}
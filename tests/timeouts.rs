@@ -0,0 +1,112 @@
+// tests/timeouts.rs
+//
+// Story 40: A test run that exceeds the configured wall-clock timeout is
+// stopped and reported as timed out, not as a wall of disappeared tests.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, cargo_bin, git};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tdd_ratchet::config::{RatchetConfig, TimeoutsConfig};
+use tdd_ratchet::runner::{CommandRunner, OutputParser, RunnerError, TestRunner};
+
+fn sh(script: &str) -> Vec<String> {
+    vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+}
+
+#[test]
+fn timeouts_are_configured_in_seconds() {
+    let config = RatchetConfig::parse_from_str(
+        "[timeouts]\nrun = 1800\ntest = 120\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert_eq!(
+        config.timeouts,
+        TimeoutsConfig {
+            run: Some(1800),
+            test: Some(120),
+        }
+    );
+    assert_eq!(RatchetConfig::default().timeouts, TimeoutsConfig::default());
+}
+
+#[cfg(unix)]
+#[test]
+fn command_runner_is_stopped_at_its_deadline() {
+    let runner = CommandRunner::new(
+        &sh("echo 'test fast ... ok'; exec sleep 30"),
+        ".",
+        OutputParser::Libtest,
+    )
+    .unwrap()
+    .with_deadline(Instant::now() + Duration::from_millis(300));
+
+    let started = Instant::now();
+    let err = runner.run().unwrap_err();
+
+    assert!(matches!(err, RunnerError::TimedOut { .. }), "{err}");
+    assert!(
+        err.to_string()
+            .contains("run timed out, results incomplete")
+    );
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[cfg(unix)]
+#[test]
+fn command_runner_finishing_before_its_deadline_is_unaffected() {
+    let runner = CommandRunner::new(&sh("echo 'test fast ... ok'"), ".", OutputParser::Libtest)
+        .unwrap()
+        .with_deadline(Instant::now() + Duration::from_secs(30));
+
+    assert_eq!(runner.run().unwrap().len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn timed_out_run_is_reported_once_and_leaves_the_status_file_alone() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[timeouts]\nrun = 1\n\n[[runners]]\nkind = \"command\"\ncommand = [\"sh\", \"-c\", \"cat results.txt; exec sleep 30\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\n",
+    )
+    .unwrap();
+    let status = r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","a":"passing","b":"passing"}}"#;
+    fs::write(path.join(".test-status.json"), status).unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Track tests"]);
+
+    let output = Command::new(cargo_bin())
+        .current_dir(path)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("run timed out, results incomplete"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("missing from the run"), "{stderr}");
+    assert_eq!(
+        fs::read_to_string(path.join(".test-status.json")).unwrap(),
+        status
+    );
+    dir.pass();
+}
@@ -0,0 +1,234 @@
+// tests/toolchain_fingerprints.rs
+//
+// Story 107: with `[state] toolchain`, the status file records the rustc
+// version, nextest version, and target triple of the run that changed each
+// test's state, and a regression under a different toolchain than the
+// promotion is flagged as possible toolchain drift.
+
+mod common;
+
+use common::{
+    TestDir, build_ratchet_binary, cargo_home, combined_output, git, ratchet_command, result,
+    rustup_home,
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::pipeline::GatheredRun;
+use tdd_ratchet::ratchet::{Warning, record_toolchain, record_transitions};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::schema::validate_status_json;
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, Toolchain, TrackedStatus};
+use tdd_ratchet::toolchain::{parse_nextest_version, parse_rustc_version};
+
+const GATEKEEPER: &str = "tdd_ratchet_gatekeeper";
+
+fn toolchain(rustc: &str) -> Toolchain {
+    Toolchain {
+        rustc: rustc.to_string(),
+        nextest: Some("0.9.72".to_string()),
+        target: "x86_64-unknown-linux-gnu".to_string(),
+    }
+}
+
+fn entry(state: TestState, toolchain: Option<Toolchain>) -> TestEntry {
+    TestEntry {
+        toolchain,
+        ..TestEntry::new(state)
+    }
+}
+
+/// `feature`, promoted under rustc `promoted_under`, fails in a run under
+/// rustc `current`.
+fn regressed(promoted_under: &str, current: &str) -> GatheredRun {
+    GatheredRun {
+        status: TrackedStatus::new(
+            [
+                (GATEKEEPER.to_string(), entry(TestState::Passing, None)),
+                (
+                    "feature".to_string(),
+                    entry(TestState::Passing, Some(toolchain(promoted_under))),
+                ),
+            ]
+            .into(),
+        ),
+        results: vec![
+            result(GATEKEEPER, TestOutcome::Passed),
+            result("feature", TestOutcome::Failed),
+        ],
+        toolchain: Some(toolchain(current)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn toolchain_versions_are_parsed_from_rustc_and_nextest() {
+    let rustc = "rustc 1.86.0 (05f9846f8 2025-03-31)\n\
+                 binary: rustc\n\
+                 commit-hash: 05f9846f893b09a1be1fc8560e33fc3c815cfecb\n\
+                 host: x86_64-unknown-linux-gnu\n\
+                 release: 1.86.0\n\
+                 LLVM version: 19.1.7\n";
+    assert_eq!(
+        parse_rustc_version(rustc),
+        Some(("1.86.0".to_string(), "x86_64-unknown-linux-gnu".to_string()))
+    );
+    assert_eq!(parse_rustc_version("rustc 1.86.0\n"), None);
+    assert_eq!(
+        parse_nextest_version("cargo-nextest 0.9.72 (9d9ad2e1e 2024-07-01)\n"),
+        Some("0.9.72".to_string())
+    );
+    assert_eq!(
+        parse_nextest_version("error: no such command: `nextest`"),
+        None
+    );
+}
+
+#[test]
+fn changed_entries_record_the_toolchain_and_others_keep_theirs() {
+    let before = TrackedStatus::new(
+        [
+            (
+                "steady".to_string(),
+                entry(TestState::Passing, Some(toolchain("1.80.0"))),
+            ),
+            ("promoted".to_string(), entry(TestState::Pending, None)),
+        ]
+        .into(),
+    );
+    let mut tests: BTreeMap<String, TestEntry> = [
+        (
+            "steady".to_string(),
+            entry(TestState::Passing, Some(toolchain("1.80.0"))),
+        ),
+        ("promoted".to_string(), entry(TestState::Passing, None)),
+        ("added".to_string(), entry(TestState::Pending, None)),
+        (
+            "renamed".to_string(),
+            entry(TestState::Passing, Some(toolchain("1.79.0"))),
+        ),
+    ]
+    .into();
+
+    record_toolchain(&mut tests, &before, Some(&toolchain("1.86.0")));
+
+    assert_eq!(tests["steady"].toolchain, Some(toolchain("1.80.0")));
+    assert_eq!(tests["promoted"].toolchain, Some(toolchain("1.86.0")));
+    assert_eq!(tests["added"].toolchain, Some(toolchain("1.86.0")));
+    assert_eq!(tests["renamed"].toolchain, Some(toolchain("1.79.0")));
+
+    let mut json = StatusFile::new(tests).to_json(Path::new("x")).unwrap();
+    assert!(validate_status_json(&json).is_empty(), "{json}");
+    json = json.replace("\"target\"", "\"triple\"");
+    assert!(!validate_status_json(&json).is_empty());
+}
+
+#[test]
+fn transitions_carry_the_toolchain_of_the_run_that_made_them() {
+    let snapshot = |commit: &str, state: TestState, rustc: &str| HistorySnapshot {
+        commit: commit.to_string(),
+        info: CommitInfo::default(),
+        status: StatusFile::new(
+            [("feature".to_string(), entry(state, Some(toolchain(rustc))))].into(),
+        ),
+    };
+    let history = vec![
+        snapshot("c1", TestState::Pending, "1.80.0"),
+        snapshot("c2", TestState::Passing, "1.86.0"),
+    ];
+    let mut tests: BTreeMap<String, TestEntry> =
+        [("feature".to_string(), entry(TestState::Passing, None))].into();
+
+    record_transitions(&mut tests, &history, 5);
+
+    let transitions = &tests["feature"].transitions;
+    assert_eq!(transitions[0].toolchain, Some(toolchain("1.80.0")));
+    assert_eq!(transitions[1].toolchain, Some(toolchain("1.86.0")));
+}
+
+#[test]
+fn regression_under_another_toolchain_is_flagged_as_possible_drift() {
+    let result = regressed("1.80.0", "1.86.0").evaluate();
+
+    assert_eq!(result.violations.len(), 1, "{:?}", result.violations);
+    assert!(result.warnings.iter().any(|w| matches!(
+        w,
+        Warning::ToolchainChanged { test, promoted, current }
+            if test == "feature" && promoted.rustc == "1.80.0" && current.rustc == "1.86.0"
+    )));
+    let report = format_report(&result);
+    assert!(
+        report.contains("regression under a different toolchain"),
+        "{report}"
+    );
+    assert!(
+        report.contains(
+            "feature regressed under rustc 1.86.0, nextest 0.9.72, x86_64-unknown-linux-gnu; \
+             it was promoted under rustc 1.80.0"
+        ),
+        "{report}"
+    );
+
+    let result = regressed("1.86.0", "1.86.0").evaluate();
+    assert_eq!(result.violations.len(), 1);
+    assert!(
+        !result
+            .warnings
+            .iter()
+            .any(|w| matches!(w, Warning::ToolchainChanged { .. }))
+    );
+}
+
+/// Run the ratchet with the real toolchain, which `rustc -vV` needs.
+fn run_ratchet(dir: &Path) -> (Option<i32>, String) {
+    combined_output(
+        ratchet_command(dir, &[])
+            .env("RUSTUP_HOME", rustup_home())
+            .env("CARGO_HOME", cargo_home()),
+    )
+}
+
+#[test]
+fn runs_record_the_toolchain_when_enabled() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [state]\ntoolchain = true\n\n\
+         [[runners]]\nkind = \"command\"\n\
+         command = [\"cat\", \"results.json\"]\nparser = \"libtest-json\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        format!(r#"{{"tests":{{"{GATEKEEPER}":"passing"}}}}"#),
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.json"),
+        format!(
+            "{}\n{}\n",
+            r#"{"type":"test","event":"ok","name":"tdd_ratchet_gatekeeper"}"#,
+            r#"{"type":"test","event":"failed","name":"parses"}"#
+        ),
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    let recorded = status.tests["parses"].toolchain.clone().expect("recorded");
+    assert!(!recorded.rustc.is_empty());
+    assert_eq!(recorded.nextest, None);
+    assert_eq!(status.tests[GATEKEEPER].toolchain, None);
+    dir.pass();
+}
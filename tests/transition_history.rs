@@ -0,0 +1,178 @@
+// tests/transition_history.rs
+//
+// Story 81: with `[state] transition-history`, each status entry keeps its
+// latest committed state changes, so `log` and `blame` work on shallow
+// clones.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet, status};
+use std::fs;
+use tdd_ratchet::history::{CommitInfo, HistorySnapshot};
+use tdd_ratchet::ratchet::record_transitions;
+use tdd_ratchet::status::{TestEntry, TestState, Transition};
+use tdd_ratchet::timeline::{format_timeline, test_timeline, with_recorded_transitions};
+
+fn snapshot(commit: &str, time: i64, feature: Option<TestState>) -> HistorySnapshot {
+    let tests: Vec<_> = feature
+        .map(|state| ("feature", state))
+        .into_iter()
+        .collect();
+    HistorySnapshot {
+        info: CommitInfo {
+            author: "Dev".into(),
+            time,
+            summary: format!("commit {commit}"),
+            ..CommitInfo::default()
+        },
+        ..common::snapshot(commit, status(&tests))
+    }
+}
+
+fn transition(from: Option<TestState>, state: TestState, commit: &str, time: i64) -> Transition {
+    Transition {
+        from,
+        state,
+        commit: commit.into(),
+        author: "Dev".into(),
+        time,
+        toolchain: None,
+    }
+}
+
+/// `feature` added as pending, promoted, demoted, then promoted again.
+fn history() -> Vec<HistorySnapshot> {
+    vec![
+        snapshot("c1", 100, Some(TestState::Pending)),
+        snapshot("c2", 200, Some(TestState::Passing)),
+        snapshot("c3", 300, Some(TestState::Pending)),
+        snapshot("c4", 400, Some(TestState::Passing)),
+        snapshot("c5", 500, Some(TestState::Passing)),
+    ]
+}
+
+fn passing_feature() -> std::collections::BTreeMap<String, TestEntry> {
+    [("feature".to_string(), TestEntry::new(TestState::Passing))].into()
+}
+
+#[test]
+fn entries_keep_their_latest_transitions_oldest_first() {
+    let mut tests = passing_feature();
+
+    record_transitions(&mut tests, &history(), 2);
+
+    assert_eq!(
+        tests["feature"].transitions,
+        [
+            transition(Some(TestState::Passing), TestState::Pending, "c3", 300),
+            transition(Some(TestState::Pending), TestState::Passing, "c4", 400),
+        ]
+    );
+}
+
+#[test]
+fn recorded_transitions_survive_a_shorter_history() {
+    let mut tests = passing_feature();
+    record_transitions(&mut tests, &history(), 5);
+    assert_eq!(tests["feature"].transitions.len(), 4);
+
+    // A shallow clone only reaches the last two commits.
+    record_transitions(&mut tests, &history()[3..], 5);
+
+    let commits: Vec<&str> = tests["feature"]
+        .transitions
+        .iter()
+        .map(|t| t.commit.as_str())
+        .collect();
+    assert_eq!(commits, ["c1", "c2", "c3", "c4"]);
+    assert_eq!(tests["feature"].transitions[0].from, None);
+
+    // Cut off at c5, the test only seems added there.
+    record_transitions(&mut tests, &history()[4..], 5);
+    assert_eq!(tests["feature"].transitions.len(), 4);
+}
+
+#[test]
+fn transitions_are_dropped_when_history_is_off() {
+    let mut tests = passing_feature();
+    record_transitions(&mut tests, &history(), 3);
+
+    record_transitions(&mut tests, &history(), 0);
+
+    assert!(tests["feature"].transitions.is_empty());
+}
+
+#[test]
+fn log_shows_recorded_transitions_the_history_does_not_reach() {
+    let mut tests = passing_feature();
+    record_transitions(&mut tests, &history(), 5);
+    let shallow = &history()[3..];
+
+    let timeline = with_recorded_transitions(
+        "feature",
+        tests.get("feature"),
+        test_timeline(shallow, "feature"),
+    );
+
+    let commits: Vec<&str> = timeline.iter().map(|c| c.commit.as_str()).collect();
+    assert_eq!(commits, ["c1", "c2", "c3", "c4"]);
+    let log = format_timeline("feature", &timeline);
+    assert!(
+        log.contains("c1  1970-01-01  Dev  added as pending\n"),
+        "{log}"
+    );
+    assert!(
+        log.contains("c4  1970-01-01  Dev  promoted to passing\n"),
+        "{log}"
+    );
+}
+
+#[test]
+fn log_works_on_a_shallow_clone() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let origin = dir.path().join("origin");
+    fs::create_dir(&origin).unwrap();
+    git(&origin, &["init", "-b", "main"]);
+    git(&origin, &["config", "user.email", "test@test.com"]);
+    git(&origin, &["config", "user.name", "Test"]);
+    fs::write(
+        origin.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [state]\ntransition-history = 5\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    git(&origin, &["add", "-A"]);
+    git(&origin, &["commit", "-m", "Initial project"]);
+    let commit_run = |results: &str, message: &str| {
+        fs::write(origin.join("results.txt"), results).unwrap();
+        let (code, out) = run_ratchet(&origin, &[]);
+        assert_eq!(code, Some(0), "{out}");
+        git(&origin, &["add", "-A"]);
+        git(&origin, &["commit", "-m", message]);
+    };
+    commit_run(
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... FAILED\n",
+        "Add feature test",
+    );
+    commit_run(
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+        "Implement feature",
+    );
+    commit_run(
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ok\n",
+        "Record transitions",
+    );
+    let status = fs::read_to_string(origin.join(".test-status.json")).unwrap();
+    assert!(status.contains(r#""transitions""#), "{status}");
+
+    let url = format!("file://{}", origin.display());
+    git(dir.path(), &["clone", "--depth", "1", &url, "shallow"]);
+    let shallow = dir.path().join("shallow");
+    let (code, out) = run_ratchet(&shallow, &["log", "feature"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("added as pending"), "{out}");
+    assert!(out.contains("promoted to passing"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,133 @@
+// tests/trend.rs
+//
+// Story 75: `cargo ratchet trend` prints passing and pending counts per
+// commit as JSON or CSV for burn-up charts, caching the counts of commits
+// it has already read.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, commit_status, git, run_ratchet};
+use std::path::Path;
+use tdd_ratchet::status::{STATUS_FILE_NAME, StatusFile, TestEntry, TestState};
+use tdd_ratchet::trend::{TREND_CACHE_FILE_NAME, TrendCache, TrendPoint, collect_trend, trend_csv};
+
+/// A project whose backlog grows by one pending test, which is then
+/// promoted.
+fn growing_project(dir: &Path) {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    git(
+        dir,
+        &["commit", "--allow-empty", "-m", "Before the ratchet"],
+    );
+    commit_status(dir, r#"{"tests":{"a":"passing"}}"#, "Adopt the ratchet");
+    commit_status(dir, r#"{"tests":{"a":"passing","b":"pending"}}"#, "Add b");
+    commit_status(
+        dir,
+        r#"{"tests":{"a":"passing","b":"passing"}}"#,
+        "Promote b",
+    );
+}
+
+fn counts(points: &[TrendPoint]) -> Vec<(usize, usize)> {
+    points.iter().map(|p| (p.passing, p.pending)).collect()
+}
+
+#[test]
+fn point_counts_passing_and_pending_tests() {
+    let status = StatusFile::new(
+        [
+            ("a", TestState::Passing),
+            ("b", TestState::Pending),
+            ("c", TestState::Pending),
+        ]
+        .into_iter()
+        .map(|(name, state)| (name.to_string(), TestEntry::new(state)))
+        .collect(),
+    );
+
+    let point = TrendPoint::of("abc", 7, &status);
+
+    assert_eq!((point.passing, point.pending, point.time), (1, 2, 7));
+}
+
+#[test]
+fn csv_has_one_row_per_point() {
+    let points = [TrendPoint {
+        commit: "abc".into(),
+        time: 7,
+        passing: 3,
+        pending: 1,
+    }];
+
+    assert_eq!(
+        trend_csv(&points),
+        "commit,time,passing,pending\nabc,7,3,1\n"
+    );
+}
+
+#[test]
+fn trend_walks_commits_with_a_status_file_oldest_first() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    growing_project(path);
+
+    let mut cache = TrendCache::default();
+    let all = collect_trend(path, Path::new(STATUS_FILE_NAME), None, &mut cache).unwrap();
+    let last = collect_trend(path, Path::new(STATUS_FILE_NAME), Some(2), &mut cache).unwrap();
+
+    assert_eq!(counts(&all), [(1, 0), (1, 1), (2, 0)]);
+    assert_eq!(counts(&last), [(1, 1), (2, 0)]);
+    assert_eq!(all[2].commit, git(path, &["rev-parse", "HEAD"]));
+    assert_eq!(cache.points.len(), 3);
+    dir.pass();
+}
+
+#[test]
+fn cached_points_are_not_recomputed() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    growing_project(path);
+    let head = git(path, &["rev-parse", "HEAD"]);
+    let mut cache = TrendCache::default();
+    cache.points.insert(
+        head.clone(),
+        TrendPoint {
+            commit: head,
+            time: 0,
+            passing: 99,
+            pending: 99,
+        },
+    );
+
+    let points = collect_trend(path, Path::new(STATUS_FILE_NAME), Some(1), &mut cache).unwrap();
+
+    assert_eq!(counts(&points), [(99, 99)]);
+    dir.pass();
+}
+
+#[test]
+fn trend_command_prints_json_or_csv_and_keeps_a_cache() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    growing_project(path);
+
+    let (code, out) = run_ratchet(path, &["trend", "--last", "2"]);
+    assert_eq!(code, Some(0), "{out}");
+    let points: Vec<TrendPoint> = serde_json::from_str(&out).unwrap();
+    assert_eq!(counts(&points), [(1, 1), (2, 0)]);
+    assert!(path.join(TREND_CACHE_FILE_NAME).exists());
+
+    let (code, out) = run_ratchet(path, &["trend", "--format", "csv"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(out.lines().count(), 4, "{out}");
+    assert!(out.starts_with("commit,time,passing,pending\n"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["trend", "--format", "xml"]);
+    assert_eq!(code, Some(2), "{out}");
+    let (code, out) = run_ratchet(path, &["trend", "--last", "many"]);
+    assert_eq!(code, Some(2), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,123 @@
+// tests/trivial_tests.rs
+//
+// Story 55: With `promoted-tests-must-assert`, a test promoted to passing
+// whose body makes no assertion is reported as a warning.
+
+mod common;
+
+use common::TestDir;
+use std::fs;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RulesConfig};
+use tdd_ratchet::errors::format_report;
+use tdd_ratchet::ratchet::{EvalResult, Warning};
+use tdd_ratchet::status::{StatusFile, TestEntry, TestState, TrackedStatus};
+use tdd_ratchet::trivial_tests::{asserts_nothing, warn_trivial_promotions};
+
+fn check(body: &str) -> Option<bool> {
+    asserts_nothing(&format!("#[test]\nfn t() {body}\n"), "t")
+}
+
+#[test]
+fn rule_is_off_by_default() {
+    let config = RatchetConfig::parse_from_str(
+        "[rules]\npromoted-tests-must-assert = true\n",
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+    assert!(config.rules.promoted_tests_must_assert);
+    assert!(!RulesConfig::default().promoted_tests_must_assert);
+}
+
+#[test]
+fn empty_and_constant_bodies_assert_nothing() {
+    assert_eq!(check("{}"), Some(true));
+    assert_eq!(check("{ let x = 1 + 1; }"), Some(true));
+    assert_eq!(check("{ assert!(true); }"), Some(true));
+    assert_eq!(check("{ assert_eq!(1, 1); }"), Some(true));
+}
+
+#[test]
+fn assertions_panics_and_fallible_calls_count() {
+    assert_eq!(check("{ assert_eq!(add(1, 1), 2); }"), Some(false));
+    assert_eq!(check("{ if x() { panic!(\"no\") } }"), Some(false));
+    assert_eq!(check("{ parse(\"1\").unwrap(); }"), Some(false));
+    assert_eq!(
+        check("{ let f = || { assert!(ok()) }; f(); }"),
+        Some(false),
+        "assertions nested in closures are found"
+    );
+    assert_eq!(check("{ check_output(run()); }"), Some(false));
+    assert_eq!(
+        asserts_nothing("#[test]\n#[should_panic]\nfn t() { divide(1, 0); }\n", "t"),
+        Some(false)
+    );
+}
+
+#[test]
+fn tests_in_nested_modules_are_found() {
+    let source = "mod tests {\n    #[tokio::test]\n    async fn t() {}\n}\n";
+    assert_eq!(asserts_nothing(source, "t"), Some(true));
+    assert_eq!(asserts_nothing(source, "other"), None);
+    assert_eq!(asserts_nothing("fn t() {}", "t"), None, "not a test");
+}
+
+#[test]
+fn promoted_trivial_tests_are_warned_about() {
+    let dir = TestDir::new();
+    fs::create_dir(dir.path().join("tests")).unwrap();
+    fs::write(
+        dir.path().join("tests/feature.rs"),
+        "#[test]\nfn empty() {}\n\n#[test]\nfn real() {\n    assert_eq!(2 + 2, 4);\n}\n\n#[test]\nfn pending_empty() {}\n",
+    )
+    .unwrap();
+    let previous = TrackedStatus::new(
+        [
+            ("empty".to_string(), TestEntry::new(TestState::Pending)),
+            ("real".to_string(), TestEntry::new(TestState::Pending)),
+            (
+                "pending_empty".to_string(),
+                TestEntry::new(TestState::Pending),
+            ),
+        ]
+        .into(),
+    );
+    let mut updated = StatusFile::empty();
+    updated.set_test_state("empty", TestState::Passing);
+    updated.set_test_state("real", TestState::Passing);
+    updated.set_test_state("pending_empty", TestState::Pending);
+    let mut result = EvalResult {
+        violations: Vec::new(),
+        warnings: Vec::new(),
+        updated,
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    };
+
+    warn_trivial_promotions(&mut result, &previous, dir.path(), Path::new("crate"));
+
+    assert!(
+        matches!(
+            result.warnings.as_slice(),
+            [Warning::TrivialTest { test }] if test == "empty"
+        ),
+        "{:?}",
+        result.warnings
+    );
+    let report = format_report(&result);
+    assert!(report.contains("promoted test asserts nothing"), "{report}");
+    assert!(
+        report.contains("Promoted test asserts nothing: crate/tests/feature.rs:2 empty"),
+        "{report}"
+    );
+    assert!(
+        report.contains("1 warning, not failing the run"),
+        "{report}"
+    );
+    dir.pass();
+}
@@ -0,0 +1,222 @@
+// tests/violation_codes.rs
+//
+// Story 56: Every violation has a stable code, shown in reports and the
+// audit trail, that `cargo ratchet explain <code>` describes in full.
+
+mod common;
+
+use common::{build_ratchet_binary, cargo_bin};
+use std::collections::BTreeSet;
+use std::process::Command;
+use tdd_ratchet::audit::{AuditRecord, run_record};
+use tdd_ratchet::errors::{format_markdown_report, format_report};
+use tdd_ratchet::explain::{EXPLANATIONS, explain, format_explanation};
+use tdd_ratchet::integrity::TamperProblem;
+use tdd_ratchet::ratchet::{EvalResult, Violation};
+use tdd_ratchet::status::{AcceptedFailure, StatusFile, TrackedStatus};
+
+/// One violation of every kind.
+fn every_violation() -> Vec<Violation> {
+    let test = || "t".to_string();
+    let rename = || ("new".to_string(), "old".to_string());
+    vec![
+        Violation::NewTestPassed { test: test() },
+        Violation::Regression { test: test() },
+        Violation::TestDisappeared { test: test() },
+        Violation::SkippedPending {
+            test: test(),
+            commit: "c".into(),
+        },
+        Violation::MissingGatekeeper,
+        Violation::RenameOldNameMissing {
+            new_name: rename().0,
+            old_name: rename().1,
+        },
+        Violation::RenameNewNameMissing {
+            new_name: rename().0,
+            old_name: rename().1,
+        },
+        Violation::RenameOldNameStillPresent {
+            new_name: rename().0,
+            old_name: rename().1,
+        },
+        Violation::RenameNewNameAlreadyTracked {
+            new_name: rename().0,
+            old_name: rename().1,
+        },
+        Violation::RenameOldNameMappedMultipleTimes {
+            old_name: rename().1,
+        },
+        Violation::RemovalMissingTrackedTest { test: test() },
+        Violation::RemovalTestStillPresent { test: test() },
+        Violation::RemovalConflictsWithRename { test: test() },
+        Violation::PromotionEditedTest {
+            test: test(),
+            commit: "c".into(),
+            path: "tests/t.rs".into(),
+        },
+        Violation::StatusTampered {
+            commit: "c".into(),
+            problem: TamperProblem::DigestMismatch,
+        },
+        Violation::PassingTestIgnored { test: test() },
+        Violation::TooManyPromotions {
+            commit: "c".into(),
+            tests: vec![test()],
+            limit: 0,
+        },
+        Violation::PendingLimitExceeded {
+            tests: vec![test()],
+            pending: 1,
+            limit: 1,
+        },
+        Violation::AcceptedFailureExpired {
+            test: test(),
+            acceptance: AcceptedFailure {
+                reason: "r".into(),
+                until: Some("2026-01-01".into()),
+                commits: None,
+            },
+        },
+        Violation::GatekeeperDoesNotBlock { test: test() },
+        Violation::GatekeeperHandshakeMissing,
+        Violation::DirtyWorktree {
+            paths: vec![".test-status.json".into()],
+        },
+        Violation::PluginViolation {
+            plugin: "licenses".into(),
+            subject: test(),
+            message: "m".into(),
+        },
+        Violation::PendingTestCommitType {
+            commit: "c".into(),
+            summary: "Add t".into(),
+            tests: vec![test()],
+            allowed: vec!["test".into()],
+        },
+        Violation::DuplicateTestName {
+            test: test(),
+            count: 2,
+        },
+    ]
+}
+
+fn result_with(violations: Vec<Violation>) -> EvalResult {
+    EvalResult {
+        violations,
+        warnings: Vec::new(),
+        updated: StatusFile::empty(),
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+#[test]
+fn every_violation_has_a_distinct_explained_code() {
+    let violations = every_violation();
+    let codes: BTreeSet<&str> = violations.iter().map(Violation::code).collect();
+    assert_eq!(codes.len(), violations.len());
+    assert_eq!(EXPLANATIONS.len(), violations.len());
+    for violation in &violations {
+        let explanation = explain(violation.code()).unwrap();
+        assert_eq!(explanation.code, violation.code());
+        assert!(!explanation.remediation.is_empty(), "{}", explanation.code);
+    }
+    assert_eq!(
+        Violation::NewTestPassed { test: "t".into() }.code(),
+        "RATCHET001"
+    );
+}
+
+#[test]
+fn codes_are_looked_up_case_insensitively_with_or_without_prefix() {
+    for code in ["RATCHET002", "ratchet002", "002", "2"] {
+        assert_eq!(explain(code).map(|e| e.name), Some("Regression"), "{code}");
+    }
+    assert!(explain("RATCHET999").is_none());
+    assert!(explain("E0308").is_none());
+}
+
+#[test]
+fn explanation_lists_numbered_remediation_steps() {
+    let text = format_explanation(explain("RATCHET003").unwrap());
+
+    assert!(text.starts_with("RATCHET003: TestDisappeared\n"), "{text}");
+    assert!(text.contains("Why: "), "{text}");
+    assert!(text.contains("What to do:\n  1. "), "{text}");
+    assert!(text.contains("  2. "), "{text}");
+}
+
+#[test]
+fn reports_show_codes() {
+    let result = result_with(vec![
+        Violation::Regression { test: "a".into() },
+        Violation::TestDisappeared { test: "b".into() },
+    ]);
+
+    let report = format_report(&result);
+    assert!(report.contains("[RATCHET002]"), "{report}");
+    assert!(report.contains("[RATCHET003]"), "{report}");
+    assert!(report.contains("cargo ratchet explain <CODE>"), "{report}");
+
+    let markdown = format_markdown_report(&result, &TrackedStatus::empty());
+    assert!(
+        markdown.contains("| RATCHET002 | Regression | `a` |"),
+        "{markdown}"
+    );
+}
+
+#[test]
+fn audit_records_carry_codes() {
+    let result = result_with(vec![Violation::Regression { test: "a".into() }]);
+    let record = run_record(None, &TrackedStatus::empty(), &result, 0);
+
+    let AuditRecord::Run { violations, .. } = &record else {
+        panic!("expected a run record: {record:?}");
+    };
+    assert_eq!(violations[0].code, "RATCHET002");
+    let json = serde_json::to_string(&record).unwrap();
+    assert!(json.contains("\"code\":\"RATCHET002\""), "{json}");
+
+    let old: AuditRecord = serde_json::from_str(
+        r#"{"kind":"run","recorded_at":0,"head":null,"violations":[{"rule":"Regression","subject":"a","detail":"d"}],"transitions":[],"overrides":[]}"#,
+    )
+    .unwrap();
+    let AuditRecord::Run { violations, .. } = old else {
+        panic!("expected a run record");
+    };
+    assert_eq!(
+        violations[0].code, "",
+        "records from before codes still load"
+    );
+}
+
+#[test]
+fn explain_command_prints_the_explanation() {
+    build_ratchet_binary();
+    let run = |args: &[&str]| {
+        let output = Command::new(cargo_bin()).args(args).output().unwrap();
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string()
+                + &String::from_utf8_lossy(&output.stderr),
+        )
+    };
+
+    let (code, out) = run(&["explain", "RATCHET001"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.starts_with("RATCHET001: NewTestPassed"), "{out}");
+
+    let (code, out) = run(&["explain"]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(out.lines().count(), EXPLANATIONS.len(), "{out}");
+
+    let (code, out) = run(&["explain", "RATCHET999"]);
+    assert_eq!(code, Some(2), "{out}");
+    assert!(out.contains("unknown violation code"), "{out}");
+}
@@ -0,0 +1,118 @@
+// tests/warning_channel.rs
+//
+// Story 52: Advisory findings are reported as warnings, apart from
+// violations, and only fail the run with `--deny warnings`.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet};
+use std::fs;
+use tdd_ratchet::errors::{ReportOptions, format_report, format_report_with};
+use tdd_ratchet::ratchet::{EvalResult, Violation, Warning};
+use tdd_ratchet::status::{StatusFile, TestState};
+
+fn flaky_result(violations: Vec<Violation>) -> EvalResult {
+    let mut updated = StatusFile::empty();
+    updated.set_test_state("tdd_ratchet_gatekeeper", TestState::Passing);
+    updated.set_test_state("wobbly", TestState::Passing);
+    EvalResult {
+        violations,
+        warnings: vec![Warning::FlakyTest {
+            test: "wobbly".into(),
+            flakes: 3,
+        }],
+        updated,
+        failure_output: Default::default(),
+        locations: Default::default(),
+        since_last_run: None,
+        history_since: None,
+        infrastructure_retries: 0,
+        partial: None,
+        last_green: Default::default(),
+    }
+}
+
+#[test]
+fn warnings_alone_do_not_fail_the_run() {
+    let result = flaky_result(Vec::new());
+
+    assert!(!result.is_failure(false));
+    assert!(result.is_failure(true));
+    assert!(
+        flaky_result(vec![Violation::MissingGatekeeper]).is_failure(false),
+        "violations always fail"
+    );
+}
+
+#[test]
+fn report_counts_warnings_separately_from_violations() {
+    let report = format_report(&flaky_result(Vec::new()));
+
+    assert!(report.contains("flaky test"), "{report}");
+    assert!(report.contains("tdd-ratchet: ok (2 passing)"), "{report}");
+    assert!(
+        report.contains("tdd-ratchet: 1 warning, not failing the run"),
+        "{report}"
+    );
+}
+
+#[test]
+fn denied_warnings_replace_the_ok_line() {
+    let report = format_report_with(
+        &flaky_result(Vec::new()),
+        ReportOptions {
+            deny_warnings: true,
+            ..ReportOptions::default()
+        },
+    );
+
+    assert!(!report.contains("tdd-ratchet: ok"), "{report}");
+    assert!(
+        report.contains("tdd-ratchet: 1 warning, failing the run (--deny warnings)"),
+        "{report}"
+    );
+}
+
+#[test]
+fn no_warning_line_without_warnings() {
+    let mut result = flaky_result(Vec::new());
+    result.warnings.clear();
+
+    assert!(!format_report(&result).contains("warning"));
+}
+
+#[test]
+fn deny_warnings_flag_sets_the_exit_code() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\npassing-to-ignored = \"warn\"\n\n[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\ntest feature ... ignored\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","feature":"passing"}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert!(out.contains("passing test ignored (warning)"), "{out}");
+
+    let (code, out) = run_ratchet(path, &["--deny", "warnings"]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("failing the run (--deny warnings)"), "{out}");
+    dir.pass();
+}
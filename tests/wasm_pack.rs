@@ -0,0 +1,191 @@
+// tests/wasm_pack.rs
+//
+// Story 97: wasm-bindgen browser tests run through `wasm-pack test` are
+// parsed into results and tracked in the same ratchet run as native tests.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, run_ratchet_with_path};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tdd_ratchet::config::{RatchetConfig, RunnerConfig};
+use tdd_ratchet::runner::{OutputParser, TestOutcome, parse_wasm_bindgen_output, wasm_test_name};
+use tdd_ratchet::status::{StatusFile, TestState};
+
+/// `wasm-pack test` output for the `web` test target whose `renders` test
+/// reports `status`.
+fn output(status: &str) -> String {
+    format!(
+        "[INFO]: Checking for the Wasm target...\n\
+         Running headless tests in Chrome on `http://127.0.0.1:40023/`\n\
+         running 2 tests\n\
+         \n\
+         test web::renders ... {status}\n\
+         test web::widgets::layout ... ok\n\
+         \n\
+         failures:\n\
+         \n\
+         ---- web::renders output ----\n\
+         \x20   error output:\n\
+         \x20       panicked at tests/web.rs:9:5:\n\
+         \x20       canvas missing\n\
+         \n\
+         failures:\n\
+         \n\
+         \x20   web::renders\n\
+         \n\
+         test result: FAILED. 1 passed; 1 failed; 0 ignored\n"
+    )
+}
+
+#[test]
+fn tests_are_named_by_target_and_path() {
+    let results = parse_wasm_bindgen_output(
+        "running 3 tests\n\
+         test web::renders ... ok\n\
+         test my_crate::tests::adds ... ok\n\
+         test web::slow ... ignored, needs a GPU\n\
+         \n\
+         test result: ok. 2 passed; 0 failed; 1 ignored\n",
+    );
+
+    let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "wasm::web$renders",
+            "wasm::my_crate$tests::adds",
+            "wasm::web$slow"
+        ]
+    );
+    assert_eq!(results[0].outcome, TestOutcome::Passed);
+    assert_eq!(results[2].outcome, TestOutcome::Ignored);
+    assert_eq!(wasm_test_name("standalone"), "wasm$standalone");
+}
+
+#[test]
+fn failures_keep_their_output_section() {
+    let results = parse_wasm_bindgen_output(&output("FAIL"));
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].outcome, TestOutcome::Failed);
+    assert_eq!(
+        results[0].output.as_deref(),
+        Some("error output:\n        panicked at tests/web.rs:9:5:\n        canvas missing")
+    );
+    assert_eq!(results[1].name, "wasm::web$widgets::layout");
+    assert_eq!(results[1].outcome, TestOutcome::Passed);
+    assert_eq!(results[1].output, None);
+
+    let legacy = parse_wasm_bindgen_output("test web::renders ... FAILED\n");
+    assert_eq!(legacy[0].outcome, TestOutcome::Failed);
+}
+
+#[test]
+fn wasm_pack_runner_and_parser_entries_parse() {
+    let config = RatchetConfig::parse_from_str(
+        r#"
+[[runners]]
+kind = "nextest"
+
+[[runners]]
+kind = "wasm-pack"
+args = ["--headless", "--firefox"]
+
+[[runners]]
+kind = "command"
+command = ["cat", "wasm.txt"]
+parser = "wasm-bindgen"
+"#,
+        Path::new("ratchet.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(config.runners[0], RunnerConfig::Nextest);
+    assert_eq!(
+        config.runners[1],
+        RunnerConfig::WasmPack {
+            args: vec!["--headless".to_string(), "--firefox".to_string()],
+            workdir: None,
+        }
+    );
+    assert!(matches!(
+        config.runners[2],
+        RunnerConfig::Command {
+            parser: OutputParser::WasmBindgen,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn browser_tests_ratchet_alongside_native_tests() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let project = dir.path().join("project");
+    let bin = dir.path().join("bin");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(&bin).unwrap();
+    let log = dir.path().join("wasm-pack.log");
+
+    // Stands in for wasm-pack: records its arguments and prints whatever
+    // output the test has left in `wasm.txt`.
+    let wasm_pack = bin.join("wasm-pack");
+    fs::write(
+        &wasm_pack,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\ncat wasm.txt\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&wasm_pack, fs::Permissions::from_mode(0o755)).unwrap();
+
+    git(&project, &["init", "-b", "main"]);
+    git(&project, &["config", "user.email", "test@test.com"]);
+    git(&project, &["config", "user.name", "Test"]);
+    fs::write(
+        project.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n\n\
+         [[runners]]\nkind = \"wasm-pack\"\n",
+    )
+    .unwrap();
+    fs::write(
+        project.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\n",
+    )
+    .unwrap();
+    fs::write(project.join("wasm.txt"), "running 0 tests\n").unwrap();
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Initial project"]);
+
+    let test = "wasm::web$renders";
+    fs::write(project.join("wasm.txt"), "test web::renders ... FAIL\n").unwrap();
+    let (code, out) = run_ratchet_with_path(&project, &bin, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(
+        fs::read_to_string(&log).unwrap().trim(),
+        "test --headless --chrome"
+    );
+    let status = StatusFile::load(&project.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[test].state(), TestState::Pending);
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Add renders test"]);
+
+    fs::write(project.join("wasm.txt"), "test web::renders ... ok\n").unwrap();
+    let (code, out) = run_ratchet_with_path(&project, &bin, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&project.join(".test-status.json")).unwrap();
+    assert_eq!(status.tests[test].state(), TestState::Passing);
+    git(&project, &["add", "-A"]);
+    git(&project, &["commit", "-m", "Implement rendering"]);
+
+    fs::write(project.join("wasm.txt"), output("FAIL")).unwrap();
+    let (code, out) = run_ratchet_with_path(&project, &bin, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains(test), "{out}");
+    assert!(out.contains("canvas missing"), "{out}");
+    dir.pass();
+}
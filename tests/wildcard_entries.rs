@@ -0,0 +1,234 @@
+// tests/wildcard_entries.rs
+//
+// Story 86: status file entries with `*` in their name, such as
+// `"proptest_regressions::*": "passing"`, track every test they match. An
+// exact entry takes precedence over a wildcard.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, git, result, run_ratchet, snapshot};
+use std::fs;
+use tdd_ratchet::history::{HistoryViolation, check_history_snapshots};
+use tdd_ratchet::ratchet::{Violation, evaluate};
+use tdd_ratchet::runner::TestOutcome;
+use tdd_ratchet::status::{
+    StatusFile, TestEntry, TestState, TrackedStatus, WorkingTreeInstructions, tracking_entry,
+};
+
+const GATEKEEPER: &str = "app::tests$tdd_ratchet_gatekeeper";
+const WILDCARD: &str = "app::tests$proptest_regressions::*";
+
+fn status(tests: &[(&str, TestState)]) -> TrackedStatus {
+    TrackedStatus::new(
+        tests
+            .iter()
+            .map(|(name, state)| (name.to_string(), TestEntry::new(*state)))
+            .collect(),
+    )
+}
+
+fn case(n: u32) -> String {
+    format!("app::tests$proptest_regressions::case_{n}")
+}
+
+#[test]
+fn exact_entries_take_precedence_over_wildcards() {
+    use TestState::{Passing, Pending};
+    let tests = status(&[
+        ("app::tests$proptest_regressions::case_1", Pending),
+        (WILDCARD, Passing),
+        ("app::tests$proptest_regressions::slow_*", Pending),
+        ("app::tests$*_regressions::*", Pending),
+    ])
+    .tests;
+    let name = |test: &str| tracking_entry(&tests, test).map(|(name, _)| name.as_str());
+
+    assert_eq!(name(&case(1)), Some(case(1).as_str()));
+    assert_eq!(name(&case(2)), Some(WILDCARD));
+    assert_eq!(
+        name("app::tests$proptest_regressions::slow_1"),
+        Some("app::tests$proptest_regressions::slow_*")
+    );
+    assert_eq!(
+        name("app::tests$fuzz_regressions::a"),
+        Some("app::tests$*_regressions::*")
+    );
+    assert_eq!(name("app::tests$other"), None);
+}
+
+#[test]
+fn a_passing_wildcard_covers_every_matching_test() {
+    let tracked = status(&[
+        (GATEKEEPER, TestState::Passing),
+        (WILDCARD, TestState::Passing),
+    ]);
+    let results = [
+        result(GATEKEEPER, TestOutcome::Passed),
+        result(&case(1), TestOutcome::Passed),
+        result(&case(2), TestOutcome::Failed),
+    ];
+
+    let result = evaluate(&tracked, &WorkingTreeInstructions::default(), &results, &[]);
+
+    assert!(
+        matches!(
+            result.violations.as_slice(),
+            [Violation::Regression { test }] if *test == case(2)
+        ),
+        "{:?}",
+        result.violations
+    );
+    assert_eq!(result.updated.tests, tracked.tests);
+}
+
+#[test]
+fn a_passing_test_under_a_pending_wildcard_gets_its_own_entry() {
+    let tracked = status(&[
+        (GATEKEEPER, TestState::Passing),
+        (WILDCARD, TestState::Pending),
+    ]);
+    let results = [
+        result(GATEKEEPER, TestOutcome::Passed),
+        result(&case(1), TestOutcome::Passed),
+        result(&case(2), TestOutcome::Failed),
+    ];
+
+    let result = evaluate(&tracked, &WorkingTreeInstructions::default(), &results, &[]);
+
+    assert!(result.violations.is_empty(), "{:?}", result.violations);
+    assert_eq!(result.updated.tests[&case(1)].state(), TestState::Passing);
+    assert!(!result.updated.tests.contains_key(&case(2)));
+    assert_eq!(result.updated.tests[WILDCARD].state(), TestState::Pending);
+}
+
+#[test]
+fn a_wildcard_disappears_when_it_matches_no_test() {
+    let tracked = status(&[
+        (GATEKEEPER, TestState::Passing),
+        (WILDCARD, TestState::Passing),
+    ]);
+
+    let disappeared = evaluate(
+        &tracked,
+        &WorkingTreeInstructions::default(),
+        &[result(GATEKEEPER, TestOutcome::Passed)],
+        &[],
+    );
+    assert!(
+        matches!(
+            disappeared.violations.as_slice(),
+            [Violation::TestDisappeared { test }] if test == WILDCARD
+        ),
+        "{:?}",
+        disappeared.violations
+    );
+
+    let instructions = WorkingTreeInstructions {
+        removals: [WILDCARD.to_string()].into(),
+        ..WorkingTreeInstructions::default()
+    };
+    let removed = evaluate(
+        &tracked,
+        &instructions,
+        &[
+            result(GATEKEEPER, TestOutcome::Passed),
+            result(&case(1), TestOutcome::Passed),
+        ],
+        &[],
+    );
+    assert!(
+        matches!(
+            removed.violations.as_slice(),
+            [Violation::RemovalTestStillPresent { test }] if test == WILDCARD
+        ),
+        "{:?}",
+        removed.violations
+    );
+}
+
+#[test]
+fn history_accepts_tests_promoted_from_an_earlier_wildcard() {
+    use TestState::{Passing, Pending};
+    let snapshots = [
+        snapshot("c1", common::status(&[(GATEKEEPER, Passing)])),
+        snapshot(
+            "c2",
+            common::status(&[(GATEKEEPER, Passing), (WILDCARD, Pending)]),
+        ),
+        snapshot(
+            "c3",
+            common::status(&[
+                (GATEKEEPER, Passing),
+                (WILDCARD, Pending),
+                (&case(1), Passing),
+                ("app::tests$cheater", Passing),
+            ]),
+        ),
+        snapshot(
+            "c4",
+            common::status(&[
+                (GATEKEEPER, Passing),
+                (WILDCARD, Pending),
+                (&case(1), Passing),
+                ("app::tests$cheater", Passing),
+                ("app::tests$fuzz::*", Passing),
+            ]),
+        ),
+    ];
+
+    let flagged: Vec<String> = check_history_snapshots(&snapshots)
+        .into_iter()
+        .map(|v| match v {
+            HistoryViolation::SkippedPending { test, .. } => test,
+        })
+        .collect();
+    assert_eq!(flagged, ["app::tests$cheater", "app::tests$fuzz::*"]);
+}
+
+#[test]
+fn wildcard_entries_are_honored_by_the_ratchet() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@test.com"]);
+    git(path, &["config", "user.name", "Test"]);
+    fs::write(
+        path.join("ratchet.toml"),
+        "[rules]\ndirty-worktree = \"allow\"\n\n\
+         [[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join(".test-status.json"),
+        r#"{"tests":{"tdd_ratchet_gatekeeper":"passing","proptest_regressions::*":"passing"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\n\
+         test proptest_regressions::case_1 ... ok\n\
+         test proptest_regressions::case_2 ... ok\n",
+    )
+    .unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Initial project"]);
+
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    let status = StatusFile::load(&path.join(".test-status.json")).unwrap();
+    let tests: Vec<&String> = status.tests.keys().collect();
+    assert_eq!(tests, ["proptest_regressions::*", "tdd_ratchet_gatekeeper"]);
+
+    fs::write(
+        path.join("results.txt"),
+        "test tdd_ratchet_gatekeeper ... ok\n\
+         test proptest_regressions::case_1 ... ok\n\
+         test proptest_regressions::case_3 ... FAILED\n",
+    )
+    .unwrap();
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(1), "{out}");
+    assert!(out.contains("proptest_regressions::case_3"), "{out}");
+    dir.pass();
+}
@@ -0,0 +1,180 @@
+// tests/worktrees.rs
+//
+// Story 37: The ratchet works in linked `git worktree` checkouts and on a
+// detached HEAD, and history can be walked from any ref or commit.
+
+mod common;
+
+use common::{TestDir, build_ratchet_binary, commit_status, git, run_ratchet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tdd_ratchet::git_cli::GitCliHistory;
+use tdd_ratchet::history::{GitHistory, HistoryProvider, collect_history_snapshots};
+use tdd_ratchet::status::{StatusFile, TestState};
+
+fn rev_parse(dir: &Path, rev: &str) -> String {
+    let out = Command::new("git")
+        .args(["rev-parse", rev])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8(out.stdout).unwrap().trim().to_string()
+}
+
+/// A project whose "test suite" prints libtest lines from `results.txt`.
+fn create_project(dir: &Path) {
+    git(dir, &["init", "-b", "main"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    fs::write(
+        dir.join("ratchet.toml"),
+        "[[runners]]\nkind = \"command\"\ncommand = [\"cat\", \"results.txt\"]\nparser = \"libtest\"\n",
+    )
+    .unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-m", "Initial project"]);
+}
+
+fn set_results(dir: &Path, results: &[(&str, &str)]) {
+    let lines: String = results
+        .iter()
+        .map(|(name, outcome)| format!("test {name} ... {outcome}\n"))
+        .collect();
+    fs::write(dir.join("results.txt"), lines).unwrap();
+}
+
+fn state(dir: &Path, test: &str) -> Option<TestState> {
+    let status = StatusFile::load(&dir.join(".test-status.json")).unwrap();
+    status.tests.get(test).map(|entry| entry.state())
+}
+
+/// Commit `feature` as pending with a passing gatekeeper, via the binary.
+fn commit_pending_feature(dir: &Path) {
+    set_results(
+        dir,
+        &[("tdd_ratchet_gatekeeper", "ok"), ("feature", "FAILED")],
+    );
+    let (code, out) = run_ratchet(dir, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-m", "Add failing feature test"]);
+}
+
+#[test]
+fn ratchet_in_linked_worktree_uses_the_worktrees_head() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let main = dir.path().join("main");
+    fs::create_dir(&main).unwrap();
+    create_project(&main);
+    set_results(&main, &[("tdd_ratchet_gatekeeper", "ok")]);
+    let (code, out) = run_ratchet(&main, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    git(&main, &["add", "-A"]);
+    git(&main, &["commit", "-m", "Track gatekeeper"]);
+
+    // The feature test only ever goes pending on the worktree's branch.
+    let worktree = dir.path().join("wt");
+    git(
+        &main,
+        &[
+            "worktree",
+            "add",
+            "-b",
+            "feature",
+            worktree.to_str().unwrap(),
+        ],
+    );
+    commit_pending_feature(&worktree);
+    set_results(
+        &worktree,
+        &[("tdd_ratchet_gatekeeper", "ok"), ("feature", "ok")],
+    );
+    let (code, out) = run_ratchet(&worktree, &[]);
+
+    assert_eq!(
+        code,
+        Some(0),
+        "feature was pending at the worktree's HEAD: {out}"
+    );
+    assert_eq!(state(&worktree, "feature"), Some(TestState::Passing));
+    assert_eq!(state(&main, "feature"), None);
+    dir.pass();
+}
+
+#[test]
+fn ratchet_on_detached_head_checks_history_from_that_commit() {
+    build_ratchet_binary();
+    let dir = TestDir::new();
+    let path = dir.path();
+    create_project(path);
+    commit_pending_feature(path);
+    let pending = rev_parse(path, "HEAD");
+    git(path, &["commit", "--allow-empty", "-m", "Later work"]);
+    git(path, &["checkout", "--detach", &pending]);
+
+    set_results(path, &[("tdd_ratchet_gatekeeper", "ok"), ("feature", "ok")]);
+    let (code, out) = run_ratchet(path, &[]);
+    assert_eq!(code, Some(0), "{out}");
+    assert_eq!(state(path, "feature"), Some(TestState::Passing));
+
+    set_results(
+        path,
+        &[
+            ("tdd_ratchet_gatekeeper", "ok"),
+            ("feature", "ok"),
+            ("sneaky", "ok"),
+        ],
+    );
+    let (code, out) = run_ratchet(path, &[]);
+    assert_ne!(code, Some(0), "a new passing test is still rejected: {out}");
+    assert!(out.contains("sneaky"), "{out}");
+    dir.pass();
+}
+
+#[test]
+fn history_can_start_from_any_revision() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    create_project(path);
+    commit_status(path, r#"{"tests":{"a":"pending"}}"#, "Add a");
+    let first = rev_parse(path, "HEAD");
+    git(path, &["checkout", "-b", "side"]);
+    commit_status(path, r#"{"tests":{"a":"passing"}}"#, "Pass a");
+    git(path, &["checkout", "main"]);
+
+    assert_eq!(collect_history_snapshots(path, "HEAD").unwrap().len(), 1);
+    assert_eq!(collect_history_snapshots(path, "side").unwrap().len(), 2);
+    assert_eq!(collect_history_snapshots(path, &first).unwrap().len(), 1);
+
+    let side = GitHistory::new(path).with_start("side");
+    assert_eq!(
+        side.head_status().unwrap().unwrap().tests["a"].state(),
+        TestState::Passing
+    );
+    let cli = GitCliHistory::new(path).with_start("side");
+    assert_eq!(cli.snapshots().unwrap().len(), 2);
+    assert_eq!(
+        cli.head_status().unwrap().unwrap().tests["a"].state(),
+        TestState::Passing
+    );
+    dir.pass();
+}
+
+#[test]
+fn unknown_start_revision_is_an_error() {
+    let dir = TestDir::new();
+    let path = dir.path();
+    create_project(path);
+    commit_status(path, r#"{"tests":{}}"#, "Empty status");
+
+    let err = collect_history_snapshots(path, "no-such-branch").unwrap_err();
+    assert!(err.to_string().contains("no-such-branch"), "{err}");
+    let err = GitCliHistory::new(path)
+        .with_start("no-such-branch")
+        .snapshots()
+        .unwrap_err();
+    assert!(err.to_string().contains("no-such-branch"), "{err}");
+    dir.pass();
+}